@@ -0,0 +1,92 @@
+#![feature(test)]
+extern crate rosc;
+extern crate test;
+
+use self::test::Bencher;
+
+use rosc::address::{AddressIndex, Matcher};
+
+#[bench]
+fn bench_matcher_new(b: &mut Bencher) {
+    b.iter(|| Matcher::new("/mixer/[0-9]/{fader,pan,mute}/ch[a-zA-Z]*/?x/level").unwrap());
+}
+
+#[bench]
+fn bench_match_address(b: &mut Bencher) {
+    let matcher = Matcher::new("/mixer/[0-9]/{fader,pan,mute}/ch[a-zA-Z]*/?x/level").unwrap();
+    b.iter(|| matcher.match_address("/mixer/3/pan/chBass/1x/level").unwrap());
+}
+
+#[bench]
+fn bench_matcher_new_literal(b: &mut Bencher) {
+    b.iter(|| Matcher::new("/mixer/channel/12/fader").unwrap());
+}
+
+#[bench]
+fn bench_matcher_new_wildcard(b: &mut Bencher) {
+    b.iter(|| Matcher::new("/mixer/channel/*/fa?er").unwrap());
+}
+
+#[bench]
+fn bench_matcher_new_alternation(b: &mut Bencher) {
+    b.iter(|| Matcher::new("/mixer/channel/{1,2,12}/{fader,pan,mute}").unwrap());
+}
+
+#[bench]
+fn bench_match_address_literal(b: &mut Bencher) {
+    let matcher = Matcher::new("/mixer/channel/12/fader").unwrap();
+    b.iter(|| matcher.match_address("/mixer/channel/12/fader").unwrap());
+}
+
+#[bench]
+fn bench_match_address_wildcard(b: &mut Bencher) {
+    let matcher = Matcher::new("/mixer/channel/*/fa?er").unwrap();
+    b.iter(|| matcher.match_address("/mixer/channel/12/fader").unwrap());
+}
+
+#[bench]
+fn bench_match_address_alternation(b: &mut Bencher) {
+    let matcher = Matcher::new("/mixer/channel/{1,2,12}/{fader,pan,mute}").unwrap();
+    b.iter(|| matcher.match_address("/mixer/channel/12/fader").unwrap());
+}
+
+/// Returns the 10240 addresses of a mixer with 128 channels of 80 parameters each.
+fn parameter_tree() -> Vec<String> {
+    let mut addrs = vec![];
+    for channel in 0..128 {
+        for group in &["eq", "dyn", "send", "insert"] {
+            for param in 0..20 {
+                addrs.push(format!("/mixer/{}/{}/{}", channel, group, param));
+            }
+        }
+    }
+    addrs
+}
+
+#[bench]
+fn bench_filter_naive(b: &mut Bencher) {
+    let addrs = parameter_tree();
+    let matcher = Matcher::new("/mixer/1?/eq/*").unwrap();
+    b.iter(|| {
+        let matches: Vec<&str> = addrs
+            .iter()
+            .map(String::as_str)
+            .filter(|addr| matcher.match_address(addr).unwrap())
+            .collect();
+        assert_eq!(matches.len(), 200);
+    });
+}
+
+#[bench]
+fn bench_filter(b: &mut Bencher) {
+    let addrs = parameter_tree();
+    let matcher = Matcher::new("/mixer/1?/eq/*").unwrap();
+    b.iter(|| assert_eq!(matcher.filter(addrs.iter().map(String::as_str)).len(), 200));
+}
+
+#[bench]
+fn bench_address_index(b: &mut Bencher) {
+    let index: AddressIndex = parameter_tree().into_iter().collect();
+    let matcher = Matcher::new("/mixer/1?/eq/*").unwrap();
+    b.iter(|| assert_eq!(index.matching(&matcher).len(), 200));
+}
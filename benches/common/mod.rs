@@ -0,0 +1,77 @@
+//! Packets shared by the benchmarks, so that encoding and decoding are measured on the same data.
+#![allow(dead_code)]
+
+use rosc::{OscBundle, OscMessage, OscPacket, OscType};
+
+/// Returns a message with a single int argument.
+pub fn small_message() -> OscPacket {
+    OscPacket::Message(OscMessage {
+        addr: "/transport/beat".to_string(),
+        args: vec![OscType::Int(1)],
+    })
+}
+
+/// Returns a message with 16 ints, floats, bools and doubles.
+pub fn message_with_16_args() -> OscPacket {
+    OscPacket::Message(OscMessage {
+        addr: "/mixer/channel/1/eq".to_string(),
+        args: (0..16)
+            .map(|i| match i % 4 {
+                0 => OscType::Int(i),
+                1 => OscType::Float(i as f32),
+                2 => OscType::Bool(i % 8 == 2),
+                _ => OscType::Double(f64::from(i)),
+            })
+            .collect(),
+    })
+}
+
+/// Returns a bundle of 4 messages with a blob of 256 KiB each.
+pub fn blob_heavy_packet() -> OscPacket {
+    let messages = (0..4)
+        .map(|i| {
+            OscPacket::Message(OscMessage {
+                addr: format!("/audio/chunk/{}", i),
+                args: vec![OscType::Blob(vec![i as u8; 256 * 1024])],
+            })
+        })
+        .collect();
+    OscPacket::Bundle(OscBundle {
+        timetag: (0, 1).into(),
+        content: messages,
+    })
+}
+
+/// Returns a bundle of 100 fader messages.
+pub fn bundle_of_100() -> OscPacket {
+    let messages = (0..100)
+        .map(|i| {
+            OscPacket::Message(OscMessage {
+                addr: format!("/mixer/channel/{}/fader", i),
+                args: vec![OscType::Float(i as f32 / 100.0)],
+            })
+        })
+        .collect();
+    OscPacket::Bundle(OscBundle {
+        timetag: (0, 1).into(),
+        content: messages,
+    })
+}
+
+/// Returns a bundle of 10 bundles with 10 messages each.
+pub fn nested_bundle() -> OscPacket {
+    let bundles = (0..10)
+        .map(|i| {
+            OscPacket::Bundle(OscBundle {
+                timetag: (i, 0).into(),
+                content: (0..10)
+                    .map(|j| OscPacket::Message(OscMessage::from(format!("/{}/{}", i, j).as_str())))
+                    .collect(),
+            })
+        })
+        .collect();
+    OscPacket::Bundle(OscBundle {
+        timetag: (0, 1).into(),
+        content: bundles,
+    })
+}
@@ -2,6 +2,8 @@
 extern crate rosc;
 extern crate test;
 
+mod common;
+
 use self::test::Bencher;
 
 #[bench]
@@ -15,3 +17,109 @@ fn bench_decode(b: &mut Bencher) {
     ];
     b.iter(|| rosc::decoder::decode_udp(&raw_msg).unwrap());
 }
+
+fn blob_heavy_packet() -> Vec<u8> {
+    rosc::encoder::encode(&common::blob_heavy_packet()).unwrap()
+}
+
+#[bench]
+fn bench_decode_small_message(b: &mut Bencher) {
+    let raw_msg = rosc::encoder::encode(&common::small_message()).unwrap();
+    b.iter(|| rosc::decoder::decode_udp(&raw_msg).unwrap());
+}
+
+#[bench]
+fn bench_decode_bundle_of_100(b: &mut Bencher) {
+    let raw_msg = rosc::encoder::encode(&common::bundle_of_100()).unwrap();
+    b.iter(|| rosc::decoder::decode_udp(&raw_msg).unwrap());
+}
+
+#[bench]
+fn bench_decode_ref_bundle_of_100(b: &mut Bencher) {
+    let raw_msg = rosc::encoder::encode(&common::bundle_of_100()).unwrap();
+    b.iter(|| rosc::decoder::decode_ref(&raw_msg).unwrap());
+}
+
+#[bench]
+fn bench_decode_blobs(b: &mut Bencher) {
+    let raw_msg = blob_heavy_packet();
+    b.iter(|| rosc::decoder::decode_udp(&raw_msg).unwrap());
+}
+
+#[bench]
+fn bench_decode_ref_blobs(b: &mut Bencher) {
+    let raw_msg = blob_heavy_packet();
+    b.iter(|| rosc::decoder::decode_ref(&raw_msg).unwrap());
+}
+
+fn repetitive_stream() -> Vec<Vec<u8>> {
+    (0..1000)
+        .map(|i| {
+            let packet = rosc::OscPacket::Message(rosc::OscMessage {
+                addr: format!("/mixer/channel/{}/fader", i % 30),
+                args: vec![rosc::OscType::Float(0.5)],
+            });
+            rosc::encoder::encode(&packet).unwrap()
+        })
+        .collect()
+}
+
+#[bench]
+fn bench_decode_repetitive(b: &mut Bencher) {
+    let stream = repetitive_stream();
+    let options = rosc::decoder::DecoderOptions::default();
+    b.iter(|| {
+        for packet in &stream {
+            rosc::decoder::decode_with_options(packet, &options).unwrap();
+        }
+    });
+}
+
+#[bench]
+fn bench_decode_repetitive_interned(b: &mut Bencher) {
+    let stream = repetitive_stream();
+    let options = rosc::decoder::DecoderOptions::default();
+    let mut interner = rosc::intern::Interner::default();
+    b.iter(|| {
+        for packet in &stream {
+            rosc::decoder::decode_with_interner(packet, &options, &mut interner).unwrap();
+        }
+    });
+}
+
+fn message_with_16_args() -> Vec<u8> {
+    rosc::encoder::encode(&common::message_with_16_args()).unwrap()
+}
+
+#[bench]
+fn bench_decode_16_args(b: &mut Bencher) {
+    let raw_msg = message_with_16_args();
+    b.iter(|| rosc::decoder::decode_udp(&raw_msg).unwrap());
+}
+
+#[bench]
+fn bench_visit_16_args(b: &mut Bencher) {
+    struct Discard;
+    impl<'a> rosc::decoder::OscVisitor<'a> for Discard {}
+
+    let raw_msg = message_with_16_args();
+    b.iter(|| rosc::decoder::decode_visit(&raw_msg, &mut Discard).unwrap());
+}
+
+#[bench]
+fn bench_decode_ref_numeric(b: &mut Bencher) {
+    let packet = rosc::OscPacket::Message(rosc::OscMessage {
+        addr: "/sensors/samples".to_string(),
+        args: (0..1000)
+            .map(|i| match i % 5 {
+                0 => rosc::OscType::Int(i),
+                1 => rosc::OscType::Float(i as f32),
+                2 => rosc::OscType::Long(i64::from(i)),
+                3 => rosc::OscType::Double(f64::from(i)),
+                _ => rosc::OscType::Time((i as u32, 0).into()),
+            })
+            .collect(),
+    });
+    let raw_msg = rosc::encoder::encode(&packet).unwrap();
+    b.iter(|| rosc::decoder::decode_ref(&raw_msg).unwrap());
+}
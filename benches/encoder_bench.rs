@@ -0,0 +1,95 @@
+#![feature(test)]
+extern crate rosc;
+extern crate test;
+
+mod common;
+
+use self::test::Bencher;
+use rosc::{encoder, OscMessage, OscMessageRef, OscPacket, OscPacketRef, OscType, OscTypeRef};
+
+#[bench]
+fn bench_encode_constant_address(b: &mut Bencher) {
+    let mut buf = Vec::with_capacity(64);
+    b.iter(|| {
+        buf.clear();
+        let packet = OscPacket::Message(OscMessage {
+            addr: "/transport/beat".to_string(),
+            args: vec![OscType::Int(1)],
+        });
+        encoder::encode_into(&packet, &mut buf).unwrap()
+    });
+}
+
+#[bench]
+fn bench_encode_ref_constant_address(b: &mut Bencher) {
+    let mut buf = Vec::with_capacity(64);
+    b.iter(|| {
+        buf.clear();
+        let msg = OscMessageRef::new("/transport/beat", vec![OscTypeRef::Int(1)]);
+        encoder::encode_ref_into(&OscPacketRef::Message(msg), &mut buf).unwrap()
+    });
+}
+
+#[bench]
+fn bench_encode_small_message(b: &mut Bencher) {
+    let packet = common::small_message();
+    b.iter(|| encoder::encode(&packet).unwrap());
+}
+
+#[bench]
+fn bench_encode_into_small_message(b: &mut Bencher) {
+    let packet = common::small_message();
+    let mut buf = Vec::with_capacity(64);
+    b.iter(|| {
+        buf.clear();
+        encoder::encode_into(&packet, &mut buf).unwrap()
+    });
+}
+
+#[bench]
+fn bench_encode_16_args(b: &mut Bencher) {
+    let packet = common::message_with_16_args();
+    b.iter(|| encoder::encode(&packet).unwrap());
+}
+
+#[bench]
+fn bench_encode_into_16_args(b: &mut Bencher) {
+    let packet = common::message_with_16_args();
+    let mut buf = Vec::with_capacity(256);
+    b.iter(|| {
+        buf.clear();
+        encoder::encode_into(&packet, &mut buf).unwrap()
+    });
+}
+
+#[bench]
+fn bench_encode_blobs(b: &mut Bencher) {
+    let packet = common::blob_heavy_packet();
+    b.iter(|| encoder::encode(&packet).unwrap());
+}
+
+#[bench]
+fn bench_encode_into_blobs(b: &mut Bencher) {
+    let packet = common::blob_heavy_packet();
+    let mut buf = Vec::with_capacity(2 * 1024 * 1024);
+    b.iter(|| {
+        buf.clear();
+        encoder::encode_into(&packet, &mut buf).unwrap()
+    });
+}
+
+#[bench]
+fn bench_encode_bundle_of_100(b: &mut Bencher) {
+    let packet = common::bundle_of_100();
+    b.iter(|| encoder::encode(&packet).unwrap());
+}
+
+#[bench]
+fn bench_encode_into_bundle_of_100(b: &mut Bencher) {
+    let packet = common::bundle_of_100();
+    let mut buf = Vec::with_capacity(4096);
+    b.iter(|| {
+        buf.clear();
+        encoder::encode_into(&packet, &mut buf).unwrap()
+    });
+}
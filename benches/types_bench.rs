@@ -0,0 +1,20 @@
+#![feature(test)]
+extern crate rosc;
+extern crate test;
+
+mod common;
+
+use self::test::Bencher;
+
+#[bench]
+fn bench_into_messages(b: &mut Bencher) {
+    let packet = common::nested_bundle();
+    b.iter(|| assert_eq!(packet.clone().into_messages().count(), 100));
+}
+
+#[bench]
+fn bench_clone_nested_bundle(b: &mut Bencher) {
+    // The share of bench_into_messages that is spent on cloning the packet
+    let packet = common::nested_bundle();
+    b.iter(|| packet.clone());
+}
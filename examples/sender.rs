@@ -15,7 +15,7 @@ fn main() {
     let args: Vec<String> = env::args().collect();
     let usage = format!(
         "Usage: {} HOST_IP:HOST_PORT CLIENT_IP:CLIENT_PORT",
-        &args[0]
+        args[0]
     );
     if args.len() < 3 {
         panic!("{}", usage);
@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rosc::decoder::{self, DecoderOptions, OscVisitor};
+
+struct Discard;
+
+impl<'a> OscVisitor<'a> for Discard {}
+
+fuzz_target!(|data: &[u8]| {
+    let _ = decoder::decode_udp(data);
+    let _ = decoder::decode_tcp(data);
+    let _ = decoder::decode_tcp_vec(data);
+    let _ = decoder::decode_ref(data);
+    let _ = decoder::decode_visit(data, &mut Discard);
+    let _ = decoder::decode_with_options(data, &DecoderOptions::default());
+    let _ = decoder::iter_packets(data, &DecoderOptions::default()).count();
+    let _ = decoder::peek_type_tags(data);
+    if let Ok(packet) = decoder::decode_lazy(data) {
+        let _ = packet.to_owned();
+    }
+});
@@ -1,24 +1,151 @@
-use crate::errors::OscError;
+use crate::errors::{truncate_input, OscError};
+use crate::profile::Profile;
 
+use alloc::borrow::Cow;
+use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
-use std::collections::HashSet;
-use std::iter::FromIterator;
+use core::fmt;
+use core::iter::{FromIterator, FusedIterator};
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(all(not(feature = "std"), feature = "core-error"))]
+use core::error;
 use nom::branch::alt;
 use nom::bytes::complete::{is_a, is_not, tag, take, take_while1, take_while_m_n};
 use nom::character::complete::{char, satisfy};
 use nom::combinator::{all_consuming, complete, opt, recognize, verify};
 use nom::error::{ErrorKind, ParseError};
-use nom::multi::{many1, separated_list1};
-use nom::sequence::{delimited, pair, separated_pair};
+use nom::multi::{fold_many1, many1, many1_count, separated_list1};
+use nom::sequence::{delimited, pair, preceded, separated_pair};
 use nom::{IResult, Parser};
 
+/// Errors of the address checking and matching methods, which convert into the corresponding
+/// [`OscError`] variants.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AddressError {
+    /// An address is not valid, `position` is the byte offset at which it stopped being valid.
+    /// `input` is the address, truncated by [`truncate_input`].
+    InvalidAddress { input: String, position: usize },
+    /// An address pattern is not valid, `position` is the byte offset of the first part that is not
+    /// valid, i.e. of the `/` preceding it. `input` is the pattern, truncated by
+    /// [`truncate_input`].
+    InvalidPattern { input: String, position: usize },
+    /// An address or address pattern of `len` bytes is longer than the limit of `max` bytes.
+    /// `input` is the address or pattern, truncated by [`truncate_input`].
+    TooLong { input: String, len: usize, max: usize },
+}
+
+/// The maximum length of addresses and address patterns in bytes used by [`verify_address`],
+/// [`verify_address_pattern`] and by default by [`Matcher`] and
+/// [`DecoderOptions`](crate::decoder::DecoderOptions). It is far longer than any sensible
+/// address, but keeps runaway addresses from being matched at length.
+pub const DEFAULT_MAX_ADDRESS_LEN: usize = 4096;
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AddressError::InvalidAddress { input, position } => {
+                write!(f, "invalid OSC address '{}' at byte {}", input, position)
+            }
+            AddressError::InvalidPattern { input, position } => {
+                write!(f, "invalid OSC address pattern '{}' at byte {}", input, position)
+            }
+            AddressError::TooLong { input, len, max } => write!(
+                f,
+                "OSC address '{}' of {} bytes exceeds the limit of {} bytes",
+                input, len, max
+            ),
+        }
+    }
+}
+
+impl AddressError {
+    /// Returns the stable numeric code of the error: 1001 for
+    /// [`InvalidAddress`](AddressError::InvalidAddress), 1002 for
+    /// [`InvalidPattern`](AddressError::InvalidPattern) and 1003 for
+    /// [`TooLong`](AddressError::TooLong). Codes are never reused or changed.
+    pub fn code(&self) -> u32 {
+        match self {
+            AddressError::InvalidAddress { .. } => 1001,
+            AddressError::InvalidPattern { .. } => 1002,
+            AddressError::TooLong { .. } => 1003,
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "core-error"))]
+impl error::Error for AddressError {}
+
+impl From<AddressError> for OscError {
+    fn from(err: AddressError) -> Self {
+        match err {
+            AddressError::InvalidAddress { input, .. } => {
+                OscError::BadAddress(format!("Invalid address '{}'", input))
+            }
+            AddressError::InvalidPattern { input, .. } => {
+                OscError::BadAddress(format!("Invalid address pattern '{}'", input))
+            }
+            AddressError::TooLong { len, max, .. } => OscError::AddressTooLong { len, max },
+        }
+    }
+}
+
+/// Returns the byte offset of the input at which `err` occurred.
+fn error_position(input: &str, err: nom::Err<nom::error::Error<&str>>) -> usize {
+    match err {
+        nom::Err::Error(err) | nom::Err::Failure(err) => input.len() - err.input.len(),
+        nom::Err::Incomplete(_) => input.len(),
+    }
+}
+
 /// With a Matcher OSC method addresses can be [matched](Matcher::match_address) against an OSC address pattern.
 /// Refer to the OSC specification for details about OSC address spaces: <http://opensoundcontrol.org/spec-1_0.html#osc-address-spaces-and-osc-addresses>
 #[derive(Debug)]
 pub struct Matcher {
     pub pattern: String,
     pattern_parts: Vec<AddressPatternComponent>,
+    // The pattern lacks the leading '/', which is then missing from the pattern parts as well
+    unprefixed: bool,
+    part_count: usize,
+    literal_prefix: String,
+    // The characters preceding the first wildcard, class or choice and the number of pattern
+    // components they make up
+    literal_chars: String,
+    literal_components: usize,
+    // Matches every valid address, see `Matcher::any`
+    any: bool,
+    max_address_len: usize,
+}
+
+/// Options for [`Matcher::with_options`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatcherOptions {
+    /// Accept patterns without the leading `/`, which then match addresses without the leading
+    /// `/` only, as decoded with
+    /// [`DecoderOptions::allow_unprefixed_addresses`](crate::decoder::DecoderOptions). Patterns
+    /// with a leading `/` are unaffected. Defaults to `false`.
+    pub allow_unprefixed: bool,
+    /// Maximum length of the pattern and of the addresses matched against it in bytes. Longer
+    /// patterns are rejected and longer addresses fail to match with [`AddressError::TooLong`]
+    /// before anything is matched. Defaults to [`DEFAULT_MAX_ADDRESS_LEN`].
+    pub max_address_len: usize,
+    /// The features the sender of the patterns supports, see [`Profile`]. Patterns containing the
+    /// `//` path traversal wildcard are rejected with [`AddressError::InvalidPattern`] at the
+    /// first `//` if the profile doesn't allow it. As matching `//` is not implemented, they are
+    /// still rejected as invalid otherwise. Defaults to [`Profile::FULL_1_1`].
+    pub profile: Profile,
+}
+
+impl Default for MatcherOptions {
+    fn default() -> Self {
+        MatcherOptions {
+            allow_unprefixed: false,
+            max_address_len: DEFAULT_MAX_ADDRESS_LEN,
+            profile: Profile::default(),
+        }
+    }
 }
 
 impl Matcher {
@@ -28,7 +155,8 @@ impl Matcher {
     /// Matcher should be instantiated once per pattern and reused because its construction requires parsing the address pattern which is computationally expensive.
     ///
     /// A valid address pattern begins with a `/` and contains at least a method name, e.g. `/tempo`.
-    /// OSC defines a couple of rules that look like regular expression but are subtly different:
+    /// The only exception is `/` itself, which denotes the root container and only matches the
+    /// address `/`. OSC defines a couple of rules that look like regular expression but are subtly different:
     ///
     /// - `?` matches a single character
     /// - `*` matches zero or more characters
@@ -45,24 +173,264 @@ impl Matcher {
     ///
     /// Matcher::new("/tempo").expect("valid address");
     /// Matcher::new("").expect_err("address does not start with a slash");
+    ///
+    /// let root = Matcher::new("/").expect("the root container");
+    /// assert!(root.match_address("/").unwrap());
+    /// assert!(!root.match_address("/tempo").unwrap());
     /// ```
-    pub fn new(pattern: &str) -> Result<Self, OscError> {
-        verify_address_pattern(pattern)?;
-        let mut match_fn = all_consuming(many1(map_address_pattern_component));
-        let (_, pattern_parts) = match_fn(pattern).map_err(|err| OscError::BadAddressPattern(err.to_string()))?;
+    pub fn new(pattern: &str) -> Result<Self, AddressError> {
+        Matcher::with_options(pattern, &MatcherOptions::default())
+    }
 
-        Ok(Matcher {
+    /// Instantiates a new `Matcher` like [`Matcher::new`], using the given options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::address::{Matcher, MatcherOptions};
+    ///
+    /// let options = MatcherOptions {
+    ///     allow_unprefixed: true,
+    ///     ..Default::default()
+    /// };
+    /// let matcher = Matcher::with_options("p?ng", &options).unwrap();
+    /// assert!(matcher.match_address("ping").unwrap());
+    /// assert!(matcher.match_address("/ping").is_err());
+    /// ```
+    pub fn with_options(pattern: &str, options: &MatcherOptions) -> Result<Self, AddressError> {
+        check_len(pattern, options.max_address_len)?;
+        if !options.profile.allows_path_traversal() {
+            if let Some(position) = pattern.find("//") {
+                return Err(AddressError::InvalidPattern {
+                    input: truncate_input(pattern),
+                    position,
+                });
+            }
+        }
+        let unprefixed = options.allow_unprefixed && !pattern.starts_with('/');
+        if unprefixed && pattern.is_empty() {
+            // Prefixing it would turn it into the pattern of the root container
+            return Err(AddressError::InvalidPattern {
+                input: String::new(),
+                position: 0,
+            });
+        }
+        let prefixed_pattern = if unprefixed {
+            let mut prefixed = String::with_capacity(pattern.len() + 1);
+            prefixed.push('/');
+            prefixed.push_str(pattern);
+            Cow::Owned(prefixed)
+        } else {
+            Cow::Borrowed(pattern)
+        };
+        // Positions refer to the pattern as given, without the added '/'
+        let unprefix = |position: usize| match unprefixed {
+            true => position.saturating_sub(1),
+            false => position,
+        };
+        // The length is checked already, against the limit of the options
+        verify_address_pattern_with_max_len(&prefixed_pattern, usize::MAX).map_err(|err| {
+            match err {
+                AddressError::InvalidPattern { position, .. } => AddressError::InvalidPattern {
+                    input: truncate_input(pattern),
+                    position: unprefix(position),
+                },
+                err => err,
+            }
+        })?;
+        if pattern == "/" {
+            // The root container is only matched literally
+            return Ok(Matcher::from_parts(pattern, Vec::new(), unprefixed, &[], options));
+        }
+        let tokens = parse_pattern_tokens(&prefixed_pattern).map_err(|err| {
+            AddressError::InvalidPattern {
+                input: truncate_input(pattern),
+                position: unprefix(error_position(&prefixed_pattern, err)),
+            }
+        })?;
+        let mut pattern_parts = map_pattern_tokens(&tokens);
+        if unprefixed {
+            pattern_parts.remove(0);
+        }
+
+        Ok(Matcher::from_parts(pattern, pattern_parts, unprefixed, &tokens, options))
+    }
+
+    fn from_parts(
+        pattern: &str,
+        pattern_parts: Vec<AddressPatternComponent>,
+        unprefixed: bool,
+        tokens: &[Vec<PatternToken>],
+        options: &MatcherOptions,
+    ) -> Self {
+        let literal_parts: Vec<String> = tokens
+            .iter()
+            .take_while(|part| part.iter().all(|token| matches!(token, PatternToken::Literal(_))))
+            .map(|part| part.iter().map(|token| token.to_string()).collect())
+            .collect();
+        let literal_prefix = match (tokens.is_empty(), literal_parts.is_empty(), unprefixed) {
+            // The root container
+            (true, _, _) => pattern.to_string(),
+            (false, true, _) => String::new(),
+            (false, false, true) => literal_parts.join("/"),
+            (false, false, false) => format!("/{}", literal_parts.join("/")),
+        };
+        let (literal_chars, literal_components) = if pattern_parts.is_empty() {
+            // The root container
+            (pattern.to_string(), 0)
+        } else {
+            let literals: Vec<&str> = pattern_parts
+                .iter()
+                .map_while(|part| match part {
+                    AddressPatternComponent::Tag(s) => Some(s.as_str()),
+                    _ => None,
+                })
+                .collect();
+            (literals.concat(), literals.len())
+        };
+        Matcher {
             pattern: pattern.into(),
             pattern_parts,
-        })
+            unprefixed,
+            part_count: tokens.len(),
+            literal_prefix,
+            literal_chars,
+            literal_components,
+            any: false,
+            max_address_len: options.max_address_len,
+        }
+    }
+
+    /// Instantiates a `Matcher` that matches every valid address regardless of its depth,
+    /// including the address `/` of the root container, e.g. for logging everything. Unlike `/*`,
+    /// which only matches addresses with a single part, this is what the OSC 1.1 pattern `//*`
+    /// means, which is used as its [`pattern`](Matcher::pattern). [`Matcher::new`] doesn't
+    /// accept `//` though, so the catch-all can only be constructed this way.
+    ///
+    /// Matching an address with it only checks that the address is valid and not longer than
+    /// [`DEFAULT_MAX_ADDRESS_LEN`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::address::Matcher;
+    ///
+    /// let any = Matcher::any();
+    /// assert!(any.match_address("/").unwrap());
+    /// assert!(any.match_address("/a").unwrap());
+    /// assert!(any.match_address("/a/b/c").unwrap());
+    /// assert!(any.match_address("/a/").is_err());
+    /// assert!(!Matcher::new("/*").unwrap().match_address("/a/b/c").unwrap());
+    /// ```
+    pub fn any() -> Self {
+        Matcher {
+            pattern: "//*".to_string(),
+            pattern_parts: Vec::new(),
+            unprefixed: false,
+            part_count: 0,
+            literal_prefix: String::new(),
+            literal_chars: "/".to_string(),
+            literal_components: 0,
+            any: true,
+            max_address_len: DEFAULT_MAX_ADDRESS_LEN,
+        }
+    }
+
+    /// Returns whether this is the catch-all [`Matcher::any`].
+    pub fn is_any(&self) -> bool {
+        self.any
+    }
+
+    /// Returns the number of parts of the pattern, e.g. 3 for `/mixer/*/gain` and 0 for the
+    /// pattern `/` of the root container. Only addresses with as many parts can match, except for
+    /// the catch-all [`Matcher::any`], for which this is 0 as well.
+    pub fn part_count(&self) -> usize {
+        self.part_count
+    }
+
+    /// Returns the leading parts of the pattern that contain no wildcards, classes or choices,
+    /// joined with slashes, which every matching address starts with. For example, this is
+    /// `/mixer` for `/mixer/1?/gain`, the whole pattern for a pattern without any wildcards, and
+    /// empty for a pattern starting with one, such as `/*/gain`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::address::Matcher;
+    ///
+    /// let matcher = Matcher::new("/mixer/1?/gain").unwrap();
+    /// assert_eq!(matcher.literal_prefix(), "/mixer");
+    /// assert_eq!(matcher.part_count(), 3);
+    /// assert!(matcher.could_match_depth(3));
+    /// assert!(!matcher.could_match_depth(2));
+    /// ```
+    pub fn literal_prefix(&self) -> &str {
+        &self.literal_prefix
+    }
+
+    /// Returns whether the pattern can match addresses with `depth` parts, which is only the case
+    /// for [`part_count`](Matcher::part_count) parts, as wildcards don't match across slashes,
+    /// and for any depth for [`Matcher::any`].
+    pub fn could_match_depth(&self, depth: usize) -> bool {
+        self.any || depth == self.part_count
+    }
+
+    /// Instantiates a new `Matcher` from the parts of an address pattern as returned by
+    /// [`tokenize_pattern`]. The tokens are joined into a pattern that is then parsed like by
+    /// [`Matcher::new`], so tokens that don't form a valid pattern, e.g. an empty
+    /// [`PatternToken::Literal`], are rejected and both ways of constructing a `Matcher` behave
+    /// the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::address::{tokenize_pattern, Matcher, PatternToken};
+    ///
+    /// let mut parts = tokenize_pattern("/mixer/*").unwrap();
+    /// parts[1].push(PatternToken::Literal("gain".to_string()));
+    /// let matcher = Matcher::from_tokens(&parts).unwrap();
+    /// assert_eq!(matcher.pattern, "/mixer/*gain");
+    /// assert!(matcher.match_address("/mixer/1gain").unwrap());
+    /// ```
+    pub fn from_tokens(parts: &[Vec<PatternToken>]) -> Result<Self, AddressError> {
+        if parts.is_empty() {
+            return Matcher::new("/");
+        }
+        let mut pattern = String::new();
+        for part in parts {
+            pattern.push('/');
+            for token in part {
+                let token = token.to_string();
+                // Tokens can't span several parts
+                if let Some(position) = token.find('/') {
+                    pattern.push_str(&token);
+                    return Err(AddressError::InvalidPattern {
+                        position: pattern.len() - token.len() + position,
+                        input: truncate_input(&pattern),
+                    });
+                }
+                pattern.push_str(&token);
+            }
+        }
+        if pattern == "/" {
+            // A single empty part is not the root container
+            return Err(AddressError::InvalidPattern {
+                input: pattern,
+                position: 1,
+            });
+        }
+        Matcher::new(&pattern)
     }
 
     /// Match an OSC address against an address pattern.
     /// If the address matches the pattern the result will be `true`, otherwise `false`.
     /// An error is returned if the given OSC address is not valid.
     ///
-    /// A valid OSC address begins with a `/` and contains at least a method name, e.g. `/tempo`.
+    /// A valid OSC address begins with a `/` and contains at least a method name, e.g. `/tempo`,
+    /// or is the address `/` of the root container, which is only matched by the pattern `/`.
     /// Despite OSC address patterns a plain address must not include any of the following characters `#*,/?[]{}`.
+    /// Addresses longer than [`MatcherOptions::max_address_len`] are rejected with
+    /// [`AddressError::TooLong`] without looking at their contents.
     ///
     /// # Examples
     ///
@@ -74,16 +442,32 @@ impl Matcher {
     /// assert!(matcher.match_address("/oscillator/8/phase").unwrap());
     /// assert_eq!(matcher.match_address("/oscillator/4/detune").unwrap(), false);
     /// ```
-    pub fn match_address(&self, address: &str) -> Result<bool, OscError> {
-        verify_address(address)?; // TODO: Create an address struct so we don't have to re-check addresses every time we match
+    pub fn match_address(&self, address: &str) -> Result<bool, AddressError> {
+        // TODO: Create an address struct so we don't have to re-check addresses every time we match
+        check_len(address, self.max_address_len)?;
+        if self.unprefixed {
+            verify_unprefixed_address(address)?;
+        } else {
+            verify_address_chars(address)?;
+        }
 
-        // Trivial case
-        if address == self.pattern {
-            return Ok(true);
+        Ok(self.match_verified(address, "", 0))
+    }
+
+    /// Matches the valid `address`, whose first `literal_parts` components are known to match
+    /// since it starts with `prefix`.
+    fn match_verified(&self, address: &str, prefix: &str, literal_parts: usize) -> bool {
+        // Trivial cases
+        if self.any || address == self.pattern {
+            return true;
+        }
+        // Wildcards can't match the empty name of the root container
+        if address == "/" {
+            return false;
         }
-        let mut remainder: &str = address;
+        let mut remainder: &str = &address[prefix.len()..];
         // Match the the address component by component
-        for (index, part) in self.pattern_parts.as_slice().iter().enumerate() {
+        for (index, part) in self.pattern_parts.iter().enumerate().skip(literal_parts) {
             let result = match part {
                 AddressPatternComponent::Tag(s) => match_literally(remainder, s.as_str()),
                 AddressPatternComponent::WildcardSingle => match_wildcard_single(remainder),
@@ -108,12 +492,492 @@ impl Matcher {
 
             match result {
                 Ok((i, _)) => remainder = i,
-                Err(_) => return Ok(false), // Component didn't match, goodbye
+                Err(_) => return false, // Component didn't match, goodbye
             };
         }
 
         // Address is only matched if it was consumed entirely
-        Ok(remainder.is_empty())
+        remainder.is_empty()
+    }
+
+    /// Returns the addresses of `addrs` that match the pattern, in the order they are given.
+    /// Invalid addresses are skipped. Addresses not starting with the literal characters the
+    /// pattern starts with, e.g. `/mixer/` of `/mixer/*/gain`, are rejected without further
+    /// matching. Use an [`AddressIndex`] to avoid looking at them at all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::address::Matcher;
+    ///
+    /// let matcher = Matcher::new("/mixer/*/gain").unwrap();
+    /// let addrs = ["/mixer/1/gain", "/mixer/1/pan", "/reverb/gain", "/mixer/2/gain"];
+    /// assert_eq!(matcher.filter(addrs), vec!["/mixer/1/gain", "/mixer/2/gain"]);
+    /// ```
+    pub fn filter<'a, I>(&self, addrs: I) -> Vec<&'a str>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let verify = |addr: &str| match self.unprefixed {
+            true => verify_unprefixed_address(addr).is_ok(),
+            false => verify_address_chars(addr).is_ok(),
+        };
+        addrs
+            .into_iter()
+            .filter(|addr| {
+                addr.starts_with(self.literal_chars.as_str())
+                    && addr.len() <= self.max_address_len
+                    && verify(addr)
+                    && self.match_verified(addr, &self.literal_chars, self.literal_components)
+            })
+            .collect()
+    }
+
+    /// Like [`filter`](Matcher::filter), for addresses that are known to be valid and to start with
+    /// the literal characters of the pattern.
+    fn filter_candidates<'a, I>(&self, addrs: I) -> Vec<&'a str>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        addrs
+            .into_iter()
+            .filter(|addr| self.match_verified(addr, &self.literal_chars, self.literal_components))
+            .collect()
+    }
+
+    /// Like [`match_address`](Matcher::match_address), but for an address given as raw bytes,
+    /// e.g. sliced from a received packet. Valid addresses are ASCII, so bytes that aren't valid
+    /// UTF-8 are reported as an invalid address at the offset of the first such byte.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::address::Matcher;
+    ///
+    /// let matcher = Matcher::new("/oscillator/[0-9]/frequency").unwrap();
+    /// assert!(matcher.match_address_bytes(b"/oscillator/1/frequency").unwrap());
+    /// assert!(matcher.match_address_bytes(b"/oscillator/\xff/frequency").is_err());
+    /// ```
+    pub fn match_address_bytes(&self, address: &[u8]) -> Result<bool, AddressError> {
+        match core::str::from_utf8(address) {
+            Ok(address) => self.match_address(address),
+            Err(err) => Err(AddressError::InvalidAddress {
+                input: truncate_input(&String::from_utf8_lossy(address)),
+                position: err.valid_up_to(),
+            }),
+        }
+    }
+}
+
+/// A sorted set of addresses, which finds the addresses matching a [`Matcher`] faster than
+/// [`Matcher::filter`] does for large address spaces: only the addresses starting with the
+/// literal characters the pattern starts with are matched against it, which are found by binary
+/// search.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::address::{AddressIndex, Matcher};
+///
+/// let index: AddressIndex = (1..=100)
+///     .flat_map(|ch| vec![format!("/mixer/{}/gain", ch), format!("/mixer/{}/pan", ch)])
+///     .collect();
+/// assert_eq!(index.len(), 200);
+///
+/// let matcher = Matcher::new("/mixer/1?/gain").unwrap();
+/// let matches = index.matching(&matcher);
+/// assert_eq!(matches.len(), 10);
+/// assert_eq!(matches[0], "/mixer/10/gain");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AddressIndex {
+    // Sorted and free of duplicates
+    addrs: Vec<String>,
+}
+
+impl AddressIndex {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        AddressIndex::default()
+    }
+
+    /// Adds `addr` to the index. Returns `false` if it was already contained, and fails if it is
+    /// not a valid address.
+    pub fn insert<S: Into<String>>(&mut self, addr: S) -> Result<bool, AddressError> {
+        let addr = addr.into();
+        verify_address(&addr)?;
+        match self.addrs.binary_search(&addr) {
+            Ok(_) => Ok(false),
+            Err(index) => {
+                self.addrs.insert(index, addr);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Removes `addr` from the index. Returns whether it was contained.
+    pub fn remove(&mut self, addr: &str) -> bool {
+        match self.addrs.binary_search_by(|probe| probe.as_str().cmp(addr)) {
+            Ok(index) => {
+                self.addrs.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Returns whether `addr` is contained in the index.
+    pub fn contains(&self, addr: &str) -> bool {
+        self.addrs.binary_search_by(|probe| probe.as_str().cmp(addr)).is_ok()
+    }
+
+    /// Returns the addresses of the index that match the pattern of `matcher`, in sorted order.
+    pub fn matching(&self, matcher: &Matcher) -> Vec<&str> {
+        if matcher.unprefixed {
+            // The addresses of the index all start with '/'
+            return Vec::new();
+        }
+        let prefix = matcher.literal_chars.as_str();
+        let start = self.addrs.partition_point(|addr| addr.as_str() < prefix);
+        let candidates = self.addrs[start..]
+            .iter()
+            .map(String::as_str)
+            .take_while(|addr| addr.starts_with(prefix));
+        matcher.filter_candidates(candidates)
+    }
+
+    /// Returns an iterator over the addresses of the index in sorted order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.addrs.iter().map(String::as_str)
+    }
+
+    /// Returns the number of addresses in the index.
+    pub fn len(&self) -> usize {
+        self.addrs.len()
+    }
+
+    /// Returns whether the index contains no addresses.
+    pub fn is_empty(&self) -> bool {
+        self.addrs.is_empty()
+    }
+}
+
+/// Collects addresses into an index. Invalid addresses are skipped, use
+/// [`AddressIndex::insert`] to check them.
+impl<S: Into<String>> FromIterator<S> for AddressIndex {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut addrs: Vec<String> = iter
+            .into_iter()
+            .map(Into::into)
+            .filter(|addr| verify_address(addr).is_ok())
+            .collect();
+        addrs.sort_unstable();
+        addrs.dedup();
+        AddressIndex { addrs }
+    }
+}
+
+/// Formats `value` as (part of) an address part, padded with leading zeros to at least `width`
+/// digits, as used by devices numbering their channels, e.g. `01` in `/ch/01/mix/fader`.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::address::format_part_u32;
+///
+/// assert_eq!(format!("/ch/{}/mix/fader", format_part_u32(1, 2)), "/ch/01/mix/fader");
+/// assert_eq!(format_part_u32(123, 2), "123");
+/// ```
+pub fn format_part_u32(value: u32, width: usize) -> String {
+    format!("{:0width$}", value, width = width)
+}
+
+/// A segment of an [`AddressTemplate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TemplateSegment {
+    Literal(String),
+    // A parameter with the width of its zero padding if it is an integer
+    Param { name: String, width: Option<usize> },
+}
+
+/// An address with values embedded in it, e.g. `/ch/{ch:02}/mix/fader`, from which addresses
+/// are [rendered](AddressTemplate::render) and whose values are
+/// [extracted](AddressTemplate::extract) from received addresses.
+///
+/// Parameters are written as `{name}` for any value, or as `{name:0N}` for an integer that is
+/// padded with leading zeros to at least `N` digits, see [`format_part_u32`]. Names consist of
+/// ASCII letters, digits and `_`. A parameter can make up a whole address part or just some of
+/// it, e.g. `/bus{n}/gain`, but two parameters must be separated by literal characters so that
+/// extracting them is unambiguous. Values are never empty and can't contain `/`, so a parameter
+/// matches within a single part.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::address::{AddressTemplate, Params};
+///
+/// let template = AddressTemplate::new("/ch/{ch:02}/{param}").unwrap();
+/// let mut params = Params::new();
+/// params.set("ch", 1).set("param", "mute");
+/// assert_eq!(template.render(&params).unwrap(), "/ch/01/mute");
+///
+/// let params = template.extract("/ch/12/fader").unwrap();
+/// assert_eq!(params.get_u32("ch"), Some(12));
+/// assert_eq!(params.get("param"), Some("fader"));
+/// // Integers have to be padded like when rendering them
+/// assert_eq!(template.extract("/ch/1/fader"), None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressTemplate {
+    template: String,
+    segments: Vec<TemplateSegment>,
+}
+
+impl AddressTemplate {
+    /// Parses `template`. Fails with [`OscError::BadAddress`] if a parameter is malformed,
+    /// repeated or directly follows another one, or if the template doesn't make up a valid
+    /// address for values of a single character.
+    pub fn new(template: &str) -> Result<Self, OscError> {
+        let error = |reason: &str| {
+            OscError::BadAddress(format!(
+                "Invalid address template '{}': {}",
+                truncate_input(template),
+                reason
+            ))
+        };
+        let mut segments = Vec::new();
+        let mut rest = template;
+        while !rest.is_empty() {
+            let (literal, param) = match rest.find('{') {
+                Some(start) => (&rest[..start], Some(&rest[start + 1..])),
+                None => (rest, None),
+            };
+            if !literal.is_empty() {
+                segments.push(TemplateSegment::Literal(literal.to_string()));
+            }
+            rest = match param {
+                Some(param) => {
+                    let end = param.find('}').ok_or_else(|| error("unclosed parameter"))?;
+                    if let Some(TemplateSegment::Param { .. }) = segments.last() {
+                        return Err(error("parameters must be separated by literal characters"));
+                    }
+                    segments.push(parse_template_param(&param[..end]).ok_or_else(|| {
+                        error(&format!("malformed parameter '{{{}}}'", &param[..end]))
+                    })?);
+                    &param[end + 1..]
+                }
+                None => "",
+            };
+        }
+
+        let mut names: Vec<&str> = segments
+            .iter()
+            .filter_map(|segment| match segment {
+                TemplateSegment::Param { name, .. } => Some(name.as_str()),
+                TemplateSegment::Literal(_) => None,
+            })
+            .collect();
+        let count = names.len();
+        names.sort_unstable();
+        names.dedup();
+        if names.len() != count {
+            return Err(error("parameters must have distinct names"));
+        }
+        // Values are address characters, so any valid value gives a valid address
+        let sample: String = segments
+            .iter()
+            .map(|segment| match segment {
+                TemplateSegment::Literal(literal) => literal.as_str(),
+                TemplateSegment::Param { .. } => "0",
+            })
+            .collect();
+        if verify_address(&sample).is_err() {
+            return Err(error("not a valid address"));
+        }
+
+        Ok(AddressTemplate {
+            template: template.to_string(),
+            segments,
+        })
+    }
+
+    /// Returns the template as it was passed to [`AddressTemplate::new`].
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Returns the address with the parameters replaced by their values in `params`. Fails with
+    /// [`OscError::BadAddress`] if a value is missing or empty, if it contains characters that
+    /// are not allowed in an address part like `/`, `*` or spaces, or if the value of a padded
+    /// parameter is not an integer.
+    pub fn render(&self, params: &Params) -> Result<String, OscError> {
+        let mut addr = String::with_capacity(self.template.len());
+        for segment in &self.segments {
+            let (name, width) = match segment {
+                TemplateSegment::Literal(literal) => {
+                    addr.push_str(literal);
+                    continue;
+                }
+                TemplateSegment::Param { name, width } => (name, width),
+            };
+            let value = params.get(name).ok_or_else(|| {
+                OscError::BadAddress(format!(
+                    "Missing value of parameter '{}' of address template '{}'",
+                    name,
+                    truncate_input(&self.template)
+                ))
+            })?;
+            let value = match width {
+                Some(width) => match value.parse() {
+                    Ok(value) => Cow::Owned(format_part_u32(value, *width)),
+                    Err(_) => {
+                        return Err(OscError::BadAddress(format!(
+                            "Value '{}' of parameter '{}' is not an integer",
+                            truncate_input(value),
+                            name
+                        )))
+                    }
+                },
+                None => Cow::Borrowed(value),
+            };
+            if invalid_part_offset(&value).is_some() {
+                return Err(OscError::BadAddress(format!(
+                    "Value '{}' of parameter '{}' is not a valid address part",
+                    truncate_input(&value),
+                    name
+                )));
+            }
+            addr.push_str(&value);
+        }
+        Ok(addr)
+    }
+
+    /// Returns the values of the parameters if `addr` is an address the template renders, or
+    /// `None` otherwise. Integer parameters only match values padded like
+    /// [`render`](AddressTemplate::render) pads them, and their values are extracted without the
+    /// padding.
+    pub fn extract(&self, addr: &str) -> Option<Params> {
+        let mut params = Params::new();
+        match extract_segments(&self.segments, addr, &mut params) {
+            true => Some(params),
+            false => None,
+        }
+    }
+}
+
+impl fmt::Display for AddressTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.template)
+    }
+}
+
+/// Parses the parameter `spec` of an address template, i.e. `name` or `name:0N`, without the
+/// enclosing braces.
+fn parse_template_param(spec: &str) -> Option<TemplateSegment> {
+    let (name, width) = match spec.split_once(':') {
+        Some((name, width)) => {
+            let digits = width.strip_prefix('0')?;
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            (name, Some(digits.parse().ok()?))
+        }
+        None => (spec, None),
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some(TemplateSegment::Param {
+        name: name.to_string(),
+        width,
+    })
+}
+
+/// Matches `addr` against the remaining `segments` of an address template, adding the values of
+/// their parameters to `params`.
+fn extract_segments(segments: &[TemplateSegment], addr: &str, params: &mut Params) -> bool {
+    let (segment, rest) = match segments.split_first() {
+        Some(segments) => segments,
+        None => return addr.is_empty(),
+    };
+    let (name, width) = match segment {
+        TemplateSegment::Literal(literal) => {
+            return match addr.strip_prefix(literal.as_str()) {
+                Some(addr) => extract_segments(rest, addr, params),
+                None => false,
+            }
+        }
+        TemplateSegment::Param { name, width } => (name, width),
+    };
+    // Values only consist of address characters, which are all ASCII
+    let max_len = addr.find(|c| !is_address_character(c)).unwrap_or(addr.len());
+    for len in 1..=max_len {
+        let value = &addr[..len];
+        match width {
+            Some(width) => match value.parse::<u32>() {
+                Ok(int) if format_part_u32(int, *width) == value => params.set(name, int),
+                _ => continue,
+            },
+            None => params.set(name, value),
+        };
+        if extract_segments(rest, &addr[len..], params) {
+            return true;
+        }
+        params.values.pop();
+    }
+    false
+}
+
+/// The values of the parameters of an [`AddressTemplate`], accessed by name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Params {
+    values: Vec<(String, String)>,
+}
+
+impl Params {
+    /// Creates an empty set of values.
+    pub fn new() -> Self {
+        Params::default()
+    }
+
+    /// Sets the value of the parameter `name`, replacing any previous value.
+    pub fn set<V: fmt::Display>(&mut self, name: &str, value: V) -> &mut Self {
+        let value = value.to_string();
+        match self.values.iter_mut().find(|(param, _)| param == name) {
+            Some((_, previous)) => *previous = value,
+            None => self.values.push((name.to_string(), value)),
+        }
+        self
+    }
+
+    /// Returns the value of the parameter `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the value of the parameter `name` parsed as an integer, or `None` if it is missing
+    /// or not an integer.
+    pub fn get_u32(&self, name: &str) -> Option<u32> {
+        self.get(name)?.parse().ok()
+    }
+
+    /// Returns an iterator over the names and values of the parameters, in the order they were
+    /// set or extracted in.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Returns the number of parameters with a value.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether no parameter has a value.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
     }
 }
 
@@ -124,7 +988,7 @@ fn is_address_character(x: char) -> bool {
         return false
     }
 
-    return ![' ', '#', '*', ',', '/', '?', '[', ']', '{', '}'].contains(&x)
+    ![' ', '#', '*', ',', '/', '?', '[', ']', '{', '}'].contains(&x)
 }
 
 /// Parser to turn a choice like '{foo,bar}' into a vector containing the choices, like ["foo", "bar"]
@@ -175,20 +1039,15 @@ struct CharacterClass {
     pub characters: String,
 }
 
-/// Expand a character range like 'a-d' to all the letters contained in the range, e.g. 'abcd'
+/// Append the characters in a range like 'a-d' to `characters` unless they are already in it.
 /// This is done by converting the characters to their ASCII values and then getting every ASCII
 /// in between.
-fn expand_character_range(first: char, second: char) -> String {
-    let start = first as u8;
-    let end = second as u8;
-
-    let range = start..=end;
-
-    range
-        .into_iter()
-        .map(char::from)
-        .filter(|c| is_address_character(*c))
-        .collect()
+fn push_character_range(characters: &mut String, first: char, second: char) {
+    for c in (first as u8..=second as u8).map(char::from) {
+        if is_address_character(c) && !characters.contains(c) {
+            characters.push(c);
+        }
+    }
 }
 
 impl CharacterClass {
@@ -203,26 +1062,37 @@ impl CharacterClass {
             Err(_) => negated = false,
         }
 
-        let characters = complete(many1(alt((
-            // '!' besides at beginning has no special meaning, but is legal
-            char::<_, nom::error::Error<&str>>('!').map(|_| String::from("")),
-            // attempt to match a range like a-z or 0-9
-            separated_pair(
-                satisfy(is_address_character),
-                char('-'),
-                satisfy(is_address_character),
-            )
-            .map(|(first, second)| expand_character_range(first, second)),
-            // Match characters literally
-            satisfy(is_address_character).map(|x| x.to_string()),
-            // Trailing dash
-            char('-').map(|_| String::from("-")),
-        ))))(input);
+        // Collect the characters into a single buffer, a class can't hold more characters than
+        // its source has unless it contains ranges
+        let characters = complete(fold_many1(
+            alt((
+                // '!' besides at beginning has no special meaning, but is legal
+                char::<_, nom::error::Error<&str>>('!').map(|_| None),
+                // attempt to match a range like a-z or 0-9
+                separated_pair(
+                    satisfy(is_address_character),
+                    char('-'),
+                    satisfy(is_address_character),
+                )
+                .map(Some),
+                // Match characters literally
+                satisfy(is_address_character).map(|x| Some((x, x))),
+                // Trailing dash
+                char('-').map(|_| Some(('-', '-'))),
+            )),
+            || String::with_capacity(input.len()),
+            |mut characters, range| {
+                if let Some((first, second)) = range {
+                    push_character_range(&mut characters, first, second);
+                }
+                characters
+            },
+        ))(input);
 
         match characters {
-            Ok((_, o)) => CharacterClass {
+            Ok((_, characters)) => CharacterClass {
                 negated,
-                characters: HashSet::<char>::from_iter(o.concat().chars()).iter().collect(),
+                characters,
             },
             _ => {
                 panic!("Invalid character class formatting {}", s)
@@ -240,26 +1110,48 @@ enum AddressPatternComponent {
     Choice(Vec<String>),
 }
 
-fn map_address_pattern_component(input: &str) -> IResult<&str, AddressPatternComponent> {
-    alt((
-        // Anything that's alphanumeric gets matched literally
-        take_while1(is_address_character)
-            .map(|s: &str| AddressPatternComponent::Tag(String::from(s))),
+/// Turn the tokens of a pattern's parts into the components the matcher works with
+fn map_pattern_tokens(parts: &[Vec<PatternToken>]) -> Vec<AddressPatternComponent> {
+    let mut components = Vec::new();
+    for part in parts {
         // Slashes must be seperated into their own tag for the non-greedy implementation of wildcards
-        char('/').map(|c: char| AddressPatternComponent::Tag(c.to_string())),
-        tag("?").map(|_| AddressPatternComponent::WildcardSingle),
-        // Combinations of wildcards are a bit tricky.
-        // Multiple '*' wildcards in a row are equal to a single '*'.
-        // A '*' wildcard followed by any number of '?' wildcards is also equal to '*' but must
-        // match at least the same amount of characters as there are '?' wildcards in the combination.
-        // For example, '*??' must match at least 2 characters.
-        is_a("*?").map(|x: &str| AddressPatternComponent::Wildcard(x.matches('?').count())),
-        pattern_choice.map(|choices: Vec<&str>| {
-            AddressPatternComponent::Choice(choices.iter().map(|x| x.to_string()).collect())
-        }),
-        pattern_character_class
-            .map(|s: &str| AddressPatternComponent::CharacterClass(CharacterClass::new(s))),
-    ))(input)
+        components.push(AddressPatternComponent::Tag(String::from("/")));
+        let mut tokens = part.iter().peekable();
+        while let Some(token) = tokens.next() {
+            components.push(match token {
+                PatternToken::Literal(s) => AddressPatternComponent::Tag(s.clone()),
+                PatternToken::SingleChar => AddressPatternComponent::WildcardSingle,
+                // Combinations of wildcards are a bit tricky.
+                // Multiple '*' wildcards in a row are equal to a single '*'.
+                // A '*' wildcard followed by any number of '?' wildcards is also equal to '*' but
+                // must match at least the same amount of characters as there are '?' wildcards in
+                // the combination. For example, '*??' must match at least 2 characters.
+                PatternToken::Wildcard => {
+                    let mut minimum_length = 0;
+                    while let Some(next) = tokens.peek() {
+                        match next {
+                            PatternToken::SingleChar => minimum_length += 1,
+                            PatternToken::Wildcard => {}
+                            _ => break,
+                        }
+                        tokens.next();
+                    }
+                    AddressPatternComponent::Wildcard(minimum_length)
+                }
+                PatternToken::Class { negated, spec } => {
+                    let class = match negated {
+                        true => format!("!{}", spec),
+                        false => spec.clone(),
+                    };
+                    AddressPatternComponent::CharacterClass(CharacterClass::new(&class))
+                }
+                PatternToken::Alternation(choices) => {
+                    AddressPatternComponent::Choice(choices.clone())
+                }
+            });
+        }
+    }
+    components
 }
 
 fn match_literally<'a>(input: &'a str, pattern: &str) -> IResult<&'a str, &'a str> {
@@ -345,7 +1237,9 @@ fn match_wildcard<'a>(
     }
 }
 
-/// Verify that an address is valid
+/// Verify that an address is valid. Besides method and container addresses like `/oscillator/1`,
+/// this accepts `/`, the address of the root container. Addresses longer than
+/// [`DEFAULT_MAX_ADDRESS_LEN`] are rejected, see [`verify_address_with_max_len`].
 ///
 /// # Examples
 /// ```
@@ -356,20 +1250,148 @@ fn match_wildcard<'a>(
 ///     Err(e) => println!("Address is not valid")
 /// }
 /// ```
-pub fn verify_address(input: &str) -> Result<(), OscError> {
-    match all_consuming::<_, _, nom::error::Error<&str>, _>(many1(pair(
-        tag("/"),
-        take_while1(is_address_character),
-    )))(input)
-    {
-        Ok(_) => Ok(()),
-        Err(_) => Err(OscError::BadAddress("Invalid address".to_string())),
+pub fn verify_address(input: &str) -> Result<(), AddressError> {
+    verify_address_with_max_len(input, DEFAULT_MAX_ADDRESS_LEN)
+}
+
+/// Verify that an address is valid like [`verify_address`], but reject addresses longer than
+/// `max_len` bytes with [`AddressError::TooLong`] instead.
+///
+/// # Examples
+/// ```
+/// use rosc::address::{verify_address_with_max_len, AddressError};
+///
+/// assert!(verify_address_with_max_len("/mixer/1", 8).is_ok());
+/// assert!(matches!(
+///     verify_address_with_max_len("/mixer/10", 8),
+///     Err(AddressError::TooLong { len: 9, max: 8, .. })
+/// ));
+/// ```
+pub fn verify_address_with_max_len(input: &str, max_len: usize) -> Result<(), AddressError> {
+    check_len(input, max_len)?;
+    verify_address_chars(input)
+}
+
+/// Rejects addresses and patterns longer than `max_len` bytes.
+fn check_len(input: &str, max_len: usize) -> Result<(), AddressError> {
+    match input.len() > max_len {
+        true => Err(AddressError::TooLong {
+            input: truncate_input(input),
+            len: input.len(),
+            max: max_len,
+        }),
+        false => Ok(()),
+    }
+}
+
+/// Verify that an address is valid regardless of its length.
+fn verify_address_chars(input: &str) -> Result<(), AddressError> {
+    split_address(input, true).map(|_| ())
+}
+
+/// Verify an address that lacks the leading '/', see [`MatcherOptions::allow_unprefixed`]
+fn verify_unprefixed_address(input: &str) -> Result<(), AddressError> {
+    split_address(input, false).map(|_| ())
+}
+
+/// Returns the byte offset of the first character of `part` that may not be used in an address
+/// part, which is 0 for an empty part, or `None` if `part` is a valid address part. This is the
+/// definition of valid parts that the addresses of the whole crate are checked against.
+fn invalid_part_offset(part: &str) -> Option<usize> {
+    match part.find(|c| !is_address_character(c)) {
+        None if part.is_empty() => Some(0),
+        offset => offset,
     }
 }
 
-/// Parse an address pattern's part until the next '/' or the end
-fn address_pattern_part_parser(input: &str) -> IResult<&str, Vec<&str>> {
-    many1::<_, _, nom::error::Error<&str>, _>(alt((
+/// Checks every part of the address `input`, which starts with a `/` if `prefixed`. The position
+/// of an error is that of the first invalid character, or of the `/` preceding a part that is
+/// empty or starts with one.
+fn split_address(input: &str, prefixed: bool) -> Result<AddressParts<'_>, AddressError> {
+    let invalid = |position| AddressError::InvalidAddress {
+        input: truncate_input(input),
+        position,
+    };
+    let parts = match prefixed {
+        true if input == "/" => return Ok(AddressParts { rest: None }),
+        true => input.strip_prefix('/').ok_or_else(|| invalid(0))?,
+        false => input,
+    };
+    let mut start = input.len() - parts.len();
+    for part in parts.split('/') {
+        match invalid_part_offset(part) {
+            Some(0) => return Err(invalid(start.saturating_sub(1))),
+            Some(offset) => return Err(invalid(start + offset)),
+            None => start += part.len() + 1,
+        }
+    }
+    Ok(AddressParts { rest: Some(parts) })
+}
+
+/// An iterator over the parts of a valid address, e.g. `mixer`, `1` and `gain` for
+/// `/mixer/1/gain`, which borrows them from the address. The address `/` of the root container
+/// has no parts.
+///
+/// The address is checked once when the iterator is created, the same way
+/// [`verify_address`] and the rest of the crate check addresses. The parts are then returned
+/// without allocating.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::address::AddressParts;
+///
+/// let parts = AddressParts::new("/mixer/1/gain").unwrap();
+/// assert_eq!(parts.collect::<Vec<_>>(), vec!["mixer", "1", "gain"]);
+/// assert_eq!(AddressParts::new("/").unwrap().count(), 0);
+/// assert!(AddressParts::new("/mixer/*").is_err());
+/// assert!(AddressParts::new("/mixer/").is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct AddressParts<'a> {
+    // The parts that are yet to be returned, separated by slashes
+    rest: Option<&'a str>,
+}
+
+impl<'a> AddressParts<'a> {
+    /// Returns the parts of `addr`, or fails with [`OscError::BadAddress`] if it is not a valid
+    /// address, see [`verify_address`]. Unlike `verify_address` this doesn't limit the length of
+    /// the address, which can be checked with [`verify_address_with_max_len`].
+    pub fn new(addr: &'a str) -> Result<Self, OscError> {
+        Ok(split_address(addr, true)?)
+    }
+
+    /// Returns the parts of `addr` like [`AddressParts::new`], for an address without the
+    /// leading `/`, see [`MatcherOptions::allow_unprefixed`].
+    pub fn unprefixed(addr: &'a str) -> Result<Self, OscError> {
+        Ok(split_address(addr, false)?)
+    }
+}
+
+impl<'a> Iterator for AddressParts<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let rest = self.rest?;
+        match rest.split_once('/') {
+            Some((part, rest)) => {
+                self.rest = Some(rest);
+                Some(part)
+            }
+            None => {
+                self.rest = None;
+                Some(rest)
+            }
+        }
+    }
+}
+
+impl FusedIterator for AddressParts<'_> {}
+
+/// Parse an address pattern's part until the next '/' or the end, returning the number of
+/// elements in it
+fn address_pattern_part_parser(input: &str) -> IResult<&str, usize> {
+    many1_count::<_, _, nom::error::Error<&str>, _>(alt((
         take_while1(is_address_character),
         tag("?"),
         tag("*"),
@@ -378,7 +1400,9 @@ fn address_pattern_part_parser(input: &str) -> IResult<&str, Vec<&str>> {
     )))(input)
 }
 
-/// Verify that an address pattern is valid
+/// Verify that an address pattern is valid, which includes `/`, the pattern matching the root
+/// container. Patterns longer than [`DEFAULT_MAX_ADDRESS_LEN`] are rejected, see
+/// [`verify_address_pattern_with_max_len`].
 ///
 /// # Examples
 /// ```
@@ -389,13 +1413,128 @@ fn address_pattern_part_parser(input: &str) -> IResult<&str, Vec<&str>> {
 ///     Err(e) => println!("Address is not valid")
 /// }
 /// ```
-pub fn verify_address_pattern(input: &str) -> Result<(), OscError> {
-    match all_consuming(many1(
+pub fn verify_address_pattern(input: &str) -> Result<(), AddressError> {
+    verify_address_pattern_with_max_len(input, DEFAULT_MAX_ADDRESS_LEN)
+}
+
+/// Verify that an address pattern is valid like [`verify_address_pattern`], but reject patterns
+/// longer than `max_len` bytes with [`AddressError::TooLong`] instead.
+pub fn verify_address_pattern_with_max_len(
+    input: &str,
+    max_len: usize,
+) -> Result<(), AddressError> {
+    check_len(input, max_len)?;
+    if input == "/" {
+        return Ok(());
+    }
+    match all_consuming(many1_count(
         // Each part must start with a '/'. This automatically also prevents a trailing '/'
-        pair(tag("/"), address_pattern_part_parser.map(|x| x.concat())),
+        pair(tag("/"), address_pattern_part_parser),
     ))(input)
     {
         Ok(_) => Ok(()),
-        Err(_) => Err(OscError::BadAddress("Invalid address pattern".to_string())),
+        Err(err) => Err(AddressError::InvalidPattern {
+            input: truncate_input(input),
+            position: error_position(input, err),
+        }),
+    }
+}
+
+/// A rule of an address pattern, as returned by [`tokenize_pattern`] for each part of the pattern.
+/// Its [`Display`](fmt::Display) implementation writes the rule the way it appears in a pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatternToken {
+    /// Characters that are matched literally, e.g. `tempo`
+    Literal(String),
+    /// `*`, matching zero or more characters
+    Wildcard,
+    /// `?`, matching a single character
+    SingleChar,
+    /// A character class like `[!a-z]`, where `spec` holds the sets and ranges following the
+    /// negating `!`
+    Class { negated: bool, spec: String },
+    /// An alternative like `{foo,bar}`, matching either of the choices
+    Alternation(Vec<String>),
+}
+
+impl fmt::Display for PatternToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatternToken::Literal(s) => f.write_str(s),
+            PatternToken::Wildcard => f.write_str("*"),
+            PatternToken::SingleChar => f.write_str("?"),
+            PatternToken::Class { negated, spec } => match negated {
+                true => write!(f, "[!{}]", spec),
+                false => write!(f, "[{}]", spec),
+            },
+            PatternToken::Alternation(choices) => write!(f, "{{{}}}", choices.join(",")),
+        }
     }
 }
+
+/// Parser for a single rule of an address pattern's part
+fn pattern_token(input: &str) -> IResult<&str, PatternToken> {
+    alt((
+        take_while1(is_address_character).map(|s: &str| PatternToken::Literal(String::from(s))),
+        char('*').map(|_| PatternToken::Wildcard),
+        char('?').map(|_| PatternToken::SingleChar),
+        pattern_choice.map(|choices: Vec<&str>| {
+            PatternToken::Alternation(choices.iter().map(|x| x.to_string()).collect())
+        }),
+        pattern_character_class.map(|s: &str| match s.strip_prefix('!') {
+            Some(spec) => PatternToken::Class {
+                negated: true,
+                spec: String::from(spec),
+            },
+            None => PatternToken::Class {
+                negated: false,
+                spec: String::from(s),
+            },
+        }),
+    ))(input)
+}
+
+/// Split a verified address pattern other than `/` into the tokens of its parts
+fn parse_pattern_tokens(
+    input: &str,
+) -> Result<Vec<Vec<PatternToken>>, nom::Err<nom::error::Error<&str>>> {
+    all_consuming(many1(preceded(char('/'), many1(pattern_token))))(input).map(|(_, parts)| parts)
+}
+
+/// Split an address pattern into its parts, each a list of the rules that make up the part. The
+/// pattern `/` of the root container has no parts. [`Matcher::from_tokens`] turns the parts back
+/// into a `Matcher`.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::address::{tokenize_pattern, PatternToken};
+///
+/// assert_eq!(
+///     tokenize_pattern("/mixer/[!0]*/{gain,mute}").unwrap(),
+///     vec![
+///         vec![PatternToken::Literal("mixer".to_string())],
+///         vec![
+///             PatternToken::Class {
+///                 negated: true,
+///                 spec: "0".to_string(),
+///             },
+///             PatternToken::Wildcard,
+///         ],
+///         vec![PatternToken::Alternation(vec!["gain".to_string(), "mute".to_string()])],
+///     ]
+/// );
+/// assert!(tokenize_pattern("/mixer/").is_err());
+/// ```
+pub fn tokenize_pattern(pattern: &str) -> Result<Vec<Vec<PatternToken>>, OscError> {
+    verify_address_pattern(pattern)?;
+    if pattern == "/" {
+        return Ok(Vec::new());
+    }
+    parse_pattern_tokens(pattern).map_err(|err| {
+        OscError::from(AddressError::InvalidPattern {
+            input: truncate_input(pattern),
+            position: error_position(pattern, err),
+        })
+    })
+}
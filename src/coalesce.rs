@@ -0,0 +1,428 @@
+use crate::encoder::BUNDLE_HEADER_SIZE;
+#[cfg(feature = "std")]
+use crate::types::{Clock, SystemClock};
+use crate::types::{OscBundle, OscMessage, OscPacket, OscTime, Result};
+
+use crate::alloc::vec::Vec;
+#[cfg(feature = "std")]
+use crate::alloc::boxed::Box;
+
+#[cfg(feature = "std")]
+use core::fmt;
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+#[cfg(feature = "std")]
+use std::thread::{self, JoinHandle};
+
+/// Options for [`Coalescer::new`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoalescerOptions {
+    /// How long messages are collected before they are flushed. Defaults to 10 ms.
+    pub interval: Duration,
+    /// Flush the collected messages before the encoded bundle would grow larger than this many
+    /// bytes. Defaults to 1432, which fits into a single Ethernet frame when sent over UDP.
+    pub max_size: usize,
+    /// Keep only the latest message for each address that is pushed within an interval. Defaults
+    /// to `false`.
+    pub dedup_by_address: bool,
+}
+
+impl Default for CoalescerOptions {
+    fn default() -> Self {
+        CoalescerOptions {
+            interval: Duration::from_millis(10),
+            max_size: 1432,
+            dedup_by_address: false,
+        }
+    }
+}
+
+/// Collects outgoing messages and flushes them as a single immediate bundle, either once
+/// [`CoalescerOptions::interval`] has passed or before the bundle would exceed
+/// [`CoalescerOptions::max_size`], whichever comes first.
+///
+/// The coalescer doesn't read a clock itself, the current time is passed to
+/// [`flush_due`](Coalescer::flush_due) instead, e.g. once per iteration of a game loop. The
+/// interval starts when `flush_due` first sees collected messages. Use [`ThreadedCoalescer`] to
+/// flush from a background thread instead.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::coalesce::{Coalescer, CoalescerOptions};
+/// use rosc::{OscMessage, OscTime};
+/// use std::time::Duration;
+///
+/// let mut coalescer = Coalescer::new(CoalescerOptions {
+///     interval: Duration::from_millis(500),
+///     ..Default::default()
+/// });
+/// assert_eq!(coalescer.push(OscMessage::from("/a")).unwrap(), None);
+/// assert_eq!(coalescer.push(OscMessage::from("/b")).unwrap(), None);
+///
+/// assert_eq!(coalescer.flush_due(OscTime::from((1, 0))), None);
+/// let bundle = coalescer.flush_due(OscTime::from((1, 1 << 31))).unwrap();
+/// assert_eq!(bundle.content.len(), 2);
+/// assert!(bundle.is_immediate());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Coalescer {
+    options: CoalescerOptions,
+    // Collected messages along with their encoded size
+    messages: Vec<(OscMessage, usize)>,
+    size: usize,
+    started: Option<OscTime>,
+}
+
+impl Coalescer {
+    /// Creates a coalescer without any collected messages.
+    pub fn new(options: CoalescerOptions) -> Self {
+        Coalescer {
+            options,
+            messages: Vec::new(),
+            size: BUNDLE_HEADER_SIZE,
+            started: None,
+        }
+    }
+
+    /// Adds `msg` to the collected messages. If the encoded bundle would exceed
+    /// [`CoalescerOptions::max_size`] with it, the previously collected messages are flushed
+    /// first and returned. A message that exceeds the maximum size on its own is still sent in a
+    /// bundle of its own.
+    ///
+    /// The message is only measured here, so errors such as an oversized blob are reported when
+    /// the flushed bundle is encoded.
+    pub fn push(&mut self, msg: OscMessage) -> Result<Option<OscBundle>> {
+        // Each bundle element is preceded by its size
+        let msg_size = msg.encoded_len() + 4;
+        let position = if self.options.dedup_by_address {
+            self.messages
+                .iter()
+                .position(|(collected, _)| collected.addr == msg.addr)
+        } else {
+            None
+        };
+        let size = match position {
+            Some(i) => self.size - self.messages[i].1 + msg_size,
+            None => self.size + msg_size,
+        };
+
+        if size <= self.options.max_size || self.messages.is_empty() {
+            match position {
+                Some(i) => self.messages[i] = (msg, msg_size),
+                None => self.messages.push((msg, msg_size)),
+            }
+            self.size = size;
+            return Ok(None);
+        }
+
+        if let Some(i) = position {
+            // The latest value replaces the collected one, so it must not be flushed
+            let (_, replaced_size) = self.messages.remove(i);
+            self.size -= replaced_size;
+        }
+        let flushed = self.flush();
+        self.messages.push((msg, msg_size));
+        self.size += msg_size;
+        Ok(flushed)
+    }
+
+    /// Flushes the collected messages if [`CoalescerOptions::interval`] has passed at `now`.
+    pub fn flush_due(&mut self, now: OscTime) -> Option<OscBundle> {
+        if self.messages.is_empty() {
+            return None;
+        }
+        let started = *self.started.get_or_insert(now);
+        if now.saturating_duration_since(started) < self.options.interval {
+            return None;
+        }
+        self.flush()
+    }
+
+    /// Starts the interval at `now` unless it has already started or no messages are collected.
+    #[cfg(feature = "std")]
+    fn start_interval(&mut self, now: OscTime) {
+        if !self.messages.is_empty() {
+            self.started.get_or_insert(now);
+        }
+    }
+
+    /// Returns how long after `now` the collected messages are due to be flushed.
+    #[cfg(feature = "std")]
+    fn time_until_due(&self, now: OscTime) -> Duration {
+        match self.started {
+            Some(started) => {
+                let elapsed = now.saturating_duration_since(started);
+                self.options.interval.saturating_sub(elapsed)
+            }
+            None => self.options.interval,
+        }
+    }
+
+    /// Flushes the collected messages right away, unless there are none.
+    pub fn flush(&mut self) -> Option<OscBundle> {
+        if self.messages.is_empty() {
+            return None;
+        }
+        self.size = BUNDLE_HEADER_SIZE;
+        self.started = None;
+        Some(OscBundle {
+            timetag: OscTime::IMMEDIATE,
+            content: self
+                .messages
+                .drain(..)
+                .map(|(msg, _)| OscPacket::Message(msg))
+                .collect(),
+        })
+    }
+
+    /// Returns the number of collected messages.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns whether no messages are collected.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Returns the size of the bundle the collected messages would be encoded as.
+    pub fn encoded_size(&self) -> usize {
+        self.size
+    }
+}
+
+/// Packs `messages` into bundles with the time tag `timetag`, each holding as many of the
+/// following messages as fit into `max_size` encoded bytes, e.g. to send a large batch in as few
+/// datagrams as possible. The messages keep their order, both within and across bundles.
+///
+/// Like [`Coalescer::push`], a message that exceeds `max_size` on its own, even in a bundle of
+/// its own, is still yielded in a bundle of its own rather than dropped. Check the size with
+/// [`OscMessage::encoded_len`] beforehand to handle such messages differently, e.g. with
+/// [`OscMessage::truncate_to_fit`].
+///
+/// # Examples
+///
+/// ```
+/// use rosc::{coalesce, encoder, OscMessage, OscPacket, OscTime};
+///
+/// let messages = (0..100).map(|i| OscMessage::from(format!("/channel/{}", i).as_str()));
+/// let bundles: Vec<_> = coalesce::pack(messages, 512, OscTime::IMMEDIATE).collect();
+/// assert_eq!(bundles.len(), 5);
+/// for bundle in bundles {
+///     assert!(encoder::encode(&OscPacket::Bundle(bundle)).unwrap().len() <= 512);
+/// }
+/// ```
+pub fn pack<I>(messages: I, max_size: usize, timetag: OscTime) -> Pack<I::IntoIter>
+where
+    I: IntoIterator<Item = OscMessage>,
+{
+    Pack {
+        messages: messages.into_iter(),
+        max_size,
+        timetag,
+        next: None,
+    }
+}
+
+/// The bundles of [`pack`].
+#[derive(Clone, Debug)]
+pub struct Pack<I> {
+    messages: I,
+    max_size: usize,
+    timetag: OscTime,
+    // The message that didn't fit into the previous bundle
+    next: Option<OscMessage>,
+}
+
+impl<I: Iterator<Item = OscMessage>> Iterator for Pack<I> {
+    type Item = OscBundle;
+
+    fn next(&mut self) -> Option<OscBundle> {
+        let first = self.next.take().or_else(|| self.messages.next())?;
+        // Each bundle element is preceded by its size
+        let mut size = BUNDLE_HEADER_SIZE + first.encoded_len() + 4;
+        let mut content = vec![OscPacket::Message(first)];
+        if size < self.max_size {
+            for msg in self.messages.by_ref() {
+                size += msg.encoded_len() + 4;
+                if size > self.max_size {
+                    self.next = Some(msg);
+                    break;
+                }
+                content.push(OscPacket::Message(msg));
+            }
+        }
+        Some(OscBundle {
+            timetag: self.timetag,
+            content,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+struct State {
+    coalescer: Coalescer,
+    clock: Box<dyn Clock + Send>,
+    // Bundles flushed by `push` because of their size, which are delivered first
+    ready: VecDeque<OscBundle>,
+    stop: bool,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("State")
+            .field("coalescer", &self.coalescer)
+            .field("ready", &self.ready)
+            .field("stop", &self.stop)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A [`Coalescer`] which is flushed by a background thread.
+///
+/// Bundles are passed to the callback on the background thread, including the ones flushed
+/// because of their size. The interval of a bundle starts when its first message is pushed. The
+/// messages that are still collected are flushed when the `ThreadedCoalescer` is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::coalesce::{CoalescerOptions, ThreadedCoalescer};
+/// use rosc::OscMessage;
+/// use std::sync::mpsc;
+///
+/// let (tx, rx) = mpsc::channel();
+/// let coalescer = ThreadedCoalescer::new(CoalescerOptions::default(), move |bundle| {
+///     tx.send(bundle).unwrap()
+/// });
+/// coalescer.push(OscMessage::from("/a")).unwrap();
+/// coalescer.push(OscMessage::from("/b")).unwrap();
+/// drop(coalescer);
+///
+/// let addrs: Vec<_> = rx.try_iter().flat_map(|bundle| bundle.content).collect();
+/// assert_eq!(addrs.len(), 2);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct ThreadedCoalescer {
+    shared: Arc<(Mutex<State>, Condvar)>,
+    worker: Option<JoinHandle<()>>,
+}
+
+#[cfg(feature = "std")]
+impl ThreadedCoalescer {
+    /// Creates a coalescer which passes flushed bundles to `callback` on its background thread.
+    pub fn new<F>(options: CoalescerOptions, callback: F) -> Self
+    where
+        F: FnMut(OscBundle) + Send + 'static,
+    {
+        ThreadedCoalescer::with_clock(options, SystemClock, callback)
+    }
+
+    /// Creates a coalescer like [`ThreadedCoalescer::new`] which takes the current time from
+    /// `clock` instead of the system time.
+    ///
+    /// The background thread sleeps until the interval has passed, so `clock` has to advance at
+    /// the same rate as the system time. Clocks that jump ahead, such as a
+    /// [`ManualClock`](crate::ManualClock), have to be followed by a call to
+    /// [`wake`](ThreadedCoalescer::wake).
+    pub fn with_clock<F, C>(options: CoalescerOptions, clock: C, mut callback: F) -> Self
+    where
+        F: FnMut(OscBundle) + Send + 'static,
+        C: Clock + Send + 'static,
+    {
+        let shared = Arc::new((
+            Mutex::new(State {
+                coalescer: Coalescer::new(options),
+                clock: Box::new(clock),
+                ready: VecDeque::new(),
+                stop: false,
+            }),
+            Condvar::new(),
+        ));
+        let worker = {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                let (state, wakeup) = &*shared;
+                let mut state = lock(state);
+                loop {
+                    let flushed = if let Some(bundle) = state.ready.pop_front() {
+                        Some(bundle)
+                    } else if state.stop {
+                        state.coalescer.flush()
+                    } else {
+                        let now = state.clock.now();
+                        state.coalescer.flush_due(now)
+                    };
+                    if let Some(bundle) = flushed {
+                        drop(state);
+                        callback(bundle);
+                        state = lock(&shared.0);
+                        continue;
+                    }
+                    if state.stop {
+                        return;
+                    }
+                    state = if state.coalescer.is_empty() {
+                        wakeup.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner())
+                    } else {
+                        let now = state.clock.now();
+                        let timeout = state.coalescer.time_until_due(now);
+                        match wakeup.wait_timeout(state, timeout) {
+                            Ok((state, _)) => state,
+                            Err(poisoned) => poisoned.into_inner().0,
+                        }
+                    };
+                }
+            })
+        };
+        ThreadedCoalescer {
+            shared,
+            worker: Some(worker),
+        }
+    }
+
+    /// Adds `msg` to the collected messages, see [`Coalescer::push`].
+    pub fn push(&self, msg: OscMessage) -> Result<()> {
+        let (state, wakeup) = &*self.shared;
+        let mut state = lock(state);
+        let now = state.clock.now();
+        if let Some(bundle) = state.coalescer.push(msg)? {
+            state.ready.push_back(bundle);
+        }
+        // Starts the interval now rather than when the background thread gets to the message
+        state.coalescer.start_interval(now);
+        wakeup.notify_one();
+        Ok(())
+    }
+
+    /// Makes the background thread check the clock right away instead of at the end of the
+    /// interval, e.g. after advancing a [`ManualClock`](crate::ManualClock).
+    pub fn wake(&self) {
+        self.shared.1.notify_one();
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for ThreadedCoalescer {
+    fn drop(&mut self) {
+        let (state, wakeup) = &*self.shared;
+        lock(state).stop = true;
+        wakeup.notify_one();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Locks the state, which remains consistent even if a callback panicked while it was unlocked.
+#[cfg(feature = "std")]
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,263 @@
+use crate::errors::{MAX_SNIPPET_LEN, TRUNCATION_MARKER};
+use crate::types::{OscArray, OscBundle, OscMessage, OscPacket, OscType};
+
+use crate::alloc::string::String;
+
+use core::fmt::{self, Write};
+
+/// Formats `buf` like `hexdump -C`, with 16 bytes per line preceded by their offset and followed
+/// by their printable ASCII characters.
+///
+/// ```
+/// use rosc::debug::hex_dump;
+///
+/// assert_eq!(
+///     hex_dump(b"/abc\0\0\0\0,i\0\0\0\0\0\x01"),
+///     "00000000  2f 61 62 63 00 00 00 00  2c 69 00 00 00 00 00 01  |/abc....,i......|\n"
+/// );
+/// ```
+pub fn hex_dump(buf: &[u8]) -> String {
+    let mut dump = String::new();
+    for (line, chunk) in buf.chunks(16).enumerate() {
+        let _ = write!(dump, "{:08x} ", line * 16);
+        for i in 0..16 {
+            if i % 8 == 0 {
+                dump.push(' ');
+            }
+            match chunk.get(i) {
+                Some(b) => {
+                    let _ = write!(dump, "{:02x} ", b);
+                }
+                None => dump.push_str("   "),
+            }
+        }
+        dump.push_str(" |");
+        dump.extend(chunk.iter().map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        }));
+        dump.push_str("|\n");
+    }
+    dump
+}
+
+/// Limits of the output of [`CompactDebug`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompactLimits {
+    /// The number of bytes shown of each blob. Defaults to 16.
+    pub blob_bytes: usize,
+    /// The number of chars shown of each string and address. Defaults to [`MAX_SNIPPET_LEN`].
+    pub string_chars: usize,
+    /// The number of elements shown of each bundle, of the arguments of each message and of the
+    /// contents of each array. Defaults to 16.
+    pub elements: usize,
+    /// The number of bundles and arrays shown nested in each other. Defaults to 4.
+    pub depth: usize,
+}
+
+impl Default for CompactLimits {
+    fn default() -> Self {
+        CompactLimits {
+            blob_bytes: 16,
+            string_chars: MAX_SNIPPET_LEN,
+            elements: 16,
+            depth: 4,
+        }
+    }
+}
+
+/// The value formatted by a [`CompactDebug`].
+#[derive(Clone, Copy)]
+enum Value<'a> {
+    Packet(&'a OscPacket),
+    Packets(&'a [OscPacket]),
+    Bundle(&'a OscBundle),
+    Message(&'a OscMessage),
+    Arg(&'a OscType),
+    Args(&'a [OscType]),
+    Array(&'a OscArray),
+    Str(&'a str),
+    Blob(&'a [u8]),
+}
+
+/// Formats a packet, bundle, message or argument like its `Debug` implementation, but within
+/// [`CompactLimits`] so that a packet with a huge blob or deeply nested bundles doesn't flood a
+/// log. Returned by the `compact_debug` methods, e.g. [`OscPacket::compact_debug`].
+///
+/// The `Debug` implementations of the packet types themselves always show everything, so that
+/// `{:?}` and `{:#?}` can be relied on in tests. The output of `CompactDebug` is the same for
+/// values within the limits, and pretty-printed with `{:#?}` as well.
+///
+/// Parts beyond the limits are cut off and marked by [`TRUNCATION_MARKER`]:
+///
+/// - strings and addresses are followed by the marker and the number of chars cut off,
+/// - lists of blob bytes, bundle elements, arguments and array contents end with the marker and
+///   the number of elements cut off,
+/// - bundles and arrays nested deeper than the limit are shown as `OscBundle { .. }` and
+///   `OscArray { .. }`.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::debug::CompactLimits;
+/// use rosc::{OscMessage, OscType};
+///
+/// let msg = OscMessage {
+///     addr: "/sample".to_string(),
+///     args: vec![OscType::Blob(vec![0; 1_000_000]), "a".repeat(100).into()],
+/// };
+/// let limits = CompactLimits {
+///     blob_bytes: 2,
+///     string_chars: 4,
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     format!("{:?}", msg.compact_debug().with_limits(limits)),
+///     concat!(
+///         r#"OscMessage { addr: "/sam"... 3 more chars, "#,
+///         r#"args: [Blob([0, 0, ... 999998 more]), String("aaaa"... 96 more chars)] }"#,
+///     )
+/// );
+/// ```
+#[derive(Clone, Copy)]
+pub struct CompactDebug<'a> {
+    value: Value<'a>,
+    limits: CompactLimits,
+    depth: usize,
+}
+
+impl<'a> CompactDebug<'a> {
+    fn new(value: Value<'a>) -> Self {
+        CompactDebug {
+            value,
+            limits: CompactLimits::default(),
+            depth: 0,
+        }
+    }
+
+    /// Formats within `limits` instead of the default ones.
+    pub fn with_limits(self, limits: CompactLimits) -> Self {
+        CompactDebug { limits, ..self }
+    }
+
+    /// Returns a formatter of `value`, which is nested in `depth` bundles and arrays.
+    fn nested(&self, value: Value<'a>, depth: usize) -> CompactDebug<'a> {
+        CompactDebug {
+            value,
+            limits: self.limits,
+            depth,
+        }
+    }
+
+    /// Formats the first elements of `items` as a list.
+    fn fmt_list<T>(
+        &self,
+        f: &mut fmt::Formatter,
+        items: &'a [T],
+        value: fn(&'a T) -> Value<'a>,
+    ) -> fmt::Result {
+        let mut list = f.debug_list();
+        for item in items.iter().take(self.limits.elements) {
+            list.entry(&self.nested(value(item), self.depth));
+        }
+        if items.len() > self.limits.elements {
+            let more = items.len() - self.limits.elements;
+            list.entry(&format_args!("{} {} more", TRUNCATION_MARKER, more));
+        }
+        list.finish()
+    }
+}
+
+impl<'a> fmt::Debug for CompactDebug<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let limits = &self.limits;
+        match self.value {
+            Value::Packet(OscPacket::Message(msg)) => f
+                .debug_tuple("Message")
+                .field(&self.nested(Value::Message(msg), self.depth))
+                .finish(),
+            Value::Packet(OscPacket::Bundle(bundle)) => f
+                .debug_tuple("Bundle")
+                .field(&self.nested(Value::Bundle(bundle), self.depth))
+                .finish(),
+            Value::Packets(packets) => self.fmt_list(f, packets, Value::Packet),
+            Value::Bundle(_) if self.depth >= limits.depth => f.write_str("OscBundle { .. }"),
+            Value::Bundle(bundle) => f
+                .debug_struct("OscBundle")
+                .field("timetag", &bundle.timetag)
+                .field("content", &self.nested(Value::Packets(&bundle.content), self.depth + 1))
+                .finish(),
+            Value::Message(msg) => f
+                .debug_struct("OscMessage")
+                .field("addr", &self.nested(Value::Str(&msg.addr), self.depth))
+                .field("args", &self.nested(Value::Args(&msg.args), self.depth))
+                .finish(),
+            Value::Arg(OscType::String(s)) => f
+                .debug_tuple("String")
+                .field(&self.nested(Value::Str(s), self.depth))
+                .finish(),
+            Value::Arg(OscType::Blob(blob)) => f
+                .debug_tuple("Blob")
+                .field(&self.nested(Value::Blob(blob), self.depth))
+                .finish(),
+            Value::Arg(OscType::Array(array)) => f
+                .debug_tuple("Array")
+                .field(&self.nested(Value::Array(array), self.depth))
+                .finish(),
+            Value::Arg(arg) => fmt::Debug::fmt(arg, f),
+            Value::Args(args) => self.fmt_list(f, args, Value::Arg),
+            Value::Array(_) if self.depth >= limits.depth => f.write_str("OscArray { .. }"),
+            Value::Array(array) => f
+                .debug_struct("OscArray")
+                .field("content", &self.nested(Value::Args(&array.content), self.depth + 1))
+                .finish(),
+            Value::Str(s) => match s.char_indices().nth(limits.string_chars) {
+                Some((end, _)) => {
+                    let more = s[end..].chars().count();
+                    write!(f, "{:?}{} {} more chars", &s[..end], TRUNCATION_MARKER, more)
+                }
+                None => fmt::Debug::fmt(s, f),
+            },
+            Value::Blob(blob) => {
+                let mut list = f.debug_list();
+                list.entries(blob.iter().take(limits.blob_bytes));
+                if blob.len() > limits.blob_bytes {
+                    let more = blob.len() - limits.blob_bytes;
+                    list.entry(&format_args!("{} {} more", TRUNCATION_MARKER, more));
+                }
+                list.finish()
+            }
+        }
+    }
+}
+
+impl OscPacket {
+    /// Returns a formatter that shows the packet within limits, see [`CompactDebug`].
+    pub fn compact_debug(&self) -> CompactDebug<'_> {
+        CompactDebug::new(Value::Packet(self))
+    }
+}
+
+impl OscBundle {
+    /// Returns a formatter that shows the bundle within limits, see [`CompactDebug`].
+    pub fn compact_debug(&self) -> CompactDebug<'_> {
+        CompactDebug::new(Value::Bundle(self))
+    }
+}
+
+impl OscMessage {
+    /// Returns a formatter that shows the message within limits, see [`CompactDebug`].
+    pub fn compact_debug(&self) -> CompactDebug<'_> {
+        CompactDebug::new(Value::Message(self))
+    }
+}
+
+impl OscType {
+    /// Returns a formatter that shows the argument within limits, see [`CompactDebug`].
+    pub fn compact_debug(&self) -> CompactDebug<'_> {
+        CompactDebug::new(Value::Arg(self))
+    }
+}
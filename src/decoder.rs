@@ -1,41 +1,1706 @@
-use crate::errors::OscError;
-use crate::types::{OscArray, OscBundle, OscColor, OscMessage, OscMidiMessage, OscPacket, OscTime, OscType};
-use crate::alloc::{string::{String, ToString}, vec::Vec};
+use crate::address::DEFAULT_MAX_ADDRESS_LEN;
+use crate::errors::{hex_snippet, OscError, OscErrorKind};
+use crate::intern::{InternedPacket, Interner};
+use crate::profile::Profile;
+use crate::types::{
+    OscArrayRef, OscBundle, OscBundleRef, OscColor, OscMessage, OscMessageRef, OscMidiMessage,
+    OscPacket, OscPacketRef, OscTime, OscType, OscTypeRef,
+};
+use crate::alloc::{
+    borrow::Cow,
+    boxed::Box,
+    format,
+    string::{self, String, ToString},
+    vec::Vec,
+};
 
 use nom::Offset;
 use nom::sequence::terminated;
 use nom::bytes::complete::{take, take_till};
-use nom::combinator::{map, map_parser};
-use nom::multi::many0;
-use nom::number::complete::{be_f32, be_f64, be_i32, be_i64, be_u32};
-use nom::{IResult,combinator::map_res,sequence::tuple};
+use nom::combinator::map;
+use nom::error::{ErrorKind, FromExternalError, ParseError};
+use nom::{IResult,combinator::map_res};
+
+use core::convert::TryFrom;
+use core::{fmt, mem};
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(all(not(feature = "std"), feature = "core-error"))]
+use core::error;
+
+/// Errors of the decoder, which convert into the corresponding [`OscError`] variants.
+///
+/// Errors found at a particular place of a packet are wrapped in [`DecodeError::At`], use
+/// [`root_cause`](DecodeError::root_cause) to get at the underlying error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// A string is not valid UTF-8.
+    StringError(string::FromUtf8Error),
+    /// A parser ran out of input or otherwise failed, contains the kind of the failed parser.
+    ReadError(ErrorKind),
+    /// A parser expected the given char.
+    BadChar(char),
+    BadPacket(&'static str),
+    BadMessage(&'static str),
+    BadString(&'static str),
+    BadArg(String),
+    BadBundle(String),
+    /// A packet starts like a bundle but not with the `#bundle` tag, contains the bytes found
+    /// instead.
+    BadBundleHeader(Vec<u8>),
+    /// A packet of `size` bytes is larger than [`DecoderOptions::max_packet_size`].
+    PacketTooLarge { size: usize, max: usize },
+    /// A blob of `size` bytes is larger than [`DecoderOptions::max_blob_size`].
+    BlobTooLarge { size: usize, max: usize },
+    /// A string of `len` bytes is longer than [`DecoderOptions::max_string_len`].
+    StringTooLong { len: usize, max: usize },
+    /// An address of `len` bytes is longer than [`DecoderOptions::max_address_len`].
+    AddressTooLong { len: usize, max: usize },
+    /// A message has `count` arguments, more than [`DecoderOptions::max_args`].
+    TooManyArgs { count: usize, max: usize },
+    /// Bundles are nested deeper than `max`, see [`DecoderOptions::max_bundle_depth`].
+    BundleTooDeep { max: usize },
+    /// A message in text form could not be parsed, `column` is the position of the offending
+    /// token, starting at 1.
+    BadText { column: usize, msg: String },
+    /// The argument at position `arg_index` of a message requires `expected` bytes but only
+    /// `remaining` bytes are left.
+    Truncated {
+        expected: usize,
+        remaining: usize,
+        arg_index: usize,
+    },
+    /// The length of a packet is not a multiple of 4 bytes.
+    Misaligned { len: usize },
+    /// A message contains a type tag that is not defined by the OSC specification.
+    UnknownTypeTag(char),
+    /// A message contains a type tag that is not allowed by [`DecoderOptions::profile`].
+    UnsupportedTypeTag(char),
+    /// A char argument is not a valid Unicode scalar value, contains the value found instead.
+    InvalidChar(u32),
+    /// The bundle at index `element` of a bundle has the time tag `timetag`, which is earlier
+    /// than the time tag `parent` the enclosing bundle takes effect at, see
+    /// [`DecoderOptions::enforce_bundle_time_monotonicity`].
+    BundleTimeNotMonotonic {
+        element: usize,
+        timetag: OscTime,
+        parent: OscTime,
+    },
+    /// Wraps an error with the position at which it occurred. `offset` is the byte offset, from
+    /// the start of the packet, of the field that failed to decode, and `context` describes that
+    /// field, e.g. `arg 3 of message '/mixer/1/name' in bundle element 2`. Argument and bundle
+    /// element indices start at 0. `snippet` contains the input around the offset as formatted
+    /// by [`hex_snippet`].
+    At {
+        offset: usize,
+        context: String,
+        snippet: String,
+        source: Box<DecodeError>,
+    },
+    /// Wraps the error of a packet in a buffer of packets stored back to back, after `decoded`
+    /// packets of the buffer decoded successfully, see [`decode_all`].
+    AfterPackets {
+        decoded: usize,
+        source: Box<DecodeError>,
+    },
+}
+
+impl DecodeError {
+    /// Returns the underlying error, stripping the positions added by `DecodeError::At` and
+    /// `DecodeError::AfterPackets`.
+    pub fn root_cause(&self) -> &DecodeError {
+        match self {
+            DecodeError::At { source, .. } | DecodeError::AfterPackets { source, .. } => {
+                source.root_cause()
+            }
+            err => err,
+        }
+    }
+
+    /// Returns the byte offset from the start of the packet at which the error occurred, if it
+    /// is known. For errors after other packets, see [`decode_all`], the offset is relative to the
+    /// start of the packet that failed to decode.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            DecodeError::At { offset, .. } => Some(*offset),
+            DecodeError::AfterPackets { source, .. } => source.offset(),
+            _ => None,
+        }
+    }
+
+    /// Returns the kind of the [`OscError`] the error converts into, which is that of the
+    /// [`root_cause`](DecodeError::root_cause).
+    pub fn kind(&self) -> OscErrorKind {
+        match self.root_cause() {
+            DecodeError::StringError(_) => OscErrorKind::StringError,
+            DecodeError::ReadError(_) => OscErrorKind::ReadError,
+            DecodeError::BadChar(_) => OscErrorKind::BadChar,
+            DecodeError::BadPacket(_) => OscErrorKind::BadPacket,
+            DecodeError::BadMessage(_) => OscErrorKind::BadMessage,
+            DecodeError::BadString(_) => OscErrorKind::BadString,
+            DecodeError::BadArg(_) => OscErrorKind::BadArg,
+            DecodeError::BadBundle(_) => OscErrorKind::BadBundle,
+            DecodeError::BadBundleHeader(_) => OscErrorKind::BadBundleHeader,
+            DecodeError::PacketTooLarge { .. } => OscErrorKind::PacketTooLarge,
+            DecodeError::BlobTooLarge { .. } => OscErrorKind::BlobTooLarge,
+            DecodeError::StringTooLong { .. } => OscErrorKind::StringTooLong,
+            DecodeError::AddressTooLong { .. } => OscErrorKind::AddressTooLong,
+            DecodeError::TooManyArgs { .. } => OscErrorKind::TooManyArgs,
+            DecodeError::BundleTooDeep { .. } => OscErrorKind::BundleTooDeep,
+            DecodeError::BadText { .. } => OscErrorKind::BadText,
+            DecodeError::Truncated { .. } => OscErrorKind::Truncated,
+            DecodeError::Misaligned { .. } => OscErrorKind::Misaligned,
+            DecodeError::UnknownTypeTag(_) => OscErrorKind::UnknownTypeTag,
+            DecodeError::UnsupportedTypeTag(_) => OscErrorKind::UnsupportedTypeTag,
+            DecodeError::InvalidChar(_) => OscErrorKind::InvalidChar,
+            DecodeError::BundleTimeNotMonotonic { .. } => OscErrorKind::BundleTimeNotMonotonic,
+            DecodeError::At { .. } | DecodeError::AfterPackets { .. } => {
+                unreachable!("root_cause strips DecodeError::At and DecodeError::AfterPackets")
+            }
+        }
+    }
+
+    /// Returns the stable numeric code of the error's [`kind`](DecodeError::kind), which is the
+    /// same as that of the [`OscError`] it converts into, see [`OscErrorKind::code`].
+    pub fn code(&self) -> u32 {
+        self.kind().code()
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::StringError(err) => write!(f, "reading OSC string as utf-8: {}", err),
+            DecodeError::ReadError(kind) => write!(f, "error reading from buffer: {:?}", kind),
+            DecodeError::BadChar(char) => write!(f, "parser error at char: {:?}", char),
+            DecodeError::BadPacket(msg) => write!(f, "bad OSC packet: {}", msg),
+            DecodeError::BadMessage(msg) => write!(f, "bad OSC message: {}", msg),
+            DecodeError::BadString(msg) => write!(f, "bad OSC string: {}", msg),
+            DecodeError::BadArg(msg) => write!(f, "bad OSC argument: {}", msg),
+            DecodeError::BadBundle(msg) => write!(f, "bad OSC bundle: {}", msg),
+            DecodeError::BadBundleHeader(found) => {
+                write!(f, "bad OSC bundle header: expected \"#bundle\\0\", found {:?}", found)
+            }
+            DecodeError::PacketTooLarge { size, max } => {
+                write!(f, "OSC packet of {} bytes exceeds the limit of {} bytes", size, max)
+            }
+            DecodeError::BlobTooLarge { size, max } => {
+                write!(f, "OSC blob of {} bytes exceeds the limit of {} bytes", size, max)
+            }
+            DecodeError::StringTooLong { len, max } => {
+                write!(f, "OSC string of {} bytes exceeds the limit of {} bytes", len, max)
+            }
+            DecodeError::AddressTooLong { len, max } => {
+                write!(f, "OSC address of {} bytes exceeds the limit of {} bytes", len, max)
+            }
+            DecodeError::TooManyArgs { count, max } => {
+                write!(f, "OSC message with {} arguments exceeds the limit of {}", count, max)
+            }
+            DecodeError::BundleTooDeep { max } => {
+                write!(f, "OSC bundles are nested deeper than the limit of {}", max)
+            }
+            DecodeError::BadText { column, msg } => {
+                write!(f, "bad OSC text at column {}: {}", column, msg)
+            }
+            DecodeError::Truncated {
+                expected,
+                remaining,
+                arg_index,
+            } => write!(
+                f,
+                "OSC argument {} requires {} bytes but only {} are left",
+                arg_index, expected, remaining
+            ),
+            DecodeError::Misaligned { len } => {
+                write!(f, "OSC packet length of {} bytes is not a multiple of 4", len)
+            }
+            DecodeError::UnknownTypeTag(tag) => write!(f, "unknown OSC type tag {:?}", tag),
+            DecodeError::UnsupportedTypeTag(tag) => {
+                write!(f, "OSC type tag {:?} is not supported by the profile", tag)
+            }
+            DecodeError::InvalidChar(value) => {
+                write!(f, "OSC char argument {:#x} is not a valid char", value)
+            }
+            DecodeError::BundleTimeNotMonotonic {
+                element,
+                timetag,
+                parent,
+            } => write!(
+                f,
+                "OSC bundle element {} has the time tag {}.{}, earlier than {}.{} of its \
+                 enclosing bundle",
+                element, timetag.seconds, timetag.fractional, parent.seconds, parent.fractional
+            ),
+            DecodeError::At {
+                offset,
+                context,
+                snippet,
+                source,
+            } => write!(f, "{} at byte {} ({}), near {}", source, offset, context, snippet),
+            DecodeError::AfterPackets { decoded, source } => {
+                write!(f, "{} after {} packets decoded successfully", source, decoded)
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "core-error"))]
+impl error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DecodeError::StringError(ref err) => Some(err),
+            DecodeError::At { ref source, .. } | DecodeError::AfterPackets { ref source, .. } => {
+                Some(source.as_ref())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<I> ParseError<I> for DecodeError {
+    fn from_error_kind(_input: I, kind: ErrorKind) -> Self {
+        Self::ReadError(kind)
+    }
+    fn append(_input: I, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn from_char(_input: I, c: char) -> Self {
+        Self::BadChar(c)
+    }
+
+    fn or(self, _other: Self) -> Self {
+        self
+    }
+}
+
+impl<I> FromExternalError<I, DecodeError> for DecodeError {
+    fn from_external_error(_input: I, _kind: ErrorKind, e: DecodeError) -> Self {
+        e
+    }
+}
+
+impl From<DecodeError> for OscError {
+    fn from(err: DecodeError) -> Self {
+        match err {
+            DecodeError::StringError(err) => OscError::StringError(err),
+            DecodeError::ReadError(kind) => OscError::ReadError(kind),
+            DecodeError::BadChar(c) => OscError::BadChar(c),
+            DecodeError::BadPacket(msg) => OscError::BadPacket(msg),
+            DecodeError::BadMessage(msg) => OscError::BadMessage(msg),
+            DecodeError::BadString(msg) => OscError::BadString(msg),
+            DecodeError::BadArg(msg) => OscError::BadArg(msg),
+            DecodeError::BadBundle(msg) => OscError::BadBundle(msg),
+            DecodeError::BadBundleHeader(found) => OscError::BadBundleHeader(found),
+            DecodeError::PacketTooLarge { size, max } => OscError::PacketTooLarge { size, max },
+            DecodeError::BlobTooLarge { size, max } => OscError::BlobTooLarge { size, max },
+            DecodeError::StringTooLong { len, max } => OscError::StringTooLong { len, max },
+            DecodeError::AddressTooLong { len, max } => OscError::AddressTooLong { len, max },
+            DecodeError::TooManyArgs { count, max } => OscError::TooManyArgs { count, max },
+            DecodeError::BundleTooDeep { max } => OscError::BundleTooDeep { max },
+            DecodeError::BadText { column, msg } => OscError::BadText { column, msg },
+            DecodeError::Truncated {
+                expected,
+                remaining,
+                arg_index,
+            } => OscError::Truncated {
+                expected,
+                remaining,
+                arg_index,
+            },
+            DecodeError::Misaligned { len } => OscError::Misaligned { len },
+            DecodeError::UnknownTypeTag(tag) => OscError::UnknownTypeTag(tag),
+            DecodeError::UnsupportedTypeTag(tag) => OscError::UnsupportedTypeTag(tag),
+            DecodeError::InvalidChar(value) => OscError::InvalidChar(value),
+            DecodeError::BundleTimeNotMonotonic {
+                element,
+                timetag,
+                parent,
+            } => OscError::BundleTimeNotMonotonic {
+                element,
+                timetag,
+                parent,
+            },
+            DecodeError::At {
+                offset,
+                context,
+                snippet,
+                source,
+            } => OscError::At {
+                offset,
+                context,
+                snippet,
+                source: Box::new((*source).into()),
+            },
+            DecodeError::AfterPackets { decoded, source } => OscError::AfterPackets {
+                decoded,
+                source: Box::new((*source).into()),
+            },
+        }
+    }
+}
 
 /// Common MTU size for ethernet
 pub const MTU: usize = 1536;
 
 /// Takes a bytes slice representing a UDP packet and returns the OSC packet as well as a slice of
 /// any bytes remaining after the OSC packet.
-pub fn decode_udp<'a>(msg: &'a [u8]) -> IResult<&'a [u8], OscPacket, OscError> {
-    decode_packet(msg, msg)
+pub fn decode_udp(msg: &[u8]) -> IResult<&[u8], OscPacket, DecodeError> {
+    decode_packet(msg, msg, &UNLIMITED)
+        .map(|(remainder, osc_packet)| (remainder, osc_packet.to_owned()))
+}
+
+/// Like [`decode_udp`] but without copying: the address as well as all string and blob arguments
+/// of the returned packet borrow from `msg`. Use [`OscPacketRef::to_owned`] to turn the result
+/// into an [`OscPacket`] once it has to outlive the buffer.
+///
+/// # Example
+///
+/// ```
+/// use rosc::{decoder, encoder, OscMessage, OscPacket, OscPacketRef, OscTypeRef};
+///
+/// let packet = OscPacket::Message(OscMessage {
+///     addr: "/blob".to_string(),
+///     args: vec![vec![1u8, 2, 3].into()],
+/// });
+/// let buf = encoder::encode(&packet).unwrap();
+///
+/// let (_, packet_ref) = decoder::decode_ref(&buf).unwrap();
+/// match packet_ref {
+///     OscPacketRef::Message(ref msg) => {
+///         assert_eq!(msg.addr, "/blob");
+///         assert_eq!(msg.args, vec![OscTypeRef::Blob(&[1, 2, 3])]);
+///     }
+///     _ => unreachable!(),
+/// }
+/// assert_eq!(packet_ref.to_owned(), packet);
+/// ```
+pub fn decode_ref(msg: &[u8]) -> IResult<&[u8], OscPacketRef<'_>, DecodeError> {
+    decode_packet(msg, msg, &UNLIMITED)
+}
+
+/// Decodes a packet without building an [`OscPacket`]: the contents of `msg` are instead reported
+/// to the given [`OscVisitor`] in the order they appear. All strings and blobs passed to the
+/// visitor borrow from `msg`, and a successful decode performs no heap allocations, which makes
+/// this suitable for memory constrained targets.
+///
+/// Unlike [`decode_udp`], `msg` has to contain exactly one packet. If an error is returned the
+/// visitor may already have been called for the part of the packet preceding the error.
+///
+/// # Example
+///
+/// ```
+/// use rosc::decoder::{self, OscVisitor};
+/// use rosc::{encoder, OscMessage, OscPacket};
+///
+/// #[derive(Default)]
+/// struct Sum(i32);
+///
+/// impl<'a> OscVisitor<'a> for Sum {
+///     fn arg_int(&mut self, x: i32) {
+///         self.0 += x;
+///     }
+/// }
+///
+/// let packet = OscPacket::Message(OscMessage {
+///     addr: "/sum".to_string(),
+///     args: vec![1.into(), 2.into(), 3.into()],
+/// });
+/// let buf = encoder::encode(&packet).unwrap();
+///
+/// let mut sum = Sum::default();
+/// decoder::decode_visit(&buf, &mut sum).unwrap();
+/// assert_eq!(sum.0, 6);
+/// ```
+pub fn decode_visit<'a, V: OscVisitor<'a> + ?Sized>(
+    msg: &'a [u8],
+    visitor: &mut V,
+) -> Result<(), DecodeError> {
+    if !msg.len().is_multiple_of(4) {
+        return Err(DecodeError::Misaligned { len: msg.len() });
+    }
+    complete_packet(visit_packet(msg, msg, &UNLIMITED, 0, OscTime::IMMEDIATE, visitor))
+}
+
+/// Decodes a packet that has to fill all of `msg` while enforcing the limits set in `options`. See
+/// [`DecoderOptions`] for the available limits and their defaults.
+///
+/// # Example
+///
+/// ```
+/// use rosc::decoder::{self, DecodeError, DecoderOptions};
+/// use rosc::{encoder, OscMessage, OscPacket};
+///
+/// let packet = OscPacket::Message(OscMessage {
+///     addr: "/blob".to_string(),
+///     args: vec![vec![0u8; 1024].into()],
+/// });
+/// let buf = encoder::encode(&packet).unwrap();
+///
+/// let options = DecoderOptions {
+///     max_blob_size: 512,
+///     ..Default::default()
+/// };
+/// match decoder::decode_with_options(&buf, &options).as_ref().map_err(DecodeError::root_cause) {
+///     Err(DecodeError::BlobTooLarge { size: 1024, max: 512 }) => {}
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// ```
+pub fn decode_with_options(msg: &[u8], options: &DecoderOptions) -> Result<OscPacket, DecodeError> {
+    decode_ref_with_options(msg, options).map(|packet| packet.to_owned())
+}
+
+/// Decodes a packet like [`decode_with_options`], taking the addresses of its messages from the
+/// cache of `interner`. Decoding the same addresses over and over then doesn't allocate them
+/// again, see [`Interner`].
+///
+/// # Example
+///
+/// ```
+/// use rosc::decoder::{self, DecoderOptions};
+/// use rosc::intern::{InternedPacket, Interner};
+/// use rosc::{encoder, OscMessage, OscPacket};
+///
+/// let buf = encoder::encode(&OscPacket::Message(OscMessage::from("/fader"))).unwrap();
+/// let mut interner = Interner::default();
+/// let options = DecoderOptions::default();
+/// match decoder::decode_with_interner(&buf, &options, &mut interner).unwrap() {
+///     InternedPacket::Message(msg) => assert_eq!(&*msg.addr, "/fader"),
+///     InternedPacket::Bundle(_) => unreachable!(),
+/// }
+/// assert_eq!(interner.len(), 1);
+/// ```
+pub fn decode_with_interner(
+    msg: &[u8],
+    options: &DecoderOptions,
+    interner: &mut Interner,
+) -> Result<InternedPacket, DecodeError> {
+    decode_ref_with_options(msg, options).map(|packet| interner.intern_packet(&packet))
+}
+
+fn decode_ref_with_options<'a>(
+    msg: &'a [u8],
+    options: &DecoderOptions,
+) -> Result<OscPacketRef<'a>, DecodeError> {
+    build_packet(msg, options)?
+        .packet
+        .ok_or(DecodeError::BadPacket("Empty packet."))
+}
+
+/// Decodes the bundle in `msg` like [`decode_with_options`], returning the elements that failed
+/// to decode along with the bundle. With
+/// [`bundle_error_policy`](DecoderOptions::bundle_error_policy) set to
+/// [`BundleErrorPolicy::SkipElement`], a corrupt element only costs the element itself, while
+/// the other elements are still decoded. Fails if `msg` is not a bundle, or if the bundle
+/// itself can't be decoded, e.g. if its time tag is truncated.
+///
+/// # Example
+///
+/// ```
+/// use rosc::decoder::{self, BundleErrorPolicy, DecodeError, DecoderOptions};
+/// use rosc::{encoder, OscBundle, OscMessage, OscPacket};
+///
+/// let bundle = OscBundle {
+///     timetag: (1, 0).into(),
+///     content: vec![
+///         OscPacket::Message(OscMessage::from("/first")),
+///         OscPacket::Message(OscMessage::from("/second")),
+///     ],
+/// };
+/// let mut buf = encoder::encode(&OscPacket::Bundle(bundle)).unwrap();
+/// // Corrupt the type tags of the first message
+/// buf[28] = b'x';
+///
+/// let options = DecoderOptions {
+///     bundle_error_policy: BundleErrorPolicy::SkipElement,
+///     ..Default::default()
+/// };
+/// let decoded = decoder::decode_bundle(&buf, &options).unwrap();
+/// assert_eq!(decoded.bundle.content, vec![OscPacket::Message(OscMessage::from("/second"))]);
+/// assert_eq!(decoded.errors.len(), 1);
+/// assert_eq!(decoded.errors[0].0, 0);
+/// assert!(matches!(decoded.errors[0].1.root_cause(), DecodeError::BadMessage(_)));
+/// ```
+pub fn decode_bundle(msg: &[u8], options: &DecoderOptions) -> Result<DecodedBundle, DecodeError> {
+    if !is_bundle(msg) {
+        return Err(error_at(
+            msg,
+            0,
+            "bundle header".to_string(),
+            DecodeError::BadBundleHeader(msg[..msg.len().min(8)].to_vec()),
+        ));
+    }
+    let builder = build_packet(msg, options)?;
+    match builder.packet {
+        Some(OscPacketRef::Bundle(bundle)) => Ok(DecodedBundle {
+            bundle: bundle.to_owned(),
+            errors: builder.errors,
+        }),
+        _ => Err(DecodeError::BadPacket("Empty packet.")),
+    }
+}
+
+/// A bundle decoded by [`decode_bundle`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedBundle {
+    /// The bundle, without the elements that failed to decode.
+    pub bundle: OscBundle,
+    /// The index of each element of the bundle that failed to decode, along with its error, in
+    /// the order of the elements. For an element of a nested bundle, the index is that of the
+    /// element of the outermost bundle containing it, while the context of the error tells the
+    /// path to the element, see [`DecodeError::At`]. Elements following one whose size is invalid
+    /// can't be found and are reported as a single error of the size field. Empty unless
+    /// [`bundle_error_policy`](DecoderOptions::bundle_error_policy) is
+    /// [`BundleErrorPolicy::SkipElement`].
+    pub errors: Vec<(usize, DecodeError)>,
+}
+
+/// Checks the size and alignment of `msg` and decodes it with a [`PacketBuilder`].
+fn build_packet<'a>(
+    msg: &'a [u8],
+    options: &DecoderOptions,
+) -> Result<PacketBuilder<'a>, DecodeError> {
+    if msg.len() > options.max_packet_size {
+        return Err(DecodeError::PacketTooLarge {
+            size: msg.len(),
+            max: options.max_packet_size,
+        });
+    }
+    let trailing = msg.len() % 4;
+    let msg = match trailing {
+        0 => msg,
+        _ if options.allow_trailing_bytes => &msg[..msg.len() - trailing],
+        _ => return Err(DecodeError::Misaligned { len: msg.len() }),
+    };
+    let mut builder = PacketBuilder::default();
+    complete_packet(visit_packet(msg, msg, options, 0, OscTime::IMMEDIATE, &mut builder))?;
+    Ok(builder)
+}
+
+/// Returns an iterator over the packets stored back to back, without any framing, in `buf`. Each
+/// packet is decoded with the given `options`.
+///
+/// Iteration ends once `buf` is exhausted or after the first error, which is yielded as well. Note
+/// that the elements of a bundle extend up to the end of `buf`, so any bundle has to be the last
+/// packet in the buffer.
+///
+/// # Example
+///
+/// ```
+/// use rosc::decoder::{self, DecoderOptions};
+/// use rosc::{encoder, OscMessage, OscPacket};
+///
+/// let mut buf = Vec::new();
+/// for addr in &["/first", "/second"] {
+///     let packet = OscPacket::Message(OscMessage::from(*addr));
+///     buf.extend(encoder::encode(&packet).unwrap());
+/// }
+///
+/// let options = DecoderOptions::default();
+/// let packets: Result<Vec<OscPacket>, _> = decoder::iter_packets(&buf, &options).collect();
+/// assert_eq!(packets.unwrap().len(), 2);
+/// ```
+pub fn iter_packets<'a>(buf: &'a [u8], options: &'a DecoderOptions) -> PacketIter<'a> {
+    PacketIter {
+        buf,
+        input: buf,
+        options,
+    }
+}
+
+/// Decodes all packets stored back to back in `buf`, like [`iter_packets`], e.g. for devices that
+/// send several messages in a single datagram without wrapping them in a bundle.
+///
+/// If a packet fails to decode after others did, its error is wrapped in an
+/// [`DecodeError::AfterPackets`] telling how many packets were decoded before it, e.g. for garbage
+/// trailing the last packet. Up to 3 trailing bytes are ignored with
+/// [`allow_trailing_bytes`](DecoderOptions::allow_trailing_bytes).
+///
+/// # Example
+///
+/// ```
+/// use rosc::decoder::{self, DecodeError, DecoderOptions};
+/// use rosc::{encoder, OscMessage, OscPacket};
+///
+/// let mut buf = Vec::new();
+/// for addr in &["/first", "/second"] {
+///     let packet = OscPacket::Message(OscMessage::from(*addr));
+///     buf.extend(encoder::encode(&packet).unwrap());
+/// }
+/// let options = DecoderOptions::default();
+/// assert_eq!(decoder::decode_all(&buf, &options).unwrap().len(), 2);
+///
+/// buf.extend(b"junk");
+/// match decoder::decode_all(&buf, &options) {
+///     Err(DecodeError::AfterPackets { decoded: 2, .. }) => {}
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// ```
+pub fn decode_all(buf: &[u8], options: &DecoderOptions) -> Result<Vec<OscPacket>, DecodeError> {
+    let buf = match options.allow_trailing_bytes {
+        true => &buf[..buf.len() - buf.len() % 4],
+        false => buf,
+    };
+    let mut packets = Vec::new();
+    for packet in iter_packets(buf, options) {
+        match packet {
+            Ok(packet) => packets.push(packet),
+            Err(err) if packets.is_empty() => return Err(err),
+            Err(err) => {
+                return Err(DecodeError::AfterPackets {
+                    decoded: packets.len(),
+                    source: Box::new(err),
+                })
+            }
+        }
+    }
+    // Like the other decoders, fail for an empty buffer instead of returning no packets
+    if packets.is_empty() {
+        return Err(DecodeError::BadPacket("Empty packet."));
+    }
+    Ok(packets)
+}
+
+/// Iterator over concatenated packets, see [`iter_packets`].
+#[derive(Clone, Debug)]
+pub struct PacketIter<'a> {
+    buf: &'a [u8],
+    input: &'a [u8],
+    options: &'a DecoderOptions,
+}
+
+impl<'a> Iterator for PacketIter<'a> {
+    type Item = Result<OscPacket, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() {
+            return None;
+        }
+
+        let input = self.input;
+        // Stop after this packet unless it turns out to be valid
+        self.input = &[];
+        let result = match decode_packet(input, self.buf, self.options) {
+            Ok((remainder, packet)) => {
+                let size = input.len() - remainder.len();
+                if size > self.options.max_packet_size {
+                    Err(DecodeError::PacketTooLarge {
+                        size,
+                        max: self.options.max_packet_size,
+                    })
+                } else {
+                    self.input = remainder;
+                    Ok(packet.to_owned())
+                }
+            }
+            Err(err) => Err(into_decode_error(err)),
+        };
+        Some(result)
+    }
+}
+
+/// Returns whether `buf` starts with the tag of an OSC bundle.
+pub fn is_bundle(buf: &[u8]) -> bool {
+    buf.starts_with(b"#bundle\0")
+}
+
+/// Returns the address of the message in `buf` without decoding the rest of the message. Only the
+/// address itself is validated, so an `Ok` result does not guarantee that the whole message can be
+/// decoded. Bundles have no address and result in an error, use [`is_bundle`] to tell them apart.
+///
+/// # Example
+///
+/// ```
+/// use rosc::{decoder, encoder, OscMessage, OscPacket};
+///
+/// let packet = OscPacket::Message(OscMessage {
+///     addr: "/mixer/1/fader".to_string(),
+///     args: vec![0.5f32.into()],
+/// });
+/// let buf = encoder::encode(&packet).unwrap();
+/// assert_eq!(decoder::peek_address(&buf).unwrap(), "/mixer/1/fader");
+/// assert_eq!(decoder::peek_type_tags(&buf).unwrap(), ",f");
+/// ```
+pub fn peek_address(buf: &[u8]) -> Result<&str, DecodeError> {
+    peek_message(buf).map(|(_, addr)| addr)
+}
+
+/// Returns the type tag string, including the leading `,`, of the message in `buf` without
+/// decoding its arguments. Only the address and type tags are validated.
+pub fn peek_type_tags(buf: &[u8]) -> Result<&str, DecodeError> {
+    let (input, addr) = peek_message(buf)?;
+    let (_, type_tags) = with_context(
+        read_osc_string(input, buf, &UNLIMITED),
+        buf,
+        buf.offset(input),
+        || format!("type tags of message '{}'", addr),
+    )
+    .map_err(into_decode_error)?;
+    if !type_tags.starts_with(',') {
+        return Err(error_at(
+            buf,
+            buf.offset(input),
+            format!("type tags of message '{}'", addr),
+            DecodeError::BadMessage("Type tag string does not start with ','"),
+        ));
+    }
+    Ok(type_tags)
+}
+
+/// Reads the address of the message in `buf`, failing like the decoder does for anything else.
+fn peek_message(buf: &[u8]) -> Result<(&[u8], &str), DecodeError> {
+    let at_address = |source| error_at(buf, 0, "address".to_string(), source);
+    if buf.is_empty() {
+        return Err(error_at(
+            buf,
+            0,
+            "packet".to_string(),
+            DecodeError::BadPacket("Empty packet."),
+        ));
+    }
+    check_bundle_header(buf)
+        .map_err(|err| error_at(buf, 0, "bundle header".to_string(), err))?;
+    let (input, addr) = with_context(read_osc_string(buf, buf, &UNLIMITED), buf, 0, || {
+        "address".to_string()
+    })
+    .map_err(into_decode_error)?;
+    if addr == "#bundle" {
+        return Err(at_address(DecodeError::BadPacket("Bundles have no address")));
+    }
+    if !addr.starts_with('/') {
+        return Err(at_address(DecodeError::BadPacket(
+            "Invalid message address or bundle tag",
+        )));
+    }
+    Ok((input, addr))
+}
+
+/// The kind of a bundle element returned by [`RawBundleIter`], as told by its first byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElementKind {
+    /// The element starts with `/`, i.e. the address of a message.
+    Message,
+    /// The element starts with `#`, i.e. the tag of a nested bundle.
+    Bundle,
+}
+
+/// Returns an iterator over the raw bytes of the elements of the bundle in `buf`, e.g. to forward
+/// them without decoding them. Only the bundle tag, the time tag and the size of each element are
+/// validated, along with the first byte of each element to tell messages from nested bundles.
+/// The elements can in turn be passed to [`peek_address`] or, for nested bundles, to
+/// `iter_bundle_elements` again, in which case error offsets refer to the nested bundle.
+///
+/// Element sizes that are not a multiple of 4 or exceed the bundle are reported with the offset
+/// of the size field, and iteration ends after the first error.
+///
+/// # Example
+///
+/// ```
+/// use rosc::decoder::{self, ElementKind};
+/// use rosc::{encoder, OscBundle, OscMessage, OscPacket};
+///
+/// let packet = OscPacket::Bundle(OscBundle {
+///     timetag: (1, 0).into(),
+///     content: vec![
+///         OscPacket::Message(OscMessage::from("/mixer/1/mute")),
+///         OscPacket::Bundle(OscBundle {
+///             timetag: (2, 0).into(),
+///             content: vec![OscPacket::Message(OscMessage::from("/transport/play"))],
+///         }),
+///     ],
+/// });
+/// let buf = encoder::encode(&packet).unwrap();
+/// let elements = decoder::iter_bundle_elements(&buf).unwrap();
+/// assert_eq!(elements.timetag(), (1, 0).into());
+/// for element in elements {
+///     match element.unwrap() {
+///         (element, ElementKind::Message) => {
+///             assert_eq!(decoder::peek_address(element).unwrap(), "/mixer/1/mute")
+///         }
+///         (element, ElementKind::Bundle) => {
+///             assert_eq!(decoder::iter_bundle_elements(element).unwrap().count(), 1)
+///         }
+///     }
+/// }
+/// ```
+pub fn iter_bundle_elements(buf: &[u8]) -> Result<RawBundleIter<'_>, DecodeError> {
+    if !is_bundle(buf) {
+        return Err(error_at(
+            buf,
+            0,
+            "bundle header".to_string(),
+            DecodeError::BadBundleHeader(buf[..buf.len().min(8)].to_vec()),
+        ));
+    }
+    let input = &buf[8..];
+    let (input, timetag) = with_context(read_time_tag(input), buf, 8, || {
+        "time tag of bundle".to_string()
+    })
+    .map_err(into_decode_error)?;
+    Ok(RawBundleIter {
+        timetag,
+        input,
+        original_input: buf,
+        index: 0,
+    })
+}
+
+/// Iterator over the raw elements of a bundle, see [`iter_bundle_elements`].
+#[derive(Clone, Debug)]
+pub struct RawBundleIter<'a> {
+    timetag: OscTime,
+    input: &'a [u8],
+    original_input: &'a [u8],
+    index: usize,
+}
+
+impl<'a> RawBundleIter<'a> {
+    /// Returns the time tag of the bundle.
+    pub fn timetag(&self) -> OscTime {
+        self.timetag
+    }
+
+    fn next_element(&mut self) -> Result<(&'a [u8], ElementKind), DecodeError> {
+        let offset = self.original_input.offset(self.input);
+        let index = self.index;
+        let original_input = self.original_input;
+        let size_error = |source| {
+            error_at(original_input, offset, format!("size of bundle element {}", index), source)
+        };
+
+        let (input, elem_size) = read_u32(self.input)
+            .map_err(|err| size_error(into_decode_error(err)))?;
+        let elem_size = check_element_size(elem_size, input.len()).map_err(size_error)?;
+        let (element, input) = input.split_at(elem_size);
+        self.input = input;
+        self.index += 1;
+
+        match element[0] {
+            b'/' => Ok((element, ElementKind::Message)),
+            b'#' => Ok((element, ElementKind::Bundle)),
+            _ => Err(error_at(
+                original_input,
+                offset + 4,
+                format!("address in bundle element {}", index),
+                DecodeError::BadPacket("Invalid message address or bundle tag"),
+            )),
+        }
+    }
+}
+
+impl<'a> Iterator for RawBundleIter<'a> {
+    type Item = Result<(&'a [u8], ElementKind), DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() {
+            return None;
+        }
+        let result = self.next_element();
+        if result.is_err() {
+            self.input = &[];
+        }
+        Some(result)
+    }
+}
+
+/// Decodes the header of the packet in `buf` while leaving its contents undecoded: for messages,
+/// only the address and type tags are read, while their arguments are decoded when iterating
+/// over [`LazyMessage::args`]. The elements of bundles are likewise decoded one by one by
+/// [`LazyBundle::elements`]. This makes inspecting and dropping packets cheap, while the packets
+/// that are kept can be fully decoded with `to_owned`.
+///
+/// `buf` has to be a multiple of 4 bytes long. Apart from the nesting depth of bundles, which is
+/// capped by [`MAX_BUNDLE_DEPTH`], nothing is limited, see [`decode_lazy_with_options`].
+///
+/// # Example
+///
+/// ```
+/// use rosc::{decoder, encoder, OscMessage, OscPacket, OscTypeRef};
+/// use rosc::decoder::LazyPacket;
+///
+/// let packet = OscPacket::Message(OscMessage {
+///     addr: "/mixer/1/fader".to_string(),
+///     args: vec![0.5f32.into(), vec![0u8; 1024].into()],
+/// });
+/// let buf = encoder::encode(&packet).unwrap();
+///
+/// match decoder::decode_lazy(&buf).unwrap() {
+///     LazyPacket::Message(msg) => {
+///         assert_eq!(msg.addr, "/mixer/1/fader");
+///         assert_eq!(msg.type_tags, ",fb");
+///         // Only the first argument gets decoded
+///         let first = msg.args().next().unwrap().unwrap();
+///         assert_eq!(first, OscTypeRef::Float(0.5));
+///     }
+///     LazyPacket::Bundle(_) => unreachable!(),
+/// }
+/// ```
+pub fn decode_lazy(buf: &[u8]) -> Result<LazyPacket<'_>, DecodeError> {
+    decode_lazy_with_options(buf, &UNLIMITED)
+}
+
+/// Decodes the header of the packet in `buf` like [`decode_lazy`] while enforcing the limits set
+/// in `options`. Limits on the arguments and elements, such as
+/// [`max_blob_size`](DecoderOptions::max_blob_size), are enforced as they are decoded.
+///
+/// The options that deal with strings, chars, extension tags, trailing bytes and `(0, 0)` time
+/// tags apply as well. Lenient type tags, unprefixed addresses, lossy addresses, the bundle time
+/// monotonicity, the bundle error policy and the profile are not supported by the lazy decoder
+/// and thus ignored.
+///
+/// # Example
+///
+/// ```
+/// use rosc::decoder::{self, DecodeError, DecoderOptions};
+/// use rosc::{encoder, OscMessage, OscPacket};
+///
+/// let packet = OscPacket::Message(OscMessage {
+///     addr: "/blob".to_string(),
+///     args: vec![vec![0u8; 1024].into()],
+/// });
+/// let buf = encoder::encode(&packet).unwrap();
+///
+/// let options = DecoderOptions {
+///     max_blob_size: 512,
+///     ..Default::default()
+/// };
+/// let packet = decoder::decode_lazy_with_options(&buf, &options).unwrap();
+/// match packet.to_owned().as_ref().map_err(DecodeError::root_cause) {
+///     Err(DecodeError::BlobTooLarge { size: 1024, max: 512 }) => {}
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// ```
+pub fn decode_lazy_with_options<'a>(
+    buf: &'a [u8],
+    options: &'a DecoderOptions,
+) -> Result<LazyPacket<'a>, DecodeError> {
+    if buf.len() > options.max_packet_size {
+        return Err(DecodeError::PacketTooLarge {
+            size: buf.len(),
+            max: options.max_packet_size,
+        });
+    }
+    let trailing = buf.len() % 4;
+    let buf = match trailing {
+        0 => buf,
+        _ if options.allow_trailing_bytes => &buf[..buf.len() - trailing],
+        _ => return Err(DecodeError::Misaligned { len: buf.len() }),
+    };
+    read_lazy_packet(buf, buf, options, 0)
+}
+
+/// A packet whose contents are decoded on demand, see [`decode_lazy`].
+#[derive(Clone, Debug)]
+pub enum LazyPacket<'a> {
+    Message(LazyMessage<'a>),
+    Bundle(LazyBundle<'a>),
+}
+
+impl<'a> LazyPacket<'a> {
+    /// Decodes the remainder of the packet.
+    pub fn to_owned(&self) -> Result<OscPacket, DecodeError> {
+        match *self {
+            LazyPacket::Message(ref msg) => msg.to_owned().map(OscPacket::Message),
+            LazyPacket::Bundle(ref bundle) => bundle.to_owned().map(OscPacket::Bundle),
+        }
+    }
+}
+
+/// A message whose arguments are decoded on demand.
+#[derive(Clone, Debug)]
+pub struct LazyMessage<'a> {
+    pub addr: &'a str,
+    /// The raw type tag string, including the leading `,`.
+    pub type_tags: &'a str,
+    args: &'a [u8],
+    original_input: &'a [u8],
+    options: &'a DecoderOptions,
+}
+
+impl<'a> LazyMessage<'a> {
+    /// Returns an iterator decoding the arguments of the message. Iteration ends after the first
+    /// error.
+    pub fn args(&self) -> LazyArgs<'a> {
+        LazyArgs {
+            addr: self.addr,
+            type_tags: self.type_tags[1..].chars(),
+            type_tags_offset: self.original_input.offset(self.type_tags.as_bytes()),
+            input: self.args,
+            original_input: self.original_input,
+            options: self.options,
+            index: 0,
+            done: false,
+        }
+    }
+
+    /// Decodes all arguments into an owned [`OscMessage`].
+    pub fn to_owned(&self) -> Result<OscMessage, DecodeError> {
+        Ok(OscMessage {
+            addr: self.addr.to_string(),
+            args: self
+                .args()
+                .map(|arg| arg.map(|arg| arg.to_owned()))
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
+/// Iterator over the arguments of a [`LazyMessage`].
+#[derive(Clone, Debug)]
+pub struct LazyArgs<'a> {
+    addr: &'a str,
+    type_tags: core::str::Chars<'a>,
+    type_tags_offset: usize,
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &'a DecoderOptions,
+    index: usize,
+    done: bool,
+}
+
+impl<'a> LazyArgs<'a> {
+    fn next_arg(&mut self) -> Result<Option<OscTypeRef<'a>>, DecodeError> {
+        // Contents of the arrays enclosing the current argument
+        let mut arrays: Vec<Vec<OscTypeRef<'a>>> = Vec::new();
+        loop {
+            let arg = match self.type_tags.next() {
+                None if arrays.is_empty() => return Ok(None),
+                None => {
+                    return Err(self.type_tags_error(DecodeError::BadMessage(
+                        "Encountered [ without matching ]",
+                    )))
+                }
+                Some('[') if arrays.len() == MAX_ARRAY_DEPTH => {
+                    return Err(self.type_tags_error(DecodeError::BadMessage(
+                        "Arrays are nested deeper than MAX_ARRAY_DEPTH",
+                    )))
+                }
+                Some('[') => {
+                    arrays.push(Vec::new());
+                    continue;
+                }
+                Some(']') => match arrays.pop() {
+                    Some(content) => OscTypeRef::Array(OscArrayRef { content }),
+                    None => {
+                        return Err(self.type_tags_error(DecodeError::BadMessage(
+                            "Encountered ] outside array",
+                        )))
+                    }
+                },
+                Some(tag) => {
+                    let (remainder, arg) = with_context(
+                        read_osc_arg(
+                            self.input,
+                            self.original_input,
+                            self.options,
+                            tag,
+                            self.index,
+                        ),
+                        self.original_input,
+                        self.original_input.offset(self.input),
+                        || format!("arg {} of message '{}'", self.index, self.addr),
+                    )
+                    .map_err(into_decode_error)?;
+                    self.input = remainder;
+                    self.index += 1;
+                    arg
+                }
+            };
+            match arrays.last_mut() {
+                Some(array) => array.push(arg),
+                None => return Ok(Some(arg)),
+            }
+        }
+    }
+
+    fn type_tags_error(&self, source: DecodeError) -> DecodeError {
+        error_at(
+            self.original_input,
+            self.type_tags_offset,
+            format!("type tags of message '{}'", self.addr),
+            source,
+        )
+    }
+}
+
+impl<'a> Iterator for LazyArgs<'a> {
+    type Item = Result<OscTypeRef<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_arg() {
+            Ok(Some(arg)) => Some(Ok(arg)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// A bundle whose elements are decoded on demand.
+#[derive(Clone, Debug)]
+pub struct LazyBundle<'a> {
+    pub timetag: OscTime,
+    elements: &'a [u8],
+    original_input: &'a [u8],
+    options: &'a DecoderOptions,
+    depth: usize,
+}
+
+impl<'a> LazyBundle<'a> {
+    /// Returns an iterator decoding the headers of the bundle's elements. Iteration ends after the
+    /// first error.
+    pub fn elements(&self) -> LazyElements<'a> {
+        LazyElements {
+            input: self.elements,
+            original_input: self.original_input,
+            options: self.options,
+            index: 0,
+            depth: self.depth,
+        }
+    }
+
+    /// Decodes all elements into an owned [`OscBundle`].
+    pub fn to_owned(&self) -> Result<OscBundle, DecodeError> {
+        Ok(OscBundle {
+            timetag: self.timetag,
+            content: self
+                .elements()
+                .map(|element| element.and_then(|element| element.to_owned()))
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
+/// Iterator over the elements of a [`LazyBundle`].
+#[derive(Clone, Debug)]
+pub struct LazyElements<'a> {
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &'a DecoderOptions,
+    index: usize,
+    // Nesting depth of the bundle containing the elements
+    depth: usize,
+}
+
+impl<'a> LazyElements<'a> {
+    fn next_element(&mut self) -> Result<LazyPacket<'a>, DecodeError> {
+        let offset = self.original_input.offset(self.input);
+        let index = self.index;
+        let original_input = self.original_input;
+        let size_error = |source| {
+            error_at(original_input, offset, format!("size of bundle element {}", index), source)
+        };
+
+        let (input, elem_size) = read_u32(self.input)
+            .map_err(|err| size_error(into_decode_error(err)))?;
+        let elem_size = check_element_size(elem_size, input.len()).map_err(size_error)?;
+        let (element, input) = input.split_at(elem_size);
+        self.input = input;
+        self.index += 1;
+
+        let element = read_lazy_packet(element, self.original_input, self.options, self.depth);
+        element.map_err(|err| match err {
+            DecodeError::At {
+                offset,
+                context,
+                snippet,
+                source,
+            } => DecodeError::At {
+                offset,
+                context: prepend_bundle_element(context, index),
+                snippet,
+                source,
+            },
+            err => err,
+        })
+    }
+}
+
+impl<'a> Iterator for LazyElements<'a> {
+    type Item = Result<LazyPacket<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() {
+            return None;
+        }
+        let result = self.next_element();
+        if result.is_err() {
+            self.input = &[];
+        }
+        Some(result)
+    }
+}
+
+/// Reads the header of the packet at the start of `input`, `depth` is the number of bundles
+/// enclosing it.
+fn read_lazy_packet<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &'a DecoderOptions,
+    depth: usize,
+) -> Result<LazyPacket<'a>, DecodeError> {
+    let offset = original_input.offset(input);
+    let at = |context: &str, source| error_at(original_input, offset, context.to_string(), source);
+    if input.is_empty() {
+        return Err(at("packet", DecodeError::BadPacket("Empty packet.")));
+    }
+    check_bundle_header(input).map_err(|err| at("bundle header", err))?;
+
+    let (input, addr) = with_context(
+        check_address_len(read_osc_string(input, original_input, options), options),
+        original_input,
+        offset,
+        || "address".to_string(),
+    )
+    .map_err(into_decode_error)?;
+
+    if addr.starts_with('/') {
+        let offset = original_input.offset(input);
+        let type_tags_context = || format!("type tags of message '{}'", addr);
+        let (args, type_tags) = with_context(
+            read_osc_string(input, original_input, options),
+            original_input,
+            offset,
+            type_tags_context,
+        )
+        .map_err(into_decode_error)?;
+        if !type_tags.starts_with(',') {
+            return Err(error_at(
+                original_input,
+                offset,
+                type_tags_context(),
+                DecodeError::BadMessage("Type tag string does not start with ','"),
+            ));
+        }
+        let arg_count = type_tags[1..].chars().filter(|&tag| tag != '[' && tag != ']').count();
+        if arg_count > options.max_args {
+            return Err(error_at(
+                original_input,
+                offset,
+                type_tags_context(),
+                DecodeError::TooManyArgs {
+                    count: arg_count,
+                    max: options.max_args,
+                },
+            ));
+        }
+        Ok(LazyPacket::Message(LazyMessage {
+            addr,
+            type_tags,
+            args,
+            original_input,
+            options,
+        }))
+    } else if addr == "#bundle" {
+        // Limit the depth as decoding nested bundles with `to_owned` recurses
+        let max_depth = options.max_bundle_depth.min(MAX_BUNDLE_DEPTH);
+        if depth >= max_depth {
+            return Err(at("bundle", DecodeError::BundleTooDeep { max: max_depth }));
+        }
+        let (elements, mut timetag) = with_context(
+            read_time_tag(input),
+            original_input,
+            original_input.offset(input),
+            || "time tag of bundle".to_string(),
+        )
+        .map_err(into_decode_error)?;
+        if options.zero_timetag_is_immediate && timetag == OscTime::from((0, 0)) {
+            timetag = OscTime::IMMEDIATE;
+        }
+        Ok(LazyPacket::Bundle(LazyBundle {
+            timetag,
+            elements,
+            original_input,
+            options,
+            depth: depth + 1,
+        }))
+    } else {
+        Err(at(
+            "address",
+            DecodeError::BadPacket("Invalid message address or bundle tag"),
+        ))
+    }
+}
+
+/// Decodes the packet filling all of `buf` into `out`, reusing the allocations of the strings,
+/// blobs and argument lists `out` already holds where the decoded packet has the same shape.
+/// Decoding messages of the same layout over and over thus performs no allocations once `out`
+/// has grown large enough, except for array arguments.
+///
+/// If an error is returned, `out` may be partially overwritten.
+///
+/// # Example
+///
+/// ```
+/// use rosc::{decoder, encoder, OscMessage, OscPacket};
+///
+/// let mut packet = OscPacket::Message(OscMessage::from("/"));
+/// for i in 0..3 {
+///     let buf = encoder::encode(&OscPacket::Message(OscMessage {
+///         addr: format!("/channel/{}", i),
+///         args: vec!["name".into()],
+///     }))
+///     .unwrap();
+///     decoder::decode_into(&buf, &mut packet).unwrap();
+/// }
+/// match packet {
+///     OscPacket::Message(msg) => assert_eq!(msg.addr, "/channel/2"),
+///     OscPacket::Bundle(_) => unreachable!(),
+/// }
+/// ```
+pub fn decode_into(buf: &[u8], out: &mut OscPacket) -> Result<(), DecodeError> {
+    let remainder = fill_packet(&decode_lazy(buf)?, out)?;
+    if !remainder.is_empty() {
+        return Err(DecodeError::BadPacket("Unexpected bytes after packet"));
+    }
+    Ok(())
+}
+
+/// Decodes `packet` into `out` and returns the bytes following the arguments of a message.
+fn fill_packet<'a>(packet: &LazyPacket<'a>, out: &mut OscPacket) -> Result<&'a [u8], DecodeError> {
+    match *packet {
+        LazyPacket::Message(ref msg) => {
+            if let OscPacket::Bundle(_) = *out {
+                *out = OscPacket::Message(OscMessage::from(""));
+            }
+            match *out {
+                OscPacket::Message(ref mut out) => fill_message(msg, out),
+                OscPacket::Bundle(_) => unreachable!(),
+            }
+        }
+        LazyPacket::Bundle(ref bundle) => {
+            if let OscPacket::Message(_) = *out {
+                *out = OscPacket::Bundle(OscBundle {
+                    timetag: bundle.timetag,
+                    content: vec![],
+                });
+            }
+            match *out {
+                OscPacket::Bundle(ref mut out) => fill_bundle(bundle, out),
+                OscPacket::Message(_) => unreachable!(),
+            }
+        }
+    }
+}
+
+fn fill_message<'a>(msg: &LazyMessage<'a>, out: &mut OscMessage) -> Result<&'a [u8], DecodeError> {
+    out.addr.clear();
+    out.addr.push_str(msg.addr);
+
+    let mut args = msg.args();
+    let mut len = 0;
+    for arg in args.by_ref() {
+        let arg = arg?;
+        match out.args.get_mut(len) {
+            Some(out) => fill_arg(arg, out),
+            None => out.args.push(arg.to_owned()),
+        }
+        len += 1;
+    }
+    out.args.truncate(len);
+    Ok(args.input)
+}
+
+fn fill_bundle<'a>(bundle: &LazyBundle<'a>, out: &mut OscBundle) -> Result<&'a [u8], DecodeError> {
+    out.timetag = bundle.timetag;
+
+    let mut len = 0;
+    for element in bundle.elements() {
+        let element = element?;
+        match out.content.get_mut(len) {
+            Some(out) => {
+                fill_packet(&element, out)?;
+            }
+            None => out.content.push(element.to_owned()?),
+        }
+        len += 1;
+    }
+    out.content.truncate(len);
+    Ok(&[])
+}
+
+fn fill_arg(arg: OscTypeRef<'_>, out: &mut OscType) {
+    match (arg, out) {
+        (OscTypeRef::String(string), &mut OscType::String(ref mut out)) => {
+            out.clear();
+            out.push_str(&string);
+        }
+        (OscTypeRef::Blob(blob), &mut OscType::Blob(ref mut out)) => {
+            out.clear();
+            out.extend_from_slice(blob);
+        }
+        (OscTypeRef::Array(array), &mut OscType::Array(ref mut out)) => {
+            let len = array.content.len();
+            for (i, arg) in array.content.into_iter().enumerate() {
+                match out.content.get_mut(i) {
+                    Some(out) => fill_arg(arg, out),
+                    None => out.content.push(arg.to_owned()),
+                }
+            }
+            out.content.truncate(len);
+        }
+        (arg, out) => *out = arg.to_owned(),
+    }
+}
+
+/// Parses a message written the way it is passed to `oscsend`: the address, followed by the type
+/// tags and one argument per type tag, all separated by whitespace, e.g. `/mixer/1/gain if 3 0.5`.
+/// The type tags may start with a `,` and are omitted for messages without arguments.
+///
+/// Arguments are written as follows:
+///
+/// - `i`, `h`, `f`, `d`: decimal numbers like `-3` or `0.5`, as well as the nonstandard `u` for
+///   [`OscType::Unsigned`]
+/// - `s`: a single word or a string in double quotes, which supports the escapes `\"`, `\\`,
+///   `\n`, `\r` and `\t`
+/// - `c`: a single character
+/// - `b`: hex bytes starting with `0x`, e.g. `0xdeadbeef`
+/// - `m`, `r`: exactly 4 hex bytes like blobs, in the order of the fields of `OscMidiMessage` and
+///   `OscColor` respectively
+/// - `t`: `@seconds.fractional` with the raw seconds and fractional parts of the time tag as
+///   decimal numbers, or `@now` for the current time, which requires the `std` feature
+/// - `T`, `F`, `N`, `I` and the array delimiters `[` and `]` take no argument
+///
+/// The error for malformed input is `DecodeError::BadText` with the column of the offending token.
+///
+/// # Example
+///
+/// ```
+/// use rosc::{decoder, OscMessage};
+///
+/// let msg = decoder::parse_text(r#"/mixer/1/name sTb "Lead \"Vox\"" 0x0102"#).unwrap();
+/// assert_eq!(
+///     msg,
+///     OscMessage {
+///         addr: "/mixer/1/name".to_string(),
+///         args: vec!["Lead \"Vox\"".into(), true.into(), vec![1u8, 2].into()],
+///     }
+/// );
+/// ```
+pub fn parse_text(line: &str) -> Result<OscMessage, DecodeError> {
+    crate::text::parse_text(line)
+}
+
+/// Hard upper bound for the nesting depth of bundles, regardless of
+/// [`DecoderOptions::max_bundle_depth`]. This keeps the recursion of the decoder bounded so that
+/// deeply nested bundles can never overflow the stack.
+pub const MAX_BUNDLE_DEPTH: usize = 128;
+
+/// Upper bound for the nesting depth of array arguments. Nested arrays are converted and dropped
+/// recursively, so their depth has to be bounded as well.
+pub const MAX_ARRAY_DEPTH: usize = 128;
+
+/// Limits enforced by [`decode_with_options`] to protect against malicious or broken packets, as
+/// well as switches to accept packets of senders that deviate from the OSC specification.
+///
+/// The default values are generous enough for typical OSC traffic while preventing a single packet
+/// from using unbounded resources. Decoding is strict by default. Use struct update syntax to only
+/// change some of the options:
+///
+/// ```
+/// use rosc::decoder::DecoderOptions;
+///
+/// let options = DecoderOptions {
+///     max_packet_size: 1024 * 1024,
+///     max_blob_size: 1024 * 1024,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecoderOptions {
+    /// Maximum size of a blob argument in bytes. Defaults to 64 KiB.
+    pub max_blob_size: usize,
+    /// Maximum length of an OSC string in bytes, excluding the terminator and padding. This applies
+    /// to addresses and type tags as well as string arguments. Defaults to 4096.
+    pub max_string_len: usize,
+    /// Maximum length of a message address in bytes, excluding the terminator and padding.
+    /// Longer addresses are rejected with [`DecodeError::AddressTooLong`], which takes precedence
+    /// over [`max_string_len`](DecoderOptions::max_string_len) if both are exceeded. Defaults to
+    /// [`DEFAULT_MAX_ADDRESS_LEN`](crate::address::DEFAULT_MAX_ADDRESS_LEN).
+    pub max_address_len: usize,
+    /// Maximum nesting depth of bundles, a bundle which is not contained in another bundle has a
+    /// depth of one. Defaults to 8 and is always capped by [`MAX_BUNDLE_DEPTH`].
+    pub max_bundle_depth: usize,
+    /// Maximum number of arguments of a message, the contents of arrays count towards the limit.
+    /// Defaults to 1024.
+    pub max_args: usize,
+    /// Maximum size of a packet in bytes. Defaults to 64 KiB, the maximum size of a UDP datagram.
+    pub max_packet_size: usize,
+    /// Accept type tag strings that are missing the leading `,` as long as they only consist of
+    /// known type tags. A message whose address is followed by nothing at all is accepted as a
+    /// message without arguments. Note that this cannot be told apart from the first argument of a
+    /// message which omits the type tag string altogether, e.g. a string argument `"iF"` would be
+    /// taken for two type tags. Defaults to `false`.
+    pub lenient_type_tags: bool,
+    /// How to handle string arguments and addresses that are not valid UTF-8. Defaults to
+    /// [`StringDecoding::Strict`].
+    pub string_decoding: StringDecoding,
+    /// Ignore up to 3 bytes following the packet, as appended by some senders, instead of
+    /// rejecting the packet with [`DecodeError::Misaligned`]. Defaults to `false`.
+    pub allow_trailing_bytes: bool,
+    /// Decode bundles with a time tag of `(0, 0)` as if they had the time tag
+    /// [`OscTime::IMMEDIATE`], for senders that use it to mean "immediately". Defaults to `false`,
+    /// in which case the time tag is kept as it is.
+    pub zero_timetag_is_immediate: bool,
+    /// Accept strings whose length is a multiple of 4 but which lack the terminating null word, as
+    /// sent by some legacy implementations. When a string fails to decode strictly, it is assumed
+    /// to end at the last 32-bit boundary before its terminator or the end of the packet. Addresses
+    /// additionally end before a `,` and type tag strings before a byte that is not a type tag, if
+    /// found on a 32-bit boundary. A string argument of a multiple of 4 bytes that is followed by
+    /// another string still can't be told apart from a single string. Defaults to `false`.
+    pub allow_unpadded_strings: bool,
+    /// Accept messages whose address does not start with `/`, as sent by some devices in violation
+    /// of the specification. The address is decoded verbatim, use
+    /// [`MatcherOptions::allow_unprefixed`](crate::address::MatcherOptions) to match it. Addresses
+    /// starting with `#` are still rejected. Defaults to `false`.
+    pub allow_unprefixed_addresses: bool,
+    /// Accept char arguments with the ASCII code in the first of their four bytes instead of the
+    /// last, as sent by some Max externals, e.g. `41 00 00 00` for `'A'`. Chars are otherwise
+    /// decoded from the big-endian Unicode scalar value of the word, `00 00 00 41` for `'A'`, which
+    /// is how rosc encodes them. The placement can't be confused, as a word with a nonzero first
+    /// byte is no valid Unicode scalar value. Defaults to `false`.
+    pub allow_high_byte_chars: bool,
+    /// Reject bundles containing a bundle with an earlier time tag, which the OSC specification
+    /// forbids, with [`DecodeError::BundleTimeNotMonotonic`]. Immediate bundles take effect at the
+    /// time of the bundle enclosing them: they may be contained in any bundle and the bundles
+    /// they contain are checked against the time of the enclosing bundle. Time tags of `(0, 0)`
+    /// are checked like any other unless
+    /// [`zero_timetag_is_immediate`](DecoderOptions::zero_timetag_is_immediate) is enabled.
+    /// Defaults to `false`.
+    pub enforce_bundle_time_monotonicity: bool,
+    /// Nonstandard type tags to accept, which are rejected with [`DecodeError::UnknownTypeTag`]
+    /// otherwise. The only supported extension is `'u'` for [`OscType::Unsigned`], a big-endian
+    /// unsigned 32-bit integer, other tags are ignored. Defaults to none.
+    pub accept_extension_tags: &'static [char],
+    /// What to do with bundle elements that fail to decode. Defaults to
+    /// [`BundleErrorPolicy::Fail`].
+    pub bundle_error_policy: BundleErrorPolicy,
+    /// The features the sender supports, see [`Profile`]. Messages with type tags the profile
+    /// doesn't allow are rejected with [`DecodeError::UnsupportedTypeTag`], extension tags are
+    /// accepted according to [`accept_extension_tags`](DecoderOptions::accept_extension_tags)
+    /// alone. Defaults to [`Profile::FULL_1_1`], which allows every type tag.
+    pub profile: Profile,
+}
+
+/// Handling of bundle elements that fail to decode, see [`DecoderOptions::bundle_error_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BundleErrorPolicy {
+    /// Reject the whole packet.
+    #[default]
+    Fail,
+    /// Leave the element out of the decoded bundle and continue with the next one. The skipped
+    /// elements are reported by [`decode_bundle`] and passed to
+    /// [`OscVisitor::skipped_element`], other decoding functions drop them silently. An element
+    /// whose size is invalid also ends its bundle, as the start of the next element can't be told.
+    SkipElement,
+}
+
+/// Handling of invalid UTF-8 in strings, see [`DecoderOptions::string_decoding`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StringDecoding {
+    /// Reject packets containing invalid strings.
+    #[default]
+    Strict,
+    /// Replace invalid sequences in string arguments and addresses with U+FFFD. The original
+    /// bytes are lost and the affected strings have to be copied. Since replaced addresses may
+    /// collide with other addresses, they should not be relied upon for dispatching.
+    Lossy,
+    /// Decode invalid string arguments as [`OscType::Blob`] containing the
+    /// raw bytes, so that they can be decoded by other means, e.g. as Latin-1. This changes the
+    /// type of the argument, so code expecting a string has to check for blobs as well. Addresses
+    /// are still decoded strictly, as a blob cannot take their place.
+    Preserve,
+}
+
+impl Default for DecoderOptions {
+    fn default() -> Self {
+        DecoderOptions {
+            max_blob_size: 64 * 1024,
+            max_string_len: 4096,
+            max_address_len: DEFAULT_MAX_ADDRESS_LEN,
+            max_bundle_depth: 8,
+            max_args: 1024,
+            max_packet_size: 64 * 1024,
+            lenient_type_tags: false,
+            string_decoding: StringDecoding::Strict,
+            allow_trailing_bytes: false,
+            zero_timetag_is_immediate: false,
+            allow_unpadded_strings: false,
+            allow_unprefixed_addresses: false,
+            allow_high_byte_chars: false,
+            enforce_bundle_time_monotonicity: false,
+            accept_extension_tags: &[],
+            bundle_error_policy: BundleErrorPolicy::Fail,
+            profile: Profile::FULL_1_1,
+        }
+    }
+}
+
+/// Options used by the decoding functions that do not take options. Apart from the bundle depth,
+/// which is always bounded by `MAX_BUNDLE_DEPTH`, nothing is limited.
+const UNLIMITED: DecoderOptions = DecoderOptions {
+    max_blob_size: usize::MAX,
+    max_string_len: usize::MAX,
+    max_address_len: usize::MAX,
+    max_bundle_depth: MAX_BUNDLE_DEPTH,
+    max_args: usize::MAX,
+    max_packet_size: usize::MAX,
+    lenient_type_tags: false,
+    string_decoding: StringDecoding::Strict,
+    allow_trailing_bytes: false,
+    zero_timetag_is_immediate: false,
+    allow_unpadded_strings: false,
+    allow_unprefixed_addresses: false,
+    allow_high_byte_chars: false,
+    enforce_bundle_time_monotonicity: false,
+    accept_extension_tags: &[],
+    bundle_error_policy: BundleErrorPolicy::Fail,
+    profile: Profile::FULL_1_1,
+};
+
+/// Converts the result of a parser that must consume all of its input.
+fn complete_packet<T>(result: IResult<&[u8], T, DecodeError>) -> Result<T, DecodeError> {
+    match result {
+        Ok((&[], value)) => Ok(value),
+        Ok(_) => Err(DecodeError::BadPacket("Unexpected bytes after packet")),
+        Err(err) => Err(into_decode_error(err)),
+    }
+}
+
+fn into_decode_error(err: nom::Err<DecodeError>) -> DecodeError {
+    match err {
+        nom::Err::Error(err) | nom::Err::Failure(err) => err,
+        nom::Err::Incomplete(_) => DecodeError::BadPacket("Incomplete packet"),
+    }
 }
 
 /// Takes a bytes slice from a TCP stream (or any stream-based protocol) and returns the first OSC
 /// packet as well as a slice of the bytes remaining after the packet.
-pub fn decode_tcp<'a>(msg: &'a [u8]) -> IResult<&'a [u8], Option<OscPacket>, OscError> {
-    let (input, osc_packet_length) = be_u32(msg)?;
+pub fn decode_tcp(msg: &[u8]) -> IResult<&[u8], Option<OscPacket>, DecodeError> {
+    let (input, osc_packet_length) = read_u32(msg)?;
 
-    if osc_packet_length as usize > msg.len() {
+    if osc_packet_length as usize > input.len() {
         return Ok((msg, None));
     }
 
-    decode_packet(input, msg).map(|(remainder, osc_packet)| {
-        (remainder, Some(osc_packet))
+    let (remainder, osc_packet) = take(osc_packet_length)(input)?;
+    decode_packet(osc_packet, msg, &UNLIMITED).map(|(_, osc_packet)| {
+        (remainder, Some(osc_packet.to_owned()))
     })
 }
 
 /// Takes a bytes slice from a TCP stream (or any stream-based protocol) and returns a vec of all
 /// OSC packets in the slice as well as a slice of the bytes remaining after the last packet.
-pub fn decode_tcp_vec<'a>(msg: &'a [u8]) -> IResult<&'a [u8], Vec<OscPacket>, OscError> {
+pub fn decode_tcp_vec(msg: &[u8]) -> IResult<&[u8], Vec<OscPacket>, DecodeError> {
     let mut input = msg;
     let mut osc_packets = vec![];
 
@@ -43,7 +1708,7 @@ pub fn decode_tcp_vec<'a>(msg: &'a [u8]) -> IResult<&'a [u8], Vec<OscPacket>, Os
         input = remainder;
         osc_packets.push(osc_packet);
 
-        if remainder.len() == 0 {
+        if remainder.is_empty() {
             break;
         }
     };
@@ -51,226 +1716,937 @@ pub fn decode_tcp_vec<'a>(msg: &'a [u8]) -> IResult<&'a [u8], Vec<OscPacket>, Os
     Ok((input, osc_packets))
 }
 
+/// Receives the contents of a packet decoded by [`decode_visit`].
+///
+/// Bundles are reported as [`begin_bundle`](OscVisitor::begin_bundle), followed by their
+/// elements and [`end_bundle`](OscVisitor::end_bundle). Messages start with
+/// [`message_addr`](OscVisitor::message_addr) and
+/// [`message_type_tags`](OscVisitor::message_type_tags), followed by their arguments and
+/// [`end_message`](OscVisitor::end_message). Array arguments are enclosed by
+/// [`begin_array`](OscVisitor::begin_array) and [`end_array`](OscVisitor::end_array).
+///
+/// Every argument is passed to [`arg`](OscVisitor::arg), which by default forwards it to the
+/// callback for its type, e.g. [`arg_int`](OscVisitor::arg_int). All callbacks do nothing by
+/// default so that implementations only need to provide the ones they are interested in.
+///
+/// Addresses and strings are passed as [`Cow`], which is always borrowed from the decoded buffer
+/// unless invalid UTF-8 was replaced as configured by [`StringDecoding::Lossy`].
+#[allow(unused_variables)]
+pub trait OscVisitor<'a> {
+    fn begin_bundle(&mut self, timetag: OscTime) {}
+    fn end_bundle(&mut self) {}
+    fn message_addr(&mut self, addr: Cow<'a, str>) {}
+    /// Receives the raw type tag string of the message, including the leading `,`.
+    fn message_type_tags(&mut self, type_tags: &'a str) {}
+    fn end_message(&mut self) {}
+    fn begin_array(&mut self) {}
+    fn end_array(&mut self) {}
+
+    fn arg(&mut self, arg: OscTypeRef<'a>) {
+        match arg {
+            OscTypeRef::Int(x) => self.arg_int(x),
+            OscTypeRef::Float(x) => self.arg_float(x),
+            OscTypeRef::String(x) => self.arg_string(x),
+            OscTypeRef::Blob(x) => self.arg_blob(x),
+            OscTypeRef::Time(x) => self.arg_time(x),
+            OscTypeRef::Long(x) => self.arg_long(x),
+            OscTypeRef::Double(x) => self.arg_double(x),
+            OscTypeRef::Char(x) => self.arg_char(x),
+            OscTypeRef::Color(x) => self.arg_color(x),
+            OscTypeRef::Midi(x) => self.arg_midi(x),
+            OscTypeRef::Bool(x) => self.arg_bool(x),
+            OscTypeRef::Array(x) => {
+                self.begin_array();
+                for arg in x.content {
+                    self.arg(arg);
+                }
+                self.end_array();
+            }
+            OscTypeRef::Nil => self.arg_nil(),
+            OscTypeRef::Impulse => self.arg_impulse(),
+            OscTypeRef::Unsigned(x) => self.arg_unsigned(x),
+        }
+    }
+    fn arg_int(&mut self, x: i32) {}
+    fn arg_float(&mut self, x: f32) {}
+    fn arg_string(&mut self, x: Cow<'a, str>) {}
+    fn arg_blob(&mut self, x: &'a [u8]) {}
+    fn arg_time(&mut self, x: OscTime) {}
+    fn arg_long(&mut self, x: i64) {}
+    fn arg_double(&mut self, x: f64) {}
+    fn arg_char(&mut self, x: char) {}
+    fn arg_color(&mut self, x: OscColor) {}
+    fn arg_midi(&mut self, x: OscMidiMessage) {}
+    fn arg_bool(&mut self, x: bool) {}
+    fn arg_nil(&mut self) {}
+    /// Receives an [`OscTypeRef::Impulse`] argument. Calls [`arg_inf`](OscVisitor::arg_inf) by
+    /// default, for visitors implementing the callback by its former name.
+    fn arg_impulse(&mut self) {
+        #[allow(deprecated)]
+        self.arg_inf()
+    }
+    #[deprecated(note = "renamed to `arg_impulse`")]
+    fn arg_inf(&mut self) {}
+    fn arg_unsigned(&mut self, x: u32) {}
+
+    /// Receives the error of the element at `index` of the innermost bundle being decoded, which
+    /// was left out with [`BundleErrorPolicy::SkipElement`]. Nothing of the element has been
+    /// passed to the visitor. The context of the error only tells the path to the element within
+    /// the innermost bundle.
+    fn skipped_element(&mut self, index: usize, error: DecodeError) {}
+}
+
+/// Visitor ignoring everything, to check that a packet decodes before visiting it.
+struct NoopVisitor;
+
+impl<'a> OscVisitor<'a> for NoopVisitor {}
+
+/// Visitor assembling an [`OscPacketRef`] from the decoded contents.
+#[derive(Default)]
+struct PacketBuilder<'a> {
+    bundles: Vec<OscBundleRef<'a>>,
+    message: Option<OscMessageRef<'a>>,
+    arrays: Vec<Vec<OscTypeRef<'a>>>,
+    packet: Option<OscPacketRef<'a>>,
+    // Number of elements skipped so far in each of the bundles being assembled
+    skipped: Vec<usize>,
+    errors: Vec<(usize, DecodeError)>,
+}
+
+impl<'a> PacketBuilder<'a> {
+    fn finish_packet(&mut self, packet: OscPacketRef<'a>) {
+        match self.bundles.last_mut() {
+            Some(bundle) => bundle.content.push(packet),
+            None => self.packet = Some(packet),
+        }
+    }
+}
+
+impl<'a> OscVisitor<'a> for PacketBuilder<'a> {
+    fn begin_bundle(&mut self, timetag: OscTime) {
+        self.bundles.push(OscBundleRef {
+            timetag,
+            content: vec![],
+        });
+        self.skipped.push(0);
+    }
+
+    fn end_bundle(&mut self) {
+        self.skipped.pop();
+        if let Some(bundle) = self.bundles.pop() {
+            self.finish_packet(OscPacketRef::Bundle(bundle));
+        }
+    }
+
+    fn message_addr(&mut self, addr: Cow<'a, str>) {
+        self.message = Some(OscMessageRef { addr, args: vec![] });
+    }
+
+    fn message_type_tags(&mut self, type_tags: &'a str) {
+        if let Some(msg) = self.message.as_mut() {
+            msg.args.reserve(type_tags.len().saturating_sub(1));
+        }
+    }
+
+    fn end_message(&mut self) {
+        if let Some(msg) = self.message.take() {
+            self.finish_packet(OscPacketRef::Message(msg));
+        }
+    }
+
+    fn begin_array(&mut self) {
+        self.arrays.push(Vec::new());
+    }
+
+    fn end_array(&mut self) {
+        if let Some(content) = self.arrays.pop() {
+            self.arg(OscTypeRef::Array(OscArrayRef { content }));
+        }
+    }
+
+    fn arg(&mut self, arg: OscTypeRef<'a>) {
+        match self.arrays.last_mut() {
+            Some(array) => array.push(arg),
+            None => {
+                if let Some(msg) = self.message.as_mut() {
+                    msg.args.push(arg);
+                }
+            }
+        }
+    }
+
+    fn skipped_element(&mut self, _index: usize, mut error: DecodeError) {
+        // The index of the element being assembled in each bundle, from the outermost one
+        let path: Vec<usize> = self
+            .bundles
+            .iter()
+            .zip(&self.skipped)
+            .map(|(bundle, skipped)| bundle.content.len() + skipped)
+            .collect();
+        let index = match path.first() {
+            Some(&index) => index,
+            None => return,
+        };
+        // The context only tells the path within the innermost bundle
+        if let DecodeError::At { ref mut context, .. } = error {
+            for &enclosing in path[..path.len() - 1].iter().rev() {
+                *context = prepend_bundle_element(mem::take(context), enclosing);
+            }
+        }
+        self.errors.push((index, error));
+        if let Some(skipped) = self.skipped.last_mut() {
+            *skipped += 1;
+        }
+    }
+}
 
 fn decode_packet<'a>(
     input: &'a [u8],
-    original_input: &'a[u8],
-) -> IResult<&'a [u8], OscPacket, OscError> {
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+) -> IResult<&'a [u8], OscPacketRef<'a>, DecodeError> {
+    let mut builder = PacketBuilder::default();
+    let (input, _) = visit_packet(input, original_input, options, 0, OscTime::IMMEDIATE, &mut builder)?;
+    match builder.packet {
+        Some(packet) => Ok((input, packet)),
+        None => Err(nom::Err::Error(DecodeError::BadPacket("Empty packet."))),
+    }
+}
+
+/// Visits the packet at the start of `input`. `depth` is the number of bundles enclosing it and
+/// `timetag` the time tag they take effect at, which is [`OscTime::IMMEDIATE`] if all of them
+/// are immediate or there are none.
+fn visit_packet<'a, V: OscVisitor<'a> + ?Sized>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+    depth: usize,
+    timetag: OscTime,
+    visitor: &mut V,
+) -> IResult<&'a [u8], (), DecodeError> {
+    let offset = original_input.offset(input);
     if input.is_empty() {
-        return Err(nom::Err::Error(OscError::BadPacket("Empty packet.")));
+        return with_context(
+            Err(nom::Err::Error(DecodeError::BadPacket("Empty packet."))),
+            original_input,
+            offset,
+            || "packet".to_string(),
+        );
     }
+    check_bundle_header(input).map_err(|err| {
+        nom::Err::Error(error_at(original_input, offset, "bundle header".to_string(), err))
+    })?;
 
-    let (input, addr) = read_osc_string(input, original_input)?;
+    let (input, addr) = with_context(
+        read_osc_address(input, original_input, options),
+        original_input,
+        offset,
+        || "address".to_string(),
+    )?;
 
     match addr.chars().next() {
         Some('/') => {
-            decode_message(addr, input, original_input)
+            visit_message(addr, input, original_input, options, visitor)
         }
-        Some('#') if &addr == "#bundle" => {
-            decode_bundle(input, original_input)
+        Some('#') if addr == "#bundle" => {
+            visit_bundle(input, original_input, options, depth + 1, timetag, visitor)
         }
-        _ => Err(nom::Err::Error(OscError::BadPacket(
-            "Invalid message address or bundle tag"
-        ))),
+        Some(c) if c != '#' && options.allow_unprefixed_addresses => {
+            visit_message(addr, input, original_input, options, visitor)
+        }
+        _ => with_context(
+            Err(nom::Err::Error(DecodeError::BadPacket(
+                "Invalid message address or bundle tag"
+            ))),
+            original_input,
+            offset,
+            || "address".to_string(),
+        ),
     }
 }
 
-fn decode_message<'a>(
-    addr: String,
+fn visit_message<'a, V: OscVisitor<'a> + ?Sized>(
+    addr: Cow<'a, str>,
     input: &'a [u8],
-    original_input: &'a[u8],
-) -> IResult<&'a [u8], OscPacket, OscError> {
-    let (input, type_tags) = read_osc_string(input, original_input)?;
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+    visitor: &mut V,
+) -> IResult<&'a [u8], (), DecodeError> {
+    let offset = original_input.offset(input);
+    let type_tags_context = || format!("type tags of message '{}'", addr);
+    let (input, type_tags) = if input.is_empty() && options.lenient_type_tags {
+        (input, "")
+    } else {
+        with_context(
+            read_osc_string_until(input, original_input, options, &|b| {
+                !is_type_tag(b as char, options)
+            }),
+            original_input,
+            offset,
+            type_tags_context,
+        )?
+    };
+    let tags = match type_tags.strip_prefix(',') {
+        Some(tags) => tags,
+        None if options.lenient_type_tags && type_tags.chars().all(|tag| is_type_tag(tag, options)) => {
+            type_tags
+        }
+        None => {
+            return with_context(
+                Err(nom::Err::Error(DecodeError::BadMessage(
+                    "Type tag string does not start with ','",
+                ))),
+                original_input,
+                offset,
+                type_tags_context,
+            );
+        }
+    };
 
-    if type_tags.len() > 1 {
-        let (input, args) = read_osc_args(
-            input,
+    let arg_count = tags.chars().filter(|&tag| tag != '[' && tag != ']').count();
+    if arg_count > options.max_args {
+        return with_context(
+            Err(nom::Err::Error(DecodeError::TooManyArgs {
+                count: arg_count,
+                max: options.max_args,
+            })),
             original_input,
-            type_tags,
-        )?;
-        Ok((input, OscPacket::Message(OscMessage { addr, args })))
-    } else {
-        Ok((input, OscPacket::Message(OscMessage { addr, args: vec![] })))
+            offset,
+            type_tags_context,
+        );
     }
+
+    visitor.message_addr(addr.clone());
+    visitor.message_type_tags(type_tags);
+    let (input, _) = with_context(
+        visit_osc_args(input, original_input, options, &addr, tags, visitor),
+        original_input,
+        offset,
+        type_tags_context,
+    )?;
+    visitor.end_message();
+
+    Ok((input, ()))
 }
 
-fn decode_bundle<'a>(
+fn visit_bundle<'a, V: OscVisitor<'a> + ?Sized>(
     input: &'a [u8],
-    original_input: &'a[u8],
-) -> IResult<&'a [u8], OscPacket, OscError> {
-    let (input, (timetag, content)) = tuple((
-        read_time_tag,
-        many0(|input| read_bundle_element(input, original_input)),
-    ))(input)?;
-
-    Ok((
-        input,
-        OscPacket::Bundle(OscBundle {
-            timetag,
-            content,
-        }),
-    ))
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+    depth: usize,
+    parent_timetag: OscTime,
+    visitor: &mut V,
+) -> IResult<&'a [u8], (), DecodeError> {
+    let offset = original_input.offset(input);
+    let max_depth = options.max_bundle_depth.min(MAX_BUNDLE_DEPTH);
+    if depth > max_depth {
+        return with_context(
+            Err(nom::Err::Error(DecodeError::BundleTooDeep { max: max_depth })),
+            original_input,
+            offset,
+            || "bundle".to_string(),
+        );
+    }
+
+    let (mut input, mut timetag) =
+        with_context(read_time_tag(input), original_input, offset, || {
+            "time tag of bundle".to_string()
+        })?;
+    if options.zero_timetag_is_immediate && timetag == OscTime::from((0, 0)) {
+        timetag = OscTime::IMMEDIATE;
+    }
+
+    visitor.begin_bundle(timetag);
+    // Immediate bundles take effect at the time of the enclosing bundle
+    let timetag = match timetag.is_immediate() {
+        true => parent_timetag,
+        false => timetag,
+    };
+    let skip = options.bundle_error_policy == BundleErrorPolicy::SkipElement;
+    let mut index = 0;
+    while !input.is_empty() {
+        let (remainder, element) = match read_bundle_element(input, original_input, index) {
+            Ok(element) => element,
+            Err(err) if skip => {
+                // The next element can't be found without a valid size
+                visitor.skipped_element(index, into_decode_error(err));
+                input = &input[input.len()..];
+                break;
+            }
+            Err(err) => return Err(err),
+        };
+        input = remainder;
+        let result =
+            visit_bundle_element(element, original_input, options, depth, timetag, index, visitor);
+        match result {
+            Err(err) if skip => visitor.skipped_element(index, into_decode_error(err)),
+            result => result?,
+        }
+        index += 1;
+    }
+    visitor.end_bundle();
+
+    Ok((input, ()))
+}
+
+/// Reads the size of the bundle element at the start of `input` and returns the element along
+/// with the remainder of the bundle.
+fn read_bundle_element<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    index: usize,
+) -> IResult<&'a [u8], &'a [u8], DecodeError> {
+    let offset = original_input.offset(input);
+    let size_context = || format!("size of bundle element {}", index);
+    let (input, elem_size) = with_context(read_u32(input), original_input, offset, size_context)?;
+    let elem_size = check_element_size(elem_size, input.len()).map_err(|err| {
+        nom::Err::Error(error_at(original_input, offset, size_context(), err))
+    })?;
+    let (element, input) = input.split_at(elem_size);
+    Ok((input, element))
 }
 
-fn read_bundle_element<'a>(input: &'a [u8], original_input: &'a[u8]) -> IResult<&'a [u8], OscPacket, OscError> {
-    let (input, elem_size) = be_u32(input)?;
+/// Visits the bundle element `element` at `index` of a bundle, whose size has been read.
+fn visit_bundle_element<'a, V: OscVisitor<'a> + ?Sized>(
+    element: &'a [u8],
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+    depth: usize,
+    timetag: OscTime,
+    index: usize,
+    visitor: &mut V,
+) -> Result<(), nom::Err<DecodeError>> {
+    if options.enforce_bundle_time_monotonicity {
+        check_element_time(element, original_input, options, timetag, index)
+            .map_err(nom::Err::Error)?;
+    }
 
-    let result = map_parser(
-        |input| {
-            take(elem_size)(input).map_err(|_: nom::Err<OscError>| {
-                nom::Err::Error(OscError::BadBundle(
-                    "Bundle shorter than expected!".to_string(),
-                ))
-            })
+    let in_element = |err: nom::Err<DecodeError>| {
+        err.map(|err| match err {
+            DecodeError::At {
+                offset,
+                context,
+                snippet,
+                source,
+            } => DecodeError::At {
+                offset,
+                context: prepend_bundle_element(context, index),
+                snippet,
+                source,
+            },
+            err => err,
+        })
+    };
+    if options.bundle_error_policy == BundleErrorPolicy::SkipElement && !is_bundle(element) {
+        // A message failing halfway would have been passed to the visitor in part. Nested
+        // bundles skip their own elements instead and only fail before visiting anything.
+        visit_packet(element, original_input, options, depth, timetag, &mut NoopVisitor)
+            .map_err(in_element)?;
+    }
+    visit_packet(element, original_input, options, depth, timetag, visitor).map_err(in_element)?;
+    Ok(())
+}
+
+/// Checks that the bundle element at `index`, if it is a bundle, doesn't have a time tag earlier
+/// than `timetag`, that of the enclosing bundle, see
+/// [`DecoderOptions::enforce_bundle_time_monotonicity`].
+fn check_element_time(
+    element: &[u8],
+    original_input: &[u8],
+    options: &DecoderOptions,
+    timetag: OscTime,
+    index: usize,
+) -> Result<(), DecodeError> {
+    if timetag.is_immediate() || !is_bundle(element) {
+        return Ok(());
+    }
+    // A truncated time tag is reported when visiting the element
+    let mut element_timetag = match read_time_tag(&element[8..]) {
+        Ok((_, element_timetag)) => element_timetag,
+        Err(_) => return Ok(()),
+    };
+    if options.zero_timetag_is_immediate && element_timetag == OscTime::from((0, 0)) {
+        element_timetag = OscTime::IMMEDIATE;
+    }
+    if element_timetag.is_immediate() || element_timetag >= timetag {
+        return Ok(());
+    }
+    Err(error_at(
+        original_input,
+        original_input.offset(element) + 8,
+        prepend_bundle_element("time tag of bundle".to_string(), index),
+        DecodeError::BundleTimeNotMonotonic {
+            element: index,
+            timetag: element_timetag,
+            parent: timetag,
         },
-        |input| decode_packet(input, original_input),
-    )(input);
-    drop(elem_size);
-    result
+    ))
+}
+
+/// Validates the size of a bundle element that is followed by `remaining` bytes of the bundle.
+fn check_element_size(elem_size: u32, remaining: usize) -> Result<usize, DecodeError> {
+    if elem_size > i32::MAX as u32 {
+        Err(DecodeError::BadBundle("Negative bundle element size".to_string()))
+    } else if elem_size == 0 {
+        Err(DecodeError::BadBundle("Empty bundle element".to_string()))
+    } else if !elem_size.is_multiple_of(4) {
+        Err(DecodeError::BadBundle(format!(
+            "Bundle element size {} is not a multiple of 4",
+            elem_size
+        )))
+    } else if elem_size as usize > remaining {
+        Err(DecodeError::BadBundle(format!(
+            "Bundle element of {} bytes exceeds the remaining {} bytes of the bundle",
+            elem_size, remaining
+        )))
+    } else {
+        Ok(elem_size as usize)
+    }
+}
+
+/// Checks that a packet starting with `#` is a bundle, i.e. starts with the complete `#bundle`
+/// tag. Anything else can't be a valid packet either, but is reported separately as it is most
+/// likely a truncated or corrupted bundle.
+fn check_bundle_header(input: &[u8]) -> Result<(), DecodeError> {
+    if input.first() == Some(&b'#') && !is_bundle(input) {
+        return Err(DecodeError::BadBundleHeader(input[..input.len().min(8)].to_vec()));
+    }
+    Ok(())
+}
+
+/// Adds the index of an enclosing bundle element to an error context. Indices of nested bundles are
+/// joined into a path, so the context of an error in the second element of a bundle contained
+/// in the first element of the packet ends with `in bundle element 0.1`.
+fn prepend_bundle_element(mut context: String, index: usize) -> String {
+    const IN_ELEMENT: &str = " in bundle element ";
+    match context.rfind(IN_ELEMENT) {
+        Some(pos) => context.insert_str(pos + IN_ELEMENT.len(), &format!("{}.", index)),
+        None => context.push_str(&format!("{}{}", IN_ELEMENT, index)),
+    }
+    context
 }
 
-fn read_osc_string<'a>(input: &'a [u8], original_input: &'a[u8]) -> IResult<&'a [u8], String, OscError> {
+/// Attaches the offset and a description of the field being decoded to errors that don't carry a
+/// position yet. `context` is only evaluated if an error occurred.
+fn with_context<'a, T>(
+    result: IResult<&'a [u8], T, DecodeError>,
+    original_input: &[u8],
+    offset: usize,
+    context: impl FnOnce() -> String,
+) -> IResult<&'a [u8], T, DecodeError> {
+    result.map_err(|err| {
+        err.map(|err| match err {
+            DecodeError::At { .. } => err,
+            err => error_at(original_input, offset, context(), err),
+        })
+    })
+}
+
+/// Wraps `source` with the `offset` in `original_input` at which it occurred.
+fn error_at(
+    original_input: &[u8],
+    offset: usize,
+    context: String,
+    source: DecodeError,
+) -> DecodeError {
+    DecodeError::At {
+        offset,
+        context,
+        snippet: hex_snippet(original_input, offset),
+        source: Box::new(source),
+    }
+}
+
+fn read_osc_string<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+) -> IResult<&'a [u8], &'a str, DecodeError> {
     map_res(
-        terminated(
-            take_till(|c| c == 0u8),
-            pad_to_32_bit_boundary(original_input),
-        ),
-        |str_buf: &'a[u8]| {
-            String::from_utf8(str_buf.into())
-                .map_err(OscError::StringError)
-                .map(|s| s.trim_matches(0u8 as char).to_string())
-        },
+        |input| read_osc_string_bytes(input, original_input, options),
+        |str_buf: &'a [u8]| core::str::from_utf8(str_buf).map_err(|_| utf8_error(str_buf)),
     )(input)
 }
 
-fn read_osc_args<'a>(
-    mut input: &'a[u8],
-    original_input: &'a[u8],
-    raw_type_tags: String,
-) ->  IResult<&'a [u8], Vec<OscType>, OscError> {
-    let type_tags: Vec<char> = raw_type_tags.chars().skip(1).collect();
+/// Reads an OSC string which ends before `is_delimiter` matches a byte on a 32-bit boundary if
+/// [`DecoderOptions::allow_unpadded_strings`] is enabled.
+fn read_osc_string_until<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+    is_delimiter: &dyn Fn(u8) -> bool,
+) -> IResult<&'a [u8], &'a str, DecodeError> {
+    map_res(
+        |input| read_osc_string_bytes_until(input, original_input, options, is_delimiter),
+        |str_buf: &'a [u8]| core::str::from_utf8(str_buf).map_err(|_| utf8_error(str_buf)),
+    )(input)
+}
+
+/// Reads an OSC string and returns its contents without validating them.
+fn read_osc_string_bytes<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+) -> IResult<&'a [u8], &'a [u8], DecodeError> {
+    let result = terminated(
+        take_till(|c| c == 0u8),
+        pad_to_32_bit_boundary(original_input),
+    )(input);
+    let (input, str_buf) = match result {
+        Ok(result) => result,
+        Err(nom::Err::Error(err)) if options.allow_unpadded_strings => {
+            // Retry assuming that the terminator is missing because the string ends on a boundary
+            let len = input.iter().position(|&b| b == 0).unwrap_or(input.len());
+            if len == 0 || !(original_input.offset(input) + len).is_multiple_of(4) {
+                return Err(nom::Err::Error(err));
+            }
+            (&input[len..], &input[..len])
+        }
+        Err(err) => return Err(err),
+    };
+    check_string_len(input, str_buf, options)
+}
+
+/// Like [`read_osc_string_bytes`], but unpadded strings also end before a delimiter.
+fn read_osc_string_bytes_until<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+    is_delimiter: &dyn Fn(u8) -> bool,
+) -> IResult<&'a [u8], &'a [u8], DecodeError> {
+    if options.allow_unpadded_strings {
+        let len = input.iter().position(|&b| b == 0).unwrap_or(input.len());
+        let start = original_input.offset(input);
+        let delimiter = (1..len).find(|&i| (start + i).is_multiple_of(4) && is_delimiter(input[i]));
+        if let Some(end) = delimiter {
+            let (str_buf, input) = input.split_at(end);
+            return check_string_len(input, str_buf, options);
+        }
+    }
+    read_osc_string_bytes(input, original_input, options)
+}
+
+fn check_string_len<'a>(
+    input: &'a [u8],
+    str_buf: &'a [u8],
+    options: &DecoderOptions,
+) -> IResult<&'a [u8], &'a [u8], DecodeError> {
+    if str_buf.len() > options.max_string_len {
+        return Err(nom::Err::Error(DecodeError::StringTooLong {
+            len: str_buf.len(),
+            max: options.max_string_len,
+        }));
+    }
+    Ok((input, str_buf))
+}
+
+/// Reads a message address, replacing invalid UTF-8 if lossy string decoding is enabled.
+fn read_osc_address<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+) -> IResult<&'a [u8], Cow<'a, str>, DecodeError> {
+    check_address_len(read_osc_address_unchecked(input, original_input, options), options)
+}
+
+/// Turns the result of reading an address that exceeds [`DecoderOptions::max_address_len`] into
+/// [`DecodeError::AddressTooLong`].
+fn check_address_len<'a, T: core::ops::Deref<Target = str>>(
+    result: IResult<&'a [u8], T, DecodeError>,
+    options: &DecoderOptions,
+) -> IResult<&'a [u8], T, DecodeError> {
+    let too_long = |len| {
+        nom::Err::Error(DecodeError::AddressTooLong {
+            len,
+            max: options.max_address_len,
+        })
+    };
+    match result {
+        Ok((_, addr)) if addr.len() > options.max_address_len => Err(too_long(addr.len())),
+        Err(nom::Err::Error(DecodeError::StringTooLong { len, .. }))
+            if len > options.max_address_len =>
+        {
+            Err(too_long(len))
+        }
+        result => result,
+    }
+}
+
+/// Reads a message address like [`read_osc_address`], regardless of its length.
+fn read_osc_address_unchecked<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+) -> IResult<&'a [u8], Cow<'a, str>, DecodeError> {
+    let is_comma = |b| b == b',';
+    match options.string_decoding {
+        StringDecoding::Lossy => {
+            let (input, str_buf) =
+                read_osc_string_bytes_until(input, original_input, options, &is_comma)?;
+            Ok((input, String::from_utf8_lossy(str_buf)))
+        }
+        StringDecoding::Strict | StringDecoding::Preserve => {
+            read_osc_string_until(input, original_input, options, &is_comma)
+                .map(|(input, string)| (input, Cow::Borrowed(string)))
+        }
+    }
+}
 
-    let mut args: Vec<OscType> = Vec::with_capacity(type_tags.len());
-    let mut stack: Vec<Vec<OscType>> = Vec::new();
-    for tag in type_tags {
+/// Reads a string argument according to [`DecoderOptions::string_decoding`].
+fn read_osc_string_arg<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+) -> IResult<&'a [u8], OscTypeRef<'a>, DecodeError> {
+    let (input, str_buf) = read_osc_string_bytes(input, original_input, options)?;
+    let arg = match core::str::from_utf8(str_buf) {
+        Ok(string) => OscTypeRef::String(Cow::Borrowed(string)),
+        Err(_) => match options.string_decoding {
+            StringDecoding::Strict => return Err(nom::Err::Error(utf8_error(str_buf))),
+            StringDecoding::Lossy => OscTypeRef::String(String::from_utf8_lossy(str_buf)),
+            StringDecoding::Preserve => OscTypeRef::Blob(str_buf),
+        },
+    };
+    Ok((input, arg))
+}
+
+/// Builds the `DecodeError::StringError` for a byte sequence that failed UTF-8 validation.
+fn utf8_error(str_buf: &[u8]) -> DecodeError {
+    match String::from_utf8(str_buf.to_vec()) {
+        Err(err) => DecodeError::StringError(err),
+        Ok(_) => DecodeError::BadString("Invalid UTF-8"),
+    }
+}
+
+fn visit_osc_args<'a, V: OscVisitor<'a> + ?Sized>(
+    mut input: &'a [u8],
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+    addr: &str,
+    type_tags: &str,
+    visitor: &mut V,
+) -> IResult<&'a [u8], (), DecodeError> {
+    let mut array_depth: usize = 0;
+    let mut index = 0;
+    for tag in type_tags.chars() {
+        if is_type_tag(tag, options)
+            && !is_extension_tag(tag, options)
+            && !options.profile.supports_tag(tag)
+        {
+            return Err(nom::Err::Error(DecodeError::UnsupportedTypeTag(tag)));
+        }
         if tag == '[' {
-            // array start: save current frame and start a new frame
-            // for the array's content
-            stack.push(args);
-            args = Vec::new();
+            if array_depth == MAX_ARRAY_DEPTH {
+                return Err(nom::Err::Error(DecodeError::BadMessage(
+                    "Arrays are nested deeper than MAX_ARRAY_DEPTH"
+                )));
+            }
+            array_depth += 1;
+            visitor.begin_array();
         } else if tag == ']' {
-            // found the end of the current array:
-            // create array object from current frame and step one level up
-            let array = OscType::Array(OscArray { content: args });
-            match stack.pop() {
-                Some(stashed) => args = stashed,
-                None => return Err(nom::Err::Error(OscError::BadMessage(
+            if array_depth == 0 {
+                return Err(nom::Err::Error(DecodeError::BadMessage(
                     "Encountered ] outside array"
-                ))),
+                )));
             }
-            args.push(array);
+            array_depth -= 1;
+            visitor.end_array();
         } else {
-            let input_and_arg = read_osc_arg(input, original_input, tag)?;
-            input = input_and_arg.0;
-            args.push(input_and_arg.1);
+            let (remainder, arg) = with_context(
+                read_osc_arg(input, original_input, options, tag, index),
+                original_input,
+                original_input.offset(input),
+                || format!("arg {} of message '{}'", index, addr),
+            )?;
+            input = remainder;
+            index += 1;
+            visitor.arg(arg);
         }
     }
-    Ok((input, args))
+    if array_depth > 0 {
+        return Err(nom::Err::Error(DecodeError::BadMessage(
+            "Encountered [ without matching ]"
+        )));
+    }
+    Ok((input, ()))
+}
+
+/// Returns whether `tag` is a type tag understood by [`read_osc_arg`] or an array delimiter.
+fn is_type_tag(tag: char, options: &DecoderOptions) -> bool {
+    matches!(
+        tag,
+        'f' | 'd' | 'i' | 'h' | 's' | 't' | 'b' | 'r' | 'T' | 'F' | 'N' | 'I' | 'c' | 'm' | '[' | ']'
+    ) || is_extension_tag(tag, options)
 }
 
-fn read_osc_arg<'a>(input: &'a[u8], original_input: &'a[u8], tag: char) -> IResult<&'a[u8], OscType, OscError> {
+/// Returns whether `tag` is a supported extension type tag that is enabled in `options`.
+fn is_extension_tag(tag: char, options: &DecoderOptions) -> bool {
+    tag == 'u' && options.accept_extension_tags.contains(&tag)
+}
+
+/// Returns the size of the payload of arguments that have a fixed size.
+fn fixed_arg_size(tag: char) -> Option<usize> {
     match tag {
-        'f' => map(be_f32, OscType::Float)(input),
-        'd' => map(be_f64, OscType::Double)(input),
-        'i' => map(be_i32, OscType::Int)(input),
-        'h' => map(be_i64, OscType::Long)(input),
-        's' => {
-            read_osc_string(input, original_input)
-                .map(|(remainder, string)| (remainder, OscType::String(string)))
+        'i' | 'f' | 'c' | 'r' | 'm' | 'u' => Some(4),
+        'h' | 'd' | 't' => Some(8),
+        _ => None,
+    }
+}
+
+/// Reads the argument with the given type tag, `arg_index` is its position in the message.
+fn read_osc_arg<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+    tag: char,
+    arg_index: usize,
+) -> IResult<&'a [u8], OscTypeRef<'a>, DecodeError> {
+    if let Some(expected) = fixed_arg_size(tag) {
+        if input.len() < expected {
+            return Err(nom::Err::Error(DecodeError::Truncated {
+                expected,
+                remaining: input.len(),
+                arg_index,
+            }));
         }
+    }
+    match tag {
+        'f' => map(read_bytes, |b| OscTypeRef::Float(f32::from_be_bytes(b)))(input),
+        'd' => map(read_bytes, |b| OscTypeRef::Double(f64::from_be_bytes(b)))(input),
+        'i' => map(read_bytes, |b| OscTypeRef::Int(i32::from_be_bytes(b)))(input),
+        'h' => map(read_bytes, |b| OscTypeRef::Long(i64::from_be_bytes(b)))(input),
+        's' => read_osc_string_arg(input, original_input, options),
         't' => read_time_tag(input).map(|(remainder, time)| {
-            (remainder, OscType::Time(time))
+            (remainder, OscTypeRef::Time(time))
         }),
-        'b' => read_blob(input, original_input),
+        'b' => read_blob(input, original_input, options),
         'r' => read_osc_color(input),
-        'T' => Ok((input, true.into())),
-        'F' => Ok((input, false.into())),
-        'N' => Ok((input, OscType::Nil)),
-        'I' => Ok((input, OscType::Inf)),
-        'c' => read_char(input),
+        'T' => Ok((input, OscTypeRef::Bool(true))),
+        'F' => Ok((input, OscTypeRef::Bool(false))),
+        'N' => Ok((input, OscTypeRef::Nil)),
+        'I' => Ok((input, OscTypeRef::Impulse)),
+        'c' => read_char(input, options),
         'm' => read_midi_message(input),
-        _ => Err(nom::Err::Error(OscError::BadArg(format!(
-            "Type tag \"{}\" is not implemented!",
-            tag
-        )))),
+        'u' if is_extension_tag(tag, options) => {
+            map(read_bytes, |b| OscTypeRef::Unsigned(u32::from_be_bytes(b)))(input)
+        }
+        _ => Err(nom::Err::Error(DecodeError::UnknownTypeTag(tag))),
     }
 }
 
-fn read_char(input: &[u8]) -> IResult<&[u8], OscType, OscError> {
+/// Reads a char argument, see [`DecoderOptions::allow_high_byte_chars`].
+fn read_char<'a>(
+    input: &'a [u8],
+    options: &DecoderOptions,
+) -> IResult<&'a [u8], OscTypeRef<'a>, DecodeError> {
     map_res(
-        be_u32,
+        read_u32,
         |b| {
-            let opt_char = char::from_u32(b);
+            let opt_char = match b.to_be_bytes() {
+                [c, 0, 0, 0] if options.allow_high_byte_chars && c.is_ascii() => Some(c as char),
+                _ => char::from_u32(b),
+            };
             match opt_char {
-                Some(c) => Ok(OscType::Char(c)),
-                None => Err(OscError::BadArg(
-                    "Argument is not a char!".to_string(),
-                )),
+                Some(c) => Ok(OscTypeRef::Char(c)),
+                None => Err(DecodeError::InvalidChar(b)),
             }
         },
     )(input)
 }
 
-fn read_blob<'a>(input: &'a[u8], original_input: &'a[u8]) -> IResult<&'a[u8], OscType, OscError> {
-    let (input, size) = be_u32(input)?;
+fn read_blob<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    options: &DecoderOptions,
+) -> IResult<&'a [u8], OscTypeRef<'a>, DecodeError> {
+    let (input, size) = read_u32(input)?;
+    if size > i32::MAX as u32 {
+        return Err(nom::Err::Error(DecodeError::BadArg("Negative blob size".to_string())));
+    }
+    if size as usize > options.max_blob_size {
+        return Err(nom::Err::Error(DecodeError::BlobTooLarge {
+            size: size as usize,
+            max: options.max_blob_size,
+        }));
+    }
 
     map(
         terminated(
             take(size),
-            pad_to_32_bit_boundary(original_input),
+            pad_blob_to_32_bit_boundary(original_input),
         ),
-        |blob| OscType::Blob(blob.into()),
+        OscTypeRef::Blob,
     )(input)
 }
 
-fn read_time_tag<'a>(input: &'a[u8]) -> IResult<&'a[u8], OscTime, OscError> {
-    map(
-        tuple((be_u32, be_u32)),
-        |(seconds, fractional)| OscTime {
-            seconds,
-            fractional,
-        },
-    )(input)
+fn read_time_tag(input: &[u8]) -> IResult<&[u8], OscTime, DecodeError> {
+    map(read_bytes, |raw: [u8; 8]| OscTime::from_raw(u64::from_be_bytes(raw)))(input)
 }
 
-fn read_midi_message<'a>(input: &'a[u8]) -> IResult<&'a[u8], OscType, OscError> {
-    map(take(4usize), |buf: &[u8]| {
-        OscType::Midi(OscMidiMessage {
-            port: buf[0],
-            status: buf[1],
-            data1: buf[2],
-            data2: buf[3],
+fn read_midi_message<'a>(input: &'a [u8]) -> IResult<&'a [u8], OscTypeRef<'a>, DecodeError> {
+    map(read_bytes, |[port, status, data1, data2]: [u8; 4]| {
+        OscTypeRef::Midi(OscMidiMessage {
+            port,
+            status,
+            data1,
+            data2,
         })
     })(input)
 }
 
-fn read_osc_color<'a>(input: &'a[u8]) -> IResult<&'a[u8], OscType, OscError> {
-    map(take(4usize), |buf: &[u8]| {
-        OscType::Color(OscColor {
-            red: buf[0],
-            green: buf[1],
-            blue: buf[2],
-            alpha: buf[3],
+/// Reads the next `N` bytes, which fails like nom's number parsers if there are fewer left.
+fn read_bytes<const N: usize>(input: &[u8]) -> IResult<&[u8], [u8; N], DecodeError> {
+    match input.get(..N).map(<[u8; N]>::try_from) {
+        Some(Ok(bytes)) => Ok((&input[N..], bytes)),
+        _ => Err(nom::Err::Error(DecodeError::ReadError(ErrorKind::Eof))),
+    }
+}
+
+fn read_u32(input: &[u8]) -> IResult<&[u8], u32, DecodeError> {
+    map(read_bytes, u32::from_be_bytes)(input)
+}
+
+fn read_osc_color<'a>(input: &'a [u8]) -> IResult<&'a [u8], OscTypeRef<'a>, DecodeError> {
+    map(read_bytes, |[red, green, blue, alpha]: [u8; 4]| {
+        OscTypeRef::Color(OscColor {
+            red,
+            green,
+            blue,
+            alpha,
         })
     })(input)
 }
 
+/// Consumes the null terminator of a string and the zero bytes following it up to the next 32-bit
+/// boundary.
 fn pad_to_32_bit_boundary<'a>(
-    original_input: &'a[u8]
-) -> impl Fn(&'a[u8]) -> IResult<&'a[u8], (), OscError> {
+    original_input: &'a [u8]
+) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], (), DecodeError> {
     move |input| {
         let offset = 4 - original_input.offset(input) % 4;
-        let (input, _) = take(offset)(input)?;
+        let (input, padding) = take(offset)(input)?;
+        if padding.iter().any(|&b| b != 0u8) {
+            return Err(nom::Err::Error(DecodeError::BadString(
+                "String padding contains non-zero bytes",
+            )));
+        }
+        Ok((input, ()))
+    }
+}
+
+/// Consumes the zero bytes following a blob up to the next 32-bit boundary. Unlike strings, blobs
+/// are not null terminated so there is no padding if the blob already ends on a boundary.
+fn pad_blob_to_32_bit_boundary<'a>(
+    original_input: &'a [u8]
+) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], (), DecodeError> {
+    move |input| {
+        let offset = (4 - original_input.offset(input) % 4) % 4;
+        let (input, padding) = take(offset)(input)?;
+        if padding.iter().any(|&b| b != 0u8) {
+            return Err(nom::Err::Error(DecodeError::BadArg(
+                "Blob padding contains non-zero bytes".to_string(),
+            )));
+        }
         Ok((input, ()))
     }
 }
@@ -0,0 +1,1188 @@
+use crate::address::{verify_address, AddressParts, Matcher};
+use crate::errors::OscError;
+use crate::types::{OscMessage, OscPacket, OscTime, OscType};
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt::{self, Write};
+use core::mem;
+use std::net::SocketAddr;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+/// The handler of a method, which fails if it can't take the arguments of the message.
+type Handler = Box<dyn FnMut(&OscMessage) -> Result<(), OscError> + Send>;
+
+/// The handler of the messages that fail to invoke a method.
+type ErrorHandler = Box<dyn FnMut(&OscMessage, &OscError) + Send>;
+
+/// The handler of the messages that match no method, see
+/// [`AddressSpace::set_fallback_handler`].
+type FallbackHandler = Box<dyn FnMut(&OscMessage, &DispatchContext, Option<&OscError>) + Send>;
+
+/// A hook run before a message is dispatched, see [`AddressSpace::add_pre_hook`].
+type PreHook = Box<dyn FnMut(&OscMessage, &DispatchContext) -> HookAction + Send>;
+
+/// A hook run after a message was dispatched, see [`AddressSpace::add_post_hook`].
+type PostHook = Box<dyn FnMut(&OscMessage, &DispatchContext, usize, Duration) + Send>;
+
+/// A hook run after the namespace changed, see [`AddressSpace::add_change_hook`].
+type ChangeHook = Box<dyn FnMut(u64, &NamespaceChange) + Send>;
+
+/// A method of an [`AddressSpace`], i.e. a handler registered for an address.
+struct Method {
+    addr: String,
+    handler: Handler,
+}
+
+/// Dispatches messages to the methods whose address matches the address pattern of the message.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::dispatch::AddressSpace;
+/// use rosc::OscMessage;
+///
+/// let mut space = AddressSpace::new();
+/// space.add_method("/mixer/1/gain", |msg| println!("Gain 1: {:?}", msg.args)).unwrap();
+/// space.add_method("/mixer/2/gain", |msg| println!("Gain 2: {:?}", msg.args)).unwrap();
+///
+/// assert_eq!(space.dispatch(&OscMessage::from("/mixer/*/gain")).unwrap(), 2);
+/// assert_eq!(space.dispatch(&OscMessage::from("/mixer/3/gain")).unwrap(), 0);
+/// ```
+#[derive(Default)]
+pub struct AddressSpace {
+    methods: Vec<Method>,
+    // Handlers of every message, see `AddressSpace::register_any`
+    catch_all: Vec<Handler>,
+    info: BTreeMap<String, MethodInfo>,
+    error_handler: Option<ErrorHandler>,
+    fallback_handler: Option<FallbackHandler>,
+    pre_hooks: Vec<PreHook>,
+    post_hooks: Vec<PostHook>,
+    generation: u64,
+    changes: Vec<NamespaceChange>,
+    change_hooks: Vec<ChangeHook>,
+}
+
+/// A change of the namespace of an [`AddressSpace`], i.e. of the addresses methods are
+/// registered for, see [`AddressSpace::changes_since`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NamespaceChange {
+    /// The first handler for the address was registered.
+    Added(String),
+    /// The handlers for the address were removed.
+    Removed(String),
+}
+
+/// Where a dispatched message comes from, which is passed to the hooks of an [`AddressSpace`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DispatchContext {
+    /// The address the message was received from, if it was received from the network. Messages
+    /// received by a socket with a source filter are only dispatched if the filter accepted the
+    /// address, see [`OscUdpSocket::set_source_filter`](crate::net::OscUdpSocket).
+    pub peer: Option<SocketAddr>,
+    /// The time tag of the bundle containing the message, which for nested bundles that are
+    /// immediate is the one of the enclosing bundle, see [`OscPacket::into_messages`].
+    /// [`OscTime::IMMEDIATE`] if the message is not part of a bundle.
+    pub timetag: OscTime,
+    /// The time the message was received at, if it was received from the network, see
+    /// [`ReceivedPacket`](crate::net::ReceivedPacket).
+    pub received_at: Option<OscTime>,
+}
+
+impl Default for DispatchContext {
+    fn default() -> Self {
+        DispatchContext {
+            peer: None,
+            timetag: OscTime::IMMEDIATE,
+            received_at: None,
+        }
+    }
+}
+
+/// What to do with a message after running a pre-dispatch hook, see
+/// [`AddressSpace::add_pre_hook`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookAction {
+    /// Run the remaining hooks and dispatch the message.
+    Continue,
+    /// Drop the message without running the remaining hooks or invoking any method.
+    Drop,
+}
+
+/// The arguments a trigger method accepts, see [`AddressSpace::add_trigger_method`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TriggerArgs {
+    /// Only messages without arguments.
+    #[default]
+    None,
+    /// Messages without arguments or with only nil and impulse arguments, see
+    /// [`OscMessage::is_trigger`].
+    NilOrImpulse,
+}
+
+/// Which of several messages of a packet to the same address are dispatched, see
+/// [`DispatchOptions::duplicates`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// All of them in order, as the OSC specification requires, e.g. for the steps of a ramp.
+    #[default]
+    All,
+    /// Only the last one, e.g. to only apply the latest state of a control.
+    LastWins,
+    /// Only the first one.
+    FirstWins,
+}
+
+/// Options for [`AddressSpace::dispatch_packet_with_options`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DispatchOptions {
+    /// Which of several messages to the same address are dispatched. Defaults to
+    /// [`DuplicatePolicy::All`].
+    pub duplicates: DuplicatePolicy,
+}
+
+/// Describes a method of an [`AddressSpace`] for [`AddressSpace::to_oscquery_json`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MethodInfo {
+    /// The type tags of the arguments the method expects, without the leading comma.
+    pub type_tags: Option<String>,
+    /// The range of each argument, in the order of the type tags.
+    pub range: Vec<ValueRange>,
+    /// A human readable description of the method.
+    pub description: Option<String>,
+    /// Whether the value of the method can be read or written.
+    pub access: Access,
+}
+
+/// The range of values of an argument, either bound may be missing.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ValueRange {
+    /// The smallest value, `MIN` in OSCQuery.
+    pub min: Option<f64>,
+    /// The largest value, `MAX` in OSCQuery.
+    pub max: Option<f64>,
+}
+
+/// The access mode of a method, as defined by OSCQuery.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Access {
+    NoValue,
+    ReadOnly,
+    /// The default, as methods are invoked by sending messages to them.
+    #[default]
+    WriteOnly,
+    ReadWrite,
+}
+
+impl Access {
+    fn code(self) -> u8 {
+        match self {
+            Access::NoValue => 0,
+            Access::ReadOnly => 1,
+            Access::WriteOnly => 2,
+            Access::ReadWrite => 3,
+        }
+    }
+}
+
+/// A node of the address tree built by [`AddressSpace::to_oscquery_json`].
+#[derive(Default)]
+struct Node<'a> {
+    method: Option<Option<&'a MethodInfo>>,
+    contents: BTreeMap<&'a str, Node<'a>>,
+}
+
+impl AddressSpace {
+    /// Creates an empty address space.
+    pub fn new() -> Self {
+        AddressSpace::default()
+    }
+
+    /// Registers `handler` for the address `addr`, which must be a valid OSC address without
+    /// pattern characters. Several handlers may be registered for the same address, they are
+    /// invoked in the order they were added.
+    pub fn add_method<F>(&mut self, addr: &str, mut handler: F) -> Result<(), OscError>
+    where
+        F: FnMut(&OscMessage) + Send + 'static,
+    {
+        self.add_handler(addr, Box::new(move |msg| {
+            handler(msg);
+            Ok(())
+        }))
+    }
+
+    /// Registers `handler` for the address `addr` like [`add_method`](AddressSpace::add_method),
+    /// but passes the arguments of the message as the parameters of `handler`. Each parameter can
+    /// be of any type that implements `TryFrom<OscType>`, such as `f32`, `String` or `OscType`
+    /// itself, and there may be up to 8 of them.
+    ///
+    /// Messages whose arguments don't match the parameters, in number or in type, don't invoke
+    /// `handler` but the [error handler](AddressSpace::set_error_handler) instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::dispatch::AddressSpace;
+    /// use rosc::{OscMessage, OscType};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let levels = Arc::new(Mutex::new(vec![]));
+    /// let mut space = AddressSpace::new();
+    /// let recorded = levels.clone();
+    /// space
+    ///     .add_typed_method("/fader/1/level", move |level: f32| {
+    ///         recorded.lock().unwrap().push(level)
+    ///     })
+    ///     .unwrap();
+    ///
+    /// let msg = OscMessage {
+    ///     addr: "/fader/*/level".to_string(),
+    ///     args: vec![OscType::Float(0.5)],
+    /// };
+    /// assert_eq!(space.dispatch(&msg).unwrap(), 1);
+    /// assert_eq!(*levels.lock().unwrap(), vec![0.5]);
+    /// ```
+    pub fn add_typed_method<Args, F>(&mut self, addr: &str, mut handler: F) -> Result<(), OscError>
+    where
+        Args: FromArgs,
+        F: TypedHandler<Args>,
+    {
+        self.add_handler(addr, Box::new(move |msg| {
+            handler.call(Args::from_args(&msg.args)?);
+            Ok(())
+        }))
+    }
+
+    /// Registers `handler` for the address `addr` like [`add_method`](AddressSpace::add_method),
+    /// for trigger messages that carry no values. Messages with other arguments than `args`
+    /// accepts don't invoke `handler` but the [error handler](AddressSpace::set_error_handler)
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::dispatch::{AddressSpace, TriggerArgs};
+    /// use rosc::OscMessage;
+    ///
+    /// let mut space = AddressSpace::new();
+    /// space
+    ///     .add_trigger_method("/scene/next", TriggerArgs::None, || println!("Next scene"))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(space.dispatch(&OscMessage::trigger("/scene/next")).unwrap(), 1);
+    /// let mut msg = OscMessage::trigger("/scene/next");
+    /// msg.push_int(1);
+    /// assert_eq!(space.dispatch(&msg).unwrap(), 0);
+    /// ```
+    pub fn add_trigger_method<F>(
+        &mut self,
+        addr: &str,
+        args: TriggerArgs,
+        mut handler: F,
+    ) -> Result<(), OscError>
+    where
+        F: FnMut() + Send + 'static,
+    {
+        self.add_handler(addr, Box::new(move |msg| {
+            let accepted = match args {
+                TriggerArgs::None => msg.args.is_empty(),
+                TriggerArgs::NilOrImpulse => msg.is_trigger(),
+            };
+            if !accepted {
+                return Err(OscError::BadArg(format!(
+                    "Expected a trigger without values, found the arguments {:?}",
+                    msg.args
+                )));
+            }
+            handler();
+            Ok(())
+        }))
+    }
+
+    /// Registers `handler` for every message with a valid address pattern, regardless of the
+    /// addresses of the other methods, like a method whose address is matched by every pattern,
+    /// e.g. to log all messages. This is the dispatching counterpart of [`Matcher::any`]: the
+    /// address pattern is not matched against anything for it.
+    ///
+    /// Catch-all handlers are invoked in the order they were added, after the methods matching
+    /// the message, and are counted among the invoked handlers. They are not part of the
+    /// namespace, and don't keep the [fallback handler](AddressSpace::set_fallback_handler) from
+    /// being invoked for messages that match no method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::dispatch::AddressSpace;
+    /// use rosc::OscMessage;
+    ///
+    /// let mut space = AddressSpace::new();
+    /// space.add_method("/mixer/1/gain", |msg| println!("Gain 1: {:?}", msg.args)).unwrap();
+    /// space.register_any(|msg| println!("Received {}", msg.addr));
+    ///
+    /// assert_eq!(space.dispatch(&OscMessage::from("/mixer/1/gain")).unwrap(), 2);
+    /// assert_eq!(space.dispatch(&OscMessage::from("/transport/play")).unwrap(), 1);
+    /// assert_eq!(space.dispatch(&OscMessage::from("/")).unwrap(), 1);
+    /// assert_eq!(space.addresses().count(), 1);
+    /// ```
+    pub fn register_any<F>(&mut self, mut handler: F)
+    where
+        F: FnMut(&OscMessage) + Send + 'static,
+    {
+        self.catch_all.push(Box::new(move |msg| {
+            handler(msg);
+            Ok(())
+        }));
+    }
+
+    fn add_handler(&mut self, addr: &str, handler: Handler) -> Result<(), OscError> {
+        verify_address(addr)?;
+        let added = !self.methods.iter().any(|method| method.addr == addr);
+        self.methods.push(Method {
+            addr: addr.to_string(),
+            handler,
+        });
+        if added {
+            self.record_change(NamespaceChange::Added(addr.to_string()));
+        }
+        Ok(())
+    }
+
+    fn record_change(&mut self, change: NamespaceChange) {
+        self.generation += 1;
+        for hook in &mut self.change_hooks {
+            hook(self.generation, &change);
+        }
+        self.changes.push(change);
+    }
+
+    /// Returns the generation of the namespace, which starts at 0 and is incremented by each
+    /// change of the namespace, i.e. when a method is registered for a new address or when a
+    /// method is [removed](AddressSpace::remove_method). Registering another handler for an
+    /// address doesn't change the namespace.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the changes of the namespace after the generation `generation`, in the order they
+    /// were made, e.g. to update a view of the namespace that was taken at that generation.
+    /// Replaying the changes since generation 0 gives the current namespace, as every change is
+    /// kept for the lifetime of the address space.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::dispatch::{AddressSpace, NamespaceChange};
+    ///
+    /// let mut space = AddressSpace::new();
+    /// space.add_method("/gain", |_| {}).unwrap();
+    /// let generation = space.generation();
+    /// space.add_method("/gain", |_| {}).unwrap();
+    /// space.add_method("/mute", |_| {}).unwrap();
+    /// space.remove_method("/gain");
+    ///
+    /// assert_eq!(
+    ///     space.changes_since(generation),
+    ///     vec![
+    ///         NamespaceChange::Added("/mute".to_string()),
+    ///         NamespaceChange::Removed("/gain".to_string()),
+    ///     ]
+    /// );
+    /// assert_eq!(space.changes_since(space.generation()), vec![]);
+    /// ```
+    pub fn changes_since(&self, generation: u64) -> Vec<NamespaceChange> {
+        let start = (generation as usize).min(self.changes.len());
+        self.changes[start..].to_vec()
+    }
+
+    /// Adds `hook`, which is run with the new generation and the change after each change of the
+    /// namespace, see [`changes_since`](AddressSpace::changes_since). Hooks run in the order they
+    /// were added.
+    pub fn add_change_hook<F>(&mut self, hook: F)
+    where
+        F: FnMut(u64, &NamespaceChange) + Send + 'static,
+    {
+        self.change_hooks.push(Box::new(hook));
+    }
+
+    /// Sets the handler invoked with the message and the error for each method that can't take
+    /// the arguments of a dispatched message, see
+    /// [`add_typed_method`](AddressSpace::add_typed_method). Without one, such messages are
+    /// ignored by these methods.
+    pub fn set_error_handler<F>(&mut self, handler: F)
+    where
+        F: FnMut(&OscMessage, &OscError) + Send + 'static,
+    {
+        self.error_handler = Some(Box::new(handler));
+    }
+
+    /// Sets the handler invoked with the messages that match no method, e.g. to answer them with
+    /// an error message built with [`reply`]. It is passed where the message comes from and, if
+    /// the address pattern of the message is invalid, the error that
+    /// [`dispatch`](AddressSpace::dispatch) returns. Messages that match a method which can't
+    /// take their arguments are passed to the [error handler](AddressSpace::set_error_handler)
+    /// instead, and messages dropped by a [pre-dispatch hook](AddressSpace::add_pre_hook) to
+    /// neither.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::dispatch::{self, AddressSpace};
+    /// use rosc::OscMessage;
+    /// use std::sync::mpsc;
+    ///
+    /// let (replies, received) = mpsc::channel();
+    /// let mut space = AddressSpace::new();
+    /// space.add_method("/play", |_| println!("Play")).unwrap();
+    /// space.set_fallback_handler(move |msg, _, err| {
+    ///     let mut reply = dispatch::reply("/error", msg);
+    ///     reply.push_str(msg.addr.as_str());
+    ///     reply.push_str(match err {
+    ///         Some(_) => "invalid address",
+    ///         None => "not found",
+    ///     });
+    ///     replies.send(reply).unwrap();
+    /// });
+    ///
+    /// assert_eq!(space.dispatch(&OscMessage::from("/stop")).unwrap(), 0);
+    /// assert!(space.dispatch(&OscMessage::from("stop")).is_err());
+    /// let reasons: Vec<_> = received.try_iter().map(|reply| reply.args[1].clone()).collect();
+    /// assert_eq!(reasons, vec!["not found".into(), "invalid address".into()]);
+    /// ```
+    pub fn set_fallback_handler<F>(&mut self, handler: F)
+    where
+        F: FnMut(&OscMessage, &DispatchContext, Option<&OscError>) + Send + 'static,
+    {
+        self.fallback_handler = Some(Box::new(handler));
+    }
+
+    /// Adds `hook`, which is run with every message before it is dispatched. Hooks run in the
+    /// order they were added, and if one of them returns [`HookAction::Drop`] the message is
+    /// dropped: the remaining hooks, the methods and the post-dispatch hooks are not run, and the
+    /// dispatch invokes no handler. This can be used to filter, log or rate limit messages.
+    ///
+    /// Hooks only get to see the message and where it comes from, so they can't change the
+    /// methods of the address space while a message is being dispatched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::dispatch::{AddressSpace, HookAction};
+    /// use rosc::OscMessage;
+    ///
+    /// let mut space = AddressSpace::new();
+    /// space.add_method("/admin/reset", |_| println!("Reset")).unwrap();
+    /// space.add_pre_hook(|msg, context| match context.peer {
+    ///     Some(peer) if !peer.ip().is_loopback() && msg.addr.starts_with("/admin/") => {
+    ///         HookAction::Drop
+    ///     }
+    ///     _ => HookAction::Continue,
+    /// });
+    ///
+    /// assert_eq!(space.dispatch(&OscMessage::from("/admin/reset")).unwrap(), 1);
+    /// ```
+    pub fn add_pre_hook<F>(&mut self, hook: F)
+    where
+        F: FnMut(&OscMessage, &DispatchContext) -> HookAction + Send + 'static,
+    {
+        self.pre_hooks.push(Box::new(hook));
+    }
+
+    /// Adds `hook`, which is run with every message after it was dispatched, along with the
+    /// number of handlers invoked and the time it took to invoke them. Hooks run in the order they
+    /// were added. They are not run for messages that were dropped by a
+    /// [pre-dispatch hook](AddressSpace::add_pre_hook) or whose address pattern is invalid.
+    pub fn add_post_hook<F>(&mut self, hook: F)
+    where
+        F: FnMut(&OscMessage, &DispatchContext, usize, Duration) + Send + 'static,
+    {
+        self.post_hooks.push(Box::new(hook));
+    }
+
+    /// Removes all handlers registered for `addr` and returns how many there were.
+    pub fn remove_method(&mut self, addr: &str) -> usize {
+        let len = self.methods.len();
+        self.methods.retain(|method| method.addr != addr);
+        self.info.remove(addr);
+        let removed = len - self.methods.len();
+        if removed > 0 {
+            self.record_change(NamespaceChange::Removed(addr.to_string()));
+        }
+        removed
+    }
+
+    /// Annotates the method registered for `addr` with `info`, replacing any previous annotation.
+    /// Fails if no method is registered for `addr`.
+    pub fn describe_method(&mut self, addr: &str, info: MethodInfo) -> Result<(), OscError> {
+        if !self.methods.iter().any(|method| method.addr == addr) {
+            return Err(OscError::BadAddress(format!("No method registered for {}", addr)));
+        }
+        self.info.insert(addr.to_string(), info);
+        Ok(())
+    }
+
+    /// Returns the annotation of the method registered for `addr`, if any.
+    pub fn method_info(&self, addr: &str) -> Option<&MethodInfo> {
+        self.info.get(addr)
+    }
+
+    /// Describes the address space as an [OSCQuery](https://github.com/Vidvox/OSCQueryProposal)
+    /// namespace, i.e. a tree of `FULL_PATH`, `CONTENTS`, `TYPE`, `RANGE`, `DESCRIPTION` and
+    /// `ACCESS` attributes, with a container for each part of the method addresses.
+    ///
+    /// Containers and methods are sorted by name, so the output only depends on the registered
+    /// methods and not on the order they were added in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::dispatch::{AddressSpace, MethodInfo};
+    ///
+    /// let mut space = AddressSpace::new();
+    /// space.add_method("/gain", |_| {}).unwrap();
+    /// let info = MethodInfo {
+    ///     type_tags: Some("f".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// space.describe_method("/gain", info).unwrap();
+    ///
+    /// let json = space.to_oscquery_json();
+    /// assert!(json.contains(r#""FULL_PATH": "/gain""#));
+    /// assert!(json.contains(r#""TYPE": "f""#));
+    /// ```
+    pub fn to_oscquery_json(&self) -> String {
+        let mut json = String::new();
+        write_node(&mut json, &self.namespace(), "/", 0);
+        json.push('\n');
+        json
+    }
+
+    /// Describes the part of the namespace at `path`, i.e. a container or a method, like
+    /// [`to_oscquery_json`](AddressSpace::to_oscquery_json) describes the whole namespace, e.g. to
+    /// send only the subtrees that [changed](AddressSpace::changes_since). Returns `None` if there
+    /// is nothing at `path`, or if it is not a valid address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::dispatch::AddressSpace;
+    ///
+    /// let mut space = AddressSpace::new();
+    /// space.add_method("/mixer/1/gain", |_| {}).unwrap();
+    /// space.add_method("/transport/play", |_| {}).unwrap();
+    ///
+    /// let json = space.to_oscquery_json_at("/mixer").unwrap();
+    /// assert!(json.contains(r#""FULL_PATH": "/mixer/1/gain""#));
+    /// assert!(!json.contains("/transport"));
+    /// assert_eq!(space.to_oscquery_json_at("/mixer/2"), None);
+    /// ```
+    pub fn to_oscquery_json_at(&self, path: &str) -> Option<String> {
+        let parts = AddressParts::new(path).ok()?;
+        let root = self.namespace();
+        let mut node = &root;
+        for part in parts {
+            node = node.contents.get(part)?;
+        }
+        let mut json = String::new();
+        write_node(&mut json, node, path, 0);
+        json.push('\n');
+        Some(json)
+    }
+
+    /// Returns the tree of containers and methods of the namespace.
+    fn namespace(&self) -> Node<'_> {
+        let mut root = Node::default();
+        for method in &self.methods {
+            let mut node = &mut root;
+            // A method at `/` is the root container itself. The addresses of methods are valid
+            for part in AddressParts::new(&method.addr).into_iter().flatten() {
+                node = node.contents.entry(part).or_default();
+            }
+            node.method = Some(self.info.get(&method.addr));
+        }
+        root
+    }
+
+    /// Returns the addresses of all methods in the order they were added, the address of a method
+    /// with several handlers is returned once per handler.
+    pub fn addresses(&self) -> impl Iterator<Item = &str> {
+        self.methods.iter().map(|method| method.addr.as_str())
+    }
+
+    /// Invokes the handlers of all methods matching the address pattern of `msg` and returns how
+    /// many were invoked. Fails if the address pattern is invalid, in which case no handler is
+    /// invoked. Methods that can't take the arguments of `msg` are not counted.
+    ///
+    /// The hooks are run with a default [`DispatchContext`], use
+    /// [`dispatch_with_context`](AddressSpace::dispatch_with_context) to tell them where `msg`
+    /// comes from.
+    pub fn dispatch(&mut self, msg: &OscMessage) -> Result<usize, OscError> {
+        self.dispatch_with_context(msg, &DispatchContext::default())
+    }
+
+    /// Dispatches `msg` like [`dispatch`](AddressSpace::dispatch), running the hooks with
+    /// `context`. Returns 0 if a pre-dispatch hook dropped the message.
+    pub fn dispatch_with_context(
+        &mut self,
+        msg: &OscMessage,
+        context: &DispatchContext,
+    ) -> Result<usize, OscError> {
+        let matcher = match Matcher::new(&msg.addr) {
+            Ok(matcher) => matcher,
+            Err(err) => {
+                let err = OscError::from(err);
+                if let Some(fallback_handler) = &mut self.fallback_handler {
+                    fallback_handler(msg, context, Some(&err));
+                }
+                return Err(err);
+            }
+        };
+        for hook in &mut self.pre_hooks {
+            if hook(msg, context) == HookAction::Drop {
+                return Ok(0);
+            }
+        }
+        let start = Instant::now();
+        let mut invoked = 0;
+        let mut matched = false;
+        for method in &mut self.methods {
+            if matcher.match_address(&method.addr)? {
+                matched = true;
+                match (method.handler)(msg) {
+                    Ok(()) => invoked += 1,
+                    Err(err) => {
+                        if let Some(error_handler) = &mut self.error_handler {
+                            error_handler(msg, &err);
+                        }
+                    }
+                }
+            }
+        }
+        for handler in &mut self.catch_all {
+            if handler(msg).is_ok() {
+                invoked += 1;
+            }
+        }
+        if !matched {
+            if let Some(fallback_handler) = &mut self.fallback_handler {
+                fallback_handler(msg, context, None);
+            }
+        }
+        let elapsed = start.elapsed();
+        for hook in &mut self.post_hooks {
+            hook(msg, context, invoked, elapsed);
+        }
+        Ok(invoked)
+    }
+
+    /// Dispatches every message of `packet`, including those of nested bundles, immediately and
+    /// regardless of their time tags. Returns the total number of handlers invoked, or the first
+    /// error, in which case the subsequent messages are not dispatched.
+    ///
+    /// The hooks are passed the time tag of the bundle containing each message, see
+    /// [`DispatchContext::timetag`].
+    pub fn dispatch_packet(&mut self, packet: &OscPacket) -> Result<usize, OscError> {
+        self.dispatch_packet_with_options(packet, &DispatchOptions::default())
+    }
+
+    /// Dispatches the messages of `packet` like [`dispatch_packet`](AddressSpace::dispatch_packet),
+    /// using the given options.
+    ///
+    /// With [`DuplicatePolicy::LastWins`] or [`DuplicatePolicy::FirstWins`], messages with the
+    /// same address as another one of the packet, including those of nested bundles, are dropped
+    /// without running any hooks. Which one is last or first is told by the time tags of their
+    /// bundles, and by their order for messages with the same time tag. The remaining messages
+    /// are dispatched in the order they appear in the packet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::dispatch::{AddressSpace, DispatchOptions, DuplicatePolicy};
+    /// use rosc::{OscBundle, OscMessage, OscPacket, OscTime};
+    ///
+    /// let mut space = AddressSpace::new();
+    /// space.add_typed_method("/fader", |value: f32| println!("Fader: {}", value)).unwrap();
+    ///
+    /// let fader = |value: f32| {
+    ///     let mut msg = OscMessage::from("/fader");
+    ///     msg.push_float(value);
+    ///     OscPacket::Message(msg)
+    /// };
+    /// let packet = OscPacket::Bundle(OscBundle {
+    ///     timetag: OscTime::IMMEDIATE,
+    ///     content: vec![fader(0.1), fader(0.2), fader(0.3)],
+    /// });
+    /// let options = DispatchOptions {
+    ///     duplicates: DuplicatePolicy::LastWins,
+    /// };
+    /// // Only prints "Fader: 0.3"
+    /// assert_eq!(space.dispatch_packet_with_options(&packet, &options).unwrap(), 1);
+    /// ```
+    pub fn dispatch_packet_with_options(
+        &mut self,
+        packet: &OscPacket,
+        options: &DispatchOptions,
+    ) -> Result<usize, OscError> {
+        let messages = retain_duplicates(packet_messages(packet), options.duplicates);
+        let mut invoked = 0;
+        for (timetag, msg) in messages {
+            let context = DispatchContext {
+                timetag,
+                ..Default::default()
+            };
+            invoked += self.dispatch_with_context(msg, &context)?
+        }
+        Ok(invoked)
+    }
+}
+
+/// Returns the messages of `packet` in order, including those of nested bundles, along with the
+/// time tags they take effect at, see [`DispatchContext::timetag`].
+fn packet_messages(packet: &OscPacket) -> Vec<(OscTime, &OscMessage)> {
+    let mut messages = Vec::new();
+    let mut packets = vec![(OscTime::IMMEDIATE, packet)];
+    while let Some((timetag, packet)) = packets.pop() {
+        match packet {
+            OscPacket::Message(msg) => messages.push((timetag, msg)),
+            OscPacket::Bundle(bundle) => {
+                let timetag = match bundle.timetag.is_immediate() {
+                    true => timetag,
+                    false => bundle.timetag,
+                };
+                packets.extend(bundle.content.iter().rev().map(|packet| (timetag, packet)))
+            }
+        }
+    }
+    messages
+}
+
+/// Removes the messages that `policy` drops from the messages of a packet.
+fn retain_duplicates(
+    mut messages: Vec<(OscTime, &OscMessage)>,
+    policy: DuplicatePolicy,
+) -> Vec<(OscTime, &OscMessage)> {
+    if policy == DuplicatePolicy::All {
+        return messages;
+    }
+    // The index of the message that is kept for each address
+    let mut kept: BTreeMap<&str, usize> = BTreeMap::new();
+    for (index, &(timetag, msg)) in messages.iter().enumerate() {
+        let replace = match kept.get(msg.addr.as_str()) {
+            None => true,
+            Some(&other) => match policy {
+                DuplicatePolicy::LastWins => timetag >= messages[other].0,
+                _ => timetag < messages[other].0,
+            },
+        };
+        if replace {
+            kept.insert(&msg.addr, index);
+        }
+    }
+    let mut index = 0;
+    messages.retain(|(_, msg)| {
+        index += 1;
+        kept[msg.addr.as_str()] == index - 1
+    });
+    messages
+}
+
+impl fmt::Debug for AddressSpace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AddressSpace")
+            .field("methods", &self.addresses().collect::<Vec<_>>())
+            .field("catch_all", &self.catch_all.len())
+            .field("info", &self.info)
+            .field("error_handler", &self.error_handler.is_some())
+            .field("fallback_handler", &self.fallback_handler.is_some())
+            .field("pre_hooks", &self.pre_hooks.len())
+            .field("post_hooks", &self.post_hooks.len())
+            .field("generation", &self.generation)
+            .field("change_hooks", &self.change_hooks.len())
+            .finish()
+    }
+}
+
+/// The handler of a route of a [`RouteTable`], which may be invoked from several threads at once.
+type SharedHandler = Arc<dyn Fn(&OscMessage) + Send + Sync>;
+
+/// A handler registered for an address of a [`RouteTable`].
+#[derive(Clone)]
+struct Route {
+    addr: String,
+    handler: SharedHandler,
+}
+
+/// Collects the routes of a [`RouteTable`], see [`RouteTable::builder`].
+#[derive(Clone, Default)]
+pub struct RouteTableBuilder {
+    routes: Vec<Route>,
+}
+
+impl RouteTableBuilder {
+    /// Registers `handler` for the address `addr` like [`AddressSpace::add_method`]. The handler
+    /// only gets shared access to its state, as it may be invoked from several threads at once.
+    pub fn add_method<F>(&mut self, addr: &str, handler: F) -> Result<(), OscError>
+    where
+        F: Fn(&OscMessage) + Send + Sync + 'static,
+    {
+        verify_address(addr)?;
+        self.routes.push(Route {
+            addr: addr.to_string(),
+            handler: Arc::new(handler),
+        });
+        Ok(())
+    }
+
+    /// Removes all handlers registered for `addr` and returns how many there were.
+    pub fn remove_method(&mut self, addr: &str) -> usize {
+        let len = self.routes.len();
+        self.routes.retain(|route| route.addr != addr);
+        len - self.routes.len()
+    }
+
+    /// Returns the table of the registered routes.
+    pub fn build(self) -> RouteTable {
+        RouteTable {
+            routes: self.routes.into(),
+        }
+    }
+}
+
+impl fmt::Debug for RouteTableBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RouteTableBuilder")
+            .field("routes", &self.routes.iter().map(|route| &route.addr).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// An immutable set of methods that messages can be dispatched to from several threads at once,
+/// e.g. by the workers of a server. Unlike [`AddressSpace`], dispatching only needs shared
+/// access, and cloning a table is cheap as the clones share their routes. Use a
+/// [`SharedAddressSpace`] to replace the table while messages are being dispatched.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::dispatch::RouteTable;
+/// use rosc::OscMessage;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let count = Arc::new(AtomicUsize::new(0));
+/// let mut builder = RouteTable::builder();
+/// let counted = count.clone();
+/// builder
+///     .add_method("/ping", move |_| {
+///         counted.fetch_add(1, Ordering::Relaxed);
+///     })
+///     .unwrap();
+/// let table = builder.build();
+///
+/// let workers: Vec<_> = (0..4)
+///     .map(|_| {
+///         let table = table.clone();
+///         thread::spawn(move || table.dispatch(&OscMessage::from("/ping")).unwrap())
+///     })
+///     .collect();
+/// for worker in workers {
+///     assert_eq!(worker.join().unwrap(), 1);
+/// }
+/// assert_eq!(count.load(Ordering::Relaxed), 4);
+/// ```
+#[derive(Clone, Default)]
+pub struct RouteTable {
+    routes: Arc<[Route]>,
+}
+
+impl RouteTable {
+    /// Returns a builder for a new table.
+    pub fn builder() -> RouteTableBuilder {
+        RouteTableBuilder::default()
+    }
+
+    /// Returns a builder with the routes of this table, e.g. to add a route to a table that is
+    /// in use without affecting it.
+    pub fn to_builder(&self) -> RouteTableBuilder {
+        RouteTableBuilder {
+            routes: self.routes.to_vec(),
+        }
+    }
+
+    /// Invokes the handlers of all routes matching the address pattern of `msg` and returns how
+    /// many were invoked. Fails if the address pattern is invalid, in which case no handler is
+    /// invoked.
+    pub fn dispatch(&self, msg: &OscMessage) -> Result<usize, OscError> {
+        let matcher = Matcher::new(&msg.addr)?;
+        let mut invoked = 0;
+        for route in self.routes.iter() {
+            if matcher.match_address(&route.addr)? {
+                (route.handler)(msg);
+                invoked += 1;
+            }
+        }
+        Ok(invoked)
+    }
+
+    /// Dispatches every message of `packet`, including those of nested bundles, like
+    /// [`AddressSpace::dispatch_packet`].
+    pub fn dispatch_packet(&self, packet: &OscPacket) -> Result<usize, OscError> {
+        let mut invoked = 0;
+        for (_, msg) in packet_messages(packet) {
+            invoked += self.dispatch(msg)?;
+        }
+        Ok(invoked)
+    }
+
+    /// Returns the addresses of all routes in the order they were added, the address of a route
+    /// with several handlers is returned once per handler.
+    pub fn addresses(&self) -> impl Iterator<Item = &str> {
+        self.routes.iter().map(|route| route.addr.as_str())
+    }
+}
+
+impl fmt::Debug for RouteTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RouteTable")
+            .field("routes", &self.addresses().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// A [`RouteTable`] that can be replaced at runtime while other threads dispatch messages to it.
+///
+/// Dispatching takes the current table and releases the lock before invoking any handler, so
+/// dispatching threads don't wait for each other or for handlers, and a
+/// [`reload`](SharedAddressSpace::reload) only waits for dispatching threads to take the table.
+/// Messages being dispatched while the table is replaced are dispatched to either the old or the
+/// new table as a whole, never to a mix of both.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::dispatch::{RouteTable, SharedAddressSpace};
+/// use rosc::OscMessage;
+///
+/// let space = SharedAddressSpace::new(RouteTable::default());
+/// assert_eq!(space.dispatch(&OscMessage::from("/ping")).unwrap(), 0);
+///
+/// let mut builder = space.table().to_builder();
+/// builder.add_method("/ping", |_| println!("pong")).unwrap();
+/// space.reload(builder.build());
+/// assert_eq!(space.dispatch(&OscMessage::from("/ping")).unwrap(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct SharedAddressSpace {
+    table: RwLock<RouteTable>,
+}
+
+impl SharedAddressSpace {
+    /// Creates an address space dispatching to `table`.
+    pub fn new(table: RouteTable) -> Self {
+        SharedAddressSpace {
+            table: RwLock::new(table),
+        }
+    }
+
+    /// Returns the current table.
+    pub fn table(&self) -> RouteTable {
+        // The lock is never held while running foreign code, so a poisoned lock is still intact
+        self.table.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Replaces the table and returns the previous one. Messages that are being dispatched to the
+    /// previous table are not affected.
+    pub fn reload(&self, table: RouteTable) -> RouteTable {
+        let mut current = self.table.write().unwrap_or_else(PoisonError::into_inner);
+        mem::replace(&mut *current, table)
+    }
+
+    /// Dispatches `msg` to the current table, see [`RouteTable::dispatch`].
+    pub fn dispatch(&self, msg: &OscMessage) -> Result<usize, OscError> {
+        self.table().dispatch(msg)
+    }
+
+    /// Dispatches every message of `packet` to the current table, see
+    /// [`RouteTable::dispatch_packet`]. All messages of the packet are dispatched to the same
+    /// table.
+    pub fn dispatch_packet(&self, packet: &OscPacket) -> Result<usize, OscError> {
+        self.table().dispatch_packet(packet)
+    }
+}
+
+/// Returns a message without arguments that answers `msg`, e.g. from a
+/// [fallback handler](AddressSpace::set_fallback_handler). Its address is `template`, in which
+/// each `{addr}` is replaced with the address pattern of `msg`.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::dispatch::reply;
+/// use rosc::OscMessage;
+///
+/// let msg = OscMessage::from("/mixer/1/gain");
+/// assert_eq!(reply("/error", &msg).addr, "/error");
+/// assert_eq!(reply("{addr}/reply", &msg).addr, "/mixer/1/gain/reply");
+/// ```
+pub fn reply(template: &str, msg: &OscMessage) -> OscMessage {
+    OscMessage {
+        addr: template.replace("{addr}", &msg.addr),
+        args: Vec::new(),
+    }
+}
+
+/// The parameters of a handler registered with [`AddressSpace::add_typed_method`], which are
+/// taken from the arguments of a message. Implemented for tuples of up to 8 types that implement
+/// `TryFrom<OscType>`.
+pub trait FromArgs: Sized {
+    /// Converts `args`, failing if there are more or fewer arguments than parameters or if one of
+    /// them can't be converted.
+    fn from_args(args: &[OscType]) -> Result<Self, OscError>;
+}
+
+/// A handler taking the parameters `Args`, see [`AddressSpace::add_typed_method`]. Implemented
+/// for closures taking up to 8 parameters.
+pub trait TypedHandler<Args>: Send + 'static {
+    /// Invokes the handler with `args`.
+    fn call(&mut self, args: Args);
+}
+
+/// Converts the argument at `index`, which must be in range.
+fn arg<A>(args: &[OscType], index: usize) -> Result<A, OscError>
+where
+    A: TryFrom<OscType>,
+    OscError: From<A::Error>,
+{
+    Ok(A::try_from(args[index].clone())?)
+}
+
+macro_rules! typed_handler_impl {
+    ($count:expr; $($arg:ident $index:expr),*) => {
+        impl<$($arg),*> FromArgs for ($($arg,)*)
+        where
+            $($arg: TryFrom<OscType>, OscError: From<$arg::Error>,)*
+        {
+            fn from_args(args: &[OscType]) -> Result<Self, OscError> {
+                if args.len() != $count {
+                    return Err(OscError::BadArg(format!(
+                        "Expected {} arguments, found {}",
+                        $count,
+                        args.len()
+                    )));
+                }
+                Ok(($(arg::<$arg>(args, $index)?,)*))
+            }
+        }
+
+        impl<F, $($arg),*> TypedHandler<($($arg,)*)> for F
+        where
+            F: FnMut($($arg),*) + Send + 'static,
+        {
+            #[allow(non_snake_case)]
+            fn call(&mut self, ($($arg,)*): ($($arg,)*)) {
+                self($($arg),*)
+            }
+        }
+    };
+}
+
+typed_handler_impl!(0;);
+typed_handler_impl!(1; A 0);
+typed_handler_impl!(2; A 0, B 1);
+typed_handler_impl!(3; A 0, B 1, C 2);
+typed_handler_impl!(4; A 0, B 1, C 2, D 3);
+typed_handler_impl!(5; A 0, B 1, C 2, D 3, E 4);
+typed_handler_impl!(6; A 0, B 1, C 2, D 3, E 4, G 5);
+typed_handler_impl!(7; A 0, B 1, C 2, D 3, E 4, G 5, H 6);
+typed_handler_impl!(8; A 0, B 1, C 2, D 3, E 4, G 5, H 6, I 7);
+
+/// Writes `node` as a pretty-printed JSON object indented by `depth` levels.
+fn write_node(json: &mut String, node: &Node, path: &str, depth: usize) {
+    let indent = "  ".repeat(depth + 1);
+    let mut attributes = vec![format!("\"FULL_PATH\": {}", json_string(path))];
+    if let Some(info) = node.method {
+        let info = info.cloned().unwrap_or_default();
+        if let Some(type_tags) = &info.type_tags {
+            attributes.push(format!("\"TYPE\": {}", json_string(type_tags)));
+        }
+        if !info.range.is_empty() {
+            let ranges: Vec<String> = info
+                .range
+                .iter()
+                .map(|range| {
+                    let mut bounds = vec![];
+                    if let Some(min) = range.min {
+                        bounds.push(format!("\"MIN\": {}", json_number(min)));
+                    }
+                    if let Some(max) = range.max {
+                        bounds.push(format!("\"MAX\": {}", json_number(max)));
+                    }
+                    format!("{{{}}}", bounds.join(", "))
+                })
+                .collect();
+            attributes.push(format!("\"RANGE\": [{}]", ranges.join(", ")));
+        }
+        if let Some(description) = &info.description {
+            attributes.push(format!("\"DESCRIPTION\": {}", json_string(description)));
+        }
+        attributes.push(format!("\"ACCESS\": {}", info.access.code()));
+    }
+
+    json.push_str("{\n");
+    for attribute in &attributes {
+        let _ = writeln!(json, "{}{},", indent, attribute);
+    }
+    if node.contents.is_empty() {
+        // Drop the comma after the last attribute
+        json.truncate(json.len() - 2);
+        json.push('\n');
+    } else {
+        let _ = writeln!(json, "{}\"CONTENTS\": {{", indent);
+        for (i, (name, child)) in node.contents.iter().enumerate() {
+            let child_path = match path {
+                "/" => format!("/{}", name),
+                _ => format!("{}/{}", path, name),
+            };
+            let _ = write!(json, "{}  {}: ", indent, json_string(name));
+            write_node(json, child, &child_path, depth + 2);
+            json.push_str(if i + 1 < node.contents.len() { ",\n" } else { "\n" });
+        }
+        let _ = writeln!(json, "{}}}", indent);
+    }
+    json.push_str(&"  ".repeat(depth));
+    json.push('}');
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Formats `x` as a JSON number, which can't represent NaN or the infinities.
+fn json_number(x: f64) -> String {
+    if x.is_finite() {
+        format!("{}", x)
+    } else {
+        "null".to_string()
+    }
+}
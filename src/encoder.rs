@@ -1,12 +1,80 @@
 use crate::errors::OscError;
-use crate::types::{OscBundle, OscMessage, OscPacket, OscTime, OscType, Result};
-use crate::alloc::{string::{ String, ToString }, vec::Vec};
+use crate::profile::Profile;
+use crate::types::{
+    OscArray, OscArrayRef, OscPacket, OscPacketRef, OscTime, OscType, OscTypeRef,
+    StaticOscMessage,
+};
+use crate::alloc::{string::String, vec::Vec};
 
-use byteorder::{BigEndian, ByteOrder};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(all(not(feature = "std"), feature = "core-error"))]
+use core::error;
+
+type Result<T> = core::result::Result<T, EncodeError>;
+
+/// Largest blob that can be encoded, as its size is written as an `i32`.
+pub const MAX_BLOB_SIZE: usize = i32::MAX as usize;
+
+/// Size of the `#bundle` tag and the time tag of an encoded bundle.
+pub(crate) const BUNDLE_HEADER_SIZE: usize = 16;
+
+/// Errors of the encoder, which convert into the corresponding [`OscError`] variants.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EncodeError {
+    /// A blob is larger than the [`MAX_BLOB_SIZE`] bytes its size field can hold.
+    BlobTooLarge { size: usize },
+    /// An argument has a type tag that is not allowed by [`EncoderOptions::profile`].
+    UnsupportedTypeTag(char),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodeError::BlobTooLarge { size } => write!(
+                f,
+                "OSC blob of {} bytes exceeds the limit of {} bytes",
+                size, MAX_BLOB_SIZE
+            ),
+            EncodeError::UnsupportedTypeTag(tag) => {
+                write!(f, "OSC type tag {:?} is not supported by the profile", tag)
+            }
+        }
+    }
+}
+
+impl EncodeError {
+    /// Returns the stable numeric code of the error: 2001 for
+    /// [`BlobTooLarge`](EncodeError::BlobTooLarge) and 2002 for
+    /// [`UnsupportedTypeTag`](EncodeError::UnsupportedTypeTag). Codes are never reused or changed.
+    pub fn code(&self) -> u32 {
+        match self {
+            EncodeError::BlobTooLarge { .. } => 2001,
+            EncodeError::UnsupportedTypeTag(_) => 2002,
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "core-error"))]
+impl error::Error for EncodeError {}
+
+impl From<EncodeError> for OscError {
+    fn from(err: EncodeError) -> Self {
+        match err {
+            EncodeError::BlobTooLarge { size } => OscError::BlobTooLarge {
+                size,
+                max: MAX_BLOB_SIZE,
+            },
+            EncodeError::UnsupportedTypeTag(tag) => OscError::UnsupportedTypeTag(tag),
+        }
+    }
+}
 
 /// Takes a reference to an OSC packet and returns
 /// a byte vector on success. If the packet was invalid
-/// an `OscError` is returned.
+/// an `EncodeError` is returned.
 ///
 /// # Example
 ///
@@ -21,161 +89,384 @@ use byteorder::{BigEndian, ByteOrder};
 /// );
 /// assert!(encoder::encode(&packet).is_ok())
 /// ```
-pub fn encode(packet: &OscPacket) -> Result<Vec<u8>> {
-    match *packet {
-        OscPacket::Message(ref msg) => encode_message(msg),
-        OscPacket::Bundle(ref bundle) => encode_bundle(bundle),
+pub fn encode(packet: &OscPacket) -> core::result::Result<Vec<u8>, EncodeError> {
+    let mut bytes = Vec::new();
+    encode_into(packet, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Encodes `packet` like [`encode`] but appends it to `buf` instead of allocating a new vector,
+/// returning the number of bytes appended. Reusing a buffer that has grown large enough makes
+/// encoding allocation free.
+///
+/// If an error is returned, `buf` is left unchanged.
+///
+/// # Example
+///
+/// ```
+/// use rosc::{encoder, OscMessage, OscPacket};
+///
+/// let mut buf = Vec::with_capacity(1024);
+/// for i in 0..3 {
+///     buf.clear();
+///     let packet = OscPacket::Message(OscMessage::from(format!("/channel/{}", i).as_str()));
+///     assert_eq!(encoder::encode_into(&packet, &mut buf).unwrap(), 16);
+/// }
+/// ```
+pub fn encode_into(
+    packet: &OscPacket,
+    buf: &mut Vec<u8>,
+) -> core::result::Result<usize, EncodeError> {
+    let start = buf.len();
+    match write_packet(packet, buf) {
+        Ok(()) => Ok(buf.len() - start),
+        Err(err) => {
+            buf.truncate(start);
+            Err(err)
+        }
     }
 }
 
-fn encode_message(msg: &OscMessage) -> Result<Vec<u8>> {
-    let mut msg_bytes: Vec<u8> = Vec::new();
+/// Options for [`encode_with_options`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EncoderOptions {
+    /// The features the receiver supports, see [`Profile`]. Packets with arguments whose type
+    /// tags the profile doesn't allow are rejected with [`EncodeError::UnsupportedTypeTag`].
+    /// Defaults to [`Profile::FULL_1_1`], which allows every argument.
+    pub profile: Profile,
+}
 
-    msg_bytes.extend(encode_string(msg.addr.clone()));
-    let mut type_tags: Vec<char> = vec![','];
-    let mut arg_bytes: Vec<u8> = Vec::new();
+/// Encodes `packet` like [`encode`], using the given options.
+///
+/// # Example
+///
+/// ```
+/// use rosc::encoder::{self, EncodeError, EncoderOptions};
+/// use rosc::profile::Profile;
+/// use rosc::{OscMessage, OscPacket, OscType};
+///
+/// let options = EncoderOptions {
+///     profile: Profile::CORE_1_0,
+/// };
+/// let packet = OscPacket::Message(OscMessage {
+///     addr: "/position".to_string(),
+///     args: vec![OscType::Double(0.5)],
+/// });
+/// assert_eq!(
+///     encoder::encode_with_options(&packet, &options),
+///     Err(EncodeError::UnsupportedTypeTag('d'))
+/// );
+/// ```
+pub fn encode_with_options(
+    packet: &OscPacket,
+    options: &EncoderOptions,
+) -> core::result::Result<Vec<u8>, EncodeError> {
+    check_packet_profile(packet, &options.profile)?;
+    encode(packet)
+}
 
-    for arg in &msg.args {
-        let (bytes, tags): (Option<Vec<u8>>, String) = encode_arg(arg)?;
+/// Checks that the type tags of the arguments of `packet` are allowed by `profile`.
+fn check_packet_profile(packet: &OscPacket, profile: &Profile) -> Result<()> {
+    match *packet {
+        OscPacket::Message(ref msg) => check_args_profile(&msg.args, profile),
+        OscPacket::Bundle(ref bundle) => bundle
+            .content
+            .iter()
+            .try_for_each(|packet| check_packet_profile(packet, profile)),
+    }
+}
 
-        type_tags.extend(tags.chars());
-        if let Some(data) = bytes {
-            arg_bytes.extend(data);
+fn check_args_profile(args: &[OscType], profile: &Profile) -> Result<()> {
+    let mut tags = Vec::new();
+    for arg in args {
+        tags.clear();
+        arg.write_type_tags(&mut tags);
+        // Extension tags are not part of any profile
+        let unsupported = tags
+            .iter()
+            .map(|&tag| tag as char)
+            .find(|&tag| tag != 'u' && !profile.supports_tag(tag));
+        if let Some(tag) = unsupported {
+            return Err(EncodeError::UnsupportedTypeTag(tag));
         }
     }
+    Ok(())
+}
 
-    msg_bytes.extend(encode_string(type_tags.into_iter().collect::<String>()));
-    if !arg_bytes.is_empty() {
-        msg_bytes.extend(arg_bytes);
+/// Encodes a borrowed packet like [`encode`], e.g. one built with [`OscMessageRef::new`] from a
+/// `&'static str` address, which doesn't have to be copied into a `String` first.
+///
+/// [`OscMessageRef::new`]: crate::OscMessageRef::new
+///
+/// # Example
+///
+/// ```
+/// use rosc::{encoder, OscMessage, OscMessageRef, OscPacket, OscPacketRef, OscTypeRef};
+///
+/// let msg = OscMessageRef::new("/transport/beat", vec![OscTypeRef::Int(1)]);
+/// let packet = OscPacketRef::Message(msg);
+/// let owned = OscPacket::Message(OscMessage {
+///     addr: "/transport/beat".to_string(),
+///     args: vec![1.into()],
+/// });
+/// assert_eq!(encoder::encode_ref(&packet).unwrap(), encoder::encode(&owned).unwrap());
+/// ```
+pub fn encode_ref(packet: &OscPacketRef) -> core::result::Result<Vec<u8>, EncodeError> {
+    let mut bytes = Vec::new();
+    encode_ref_into(packet, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Encodes a borrowed packet like [`encode_into`], appending it to `buf`.
+pub fn encode_ref_into(
+    packet: &OscPacketRef,
+    buf: &mut Vec<u8>,
+) -> core::result::Result<usize, EncodeError> {
+    let start = buf.len();
+    match write_packet_ref(packet, buf) {
+        Ok(()) => Ok(buf.len() - start),
+        Err(err) => {
+            buf.truncate(start);
+            Err(err)
+        }
     }
-    Ok(msg_bytes)
 }
 
-fn encode_bundle(bundle: &OscBundle) -> Result<Vec<u8>> {
-    let mut bundle_bytes: Vec<u8> = Vec::new();
-    bundle_bytes.extend(encode_string("#bundle".to_string()).into_iter());
+/// Encodes a [`StaticOscMessage`] like [`encode`].
+pub fn encode_static(msg: &StaticOscMessage) -> core::result::Result<Vec<u8>, EncodeError> {
+    let mut bytes = Vec::new();
+    encode_static_into(msg, &mut bytes)?;
+    Ok(bytes)
+}
 
-    match encode_arg(&OscType::Time(bundle.timetag))? {
-        (Some(x), _) => {
-            bundle_bytes.extend(x.into_iter());
+/// Encodes a [`StaticOscMessage`] like [`encode_into`], appending it to `buf`. Nothing but `buf`
+/// is allocated, so a message defined as a `static` can be sent without allocating at all once
+/// `buf` has grown large enough.
+pub fn encode_static_into(
+    msg: &StaticOscMessage,
+    buf: &mut Vec<u8>,
+) -> core::result::Result<usize, EncodeError> {
+    let start = buf.len();
+    match write_message(msg.addr, msg.args, buf) {
+        Ok(()) => Ok(buf.len() - start),
+        Err(err) => {
+            buf.truncate(start);
+            Err(err)
         }
-        (None, _) => {
-            return Err(OscError::BadBundle("Missing time tag!".to_string()));
+    }
+}
+
+fn write_packet(packet: &OscPacket, out: &mut Vec<u8>) -> Result<()> {
+    match *packet {
+        OscPacket::Message(ref msg) => write_message(&msg.addr, &msg.args, out),
+        OscPacket::Bundle(ref bundle) => {
+            write_bundle(bundle.timetag, &bundle.content, write_packet, out)
         }
     }
+}
+
+fn write_packet_ref(packet: &OscPacketRef, out: &mut Vec<u8>) -> Result<()> {
+    match *packet {
+        OscPacketRef::Message(ref msg) => write_message(&msg.addr, &msg.args, out),
+        OscPacketRef::Bundle(ref bundle) => {
+            write_bundle(bundle.timetag, &bundle.content, write_packet_ref, out)
+        }
+    }
+}
+
+/// An argument the encoder can write, which is either an [`OscType`] or an [`OscTypeRef`].
+trait Arg {
+    /// Writes the type tag of the argument, or those of its contents if it is an array.
+    fn write_type_tags(&self, out: &mut Vec<u8>);
+    /// Writes the data of the argument.
+    fn write_data(&self, out: &mut Vec<u8>) -> Result<()>;
+}
+
+fn write_message<A: Arg>(addr: &str, args: &[A], out: &mut Vec<u8>) -> Result<()> {
+    write_string(addr, out);
+
+    // The type tags are written from the arguments directly, followed by their data
+    let type_tags_start = out.len();
+    out.push(b',');
+    for arg in args {
+        arg.write_type_tags(out);
+    }
+    let type_tags_len = out.len() - type_tags_start;
+    write_padding(type_tags_len, out);
 
-    if bundle.content.is_empty() {
-        return Ok(bundle_bytes);
+    for arg in args {
+        arg.write_data(out)?;
     }
+    Ok(())
+}
 
-    for packet in &bundle.content {
-        match *packet {
-            OscPacket::Message(ref m) => {
-                let msg = encode_message(m)?;
-                let mut msg_size = vec![0u8; 4];
-                BigEndian::write_u32(&mut msg_size, msg.len() as u32);
-                bundle_bytes.extend(msg_size.into_iter().chain(msg.into_iter()));
+fn write_bundle<P>(
+    timetag: OscTime,
+    content: &[P],
+    write_packet: fn(&P, &mut Vec<u8>) -> Result<()>,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    write_string("#bundle", out);
+    write_time_tag(timetag, out);
+
+    for packet in content {
+        // Each element is preceded by its size, which is known once it has been written
+        let size_start = out.len();
+        out.extend_from_slice(&[0u8; 4]);
+        write_packet(packet, out)?;
+        let size = out.len() - size_start - 4;
+        out[size_start..size_start + 4].copy_from_slice(&(size as u32).to_be_bytes());
+    }
+    Ok(())
+}
+
+impl Arg for OscType {
+    fn write_type_tags(&self, out: &mut Vec<u8>) {
+        let tag = match *self {
+            OscType::Int(_) => b'i',
+            OscType::Long(_) => b'h',
+            OscType::Float(_) => b'f',
+            OscType::Double(_) => b'd',
+            OscType::Char(_) => b'c',
+            OscType::String(_) => b's',
+            OscType::Blob(_) => b'b',
+            OscType::Time(_) => b't',
+            OscType::Midi(_) => b'm',
+            OscType::Color(_) => b'r',
+            OscType::Bool(true) => b'T',
+            OscType::Bool(false) => b'F',
+            OscType::Nil => b'N',
+            OscType::Impulse => b'I',
+            OscType::Unsigned(_) => b'u',
+            OscType::Array(OscArray { ref content }) => {
+                out.push(b'[');
+                for v in content {
+                    v.write_type_tags(out);
+                }
+                b']'
             }
-            OscPacket::Bundle(ref b) => {
-                let bdl = encode_bundle(b)?;
-                let mut bdl_size = vec![0u8; 4];
-                BigEndian::write_u32(&mut bdl_size, bdl.len() as u32);
-                bundle_bytes.extend(bdl_size.into_iter().chain(bdl.into_iter()));
+        };
+        out.push(tag);
+    }
+
+    fn write_data(&self, out: &mut Vec<u8>) -> Result<()> {
+        match *self {
+            OscType::Int(x) => out.extend_from_slice(&x.to_be_bytes()),
+            OscType::Long(x) => out.extend_from_slice(&x.to_be_bytes()),
+            OscType::Unsigned(x) => out.extend_from_slice(&x.to_be_bytes()),
+            OscType::Float(x) => out.extend_from_slice(&x.to_be_bytes()),
+            OscType::Double(x) => out.extend_from_slice(&x.to_be_bytes()),
+            OscType::Char(x) => out.extend_from_slice(&(x as u32).to_be_bytes()),
+            OscType::String(ref x) => write_string(x, out),
+            OscType::Blob(ref x) => write_blob(x, out)?,
+            OscType::Time(time) => write_time_tag(time, out),
+            OscType::Midi(ref x) => out.extend_from_slice(&[x.port, x.status, x.data1, x.data2]),
+            OscType::Color(ref x) => out.extend_from_slice(&[x.red, x.green, x.blue, x.alpha]),
+            OscType::Bool(_) | OscType::Nil | OscType::Impulse => {}
+            OscType::Array(OscArray { ref content }) => {
+                for v in content {
+                    v.write_data(out)?;
+                }
             }
         }
+        Ok(())
     }
-
-    Ok(bundle_bytes)
 }
 
-fn encode_arg(arg: &OscType) -> Result<(Option<Vec<u8>>, String)> {
-    match *arg {
-        OscType::Int(ref x) => {
-            let mut bytes = vec![0u8; 4];
-            BigEndian::write_i32(&mut bytes, *x);
-            Ok((Some(bytes), "i".into()))
-        }
-        OscType::Long(ref x) => {
-            let mut bytes = vec![0u8; 8];
-            BigEndian::write_i64(&mut bytes, *x);
-            Ok((Some(bytes), "h".into()))
-        }
-        OscType::Float(ref x) => {
-            let mut bytes = vec![0u8; 4];
-            BigEndian::write_f32(&mut bytes, *x);
-            Ok((Some(bytes), "f".into()))
-        }
-        OscType::Double(ref x) => {
-            let mut bytes = vec![0u8; 8];
-            BigEndian::write_f64(&mut bytes, *x);
-            Ok((Some(bytes), "d".into()))
-        }
-        OscType::Char(ref x) => {
-            let mut bytes = vec![0u8; 4];
-            BigEndian::write_u32(&mut bytes, *x as u32);
-            Ok((Some(bytes), "c".into()))
-        }
-        OscType::String(ref x) => Ok((Some(encode_string(x.clone())), "s".into())),
-        OscType::Blob(ref x) => {
-            let padded_blob_length: usize = pad(x.len() as u64) as usize;
-            let mut bytes = vec![0u8; 4 + padded_blob_length];
-            // write length
-            BigEndian::write_i32(&mut bytes[..4], x.len() as i32);
-            for (i, v) in x.iter().enumerate() {
-                bytes[i + 4] = *v;
+impl Arg for OscTypeRef<'_> {
+    fn write_type_tags(&self, out: &mut Vec<u8>) {
+        let tag = match *self {
+            OscTypeRef::Int(_) => b'i',
+            OscTypeRef::Long(_) => b'h',
+            OscTypeRef::Float(_) => b'f',
+            OscTypeRef::Double(_) => b'd',
+            OscTypeRef::Char(_) => b'c',
+            OscTypeRef::String(_) => b's',
+            OscTypeRef::Blob(_) => b'b',
+            OscTypeRef::Time(_) => b't',
+            OscTypeRef::Midi(_) => b'm',
+            OscTypeRef::Color(_) => b'r',
+            OscTypeRef::Bool(true) => b'T',
+            OscTypeRef::Bool(false) => b'F',
+            OscTypeRef::Nil => b'N',
+            OscTypeRef::Impulse => b'I',
+            OscTypeRef::Unsigned(_) => b'u',
+            OscTypeRef::Array(OscArrayRef { ref content }) => {
+                out.push(b'[');
+                for v in content {
+                    v.write_type_tags(out);
+                }
+                b']'
             }
-            Ok((Some(bytes), "b".into()))
-        }
-        OscType::Time(time) => Ok((Some(encode_time_tag(time)), "t".into())),
-        OscType::Midi(ref x) => Ok((Some(vec![x.port, x.status, x.data1, x.data2]), "m".into())),
-        OscType::Color(ref x) => Ok((Some(vec![x.red, x.green, x.blue, x.alpha]), "r".into())),
-        OscType::Bool(ref x) => {
-            if *x {
-                Ok((None, "T".into()))
-            } else {
-                Ok((None, "F".into()))
+        };
+        out.push(tag);
+    }
+
+    fn write_data(&self, out: &mut Vec<u8>) -> Result<()> {
+        match *self {
+            OscTypeRef::Int(x) => out.extend_from_slice(&x.to_be_bytes()),
+            OscTypeRef::Long(x) => out.extend_from_slice(&x.to_be_bytes()),
+            OscTypeRef::Unsigned(x) => out.extend_from_slice(&x.to_be_bytes()),
+            OscTypeRef::Float(x) => out.extend_from_slice(&x.to_be_bytes()),
+            OscTypeRef::Double(x) => out.extend_from_slice(&x.to_be_bytes()),
+            OscTypeRef::Char(x) => out.extend_from_slice(&(x as u32).to_be_bytes()),
+            OscTypeRef::String(ref x) => write_string(x, out),
+            OscTypeRef::Blob(x) => write_blob(x, out)?,
+            OscTypeRef::Time(time) => write_time_tag(time, out),
+            OscTypeRef::Midi(ref x) => {
+                out.extend_from_slice(&[x.port, x.status, x.data1, x.data2])
             }
-        }
-        OscType::Nil => Ok((None, "N".into())),
-        OscType::Inf => Ok((None, "I".into())),
-        OscType::Array(ref x) => {
-            let mut bytes = vec![0u8; 0];
-            let mut type_tags = String::from("[");
-            for v in x.content.iter() {
-                match encode_arg(v) {
-                    Ok((Some(other_bytes), other_type_tags)) => {
-                        bytes.extend(other_bytes);
-                        type_tags.push_str(&other_type_tags);
-                    }
-                    Ok((None, other_type_tags)) => {
-                        type_tags.push_str(&other_type_tags);
-                    }
-                    Err(err) => return Err(err),
+            OscTypeRef::Color(ref x) => {
+                out.extend_from_slice(&[x.red, x.green, x.blue, x.alpha])
+            }
+            OscTypeRef::Bool(_) | OscTypeRef::Nil | OscTypeRef::Impulse => {}
+            OscTypeRef::Array(OscArrayRef { ref content }) => {
+                for v in content {
+                    v.write_data(out)?;
                 }
             }
-            type_tags.push(']');
-            Ok((Some(bytes), type_tags))
         }
+        Ok(())
+    }
+}
+
+/// Writes the size of blob `x`, followed by its data and padding.
+fn write_blob(x: &[u8], out: &mut Vec<u8>) -> Result<()> {
+    if x.len() > MAX_BLOB_SIZE {
+        return Err(EncodeError::BlobTooLarge { size: x.len() });
     }
+    out.extend_from_slice(&(x.len() as i32).to_be_bytes());
+    out.extend_from_slice(x);
+    let padded_blob_length = pad(x.len() as u64) as usize;
+    out.resize(out.len() + padded_blob_length - x.len(), 0);
+    Ok(())
+}
+
+/// Writes `s` along with its null terminator and padding.
+fn write_string(s: &str, out: &mut Vec<u8>) {
+    out.extend_from_slice(s.as_bytes());
+    write_padding(s.len(), out);
+}
+
+/// Writes the null terminator and padding of a string of `len` bytes.
+fn write_padding(len: usize, out: &mut Vec<u8>) {
+    let padded_len = pad(len as u64 + 1) as usize;
+    out.resize(out.len() + padded_len - len, 0);
+}
+
+fn write_time_tag(time: OscTime, out: &mut Vec<u8>) {
+    out.extend_from_slice(&time.as_raw().to_be_bytes());
 }
 
 /// Null terminates the byte representation of string `s` and
 /// adds null bytes until the length of the result is a
 /// multiple of 4.
 pub fn encode_string<S: Into<String>>(s: S) -> Vec<u8> {
-    let mut bytes: Vec<u8> = s.into().as_bytes().into();
-    bytes.push(0u8);
-    pad_bytes(&mut bytes);
+    let mut bytes = Vec::new();
+    write_string(&s.into(), &mut bytes);
     bytes
 }
 
-fn pad_bytes(bytes: &mut Vec<u8>) {
-    let padded_lengh = pad(bytes.len() as u64);
-    while bytes.len() < padded_lengh as usize {
-        bytes.push(0u8)
-    }
-}
-
 /// Returns the position padded to 4 bytes.
 ///
 /// # Example
@@ -193,13 +484,6 @@ pub fn pad(pos: u64) -> u64 {
     }
 }
 
-fn encode_time_tag(time: OscTime) -> Vec<u8> {
-    let mut bytes = vec![0u8; 8];
-    BigEndian::write_u32(&mut bytes[..4], time.seconds);
-    BigEndian::write_u32(&mut bytes[4..], time.fractional);
-    bytes
-}
-
 #[test]
 fn test_pad() {
     assert_eq!(4, pad(4));
@@ -1,10 +1,21 @@
 #[cfg(feature = "std")]
-use std::error;
-use alloc::{fmt, string::{ self, String }};
+use std::{error, io};
+#[cfg(all(not(feature = "std"), feature = "core-error"))]
+use core::error;
+use alloc::{boxed::Box, fmt, format, string::{ self, String, ToString }, vec::Vec};
 use nom::error::{ErrorKind, FromExternalError, ParseError};
 
 /// Represents errors returned by `decode` or `encode`.
+///
+/// New variants may be added in minor releases, so matches on `OscError` need a wildcard arm.
+/// Errors caused by another error, such as [`OscError::StringError`] or [`OscError::Io`], return
+/// it from [`source`](std::error::Error::source) rather than including it in their message only.
+///
+/// Errors can be cloned and compared, e.g. with `assert_eq!` in tests. As `io::Error` supports
+/// neither, [`OscError::Io`] errors are equal if their [`kind`](std::io::Error::kind) is, and a
+/// clone of one only retains its kind and message.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum OscError {
     StringError(string::FromUtf8Error),
     ReadError(ErrorKind),
@@ -14,10 +25,360 @@ pub enum OscError {
     BadString(&'static str),
     BadArg(String),
     BadBundle(String),
+    /// A packet starts like a bundle but not with the `#bundle` tag, contains the bytes found
+    /// instead.
+    BadBundleHeader(Vec<u8>),
     BadAddressPattern(String),
     BadAddress(String),
     RegexError(String),
     Unimplemented,
+    PacketTooLarge { size: usize, max: usize },
+    BlobTooLarge { size: usize, max: usize },
+    StringTooLong { len: usize, max: usize },
+    TooManyArgs { count: usize, max: usize },
+    BundleTooDeep { max: usize },
+    /// A message in text form could not be parsed, `column` is the position of the offending
+    /// token, starting at 1.
+    BadText { column: usize, msg: String },
+    /// The argument at position `arg_index` of a message requires `expected` bytes but only
+    /// `remaining` bytes are left.
+    Truncated {
+        expected: usize,
+        remaining: usize,
+        arg_index: usize,
+    },
+    /// The length of a packet is not a multiple of 4 bytes.
+    Misaligned { len: usize },
+    /// Wraps a decoding error with the position at which it occurred. `offset` is the byte offset,
+    /// from the start of the packet, of the field that failed to decode, and `context` describes
+    /// that field, e.g. `arg 3 of message '/mixer/1/name' in bundle element 2`. Argument and
+    /// bundle element indices start at 0. `snippet` contains the input around the offset as
+    /// formatted by [`hex_snippet`].
+    At {
+        offset: usize,
+        context: String,
+        snippet: String,
+        source: Box<OscError>,
+    },
+    /// A packet was scheduled for a time further in the future than allowed.
+    TooFarInFuture(crate::types::OscTime),
+    /// Sending or receiving a packet failed, or reading or writing a recording.
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// A recording of packets, see the `replay` module, is malformed.
+    BadRecording(String),
+    /// A message contains a type tag that is not defined by the OSC specification.
+    UnknownTypeTag(char),
+    /// A char argument is not a valid Unicode scalar value, contains the value found instead.
+    InvalidChar(u32),
+    /// The bundle at index `element` of a bundle has the time tag `timetag`, which is earlier
+    /// than the time tag `parent` the enclosing bundle takes effect at, see
+    /// [`DecoderOptions::enforce_bundle_time_monotonicity`](crate::decoder::DecoderOptions).
+    BundleTimeNotMonotonic {
+        element: usize,
+        timetag: crate::types::OscTime,
+        parent: crate::types::OscTime,
+    },
+    /// A message contains a type tag that is not allowed by the profile it is encoded or decoded
+    /// with, see [`Profile::supports_tag`](crate::profile::Profile::supports_tag).
+    UnsupportedTypeTag(char),
+    /// An address or address pattern of `len` bytes is longer than the limit of `max` bytes, see
+    /// [`DEFAULT_MAX_ADDRESS_LEN`](crate::address::DEFAULT_MAX_ADDRESS_LEN).
+    AddressTooLong { len: usize, max: usize },
+    /// Wraps the error of a packet in a buffer of packets stored back to back, after `decoded`
+    /// packets of the buffer decoded successfully, see
+    /// [`decode_all`](crate::decoder::decode_all).
+    AfterPackets {
+        decoded: usize,
+        source: Box<OscError>,
+    },
+}
+
+impl Clone for OscError {
+    fn clone(&self) -> Self {
+        match self {
+            OscError::StringError(err) => OscError::StringError(err.clone()),
+            OscError::ReadError(kind) => OscError::ReadError(*kind),
+            OscError::BadChar(c) => OscError::BadChar(*c),
+            OscError::BadPacket(msg) => OscError::BadPacket(msg),
+            OscError::BadMessage(msg) => OscError::BadMessage(msg),
+            OscError::BadString(msg) => OscError::BadString(msg),
+            OscError::BadArg(msg) => OscError::BadArg(msg.clone()),
+            OscError::BadBundle(msg) => OscError::BadBundle(msg.clone()),
+            OscError::BadBundleHeader(found) => OscError::BadBundleHeader(found.clone()),
+            OscError::BadAddressPattern(msg) => OscError::BadAddressPattern(msg.clone()),
+            OscError::BadAddress(msg) => OscError::BadAddress(msg.clone()),
+            OscError::RegexError(msg) => OscError::RegexError(msg.clone()),
+            OscError::Unimplemented => OscError::Unimplemented,
+            OscError::PacketTooLarge { size, max } => OscError::PacketTooLarge {
+                size: *size,
+                max: *max,
+            },
+            OscError::BlobTooLarge { size, max } => OscError::BlobTooLarge {
+                size: *size,
+                max: *max,
+            },
+            OscError::StringTooLong { len, max } => OscError::StringTooLong {
+                len: *len,
+                max: *max,
+            },
+            OscError::AddressTooLong { len, max } => OscError::AddressTooLong {
+                len: *len,
+                max: *max,
+            },
+            OscError::TooManyArgs { count, max } => OscError::TooManyArgs {
+                count: *count,
+                max: *max,
+            },
+            OscError::BundleTooDeep { max } => OscError::BundleTooDeep { max: *max },
+            OscError::BadText { column, msg } => OscError::BadText {
+                column: *column,
+                msg: msg.clone(),
+            },
+            OscError::Truncated {
+                expected,
+                remaining,
+                arg_index,
+            } => OscError::Truncated {
+                expected: *expected,
+                remaining: *remaining,
+                arg_index: *arg_index,
+            },
+            OscError::Misaligned { len } => OscError::Misaligned { len: *len },
+            OscError::At {
+                offset,
+                context,
+                snippet,
+                source,
+            } => OscError::At {
+                offset: *offset,
+                context: context.clone(),
+                snippet: snippet.clone(),
+                source: source.clone(),
+            },
+            OscError::TooFarInFuture(time) => OscError::TooFarInFuture(*time),
+            #[cfg(feature = "std")]
+            OscError::Io(err) => OscError::Io(io::Error::new(err.kind(), err.to_string())),
+            OscError::BadRecording(msg) => OscError::BadRecording(msg.clone()),
+            OscError::UnknownTypeTag(tag) => OscError::UnknownTypeTag(*tag),
+            OscError::InvalidChar(value) => OscError::InvalidChar(*value),
+            OscError::BundleTimeNotMonotonic {
+                element,
+                timetag,
+                parent,
+            } => OscError::BundleTimeNotMonotonic {
+                element: *element,
+                timetag: *timetag,
+                parent: *parent,
+            },
+            OscError::UnsupportedTypeTag(tag) => OscError::UnsupportedTypeTag(*tag),
+            OscError::AfterPackets { decoded, source } => OscError::AfterPackets {
+                decoded: *decoded,
+                source: source.clone(),
+            },
+        }
+    }
+}
+
+impl PartialEq for OscError {
+    fn eq(&self, other: &Self) -> bool {
+        use OscError::*;
+
+        match (self, other) {
+            (StringError(a), StringError(b)) => a == b,
+            (ReadError(a), ReadError(b)) => a == b,
+            (BadChar(a), BadChar(b)) => a == b,
+            (BadPacket(a), BadPacket(b))
+            | (BadMessage(a), BadMessage(b))
+            | (BadString(a), BadString(b)) => a == b,
+            (BadArg(a), BadArg(b))
+            | (BadBundle(a), BadBundle(b))
+            | (BadAddressPattern(a), BadAddressPattern(b))
+            | (BadAddress(a), BadAddress(b))
+            | (RegexError(a), RegexError(b))
+            | (BadRecording(a), BadRecording(b)) => a == b,
+            (BadBundleHeader(a), BadBundleHeader(b)) => a == b,
+            (Unimplemented, Unimplemented) => true,
+            (PacketTooLarge { size: a, max: c }, PacketTooLarge { size: b, max: d })
+            | (BlobTooLarge { size: a, max: c }, BlobTooLarge { size: b, max: d })
+            | (StringTooLong { len: a, max: c }, StringTooLong { len: b, max: d })
+            | (AddressTooLong { len: a, max: c }, AddressTooLong { len: b, max: d })
+            | (TooManyArgs { count: a, max: c }, TooManyArgs { count: b, max: d }) => {
+                (a, c) == (b, d)
+            }
+            (BundleTooDeep { max: a }, BundleTooDeep { max: b }) => a == b,
+            (BadText { column: a, msg: c }, BadText { column: b, msg: d }) => (a, c) == (b, d),
+            (
+                Truncated {
+                    expected: a,
+                    remaining: c,
+                    arg_index: e,
+                },
+                Truncated {
+                    expected: b,
+                    remaining: d,
+                    arg_index: f,
+                },
+            ) => (a, c, e) == (b, d, f),
+            (Misaligned { len: a }, Misaligned { len: b }) => a == b,
+            (
+                At {
+                    offset: a,
+                    context: c,
+                    snippet: e,
+                    source: g,
+                },
+                At {
+                    offset: b,
+                    context: d,
+                    snippet: f,
+                    source: h,
+                },
+            ) => (a, c, e, g) == (b, d, f, h),
+            (TooFarInFuture(a), TooFarInFuture(b)) => a == b,
+            #[cfg(feature = "std")]
+            (Io(a), Io(b)) => a.kind() == b.kind(),
+            (UnknownTypeTag(a), UnknownTypeTag(b)) => a == b,
+            (UnsupportedTypeTag(a), UnsupportedTypeTag(b)) => a == b,
+            (InvalidChar(a), InvalidChar(b)) => a == b,
+            (
+                BundleTimeNotMonotonic {
+                    element: a,
+                    timetag: c,
+                    parent: e,
+                },
+                BundleTimeNotMonotonic {
+                    element: b,
+                    timetag: d,
+                    parent: f,
+                },
+            ) => (a, c, e) == (b, d, f),
+            (
+                AfterPackets {
+                    decoded: a,
+                    source: c,
+                },
+                AfterPackets {
+                    decoded: b,
+                    source: d,
+                },
+            ) => (a, c) == (b, d),
+            // Different variants, which is why every variant needs an arm above
+            _ => false,
+        }
+    }
+}
+
+impl Eq for OscError {}
+
+impl OscError {
+    /// Returns the underlying error, stripping the positions added by `OscError::At` and
+    /// `OscError::AfterPackets`.
+    pub fn root_cause(&self) -> &OscError {
+        match self {
+            OscError::At { source, .. } | OscError::AfterPackets { source, .. } => {
+                source.root_cause()
+            }
+            err => err,
+        }
+    }
+
+    /// Returns the kind of the error, which is that of the [`root_cause`](OscError::root_cause)
+    /// for an [`OscError::At`] or an [`OscError::AfterPackets`].
+    pub fn kind(&self) -> OscErrorKind {
+        match self.root_cause() {
+            OscError::StringError(_) => OscErrorKind::StringError,
+            OscError::ReadError(_) => OscErrorKind::ReadError,
+            OscError::BadChar(_) => OscErrorKind::BadChar,
+            OscError::BadPacket(_) => OscErrorKind::BadPacket,
+            OscError::BadMessage(_) => OscErrorKind::BadMessage,
+            OscError::BadString(_) => OscErrorKind::BadString,
+            OscError::BadArg(_) => OscErrorKind::BadArg,
+            OscError::BadBundle(_) => OscErrorKind::BadBundle,
+            OscError::BadBundleHeader(_) => OscErrorKind::BadBundleHeader,
+            OscError::BadAddressPattern(_) => OscErrorKind::BadAddressPattern,
+            OscError::BadAddress(_) => OscErrorKind::BadAddress,
+            OscError::RegexError(_) => OscErrorKind::RegexError,
+            OscError::Unimplemented => OscErrorKind::Unimplemented,
+            OscError::PacketTooLarge { .. } => OscErrorKind::PacketTooLarge,
+            OscError::BlobTooLarge { .. } => OscErrorKind::BlobTooLarge,
+            OscError::StringTooLong { .. } => OscErrorKind::StringTooLong,
+            OscError::TooManyArgs { .. } => OscErrorKind::TooManyArgs,
+            OscError::BundleTooDeep { .. } => OscErrorKind::BundleTooDeep,
+            OscError::BadText { .. } => OscErrorKind::BadText,
+            OscError::Truncated { .. } => OscErrorKind::Truncated,
+            OscError::Misaligned { .. } => OscErrorKind::Misaligned,
+            OscError::TooFarInFuture(_) => OscErrorKind::TooFarInFuture,
+            #[cfg(feature = "std")]
+            OscError::Io(_) => OscErrorKind::Io,
+            OscError::BadRecording(_) => OscErrorKind::BadRecording,
+            OscError::UnknownTypeTag(_) => OscErrorKind::UnknownTypeTag,
+            OscError::InvalidChar(_) => OscErrorKind::InvalidChar,
+            OscError::BundleTimeNotMonotonic { .. } => OscErrorKind::BundleTimeNotMonotonic,
+            OscError::UnsupportedTypeTag(_) => OscErrorKind::UnsupportedTypeTag,
+            OscError::AddressTooLong { .. } => OscErrorKind::AddressTooLong,
+            OscError::At { .. } | OscError::AfterPackets { .. } => {
+                unreachable!("root_cause strips OscError::At and OscError::AfterPackets")
+            }
+        }
+    }
+
+    /// Returns the stable numeric code of the error's [`kind`](OscError::kind), see
+    /// [`OscErrorKind::code`].
+    pub fn code(&self) -> u32 {
+        self.kind().code()
+    }
+}
+
+/// The kind of an [`OscError`], without any of its details, e.g. for counting errors.
+///
+/// Each kind has a numeric code, see [`OscErrorKind::code`], which is the same as its
+/// discriminant. Codes are never reused or changed, new kinds get new codes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum OscErrorKind {
+    StringError = 1,
+    ReadError = 2,
+    BadChar = 3,
+    BadPacket = 4,
+    BadMessage = 5,
+    BadString = 6,
+    BadArg = 7,
+    BadBundle = 8,
+    BadBundleHeader = 9,
+    BadAddressPattern = 10,
+    BadAddress = 11,
+    RegexError = 12,
+    Unimplemented = 13,
+    PacketTooLarge = 14,
+    BlobTooLarge = 15,
+    StringTooLong = 16,
+    TooManyArgs = 17,
+    BundleTooDeep = 18,
+    BadText = 19,
+    Truncated = 20,
+    Misaligned = 21,
+    TooFarInFuture = 22,
+    Io = 23,
+    BadRecording = 24,
+    UnknownTypeTag = 25,
+    InvalidChar = 26,
+    BundleTimeNotMonotonic = 27,
+    UnsupportedTypeTag = 28,
+    AddressTooLong = 29,
+}
+
+impl OscErrorKind {
+    /// Returns the stable numeric code of the kind, which starts at 1 for
+    /// [`OscErrorKind::StringError`] and follows the order of the variants.
+    ///
+    /// Errors of the address and encoder modules have their own codes, starting at 1001 for
+    /// [`AddressError`](crate::address::AddressError) and at 2001 for
+    /// [`EncodeError`](crate::encoder::EncodeError), while a
+    /// [`DecodeError`](crate::decoder::DecodeError) has the code of the kind it converts into.
+    pub fn code(self) -> u32 {
+        self as u32
+    }
 }
 
 impl fmt::Display for OscError {
@@ -31,14 +392,134 @@ impl fmt::Display for OscError {
             OscError::BadString(msg) => write!(f, "bad OSC string: {}", msg),
             OscError::BadArg(msg) => write!(f, "bad OSC argument: {}", msg),
             OscError::BadBundle(msg) => write!(f, "bad OSC bundle: {}", msg),
+            OscError::BadBundleHeader(found) => {
+                write!(f, "bad OSC bundle header: expected \"#bundle\\0\", found {:?}", found)
+            }
             OscError::BadAddressPattern(msg) => write!(f, "bad OSC address pattern: {}", msg),
             OscError::BadAddress(msg) => write!(f, "bad OSC address: {}", msg),
             OscError::RegexError(msg) => write!(f, "OSC address pattern regex error: {}", msg),
             OscError::Unimplemented => write!(f, "unimplemented"),
+            OscError::PacketTooLarge { size, max } => {
+                write!(f, "OSC packet of {} bytes exceeds the limit of {} bytes", size, max)
+            }
+            OscError::BlobTooLarge { size, max } => {
+                write!(f, "OSC blob of {} bytes exceeds the limit of {} bytes", size, max)
+            }
+            OscError::StringTooLong { len, max } => {
+                write!(f, "OSC string of {} bytes exceeds the limit of {} bytes", len, max)
+            }
+            OscError::TooManyArgs { count, max } => {
+                write!(f, "OSC message with {} arguments exceeds the limit of {}", count, max)
+            }
+            OscError::BundleTooDeep { max } => {
+                write!(f, "OSC bundles are nested deeper than the limit of {}", max)
+            }
+            OscError::BadText { column, msg } => {
+                write!(f, "bad OSC text at column {}: {}", column, msg)
+            }
+            OscError::Truncated {
+                expected,
+                remaining,
+                arg_index,
+            } => write!(
+                f,
+                "OSC argument {} requires {} bytes but only {} are left",
+                arg_index, expected, remaining
+            ),
+            OscError::Misaligned { len } => {
+                write!(f, "OSC packet length of {} bytes is not a multiple of 4", len)
+            }
+            OscError::At {
+                offset,
+                context,
+                snippet,
+                source,
+            } => write!(f, "{} at byte {} ({}), near {}", source, offset, context, snippet),
+            OscError::TooFarInFuture(time) => write!(
+                f,
+                "OSC time tag {}.{} is too far in the future",
+                time.seconds, time.fractional
+            ),
+            #[cfg(feature = "std")]
+            OscError::Io(err) => write!(f, "OSC I/O error: {}", err),
+            OscError::BadRecording(msg) => write!(f, "bad OSC recording: {}", msg),
+            OscError::UnknownTypeTag(tag) => write!(f, "unknown OSC type tag {:?}", tag),
+            OscError::InvalidChar(value) => {
+                write!(f, "OSC char argument {:#x} is not a valid char", value)
+            }
+            OscError::BundleTimeNotMonotonic {
+                element,
+                timetag,
+                parent,
+            } => write!(
+                f,
+                "OSC bundle element {} has the time tag {}.{}, earlier than {}.{} of its \
+                 enclosing bundle",
+                element, timetag.seconds, timetag.fractional, parent.seconds, parent.fractional
+            ),
+            OscError::UnsupportedTypeTag(tag) => {
+                write!(f, "OSC type tag {:?} is not supported by the profile", tag)
+            }
+            OscError::AddressTooLong { len, max } => {
+                write!(f, "OSC address of {} bytes exceeds the limit of {} bytes", len, max)
+            }
+            OscError::AfterPackets { decoded, source } => {
+                write!(f, "{} after {} packets decoded successfully", source, decoded)
+            }
         }
     }
 }
 
+/// Maximum length of the input included in an error, in chars for strings and in bytes for
+/// binary input, so that hostile input doesn't end up in logs wholesale.
+pub const MAX_SNIPPET_LEN: usize = 64;
+
+/// Marks the ends of a snippet of the input that were cut off.
+pub const TRUNCATION_MARKER: &str = "...";
+
+/// Returns `input` limited to [`MAX_SNIPPET_LEN`] chars, followed by [`TRUNCATION_MARKER`] if it
+/// was cut off.
+///
+/// ```
+/// use rosc::truncate_input;
+///
+/// assert_eq!(truncate_input("/short"), "/short");
+/// assert_eq!(truncate_input(&"/a".repeat(100)).len(), 64 + 3);
+/// ```
+pub fn truncate_input(input: &str) -> String {
+    match input.char_indices().nth(MAX_SNIPPET_LEN) {
+        Some((end, _)) => {
+            let mut truncated = input[..end].to_string();
+            truncated.push_str(TRUNCATION_MARKER);
+            truncated
+        }
+        None => input.to_string(),
+    }
+}
+
+/// Formats up to [`MAX_SNIPPET_LEN`] bytes of `buf` around `offset` as hex, starting a quarter of
+/// that before the offset. Ends of `buf` that are cut off are marked by [`TRUNCATION_MARKER`].
+///
+/// ```
+/// use rosc::hex_snippet;
+///
+/// assert_eq!(hex_snippet(b"/abc\0\0\0\0,X\0\0", 8), "2f 61 62 63 00 00 00 00 2c 58 00 00");
+/// assert!(hex_snippet(&[0; 100], 50).starts_with("... 00 00"));
+/// ```
+pub fn hex_snippet(buf: &[u8], offset: usize) -> String {
+    let start = offset.saturating_sub(MAX_SNIPPET_LEN / 4).min(buf.len());
+    let end = buf.len().min(start + MAX_SNIPPET_LEN);
+    let mut parts = Vec::with_capacity(end - start + 2);
+    if start > 0 {
+        parts.push(TRUNCATION_MARKER.to_string());
+    }
+    parts.extend(buf[start..end].iter().map(|b| format!("{:02x}", b)));
+    if end < buf.len() {
+        parts.push(TRUNCATION_MARKER.to_string());
+    }
+    parts.join(" ")
+}
+
 impl<I> ParseError<I> for OscError {
     fn from_error_kind(_input: I, kind: ErrorKind) -> Self {
         Self::ReadError(kind)
@@ -62,12 +543,31 @@ impl<I> FromExternalError<I, OscError> for OscError {
     }
 }
 
-#[cfg(feature = "std")]
+// Without std this needs `core::error::Error`, which is only available since Rust 1.81
+#[cfg(any(feature = "std", feature = "core-error"))]
 impl error::Error for OscError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             OscError::StringError(ref err) => Some(err),
+            OscError::At { ref source, .. } | OscError::AfterPackets { ref source, .. } => {
+                Some(source.as_ref())
+            }
+            #[cfg(feature = "std")]
+            OscError::Io(ref err) => Some(err),
             _ => None,
         }
     }
 }
+
+impl From<core::convert::Infallible> for OscError {
+    fn from(err: core::convert::Infallible) -> Self {
+        match err {}
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for OscError {
+    fn from(err: io::Error) -> Self {
+        OscError::Io(err)
+    }
+}
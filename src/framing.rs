@@ -0,0 +1,239 @@
+use crate::alloc::vec::Vec;
+use crate::decoder::{self, DecoderOptions};
+use crate::encoder;
+use crate::errors::OscError;
+use crate::types::{OscPacket, Result};
+
+const SLIP_END: u8 = 0xc0;
+const SLIP_ESC: u8 = 0xdb;
+const SLIP_ESC_END: u8 = 0xdc;
+const SLIP_ESC_ESC: u8 = 0xdd;
+
+/// Encodes `packet` prefixed with its length as a big-endian `u32`, the framing of OSC 1.0 over
+/// TCP.
+pub fn encode_length_prefixed(packet: &OscPacket) -> Result<Vec<u8>> {
+    let buf = encoder::encode(packet)?;
+    let mut frame = Vec::with_capacity(buf.len() + 4);
+    frame.extend((buf.len() as u32).to_be_bytes());
+    frame.extend(buf);
+    Ok(frame)
+}
+
+/// Encodes `packet` as a SLIP frame (RFC 1055), the framing of OSC 1.1 over streams. The frame is
+/// delimited by `END` bytes on both sides, as recommended by OSC 1.1.
+pub fn encode_slip(packet: &OscPacket) -> Result<Vec<u8>> {
+    let buf = encoder::encode(packet)?;
+    let mut frame = Vec::with_capacity(buf.len() + 2);
+    frame.push(SLIP_END);
+    for b in buf {
+        match b {
+            SLIP_END => frame.extend([SLIP_ESC, SLIP_ESC_END]),
+            SLIP_ESC => frame.extend([SLIP_ESC, SLIP_ESC_ESC]),
+            b => frame.push(b),
+        }
+    }
+    frame.push(SLIP_END);
+    Ok(frame)
+}
+
+/// Incrementally decodes a stream of length prefixed packets, as received over TCP.
+///
+/// Bytes are added with [`feed`](TcpDecoder::feed) as they arrive, in chunks of any size, and
+/// complete packets are taken with [`next_packet`](TcpDecoder::next_packet). Frames larger than
+/// [`DecoderOptions::max_packet_size`] are rejected as soon as their length is known and their
+/// contents are discarded without being buffered.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::framing::{encode_length_prefixed, TcpDecoder};
+/// use rosc::{OscMessage, OscPacket};
+///
+/// let packet = OscPacket::Message(OscMessage::from("/ping"));
+/// let stream = encode_length_prefixed(&packet).unwrap();
+///
+/// let mut decoder = TcpDecoder::default();
+/// decoder.feed(&stream[..5]);
+/// assert_eq!(decoder.next_packet().unwrap(), None);
+/// decoder.feed(&stream[5..]);
+/// assert_eq!(decoder.next_packet().unwrap(), Some(packet));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TcpDecoder {
+    buf: Vec<u8>,
+    options: DecoderOptions,
+    // Number of bytes of an oversized frame that still have to be discarded
+    skip: usize,
+}
+
+impl TcpDecoder {
+    /// Creates a decoder which decodes packets with the given options.
+    pub fn with_options(options: DecoderOptions) -> Self {
+        TcpDecoder {
+            buf: Vec::new(),
+            options,
+            skip: 0,
+        }
+    }
+
+    /// Appends bytes received from the stream.
+    pub fn feed(&mut self, data: &[u8]) {
+        let skipped = self.skip.min(data.len());
+        self.skip -= skipped;
+        self.buf.extend_from_slice(&data[skipped..]);
+    }
+
+    /// Returns the number of bytes that have been fed but not consumed by a packet yet.
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Decodes the next complete packet, or returns `Ok(None)` if more bytes are needed. A frame
+    /// that fails to decode is consumed, so that decoding continues with the next frame.
+    pub fn next_packet(&mut self) -> Result<Option<OscPacket>> {
+        if self.buf.len() < 4 {
+            return Ok(None);
+        }
+        let size = [self.buf[0], self.buf[1], self.buf[2], self.buf[3]];
+        let size = u32::from_be_bytes(size) as usize;
+        if size > self.options.max_packet_size {
+            let buffered = self.buf.len() - 4;
+            self.skip = size.saturating_sub(buffered);
+            self.buf.drain(..4 + size.min(buffered));
+            return Err(OscError::PacketTooLarge {
+                size,
+                max: self.options.max_packet_size,
+            });
+        }
+        if self.buf.len() - 4 < size {
+            return Ok(None);
+        }
+        let result = decoder::decode_with_options(&self.buf[4..4 + size], &self.options);
+        self.buf.drain(..4 + size);
+        result.map(Some).map_err(OscError::from)
+    }
+}
+
+/// Incrementally decodes a stream of SLIP encoded packets (RFC 1055), as used by OSC 1.1 over
+/// serial lines and TCP.
+///
+/// Bytes are added with [`feed`](SlipDecoder::feed) as they arrive, in chunks of any size, and
+/// complete packets are taken with [`next_packet`](SlipDecoder::next_packet). Empty frames are
+/// skipped, so frames may be delimited by one or two `END` bytes. A frame that exceeds
+/// [`DecoderOptions::max_packet_size`] or contains an invalid escape sequence is rejected and the
+/// rest of it is discarded up to the next `END` byte.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::framing::{encode_slip, SlipDecoder};
+/// use rosc::{OscMessage, OscPacket};
+///
+/// let packet = OscPacket::Message(OscMessage::from("/ping"));
+/// let stream = encode_slip(&packet).unwrap();
+///
+/// let mut decoder = SlipDecoder::default();
+/// decoder.feed(&stream[..5]);
+/// assert_eq!(decoder.next_packet().unwrap(), None);
+/// decoder.feed(&stream[5..]);
+/// assert_eq!(decoder.next_packet().unwrap(), Some(packet));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SlipDecoder {
+    buf: Vec<u8>,
+    frame: Vec<u8>,
+    options: DecoderOptions,
+    escape: bool,
+    // Discard everything up to the next END byte after an error
+    discard: bool,
+}
+
+impl SlipDecoder {
+    /// Creates a decoder which decodes packets with the given options.
+    pub fn with_options(options: DecoderOptions) -> Self {
+        SlipDecoder {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Appends bytes received from the stream.
+    pub fn feed(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Returns the number of bytes that have been fed but not consumed by a packet yet.
+    pub fn buffered(&self) -> usize {
+        self.buf.len() + self.frame.len()
+    }
+
+    /// Decodes the next complete packet, or returns `Ok(None)` if more bytes are needed. A frame
+    /// that fails to decode is consumed, so that decoding continues with the next frame.
+    pub fn next_packet(&mut self) -> Result<Option<OscPacket>> {
+        let mut result = Ok(None);
+        let mut consumed = 0;
+        while consumed < self.buf.len() {
+            let b = self.buf[consumed];
+            consumed += 1;
+            match self.unescape(b) {
+                Ok(false) => {}
+                Ok(true) => {
+                    result = decoder::decode_with_options(&self.frame, &self.options)
+                        .map(Some)
+                        .map_err(OscError::from);
+                    self.frame.clear();
+                    break;
+                }
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+        self.buf.drain(..consumed);
+        result
+    }
+
+    /// Adds a byte of the stream to the current frame, returns whether the frame is complete.
+    fn unescape(&mut self, b: u8) -> Result<bool> {
+        if b == SLIP_END {
+            if self.escape {
+                self.escape = false;
+                self.frame.clear();
+                return Err(OscError::BadPacket("Invalid SLIP escape sequence"));
+            }
+            let complete = !self.discard && !self.frame.is_empty();
+            self.discard = false;
+            return Ok(complete);
+        }
+        if self.discard {
+            return Ok(false);
+        }
+        if self.escape {
+            self.escape = false;
+            match b {
+                SLIP_ESC_END => self.frame.push(SLIP_END),
+                SLIP_ESC_ESC => self.frame.push(SLIP_ESC),
+                _ => {
+                    self.frame.clear();
+                    self.discard = true;
+                    return Err(OscError::BadPacket("Invalid SLIP escape sequence"));
+                }
+            }
+        } else if b == SLIP_ESC {
+            self.escape = true;
+        } else {
+            self.frame.push(b);
+        }
+        if self.frame.len() > self.options.max_packet_size {
+            let size = self.frame.len();
+            self.frame.clear();
+            self.discard = true;
+            return Err(OscError::PacketTooLarge {
+                size,
+                max: self.options.max_packet_size,
+            });
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,159 @@
+use crate::types::{
+    OscBundle, OscBundleRef, OscMessage, OscMessageRef, OscPacket, OscPacketRef, OscTime,
+    OscType, OscTypeRef,
+};
+
+use crate::alloc::boxed::Box;
+use crate::alloc::collections::BTreeMap;
+use crate::alloc::string::ToString;
+use crate::alloc::sync::Arc;
+use crate::alloc::vec::Vec;
+
+/// A bounded cache of addresses, which lets packets that are decoded over and over share the
+/// allocations of their addresses, see
+/// [`decode_with_interner`](crate::decoder::decode_with_interner).
+///
+/// Addresses are cached until [`capacity`](Interner::capacity) different ones have been seen.
+/// Any further addresses are allocated on every call instead, so that a stream of random
+/// addresses can't grow the cache without bound.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::intern::Interner;
+/// use std::sync::Arc;
+///
+/// let mut interner = Interner::new(2);
+/// let first = interner.intern("/fader");
+/// assert!(Arc::ptr_eq(&first, &interner.intern("/fader")));
+/// assert_eq!(interner.len(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Interner {
+    addresses: BTreeMap<Box<[u8]>, Arc<str>>,
+    capacity: usize,
+}
+
+impl Interner {
+    /// Creates an empty interner caching up to `capacity` addresses.
+    pub fn new(capacity: usize) -> Self {
+        Interner {
+            addresses: BTreeMap::new(),
+            capacity,
+        }
+    }
+
+    /// Returns the cached `addr`, caching it first if it is new and the cache isn't full yet.
+    pub fn intern(&mut self, addr: &str) -> Arc<str> {
+        if let Some(interned) = self.addresses.get(addr.as_bytes()) {
+            return interned.clone();
+        }
+        let interned: Arc<str> = Arc::from(addr);
+        if self.addresses.len() < self.capacity {
+            self.addresses.insert(addr.as_bytes().into(), interned.clone());
+        }
+        interned
+    }
+
+    /// Copies `packet` into an [`InternedPacket`] whose addresses are taken from the cache.
+    pub fn intern_packet(&mut self, packet: &OscPacketRef) -> InternedPacket {
+        match *packet {
+            OscPacketRef::Message(ref msg) => InternedPacket::Message(self.intern_message(msg)),
+            OscPacketRef::Bundle(ref bundle) => InternedPacket::Bundle(self.intern_bundle(bundle)),
+        }
+    }
+
+    fn intern_message(&mut self, msg: &OscMessageRef) -> InternedMessage {
+        InternedMessage {
+            addr: self.intern(&msg.addr),
+            args: msg.args.iter().map(OscTypeRef::to_owned).collect(),
+        }
+    }
+
+    fn intern_bundle(&mut self, bundle: &OscBundleRef) -> InternedBundle {
+        InternedBundle {
+            timetag: bundle.timetag,
+            content: bundle
+                .content
+                .iter()
+                .map(|packet| self.intern_packet(packet))
+                .collect(),
+        }
+    }
+
+    /// Returns the number of cached addresses.
+    pub fn len(&self) -> usize {
+        self.addresses.len()
+    }
+
+    /// Returns whether no addresses are cached.
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
+
+    /// Returns the maximum number of cached addresses.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Removes all cached addresses, e.g. once the set of addresses received changes.
+    pub fn clear(&mut self) {
+        self.addresses.clear();
+    }
+}
+
+impl Default for Interner {
+    /// Creates an interner caching up to 256 addresses.
+    fn default() -> Self {
+        Interner::new(256)
+    }
+}
+
+/// A packet decoded by [`decode_with_interner`](crate::decoder::decode_with_interner).
+#[derive(Clone, Debug, PartialEq)]
+pub enum InternedPacket {
+    Message(InternedMessage),
+    Bundle(InternedBundle),
+}
+
+/// An [`OscMessage`] whose address is shared with the cache of an [`Interner`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct InternedMessage {
+    pub addr: Arc<str>,
+    pub args: Vec<OscType>,
+}
+
+/// An [`OscBundle`] containing [`InternedPacket`]s.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InternedBundle {
+    pub timetag: OscTime,
+    pub content: Vec<InternedPacket>,
+}
+
+impl From<InternedPacket> for OscPacket {
+    fn from(packet: InternedPacket) -> Self {
+        match packet {
+            InternedPacket::Message(msg) => OscPacket::Message(msg.into()),
+            InternedPacket::Bundle(bundle) => OscPacket::Bundle(bundle.into()),
+        }
+    }
+}
+
+impl From<InternedMessage> for OscMessage {
+    fn from(msg: InternedMessage) -> Self {
+        OscMessage {
+            addr: msg.addr.to_string(),
+            args: msg.args,
+        }
+    }
+}
+
+impl From<InternedBundle> for OscBundle {
+    fn from(bundle: InternedBundle) -> Self {
+        OscBundle {
+            timetag: bundle.timetag,
+            content: bundle.content.into_iter().map(OscPacket::from).collect(),
+        }
+    }
+}
+
@@ -1,5 +1,17 @@
 //! **rosc** is an implementation of the [OSC 1.0](http://opensoundcontrol.org/spec-1_0) protocol in pure Rust.
 //!
+//! # Features
+//!
+//! - `std`, enabled by default: networking, dispatching messages to an address space, scheduling
+//!   bundles, recordings and everything else that needs the standard library. Without it the
+//!   crate is `no_std` and only needs `alloc`, while encoding, decoding, the packet types and
+//!   address matching remain available. The only dependency is `nom` either way.
+//! - `ws`: carrying packets in WebSocket messages.
+//! - `test-util`: comparing packets in tests, see `assert_osc_eq!`, and loading fixtures.
+//! - `tuio`: typed messages of the TUIO 1.1 multitouch protocol.
+//! - `core-error`: implements `core::error::Error` for the error types in `no_std` builds, which
+//!   needs Rust 1.81. With `std` the error types always implement `std::error::Error`.
+//!
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -12,20 +24,71 @@ extern crate std as core;
 #[macro_use] extern crate std as alloc;
 
 extern crate nom;
-extern crate byteorder;
 
 /// Crate specific error types.
 mod errors;
 /// OSC data types, see [OSC 1.0 specification](https://opensoundcontrol.stanford.edu/spec-1_0.html) for details.
 mod types;
+/// Parser for messages in the text form used by `oscsend`.
+mod text;
 
 pub use crate::errors::*;
 pub use crate::types::*;
 
 /// Provides a decoding method for OSC packets.
+///
+/// The decoders are meant to be fed untrusted data straight from the network: malformed input,
+/// such as truncated fields, bad padding, negative lengths or invalid UTF-8, always results in an
+/// `OscError` and never in a panic or abort. Use
+/// [`decode_with_options`](decoder::decode_with_options) to additionally bound the resources a
+/// single packet may use.
 pub mod decoder;
 /// Encodes an `OscPacket` to a byte vector.
 pub mod encoder;
+/// Framing of OSC packets sent over streams, such as TCP connections or serial lines.
+pub mod framing;
+/// Helpers for debugging the raw bytes of OSC packets.
+pub mod debug;
+/// Batching outgoing messages into bundles.
+pub mod coalesce;
+/// Sharing the addresses of decoded messages between packets.
+pub mod intern;
+/// Rewriting the addresses of raw packets while forwarding everything else as it is.
+pub mod rewrite;
+/// The features of OSC supported by peers, for encoding and decoding only what they understand.
+pub mod profile;
+/// The most commonly used types and modules, for importing them all at once.
+pub mod prelude;
+/// Carrying OSC packets in WebSocket messages.
+#[cfg(feature = "ws")]
+pub mod ws;
+/// Comparing packets in tests, with messages that point out the first difference, and loading
+/// raw packets of other implementations as fixtures.
+#[cfg(feature = "test-util")]
+pub mod test_util;
+/// Typed messages of the TUIO multitouch protocol.
+#[cfg(feature = "tuio")]
+pub mod tuio;
 /// Address checking and matching methods
-#[cfg(feature = "std")]
 pub mod address;
+/// Dispatching messages to the handlers of an address space.
+#[cfg(feature = "std")]
+pub mod dispatch;
+/// Delivering the messages of bundles at their time tags.
+#[cfg(feature = "std")]
+pub mod scheduler;
+/// Sending and receiving OSC packets over UDP.
+#[cfg(feature = "std")]
+pub mod net;
+/// Recording packets and replaying them with their original timing.
+#[cfg(feature = "std")]
+pub mod replay;
+/// Reusing scratch buffers for encoding and decoding.
+#[cfg(feature = "std")]
+pub mod pool;
+/// Checking packets against the OSC specification before sending them.
+#[cfg(feature = "std")]
+pub mod validate;
+/// Matching replies to requests by correlation IDs.
+#[cfg(feature = "std")]
+pub mod rpc;
@@ -0,0 +1,1360 @@
+use crate::address::verify_address_pattern;
+use crate::decoder::{self, DecoderOptions};
+use crate::dispatch::{AddressSpace, DispatchContext};
+use crate::encoder;
+use crate::errors::{OscError, OscErrorKind};
+use crate::pool::BufferPool;
+use crate::scheduler::Queue;
+use crate::types::{Clock, IntoMessages, OscMessage, OscPacket, OscTime, OscType, SystemClock};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(unix)]
+use std::os::unix::net::{self as unix, UnixDatagram};
+#[cfg(windows)]
+use std::os::windows::io::{AsRawSocket, RawSocket};
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+
+/// Maximum payload of a UDP datagram.
+const MAX_UDP_PAYLOAD: usize = 65_507;
+
+/// Longest time [`serve`] blocks in a receive before checking whether to shut down.
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A `UdpSocket` sending and receiving [`OscPacket`]s. Received packets are decoded with
+/// [`decode_with_options`](decoder::decode_with_options) from a buffer that is allocated once and
+/// large enough for any datagram.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rosc::net::OscUdpSocket;
+/// use rosc::{OscMessage, OscPacket};
+///
+/// let mut socket = OscUdpSocket::bind("127.0.0.1:9000").unwrap();
+/// let ping = OscPacket::Message(OscMessage::from("/ping"));
+/// socket.send_to(&ping, "127.0.0.1:9001").unwrap();
+/// let (packet, addr) = socket.recv_from().unwrap();
+/// println!("Received {:?} from {}", packet, addr);
+/// ```
+#[derive(Debug)]
+pub struct OscUdpSocket {
+    socket: UdpSocket,
+    recv: RecvBuffer,
+    last_peer: Option<SocketAddr>,
+    nonblocking: bool,
+    pool: Option<Arc<BufferPool>>,
+    stats: Option<Arc<SocketStats>>,
+    source_filter: Option<SourceFilter>,
+}
+
+/// The filter of [`OscUdpSocket::set_source_filter`].
+struct SourceFilter(Box<dyn Fn(&SocketAddr) -> bool + Send>);
+
+impl fmt::Debug for SourceFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SourceFilter")
+    }
+}
+
+impl OscUdpSocket {
+    /// Creates a socket bound to the given address.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> Result<Self, OscError> {
+        Ok(UdpSocket::bind(addr)?.into())
+    }
+
+    /// Creates a socket receiving the packets sent to the multicast `group` on `port`. The socket
+    /// is bound to the unspecified address of the group's family, which is what Linux, macOS and
+    /// Windows all accept for receiving multicast, and joins the group on the default interface.
+    ///
+    /// `SO_REUSEADDR` is set before binding, and on macOS and the BSDs `SO_REUSEPORT` as well, so
+    /// that several receivers on the same host can bind to `port`, as long as they all set these
+    /// options. Linux, Android, macOS, iOS, FreeBSD and Windows are supported, on other platforms
+    /// the socket is bound without them and only one socket can be bound to `port` at a time.
+    pub fn bind_multicast(group: IpAddr, port: u16) -> Result<Self, OscError> {
+        match group {
+            IpAddr::V4(group) => {
+                let socket: OscUdpSocket =
+                    reuse::bind_reusable((Ipv4Addr::UNSPECIFIED, port).into())?.into();
+                socket.join_multicast_v4(group, Ipv4Addr::UNSPECIFIED)?;
+                Ok(socket)
+            }
+            IpAddr::V6(group) => {
+                let socket: OscUdpSocket =
+                    reuse::bind_reusable((Ipv6Addr::UNSPECIFIED, port).into())?.into();
+                socket.join_multicast_v6(&group, 0)?;
+                Ok(socket)
+            }
+        }
+    }
+
+    /// Returns the underlying socket, e.g. to set timeouts or query the local address. Use
+    /// [`set_nonblocking`](OscUdpSocket::set_nonblocking) instead of the method of the socket to
+    /// switch it to non-blocking mode.
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Switches the socket to non-blocking mode, in which [`recv_from`](OscUdpSocket::recv_from)
+    /// fails with an `io::ErrorKind::WouldBlock` error instead of waiting for a packet.
+    ///
+    /// This is meant for event loops such as `mio` or `epoll`, which wait for the socket, found
+    /// through `AsRawFd` or `AsRawSocket`, to become readable. Readiness is often reported
+    /// edge-triggered, i.e. once for any number of datagrams, so call
+    /// [`try_recv`](OscUdpSocket::try_recv) until it returns `Ok(None)` on every event. A
+    /// datagram that fails to decode only consumes that datagram, so keep receiving after such an
+    /// error as well:
+    ///
+    /// ```no_run
+    /// use rosc::net::OscUdpSocket;
+    ///
+    /// let mut socket = OscUdpSocket::bind("127.0.0.1:9000").unwrap();
+    /// socket.set_nonblocking(true).unwrap();
+    ///
+    /// // Once the event loop reports the socket as readable
+    /// loop {
+    ///     match socket.try_recv() {
+    ///         Ok(Some((packet, addr))) => println!("Received {:?} from {}", packet, addr),
+    ///         Ok(None) => break,
+    ///         Err(err) => eprintln!("{}", err),
+    ///     }
+    /// }
+    /// ```
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> Result<(), OscError> {
+        self.socket.set_nonblocking(nonblocking)?;
+        self.nonblocking = nonblocking;
+        Ok(())
+    }
+
+    /// Joins the IPv4 multicast `group` on the interface with the address `interface`, which may
+    /// be `Ipv4Addr::UNSPECIFIED` to let the OS choose.
+    pub fn join_multicast_v4(&self, group: Ipv4Addr, interface: Ipv4Addr) -> Result<(), OscError> {
+        Ok(self.socket.join_multicast_v4(&group, &interface)?)
+    }
+
+    /// Leaves an IPv4 multicast group joined with
+    /// [`join_multicast_v4`](OscUdpSocket::join_multicast_v4).
+    pub fn leave_multicast_v4(&self, group: Ipv4Addr, interface: Ipv4Addr) -> Result<(), OscError> {
+        Ok(self.socket.leave_multicast_v4(&group, &interface)?)
+    }
+
+    /// Joins the IPv6 multicast `group` on the interface with the index `interface`, which may be
+    /// 0 to let the OS choose.
+    pub fn join_multicast_v6(&self, group: &Ipv6Addr, interface: u32) -> Result<(), OscError> {
+        Ok(self.socket.join_multicast_v6(group, interface)?)
+    }
+
+    /// Leaves an IPv6 multicast group joined with
+    /// [`join_multicast_v6`](OscUdpSocket::join_multicast_v6).
+    pub fn leave_multicast_v6(&self, group: &Ipv6Addr, interface: u32) -> Result<(), OscError> {
+        Ok(self.socket.leave_multicast_v6(group, interface)?)
+    }
+
+    /// Sets the time to live of IPv4 multicast packets, i.e. the number of routers they may pass.
+    /// Defaults to 1, which keeps them in the local network.
+    pub fn set_multicast_ttl(&self, ttl: u32) -> Result<(), OscError> {
+        Ok(self.socket.set_multicast_ttl_v4(ttl)?)
+    }
+
+    /// Sets whether multicast packets sent by this socket are looped back to the local host,
+    /// including this socket if it joined the group. Defaults to `true`.
+    pub fn set_multicast_loop(&self, on: bool) -> Result<(), OscError> {
+        match self.socket.local_addr()? {
+            SocketAddr::V4(_) => self.socket.set_multicast_loop_v4(on)?,
+            SocketAddr::V6(_) => self.socket.set_multicast_loop_v6(on)?,
+        }
+        Ok(())
+    }
+
+    /// Sets the options used to decode received packets, which defaults to
+    /// `DecoderOptions::default()`.
+    pub fn set_decoder_options(&mut self, options: DecoderOptions) {
+        self.recv.options = options;
+    }
+
+    /// Encodes packets that are sent into buffers taken from `pool` instead of allocating a new
+    /// buffer for each packet. The pool can be shared with other sockets and threads.
+    pub fn set_buffer_pool(&mut self, pool: Arc<BufferPool>) {
+        self.pool = Some(pool);
+    }
+
+    /// Counts the datagrams sent and received by the socket in `stats`, which is off by default.
+    /// The statistics can be shared with other sockets and threads, e.g. to read them while the
+    /// socket is used by [`serve`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rosc::net::{OscUdpSocket, SocketStats};
+    /// use std::sync::Arc;
+    ///
+    /// let stats = Arc::new(SocketStats::new());
+    /// let mut socket = OscUdpSocket::bind("127.0.0.1:9000").unwrap();
+    /// socket.set_stats(stats.clone());
+    /// let _ = socket.recv_from();
+    /// let snapshot = stats.snapshot();
+    /// println!("{} datagrams, {} failed", snapshot.datagrams_received, snapshot.decode_errors);
+    /// ```
+    pub fn set_stats(&mut self, stats: Arc<SocketStats>) {
+        self.stats = Some(stats);
+    }
+
+    /// Only accepts datagrams from the senders for which `filter` returns `true`. Other datagrams
+    /// are dropped right after they are received, without being decoded, and the receive methods
+    /// wait for the next datagram instead, as does [`serve`]. Dropped datagrams don't change the
+    /// [`last_peer`](OscUdpSocket::last_peer) and are only counted as
+    /// [`filtered`](SocketStatsSnapshot::filtered) by the statistics. Replaces any previous
+    /// filter.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rosc::net::OscUdpSocket;
+    /// use std::net::{IpAddr, Ipv4Addr};
+    ///
+    /// let allowed = [IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10))];
+    /// let mut socket = OscUdpSocket::bind("0.0.0.0:9000").unwrap();
+    /// socket.set_source_filter(move |source| allowed.contains(&source.ip()));
+    /// // Only returns packets sent by 192.168.1.10
+    /// let (packet, source) = socket.recv_from().unwrap();
+    /// ```
+    pub fn set_source_filter<F>(&mut self, filter: F)
+    where
+        F: Fn(&SocketAddr) -> bool + Send + 'static,
+    {
+        self.source_filter = Some(SourceFilter(Box::new(filter)));
+    }
+
+    /// Returns the current statistics of the socket, or `None` unless they are collected, see
+    /// [`set_stats`](OscUdpSocket::set_stats).
+    pub fn stats(&self) -> Option<SocketStatsSnapshot> {
+        self.stats.as_ref().map(|stats| stats.snapshot())
+    }
+
+    /// Encodes `packet` and sends it to `addr`, returning the number of bytes sent. Packets that
+    /// don't fit into a single datagram are rejected with [`OscError::PacketTooLarge`].
+    pub fn send_to<A: ToSocketAddrs>(
+        &self,
+        packet: &OscPacket,
+        addr: A,
+    ) -> Result<usize, OscError> {
+        let size = match self.pool {
+            Some(ref pool) => {
+                let mut buf = pool.get();
+                encode_datagram_into(packet, &mut buf)?;
+                self.socket.send_to(&buf, addr)?
+            }
+            None => self.socket.send_to(&encode_datagram(packet)?, addr)?,
+        };
+        if let Some(ref stats) = self.stats {
+            stats.datagrams_sent.fetch_add(1, Ordering::Relaxed);
+            stats.bytes_sent.fetch_add(size as u64, Ordering::Relaxed);
+        }
+        Ok(size)
+    }
+
+    /// Allows sending to broadcast addresses by setting `SO_BROADCAST`, which
+    /// [`send_broadcast`](OscUdpSocket::send_broadcast) requires.
+    pub fn enable_broadcast(&self) -> Result<(), OscError> {
+        Ok(self.socket.set_broadcast(true)?)
+    }
+
+    /// Sends `packet` to `port` of the limited broadcast address `255.255.255.255`, which reaches
+    /// every host of the local network but is not forwarded by routers. Broadcasting has to be
+    /// enabled with [`enable_broadcast`](OscUdpSocket::enable_broadcast) first, otherwise the OS
+    /// rejects the packet with an [`OscError::Io`] error, as it may if broadcasting is not
+    /// permitted at all.
+    ///
+    /// To broadcast to a specific subnet, e.g. on a host with several interfaces, pass its
+    /// directed broadcast address to [`send_to`](OscUdpSocket::send_to) instead, e.g.
+    /// `192.168.1.255` for `192.168.1.0/24`.
+    pub fn send_broadcast(&self, packet: &OscPacket, port: u16) -> Result<usize, OscError> {
+        self.send_to(packet, (Ipv4Addr::BROADCAST, port))
+    }
+
+    /// Returns the sender of the last packet that was received, even if it failed to decode.
+    pub fn last_peer(&self) -> Option<SocketAddr> {
+        self.last_peer
+    }
+
+    /// Sends `packet` to the sender of the last packet that was received, see
+    /// [`last_peer`](OscUdpSocket::last_peer). Fails with an `io::ErrorKind::NotConnected` error
+    /// if no packet has been received yet.
+    pub fn reply(&self, packet: &OscPacket) -> Result<usize, OscError> {
+        match self.last_peer {
+            Some(addr) => self.send_to(packet, addr),
+            None => {
+                let err = io::Error::new(io::ErrorKind::NotConnected, "no packet received yet");
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Receives a single packet and returns it along with the address of its sender. A datagram
+    /// that fails to decode results in an error, the socket can be used to receive the next
+    /// datagram afterwards.
+    pub fn recv_from(&mut self) -> Result<(OscPacket, SocketAddr), OscError> {
+        loop {
+            match self.socket.recv_from(&mut self.recv.buf) {
+                Ok((_, addr)) if self.is_filtered(addr) => continue,
+                Ok((size, addr)) => return self.decode(size, addr),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Receives a single datagram like [`recv_from`](OscUdpSocket::recv_from) and returns all
+    /// packets it contains, for senders that put several packets back to back into a datagram
+    /// instead of wrapping them in a bundle, see [`decode_all`](decoder::decode_all).
+    pub fn recv_all_from(&mut self) -> Result<(Vec<OscPacket>, SocketAddr), OscError> {
+        loop {
+            match self.socket.recv_from(&mut self.recv.buf) {
+                Ok((_, addr)) if self.is_filtered(addr) => continue,
+                Ok((size, addr)) => return self.decode_with(size, addr, RecvBuffer::decode_all),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Receives a single packet like [`recv_from`](OscUdpSocket::recv_from), but returns
+    /// `Ok(None)` if none arrived within `timeout`. A zero `timeout` behaves like
+    /// [`try_recv`](OscUdpSocket::try_recv), as does any `timeout` in non-blocking mode. The read
+    /// timeout of the socket is restored afterwards.
+    pub fn recv_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Option<(OscPacket, SocketAddr)>, OscError> {
+        if timeout == Duration::ZERO {
+            return self.try_recv();
+        }
+        let previous = self.socket.read_timeout()?;
+        let deadline = Instant::now() + timeout;
+        let result = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining == Duration::ZERO {
+                break Ok(None);
+            }
+            if let Err(err) = self.socket.set_read_timeout(Some(remaining)) {
+                break Err(err);
+            }
+            match self.socket.recv_from(&mut self.recv.buf) {
+                Ok((_, addr)) if self.is_filtered(addr) => continue,
+                Ok(received) => break Ok(Some(received)),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) if is_timeout(&err) => break Ok(None),
+                Err(err) => break Err(err),
+            }
+        };
+        self.socket.set_read_timeout(previous)?;
+        match result? {
+            Some((size, addr)) => self.decode(size, addr).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Receives a single packet like [`recv_from`](OscUdpSocket::recv_from) if one is available,
+    /// returning `Ok(None)` instead of blocking otherwise. Unless the socket is in non-blocking
+    /// mode, see [`set_nonblocking`](OscUdpSocket::set_nonblocking), it is temporarily switched to
+    /// it.
+    pub fn try_recv(&mut self) -> Result<Option<(OscPacket, SocketAddr)>, OscError> {
+        if !self.nonblocking {
+            self.socket.set_nonblocking(true)?;
+        }
+        let result = loop {
+            match self.socket.recv_from(&mut self.recv.buf) {
+                Ok((_, addr)) if self.is_filtered(addr) => continue,
+                Ok(received) => break Ok(Some(received)),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) if is_timeout(&err) => break Ok(None),
+                Err(err) => break Err(err),
+            }
+        };
+        if !self.nonblocking {
+            self.socket.set_nonblocking(false)?;
+        }
+        match result? {
+            Some((size, addr)) => self.decode(size, addr).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Receives a single packet like [`recv_from`](OscUdpSocket::recv_from), along with the time
+    /// it was received at.
+    pub fn recv_packet(&mut self) -> Result<ReceivedPacket, OscError> {
+        self.recv_from().map(ReceivedPacket::now)
+    }
+
+    /// Receives a single packet like [`recv_timeout`](OscUdpSocket::recv_timeout), along with the
+    /// time it was received at.
+    pub fn recv_packet_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Option<ReceivedPacket>, OscError> {
+        Ok(self.recv_timeout(timeout)?.map(ReceivedPacket::now))
+    }
+
+    /// Receives a single packet like [`try_recv`](OscUdpSocket::try_recv), along with the time it
+    /// was received at.
+    pub fn try_recv_packet(&mut self) -> Result<Option<ReceivedPacket>, OscError> {
+        Ok(self.try_recv()?.map(ReceivedPacket::now))
+    }
+
+    /// Returns whether datagrams from `addr` are dropped by the source filter, counting them if so.
+    fn is_filtered(&self, addr: SocketAddr) -> bool {
+        let filtered = match self.source_filter {
+            Some(SourceFilter(ref filter)) => !filter(&addr),
+            None => false,
+        };
+        if filtered {
+            if let Some(ref stats) = self.stats {
+                stats.filtered.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        filtered
+    }
+
+    fn decode(
+        &mut self,
+        size: usize,
+        addr: SocketAddr,
+    ) -> Result<(OscPacket, SocketAddr), OscError> {
+        self.decode_with(size, addr, RecvBuffer::decode)
+    }
+
+    fn decode_with<T>(
+        &mut self,
+        size: usize,
+        addr: SocketAddr,
+        decode: fn(&RecvBuffer, usize) -> Result<T, OscError>,
+    ) -> Result<(T, SocketAddr), OscError> {
+        self.last_peer = Some(addr);
+        let decoded = decode(&self.recv, size);
+        if let Some(ref stats) = self.stats {
+            stats.record_received(size, addr, decoded.as_ref().err());
+        }
+        Ok((decoded?, addr))
+    }
+}
+
+/// Statistics of the datagrams sent and received by an [`OscUdpSocket`], see
+/// [`OscUdpSocket::set_stats`]. The counters are atomic, only recording the receive time of a
+/// peer or the kind of a decoding error takes a short lock.
+#[derive(Debug, Default)]
+pub struct SocketStats {
+    datagrams_received: AtomicU64,
+    bytes_received: AtomicU64,
+    decode_errors: AtomicU64,
+    datagrams_sent: AtomicU64,
+    bytes_sent: AtomicU64,
+    filtered: AtomicU64,
+    errors_by_kind: Mutex<BTreeMap<OscErrorKind, u64>>,
+    last_packets: Mutex<BTreeMap<SocketAddr, OscTime>>,
+}
+
+/// The statistics of a socket at one point in time, see [`SocketStats::snapshot`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SocketStatsSnapshot {
+    /// The number of datagrams received, including those that failed to decode.
+    pub datagrams_received: u64,
+    /// The number of bytes received, including those of datagrams that failed to decode.
+    pub bytes_received: u64,
+    /// The number of datagrams that failed to decode.
+    pub decode_errors: u64,
+    /// The number of datagrams that failed to decode by the [kind](OscError::kind) of the error.
+    pub errors_by_kind: BTreeMap<OscErrorKind, u64>,
+    /// The number of datagrams sent.
+    pub datagrams_sent: u64,
+    /// The number of bytes sent.
+    pub bytes_sent: u64,
+    /// The number of datagrams dropped by the source filter, see
+    /// [`OscUdpSocket::set_source_filter`], which are not counted as received.
+    pub filtered: u64,
+    /// The time the last packet that decoded successfully was received at, by the address it was
+    /// received from.
+    pub last_packets: BTreeMap<SocketAddr, OscTime>,
+}
+
+impl SocketStats {
+    /// Creates statistics with all counters at zero.
+    pub fn new() -> Self {
+        SocketStats::default()
+    }
+
+    /// Returns the current values of the counters. As they are read one after the other, a
+    /// datagram sent or received meanwhile may be counted by some of them only.
+    pub fn snapshot(&self) -> SocketStatsSnapshot {
+        SocketStatsSnapshot {
+            datagrams_received: self.datagrams_received.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            decode_errors: self.decode_errors.load(Ordering::Relaxed),
+            errors_by_kind: lock(&self.errors_by_kind).clone(),
+            datagrams_sent: self.datagrams_sent.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+            last_packets: lock(&self.last_packets).clone(),
+        }
+    }
+
+    /// Sets all counters back to zero and forgets the peers.
+    pub fn reset(&self) {
+        self.datagrams_received.store(0, Ordering::Relaxed);
+        self.bytes_received.store(0, Ordering::Relaxed);
+        self.decode_errors.store(0, Ordering::Relaxed);
+        self.datagrams_sent.store(0, Ordering::Relaxed);
+        self.bytes_sent.store(0, Ordering::Relaxed);
+        self.filtered.store(0, Ordering::Relaxed);
+        lock(&self.errors_by_kind).clear();
+        lock(&self.last_packets).clear();
+    }
+
+    fn record_received(&self, size: usize, source: SocketAddr, err: Option<&OscError>) {
+        self.datagrams_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received.fetch_add(size as u64, Ordering::Relaxed);
+        match err {
+            Some(err) => {
+                self.decode_errors.fetch_add(1, Ordering::Relaxed);
+                *lock(&self.errors_by_kind).entry(err.kind()).or_insert(0) += 1;
+            }
+            None => {
+                lock(&self.last_packets).insert(source, OscTime::now());
+            }
+        }
+    }
+}
+
+/// Locks `mutex`, recovering the data if a thread panicked while holding it, as the statistics
+/// stay consistent enough to keep counting.
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A packet received from the network, along with where and when it was received.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rosc::net::OscUdpSocket;
+///
+/// let mut socket = OscUdpSocket::bind("127.0.0.1:9000").unwrap();
+/// let received = socket.recv_packet().unwrap();
+/// for (context, msg) in received.into_messages() {
+///     println!("{} from {:?} at {:?}", msg.addr, context.peer, context.received_at);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReceivedPacket {
+    /// The packet that was received.
+    pub packet: OscPacket,
+    /// The address the packet was received from.
+    pub source: SocketAddr,
+    /// The time the packet was received at.
+    pub received_at: OscTime,
+}
+
+impl ReceivedPacket {
+    /// Returns the packet received from `source` at the current system time.
+    fn now((packet, source): (OscPacket, SocketAddr)) -> Self {
+        ReceivedPacket {
+            packet,
+            source,
+            received_at: OscTime::now(),
+        }
+    }
+
+    /// Returns an iterator over the messages of the packet like [`OscPacket::into_messages`],
+    /// which yields each message along with the [`DispatchContext`] to dispatch it with. The
+    /// context carries the source and the receive time of the packet, and the time tag of the
+    /// bundle containing the message.
+    pub fn into_messages(self) -> ReceivedMessages {
+        ReceivedMessages {
+            messages: self.packet.into_messages(),
+            source: self.source,
+            received_at: self.received_at,
+        }
+    }
+}
+
+/// Iterator over the messages of a received packet, see [`ReceivedPacket::into_messages`].
+#[derive(Debug)]
+pub struct ReceivedMessages {
+    messages: IntoMessages,
+    source: SocketAddr,
+    received_at: OscTime,
+}
+
+impl Iterator for ReceivedMessages {
+    type Item = (DispatchContext, OscMessage);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (timetag, msg) = self.messages.next()?;
+        let context = DispatchContext {
+            peer: Some(self.source),
+            timetag,
+            received_at: Some(self.received_at),
+        };
+        Some((context, msg))
+    }
+}
+
+/// Returns whether `err` was caused by a read timeout or a non-blocking read, which is reported as
+/// `WouldBlock` or `TimedOut` depending on the platform.
+fn is_timeout(err: &io::Error) -> bool {
+    matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
+
+impl From<UdpSocket> for OscUdpSocket {
+    fn from(socket: UdpSocket) -> Self {
+        OscUdpSocket {
+            socket,
+            recv: RecvBuffer::new(),
+            last_peer: None,
+            nonblocking: false,
+            pool: None,
+            stats: None,
+            source_filter: None,
+        }
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for OscUdpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+#[cfg(windows)]
+impl AsRawSocket for OscUdpSocket {
+    fn as_raw_socket(&self) -> RawSocket {
+        self.socket.as_raw_socket()
+    }
+}
+
+/// A UDP socket sending [`OscPacket`]s to a single peer.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rosc::net::OscSender;
+/// use rosc::{OscMessage, OscPacket};
+///
+/// let sender = OscSender::connect("0.0.0.0:0", "127.0.0.1:9000").unwrap();
+/// sender.send(&OscPacket::Message(OscMessage::from("/ping"))).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct OscSender {
+    socket: UdpSocket,
+    remote: SocketAddr,
+}
+
+impl OscSender {
+    /// Creates a socket bound to `local` which sends to `remote`.
+    pub fn connect<A: ToSocketAddrs, B: ToSocketAddrs>(
+        local: A,
+        remote: B,
+    ) -> Result<Self, OscError> {
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(remote)?;
+        let remote = socket.peer_addr()?;
+        Ok(OscSender { socket, remote })
+    }
+
+    /// Returns the underlying socket.
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Returns the address packets are sent to.
+    pub fn remote(&self) -> SocketAddr {
+        self.remote
+    }
+
+    /// Encodes `packet` and sends it to the peer, returning the number of bytes sent. Packets that
+    /// don't fit into a single datagram are rejected with [`OscError::PacketTooLarge`]. An
+    /// `io::ErrorKind::ConnectionRefused` error reports that a previous packet was not received,
+    /// the sender can still be used afterwards.
+    pub fn send(&self, packet: &OscPacket) -> Result<usize, OscError> {
+        let buf = encode_datagram(packet)?;
+        Ok(self.socket.send(&buf)?)
+    }
+
+    /// Sends subsequent packets to `remote` instead.
+    pub fn reconnect<A: ToSocketAddrs>(&mut self, remote: A) -> Result<(), OscError> {
+        self.socket.connect(remote)?;
+        self.remote = self.socket.peer_addr()?;
+        Ok(())
+    }
+
+    /// Replaces the socket by a new one bound to `local`, which sends to the same peer.
+    pub fn rebind<A: ToSocketAddrs>(&mut self, local: A) -> Result<(), OscError> {
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(self.remote)?;
+        self.socket = socket;
+        Ok(())
+    }
+}
+
+/// A Unix domain datagram socket sending and receiving [`OscPacket`]s, like [`OscUdpSocket`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use rosc::net::OscUnixDatagram;
+/// use rosc::{OscMessage, OscPacket};
+///
+/// let mut socket = OscUnixDatagram::bind("/tmp/engine.sock").unwrap();
+/// socket.set_remove_on_drop(true);
+/// let ping = OscPacket::Message(OscMessage::from("/ping"));
+/// socket.send_to(&ping, "/tmp/compositor.sock").unwrap();
+/// let (packet, addr) = socket.recv_from().unwrap();
+/// println!("Received {:?} from {:?}", packet, addr.as_pathname());
+/// ```
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct OscUnixDatagram {
+    socket: UnixDatagram,
+    recv: RecvBuffer,
+    // Path of the socket file that is removed on drop
+    remove_on_drop: Option<PathBuf>,
+}
+
+#[cfg(unix)]
+impl OscUnixDatagram {
+    /// Creates a socket bound to the given path. Binding fails if the path already exists.
+    pub fn bind<P: AsRef<Path>>(path: P) -> Result<Self, OscError> {
+        Ok(UnixDatagram::bind(path)?.into())
+    }
+
+    /// Creates a socket which is not bound to a path, it can send packets but receives no replies.
+    pub fn unbound() -> Result<Self, OscError> {
+        Ok(UnixDatagram::unbound()?.into())
+    }
+
+    /// Returns the underlying socket.
+    pub fn socket(&self) -> &UnixDatagram {
+        &self.socket
+    }
+
+    /// Sets the options used to decode received packets, which defaults to
+    /// `DecoderOptions::default()`.
+    pub fn set_decoder_options(&mut self, options: DecoderOptions) {
+        self.recv.options = options;
+    }
+
+    /// Removes the socket file the socket is bound to when it is dropped. Defaults to `false`,
+    /// in which case the file has to be removed before it can be bound again.
+    pub fn set_remove_on_drop(&mut self, remove: bool) {
+        self.remove_on_drop = if remove {
+            self.socket
+                .local_addr()
+                .ok()
+                .and_then(|addr| addr.as_pathname().map(Path::to_path_buf))
+        } else {
+            None
+        };
+    }
+
+    /// Encodes `packet` and sends it to the socket bound to `path`, returning the number of bytes
+    /// sent. Packets that would not fit into a UDP datagram are rejected with
+    /// [`OscError::PacketTooLarge`] as well, to keep both transports interchangeable.
+    pub fn send_to<P: AsRef<Path>>(&self, packet: &OscPacket, path: P) -> Result<usize, OscError> {
+        let buf = encode_datagram(packet)?;
+        Ok(self.socket.send_to(&buf, path)?)
+    }
+
+    /// Receives a single packet and returns it along with the address of its sender, which has a
+    /// path unless the sender is unbound.
+    pub fn recv_from(&mut self) -> Result<(OscPacket, unix::SocketAddr), OscError> {
+        loop {
+            match self.socket.recv_from(&mut self.recv.buf) {
+                Ok((size, addr)) => return Ok((self.recv.decode(size)?, addr)),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl From<UnixDatagram> for OscUnixDatagram {
+    fn from(socket: UnixDatagram) -> Self {
+        OscUnixDatagram {
+            socket,
+            recv: RecvBuffer::new(),
+            remove_on_drop: None,
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for OscUnixDatagram {
+    fn drop(&mut self) {
+        if let Some(ref path) = self.remove_on_drop {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Buffer receiving datagrams, which is large enough for any UDP payload.
+#[derive(Debug)]
+struct RecvBuffer {
+    buf: Vec<u8>,
+    options: DecoderOptions,
+}
+
+impl RecvBuffer {
+    fn new() -> Self {
+        RecvBuffer {
+            buf: vec![0u8; MAX_UDP_PAYLOAD],
+            options: DecoderOptions::default(),
+        }
+    }
+
+    /// Decodes a datagram of `size` bytes that was received into the buffer.
+    fn decode(&self, size: usize) -> Result<OscPacket, OscError> {
+        decoder::decode_with_options(&self.buf[..size], &self.options).map_err(OscError::from)
+    }
+
+    /// Decodes all packets of a datagram of `size` bytes that was received into the buffer.
+    fn decode_all(&self, size: usize) -> Result<Vec<OscPacket>, OscError> {
+        decoder::decode_all(&self.buf[..size], &self.options).map_err(OscError::from)
+    }
+}
+
+/// Encodes a packet that has to fit into a single datagram.
+fn encode_datagram(packet: &OscPacket) -> Result<Vec<u8>, OscError> {
+    let mut buf = Vec::new();
+    encode_datagram_into(packet, &mut buf)?;
+    Ok(buf)
+}
+
+/// Encodes a packet that has to fit into a single datagram into the empty `buf`.
+fn encode_datagram_into(packet: &OscPacket, buf: &mut Vec<u8>) -> Result<(), OscError> {
+    let size = encoder::encode_into(packet, buf)?;
+    if size > MAX_UDP_PAYLOAD {
+        return Err(OscError::PacketTooLarge {
+            size,
+            max: MAX_UDP_PAYLOAD,
+        });
+    }
+    Ok(())
+}
+
+/// Receives packets on `socket` and dispatches their messages to `router` until `shutdown`
+/// returns `true`, which is checked at least every 50 milliseconds.
+///
+/// Messages of bundles with a time tag in the future are kept until the time tag is due, all
+/// others are dispatched right away. Messages that are still pending on shutdown are dropped.
+/// Packets that fail to decode and messages that fail to dispatch are passed to `on_error` and
+/// don't stop the loop, only IO errors of the socket do. Packets from senders that the source
+/// filter of `socket` rejects are dropped, see [`OscUdpSocket::set_source_filter`].
+///
+/// The hooks of `router` are passed the address each message was received from, the time it was
+/// received at and the time tag of its bundle, see [`AddressSpace::dispatch_with_context`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use rosc::dispatch::AddressSpace;
+/// use rosc::net::{serve, OscUdpSocket};
+/// use std::sync::atomic::{AtomicBool, Ordering};
+///
+/// static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+///
+/// let socket = OscUdpSocket::bind("127.0.0.1:9000").unwrap();
+/// let mut router = AddressSpace::new();
+/// router.add_method("/quit", |_| SHUTDOWN.store(true, Ordering::SeqCst)).unwrap();
+/// serve(
+///     socket,
+///     &mut router,
+///     || SHUTDOWN.load(Ordering::SeqCst),
+///     |err| eprintln!("{}", err),
+/// )
+/// .unwrap();
+/// ```
+pub fn serve<S, E>(
+    socket: OscUdpSocket,
+    router: &mut AddressSpace,
+    shutdown: S,
+    on_error: E,
+) -> Result<(), OscError>
+where
+    S: Fn() -> bool,
+    E: FnMut(OscError),
+{
+    serve_with_clock(socket, router, SystemClock, shutdown, on_error)
+}
+
+/// Serves `router` like [`serve`], but takes the current time from `clock` to decide when
+/// messages are due. Pending messages are checked at least every 50 milliseconds, so `clock` may
+/// also jump ahead, like a [`ManualClock`](crate::ManualClock).
+pub fn serve_with_clock<C, S, E>(
+    mut socket: OscUdpSocket,
+    router: &mut AddressSpace,
+    clock: C,
+    shutdown: S,
+    mut on_error: E,
+) -> Result<(), OscError>
+where
+    C: Clock,
+    S: Fn() -> bool,
+    E: FnMut(OscError),
+{
+    let mut pending = Queue::default();
+    while !shutdown() {
+        let now = clock.now();
+        while let Some((msg, context)) = pending.pop_due(now) {
+            if let Err(err) = router.dispatch_with_context(&msg, &context) {
+                on_error(err);
+            }
+        }
+
+        let timeout = pending
+            .time_until_next(now)
+            .map_or(SHUTDOWN_POLL_INTERVAL, |timeout| timeout.min(SHUTDOWN_POLL_INTERVAL));
+        let received = match socket.recv_timeout(timeout) {
+            Ok(Some((packet, source))) => ReceivedPacket {
+                packet,
+                source,
+                received_at: clock.now(),
+            },
+            Ok(None) => continue,
+            Err(OscError::Io(err)) => return Err(OscError::Io(err)),
+            Err(err) => {
+                on_error(err);
+                continue;
+            }
+        };
+        let received_at = received.received_at;
+        for (context, msg) in received.into_messages() {
+            let timetag = context.timetag;
+            if timetag.is_immediate() || timetag <= received_at {
+                if let Err(err) = router.dispatch_with_context(&msg, &context) {
+                    on_error(err);
+                }
+            } else {
+                pending.push(timetag, (msg, context));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Sends a message with the address `addr` and the arguments `args` to `target` from a socket
+/// that is bound to an ephemeral port for this message only, like `oscsend` does.
+///
+/// Fails without sending anything if `addr` is not a valid OSC address pattern.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rosc::net::send_once;
+/// use rosc::OscType;
+///
+/// send_once("127.0.0.1:9000", "/mixer/1/gain", vec![0.5f32]).unwrap();
+/// send_once(
+///     "127.0.0.1:9000",
+///     "/mixer/1/label",
+///     vec![OscType::Int(1), "Vocals".into()],
+/// )
+/// .unwrap();
+/// ```
+pub fn send_once<A, I, T>(target: A, addr: &str, args: I) -> Result<(), OscError>
+where
+    A: ToSocketAddrs,
+    I: IntoIterator<Item = T>,
+    T: Into<OscType>,
+{
+    send_packet_once(target, &message(addr, args)?)
+}
+
+/// Sends `packet` to `target` like [`send_once`].
+pub fn send_packet_once<A: ToSocketAddrs>(target: A, packet: &OscPacket) -> Result<(), OscError> {
+    let target = target.to_socket_addrs()?.next().ok_or_else(no_address)?;
+    send_to_resolved(target, packet)
+}
+
+/// Sends a message to `target` like [`send_once`], but fails with an `io::ErrorKind::TimedOut`
+/// error if resolving `target` takes longer than `timeout`. The name is resolved on a separate
+/// thread, which keeps running until the resolution finishes.
+pub fn send_once_timeout<A, I, T>(
+    target: A,
+    addr: &str,
+    args: I,
+    timeout: Duration,
+) -> Result<(), OscError>
+where
+    A: ToSocketAddrs + Send + 'static,
+    I: IntoIterator<Item = T>,
+    T: Into<OscType>,
+{
+    let packet = message(addr, args)?;
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(target.to_socket_addrs().map(|mut addrs| addrs.next()));
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(Ok(Some(target))) => send_to_resolved(target, &packet),
+        Ok(Ok(None)) => Err(no_address().into()),
+        Ok(Err(err)) => Err(err.into()),
+        Err(_) => {
+            let err = io::Error::new(io::ErrorKind::TimedOut, "resolving the target timed out");
+            Err(err.into())
+        }
+    }
+}
+
+fn message<I, T>(addr: &str, args: I) -> Result<OscPacket, OscError>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OscType>,
+{
+    verify_address_pattern(addr)?;
+    Ok(OscPacket::Message(OscMessage {
+        addr: addr.to_string(),
+        args: args.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Sends `packet` from an ephemeral port of the same address family as `target`.
+fn send_to_resolved(target: SocketAddr, packet: &OscPacket) -> Result<(), OscError> {
+    let local: SocketAddr = match target {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let buf = encode_datagram(packet)?;
+    UdpSocket::bind(local)?.send_to(&buf, target)?;
+    Ok(())
+}
+
+fn no_address() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "no address to send to")
+}
+
+/// Binding UDP sockets that several sockets on the same host can bind to, for
+/// [`OscUdpSocket::bind_multicast`]. The standard library only offers to set socket options after
+/// binding, so the socket is created and bound through the system's socket API.
+mod reuse {
+    use std::io;
+    use std::net::SocketAddr;
+
+    pub(super) use self::sys::bind_reusable;
+
+    /// The layout of `sockaddr_in` on Linux and Windows, which BSDs prefix with its length.
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct SockaddrIn {
+        #[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
+        len: u8,
+        #[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
+        family: u8,
+        #[cfg(not(any(target_vendor = "apple", target_os = "freebsd")))]
+        family: u16,
+        port: u16,
+        addr: [u8; 4],
+        zero: [u8; 8],
+    }
+
+    /// The layout of `sockaddr_in6`, see [`SockaddrIn`].
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct SockaddrIn6 {
+        #[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
+        len: u8,
+        #[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
+        family: u8,
+        #[cfg(not(any(target_vendor = "apple", target_os = "freebsd")))]
+        family: u16,
+        port: u16,
+        flowinfo: u32,
+        addr: [u8; 16],
+        scope_id: u32,
+    }
+
+    /// An address in the form expected by `bind`.
+    #[allow(dead_code)]
+    enum RawAddr {
+        V4(SockaddrIn),
+        V6(SockaddrIn6),
+    }
+
+    #[allow(dead_code)]
+    impl RawAddr {
+        fn new(addr: SocketAddr, af_inet: i32, af_inet6: i32) -> Self {
+            match addr {
+                SocketAddr::V4(addr) => RawAddr::V4(SockaddrIn {
+                    #[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
+                    len: std::mem::size_of::<SockaddrIn>() as u8,
+                    family: af_inet as _,
+                    port: addr.port().to_be(),
+                    addr: addr.ip().octets(),
+                    zero: [0; 8],
+                }),
+                SocketAddr::V6(addr) => RawAddr::V6(SockaddrIn6 {
+                    #[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
+                    len: std::mem::size_of::<SockaddrIn6>() as u8,
+                    family: af_inet6 as _,
+                    port: addr.port().to_be(),
+                    flowinfo: addr.flowinfo().to_be(),
+                    addr: addr.ip().octets(),
+                    scope_id: addr.scope_id(),
+                }),
+            }
+        }
+
+        /// Returns the pointer and length to pass to `bind`.
+        fn as_raw(&self) -> (*const u8, usize) {
+            match self {
+                RawAddr::V4(addr) => {
+                    (addr as *const SockaddrIn as *const u8, std::mem::size_of::<SockaddrIn>())
+                }
+                RawAddr::V6(addr) => {
+                    (addr as *const SockaddrIn6 as *const u8, std::mem::size_of::<SockaddrIn6>())
+                }
+            }
+        }
+    }
+
+    /// Turns the result of a socket call into the last OS error if it failed.
+    #[allow(dead_code)]
+    fn check(result: i32) -> io::Result<()> {
+        if result < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    // Linux on MIPS and SPARC numbers the socket options differently
+    #[cfg(any(
+        all(
+            any(target_os = "linux", target_os = "android"),
+            not(any(
+                target_arch = "mips",
+                target_arch = "mips32r6",
+                target_arch = "mips64",
+                target_arch = "mips64r6",
+                target_arch = "sparc",
+                target_arch = "sparc64"
+            ))
+        ),
+        target_vendor = "apple",
+        target_os = "freebsd"
+    ))]
+    mod sys {
+        use super::{check, RawAddr};
+
+        use std::io;
+        use std::net::{SocketAddr, UdpSocket};
+        use std::os::raw::{c_int, c_void};
+        use std::os::unix::io::FromRawFd;
+
+        const AF_INET: c_int = 2;
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        const AF_INET6: c_int = 10;
+        #[cfg(target_vendor = "apple")]
+        const AF_INET6: c_int = 30;
+        #[cfg(target_os = "freebsd")]
+        const AF_INET6: c_int = 28;
+        const SOCK_DGRAM: c_int = 2;
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        const SOCK_CLOEXEC: c_int = 0o2_000_000;
+        #[cfg(target_os = "freebsd")]
+        const SOCK_CLOEXEC: c_int = 0x1000_0000;
+        // macOS has no `SOCK_CLOEXEC`, the flag is set with `fcntl` instead
+        #[cfg(target_vendor = "apple")]
+        const SOCK_CLOEXEC: c_int = 0;
+        #[cfg(target_vendor = "apple")]
+        const F_SETFD: c_int = 2;
+        #[cfg(target_vendor = "apple")]
+        const FD_CLOEXEC: c_int = 1;
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        const SOL_SOCKET: c_int = 1;
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        const SO_REUSEADDR: c_int = 2;
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        const SOL_SOCKET: c_int = 0xffff;
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        const SO_REUSEADDR: c_int = 4;
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        const SO_REUSEPORT: c_int = 0x200;
+
+        extern "C" {
+            fn socket(domain: c_int, ty: c_int, protocol: c_int) -> c_int;
+            fn setsockopt(
+                socket: c_int,
+                level: c_int,
+                name: c_int,
+                value: *const c_void,
+                len: u32,
+            ) -> c_int;
+            fn bind(socket: c_int, addr: *const c_void, len: u32) -> c_int;
+            #[cfg(target_vendor = "apple")]
+            fn fcntl(fd: c_int, cmd: c_int, ...) -> c_int;
+        }
+
+        fn enable(fd: c_int, name: c_int) -> io::Result<()> {
+            let on: c_int = 1;
+            let len = std::mem::size_of::<c_int>() as u32;
+            // SAFETY: `on` outlives the call and `len` is its size
+            check(unsafe { setsockopt(fd, SOL_SOCKET, name, &on as *const c_int as _, len) })
+        }
+
+        /// Creates a UDP socket bound to `addr` with `SO_REUSEADDR` set, and on BSDs
+        /// `SO_REUSEPORT`, which they need to share a port bound to the unspecified address.
+        pub(in super::super) fn bind_reusable(addr: SocketAddr) -> io::Result<UdpSocket> {
+            let family = match addr {
+                SocketAddr::V4(_) => AF_INET,
+                SocketAddr::V6(_) => AF_INET6,
+            };
+            // SAFETY: `socket` takes no pointers
+            let fd = unsafe { socket(family, SOCK_DGRAM | SOCK_CLOEXEC, 0) };
+            check(fd)?;
+            // SAFETY: `fd` is a new socket owned by nothing else, which the `UdpSocket` closes
+            // when it is dropped, also if one of the following calls fails
+            let udp = unsafe { UdpSocket::from_raw_fd(fd) };
+            #[cfg(target_vendor = "apple")]
+            // SAFETY: `F_SETFD` takes an integer argument
+            check(unsafe { fcntl(fd, F_SETFD, FD_CLOEXEC) })?;
+            enable(fd, SO_REUSEADDR)?;
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            enable(fd, SO_REUSEPORT)?;
+            let raw = RawAddr::new(addr, AF_INET, AF_INET6);
+            let (ptr, len) = raw.as_raw();
+            // SAFETY: `raw` outlives the call and `len` is its size
+            check(unsafe { bind(fd, ptr as _, len as u32) })?;
+            Ok(udp)
+        }
+    }
+
+    #[cfg(windows)]
+    mod sys {
+        use super::{check, RawAddr};
+
+        use std::io;
+        use std::net::{SocketAddr, UdpSocket};
+        use std::os::raw::{c_int, c_void};
+        use std::os::windows::io::{FromRawSocket, RawSocket};
+        use std::sync::Once;
+
+        const AF_INET: c_int = 2;
+        const AF_INET6: c_int = 23;
+        const SOCK_DGRAM: c_int = 2;
+        const SOL_SOCKET: c_int = 0xffff;
+        const SO_REUSEADDR: c_int = 4;
+        const INVALID_SOCKET: usize = !0;
+        // The flags the standard library creates its sockets with
+        const WSA_FLAG_OVERLAPPED: u32 = 0x01;
+        const WSA_FLAG_NO_HANDLE_INHERIT: u32 = 0x80;
+
+        #[link(name = "ws2_32")]
+        extern "system" {
+            fn WSAStartup(version: u16, data: *mut c_void) -> c_int;
+            fn WSASocketW(
+                af: c_int,
+                ty: c_int,
+                protocol: c_int,
+                info: *mut c_void,
+                group: u32,
+                flags: u32,
+            ) -> usize;
+            fn setsockopt(
+                socket: usize,
+                level: c_int,
+                name: c_int,
+                value: *const c_void,
+                len: c_int,
+            ) -> c_int;
+            fn bind(socket: usize, addr: *const c_void, len: c_int) -> c_int;
+        }
+
+        /// Initializes Winsock, which the standard library only does when it creates a socket.
+        fn startup() -> io::Result<()> {
+            static STARTUP: Once = Once::new();
+            let mut result = 0;
+            STARTUP.call_once(|| {
+                // Larger than `WSADATA` on every architecture
+                let mut data = [0u64; 64];
+                // SAFETY: `data` is large enough for the `WSADATA` written to it
+                result = unsafe { WSAStartup(0x202, data.as_mut_ptr() as _) };
+            });
+            match result {
+                0 => Ok(()),
+                err => Err(io::Error::from_raw_os_error(err)),
+            }
+        }
+
+        fn enable(socket: usize, name: c_int) -> io::Result<()> {
+            let on: c_int = 1;
+            let len = std::mem::size_of::<c_int>() as c_int;
+            // SAFETY: `on` outlives the call and `len` is its size
+            check(unsafe { setsockopt(socket, SOL_SOCKET, name, &on as *const c_int as _, len) })
+        }
+
+        /// Creates a UDP socket bound to `addr` with `SO_REUSEADDR` set.
+        pub(in super::super) fn bind_reusable(addr: SocketAddr) -> io::Result<UdpSocket> {
+            startup()?;
+            let family = match addr {
+                SocketAddr::V4(_) => AF_INET,
+                SocketAddr::V6(_) => AF_INET6,
+            };
+            let flags = WSA_FLAG_OVERLAPPED | WSA_FLAG_NO_HANDLE_INHERIT;
+            // SAFETY: no protocol info is passed
+            let socket = unsafe {
+                WSASocketW(family, SOCK_DGRAM, 0, std::ptr::null_mut(), 0, flags)
+            };
+            if socket == INVALID_SOCKET {
+                return Err(io::Error::last_os_error());
+            }
+            // SAFETY: `socket` is new and owned by nothing else, which the `UdpSocket` closes
+            // when it is dropped, also if one of the following calls fails
+            let udp = unsafe { UdpSocket::from_raw_socket(socket as RawSocket) };
+            enable(socket, SO_REUSEADDR)?;
+            let raw = RawAddr::new(addr, AF_INET, AF_INET6);
+            let (ptr, len) = raw.as_raw();
+            // SAFETY: `raw` outlives the call and `len` is its size
+            check(unsafe { bind(socket, ptr as _, len as c_int) })?;
+            Ok(udp)
+        }
+    }
+
+    #[cfg(not(any(
+        all(
+            any(target_os = "linux", target_os = "android"),
+            not(any(
+                target_arch = "mips",
+                target_arch = "mips32r6",
+                target_arch = "mips64",
+                target_arch = "mips64r6",
+                target_arch = "sparc",
+                target_arch = "sparc64"
+            ))
+        ),
+        target_vendor = "apple",
+        target_os = "freebsd",
+        windows
+    )))]
+    mod sys {
+        use std::io;
+        use std::net::{SocketAddr, UdpSocket};
+
+        /// Binds the socket without `SO_REUSEADDR`, whose value isn't known for this platform.
+        pub(in super::super) fn bind_reusable(addr: SocketAddr) -> io::Result<UdpSocket> {
+            UdpSocket::bind(addr)
+        }
+    }
+}
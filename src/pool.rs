@@ -0,0 +1,125 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard};
+
+/// A bounded pool of scratch buffers, which lets threads encoding or decoding packets reuse
+/// buffers instead of allocating a new one for every packet.
+///
+/// Buffers are taken from the pool with [`get`](BufferPool::get) and return to it when the
+/// returned guard is dropped. Returned buffers are cleared. Buffers that grew beyond
+/// [`max_capacity`](BufferPool::new) are discarded rather than kept around, as are buffers
+/// returned to a full pool. The pool can be shared between threads, e.g. in an `Arc`.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::pool::BufferPool;
+/// use rosc::{decoder, encoder, OscMessage, OscPacket};
+///
+/// let pool = BufferPool::default();
+/// let packet = OscPacket::Message(OscMessage::from("/ping"));
+/// {
+///     let mut buf = pool.get();
+///     encoder::encode_into(&packet, &mut buf).unwrap();
+///     assert_eq!(decoder::decode_udp(&buf).unwrap().1, packet);
+/// }
+/// assert_eq!(pool.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct BufferPool {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    max_buffers: usize,
+    max_capacity: usize,
+}
+
+impl BufferPool {
+    /// Creates an empty pool keeping up to `max_buffers` buffers whose capacity doesn't exceed
+    /// `max_capacity` bytes.
+    pub fn new(max_buffers: usize, max_capacity: usize) -> Self {
+        BufferPool {
+            buffers: Mutex::new(Vec::new()),
+            max_buffers,
+            max_capacity,
+        }
+    }
+
+    /// Takes an empty buffer from the pool, or allocates one if the pool is empty. The buffer
+    /// returns to the pool when the guard is dropped.
+    pub fn get(&self) -> PooledBuffer<'_> {
+        let buf = self.lock().pop().unwrap_or_default();
+        PooledBuffer {
+            pool: self,
+            buf: Some(buf),
+        }
+    }
+
+    /// Returns `buf` to the pool, unless it is too large or the pool is full.
+    pub fn put(&self, mut buf: Vec<u8>) {
+        if buf.capacity() > self.max_capacity {
+            return;
+        }
+        let mut buffers = self.lock();
+        if buffers.len() < self.max_buffers {
+            buf.clear();
+            buffers.push(buf);
+        }
+    }
+
+    /// Returns the number of buffers in the pool.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns whether the pool holds no buffers.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Locks the buffers, which remain consistent even if a thread panicked while holding them.
+    fn lock(&self) -> MutexGuard<'_, Vec<Vec<u8>>> {
+        self.buffers.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for BufferPool {
+    /// Creates a pool keeping up to 64 buffers of up to 64 KiB, which fits any UDP datagram.
+    fn default() -> Self {
+        BufferPool::new(64, 64 * 1024)
+    }
+}
+
+/// A buffer borrowed from a [`BufferPool`], which returns to the pool when dropped.
+#[derive(Debug)]
+pub struct PooledBuffer<'a> {
+    pool: &'a BufferPool,
+    // Only `None` once the buffer was taken by `into_inner` or returned by `drop`
+    buf: Option<Vec<u8>>,
+}
+
+impl PooledBuffer<'_> {
+    /// Takes the buffer out of the pool for good.
+    pub fn into_inner(mut self) -> Vec<u8> {
+        self.buf.take().unwrap_or_default()
+    }
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        self.buf.as_ref().expect("buffer is only taken when the guard is consumed")
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        self.buf.as_mut().expect("buffer is only taken when the guard is consumed")
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        if let Some(buf) = self.buf.take() {
+            self.pool.put(buf);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Import them with `use rosc::prelude::*;`. The prelude contains:
+//!
+//! - the [`decoder`] and [`encoder`] modules, e.g. for `decoder::decode_udp` and
+//!   `encoder::encode`
+//! - the packet types [`OscPacket`], [`OscMessage`], [`OscBundle`], [`OscType`], [`OscArray`],
+//!   [`OscTime`], [`OscColor`] and [`OscMidiMessage`]
+//! - the errors [`OscError`], [`DecodeError`](crate::decoder::DecodeError) and
+//!   [`EncodeError`](crate::encoder::EncodeError)
+//! - the options [`DecoderOptions`](crate::decoder::DecoderOptions)
+//! - the address matching [`Matcher`](crate::address::Matcher) along with
+//!   [`MatcherOptions`](crate::address::MatcherOptions) and
+//!   [`AddressError`](crate::address::AddressError)
+//!
+//! Items are only added to the prelude in major releases, so that glob imports of it don't
+//! start to conflict with names of your own.
+//!
+//! # Examples
+//!
+//! ```
+//! use rosc::prelude::*;
+//!
+//! let mut msg = OscMessage::from("/tempo");
+//! msg.push_float(120.0);
+//! let packet = OscPacket::Message(msg);
+//! let buf = encoder::encode(&packet).unwrap();
+//! assert_eq!(decoder::decode_udp(&buf).unwrap().1, packet);
+//! assert!(Matcher::new("/temp?").unwrap().match_address("/tempo").unwrap());
+//! ```
+
+pub use crate::address::{AddressError, Matcher, MatcherOptions};
+pub use crate::decoder::{self, DecodeError, DecoderOptions};
+pub use crate::encoder::{self, EncodeError};
+pub use crate::{
+    OscArray, OscBundle, OscColor, OscError, OscMessage, OscMidiMessage, OscPacket, OscTime,
+    OscType,
+};
@@ -0,0 +1,161 @@
+/// The type tags defined by the OSC specification that rosc understands, including the array
+/// delimiters.
+const STANDARD_TYPE_TAGS: &str = "ifsbhtdcrmTFNI[]";
+
+/// The features of OSC a peer supports, e.g. to only send older devices packets they can decode
+/// and reject anything else they would not send. Pass the same profile as
+/// [`EncoderOptions::profile`](crate::encoder::EncoderOptions),
+/// [`DecoderOptions::profile`](crate::decoder::DecoderOptions) and
+/// [`MatcherOptions::profile`](crate::address::MatcherOptions) for each peer.
+///
+/// A profile lists the standard type tags that may be used, see [`Profile::supports_tag`].
+/// Extension tags such as `u` are not part of any profile: the decoder accepts them according to
+/// [`DecoderOptions::accept_extension_tags`](crate::decoder::DecoderOptions) and the encoder
+/// always writes them. The matcher rejects patterns with the `//` path traversal wildcard unless
+/// the profile allows it, though [`Matcher`](crate::address::Matcher) doesn't implement matching
+/// `//` either way. Whether SLIP framing is allowed is only recorded for choosing how to talk to
+/// the peer, as the framing is up to the transport.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::decoder::{self, DecodeError, DecoderOptions};
+/// use rosc::encoder::{self, EncoderOptions};
+/// use rosc::profile::Profile;
+/// use rosc::{OscMessage, OscPacket, OscType};
+///
+/// let packet = OscPacket::Message(OscMessage {
+///     addr: "/mute".to_string(),
+///     args: vec![OscType::Bool(true)],
+/// });
+/// let options = EncoderOptions {
+///     profile: Profile::CORE_1_0,
+/// };
+/// assert!(encoder::encode_with_options(&packet, &options).is_err());
+///
+/// let buf = encoder::encode(&packet).unwrap();
+/// let options = DecoderOptions {
+///     profile: Profile::CORE_1_0,
+///     ..Default::default()
+/// };
+/// let err = decoder::decode_with_options(&buf, &options).unwrap_err();
+/// assert_eq!(err.root_cause(), &DecodeError::UnsupportedTypeTag('T'));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Profile {
+    // Bit n is set if the type tag with the ASCII code n is allowed
+    type_tags: u128,
+    path_traversal: bool,
+    slip: bool,
+}
+
+impl Profile {
+    /// OSC 1.0 with only the four types every implementation has to support: int, float,
+    /// string and blob, without path traversal and SLIP framing.
+    pub const CORE_1_0: Profile = Profile {
+        type_tags: type_tag_bits("ifsb"),
+        path_traversal: false,
+        slip: false,
+    };
+
+    /// OSC 1.1 with every standard type, path traversal and SLIP framing. This is the default,
+    /// under which nothing is rejected.
+    pub const FULL_1_1: Profile = Profile {
+        type_tags: type_tag_bits(STANDARD_TYPE_TAGS),
+        path_traversal: true,
+        slip: true,
+    };
+
+    /// Returns a builder for a custom profile, which starts out allowing nothing.
+    pub fn builder() -> ProfileBuilder {
+        ProfileBuilder {
+            profile: Profile {
+                type_tags: 0,
+                path_traversal: false,
+                slip: false,
+            },
+        }
+    }
+
+    /// Returns whether the standard type tag `tag`, or the array delimiter `[` or `]`, may be
+    /// used. Always `false` for other characters.
+    pub fn supports_tag(&self, tag: char) -> bool {
+        (tag as u32) < 128 && self.type_tags & (1 << tag as u32) != 0
+    }
+
+    /// Returns whether address patterns may contain the `//` path traversal wildcard.
+    pub fn allows_path_traversal(&self) -> bool {
+        self.path_traversal
+    }
+
+    /// Returns whether packets may be framed with SLIP on streams, see
+    /// [`framing::encode_slip`](crate::framing::encode_slip), instead of being prefixed with
+    /// their size.
+    pub fn allows_slip(&self) -> bool {
+        self.slip
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile::FULL_1_1
+    }
+}
+
+/// Builds a custom [`Profile`], see [`Profile::builder`].
+///
+/// # Examples
+///
+/// ```
+/// use rosc::profile::Profile;
+///
+/// let profile = Profile::builder().type_tags("ifsbTF").slip(true).build();
+/// assert!(profile.supports_tag('T'));
+/// assert!(!profile.supports_tag('h'));
+/// assert!(profile.allows_slip());
+/// assert!(!profile.allows_path_traversal());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProfileBuilder {
+    profile: Profile,
+}
+
+impl ProfileBuilder {
+    /// Allows the type tags in `tags` in addition to those allowed already. Characters that are
+    /// not standard type tags are ignored.
+    pub fn type_tags(mut self, tags: &str) -> Self {
+        self.profile.type_tags |= type_tag_bits(tags) & type_tag_bits(STANDARD_TYPE_TAGS);
+        self
+    }
+
+    /// Sets whether address patterns may contain the `//` path traversal wildcard.
+    pub fn path_traversal(mut self, allowed: bool) -> Self {
+        self.profile.path_traversal = allowed;
+        self
+    }
+
+    /// Sets whether packets may be framed with SLIP.
+    pub fn slip(mut self, allowed: bool) -> Self {
+        self.profile.slip = allowed;
+        self
+    }
+
+    /// Returns the profile.
+    pub fn build(self) -> Profile {
+        self.profile
+    }
+}
+
+/// Returns the bits of the ASCII characters in `tags`, see `Profile::type_tags`.
+const fn type_tag_bits(tags: &str) -> u128 {
+    let tags = tags.as_bytes();
+    let mut bits = 0;
+    let mut i = 0;
+    while i < tags.len() {
+        if tags[i] < 128 {
+            bits |= 1 << tags[i];
+        }
+        i += 1;
+    }
+    bits
+}
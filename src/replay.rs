@@ -0,0 +1,330 @@
+use crate::decoder::{self, DecoderOptions};
+use crate::encoder;
+use crate::errors::OscError;
+use crate::types::OscPacket;
+
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Identifies a recording, followed by the version of the format.
+const MAGIC: &[u8; 8] = b"ROSCREC\0";
+
+/// Version of the format written by [`Recorder`].
+const VERSION: u8 = 1;
+
+// Address families of the peer of a record
+const NO_PEER: u8 = 0;
+const IPV4_PEER: u8 = 4;
+const IPV6_PEER: u8 = 6;
+
+/// Whether a recorded packet was received or sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Received,
+    Sent,
+}
+
+/// A packet read from a recording by [`Player::next_record`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// Time since the recording was started.
+    pub timestamp: Duration,
+    pub direction: Direction,
+    /// The sender of a received packet or the receiver of a sent one, if known.
+    pub peer: Option<SocketAddr>,
+    /// The packet as it was received or sent, which is not necessarily a valid OSC packet.
+    pub data: Vec<u8>,
+}
+
+/// Records packets along with the time since the recording was started, which can be replayed
+/// with [`Player`].
+///
+/// A recording starts with the magic bytes `ROSCREC\0` and a version byte, currently 1. Each
+/// record then consists of, in network byte order:
+///
+/// * the timestamp in nanoseconds as a 64-bit integer,
+/// * the direction, 0 if the packet was received and 1 if it was sent,
+/// * the address family of the peer, 0 if there is none, 4 for IPv4 or 6 for IPv6, followed by
+///   the 4 or 16 bytes of the IP address and the 16-bit port,
+/// * the length of the packet as a 32-bit integer and the packet itself.
+///
+/// Timestamps are taken from a monotonic clock, so they are unaffected by changes of the system
+/// time.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::replay::{Direction, Player, Recorder};
+/// use rosc::{OscMessage, OscPacket};
+///
+/// let mut recorder = Recorder::new(Vec::new()).unwrap();
+/// let packet = OscPacket::Message(OscMessage::from("/play"));
+/// recorder.record_packet(Direction::Sent, None, &packet).unwrap();
+///
+/// let recording = recorder.into_inner();
+/// let mut player = Player::new(&recording[..]).unwrap();
+/// let (_, replayed) = player.next().unwrap().unwrap();
+/// assert_eq!(replayed, packet);
+/// assert!(player.next().is_none());
+/// ```
+#[derive(Debug)]
+pub struct Recorder<W: Write> {
+    writer: W,
+    start: Instant,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Starts a recording by writing the header to `writer`.
+    pub fn new(mut writer: W) -> Result<Self, OscError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        Ok(Recorder {
+            writer,
+            start: Instant::now(),
+        })
+    }
+
+    /// Appends a record of the raw bytes of a packet.
+    pub fn record(
+        &mut self,
+        direction: Direction,
+        peer: Option<SocketAddr>,
+        data: &[u8],
+    ) -> Result<(), OscError> {
+        let len = u32::try_from(data.len()).map_err(|_| OscError::PacketTooLarge {
+            size: data.len(),
+            max: u32::MAX as usize,
+        })?;
+        let timestamp = self.start.elapsed().as_nanos() as u64;
+
+        let mut header = timestamp.to_be_bytes().to_vec();
+        header.push(match direction {
+            Direction::Received => 0,
+            Direction::Sent => 1,
+        });
+        match peer {
+            None => header.push(NO_PEER),
+            Some(SocketAddr::V4(addr)) => {
+                header.push(IPV4_PEER);
+                header.extend(addr.ip().octets());
+                header.extend(addr.port().to_be_bytes());
+            }
+            Some(SocketAddr::V6(addr)) => {
+                header.push(IPV6_PEER);
+                header.extend(addr.ip().octets());
+                header.extend(addr.port().to_be_bytes());
+            }
+        }
+        header.extend(len.to_be_bytes());
+        self.writer.write_all(&header)?;
+        self.writer.write_all(data)?;
+        Ok(())
+    }
+
+    /// Encodes `packet` and appends a record of it.
+    pub fn record_packet(
+        &mut self,
+        direction: Direction,
+        peer: Option<SocketAddr>,
+        packet: &OscPacket,
+    ) -> Result<(), OscError> {
+        self.record(direction, peer, &encoder::encode(packet)?)
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> Result<(), OscError> {
+        Ok(self.writer.flush()?)
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads a recording written by [`Recorder`].
+///
+/// As an iterator, a `Player` yields the timestamp and the decoded packet of each record. Packets
+/// that fail to decode are yielded as the error of the decoder. Malformed recordings, including
+/// truncated ones, result in [`OscError::BadRecording`], after which the iteration ends.
+#[derive(Debug)]
+pub struct Player<R: Read> {
+    reader: R,
+    options: DecoderOptions,
+    failed: bool,
+}
+
+impl<R: Read> Player<R> {
+    /// Reads the header of the recording from `reader`, failing if it is not a recording or its
+    /// version is not supported.
+    pub fn new(mut reader: R) -> Result<Self, OscError> {
+        let mut header = [0u8; 9];
+        let len = read_fully(&mut reader, &mut header)?;
+        let magic_len = len.min(MAGIC.len());
+        if header[..magic_len] != MAGIC[..magic_len] {
+            return Err(bad_recording("Not an OSC recording"));
+        }
+        if len < header.len() {
+            return Err(bad_recording("Truncated header"));
+        }
+        if header[8] != VERSION {
+            return Err(OscError::BadRecording(format!(
+                "Unsupported version {}",
+                header[8]
+            )));
+        }
+        Ok(Player {
+            reader,
+            options: DecoderOptions::default(),
+            failed: false,
+        })
+    }
+
+    /// Sets the options used to decode the recorded packets, which default to
+    /// `DecoderOptions::default()`.
+    pub fn set_decoder_options(&mut self, options: DecoderOptions) {
+        self.options = options;
+    }
+
+    /// Reads the next record without decoding its packet, or returns `None` at the end of the
+    /// recording.
+    pub fn next_record(&mut self) -> Result<Option<Record>, OscError> {
+        let mut fixed = [0u8; 10];
+        match read_fully(&mut self.reader, &mut fixed)? {
+            0 => return Ok(None),
+            10 => {}
+            _ => return Err(bad_recording("Truncated record")),
+        }
+        let mut timestamp = [0u8; 8];
+        timestamp.copy_from_slice(&fixed[..8]);
+        let timestamp = Duration::from_nanos(u64::from_be_bytes(timestamp));
+        let direction = match fixed[8] {
+            0 => Direction::Received,
+            1 => Direction::Sent,
+            other => {
+                return Err(OscError::BadRecording(format!("Invalid direction {}", other)))
+            }
+        };
+        let peer = match fixed[9] {
+            NO_PEER => None,
+            IPV4_PEER => {
+                let mut ip = [0u8; 4];
+                self.read_exact(&mut ip)?;
+                Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::from(ip)), self.read_u16()?))
+            }
+            IPV6_PEER => {
+                let mut ip = [0u8; 16];
+                self.read_exact(&mut ip)?;
+                Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(ip)), self.read_u16()?))
+            }
+            other => {
+                return Err(OscError::BadRecording(format!(
+                    "Invalid peer address family {}",
+                    other
+                )))
+            }
+        };
+        let mut len = [0u8; 4];
+        self.read_exact(&mut len)?;
+        let len = u32::from_be_bytes(len) as u64;
+
+        // Read through `take` instead of allocating `len` bytes up front, which a corrupted
+        // length would make arbitrarily large
+        let mut data = Vec::new();
+        (&mut self.reader).take(len).read_to_end(&mut data)?;
+        if (data.len() as u64) < len {
+            return Err(bad_recording("Truncated record"));
+        }
+        Ok(Some(Record {
+            timestamp,
+            direction,
+            peer,
+            data,
+        }))
+    }
+
+    /// Sends the packets of all records with the given `direction` from `socket` to `target`,
+    /// keeping the original time between them. The first packet is sent right away.
+    ///
+    /// Packets are sent as they were recorded, without decoding them. Stops at the first error.
+    pub fn play_into<A: ToSocketAddrs>(
+        mut self,
+        socket: &UdpSocket,
+        target: A,
+        direction: Direction,
+    ) -> Result<(), OscError> {
+        let target = target.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no address to play into")
+        })?;
+        let mut timing = None;
+        while let Some(record) = self.next_record()? {
+            if record.direction != direction {
+                continue;
+            }
+            let (start, first) = *timing.get_or_insert((Instant::now(), record.timestamp));
+            let due = start + record.timestamp.saturating_sub(first);
+            let now = Instant::now();
+            if due > now {
+                thread::sleep(due - now);
+            }
+            socket.send_to(&record.data, target)?;
+        }
+        Ok(())
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), OscError> {
+        if read_fully(&mut self.reader, buf)? < buf.len() {
+            return Err(bad_recording("Truncated record"));
+        }
+        Ok(())
+    }
+
+    fn read_u16(&mut self) -> Result<u16, OscError> {
+        let mut buf = [0u8; 2];
+        self.read_exact(&mut buf)?;
+        Ok(u16::from_be_bytes(buf))
+    }
+}
+
+impl<R: Read> Iterator for Player<R> {
+    type Item = Result<(Duration, OscPacket), OscError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.next_record() {
+            Ok(Some(record)) => Some(
+                decoder::decode_with_options(&record.data, &self.options)
+                    .map(|packet| (record.timestamp, packet))
+                    .map_err(OscError::from),
+            ),
+            Ok(None) => None,
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+fn bad_recording(msg: &str) -> OscError {
+    OscError::BadRecording(msg.to_string())
+}
+
+/// Fills `buf` from `reader` unless it ends before, returns the number of bytes read.
+fn read_fully<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, OscError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(filled)
+}
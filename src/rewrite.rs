@@ -0,0 +1,89 @@
+use crate::address::verify_address_pattern;
+use crate::decoder::{self, MAX_BUNDLE_DEPTH};
+use crate::encoder::{self, BUNDLE_HEADER_SIZE};
+use crate::errors::OscError;
+
+use crate::alloc::{string::String, vec::Vec};
+
+/// Rewrites the addresses of the messages in the raw packet `buf`, including those in nested
+/// bundles, and returns the rewritten packet. `f` is passed each address and returns the address
+/// to replace it with, or `None` to keep it.
+///
+/// Only the address strings and the sizes of the bundle elements containing them change: the
+/// time tags, type tags and arguments are copied byte by byte without being decoded, so float bit
+/// patterns, unknown type tags and the padding of non-canonical senders are forwarded as they
+/// are. A packet in which no address is replaced is returned unchanged.
+///
+/// Fails if `buf` is not made up of well-formed bundles and messages as far as the addresses go,
+/// see [`decoder::iter_bundle_elements`] and [`decoder::peek_address`], if bundles are nested
+/// deeper than [`MAX_BUNDLE_DEPTH`], or if `f` returns an invalid address pattern.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::{encoder, rewrite, OscMessage, OscPacket};
+///
+/// let packet = OscPacket::Message(OscMessage {
+///     addr: "/deviceA/gain".to_string(),
+///     args: vec![0.5f32.into()],
+/// });
+/// let buf = encoder::encode(&packet).unwrap();
+/// let rewritten = rewrite::rewrite_addresses(&buf, |addr| {
+///     addr.strip_prefix("/deviceA/").map(|rest| format!("/rig/1/{}", rest))
+/// })
+/// .unwrap();
+///
+/// let expected = OscPacket::Message(OscMessage {
+///     addr: "/rig/1/gain".to_string(),
+///     args: vec![0.5f32.into()],
+/// });
+/// assert_eq!(rewritten, encoder::encode(&expected).unwrap());
+/// ```
+pub fn rewrite_addresses<F>(buf: &[u8], f: F) -> Result<Vec<u8>, OscError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut out = Vec::with_capacity(buf.len());
+    rewrite_packet(buf, &f, &mut out, 0)?;
+    Ok(out)
+}
+
+/// Appends the rewritten packet `buf`, which is contained in `depth` bundles, to `out`.
+fn rewrite_packet(
+    buf: &[u8],
+    f: &dyn Fn(&str) -> Option<String>,
+    out: &mut Vec<u8>,
+    depth: usize,
+) -> Result<(), OscError> {
+    if !decoder::is_bundle(buf) {
+        let addr = decoder::peek_address(buf)?;
+        match f(addr) {
+            Some(rewritten) => {
+                verify_address_pattern(&rewritten)?;
+                // The address was read strictly, so it is followed by 1 to 4 zeros
+                let rest = &buf[encoder::pad(addr.len() as u64 + 1) as usize..];
+                out.extend(encoder::encode_string(rewritten));
+                out.extend_from_slice(rest);
+            }
+            None => out.extend_from_slice(buf),
+        }
+        return Ok(());
+    }
+
+    if depth >= MAX_BUNDLE_DEPTH {
+        return Err(OscError::BundleTooDeep {
+            max: MAX_BUNDLE_DEPTH,
+        });
+    }
+    let elements = decoder::iter_bundle_elements(buf)?;
+    out.extend_from_slice(&buf[..BUNDLE_HEADER_SIZE]);
+    for element in elements {
+        let (element, _) = element?;
+        let size_start = out.len();
+        out.extend_from_slice(&[0; 4]);
+        rewrite_packet(element, f, out, depth + 1)?;
+        let size = out.len() - size_start - 4;
+        out[size_start..size_start + 4].copy_from_slice(&(size as u32).to_be_bytes());
+    }
+    Ok(())
+}
@@ -0,0 +1,299 @@
+use crate::address::verify_address;
+use crate::errors::OscError;
+use crate::types::{Clock, OscMessage, OscTime, OscType, SystemClock};
+
+use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// The last correlation ID handed out by [`Request::new`], shared by all requests so that
+/// requests of different [`PendingRequests`] tables to the same device don't collide.
+static LAST_ID: AtomicU32 = AtomicU32::new(0);
+
+/// The type of the correlation ID argument, see [`RequestOptions::id_kind`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdKind {
+    /// An int argument.
+    #[default]
+    Int,
+    /// A string argument with the decimal digits of the ID.
+    String,
+}
+
+/// The address replies to a request are sent to, see [`RequestOptions::reply_address`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplyAddress {
+    /// The address of the request.
+    Same,
+    /// The address of the request followed by the given suffix, e.g. `/status/reply` for the
+    /// suffix `/reply`.
+    Suffix(String),
+    /// The given address, regardless of the address of the request.
+    Exact(String),
+}
+
+impl Default for ReplyAddress {
+    fn default() -> Self {
+        ReplyAddress::Suffix("/reply".to_string())
+    }
+}
+
+/// Where replies carry the correlation ID, see [`RequestOptions::id_position`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdPosition {
+    /// Replies echo the arguments of the request, including the correlation ID, followed by the
+    /// results.
+    #[default]
+    Echoed,
+    /// The correlation ID is the first argument of replies.
+    First,
+}
+
+/// Options for [`Request::with_options`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// The type of the correlation ID argument. Defaults to [`IdKind::Int`].
+    pub id_kind: IdKind,
+    /// The address replies are expected at. Defaults to the address of the request followed by
+    /// `/reply`.
+    pub reply_address: ReplyAddress,
+    /// Where replies carry the correlation ID. Defaults to [`IdPosition::Echoed`].
+    pub id_position: IdPosition,
+}
+
+/// A message that expects a reply, with a correlation ID appended to its arguments to tell the
+/// reply apart from those to other requests. Register it with [`PendingRequests::register`] and
+/// send [`message`](Request::message) over any transport.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::rpc::Request;
+/// use rosc::OscType;
+///
+/// let request = Request::new("/status", vec![OscType::Int(1)]).unwrap();
+/// assert_eq!(request.message().addr, "/status");
+/// assert_eq!(request.message().args, vec![OscType::Int(1), request.id().clone()]);
+/// assert_eq!(request.reply_address(), "/status/reply");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    message: OscMessage,
+    id: OscType,
+    reply_address: String,
+    id_index: usize,
+}
+
+impl Request {
+    /// Creates a request to `addr` with the arguments `args` and an int correlation ID, whose
+    /// replies are expected at `addr` followed by `/reply`. Fails if `addr` is not a valid OSC
+    /// address.
+    pub fn new<I, T>(addr: &str, args: I) -> Result<Self, OscError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OscType>,
+    {
+        Request::with_options(addr, args, &RequestOptions::default())
+    }
+
+    /// Creates a request like [`Request::new`], using the given options.
+    pub fn with_options<I, T>(
+        addr: &str,
+        args: I,
+        options: &RequestOptions,
+    ) -> Result<Self, OscError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OscType>,
+    {
+        verify_address(addr)?;
+        let mut args: Vec<OscType> = args.into_iter().map(Into::into).collect();
+        // IDs stay positive, since some devices treat negative ints as errors
+        let id = LAST_ID.fetch_add(1, Ordering::Relaxed).wrapping_add(1) & i32::MAX as u32;
+        let id = match options.id_kind {
+            IdKind::Int => OscType::Int(id as i32),
+            IdKind::String => OscType::String(id.to_string()),
+        };
+        let id_index = match options.id_position {
+            IdPosition::Echoed => args.len(),
+            IdPosition::First => 0,
+        };
+        args.push(id.clone());
+        let reply_address = match options.reply_address {
+            ReplyAddress::Same => addr.to_string(),
+            ReplyAddress::Suffix(ref suffix) => format!("{}{}", addr, suffix),
+            ReplyAddress::Exact(ref reply_address) => reply_address.clone(),
+        };
+        Ok(Request {
+            message: OscMessage {
+                addr: addr.to_string(),
+                args,
+            },
+            id,
+            reply_address,
+            id_index,
+        })
+    }
+
+    /// Returns the message to send, whose last argument is the correlation ID.
+    pub fn message(&self) -> &OscMessage {
+        &self.message
+    }
+
+    /// Returns the correlation ID argument.
+    pub fn id(&self) -> &OscType {
+        &self.id
+    }
+
+    /// Returns the address replies are expected at.
+    pub fn reply_address(&self) -> &str {
+        &self.reply_address
+    }
+
+    /// Returns whether `reply` is sent to the reply address and carries the correlation ID of the
+    /// request.
+    pub fn is_reply(&self, reply: &OscMessage) -> bool {
+        reply.addr == self.reply_address && reply.args.get(self.id_index) == Some(&self.id)
+    }
+}
+
+/// Identifies a request registered with [`PendingRequests::register`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RequestToken(u64);
+
+/// A registered request along with the time it was registered at.
+#[derive(Debug)]
+struct Pending {
+    token: RequestToken,
+    request: Request,
+    registered_at: OscTime,
+}
+
+/// The requests that are waiting for a reply. Received messages are passed to
+/// [`try_complete`](PendingRequests::try_complete), which takes the request they reply to out of
+/// the table, and requests that receive no reply are removed with
+/// [`expire_older_than`](PendingRequests::expire_older_than).
+///
+/// The table doesn't send or receive anything itself, so it can be used with any transport.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::rpc::{PendingRequests, Request};
+/// use rosc::{OscMessage, OscType};
+/// use std::time::Duration;
+///
+/// let mut pending = PendingRequests::new();
+/// let request = Request::new("/status", Vec::<OscType>::new()).unwrap();
+/// let token = pending.register(request.clone());
+/// // Send request.message() to the device, which replies with the ID and its status
+/// let mut reply = OscMessage::from("/status/reply");
+/// reply.args.push(request.id().clone());
+/// reply.push_str("ok");
+///
+/// assert_eq!(pending.try_complete(&OscMessage::from("/other")), None);
+/// assert_eq!(pending.try_complete(&reply), Some((token, reply.clone())));
+/// assert!(pending.is_empty());
+/// assert_eq!(pending.expire_older_than(Duration::from_secs(1)), vec![]);
+/// ```
+pub struct PendingRequests {
+    pending: Vec<Pending>,
+    next_token: u64,
+    clock: Box<dyn Clock + Send + Sync>,
+}
+
+impl PendingRequests {
+    /// Creates an empty table, which takes the time requests are registered at from the system
+    /// time.
+    pub fn new() -> Self {
+        PendingRequests::with_clock(SystemClock)
+    }
+
+    /// Creates an empty table like [`PendingRequests::new`], which takes the time requests are
+    /// registered at from `clock`, e.g. a [`ManualClock`](crate::ManualClock) in tests.
+    pub fn with_clock<C: Clock + Send + Sync + 'static>(clock: C) -> Self {
+        PendingRequests {
+            pending: Vec::new(),
+            next_token: 0,
+            clock: Box::new(clock),
+        }
+    }
+
+    /// Adds `request` to the table and returns the token identifying it.
+    pub fn register(&mut self, request: Request) -> RequestToken {
+        let token = RequestToken(self.next_token);
+        self.next_token += 1;
+        self.pending.push(Pending {
+            token,
+            request,
+            registered_at: self.clock.now(),
+        });
+        token
+    }
+
+    /// Removes the request that `reply` replies to, see [`Request::is_reply`], and returns its
+    /// token along with `reply`. Returns `None` and leaves the table unchanged if `reply` doesn't
+    /// reply to any pending request.
+    pub fn try_complete(&mut self, reply: &OscMessage) -> Option<(RequestToken, OscMessage)> {
+        let index = self
+            .pending
+            .iter()
+            .position(|pending| pending.request.is_reply(reply))?;
+        Some((self.pending.remove(index).token, reply.clone()))
+    }
+
+    /// Removes the request identified by `token`, e.g. when giving up on it. Returns the request,
+    /// or `None` if it was already completed, expired or cancelled.
+    pub fn cancel(&mut self, token: RequestToken) -> Option<Request> {
+        let index = self.pending.iter().position(|pending| pending.token == token)?;
+        Some(self.pending.remove(index).request)
+    }
+
+    /// Removes the requests that were registered more than `age` ago and returns their tokens,
+    /// in the order they were registered in.
+    pub fn expire_older_than(&mut self, age: Duration) -> Vec<RequestToken> {
+        let now = self.clock.now();
+        let mut expired = Vec::new();
+        self.pending.retain(|pending| {
+            let keep = now.saturating_duration_since(pending.registered_at) <= age;
+            if !keep {
+                expired.push(pending.token);
+            }
+            keep
+        });
+        expired
+    }
+
+    /// Returns the request identified by `token` if it is still pending.
+    pub fn get(&self, token: RequestToken) -> Option<&Request> {
+        self.pending
+            .iter()
+            .find(|pending| pending.token == token)
+            .map(|pending| &pending.request)
+    }
+
+    /// Returns the number of pending requests.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns whether no requests are pending.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+impl Default for PendingRequests {
+    fn default() -> Self {
+        PendingRequests::new()
+    }
+}
+
+impl fmt::Debug for PendingRequests {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PendingRequests")
+            .field("pending", &self.pending)
+            .field("next_token", &self.next_token)
+            .finish()
+    }
+}
@@ -0,0 +1,365 @@
+use crate::errors::OscError;
+use crate::types::{Clock, OscMessage, OscPacket, OscTime, SystemClock};
+
+use std::cmp::{Ordering, Reverse};
+use std::fmt;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Messages waiting for their time tags, ordered by time tag and then by the order in which they
+/// were pushed.
+///
+/// This is the queue used by [`Scheduler`], which can be used on its own when messages have to be
+/// delivered on a particular thread, e.g. in an event loop. Messages may carry data of their own,
+/// e.g. the address they were received from, by queueing a type other than `OscMessage` with
+/// [`push`](Queue::push).
+///
+/// # Examples
+///
+/// ```
+/// use rosc::scheduler::Queue;
+/// use rosc::{OscBundle, OscMessage, OscPacket, OscTime};
+///
+/// let mut queue = Queue::new();
+/// queue.push_packet(OscPacket::Bundle(OscBundle {
+///     timetag: (100, 0).into(),
+///     content: vec![OscPacket::Message(OscMessage::from("/later"))],
+/// }));
+/// queue.push_packet(OscPacket::Message(OscMessage::from("/now")));
+///
+/// let now = OscTime::from((50, 0));
+/// assert_eq!(queue.pop_due(now), Some(OscMessage::from("/now")));
+/// assert_eq!(queue.pop_due(now), None);
+/// assert_eq!(queue.next_timetag(), Some((100, 0).into()));
+/// ```
+#[derive(Debug)]
+pub struct Queue<M = OscMessage> {
+    heap: BinaryHeap<Reverse<Pending<M>>>,
+    pushed: u64,
+}
+
+#[derive(Debug)]
+struct Pending<M> {
+    timetag: OscTime,
+    index: u64,
+    msg: M,
+}
+
+impl<M> PartialEq for Pending<M> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.timetag, self.index) == (other.timetag, other.index)
+    }
+}
+
+impl<M> Eq for Pending<M> {}
+
+impl<M> PartialOrd for Pending<M> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<M> Ord for Pending<M> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.timetag, self.index).cmp(&(other.timetag, other.index))
+    }
+}
+
+impl<M> Default for Queue<M> {
+    fn default() -> Self {
+        Queue {
+            heap: BinaryHeap::new(),
+            pushed: 0,
+        }
+    }
+}
+
+impl Queue {
+    /// Creates an empty queue.
+    pub fn new() -> Self {
+        Queue::default()
+    }
+
+    /// Adds all messages of `packet`, each of which is due at the time tag of the bundle
+    /// containing it, see [`OscPacket::into_messages`].
+    pub fn push_packet(&mut self, packet: OscPacket) {
+        for (timetag, msg) in packet.into_messages() {
+            self.push(timetag, msg);
+        }
+    }
+}
+
+impl<M> Queue<M> {
+    /// Adds a message that is due at `timetag`. Immediate messages are due right away, as
+    /// [`OscTime::IMMEDIATE`] precedes every other time tag except `(0, 0)`.
+    pub fn push(&mut self, timetag: OscTime, msg: M) {
+        self.heap.push(Reverse(Pending {
+            timetag,
+            index: self.pushed,
+            msg,
+        }));
+        self.pushed += 1;
+    }
+
+    /// Removes and returns the next message if it is due at `now`.
+    pub fn pop_due(&mut self, now: OscTime) -> Option<M> {
+        if self.next_timetag()? > now {
+            return None;
+        }
+        self.heap.pop().map(|Reverse(pending)| pending.msg)
+    }
+
+    /// Returns the time tag of the next message.
+    pub fn next_timetag(&self) -> Option<OscTime> {
+        self.heap.peek().map(|next| next.0.timetag)
+    }
+
+    /// Returns the time from `now` until the next message is due, which is zero if it is due
+    /// already.
+    pub fn time_until_next(&self, now: OscTime) -> Option<Duration> {
+        self.next_timetag().map(|timetag| timetag.saturating_duration_since(now))
+    }
+
+    /// Returns the number of messages in the queue.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns whether the queue contains no messages.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
+
+/// Options for [`Scheduler::with_options`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SchedulerOptions {
+    /// Reject packets containing messages that are due further than this in the future with
+    /// [`OscError::TooFarInFuture`]. Defaults to `None`, which accepts any time tag.
+    pub max_delay: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    queue: Queue,
+    stop: Option<Stop>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stop {
+    // Deliver the pending messages right away and stop
+    Drain,
+    // Drop the pending messages and stop
+    Shutdown,
+}
+
+/// Delivers the messages of submitted packets at their time tags on a worker thread.
+///
+/// Messages that are due already, including immediate ones, are delivered right away, all others
+/// when their time tag is reached. Messages are delivered in the order of their time tags and, if
+/// they are equal, in the order they were submitted. Dropping the scheduler behaves like
+/// [`shutdown`](Scheduler::shutdown).
+///
+/// # Examples
+///
+/// ```
+/// use rosc::scheduler::Scheduler;
+/// use rosc::{OscBundle, OscMessage, OscPacket, OscTime};
+/// use std::convert::TryFrom;
+/// use std::sync::mpsc;
+/// use std::time::{Duration, SystemTime};
+///
+/// let (tx, rx) = mpsc::channel();
+/// let scheduler = Scheduler::new(move |msg| tx.send(msg).unwrap());
+/// let later = SystemTime::now() + Duration::from_millis(10);
+/// scheduler
+///     .submit(OscPacket::Bundle(OscBundle {
+///         timetag: OscTime::try_from(later).unwrap(),
+///         content: vec![OscPacket::Message(OscMessage::from("/later"))],
+///     }))
+///     .unwrap();
+///
+/// assert_eq!(rx.recv().unwrap(), OscMessage::from("/later"));
+/// assert!(SystemTime::now() >= later);
+/// ```
+pub struct Scheduler {
+    shared: Arc<(Mutex<State>, Condvar)>,
+    worker: Option<JoinHandle<()>>,
+    options: SchedulerOptions,
+    clock: Arc<dyn Clock + Send + Sync>,
+}
+
+impl Scheduler {
+    /// Creates a scheduler which passes due messages to `callback` on its worker thread.
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: FnMut(OscMessage) + Send + 'static,
+    {
+        Scheduler::with_options(callback, SchedulerOptions::default())
+    }
+
+    /// Creates a scheduler like [`Scheduler::new`], using the given options.
+    pub fn with_options<F>(callback: F, options: SchedulerOptions) -> Self
+    where
+        F: FnMut(OscMessage) + Send + 'static,
+    {
+        Scheduler::with_clock(callback, options, SystemClock)
+    }
+
+    /// Creates a scheduler like [`Scheduler::with_options`] which takes the current time from
+    /// `clock` instead of the system time.
+    ///
+    /// The worker thread sleeps for the time until the next message is due, so `clock` has to
+    /// advance at the same rate as the system time, but it may be offset from it. Clocks that
+    /// jump ahead, such as a [`ManualClock`](crate::ManualClock), have to be followed by a call
+    /// to [`wake`](Scheduler::wake).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::scheduler::{Scheduler, SchedulerOptions};
+    /// use rosc::{ManualClock, OscBundle, OscMessage, OscPacket};
+    /// use std::sync::mpsc;
+    /// use std::time::Duration;
+    ///
+    /// let clock = ManualClock::new((100, 0).into());
+    /// let (tx, rx) = mpsc::channel();
+    /// let scheduler = Scheduler::with_clock(
+    ///     move |msg| tx.send(msg).unwrap(),
+    ///     SchedulerOptions::default(),
+    ///     clock.clone(),
+    /// );
+    /// scheduler
+    ///     .submit(OscPacket::Bundle(OscBundle {
+    ///         timetag: (160, 0).into(),
+    ///         content: vec![OscPacket::Message(OscMessage::from("/in_a_minute"))],
+    ///     }))
+    ///     .unwrap();
+    ///
+    /// clock.advance(Duration::from_secs(60));
+    /// scheduler.wake();
+    /// assert_eq!(rx.recv().unwrap(), OscMessage::from("/in_a_minute"));
+    /// ```
+    pub fn with_clock<F, C>(mut callback: F, options: SchedulerOptions, clock: C) -> Self
+    where
+        F: FnMut(OscMessage) + Send + 'static,
+        C: Clock + Send + Sync + 'static,
+    {
+        let clock: Arc<dyn Clock + Send + Sync> = Arc::new(clock);
+        let shared = Arc::new((Mutex::new(State::default()), Condvar::new()));
+        let worker = {
+            let shared = shared.clone();
+            let clock = clock.clone();
+            thread::spawn(move || {
+                let (state, wakeup) = &*shared;
+                let mut state = lock(state);
+                loop {
+                    let now = match state.stop {
+                        Some(Stop::Shutdown) => return,
+                        Some(Stop::Drain) => OscTime::from((u32::MAX, u32::MAX)),
+                        None => clock.now(),
+                    };
+                    if let Some(msg) = state.queue.pop_due(now) {
+                        drop(state);
+                        callback(msg);
+                        state = lock(&shared.0);
+                        continue;
+                    }
+                    if state.stop.is_some() {
+                        return;
+                    }
+                    state = match state.queue.time_until_next(now) {
+                        Some(timeout) => match wakeup.wait_timeout(state, timeout) {
+                            Ok((state, _)) => state,
+                            Err(poisoned) => poisoned.into_inner().0,
+                        },
+                        None => wakeup.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner()),
+                    };
+                }
+            })
+        };
+        Scheduler {
+            shared,
+            worker: Some(worker),
+            options,
+            clock,
+        }
+    }
+
+    /// Schedules the messages of `packet`. If any of them is due later than
+    /// [`SchedulerOptions::max_delay`] allows, the whole packet is rejected.
+    pub fn submit(&self, packet: OscPacket) -> Result<(), OscError> {
+        let messages: Vec<_> = packet.into_messages().collect();
+        if let Some(max_delay) = self.options.max_delay {
+            let now = self.clock.now();
+            let too_late = messages
+                .iter()
+                .find(|(timetag, _)| timetag.saturating_duration_since(now) > max_delay);
+            if let Some(&(timetag, _)) = too_late {
+                return Err(OscError::TooFarInFuture(timetag));
+            }
+        }
+        let (state, wakeup) = &*self.shared;
+        let mut state = lock(state);
+        for (timetag, msg) in messages {
+            state.queue.push(timetag, msg);
+        }
+        wakeup.notify_one();
+        Ok(())
+    }
+
+    /// Makes the worker thread check the clock right away instead of when it expects the next
+    /// message to be due, e.g. after advancing a [`ManualClock`](crate::ManualClock).
+    pub fn wake(&self) {
+        self.shared.1.notify_one();
+    }
+
+    /// Returns the number of messages that have not been delivered yet.
+    pub fn pending(&self) -> usize {
+        lock(&self.shared.0).queue.len()
+    }
+
+    /// Delivers all pending messages right away, in order, and waits for the worker thread to
+    /// finish.
+    pub fn drain(mut self) {
+        self.stop(Stop::Drain);
+    }
+
+    /// Drops all pending messages and waits for the worker thread to finish, which includes a
+    /// callback that is running.
+    pub fn shutdown(mut self) {
+        self.stop(Stop::Shutdown);
+    }
+
+    fn stop(&mut self, stop: Stop) {
+        let (state, wakeup) = &*self.shared;
+        lock(state).stop = Some(stop);
+        wakeup.notify_one();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("shared", &self.shared)
+            .field("worker", &self.worker)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        self.stop(Stop::Shutdown);
+    }
+}
+
+/// Locks the state, which remains consistent even if a callback panicked while it was unlocked.
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
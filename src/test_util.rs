@@ -0,0 +1,314 @@
+use crate::debug::hex_dump;
+use crate::decoder::{self, DecoderOptions};
+use crate::encoder;
+use crate::types::{OscBundle, OscMessage, OscPacket, OscTime, OscType, SemanticEqOptions};
+
+use crate::alloc::{format, string::String, vec::Vec};
+
+/// Asserts that two [`OscPacket`]s are equal like `assert_eq!`, but on failure reports only the
+/// first difference found by [`diff`](crate::test_util::diff) instead of both packets.
+///
+/// With `epsilon = ...`, floats and doubles are considered equal if they differ by at most the
+/// given amount, see [`diff_with_epsilon`](crate::test_util::diff_with_epsilon). With
+/// `semantic = ...`, the differences ignored by the given [`SemanticEqOptions`] are, see
+/// [`diff_semantic`](crate::test_util::diff_semantic).
+///
+/// # Examples
+///
+/// ```
+/// use rosc::{assert_osc_eq, OscMessage, OscPacket, OscType, SemanticEqOptions};
+///
+/// let packet = |x| {
+///     OscPacket::Message(OscMessage {
+///         addr: "/gain".to_string(),
+///         args: vec![OscType::Float(x)],
+///     })
+/// };
+/// assert_osc_eq!(packet(0.5), packet(0.5));
+/// assert_osc_eq!(packet(0.5), packet(0.5000001), epsilon = 1e-6);
+///
+/// let widening = SemanticEqOptions {
+///     numeric_widening: true,
+///     ..Default::default()
+/// };
+/// let double = OscPacket::Message(OscMessage {
+///     addr: "/gain".to_string(),
+///     args: vec![OscType::Double(0.5)],
+/// });
+/// assert_osc_eq!(packet(0.5), double, semantic = widening);
+/// ```
+#[macro_export]
+macro_rules! assert_osc_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        if let Some(diff) = $crate::test_util::diff(&$left, &$right) {
+            panic!("assertion `left == right` failed: {}", diff);
+        }
+    };
+    ($left:expr, $right:expr, epsilon = $epsilon:expr $(,)?) => {
+        if let Some(diff) = $crate::test_util::diff_with_epsilon(&$left, &$right, $epsilon) {
+            panic!("assertion `left == right` failed: {}", diff);
+        }
+    };
+    ($left:expr, $right:expr, semantic = $options:expr $(,)?) => {
+        if let Some(diff) = $crate::test_util::diff_semantic(&$left, &$right, &$options) {
+            panic!("assertion `left == right` failed: {}", diff);
+        }
+    };
+}
+
+/// Returns a description of the first difference between `left` and `right`, or `None` if they
+/// are equal. The description names where the difference is, e.g.
+/// `element 1: argument 2: blob differs at byte 5: 0x01 != 0x02`.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::test_util::diff;
+/// use rosc::{OscMessage, OscPacket};
+///
+/// let left = OscPacket::Message(OscMessage::from("/a"));
+/// let right = OscPacket::Message(OscMessage::from("/b"));
+/// assert_eq!(diff(&left, &left), None);
+/// assert_eq!(diff(&left, &right).unwrap(), r#"address differs: "/a" != "/b""#);
+/// ```
+pub fn diff(left: &OscPacket, right: &OscPacket) -> Option<String> {
+    diff_packets(left, right, &SemanticEqOptions::default())
+}
+
+/// Like [`diff`], but floats and doubles are considered equal if they differ by at most
+/// `epsilon`.
+pub fn diff_with_epsilon(left: &OscPacket, right: &OscPacket, epsilon: f64) -> Option<String> {
+    let options = SemanticEqOptions {
+        epsilon,
+        ..Default::default()
+    };
+    diff_packets(left, right, &options)
+}
+
+/// Like [`diff`], but ignores the differences of messages that `options` ignore, see
+/// [`OscMessage::semantically_eq`]. Time tags and the structure of bundles must still be equal.
+pub fn diff_semantic(
+    left: &OscPacket,
+    right: &OscPacket,
+    options: &SemanticEqOptions,
+) -> Option<String> {
+    diff_packets(left, right, options)
+}
+
+/// Decodes the raw packet `bytes`, e.g. a fixture captured from another implementation, and
+/// encodes it again. Returns the packet along with its encoding, which equals `bytes` if they are
+/// encoded canonically.
+///
+/// # Panics
+///
+/// Panics with a hex dump of `bytes` if they don't decode to a packet that can be encoded.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::test_util::load_fixture;
+/// use rosc::{OscMessage, OscPacket};
+///
+/// let bytes = b"/ping\0\0\0,\0\0\0";
+/// let (packet, encoded) = load_fixture(bytes);
+/// assert_eq!(packet, OscPacket::Message(OscMessage::from("/ping")));
+/// assert_eq!(encoded, bytes);
+/// ```
+pub fn load_fixture(bytes: &[u8]) -> (OscPacket, Vec<u8>) {
+    load_fixture_with_options(bytes, &DecoderOptions::default())
+}
+
+/// Like [`load_fixture`], but decodes `bytes` with `options`, e.g. for fixtures of senders that
+/// need [`DecoderOptions::lenient_type_tags`].
+pub fn load_fixture_with_options(
+    bytes: &[u8],
+    options: &DecoderOptions,
+) -> (OscPacket, Vec<u8>) {
+    let packet = decoder::decode_with_options(bytes, options).unwrap_or_else(|err| {
+        panic!("fixture failed to decode: {}\n{}", err, hex_dump(bytes))
+    });
+    let encoded = encoder::encode(&packet).unwrap_or_else(|err| {
+        panic!("fixture failed to encode: {}\n{}", err, hex_dump(bytes))
+    });
+    (packet, encoded)
+}
+
+fn diff_packets(
+    left: &OscPacket,
+    right: &OscPacket,
+    options: &SemanticEqOptions,
+) -> Option<String> {
+    match (left, right) {
+        (OscPacket::Message(left), OscPacket::Message(right)) => {
+            diff_messages(left, right, options)
+        }
+        (OscPacket::Bundle(left), OscPacket::Bundle(right)) => diff_bundles(left, right, options),
+        (OscPacket::Message(_), OscPacket::Bundle(_)) => {
+            Some("left is a message, right is a bundle".into())
+        }
+        (OscPacket::Bundle(_), OscPacket::Message(_)) => {
+            Some("left is a bundle, right is a message".into())
+        }
+    }
+}
+
+fn diff_bundles(
+    left: &OscBundle,
+    right: &OscBundle,
+    options: &SemanticEqOptions,
+) -> Option<String> {
+    if left.timetag != right.timetag {
+        return Some(format!(
+            "time tag differs: {} != {}",
+            time(left.timetag),
+            time(right.timetag)
+        ));
+    }
+    if left.content.len() != right.content.len() {
+        return Some(format!(
+            "bundle element count differs: {} != {}",
+            left.content.len(),
+            right.content.len()
+        ));
+    }
+    left.content
+        .iter()
+        .zip(&right.content)
+        .enumerate()
+        .find_map(|(index, (left, right))| {
+            diff_packets(left, right, options).map(|diff| format!("element {}: {}", index, diff))
+        })
+}
+
+fn diff_messages(
+    left: &OscMessage,
+    right: &OscMessage,
+    options: &SemanticEqOptions,
+) -> Option<String> {
+    let addr_eq = match options.case_insensitive_address {
+        true => left.addr.eq_ignore_ascii_case(&right.addr),
+        false => left.addr == right.addr,
+    };
+    if !addr_eq {
+        return Some(format!("address differs: {:?} != {:?}", left.addr, right.addr));
+    }
+    diff_args(
+        compared_args(&left.args, options),
+        compared_args(&right.args, options),
+        options,
+    )
+}
+
+/// Returns the arguments that are compared, i.e. without trailing nils if they are ignored.
+fn compared_args<'a>(args: &'a [OscType], options: &SemanticEqOptions) -> &'a [OscType] {
+    if !options.ignore_trailing_nil {
+        return args;
+    }
+    let len = args.iter().rposition(|arg| *arg != OscType::Nil).map_or(0, |i| i + 1);
+    &args[..len]
+}
+
+fn diff_args(left: &[OscType], right: &[OscType], options: &SemanticEqOptions) -> Option<String> {
+    if left.len() != right.len() {
+        return Some(format!(
+            "argument count differs: {} != {}",
+            left.len(),
+            right.len()
+        ));
+    }
+    left.iter()
+        .zip(right)
+        .enumerate()
+        .find_map(|(index, (left, right))| {
+            diff_arg(left, right, options).map(|diff| format!("argument {}: {}", index, diff))
+        })
+}
+
+fn diff_arg(left: &OscType, right: &OscType, options: &SemanticEqOptions) -> Option<String> {
+    let differs = match (left, right) {
+        (left, right) if options.numeric_widening && is_number(left) && is_number(right) => {
+            !left.numerically_eq(right, options.epsilon)
+        }
+        (OscType::Float(l), OscType::Float(r)) => {
+            !floats_equal(f64::from(*l), f64::from(*r), options.epsilon)
+        }
+        (OscType::Double(l), OscType::Double(r)) => !floats_equal(*l, *r, options.epsilon),
+        (OscType::Blob(l), OscType::Blob(r)) => return diff_blobs(l, r),
+        (OscType::Array(l), OscType::Array(r)) => {
+            return diff_args(&l.content, &r.content, options)
+                .map(|diff| format!("array {}", diff));
+        }
+        // Booleans are encoded as two different type tags, but are the same type
+        (OscType::Bool(_), OscType::Bool(_)) => left != right,
+        (left, right) if type_tag(left) != type_tag(right) => {
+            return Some(format!(
+                "type differs: '{}' != '{}' ({:?} != {:?})",
+                type_tag(left),
+                type_tag(right),
+                left,
+                right
+            ))
+        }
+        (left, right) => left != right,
+    };
+    match differs {
+        true => Some(format!("value differs: {:?} != {:?}", left, right)),
+        false => None,
+    }
+}
+
+fn diff_blobs(left: &[u8], right: &[u8]) -> Option<String> {
+    match left.iter().zip(right).position(|(l, r)| l != r) {
+        Some(i) => Some(format!(
+            "blob differs at byte {}: {:#04x} != {:#04x}",
+            i, left[i], right[i]
+        )),
+        None if left.len() != right.len() => Some(format!(
+            "blob length differs: {} != {}",
+            left.len(),
+            right.len()
+        )),
+        None => None,
+    }
+}
+
+fn floats_equal(left: f64, right: f64, epsilon: f64) -> bool {
+    left == right || (left - right).abs() <= epsilon
+}
+
+fn is_number(arg: &OscType) -> bool {
+    matches!(
+        arg,
+        OscType::Int(_)
+            | OscType::Long(_)
+            | OscType::Unsigned(_)
+            | OscType::Float(_)
+            | OscType::Double(_)
+    )
+}
+
+/// Returns the type tag of `arg`, with `[` for arrays.
+fn type_tag(arg: &OscType) -> char {
+    match arg {
+        OscType::Int(_) => 'i',
+        OscType::Long(_) => 'h',
+        OscType::Float(_) => 'f',
+        OscType::Double(_) => 'd',
+        OscType::Char(_) => 'c',
+        OscType::String(_) => 's',
+        OscType::Blob(_) => 'b',
+        OscType::Time(_) => 't',
+        OscType::Midi(_) => 'm',
+        OscType::Color(_) => 'r',
+        OscType::Bool(true) => 'T',
+        OscType::Bool(false) => 'F',
+        OscType::Nil => 'N',
+        OscType::Impulse => 'I',
+        OscType::Unsigned(_) => 'u',
+        OscType::Array(_) => '[',
+    }
+}
+
+fn time(time: OscTime) -> String {
+    format!("({}, {})", time.seconds, time.fractional)
+}
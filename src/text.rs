@@ -0,0 +1,251 @@
+use crate::decoder::{DecodeError, MAX_ARRAY_DEPTH};
+use crate::types::{OscArray, OscColor, OscMessage, OscMidiMessage, OscTime, OscType};
+
+use crate::alloc::{string::{String, ToString}, vec::Vec};
+
+#[cfg(feature = "std")]
+use std::{convert::TryFrom, time::SystemTime};
+
+/// A whitespace separated token of a text message.
+struct Token {
+    text: String,
+    /// Column of the first character of the token, starting at 1.
+    column: usize,
+    quoted: bool,
+}
+
+fn text_error<S: Into<String>>(column: usize, msg: S) -> DecodeError {
+    DecodeError::BadText {
+        column,
+        msg: msg.into(),
+    }
+}
+
+/// Splits `line` at whitespace outside of double quotes and resolves the escapes within quotes.
+fn tokenize(line: &str) -> Result<Vec<Token>, DecodeError> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().enumerate().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut token = Token {
+            text: String::new(),
+            column: start + 1,
+            quoted: c == '"',
+        };
+        if token.quoted {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((column, '\\')) => match chars.next() {
+                        Some((_, '"')) => token.text.push('"'),
+                        Some((_, '\\')) => token.text.push('\\'),
+                        Some((_, 'n')) => token.text.push('\n'),
+                        Some((_, 'r')) => token.text.push('\r'),
+                        Some((_, 't')) => token.text.push('\t'),
+                        _ => return Err(text_error(column + 1, "Invalid escape sequence")),
+                    },
+                    Some((_, c)) => token.text.push(c),
+                    None => return Err(text_error(token.column, "Unterminated string")),
+                }
+            }
+            if let Some(&(column, c)) = chars.peek() {
+                if !c.is_whitespace() {
+                    return Err(text_error(column + 1, "Expected whitespace after string"));
+                }
+            }
+        } else {
+            while let Some(&(_, c)) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                token.text.push(c);
+                chars.next();
+            }
+        }
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Parses hex bytes of the form `0x` followed by pairs of hex digits.
+fn parse_hex(text: &str) -> Result<Vec<u8>, &'static str> {
+    let digits = text.strip_prefix("0x").ok_or("Expected hex bytes starting with 0x")?;
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Invalid hex digit");
+    }
+    if digits.len() % 2 != 0 {
+        return Err("Odd number of hex digits");
+    }
+    Ok((0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap_or_default())
+        .collect())
+}
+
+fn parse_hex_word(text: &str) -> Result<[u8; 4], &'static str> {
+    let bytes = parse_hex(text)?;
+    if bytes.len() != 4 {
+        return Err("Expected exactly 4 hex bytes");
+    }
+    Ok([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn parse_time(text: &str) -> Result<OscTime, &'static str> {
+    let time = text.strip_prefix('@').ok_or("Expected a time tag starting with @")?;
+    if time == "now" {
+        return now();
+    }
+    let (seconds, fractional) = match time.find('.') {
+        Some(pos) => (&time[..pos], &time[pos + 1..]),
+        None => (time, "0"),
+    };
+    match (seconds.parse(), fractional.parse()) {
+        (Ok(seconds), Ok(fractional)) => Ok(OscTime {
+            seconds,
+            fractional,
+        }),
+        _ => Err("Invalid time tag"),
+    }
+}
+
+#[cfg(feature = "std")]
+fn now() -> Result<OscTime, &'static str> {
+    OscTime::try_from(SystemTime::now()).map_err(|_| "Invalid system time")
+}
+
+#[cfg(not(feature = "std"))]
+fn now() -> Result<OscTime, &'static str> {
+    Err("@now requires the std feature")
+}
+
+fn parse_arg(tag: char, token: &Token) -> Result<OscType, DecodeError> {
+    parse_value(tag, &token.text).map_err(|msg| text_error(token.column, msg))
+}
+
+/// Parses the value of an argument with the type tag `tag`, which must be one of the tags that
+/// take a value, written as described for `decoder::parse_text`. Returns the reason if `text`
+/// is malformed.
+pub fn parse_value(tag: char, text: &str) -> Result<OscType, String> {
+    let invalid = |kind: &str| format!("Invalid {} argument", kind);
+    Ok(match tag {
+        'i' => OscType::Int(text.parse().map_err(|_| invalid("int"))?),
+        'h' => OscType::Long(text.parse().map_err(|_| invalid("long"))?),
+        'u' => OscType::Unsigned(text.parse().map_err(|_| invalid("unsigned"))?),
+        'f' => OscType::Float(text.parse().map_err(|_| invalid("float"))?),
+        'd' => OscType::Double(text.parse().map_err(|_| invalid("double"))?),
+        's' => {
+            if text.contains('\0') {
+                return Err("Strings must not contain null characters".to_string());
+            }
+            OscType::String(text.to_string())
+        }
+        'c' => {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => OscType::Char(c),
+                _ => return Err(invalid("char")),
+            }
+        }
+        'b' => OscType::Blob(parse_hex(text)?),
+        't' => OscType::Time(parse_time(text)?),
+        'm' => {
+            let [port, status, data1, data2] = parse_hex_word(text)?;
+            OscType::Midi(OscMidiMessage {
+                port,
+                status,
+                data1,
+                data2,
+            })
+        }
+        'r' => {
+            let [red, green, blue, alpha] = parse_hex_word(text)?;
+            OscType::Color(OscColor {
+                red,
+                green,
+                blue,
+                alpha,
+            })
+        }
+        _ => unreachable!("type tag without argument"),
+    })
+}
+
+pub fn parse_text(line: &str) -> Result<OscMessage, DecodeError> {
+    let tokens = tokenize(line)?;
+    let mut tokens = tokens.iter();
+    let end_column = line.chars().count() + 1;
+
+    let addr = match tokens.next() {
+        Some(token) if token.text.starts_with('/') => token.text.clone(),
+        Some(token) => return Err(text_error(token.column, "Address must start with /")),
+        None => return Err(text_error(end_column, "Missing address")),
+    };
+
+    let type_tags = match tokens.next() {
+        Some(token) if !token.quoted => token,
+        Some(token) => return Err(text_error(token.column, "Expected type tags")),
+        None => return Ok(OscMessage { addr, args: vec![] }),
+    };
+    let tags = type_tags.text.strip_prefix(',').unwrap_or(&type_tags.text);
+    let tags_column = type_tags.column + type_tags.text.len() - tags.len();
+
+    // Contents of the arrays enclosing the current argument
+    let mut arrays: Vec<Vec<OscType>> = Vec::new();
+    let mut args = Vec::new();
+    for (i, tag) in tags.chars().enumerate() {
+        let arg = match tag {
+            '[' if arrays.len() == MAX_ARRAY_DEPTH => {
+                return Err(text_error(
+                    tags_column + i,
+                    "Arrays are nested deeper than MAX_ARRAY_DEPTH",
+                ))
+            }
+            '[' => {
+                arrays.push(Vec::new());
+                continue;
+            }
+            ']' => match arrays.pop() {
+                Some(content) => OscType::Array(OscArray { content }),
+                None => {
+                    return Err(text_error(tags_column + i, "Encountered ] outside array"))
+                }
+            },
+            'T' => OscType::Bool(true),
+            'F' => OscType::Bool(false),
+            'N' => OscType::Nil,
+            'I' => OscType::Impulse,
+            'i' | 'h' | 'u' | 'f' | 'd' | 's' | 'c' | 'b' | 't' | 'm' | 'r' => match tokens.next() {
+                Some(token) => parse_arg(tag, token)?,
+                None => {
+                    return Err(text_error(
+                        end_column,
+                        format!("Missing argument for type tag '{}'", tag),
+                    ))
+                }
+            },
+            _ => {
+                return Err(text_error(
+                    tags_column + i,
+                    format!("Unknown type tag '{}'", tag),
+                ))
+            }
+        };
+        match arrays.last_mut() {
+            Some(array) => array.push(arg),
+            None => args.push(arg),
+        }
+    }
+    if !arrays.is_empty() {
+        return Err(text_error(type_tags.column, "Encountered [ without matching ]"));
+    }
+    if let Some(token) = tokens.next() {
+        return Err(text_error(token.column, "Argument without type tag"));
+    }
+
+    Ok(OscMessage { addr, args })
+}
@@ -0,0 +1,565 @@
+//! Messages of the [TUIO 1.1](https://www.tuio.org/?specification) protocol for tangible
+//! multitouch surfaces, which is layered on OSC and sent by trackers such as
+//! [reacTIVision](https://reactivision.sourceforge.net/).
+//!
+//! TUIO sends the state of a surface in frames. A frame is a bundle with the messages of one
+//! profile, e.g. `/tuio/2Dcur` for fingers: an optional `source` message naming the tracker, an
+//! `alive` message with the session IDs of all cursors on the surface, a `set` message for each
+//! cursor that moved and an `fseq` message with the frame ID.
+//! [`TuioAssembler`](crate::tuio::TuioAssembler) collects these into a
+//! [`TuioFrame`](crate::tuio::TuioFrame).
+//!
+//! The 2D cursor and object profiles are supported, other profiles are rejected with an error.
+
+use crate::errors::OscError;
+use crate::types::{OscBundle, OscMessage, OscPacket, OscTime, OscType};
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+/// The TUIO profiles supported by this module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TuioProfile {
+    /// `/tuio/2Dcur`, fingers or other pointers on the surface.
+    Cursor2D,
+    /// `/tuio/2Dobj`, tagged objects on the surface, such as reacTIVision's fiducials.
+    Object2D,
+}
+
+impl TuioProfile {
+    /// Returns the address of the messages of the profile.
+    pub fn addr(self) -> &'static str {
+        match self {
+            TuioProfile::Cursor2D => "/tuio/2Dcur",
+            TuioProfile::Object2D => "/tuio/2Dobj",
+        }
+    }
+
+    /// Returns the profile whose messages are sent to `addr`. Fails if `addr` is not a TUIO
+    /// address or belongs to an unsupported profile.
+    pub fn from_addr(addr: &str) -> Result<TuioProfile, OscError> {
+        match addr {
+            "/tuio/2Dcur" => Ok(TuioProfile::Cursor2D),
+            "/tuio/2Dobj" => Ok(TuioProfile::Object2D),
+            _ if addr.starts_with("/tuio/") => {
+                Err(OscError::BadAddress(format!("Unsupported TUIO profile {}", addr)))
+            }
+            _ => Err(OscError::BadAddress(format!("{} is not a TUIO address", addr))),
+        }
+    }
+}
+
+/// The state of a cursor, as sent by a `/tuio/2Dcur set` message. Positions are normalized to
+/// the range from 0 to 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tuio2DCursor {
+    pub session_id: i32,
+    pub x: f32,
+    pub y: f32,
+    /// Velocity along the x axis.
+    pub vx: f32,
+    /// Velocity along the y axis.
+    pub vy: f32,
+    /// Motion acceleration.
+    pub accel: f32,
+}
+
+/// The state of an object, as sent by a `/tuio/2Dobj set` message. Positions are normalized to
+/// the range from 0 to 1, angles are in radians.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tuio2DObject {
+    pub session_id: i32,
+    /// The ID of the marker of the object, e.g. the fiducial ID in reacTIVision.
+    pub class_id: i32,
+    pub x: f32,
+    pub y: f32,
+    pub angle: f32,
+    /// Velocity along the x axis.
+    pub vx: f32,
+    /// Velocity along the y axis.
+    pub vy: f32,
+    /// Rotation velocity.
+    pub vangle: f32,
+    /// Motion acceleration.
+    pub accel: f32,
+    /// Rotation acceleration.
+    pub rotation_accel: f32,
+}
+
+/// A TUIO message of one of the supported profiles.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::tuio::{Tuio2DCursor, TuioMessage, ToOscMessage};
+/// use rosc::{OscMessage, OscType};
+/// use std::convert::TryFrom;
+///
+/// let msg = OscMessage {
+///     addr: "/tuio/2Dcur".to_string(),
+///     args: vec![
+///         "set".into(),
+///         OscType::Int(7),
+///         OscType::Float(0.25),
+///         OscType::Float(0.5),
+///         OscType::Float(0.0),
+///         OscType::Float(0.0),
+///         OscType::Float(0.0),
+///     ],
+/// };
+/// let cursor = Tuio2DCursor::try_from(&msg).unwrap();
+/// assert_eq!((cursor.session_id, cursor.x, cursor.y), (7, 0.25, 0.5));
+/// assert_eq!(TuioMessage::try_from(&msg).unwrap(), TuioMessage::Cursor(cursor));
+/// assert_eq!(cursor.to_osc_message(), msg);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum TuioMessage {
+    /// `source`, which names the tracker sending the frame, e.g. `name@address`.
+    Source { profile: TuioProfile, source: String },
+    /// `alive`, which lists the session IDs of everything currently on the surface.
+    Alive { profile: TuioProfile, session_ids: Vec<i32> },
+    /// `set` of the 2D cursor profile.
+    Cursor(Tuio2DCursor),
+    /// `set` of the 2D object profile.
+    Object(Tuio2DObject),
+    /// `fseq`, which ends a frame. Trackers may resend a frame unchanged with an ID of -1.
+    Fseq { profile: TuioProfile, frame_id: i32 },
+}
+
+impl TuioMessage {
+    /// Returns the profile the message belongs to.
+    pub fn profile(&self) -> TuioProfile {
+        match self {
+            TuioMessage::Source { profile, .. }
+            | TuioMessage::Alive { profile, .. }
+            | TuioMessage::Fseq { profile, .. } => *profile,
+            TuioMessage::Cursor(_) => TuioProfile::Cursor2D,
+            TuioMessage::Object(_) => TuioProfile::Object2D,
+        }
+    }
+}
+
+/// Conversion of TUIO types to the OSC message sending them.
+pub trait ToOscMessage {
+    fn to_osc_message(&self) -> OscMessage;
+}
+
+impl ToOscMessage for Tuio2DCursor {
+    fn to_osc_message(&self) -> OscMessage {
+        OscMessage {
+            addr: TuioProfile::Cursor2D.addr().to_string(),
+            args: vec![
+                "set".into(),
+                OscType::Int(self.session_id),
+                OscType::Float(self.x),
+                OscType::Float(self.y),
+                OscType::Float(self.vx),
+                OscType::Float(self.vy),
+                OscType::Float(self.accel),
+            ],
+        }
+    }
+}
+
+impl ToOscMessage for Tuio2DObject {
+    fn to_osc_message(&self) -> OscMessage {
+        OscMessage {
+            addr: TuioProfile::Object2D.addr().to_string(),
+            args: vec![
+                "set".into(),
+                OscType::Int(self.session_id),
+                OscType::Int(self.class_id),
+                OscType::Float(self.x),
+                OscType::Float(self.y),
+                OscType::Float(self.angle),
+                OscType::Float(self.vx),
+                OscType::Float(self.vy),
+                OscType::Float(self.vangle),
+                OscType::Float(self.accel),
+                OscType::Float(self.rotation_accel),
+            ],
+        }
+    }
+}
+
+impl ToOscMessage for TuioMessage {
+    fn to_osc_message(&self) -> OscMessage {
+        let (profile, args) = match self {
+            TuioMessage::Source { profile, source } => {
+                (*profile, vec!["source".into(), source.as_str().into()])
+            }
+            TuioMessage::Alive {
+                profile,
+                session_ids,
+            } => {
+                let mut args = vec!["alive".into()];
+                args.extend(session_ids.iter().map(|&id| OscType::Int(id)));
+                (*profile, args)
+            }
+            TuioMessage::Cursor(cursor) => return cursor.to_osc_message(),
+            TuioMessage::Object(object) => return object.to_osc_message(),
+            TuioMessage::Fseq { profile, frame_id } => {
+                (*profile, vec!["fseq".into(), OscType::Int(*frame_id)])
+            }
+        };
+        OscMessage {
+            addr: profile.addr().to_string(),
+            args,
+        }
+    }
+}
+
+impl TryFrom<&OscMessage> for TuioMessage {
+    type Error = OscError;
+
+    /// Parses a TUIO message. Fails if the message doesn't belong to a supported profile, has an
+    /// unknown command or its arguments don't match the command.
+    fn try_from(msg: &OscMessage) -> Result<Self, OscError> {
+        let profile = TuioProfile::from_addr(&msg.addr)?;
+        let mut args = Args::new(msg)?;
+        let tuio = match (args.command, profile) {
+            ("source", _) => TuioMessage::Source {
+                profile,
+                source: args.string()?,
+            },
+            ("alive", _) => {
+                let mut session_ids = Vec::with_capacity(msg.args.len() - 1);
+                while !args.is_empty() {
+                    session_ids.push(args.int()?);
+                }
+                TuioMessage::Alive {
+                    profile,
+                    session_ids,
+                }
+            }
+            ("set", TuioProfile::Cursor2D) => TuioMessage::Cursor(Tuio2DCursor {
+                session_id: args.int()?,
+                x: args.float()?,
+                y: args.float()?,
+                vx: args.float()?,
+                vy: args.float()?,
+                accel: args.float()?,
+            }),
+            ("set", TuioProfile::Object2D) => TuioMessage::Object(Tuio2DObject {
+                session_id: args.int()?,
+                class_id: args.int()?,
+                x: args.float()?,
+                y: args.float()?,
+                angle: args.float()?,
+                vx: args.float()?,
+                vy: args.float()?,
+                vangle: args.float()?,
+                accel: args.float()?,
+                rotation_accel: args.float()?,
+            }),
+            ("fseq", _) => TuioMessage::Fseq {
+                profile,
+                frame_id: args.int()?,
+            },
+            (command, _) => {
+                return Err(OscError::BadArg(format!(
+                    "Unknown TUIO command {} {}",
+                    msg.addr, command
+                )))
+            }
+        };
+        args.finish()?;
+        Ok(tuio)
+    }
+}
+
+impl TryFrom<&OscMessage> for Tuio2DCursor {
+    type Error = OscError;
+
+    /// Parses a `/tuio/2Dcur set` message.
+    fn try_from(msg: &OscMessage) -> Result<Self, OscError> {
+        match TuioMessage::try_from(msg)? {
+            TuioMessage::Cursor(cursor) => Ok(cursor),
+            _ => Err(OscError::BadArg(format!(
+                "Expected a /tuio/2Dcur set message, found {} {:?}",
+                msg.addr,
+                msg.args.first()
+            ))),
+        }
+    }
+}
+
+impl TryFrom<&OscMessage> for Tuio2DObject {
+    type Error = OscError;
+
+    /// Parses a `/tuio/2Dobj set` message.
+    fn try_from(msg: &OscMessage) -> Result<Self, OscError> {
+        match TuioMessage::try_from(msg)? {
+            TuioMessage::Object(object) => Ok(object),
+            _ => Err(OscError::BadArg(format!(
+                "Expected a /tuio/2Dobj set message, found {} {:?}",
+                msg.addr,
+                msg.args.first()
+            ))),
+        }
+    }
+}
+
+/// The arguments of a TUIO message following the command, which are taken in order.
+struct Args<'a> {
+    msg: &'a OscMessage,
+    command: &'a str,
+    // Index of the next argument
+    index: usize,
+}
+
+impl<'a> Args<'a> {
+    fn new(msg: &'a OscMessage) -> Result<Self, OscError> {
+        match msg.args.first() {
+            Some(OscType::String(command)) => Ok(Args {
+                msg,
+                command,
+                index: 1,
+            }),
+            Some(arg) => Err(OscError::BadArg(format!(
+                "Malformed TUIO message {}: expected a command, found {:?}",
+                msg.addr, arg
+            ))),
+            None => Err(OscError::BadArg(format!(
+                "Malformed TUIO message {}: missing command",
+                msg.addr
+            ))),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.index == self.msg.args.len()
+    }
+
+    fn next(&mut self, expected: &str) -> Result<&'a OscType, OscError> {
+        let arg = self.msg.args.get(self.index).ok_or_else(|| {
+            self.error(format_args!("missing {} argument {}", expected, self.index))
+        })?;
+        self.index += 1;
+        Ok(arg)
+    }
+
+    fn int(&mut self) -> Result<i32, OscError> {
+        match self.next("int")? {
+            OscType::Int(x) => Ok(*x),
+            arg => Err(self.unexpected("int", arg)),
+        }
+    }
+
+    fn float(&mut self) -> Result<f32, OscError> {
+        match self.next("float")? {
+            OscType::Float(x) => Ok(*x),
+            arg => Err(self.unexpected("float", arg)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, OscError> {
+        match self.next("string")? {
+            OscType::String(s) => Ok(s.clone()),
+            arg => Err(self.unexpected("string", arg)),
+        }
+    }
+
+    /// Fails if there are arguments left.
+    fn finish(&self) -> Result<(), OscError> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err(self.error(format_args!(
+                "expected {} arguments, found {}",
+                self.index,
+                self.msg.args.len()
+            ))),
+        }
+    }
+
+    /// Describes the argument just taken not being of the `expected` type.
+    fn unexpected(&self, expected: &str, arg: &OscType) -> OscError {
+        self.error(format_args!(
+            "expected {} argument {}, found {:?}",
+            expected,
+            self.index - 1,
+            arg
+        ))
+    }
+
+    fn error(&self, problem: fmt::Arguments) -> OscError {
+        OscError::BadArg(format!(
+            "Malformed TUIO message {} {}: {}",
+            self.msg.addr, self.command, problem
+        ))
+    }
+}
+
+/// The messages of one profile between two `fseq` messages, as assembled by [`TuioAssembler`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TuioFrame {
+    pub profile: TuioProfile,
+    /// The tracker sending the frame, if it sent a `source` message.
+    pub source: Option<String>,
+    /// The ID of the frame, which is -1 if the tracker resent a frame unchanged.
+    pub frame_id: i32,
+    /// The session IDs of everything on the surface.
+    pub alive: Vec<i32>,
+    /// Session IDs that are alive but weren't in the previous frame of the profile.
+    pub added: Vec<i32>,
+    /// Session IDs that were alive in the previous frame of the profile but no longer are.
+    pub removed: Vec<i32>,
+    /// The cursors that changed, for the 2D cursor profile.
+    pub cursors: Vec<Tuio2DCursor>,
+    /// The objects that changed, for the 2D object profile.
+    pub objects: Vec<Tuio2DObject>,
+}
+
+impl TuioFrame {
+    /// Returns the frame as a bundle to be sent immediately, with the messages in the order of
+    /// the specification.
+    pub fn to_packet(&self) -> OscPacket {
+        let mut content = Vec::with_capacity(self.cursors.len() + self.objects.len() + 3);
+        let mut push = |msg: TuioMessage| content.push(OscPacket::Message(msg.to_osc_message()));
+        if let Some(source) = &self.source {
+            push(TuioMessage::Source {
+                profile: self.profile,
+                source: source.clone(),
+            });
+        }
+        push(TuioMessage::Alive {
+            profile: self.profile,
+            session_ids: self.alive.clone(),
+        });
+        self.cursors.iter().for_each(|&cursor| push(TuioMessage::Cursor(cursor)));
+        self.objects.iter().for_each(|&object| push(TuioMessage::Object(object)));
+        push(TuioMessage::Fseq {
+            profile: self.profile,
+            frame_id: self.frame_id,
+        });
+        OscPacket::Bundle(OscBundle {
+            timetag: OscTime::IMMEDIATE,
+            content,
+        })
+    }
+}
+
+/// Groups TUIO messages into frames, one profile at a time.
+///
+/// Messages are passed to [`push`](TuioAssembler::push) in the order they were received, and
+/// each `fseq` message completes the frame of its profile. Frames whose `set` messages refer to
+/// sessions which are not alive, or which lack an `alive` message, are reported as errors
+/// instead of being returned and the assembler continues with the next frame.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::tuio::{Tuio2DCursor, TuioAssembler, TuioMessage, ToOscMessage};
+/// use rosc::tuio::TuioProfile::Cursor2D;
+///
+/// let cursor = Tuio2DCursor {
+///     session_id: 7,
+///     x: 0.25,
+///     y: 0.5,
+///     ..Default::default()
+/// };
+/// let messages = vec![
+///     TuioMessage::Alive { profile: Cursor2D, session_ids: vec![7] },
+///     TuioMessage::Cursor(cursor),
+///     TuioMessage::Fseq { profile: Cursor2D, frame_id: 1 },
+/// ];
+///
+/// let mut assembler = TuioAssembler::new();
+/// let mut frames = vec![];
+/// for msg in messages {
+///     if let Some(frame) = assembler.push(&msg.to_osc_message()).unwrap() {
+///         frames.push(frame);
+///     }
+/// }
+/// assert_eq!(frames.len(), 1);
+/// assert_eq!(frames[0].frame_id, 1);
+/// assert_eq!(frames[0].added, vec![7]);
+/// assert_eq!(frames[0].cursors, vec![cursor]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TuioAssembler {
+    // The frames currently being assembled
+    partial: BTreeMap<TuioProfile, PartialFrame>,
+    // The session IDs alive in the last frame of each profile
+    alive: BTreeMap<TuioProfile, Vec<i32>>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct PartialFrame {
+    source: Option<String>,
+    alive: Option<Vec<i32>>,
+    cursors: Vec<Tuio2DCursor>,
+    objects: Vec<Tuio2DObject>,
+}
+
+impl TuioAssembler {
+    /// Creates an assembler without any frames in progress.
+    pub fn new() -> Self {
+        TuioAssembler::default()
+    }
+
+    /// Adds `msg` to the frame of its profile and returns the frame if `msg` completes it. Fails
+    /// if `msg` is not a valid TUIO message, which is not added, or if it completes an invalid
+    /// frame, which is discarded.
+    pub fn push(&mut self, msg: &OscMessage) -> Result<Option<TuioFrame>, OscError> {
+        let tuio = TuioMessage::try_from(msg)?;
+        let profile = tuio.profile();
+        let partial = self.partial.entry(profile).or_default();
+        match tuio {
+            TuioMessage::Source { source, .. } => partial.source = Some(source),
+            TuioMessage::Alive { session_ids, .. } => partial.alive = Some(session_ids),
+            TuioMessage::Cursor(cursor) => partial.cursors.push(cursor),
+            TuioMessage::Object(object) => partial.objects.push(object),
+            TuioMessage::Fseq { frame_id, .. } => {
+                let partial = self.partial.remove(&profile).unwrap_or_default();
+                return self.complete(profile, frame_id, partial).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn complete(
+        &mut self,
+        profile: TuioProfile,
+        frame_id: i32,
+        partial: PartialFrame,
+    ) -> Result<TuioFrame, OscError> {
+        let alive = partial.alive.ok_or_else(|| {
+            OscError::BadArg(format!(
+                "TUIO frame {} of {} has no alive message",
+                frame_id,
+                profile.addr()
+            ))
+        })?;
+        let set = partial
+            .cursors
+            .iter()
+            .map(|cursor| cursor.session_id)
+            .chain(partial.objects.iter().map(|object| object.session_id));
+        for session_id in set {
+            if !alive.contains(&session_id) {
+                return Err(OscError::BadArg(format!(
+                    "TUIO frame {} of {} sets session {}, which is not alive",
+                    frame_id,
+                    profile.addr(),
+                    session_id
+                )));
+            }
+        }
+
+        let previous = self.alive.insert(profile, alive.clone()).unwrap_or_default();
+        Ok(TuioFrame {
+            profile,
+            source: partial.source,
+            frame_id,
+            added: alive.iter().filter(|id| !previous.contains(id)).cloned().collect(),
+            removed: previous.iter().filter(|id| !alive.contains(id)).cloned().collect(),
+            alive,
+            cursors: partial.cursors,
+            objects: partial.objects,
+        })
+    }
+}
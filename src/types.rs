@@ -1,12 +1,14 @@
 use crate::errors;
 #[cfg(feature = "std")]
 use core::fmt::{self, Display};
-use core::{iter::FromIterator, result};
+use core::{cmp, convert::TryFrom, iter::FromIterator, mem, result, time::Duration};
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicU64, Ordering};
 
 #[cfg(feature = "std")]
-use std::{convert::{TryFrom, TryInto}, time::{Duration, SystemTime, UNIX_EPOCH}};
+use std::{convert::TryInto, time::{SystemTime, UNIX_EPOCH}};
 
-use crate::alloc::{string::{ String, ToString }, vec::Vec};
+use crate::alloc::{borrow::Cow, string::{ String, ToString }, vec::{self, Vec}};
 
 /// A time tag in OSC message consists of two 32-bit integers where the first one denotes the number of seconds since 1900-01-01 and the second the fractions of a second.
 /// For details on its semantics see http://opensoundcontrol.org/node/3/#timetags
@@ -26,6 +28,18 @@ use crate::alloc::{string::{ String, ToString }, vec::Vec};
 /// }
 /// ```
 ///
+/// # Immediate time tags
+///
+/// The OSC specification reserves the time tag with 63 zero bits followed by a one, i.e. `(0, 1)`,
+/// to mean "immediately". It is available as [`OscTime::IMMEDIATE`] and must not be taken for a
+/// point in time at the OSC epoch, as a naive conversion into a date would. Use
+/// [`OscTime::is_immediate`] or [`OscBundle::is_immediate`] to check for it.
+///
+/// A time tag of `(0, 0)` is not reserved by the specification and denotes the OSC epoch, which is
+/// long past and thus due for anyone scheduling by it. Some senders use it to mean "immediately"
+/// anyway, [`DecoderOptions::zero_timetag_is_immediate`](crate::decoder::DecoderOptions) replaces
+/// it with [`OscTime::IMMEDIATE`] while decoding.
+///
 /// # Conversions between `(u32, u32)`
 ///
 /// Prior to version `0.5.0` of this crate, `OscTime` was defined as a type alias to `(u32, u32)`.
@@ -55,6 +69,272 @@ pub struct OscTime {
     pub fractional: u32,
 }
 
+impl OscTime {
+    /// The time tag meaning "immediately".
+    pub const IMMEDIATE: OscTime = OscTime::new(0, 1);
+
+    /// Creates a time tag from the seconds since the OSC epoch and the fractions of a second,
+    /// e.g. for constants. The same as [`OscTime::from_parts`].
+    ///
+    /// ```
+    /// use rosc::OscTime;
+    ///
+    /// const START: OscTime = OscTime::new(3_900_000_000, 1 << 31);
+    /// assert_eq!(START, OscTime::from((3_900_000_000, 1 << 31)));
+    /// ```
+    pub const fn new(seconds: u32, fractional: u32) -> Self {
+        OscTime::from_parts(seconds, fractional)
+    }
+
+    /// Creates a time tag from the seconds since the OSC epoch and the fractions of a second,
+    /// the inverse of [`OscTime::parts`].
+    pub const fn from_parts(secs: u32, frac: u32) -> Self {
+        OscTime {
+            seconds: secs,
+            fractional: frac,
+        }
+    }
+
+    /// Returns the seconds since the OSC epoch and the fractions of a second.
+    pub const fn parts(&self) -> (u32, u32) {
+        (self.seconds, self.fractional)
+    }
+
+    /// Creates a time tag from its raw 64-bit value, a 32.32 fixed point number of seconds since
+    /// the OSC epoch, the inverse of [`OscTime::as_raw`].
+    ///
+    /// ```
+    /// use rosc::OscTime;
+    ///
+    /// assert_eq!(OscTime::from_raw(1), OscTime::IMMEDIATE);
+    /// assert_eq!(OscTime::from_raw(0x0000_0002_8000_0000), OscTime::from_parts(2, 1 << 31));
+    /// ```
+    pub const fn from_raw(raw: u64) -> Self {
+        OscTime::from_parts((raw >> 32) as u32, raw as u32)
+    }
+
+    /// Returns the raw 64-bit value of the time tag, with the seconds in the upper and the
+    /// fractions in the lower 32 bits. This is exactly the value encoded on the wire: its
+    /// big-endian bytes are the 8 bytes of the time tag in a bundle.
+    ///
+    /// ```
+    /// use rosc::{encoder, OscBundle, OscPacket, OscTime};
+    ///
+    /// let time = OscTime::from_parts(0xdead_beef, 0x0102_0304);
+    /// assert_eq!(time.as_raw(), 0xdead_beef_0102_0304);
+    /// let packet = OscPacket::Bundle(OscBundle {
+    ///     timetag: time,
+    ///     content: vec![],
+    /// });
+    /// let buf = encoder::encode(&packet).unwrap();
+    /// assert_eq!(buf[8..16], time.as_raw().to_be_bytes());
+    /// ```
+    pub const fn as_raw(&self) -> u64 {
+        ((self.seconds as u64) << 32) | self.fractional as u64
+    }
+
+    /// Returns whether this is the special time tag [`OscTime::IMMEDIATE`]. A time tag of `(0, 0)`
+    /// is not considered immediate.
+    pub fn is_immediate(&self) -> bool {
+        *self == OscTime::IMMEDIATE
+    }
+
+    /// Returns the time from `reference` until this time tag, which is positive if this time tag
+    /// is later than `reference` and negative if it is earlier. The difference is truncated to
+    /// whole nanoseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::OscTime;
+    /// use std::time::Duration;
+    ///
+    /// let tag = OscTime::from((100, 1 << 31));
+    /// let arrival = OscTime::from((101, 0));
+    /// // Arrived half a second late
+    /// let lateness = arrival.delta_from(tag);
+    /// assert!(!lateness.is_negative());
+    /// assert_eq!(lateness.abs(), Duration::from_millis(500));
+    /// assert_eq!(tag.delta_from(arrival).as_nanos(), -500_000_000);
+    /// ```
+    pub fn delta_from(&self, reference: OscTime) -> SignedDuration {
+        let (time, reference) = (self.as_raw(), reference.as_raw());
+        let ticks = time.abs_diff(reference);
+        let duration = Duration::from_nanos(((u128::from(ticks) * 1_000_000_000) >> 32) as u64);
+        SignedDuration::new(time < reference, duration)
+    }
+
+    /// Returns the time from `earlier` until this time tag, which is zero if this time tag is
+    /// not later, see [`OscTime::delta_from`].
+    pub fn saturating_duration_since(&self, earlier: OscTime) -> Duration {
+        let delta = self.delta_from(earlier);
+        match delta.is_negative() {
+            true => Duration::ZERO,
+            false => delta.abs(),
+        }
+    }
+}
+
+/// A duration that may be negative, e.g. the difference between two time tags as returned by
+/// [`OscTime::delta_from`]. A zero duration is never negative.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SignedDuration {
+    negative: bool,
+    duration: Duration,
+}
+
+impl SignedDuration {
+    /// A duration of zero.
+    pub const ZERO: SignedDuration = SignedDuration {
+        negative: false,
+        duration: Duration::ZERO,
+    };
+
+    /// Creates a duration of `duration`, which is negated if `negative` is `true`.
+    pub fn new(negative: bool, duration: Duration) -> Self {
+        SignedDuration {
+            negative: negative && !duration.is_zero(),
+            duration,
+        }
+    }
+
+    /// Returns whether the duration is less than zero.
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Returns the absolute value of the duration.
+    pub fn abs(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the duration in nanoseconds.
+    pub fn as_nanos(&self) -> i128 {
+        match self.negative {
+            true => -(self.duration.as_nanos() as i128),
+            false => self.duration.as_nanos() as i128,
+        }
+    }
+
+    /// Returns the duration in seconds.
+    pub fn as_secs_f64(&self) -> f64 {
+        match self.negative {
+            true => -self.duration.as_secs_f64(),
+            false => self.duration.as_secs_f64(),
+        }
+    }
+}
+
+impl Ord for SignedDuration {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.as_nanos().cmp(&other.as_nanos())
+    }
+}
+
+impl PartialOrd for SignedDuration {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(feature = "std")]
+impl OscTime {
+    /// Returns the current system time. Times that can't be represented, i.e. before 1970 or past
+    /// the year 2036, in which the seconds of the OSC time tag format overflow, result in the
+    /// earliest or latest time tag respectively.
+    pub fn now() -> OscTime {
+        match OscTime::try_from(SystemTime::now()) {
+            Ok(time) => time,
+            Err(OscTimeError(OscTimeErrorKind::BeforeEpoch)) => OscTime::from((0, 0)),
+            Err(OscTimeError(OscTimeErrorKind::Overflow)) => OscTime::from((u32::MAX, u32::MAX)),
+        }
+    }
+}
+
+/// A source of the current time, for the parts of this crate that schedule by time tags.
+///
+/// [`SystemClock`] reads the system time. A different clock can be used where the system time is
+/// not available, e.g. on WebAssembly or embedded targets, or to synchronize with another time
+/// source, such as an audio clock. Closures returning an `OscTime` implement `Clock` as well.
+/// [`ManualClock`] only changes when it is told to, for tests.
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> OscTime;
+}
+
+impl<F: Fn() -> OscTime> Clock for F {
+    fn now(&self) -> OscTime {
+        self()
+    }
+}
+
+/// The [`Clock`] reading the system time, see [`OscTime::now`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> OscTime {
+        OscTime::now()
+    }
+}
+
+/// A [`Clock`] which only changes when it is set or advanced, e.g. to test code that schedules
+/// by time tags without waiting. Clones share the time, so a clone can be kept to control the
+/// clock passed to a [`Scheduler`](crate::scheduler::Scheduler) or another consumer.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::{Clock, ManualClock, OscTime};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new((100, 0).into());
+/// let handle = clock.clone();
+/// handle.advance(Duration::from_millis(1500));
+/// assert_eq!(clock.now(), OscTime::from((101, 1 << 31)));
+/// ```
+#[cfg(target_has_atomic = "64")]
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    // The time as a 32.32 fixed point number of seconds, like the encoded time tag
+    time: crate::alloc::sync::Arc<AtomicU64>,
+}
+
+#[cfg(target_has_atomic = "64")]
+impl ManualClock {
+    /// Creates a clock that is at `time` until it is changed.
+    pub fn new(time: OscTime) -> Self {
+        ManualClock {
+            time: crate::alloc::sync::Arc::new(AtomicU64::new(time.as_raw())),
+        }
+    }
+
+    /// Sets the time, which may also move the clock backwards.
+    pub fn set(&self, time: OscTime) {
+        self.time.store(time.as_raw(), Ordering::SeqCst);
+    }
+
+    /// Moves the clock forward by `duration`, rounded to the resolution of time tags. The clock
+    /// stops at the latest time tag instead of overflowing.
+    pub fn advance(&self, duration: Duration) {
+        let nanos = duration.as_nanos().min(u128::from(u64::MAX));
+        let ticks = ((nanos << 32) + 500_000_000) / 1_000_000_000;
+        let ticks = u64::try_from(ticks).unwrap_or(u64::MAX);
+        let _ = self.time.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |time| {
+            Some(time.saturating_add(ticks))
+        });
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Clock for ManualClock {
+    fn now(&self) -> OscTime {
+        OscTime::from_raw(self.time.load(Ordering::SeqCst))
+    }
+}
+
 #[cfg(feature = "std")]
 impl OscTime {
     const UNIX_OFFSET: u64 = 2_208_988_800; // From RFC 5905
@@ -99,16 +379,13 @@ impl From<OscTime> for SystemTime {
 impl From<(u32, u32)> for OscTime {
     fn from(time: (u32, u32)) -> OscTime {
         let (seconds, fractional) = time;
-        OscTime {
-            seconds,
-            fractional,
-        }
+        OscTime::from_parts(seconds, fractional)
     }
 }
 
 impl From<OscTime> for (u32, u32) {
     fn from(time: OscTime) -> (u32, u32) {
-        (time.seconds, time.fractional)
+        time.parts()
     }
 }
 
@@ -152,13 +429,24 @@ pub enum OscType {
     Time(OscTime),
     Long(i64),
     Double(f64),
+    /// A character with the type tag `c`, encoded as the big-endian 32-bit Unicode scalar value,
+    /// so that ASCII characters end up in the last of the four bytes. The OSC specification only
+    /// allows ASCII characters, which validating packets checks. For senders putting them into the
+    /// first byte see
+    /// [`DecoderOptions::allow_high_byte_chars`](crate::decoder::DecoderOptions).
     Char(char),
     Color(OscColor),
     Midi(OscMidiMessage),
     Bool(bool),
     Array(OscArray),
     Nil,
-    Inf,
+    /// An impulse, also called bang or infinitum, with the type tag `I`. It carries no value and
+    /// is sent to trigger an event, see [`OscMessage::is_impulse_only`]. Formerly named `Inf`.
+    Impulse,
+    /// An unsigned 32-bit integer with the nonstandard type tag `u`, which is only decoded if
+    /// enabled with [`DecoderOptions::accept_extension_tags`](crate::decoder::DecoderOptions).
+    /// Added in 0.8.0, which made this a breaking change for exhaustive matches on `OscType`.
+    Unsigned(u32),
 }
 macro_rules! value_impl {
     ($(($name:ident, $variant:ident, $ty:ty)),*) => {
@@ -177,6 +465,19 @@ macro_rules! value_impl {
                 OscType::$variant(v)
             }
         }
+        impl TryFrom<OscType> for $ty {
+            type Error = errors::OscError;
+
+            fn try_from(arg: OscType) -> result::Result<Self, errors::OscError> {
+                match arg {
+                    OscType::$variant(v) => Ok(v),
+                    arg => Err(errors::OscError::BadArg(format!(
+                        concat!("Expected ", stringify!($variant), " argument, found {:?}"),
+                        arg
+                    ))),
+                }
+            }
+        }
         )*
     }
 }
@@ -191,7 +492,8 @@ value_impl! {
     (char, Char, char),
     (color, Color, OscColor),
     (midi, Midi, OscMidiMessage),
-    (bool, Bool, bool)
+    (bool, Bool, bool),
+    (unsigned, Unsigned, u32)
 }
 impl From<(u32, u32)> for OscType {
     fn from(time: (u32, u32)) -> Self {
@@ -209,20 +511,284 @@ impl TryFrom<SystemTime> for OscType {
 }
 
 impl OscType {
+    /// The former name of [`OscType::Impulse`], which can still be used in expressions and
+    /// patterns. Matches listing it don't cover `Impulse` when checking for exhaustiveness.
+    #[deprecated(note = "renamed to `OscType::Impulse`")]
+    #[allow(non_upper_case_globals)]
+    pub const Inf: OscType = OscType::Impulse;
+
     pub fn time(self) -> Option<OscTime> {
         match self {
             OscType::Time(time) => Some(time),
             _ => None,
         }
     }
+
+    /// Returns the character of a char argument, like [`char`](OscType::char) without consuming
+    /// the argument.
+    pub fn char_value(&self) -> Option<char> {
+        match *self {
+            OscType::Char(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the argument equals `other` by value, where ints, longs, unsigned ints,
+    /// floats and doubles are all comparable and equal if they differ by at most `epsilon`. Arrays are
+    /// compared element by element, all other arguments like with `==`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::OscType;
+    ///
+    /// assert!(OscType::Int(1).numerically_eq(&OscType::Double(1.0), 0.0));
+    /// assert!(OscType::Float(0.5).numerically_eq(&OscType::Double(0.5000001), 1e-6));
+    /// assert!(!OscType::Long(1).numerically_eq(&OscType::Long(2), 0.5));
+    /// assert!(!OscType::Int(1).numerically_eq(&OscType::Bool(true), 1.0));
+    /// ```
+    pub fn numerically_eq(&self, other: &OscType, epsilon: f64) -> bool {
+        match (self, other) {
+            (OscType::Array(left), OscType::Array(right)) => {
+                left.content.len() == right.content.len()
+                    && left
+                        .content
+                        .iter()
+                        .zip(&right.content)
+                        .all(|(left, right)| left.numerically_eq(right, epsilon))
+            }
+            _ => match (self.integer_value(), other.integer_value()) {
+                // Compared exactly, since longs don't fit into a double
+                (Some(left), Some(right)) => (left - right).unsigned_abs() as f64 <= epsilon,
+                _ => match (self.numeric_value(), other.numeric_value()) {
+                    (Some(left), Some(right)) => floats_eq(left, right, epsilon),
+                    _ => self == other,
+                },
+            },
+        }
+    }
+
+    /// Compares the argument to `other` in a total order, e.g. to sort arguments of mixed types
+    /// deterministically, see also [`CanonicalOscType`] for using them as keys of a `BTreeMap`.
+    ///
+    /// Arguments are ordered by their type tag first, by the ASCII code of the tag with `[` for
+    /// arrays, so that `false`, impulses, nils, `true`, arrays, blobs, chars, doubles, floats,
+    /// longs, ints, MIDI messages, colors, strings, time tags and unsigned ints follow each other
+    /// in this order. Arguments of the same type are ordered by value:
+    ///
+    /// - Floats and doubles by [`f32::total_cmp`], which puts `-0.0` before `0.0` and NaNs at the
+    ///   ends: those with the sign bit set before all other values and the others after them.
+    /// - Blobs and strings lexicographically by their bytes, so that a prefix comes first.
+    /// - Time tags by seconds and then fractional, i.e. chronologically.
+    /// - Colors and MIDI messages by their bytes in the order they are encoded in.
+    /// - Arrays lexicographically by their contents compared with `total_cmp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::OscType;
+    ///
+    /// let mut args = vec![
+    ///     OscType::Float(f32::NAN),
+    ///     OscType::String("b".to_string()),
+    ///     OscType::Float(-1.0),
+    ///     OscType::Int(2),
+    ///     OscType::String("a".to_string()),
+    /// ];
+    /// args.sort_by(OscType::total_cmp);
+    /// assert_eq!(args[0], OscType::Float(-1.0));
+    /// assert!(matches!(args[1], OscType::Float(x) if x.is_nan()));
+    /// assert_eq!(args[2..], [
+    ///     OscType::Int(2),
+    ///     OscType::String("a".to_string()),
+    ///     OscType::String("b".to_string()),
+    /// ]);
+    /// ```
+    pub fn total_cmp(&self, other: &OscType) -> cmp::Ordering {
+        let by_value = match (self, other) {
+            (OscType::Float(a), OscType::Float(b)) => a.total_cmp(b),
+            (OscType::Double(a), OscType::Double(b)) => a.total_cmp(b),
+            (OscType::Array(a), OscType::Array(b)) => {
+                let pairs = a.content.iter().zip(&b.content);
+                let by_content = pairs.map(|(a, b)| a.total_cmp(b)).find(|ord| ord.is_ne());
+                by_content.unwrap_or_else(|| a.content.len().cmp(&b.content.len()))
+            }
+            (OscType::Blob(a), OscType::Blob(b)) => a.cmp(b),
+            (a, b) => a.partial_cmp(b).unwrap_or(cmp::Ordering::Equal),
+        };
+        self.type_tag().cmp(&other.type_tag()).then(by_value)
+    }
+
+    /// Returns the type tag of the argument, `[` for arrays.
+    fn type_tag(&self) -> char {
+        match *self {
+            OscType::Int(_) => 'i',
+            OscType::Long(_) => 'h',
+            OscType::Float(_) => 'f',
+            OscType::Double(_) => 'd',
+            OscType::Char(_) => 'c',
+            OscType::String(_) => 's',
+            OscType::Blob(_) => 'b',
+            OscType::Time(_) => 't',
+            OscType::Midi(_) => 'm',
+            OscType::Color(_) => 'r',
+            OscType::Bool(true) => 'T',
+            OscType::Bool(false) => 'F',
+            OscType::Nil => 'N',
+            OscType::Impulse => 'I',
+            OscType::Unsigned(_) => 'u',
+            OscType::Array(_) => '[',
+        }
+    }
+
+    fn integer_value(&self) -> Option<i128> {
+        match *self {
+            OscType::Int(x) => Some(i128::from(x)),
+            OscType::Long(x) => Some(i128::from(x)),
+            OscType::Unsigned(x) => Some(i128::from(x)),
+            _ => None,
+        }
+    }
+
+    fn numeric_value(&self) -> Option<f64> {
+        match *self {
+            OscType::Int(x) => Some(f64::from(x)),
+            OscType::Long(x) => Some(x as f64),
+            OscType::Unsigned(x) => Some(f64::from(x)),
+            OscType::Float(x) => Some(f64::from(x)),
+            OscType::Double(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+/// Compares arguments of the same type by value, while arguments of different types are not
+/// comparable. Floats and doubles compare like [`f32`] does, consistent with `==`, so a NaN is
+/// not comparable either. Use [`OscType::total_cmp`] for a total order of all arguments.
+///
+/// ```
+/// use rosc::OscType;
+///
+/// assert!(OscType::Int(1) < OscType::Int(2));
+/// assert!(OscType::Blob(vec![1, 2]) < OscType::Blob(vec![1, 2, 0]));
+/// assert_eq!(OscType::Int(1).partial_cmp(&OscType::Long(2)), None);
+/// assert_eq!(OscType::Float(f32::NAN).partial_cmp(&OscType::Float(0.0)), None);
+/// ```
+impl PartialOrd for OscType {
+    fn partial_cmp(&self, other: &OscType) -> Option<cmp::Ordering> {
+        match (self, other) {
+            (OscType::Int(a), OscType::Int(b)) => a.partial_cmp(b),
+            (OscType::Long(a), OscType::Long(b)) => a.partial_cmp(b),
+            (OscType::Unsigned(a), OscType::Unsigned(b)) => a.partial_cmp(b),
+            (OscType::Float(a), OscType::Float(b)) => a.partial_cmp(b),
+            (OscType::Double(a), OscType::Double(b)) => a.partial_cmp(b),
+            (OscType::Char(a), OscType::Char(b)) => a.partial_cmp(b),
+            (OscType::String(a), OscType::String(b)) => a.partial_cmp(b),
+            (OscType::Blob(a), OscType::Blob(b)) => a.partial_cmp(b),
+            (OscType::Time(a), OscType::Time(b)) => a.partial_cmp(b),
+            (OscType::Midi(a), OscType::Midi(b)) => {
+                let a = (a.port, a.status, a.data1, a.data2);
+                a.partial_cmp(&(b.port, b.status, b.data1, b.data2))
+            }
+            (OscType::Color(a), OscType::Color(b)) => {
+                let a = (a.red, a.green, a.blue, a.alpha);
+                a.partial_cmp(&(b.red, b.green, b.blue, b.alpha))
+            }
+            (OscType::Bool(a), OscType::Bool(b)) => a.partial_cmp(b),
+            (OscType::Array(a), OscType::Array(b)) => a.content.partial_cmp(&b.content),
+            (OscType::Nil, OscType::Nil) | (OscType::Impulse, OscType::Impulse) => {
+                Some(cmp::Ordering::Equal)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An argument that is equal to and ordered against others by [`OscType::total_cmp`], e.g. to
+/// use arguments as the keys of a `BTreeMap` or to sort them with `sort`. Unlike with `==` on
+/// [`OscType`], a NaN equals a NaN with the same bits, and `0.0` and `-0.0` are not equal.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::{CanonicalOscType, OscType};
+/// use std::collections::BTreeMap;
+///
+/// let mut counts = BTreeMap::new();
+/// for arg in vec![OscType::Float(f32::NAN), OscType::Int(1), OscType::Float(f32::NAN)] {
+///     *counts.entry(CanonicalOscType(arg)).or_insert(0) += 1;
+/// }
+/// assert_eq!(counts.len(), 2);
+/// assert_eq!(counts[&CanonicalOscType(OscType::Float(f32::NAN))], 2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CanonicalOscType(pub OscType);
+
+impl PartialEq for CanonicalOscType {
+    fn eq(&self, other: &CanonicalOscType) -> bool {
+        self.0.total_cmp(&other.0).is_eq()
+    }
+}
+
+impl Eq for CanonicalOscType {}
+
+impl PartialOrd for CanonicalOscType {
+    fn partial_cmp(&self, other: &CanonicalOscType) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CanonicalOscType {
+    fn cmp(&self, other: &CanonicalOscType) -> cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl From<OscType> for CanonicalOscType {
+    fn from(arg: OscType) -> Self {
+        CanonicalOscType(arg)
+    }
+}
+
+impl TryFrom<OscType> for OscTime {
+    type Error = errors::OscError;
+
+    fn try_from(arg: OscType) -> result::Result<Self, errors::OscError> {
+        match arg {
+            OscType::Time(time) => Ok(time),
+            arg => Err(errors::OscError::BadArg(format!(
+                "Expected Time argument, found {:?}",
+                arg
+            ))),
+        }
+    }
 }
 impl<'a> From<&'a str> for OscType {
     fn from(string: &'a str) -> Self {
         OscType::String(string.to_string())
     }
 }
+/// Converts an ASCII code to a char argument. Bytes above 127 are no ASCII characters and become
+/// int arguments instead, as it isn't known which character set they are from.
+///
+/// ```
+/// use rosc::OscType;
+///
+/// assert_eq!(OscType::from(b'A'), OscType::Char('A'));
+/// assert_eq!(OscType::from(0xe9u8), OscType::Int(0xe9));
+/// ```
+impl From<u8> for OscType {
+    fn from(byte: u8) -> Self {
+        match byte.is_ascii() {
+            true => OscType::Char(byte as char),
+            false => OscType::Int(i32::from(byte)),
+        }
+    }
+}
 /// Represents the parts of a Midi message. Mainly used for
 /// tunneling midi over a network using the OSC protocol.
+///
+/// The argument is encoded as the 4 bytes `port`, `status`, `data1` and `data2`
+/// in this order, as defined by the OSC specification.
 #[derive(Clone, Debug, PartialEq)]
 pub struct OscMidiMessage {
     pub port: u8,
@@ -261,6 +827,10 @@ pub struct OscBundle {
 }
 
 /// An RGBA color.
+///
+/// The argument is encoded as the 4 bytes `red`, `green`, `blue` and `alpha`
+/// in this order, as defined by the OSC specification. Senders that use another
+/// byte order cannot be detected, their colors end up with swapped components.
 #[derive(Clone, Debug, PartialEq)]
 pub struct OscColor {
     pub red: u8,
@@ -283,6 +853,1143 @@ impl<T: Into<OscType>> FromIterator<T> for OscArray {
     }
 }
 
+impl OscArray {
+    /// Creates an array of alternating string keys and values, as some protocols encode
+    /// dictionaries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{OscArray, OscType};
+    ///
+    /// let array = OscArray::from_pairs(vec![("gain", OscType::Float(0.5))]);
+    /// assert_eq!(
+    ///     array.content,
+    ///     vec![OscType::String("gain".to_string()), OscType::Float(0.5)]
+    /// );
+    /// ```
+    pub fn from_pairs<I, S>(pairs: I) -> OscArray
+    where
+        I: IntoIterator<Item = (S, OscType)>,
+        S: Into<String>,
+    {
+        let mut content = Vec::new();
+        for (key, value) in pairs {
+            content.push(OscType::String(key.into()));
+            content.push(value);
+        }
+        OscArray { content }
+    }
+
+    /// Returns the key/value pairs of an array of alternating string keys and values, see
+    /// [`from_pairs`](OscArray::from_pairs). Returns [`OscError::BadArg`](errors::OscError::BadArg)
+    /// naming the offending index if a key is not a string or the last key has no value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{OscArray, OscType};
+    ///
+    /// let array = OscArray::from_pairs(vec![("gain", OscType::Float(0.5))]);
+    /// assert_eq!(array.as_pairs().unwrap(), vec![("gain", &OscType::Float(0.5))]);
+    /// ```
+    pub fn as_pairs(&self) -> Result<Vec<(&str, &OscType)>> {
+        let mut pairs = Vec::with_capacity(self.content.len() / 2);
+        for (index, pair) in self.content.chunks(2).enumerate() {
+            let key = match pair[0] {
+                OscType::String(ref key) => key.as_str(),
+                ref other => {
+                    return Err(errors::OscError::BadArg(format!(
+                        "Key at index {} of key/value array is not a string but {:?}",
+                        index * 2,
+                        other
+                    )))
+                }
+            };
+            match pair.get(1) {
+                Some(value) => pairs.push((key, value)),
+                None => {
+                    return Err(errors::OscError::BadArg(format!(
+                        "Key at index {} of key/value array has no value",
+                        index * 2
+                    )))
+                }
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// Returns the value of the first pair with the key `key` of an array of alternating string
+    /// keys and values, or `None` if there is none. Fails like [`as_pairs`](OscArray::as_pairs)
+    /// if the array isn't made up of pairs.
+    pub fn lookup(&self, key: &str) -> Result<Option<&OscType>> {
+        Ok(self
+            .as_pairs()?
+            .into_iter()
+            .find(|&(k, _)| k == key)
+            .map(|(_, value)| value))
+    }
+}
+
+/// A borrowed counterpart of [`OscType`] as returned by [`decode_ref`](crate::decoder::decode_ref).
+/// Strings and blobs point into the decoded buffer instead of being copied. Strings are only owned
+/// if invalid UTF-8 had to be replaced, see
+/// [`StringDecoding::Lossy`](crate::decoder::StringDecoding::Lossy).
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscTypeRef<'a> {
+    Int(i32),
+    Float(f32),
+    String(Cow<'a, str>),
+    Blob(&'a [u8]),
+    Time(OscTime),
+    Long(i64),
+    Double(f64),
+    Char(char),
+    Color(OscColor),
+    Midi(OscMidiMessage),
+    Bool(bool),
+    Array(OscArrayRef<'a>),
+    Nil,
+    /// An impulse, see [`OscType::Impulse`].
+    Impulse,
+    /// An unsigned 32-bit integer with the nonstandard type tag `u`, see [`OscType::Unsigned`].
+    Unsigned(u32),
+}
+
+/// A borrowed counterpart of [`OscPacket`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscPacketRef<'a> {
+    Message(OscMessageRef<'a>),
+    Bundle(OscBundleRef<'a>),
+}
+
+/// A borrowed counterpart of [`OscMessage`], the address and all string or blob
+/// arguments are slices of the decoded buffer unless invalid UTF-8 had to be replaced.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscMessageRef<'a> {
+    pub addr: Cow<'a, str>,
+    pub args: Vec<OscTypeRef<'a>>,
+}
+
+/// A message whose address and arguments are borrowed for the lifetime of the program, so it can
+/// be defined as a `const` or `static`. Arguments that can be constructed in constants are
+/// numbers, chars, time tags, MIDI messages, colors, booleans, nil, impulses, and strings, blobs
+/// and arrays that are empty. [`encoder::encode_static_into`](crate::encoder::encode_static_into)
+/// encodes it without allocating.
+///
+/// # Examples
+///
+/// ```
+/// use rosc::{encoder, OscMessage, OscType, StaticOscMessage};
+///
+/// static PING: StaticOscMessage = StaticOscMessage {
+///     addr: "/ping",
+///     args: &[OscType::Int(1), OscType::Bool(true)],
+/// };
+///
+/// let mut buf = Vec::with_capacity(64);
+/// encoder::encode_static_into(&PING, &mut buf).unwrap();
+/// assert_eq!(OscMessage::from(&PING).addr, "/ping");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StaticOscMessage {
+    pub addr: &'static str,
+    pub args: &'static [OscType],
+}
+
+impl From<&StaticOscMessage> for OscMessage {
+    fn from(msg: &StaticOscMessage) -> Self {
+        OscMessage {
+            addr: msg.addr.to_string(),
+            args: msg.args.to_vec(),
+        }
+    }
+}
+
+/// A borrowed counterpart of [`OscBundle`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscBundleRef<'a> {
+    pub timetag: OscTime,
+    pub content: Vec<OscPacketRef<'a>>,
+}
+
+/// A borrowed counterpart of [`OscArray`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscArrayRef<'a> {
+    pub content: Vec<OscTypeRef<'a>>,
+}
+
+impl<'a> OscTypeRef<'a> {
+    /// The former name of [`OscTypeRef::Impulse`], see [`OscType::Inf`].
+    #[deprecated(note = "renamed to `OscTypeRef::Impulse`")]
+    #[allow(non_upper_case_globals)]
+    pub const Inf: OscTypeRef<'a> = OscTypeRef::Impulse;
+
+    /// Copies the borrowed data into an owned [`OscType`].
+    pub fn to_owned(&self) -> OscType {
+        match *self {
+            OscTypeRef::Int(x) => OscType::Int(x),
+            OscTypeRef::Float(x) => OscType::Float(x),
+            OscTypeRef::String(ref x) => OscType::String(x.to_string()),
+            OscTypeRef::Blob(x) => OscType::Blob(x.to_vec()),
+            OscTypeRef::Time(x) => OscType::Time(x),
+            OscTypeRef::Long(x) => OscType::Long(x),
+            OscTypeRef::Double(x) => OscType::Double(x),
+            OscTypeRef::Char(x) => OscType::Char(x),
+            OscTypeRef::Color(ref x) => OscType::Color(x.clone()),
+            OscTypeRef::Midi(ref x) => OscType::Midi(x.clone()),
+            OscTypeRef::Bool(x) => OscType::Bool(x),
+            OscTypeRef::Array(ref x) => OscType::Array(x.to_owned()),
+            OscTypeRef::Nil => OscType::Nil,
+            OscTypeRef::Impulse => OscType::Impulse,
+            OscTypeRef::Unsigned(x) => OscType::Unsigned(x),
+        }
+    }
+
+    /// Returns the bytes of a blob argument, which point into the decoded buffer, or `None` if
+    /// this is not a blob.
+    pub fn as_blob(&self) -> Option<&'a [u8]> {
+        match *self {
+            OscTypeRef::Blob(blob) => Some(blob),
+            _ => None,
+        }
+    }
+
+    /// Returns a reader of the bytes of a blob argument, e.g. to stream a large blob into a file
+    /// with [`io::copy`](std::io::copy) without copying it in memory, or `None` if this is not a
+    /// blob.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{decoder, encoder, OscMessage, OscPacket, OscPacketRef, OscType};
+    /// use std::io;
+    ///
+    /// let packet = OscPacket::Message(OscMessage {
+    ///     addr: "/sample".to_string(),
+    ///     args: vec![OscType::Blob(vec![1; 4096])],
+    /// });
+    /// let buf = encoder::encode(&packet).unwrap();
+    /// let msg = match decoder::decode_ref(&buf).unwrap().1 {
+    ///     OscPacketRef::Message(msg) => msg,
+    ///     _ => unreachable!(),
+    /// };
+    /// let mut file = Vec::new();
+    /// io::copy(&mut msg.args[0].blob_reader().unwrap(), &mut file).unwrap();
+    /// assert_eq!(file, vec![1; 4096]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn blob_reader(&self) -> Option<impl std::io::Read + 'a> {
+        self.as_blob()
+    }
+
+    /// Returns an iterator over the bytes of a blob argument in pieces of `size` bytes, the last
+    /// of which may be shorter, or `None` if this is not a blob.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn blob_chunks(&self, size: usize) -> Option<core::slice::Chunks<'a, u8>> {
+        self.as_blob().map(|blob| blob.chunks(size))
+    }
+}
+
+/// Controls which differences [`OscMessage::semantically_eq`] ignores. The default ignores none,
+/// like `==`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SemanticEqOptions {
+    /// Floats and doubles are equal if they differ by at most this amount, as are all numbers
+    /// with [`numeric_widening`](SemanticEqOptions::numeric_widening).
+    pub epsilon: f64,
+    /// Compare ints, longs, unsigned ints, floats and doubles by value regardless of their type, see
+    /// [`OscType::numerically_eq`].
+    pub numeric_widening: bool,
+    /// Compare addresses ignoring ASCII case.
+    pub case_insensitive_address: bool,
+    /// Ignore nil arguments at the end of either message.
+    pub ignore_trailing_nil: bool,
+}
+
+impl SemanticEqOptions {
+    /// Returns whether the arguments of two messages are equal.
+    fn args_eq(&self, left: &[OscType], right: &[OscType]) -> bool {
+        left.len() == right.len()
+            && left
+                .iter()
+                .zip(right)
+                .all(|(left, right)| self.arg_eq(left, right))
+    }
+
+    /// Returns whether two arguments are equal.
+    fn arg_eq(&self, left: &OscType, right: &OscType) -> bool {
+        match (left, right) {
+            _ if self.numeric_widening => left.numerically_eq(right, self.epsilon),
+            (OscType::Float(l), OscType::Float(r)) => {
+                floats_eq(f64::from(*l), f64::from(*r), self.epsilon)
+            }
+            (OscType::Double(l), OscType::Double(r)) => floats_eq(*l, *r, self.epsilon),
+            (OscType::Array(l), OscType::Array(r)) => self.args_eq(&l.content, &r.content),
+            _ => left == right,
+        }
+    }
+
+    /// Returns whether two addresses are equal.
+    fn addr_eq(&self, left: &str, right: &str) -> bool {
+        match self.case_insensitive_address {
+            true => left.eq_ignore_ascii_case(right),
+            false => left == right,
+        }
+    }
+
+    /// Returns the arguments that are compared, i.e. without trailing nils if they are ignored.
+    fn compared_args<'a>(&self, args: &'a [OscType]) -> &'a [OscType] {
+        match self.ignore_trailing_nil {
+            true => {
+                let len = args.iter().rposition(|arg| *arg != OscType::Nil).map_or(0, |i| i + 1);
+                &args[..len]
+            }
+            false => args,
+        }
+    }
+}
+
+/// Returns whether two numbers differ by at most `epsilon`, which infinities of the same sign do as
+/// well.
+fn floats_eq(left: f64, right: f64, epsilon: f64) -> bool {
+    left == right || (left - right).abs() <= epsilon
+}
+
+/// Which of the messages with the same address [`OscBundle::dedup_by_address`] keeps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Keep {
+    /// The first message, e.g. to keep the state a receiver saw first.
+    First,
+    /// The last message, which carries the latest value.
+    #[default]
+    Last,
+}
+
+/// Options for [`OscBundle::dedup_with_options`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DedupOptions {
+    /// Which of the messages with the same address is kept. Defaults to [`Keep::Last`].
+    pub keep: Keep,
+    /// Only treat messages with the same address as duplicates if their type tags are identical
+    /// as well, e.g. to keep `/fader f` and `/fader s` apart. Defaults to `false`.
+    pub match_type_tags: bool,
+    /// Deduplicate the messages of nested bundles as well. Defaults to `false`.
+    pub recursive: bool,
+}
+
+impl DedupOptions {
+    /// Returns whether `right` duplicates `left`.
+    fn is_duplicate(&self, left: &OscMessage, right: &OscMessage) -> bool {
+        left.addr == right.addr
+            && (!self.match_type_tags || type_tags_eq(&left.args, &right.args))
+    }
+}
+
+/// Returns whether two argument lists have the same type tags.
+fn type_tags_eq(left: &[OscType], right: &[OscType]) -> bool {
+    left.len() == right.len()
+        && left.iter().zip(right).all(|(left, right)| match (left, right) {
+            (OscType::Bool(l), OscType::Bool(r)) => l == r,
+            (OscType::Array(l), OscType::Array(r)) => type_tags_eq(&l.content, &r.content),
+            _ => mem::discriminant(left) == mem::discriminant(right),
+        })
+}
+
+/// Chooses the arguments that [`OscMessage::truncate_to_fit`] may shorten.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TruncatePolicy {
+    /// Cut bytes off the end of blobs. Defaults to `true`.
+    pub blobs: bool,
+    /// Cut characters off the end of strings, marking them as truncated. Defaults to `true`.
+    pub strings: bool,
+    /// Drop arguments from the end of the message if shortening the remaining ones doesn't
+    /// suffice. Defaults to `false`.
+    pub drop_trailing_args: bool,
+}
+
+impl Default for TruncatePolicy {
+    fn default() -> Self {
+        TruncatePolicy {
+            blobs: true,
+            strings: true,
+            drop_trailing_args: false,
+        }
+    }
+}
+
+impl TruncatePolicy {
+    /// Returns the size of the encoded data of `arg` once shortened as far as the policy allows.
+    fn min_data_len(&self, arg: &OscType) -> usize {
+        let len = arg_data_len(arg);
+        match *arg {
+            // An empty blob still has a size
+            OscType::Blob(_) if self.blobs => 4,
+            // The shortest truncated string is the marker
+            OscType::String(_) if self.strings => {
+                len.min(padded_len(errors::TRUNCATION_MARKER.len() + 1))
+            }
+            _ => len,
+        }
+    }
+}
+
+/// Returns `len` rounded up to a multiple of 4, see [`encoder::pad`](crate::encoder::pad).
+fn padded_len(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+/// Returns the number of type tags of `arg`, including the brackets of arrays.
+fn arg_tag_count(arg: &OscType) -> usize {
+    match *arg {
+        OscType::Array(ref array) => array.content.iter().map(arg_tag_count).sum::<usize>() + 2,
+        _ => 1,
+    }
+}
+
+/// Returns the size of the encoded data of `arg`, following the type tags.
+fn arg_data_len(arg: &OscType) -> usize {
+    match *arg {
+        OscType::Int(_)
+        | OscType::Float(_)
+        | OscType::Char(_)
+        | OscType::Midi(_)
+        | OscType::Color(_)
+        | OscType::Unsigned(_) => 4,
+        OscType::Long(_) | OscType::Double(_) | OscType::Time(_) => 8,
+        OscType::String(ref s) => padded_len(s.len() + 1),
+        OscType::Blob(ref blob) => 4 + padded_len(blob.len()),
+        OscType::Bool(_) | OscType::Nil | OscType::Impulse => 0,
+        OscType::Array(ref array) => array.content.iter().map(arg_data_len).sum(),
+    }
+}
+
+/// Infers the type of a value of [`OscMessage::from_kv`].
+fn infer_kv_value(value: &str) -> OscType {
+    if let Some(x) = value.parse::<i64>().ok().and_then(|x| i32::try_from(x).ok()) {
+        return OscType::Int(x);
+    }
+    // Parsing as f32 accepts the same syntax as f64, and rounds only once
+    if let Ok(x) = value.parse::<f32>() {
+        return OscType::Float(x);
+    }
+    match value {
+        "true" => OscType::Bool(true),
+        "false" => OscType::Bool(false),
+        "nil" => OscType::Nil,
+        _ => OscType::String(value.to_string()),
+    }
+}
+
+/// Returns the error for the value at `index` of [`OscMessage::from_kv`].
+fn kv_error(index: usize, value: &str, reason: &str) -> errors::OscError {
+    errors::OscError::BadArg(format!(
+        "Value {} '{}': {}",
+        index,
+        errors::truncate_input(value),
+        reason
+    ))
+}
+
+impl OscMessage {
+    /// Returns a copy of the message with the argument at `index` replaced by `arg`, e.g. to
+    /// update a prototype message. Only the remaining arguments are cloned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range, use [`set_arg`](OscMessage::set_arg) to check it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{OscMessage, OscType};
+    ///
+    /// let prototype = OscMessage {
+    ///     addr: "/synth/1/cutoff".to_string(),
+    ///     args: vec![OscType::Int(1), OscType::Float(0.0)],
+    /// };
+    /// let msg = prototype.with_arg_at(1, OscType::Float(0.5));
+    /// assert_eq!(msg.args, vec![OscType::Int(1), OscType::Float(0.5)]);
+    /// ```
+    pub fn with_arg_at(&self, index: usize, arg: OscType) -> OscMessage {
+        assert!(
+            index < self.args.len(),
+            "argument index {} is out of range for a message with {} arguments",
+            index,
+            self.args.len()
+        );
+        let mut args = Vec::with_capacity(self.args.len());
+        args.extend_from_slice(&self.args[..index]);
+        args.push(arg);
+        args.extend_from_slice(&self.args[index + 1..]);
+        OscMessage {
+            addr: self.addr.clone(),
+            args,
+        }
+    }
+
+    /// Replaces the argument at `index` by `arg`, which may be of a different type. Returns
+    /// [`OscError::BadArg`](errors::OscError::BadArg) if `index` is out of range.
+    pub fn set_arg(&mut self, index: usize, arg: OscType) -> Result<()> {
+        let len = self.args.len();
+        match self.args.get_mut(index) {
+            Some(old) => {
+                *old = arg;
+                Ok(())
+            }
+            None => Err(errors::OscError::BadArg(format!(
+                "Argument index {} is out of range for a message with {} arguments",
+                index, len
+            ))),
+        }
+    }
+
+    /// Creates a message without arguments, as sent to trigger an action, e.g. `/scene/next`.
+    pub fn trigger<A: Into<String>>(addr: A) -> OscMessage {
+        OscMessage {
+            addr: addr.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Returns whether the message carries no values, i.e. has no arguments or only nil and
+    /// impulse arguments, which some senders add to trigger messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{OscMessage, OscType};
+    ///
+    /// assert!(OscMessage::trigger("/scene/next").is_trigger());
+    /// let mut msg = OscMessage::trigger("/scene/next");
+    /// msg.args.push(OscType::Impulse);
+    /// assert!(msg.is_trigger());
+    /// msg.push_int(1);
+    /// assert!(!msg.is_trigger());
+    /// ```
+    pub fn is_trigger(&self) -> bool {
+        self.args
+            .iter()
+            .all(|arg| matches!(arg, OscType::Nil | OscType::Impulse))
+    }
+
+    /// Returns whether the message carries nothing but impulses, the way many devices send a
+    /// bang, i.e. at least one [`OscType::Impulse`] and no other arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{OscMessage, OscType};
+    ///
+    /// let mut msg = OscMessage::from("/bang");
+    /// assert!(!msg.is_impulse_only());
+    /// msg.args.push(OscType::Impulse);
+    /// assert!(msg.is_impulse_only());
+    /// msg.push_nil();
+    /// assert!(!msg.is_impulse_only());
+    /// ```
+    pub fn is_impulse_only(&self) -> bool {
+        !self.args.is_empty() && self.args.iter().all(|arg| *arg == OscType::Impulse)
+    }
+
+    /// Returns a copy of the message sent to `addr` instead, with the same arguments.
+    pub fn retarget<A: Into<String>>(&self, addr: A) -> OscMessage {
+        OscMessage {
+            addr: addr.into(),
+            args: self.args.clone(),
+        }
+    }
+
+    /// Returns the parts of the address of the message, see
+    /// [`AddressParts`](crate::address::AddressParts). Fails with
+    /// [`OscError::BadAddress`](errors::OscError::BadAddress) if the address is not valid, which
+    /// includes address patterns with wildcards, see
+    /// [`tokenize_pattern`](crate::address::tokenize_pattern) for those.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::OscMessage;
+    ///
+    /// let msg = OscMessage::from("/mixer/1/gain");
+    /// assert_eq!(msg.addr_parts().unwrap().last(), Some("gain"));
+    /// assert!(OscMessage::from("/mixer/*/gain").addr_parts().is_err());
+    /// ```
+    pub fn addr_parts(&self) -> Result<crate::address::AddressParts<'_>> {
+        crate::address::AddressParts::new(&self.addr)
+    }
+
+    /// Splits the message into its address and arguments, e.g. into the topic and payload of a
+    /// message bridged to a key/value store. [`OscMessage::from_kv`] goes the other way.
+    pub fn to_kv(&self) -> (String, Vec<OscType>) {
+        (self.addr.clone(), self.args.clone())
+    }
+
+    /// Creates a message sent to `addr` from values in text form, e.g. from a key/value store,
+    /// inferring the type of each value:
+    ///
+    /// 1. [`OscType::Int`] if the value parses as an `i64` that fits into an `i32`, e.g. `42`,
+    ///    `-7` or `+3`, but not `1_000`, `0x10` or ` 1`
+    /// 2. [`OscType::Float`] if it parses as an `f64`, e.g. `0.5`, `-1e3`, `.5`, `1.`, `NaN`,
+    ///    `inf` and integers outside the range of `i32` like `3000000000`. The value is rounded
+    ///    to an `f32`, so that `1e39` becomes infinity
+    /// 3. [`OscType::Bool`] for exactly `true` and `false`, and [`OscType::Nil`] for exactly
+    ///    `nil`, which are case-sensitive
+    /// 4. [`OscType::String`] otherwise, including the empty string and values surrounded by
+    ///    whitespace
+    ///
+    /// Use [`from_kv_with_tags`](OscMessage::from_kv_with_tags) to choose the types instead.
+    /// Fails with [`OscError::BadAddress`](errors::OscError::BadAddress) if `addr` is no valid
+    /// address pattern, and with [`OscError::BadArg`](errors::OscError::BadArg) if a value
+    /// contains a null character, which OSC strings can't.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{OscMessage, OscType};
+    ///
+    /// let msg = OscMessage::from_kv("/mixer/1", &["1", "0.5", "true", "nil", "Lead"]).unwrap();
+    /// assert_eq!(
+    ///     msg.args,
+    ///     vec![
+    ///         OscType::Int(1),
+    ///         OscType::Float(0.5),
+    ///         OscType::Bool(true),
+    ///         OscType::Nil,
+    ///         "Lead".into(),
+    ///     ]
+    /// );
+    /// assert_eq!(msg.to_kv(), ("/mixer/1".to_string(), msg.args.clone()));
+    /// ```
+    pub fn from_kv(addr: &str, values: &[&str]) -> Result<OscMessage> {
+        crate::address::verify_address_pattern(addr)?;
+        let args = values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| match infer_kv_value(value) {
+                // Checked like strings in text form
+                OscType::String(_) => {
+                    crate::text::parse_value('s', value).map_err(|msg| kv_error(index, value, &msg))
+                }
+                arg => Ok(arg),
+            })
+            .collect::<Result<_>>()?;
+        Ok(OscMessage {
+            addr: addr.to_string(),
+            args,
+        })
+    }
+
+    /// Creates a message sent to `addr` like [`from_kv`](OscMessage::from_kv), but with the
+    /// value at each position parsed according to the type tag at the same position of `tags`,
+    /// which may start with a `,`. There must be one type tag for each value, and only the type
+    /// tags taking a value are supported, written as described for
+    /// [`decoder::parse_text`](crate::decoder::parse_text), e.g. `0x0102` for a blob and
+    /// `@1.0` for a time tag. Use `from_kv` to infer bools and nil.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{OscMessage, OscType};
+    ///
+    /// let msg = OscMessage::from_kv_with_tags("/mixer/1", &["1", "2", "3"], ",dhs").unwrap();
+    /// assert_eq!(msg.args, vec![OscType::Double(1.0), OscType::Long(2), "3".into()]);
+    /// assert!(OscMessage::from_kv_with_tags("/mixer/1", &["0.5"], "i").is_err());
+    /// ```
+    pub fn from_kv_with_tags(addr: &str, values: &[&str], tags: &str) -> Result<OscMessage> {
+        crate::address::verify_address_pattern(addr)?;
+        let tags = tags.strip_prefix(',').unwrap_or(tags);
+        if tags.chars().count() != values.len() {
+            return Err(errors::OscError::BadArg(format!(
+                "{} type tags for {} values",
+                tags.chars().count(),
+                values.len()
+            )));
+        }
+        let args = tags
+            .chars()
+            .zip(values)
+            .enumerate()
+            .map(|(index, (tag, value))| match tag {
+                'i' | 'h' | 'u' | 'f' | 'd' | 's' | 'c' | 'b' | 't' | 'm' | 'r' => {
+                    crate::text::parse_value(tag, value)
+                        .map_err(|msg| kv_error(index, value, &msg))
+                }
+                _ => Err(kv_error(index, value, &format!("Unsupported type tag '{}'", tag))),
+            })
+            .collect::<Result<_>>()?;
+        Ok(OscMessage {
+            addr: addr.to_string(),
+            args,
+        })
+    }
+
+    /// Returns whether the message equals `other` apart from the differences that `options`
+    /// ignore, e.g. for comparing with messages of devices that send doubles instead of floats.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{OscMessage, OscType, SemanticEqOptions};
+    ///
+    /// let sent = OscMessage {
+    ///     addr: "/Mixer/Gain".to_string(),
+    ///     args: vec![OscType::Float(0.5), OscType::Int(1)],
+    /// };
+    /// let received = OscMessage {
+    ///     addr: "/mixer/gain".to_string(),
+    ///     args: vec![OscType::Double(0.5), OscType::Long(1), OscType::Nil],
+    /// };
+    /// let options = SemanticEqOptions {
+    ///     numeric_widening: true,
+    ///     case_insensitive_address: true,
+    ///     ignore_trailing_nil: true,
+    ///     ..Default::default()
+    /// };
+    /// assert!(sent.semantically_eq(&received, &options));
+    /// assert!(!sent.semantically_eq(&received, &SemanticEqOptions::default()));
+    /// ```
+    pub fn semantically_eq(&self, other: &OscMessage, options: &SemanticEqOptions) -> bool {
+        options.addr_eq(&self.addr, &other.addr)
+            && options.args_eq(
+                options.compared_args(&self.args),
+                options.compared_args(&other.args),
+            )
+    }
+
+    /// Returns the size of the encoded message in bytes, without encoding it.
+    pub fn encoded_len(&self) -> usize {
+        let tags: usize = self.args.iter().map(arg_tag_count).sum();
+        let data: usize = self.args.iter().map(arg_data_len).sum();
+        padded_len(self.addr.len() + 1) + padded_len(tags + 2) + data
+    }
+
+    /// Shortens the arguments of the message until it encodes into at most `max_encoded_size`
+    /// bytes, e.g. to send a message that doesn't fit into a datagram in a degraded form rather
+    /// than not at all. `policy` chooses which arguments may be shortened, starting with the last
+    /// one. Blobs lose bytes at their end, while strings are cut off at a character boundary and
+    /// end with [`TRUNCATION_MARKER`](crate::TRUNCATION_MARKER). Arrays and the contents of arrays
+    /// are never shortened, only dropped.
+    ///
+    /// Returns whether the message was changed. Fails with [`OscError::PacketTooLarge`], telling
+    /// the smallest size the policy allows, without changing the message if it can't be made to
+    /// fit, which is always the case if the message doesn't fit even without any arguments.
+    ///
+    /// [`OscError::PacketTooLarge`]: crate::OscError::PacketTooLarge
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{encoder, OscMessage, OscPacket, OscType, TruncatePolicy};
+    ///
+    /// let mut msg = OscMessage::from("/log");
+    /// msg.push_int(3).push_str("a line".repeat(100));
+    /// assert!(msg.truncate_to_fit(64, TruncatePolicy::default()).unwrap());
+    /// assert_eq!(msg.args[0], OscType::Int(3));
+    /// assert!(msg.args[1].clone().string().unwrap().ends_with("..."));
+    /// assert!(encoder::encode(&OscPacket::Message(msg.clone())).unwrap().len() <= 64);
+    /// assert!(msg.truncate_to_fit(4, TruncatePolicy::default()).is_err());
+    /// ```
+    pub fn truncate_to_fit(
+        &mut self,
+        max_encoded_size: usize,
+        policy: TruncatePolicy,
+    ) -> Result<bool> {
+        let size = self.encoded_len();
+        if size <= max_encoded_size {
+            return Ok(false);
+        }
+
+        // The size of the message with the first `count` arguments, each shortened as far as
+        // the policy allows
+        let addr_len = padded_len(self.addr.len() + 1);
+        let min_len = |count: usize| {
+            let args = &self.args[..count];
+            let tags: usize = args.iter().map(arg_tag_count).sum();
+            let data: usize = args.iter().map(|arg| policy.min_data_len(arg)).sum();
+            addr_len + padded_len(tags + 2) + data
+        };
+        let mut count = self.args.len();
+        if policy.drop_trailing_args {
+            while count > 0 && min_len(count) > max_encoded_size {
+                count -= 1;
+            }
+        }
+        let min_size = min_len(count);
+        if min_size > max_encoded_size {
+            return Err(errors::OscError::PacketTooLarge {
+                size: min_size,
+                max: max_encoded_size,
+            });
+        }
+
+        self.args.truncate(count);
+        let mut excess = self.encoded_len().saturating_sub(max_encoded_size);
+        for arg in self.args.iter_mut().rev() {
+            if excess == 0 {
+                break;
+            }
+            let len = arg_data_len(arg);
+            let target = len.saturating_sub(excess).max(policy.min_data_len(arg));
+            match *arg {
+                OscType::Blob(ref mut blob) if target < len => {
+                    // The size of a blob is followed by its padded bytes
+                    blob.truncate((target - 4) & !3);
+                }
+                OscType::String(ref mut s) if target < len => {
+                    // The string is followed by at least one zero
+                    let mut end = (target & !3) - errors::TRUNCATION_MARKER.len() - 1;
+                    while !s.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    s.truncate(end);
+                    s.push_str(errors::TRUNCATION_MARKER);
+                }
+                _ => continue,
+            }
+            excess = excess.saturating_sub(len - arg_data_len(arg));
+        }
+        Ok(true)
+    }
+
+    /// Appends an int argument. Like the other `push_*` methods, this returns the message to
+    /// allow chaining calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{OscMessage, OscType};
+    ///
+    /// let mut msg = OscMessage::from("/note");
+    /// msg.push_int(60).push_float(0.8).push_str("piano");
+    /// assert_eq!(
+    ///     msg.args,
+    ///     vec![OscType::Int(60), OscType::Float(0.8), OscType::String("piano".to_string())]
+    /// );
+    /// ```
+    #[inline]
+    pub fn push_int(&mut self, x: i32) -> &mut Self {
+        self.args.push(OscType::Int(x));
+        self
+    }
+
+    /// Appends a float argument.
+    #[inline]
+    pub fn push_float(&mut self, x: f32) -> &mut Self {
+        self.args.push(OscType::Float(x));
+        self
+    }
+
+    /// Appends a double argument.
+    #[inline]
+    pub fn push_double(&mut self, x: f64) -> &mut Self {
+        self.args.push(OscType::Double(x));
+        self
+    }
+
+    /// Appends a string argument.
+    #[inline]
+    pub fn push_str<S: Into<String>>(&mut self, s: S) -> &mut Self {
+        self.args.push(OscType::String(s.into()));
+        self
+    }
+
+    /// Appends a blob argument.
+    #[inline]
+    pub fn push_blob<B: Into<Vec<u8>>>(&mut self, blob: B) -> &mut Self {
+        self.args.push(OscType::Blob(blob.into()));
+        self
+    }
+
+    /// Appends a bool argument.
+    #[inline]
+    pub fn push_bool(&mut self, x: bool) -> &mut Self {
+        self.args.push(OscType::Bool(x));
+        self
+    }
+
+    /// Appends a nil argument.
+    #[inline]
+    pub fn push_nil(&mut self) -> &mut Self {
+        self.args.push(OscType::Nil);
+        self
+    }
+}
+
+/// Appends the arguments.
+impl<T: Into<OscType>> Extend<T> for OscMessage {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.args.extend(iter.into_iter().map(T::into));
+    }
+}
+
+impl OscBundle {
+    /// Returns whether the contents of the bundle are meant to be applied immediately, see
+    /// [`OscTime::is_immediate`].
+    pub fn is_immediate(&self) -> bool {
+        self.timetag.is_immediate()
+    }
+
+    /// Returns how late a bundle received at `now` arrived relative to its time tag: positive if
+    /// it arrived after the time tag, negative if it arrived early. Immediate bundles have no
+    /// time to be late for and result in `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{OscBundle, OscTime};
+    ///
+    /// let bundle = OscBundle {
+    ///     timetag: (100, 0).into(),
+    ///     content: vec![],
+    /// };
+    /// let lateness = bundle.lateness(OscTime::from((99, 1 << 31))).unwrap();
+    /// assert_eq!(lateness.as_secs_f64(), -0.5);
+    /// ```
+    pub fn lateness(&self, now: OscTime) -> Option<SignedDuration> {
+        match self.is_immediate() {
+            true => None,
+            false => Some(now.delta_from(self.timetag)),
+        }
+    }
+
+    /// Removes the messages of the bundle that have the same address as another one, keeping
+    /// the first or the last of them, and returns how many were removed. The surviving elements
+    /// keep their order. Nested bundles are left alone, see
+    /// [`dedup_with_options`](OscBundle::dedup_with_options).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rosc::{Keep, OscBundle, OscMessage, OscPacket, OscTime};
+    ///
+    /// let fader = |value: f32| {
+    ///     let mut msg = OscMessage::from("/fader");
+    ///     msg.push_float(value);
+    ///     OscPacket::Message(msg)
+    /// };
+    /// let mut bundle = OscBundle {
+    ///     timetag: OscTime::IMMEDIATE,
+    ///     content: vec![fader(0.1), OscPacket::Message("/go".into()), fader(0.2)],
+    /// };
+    /// assert_eq!(bundle.dedup_by_address(Keep::Last), 1);
+    /// assert_eq!(bundle.content, vec![OscPacket::Message("/go".into()), fader(0.2)]);
+    /// ```
+    pub fn dedup_by_address(&mut self, keep: Keep) -> usize {
+        self.dedup_with_options(&DedupOptions {
+            keep,
+            ..Default::default()
+        })
+    }
+
+    /// Removes duplicate messages like [`OscBundle::dedup_by_address`], using the given options.
+    ///
+    /// Nested bundles are never removed. With [`DedupOptions::recursive`], the messages of each
+    /// nested bundle are deduplicated among themselves, but not against those of the enclosing
+    /// bundle, as their time tags may differ. The returned count includes the messages removed
+    /// from nested bundles.
+    pub fn dedup_with_options(&mut self, options: &DedupOptions) -> usize {
+        let content = &self.content;
+        let retained: Vec<bool> = (0..content.len())
+            .map(|i| {
+                let msg = match content[i] {
+                    OscPacket::Message(ref msg) => msg,
+                    OscPacket::Bundle(_) => return true,
+                };
+                let others = match options.keep {
+                    Keep::First => &content[..i],
+                    Keep::Last => &content[i + 1..],
+                };
+                !others.iter().any(|other| match *other {
+                    OscPacket::Message(ref other) => options.is_duplicate(msg, other),
+                    OscPacket::Bundle(_) => false,
+                })
+            })
+            .collect();
+        let mut removed = retained.iter().filter(|retained| !**retained).count();
+        let mut retained = retained.into_iter();
+        self.content.retain(|_| retained.next().unwrap_or(true));
+
+        if options.recursive {
+            for packet in &mut self.content {
+                if let OscPacket::Bundle(ref mut bundle) = *packet {
+                    removed += bundle.dedup_with_options(options);
+                }
+            }
+        }
+        removed
+    }
+}
+
+impl OscPacket {
+    /// Creates a packet of a message without arguments, see [`OscMessage::trigger`].
+    pub fn trigger<A: Into<String>>(addr: A) -> OscPacket {
+        OscPacket::Message(OscMessage::trigger(addr))
+    }
+
+    /// Returns an iterator over all messages of the packet, including those of nested bundles, in
+    /// order. Each message comes with the time tag of the bundle containing it, or
+    /// [`OscTime::IMMEDIATE`] if it is not part of a bundle. Nested bundles that are immediate
+    /// take the time tag of their enclosing bundle, as they can't be applied before it.
+    ///
+    /// ```
+    /// use rosc::{OscBundle, OscMessage, OscPacket, OscTime};
+    ///
+    /// let packet = OscPacket::Bundle(OscBundle {
+    ///     timetag: (100, 0).into(),
+    ///     content: vec![
+    ///         OscPacket::Message(OscMessage::from("/a")),
+    ///         OscPacket::Bundle(OscBundle {
+    ///             timetag: OscTime::IMMEDIATE,
+    ///             content: vec![OscPacket::Message(OscMessage::from("/b"))],
+    ///         }),
+    ///     ],
+    /// });
+    /// let messages: Vec<_> = packet.into_messages().collect();
+    /// assert_eq!(
+    ///     messages,
+    ///     vec![
+    ///         ((100, 0).into(), OscMessage::from("/a")),
+    ///         ((100, 0).into(), OscMessage::from("/b")),
+    ///     ]
+    /// );
+    /// ```
+    pub fn into_messages(self) -> IntoMessages {
+        match self {
+            OscPacket::Message(msg) => IntoMessages {
+                message: Some(msg),
+                bundles: Vec::new(),
+            },
+            OscPacket::Bundle(bundle) => IntoMessages {
+                message: None,
+                bundles: vec![(bundle.timetag, bundle.content.into_iter())],
+            },
+        }
+    }
+}
+
+/// Iterator over the messages of a packet, see [`OscPacket::into_messages`].
+#[derive(Debug)]
+pub struct IntoMessages {
+    // A message that is not part of a bundle
+    message: Option<OscMessage>,
+    // Time tags and remaining contents of the bundles that are currently being iterated
+    bundles: Vec<(OscTime, vec::IntoIter<OscPacket>)>,
+}
+
+impl Iterator for IntoMessages {
+    type Item = (OscTime, OscMessage);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(msg) = self.message.take() {
+            return Some((OscTime::IMMEDIATE, msg));
+        }
+        loop {
+            let (timetag, content) = self.bundles.last_mut()?;
+            let timetag = *timetag;
+            match content.next() {
+                Some(OscPacket::Message(msg)) => return Some((timetag, msg)),
+                Some(OscPacket::Bundle(bundle)) => {
+                    let nested = if bundle.is_immediate() {
+                        timetag
+                    } else {
+                        bundle.timetag
+                    };
+                    self.bundles.push((nested, bundle.content.into_iter()));
+                }
+                None => {
+                    self.bundles.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<'a> OscPacketRef<'a> {
+    /// Copies the borrowed data into an owned [`OscPacket`].
+    pub fn to_owned(&self) -> OscPacket {
+        match *self {
+            OscPacketRef::Message(ref msg) => OscPacket::Message(msg.to_owned()),
+            OscPacketRef::Bundle(ref bundle) => OscPacket::Bundle(bundle.to_owned()),
+        }
+    }
+}
+
+impl<'a> OscMessageRef<'a> {
+    /// Creates a message from an address that is either borrowed or owned, which can be
+    /// encoded with [`encode_ref`](crate::encoder::encode_ref).
+    ///
+    /// Sending from a `&'static str` address this way doesn't allocate a `String` for it, unlike
+    /// building an [`OscMessage`]. `OscMessage::addr` stays a `String`, so messages that are
+    /// decoded, matched or stored don't carry a lifetime, and existing code keeps compiling.
+    pub fn new<A: Into<Cow<'a, str>>>(addr: A, args: Vec<OscTypeRef<'a>>) -> Self {
+        OscMessageRef {
+            addr: addr.into(),
+            args,
+        }
+    }
+
+    /// Copies the borrowed data into an owned [`OscMessage`].
+    pub fn to_owned(&self) -> OscMessage {
+        OscMessage {
+            addr: self.addr.to_string(),
+            args: self.args.iter().map(OscTypeRef::to_owned).collect(),
+        }
+    }
+}
+
+impl<'a> OscBundleRef<'a> {
+    /// Returns whether the contents of the bundle are meant to be applied immediately, see
+    /// [`OscTime::is_immediate`].
+    pub fn is_immediate(&self) -> bool {
+        self.timetag.is_immediate()
+    }
+
+    /// Copies the borrowed data into an owned [`OscBundle`].
+    pub fn to_owned(&self) -> OscBundle {
+        OscBundle {
+            timetag: self.timetag,
+            content: self.content.iter().map(OscPacketRef::to_owned).collect(),
+        }
+    }
+}
+
+impl<'a> OscArrayRef<'a> {
+    /// Copies the borrowed data into an owned [`OscArray`].
+    pub fn to_owned(&self) -> OscArray {
+        OscArray {
+            content: self.content.iter().map(OscTypeRef::to_owned).collect(),
+        }
+    }
+}
+
+impl<'a> From<OscPacketRef<'a>> for OscPacket {
+    fn from(packet: OscPacketRef<'a>) -> OscPacket {
+        packet.to_owned()
+    }
+}
+
+impl<'a> From<OscMessageRef<'a>> for OscMessage {
+    fn from(msg: OscMessageRef<'a>) -> OscMessage {
+        msg.to_owned()
+    }
+}
+
+impl<'a> From<OscBundleRef<'a>> for OscBundle {
+    fn from(bundle: OscBundleRef<'a>) -> OscBundle {
+        bundle.to_owned()
+    }
+}
+
+impl<'a> From<OscTypeRef<'a>> for OscType {
+    fn from(arg: OscTypeRef<'a>) -> OscType {
+        arg.to_owned()
+    }
+}
+
 pub type Result<T> = result::Result<T, errors::OscError>;
 
 impl From<String> for OscMessage {
@@ -293,7 +2000,7 @@ impl From<String> for OscMessage {
         }
     }
 }
-impl<'a> From<&'a str> for OscMessage {
+impl From<&str> for OscMessage {
     fn from(s: &str) -> OscMessage {
         OscMessage {
             addr: s.to_string(),
@@ -329,7 +2036,7 @@ mod tests {
     fn osc_times_can_be_converted_to_and_from_system_times() {
         let mut times = vec![];
         // Sweep across a few numbers to check for tolerance
-        for seconds in vec![
+        for seconds in [
             // We don't start at zero because times before the UNIX_EPOCH cannot be converted to
             // OscTime.
             OscTime::UNIX_OFFSET as u32,
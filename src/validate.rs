@@ -0,0 +1,296 @@
+use crate::address::{verify_address_pattern, AddressError};
+use crate::decoder::{MAX_ARRAY_DEPTH, MAX_BUNDLE_DEPTH};
+use crate::encoder::MAX_BLOB_SIZE;
+use crate::types::{OscBundle, OscMessage, OscPacket, OscTime, OscType};
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The rules checked by [`OscPacket::validate`] and the methods of the same name on
+/// [`OscMessage`] and [`OscBundle`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationProfile {
+    /// Largest blob allowed. Defaults to [`MAX_BLOB_SIZE`], the largest one that can be encoded.
+    pub max_blob_size: usize,
+    /// Deepest nesting of arrays allowed. Defaults to [`MAX_ARRAY_DEPTH`], the deepest the
+    /// decoder accepts.
+    pub max_array_depth: usize,
+    /// Deepest nesting of bundles allowed, where a bundle that contains only messages has a
+    /// depth of one. Defaults to [`MAX_BUNDLE_DEPTH`], the deepest the decoder accepts.
+    pub max_bundle_depth: usize,
+    /// Reject floats and doubles that are infinite or NaN, which some receivers can't handle.
+    /// Defaults to `false`.
+    pub reject_non_finite: bool,
+    /// Reject bundles containing a bundle with an earlier time tag, which the OSC specification
+    /// forbids. Immediate bundles take effect at the time of the bundle enclosing them, like
+    /// [`DecoderOptions::enforce_bundle_time_monotonicity`](crate::decoder::DecoderOptions)
+    /// checks when decoding. Defaults to `false`.
+    pub enforce_bundle_time_monotonicity: bool,
+}
+
+impl Default for ValidationProfile {
+    fn default() -> Self {
+        ValidationProfile {
+            max_blob_size: MAX_BLOB_SIZE,
+            max_array_depth: MAX_ARRAY_DEPTH,
+            max_bundle_depth: MAX_BUNDLE_DEPTH,
+            reject_non_finite: false,
+            enforce_bundle_time_monotonicity: false,
+        }
+    }
+}
+
+/// A problem found by validating a packet, along with where it was found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationIssue {
+    /// Indices of the bundle elements leading to the message or bundle with the problem, outermost
+    /// first. Empty if the problem is with the packet itself.
+    pub element: Vec<usize>,
+    /// Index of the argument with the problem, counting the arguments inside arrays like the
+    /// type tags do, i.e. at their position in the flattened list. `None` if the problem is not
+    /// with an argument.
+    pub arg_index: Option<usize>,
+    pub problem: ValidationProblem,
+}
+
+/// The kinds of problems found by validating a packet.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ValidationProblem {
+    /// The address pattern of a message is not valid.
+    InvalidAddress(AddressError),
+    /// A string argument contains a NUL character, which would terminate it early.
+    NulInString,
+    /// A char argument is not ASCII, which the spec requires.
+    NonAsciiChar(char),
+    /// A blob argument exceeds [`ValidationProfile::max_blob_size`].
+    BlobTooLarge { size: usize, max: usize },
+    /// Arrays are nested deeper than [`ValidationProfile::max_array_depth`].
+    ArrayTooDeep { max: usize },
+    /// Bundles are nested deeper than [`ValidationProfile::max_bundle_depth`].
+    BundleTooDeep { max: usize },
+    /// A float or double argument is infinite or NaN, see
+    /// [`ValidationProfile::reject_non_finite`].
+    NonFiniteFloat,
+    /// A bundle has the time tag `timetag`, which is earlier than the time tag `parent` the
+    /// enclosing bundle takes effect at, see
+    /// [`ValidationProfile::enforce_bundle_time_monotonicity`].
+    BundleTimeNotMonotonic { timetag: OscTime, parent: OscTime },
+}
+
+impl fmt::Display for ValidationProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationProblem::InvalidAddress(err) => write!(f, "{}", err),
+            ValidationProblem::NulInString => write!(f, "string contains a NUL character"),
+            ValidationProblem::NonAsciiChar(c) => write!(f, "char {:?} is not ASCII", c),
+            ValidationProblem::BlobTooLarge { size, max } => {
+                write!(f, "blob of {} bytes exceeds the limit of {} bytes", size, max)
+            }
+            ValidationProblem::ArrayTooDeep { max } => {
+                write!(f, "arrays are nested deeper than {} levels", max)
+            }
+            ValidationProblem::BundleTooDeep { max } => {
+                write!(f, "bundles are nested deeper than {} levels", max)
+            }
+            ValidationProblem::NonFiniteFloat => write!(f, "float is infinite or NaN"),
+            ValidationProblem::BundleTimeNotMonotonic { timetag, parent } => write!(
+                f,
+                "time tag {}.{} is earlier than {}.{} of the enclosing bundle",
+                timetag.seconds, timetag.fractional, parent.seconds, parent.fractional
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for index in &self.element {
+            write!(f, "element {}: ", index)?;
+        }
+        if let Some(index) = self.arg_index {
+            write!(f, "argument {}: ", index)?;
+        }
+        write!(f, "{}", self.problem)
+    }
+}
+
+impl OscPacket {
+    /// Checks the packet against the rules of `profile`, returning every problem found.
+    ///
+    /// Encoding strictly amounts to validating first:
+    ///
+    /// ```
+    /// use rosc::validate::{ValidationProblem, ValidationProfile};
+    /// use rosc::{encoder, OscMessage, OscPacket, OscType};
+    ///
+    /// let profile = ValidationProfile {
+    ///     reject_non_finite: true,
+    ///     ..Default::default()
+    /// };
+    /// let packet = OscPacket::Message(OscMessage {
+    ///     addr: "/gain".to_string(),
+    ///     args: vec![OscType::Float(f32::NAN)],
+    /// });
+    /// let issues = packet.validate(&profile).unwrap_err();
+    /// assert_eq!(issues[0].arg_index, Some(0));
+    /// assert_eq!(issues[0].problem, ValidationProblem::NonFiniteFloat);
+    ///
+    /// let packet = OscPacket::Message(OscMessage {
+    ///     addr: "/gain".to_string(),
+    ///     args: vec![OscType::Float(0.5)],
+    /// });
+    /// packet.validate(&profile).unwrap();
+    /// encoder::encode(&packet).unwrap();
+    /// ```
+    pub fn validate(&self, profile: &ValidationProfile) -> Result<(), Vec<ValidationIssue>> {
+        let mut validator = Validator {
+            profile,
+            element: Vec::new(),
+            issues: Vec::new(),
+        };
+        validator.packet(self, 0, OscTime::IMMEDIATE);
+        validator.finish()
+    }
+}
+
+impl OscMessage {
+    /// Checks the message against the rules of `profile`, see [`OscPacket::validate`].
+    pub fn validate(&self, profile: &ValidationProfile) -> Result<(), Vec<ValidationIssue>> {
+        let mut validator = Validator {
+            profile,
+            element: Vec::new(),
+            issues: Vec::new(),
+        };
+        validator.message(self);
+        validator.finish()
+    }
+}
+
+impl OscBundle {
+    /// Checks the bundle and everything it contains against the rules of `profile`, see
+    /// [`OscPacket::validate`].
+    pub fn validate(&self, profile: &ValidationProfile) -> Result<(), Vec<ValidationIssue>> {
+        let mut validator = Validator {
+            profile,
+            element: Vec::new(),
+            issues: Vec::new(),
+        };
+        validator.bundle(self, 0, OscTime::IMMEDIATE);
+        validator.finish()
+    }
+}
+
+struct Validator<'a> {
+    profile: &'a ValidationProfile,
+    // Indices of the bundle elements leading to the packet currently being validated
+    element: Vec<usize>,
+    issues: Vec<ValidationIssue>,
+}
+
+impl Validator<'_> {
+    fn report(&mut self, arg_index: Option<usize>, problem: ValidationProblem) {
+        self.issues.push(ValidationIssue {
+            element: self.element.clone(),
+            arg_index,
+            problem,
+        });
+    }
+
+    fn finish(self) -> Result<(), Vec<ValidationIssue>> {
+        match self.issues.is_empty() {
+            true => Ok(()),
+            false => Err(self.issues),
+        }
+    }
+
+    /// Validates a packet found inside `depth` bundles, which take effect at `timetag`.
+    fn packet(&mut self, packet: &OscPacket, depth: usize, timetag: OscTime) {
+        match packet {
+            OscPacket::Message(msg) => self.message(msg),
+            OscPacket::Bundle(bundle) => self.bundle(bundle, depth, timetag),
+        }
+    }
+
+    fn bundle(&mut self, bundle: &OscBundle, depth: usize, parent: OscTime) {
+        if depth == self.profile.max_bundle_depth {
+            let max = self.profile.max_bundle_depth;
+            return self.report(None, ValidationProblem::BundleTooDeep { max });
+        }
+        // Immediate bundles take effect at the time of the enclosing bundle
+        let timetag = match bundle.timetag.is_immediate() {
+            true => parent,
+            false => bundle.timetag,
+        };
+        if self.profile.enforce_bundle_time_monotonicity
+            && !parent.is_immediate()
+            && timetag < parent
+        {
+            let problem = ValidationProblem::BundleTimeNotMonotonic {
+                timetag: bundle.timetag,
+                parent,
+            };
+            self.report(None, problem);
+        }
+        for (index, packet) in bundle.content.iter().enumerate() {
+            self.element.push(index);
+            self.packet(packet, depth + 1, timetag);
+            self.element.pop();
+        }
+    }
+
+    fn message(&mut self, msg: &OscMessage) {
+        if let Err(err) = verify_address_pattern(&msg.addr) {
+            self.report(None, ValidationProblem::InvalidAddress(err));
+        }
+        let mut index = 0;
+        self.args(&msg.args, 0, &mut index);
+    }
+
+    /// Validates arguments nested in `depth` arrays, `index` is the flattened index of the first.
+    fn args(&mut self, args: &[OscType], depth: usize, index: &mut usize) {
+        for arg in args {
+            let problem = match arg {
+                OscType::String(s) if s.contains('\0') => Some(ValidationProblem::NulInString),
+                OscType::Char(c) if !c.is_ascii() => Some(ValidationProblem::NonAsciiChar(*c)),
+                OscType::Blob(blob) if blob.len() > self.profile.max_blob_size => {
+                    Some(ValidationProblem::BlobTooLarge {
+                        size: blob.len(),
+                        max: self.profile.max_blob_size,
+                    })
+                }
+                OscType::Float(x) if self.profile.reject_non_finite && !x.is_finite() => {
+                    Some(ValidationProblem::NonFiniteFloat)
+                }
+                OscType::Double(x) if self.profile.reject_non_finite && !x.is_finite() => {
+                    Some(ValidationProblem::NonFiniteFloat)
+                }
+                OscType::Array(array) => {
+                    if depth == self.profile.max_array_depth {
+                        let max = self.profile.max_array_depth;
+                        self.report(Some(*index), ValidationProblem::ArrayTooDeep { max });
+                        *index += flattened_len(&array.content);
+                    } else {
+                        self.args(&array.content, depth + 1, index);
+                    }
+                    continue;
+                }
+                _ => None,
+            };
+            if let Some(problem) = problem {
+                self.report(Some(*index), problem);
+            }
+            *index += 1;
+        }
+    }
+}
+
+/// Returns the number of arguments in `args`, counting those inside arrays.
+fn flattened_len(args: &[OscType]) -> usize {
+    args.iter()
+        .map(|arg| match arg {
+            OscType::Array(array) => flattened_len(&array.content),
+            _ => 1,
+        })
+        .sum()
+}
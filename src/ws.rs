@@ -0,0 +1,34 @@
+use crate::decoder::{self, DecoderOptions};
+use crate::encoder;
+use crate::types::{OscPacket, Result};
+
+use crate::alloc::vec::Vec;
+
+/// Encodes `packet` as the payload of a binary WebSocket message.
+///
+/// OSC-over-WebSocket bridges carry exactly one packet in each binary message, without the
+/// framing used for other streams, so the payload is just the encoded packet.
+///
+/// # Example
+///
+/// ```
+/// use rosc::{ws, OscMessage, OscPacket};
+///
+/// let packet = OscPacket::Message(OscMessage::from("/fader/1"));
+/// let payload = ws::to_ws_binary(&packet).unwrap();
+/// assert_eq!(ws::from_ws_binary(&payload).unwrap(), packet);
+/// ```
+pub fn to_ws_binary(packet: &OscPacket) -> Result<Vec<u8>> {
+    Ok(encoder::encode(packet)?)
+}
+
+/// Decodes the payload of a binary WebSocket message, which must contain exactly one packet.
+pub fn from_ws_binary(payload: &[u8]) -> Result<OscPacket> {
+    from_ws_binary_with_options(payload, &DecoderOptions::default())
+}
+
+/// Decodes the payload of a binary WebSocket message like [`from_ws_binary`], using the given
+/// options.
+pub fn from_ws_binary_with_options(payload: &[u8], options: &DecoderOptions) -> Result<OscPacket> {
+    Ok(decoder::decode_with_options(payload, options)?)
+}
@@ -1,11 +1,14 @@
 extern crate rosc;
 
-#[cfg(feature = "std")]
-use rosc::address::{Matcher, verify_address};
-use rosc::address::verify_address_pattern;
+use rosc::address::{
+    format_part_u32, AddressError, AddressIndex, AddressParts, AddressTemplate, Matcher,
+    MatcherOptions, Params, PatternToken, tokenize_pattern, verify_address, verify_address_pattern,
+    verify_address_pattern_with_max_len, verify_address_with_max_len, DEFAULT_MAX_ADDRESS_LEN,
+};
+use rosc::{OscError, OscMessage};
 
-#[cfg(feature = "std")]
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_matcher() {
     let mut matcher;
 
@@ -35,7 +38,13 @@ fn test_matcher() {
     assert_eq!(matcher.match_address("/oscillator/9").expect("Valid address pattern"), true);  // Last member of range included
 
     // Inverted order should fail
-    Matcher::new("/oscillator/[9-0]").expect_err("Inverted range accepted");
+    assert_eq!(
+        Matcher::new("/oscillator/[9-0]").unwrap_err(),
+        AddressError::InvalidPattern {
+            input: "/oscillator/[9-0]".to_string(),
+            position: 11
+        }
+    );
 
     // Multiple ranges
     matcher = Matcher::new("/oscillator/[a-zA-Z0-9]").expect("Should be valid");
@@ -137,25 +146,75 @@ fn test_matcher() {
     assert_eq!(matcher.match_address("/a").expect("Valid address pattern"), true);
 }
 
-#[cfg(feature = "std")]
+#[test]
+fn test_matcher_root() {
+    // A single slash is the root container, which only matches itself
+    let root = Matcher::new("/").expect("Should be valid");
+    assert!(root.match_address("/").expect("Valid address"));
+    assert!(!root.match_address("/x").expect("Valid address"));
+    assert!(root.match_address("//").is_err());
+
+    let matcher = Matcher::new("/x").expect("Should be valid");
+    assert!(matcher.match_address("/x").expect("Valid address"));
+    assert!(!matcher.match_address("/").expect("Valid address"));
+    // Wildcards don't match the empty name of the root container
+    let matcher = Matcher::new("/*").expect("Should be valid");
+    assert!(!matcher.match_address("/").expect("Valid address"));
+    assert!(matcher.match_address("/x").expect("Valid address"));
+
+    // Only a single slash is the root container
+    for &(pattern, position) in &[("//", 0), ("////", 0), ("/x/", 2)] {
+        assert_eq!(
+            Matcher::new(pattern).unwrap_err(),
+            AddressError::InvalidPattern {
+                input: pattern.to_string(),
+                position,
+            }
+        );
+    }
+    verify_address("/").expect("Should be valid");
+    verify_address_pattern("/").expect("Should be valid");
+    verify_address("//").expect_err("Should not be valid");
+    verify_address_pattern("////").expect_err("Should not be valid");
+
+    // Without a prefix, the root container can't be addressed at all
+    let options = MatcherOptions {
+        allow_unprefixed: true,
+        ..Default::default()
+    };
+    Matcher::with_options("", &options).expect_err("Should not be valid");
+    let root = Matcher::with_options("/", &options).expect("Should be valid");
+    assert!(root.match_address("/").expect("Valid address"));
+}
+
 #[test]
 fn test_verify_address() {
     verify_address("/test").expect("Should be valid");
     verify_address("/oscillator/1/frequency").expect("Should be valid");
     verify_address("/!\"$%&'()+-.0123456789:;<=>@ABCDEFGHIJKLMNOPQRSTUVWXYZ^_`abcdefghijklmnopqrstuvwxyz|~/foo").expect("Should be valid");
 
+    let invalid = |input: &str, position| {
+        Err(AddressError::InvalidAddress {
+            input: input.to_string(),
+            position,
+        })
+    };
     // No '/' at beginning
-    verify_address("test").expect_err("Should not be valid");
+    assert_eq!(verify_address("test"), invalid("test", 0));
     // '/' at the end
-    verify_address("/test/").expect_err("Should not be valid");
+    assert_eq!(verify_address("/test/"), invalid("/test/", 5));
     // Different address pattern elements that are not allowed in regular addresses
-    verify_address("/test*").expect_err("Should not be valid");
-    verify_address("/test?").expect_err("Should not be valid");
-    verify_address("/test{foo,bar}").expect_err("Should not be valid");
-    verify_address("/test[a-z]").expect_err("Should not be valid");
+    assert_eq!(verify_address("/test*"), invalid("/test*", 5));
+    assert_eq!(verify_address("/test?"), invalid("/test?", 5));
+    assert_eq!(verify_address("/test{foo,bar}"), invalid("/test{foo,bar}", 5));
+    assert_eq!(verify_address("/test[a-z]"), invalid("/test[a-z]", 5));
+
+    assert_eq!(
+        OscError::from(verify_address("test").unwrap_err()),
+        OscError::BadAddress("Invalid address 'test'".to_string())
+    );
 }
 
-#[cfg(feature = "std")]
 #[test]
 fn test_verify_address_pattern() {
     verify_address_pattern("/test").expect("Should be valid");
@@ -163,7 +222,13 @@ fn test_verify_address_pattern() {
     verify_address_pattern("/!\"$%&'()+-.0123456789:;<=>@ABCDEFGHIJKLMNOPQRSTUVWXYZ^_`abcdefghijklmnopqrstuvwxyz|~/foo").expect("Should be valid");
 
     // No '/' at beginning
-    verify_address_pattern("test").expect_err("Should not be valid");
+    assert_eq!(
+        verify_address_pattern("test"),
+        Err(AddressError::InvalidPattern {
+            input: "test".to_string(),
+            position: 0
+        })
+    );
     // '/' at the end
     verify_address_pattern("/test/").expect_err("Should not be valid");
 
@@ -203,3 +268,608 @@ fn test_verify_address_pattern() {
     verify_address_pattern("/{foo").expect_err("Should not be valid");
     verify_address_pattern("/foo{,").expect_err("Should not be valid");
 }
+
+#[test]
+fn test_matcher_unprefixed() {
+    let options = MatcherOptions {
+        allow_unprefixed: true,
+        ..Default::default()
+    };
+    let matcher = Matcher::with_options("status/*", &options).expect("Should be valid");
+    assert!(matcher.match_address("status/ping").unwrap());
+    assert!(!matcher.match_address("status").unwrap());
+    matcher.match_address("/status/ping").expect_err("Address has a leading slash");
+    matcher.match_address("status//ping").expect_err("Empty part");
+
+    let matcher = Matcher::with_options("/status", &options).expect("Should be valid");
+    assert!(matcher.match_address("/status").unwrap());
+    matcher.match_address("status").expect_err("Address lacks the leading slash");
+
+    Matcher::new("status").expect_err("Unprefixed patterns are rejected by default");
+    Matcher::with_options("", &options).expect_err("Should not be valid");
+    // Positions refer to the pattern without the leading '/'
+    assert_eq!(
+        Matcher::with_options("status/{ping", &options).unwrap_err(),
+        AddressError::InvalidPattern {
+            input: "status/{ping".to_string(),
+            position: 6
+        }
+    );
+}
+
+#[test]
+fn test_address_errors_include_truncated_input() {
+    let err = Matcher::new("/mixer/[9-0]").unwrap_err();
+    assert_eq!(err.to_string(), "invalid OSC address pattern '/mixer/[9-0]' at byte 6");
+
+    let pattern = format!("/{}[", "a".repeat(1_000_000));
+    let unlimited = MatcherOptions {
+        max_address_len: usize::MAX,
+        ..Default::default()
+    };
+    match Matcher::with_options(&pattern, &unlimited).unwrap_err() {
+        AddressError::InvalidPattern { input, .. } => {
+            assert_eq!(input, format!("/{}...", "a".repeat(63)));
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    match Matcher::new(&pattern).unwrap_err() {
+        AddressError::TooLong { input, len, .. } => {
+            assert_eq!(input, format!("/{}...", "a".repeat(63)));
+            assert_eq!(len, pattern.len());
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    match verify_address(&"/\u{e4}".repeat(100)).unwrap_err() {
+        AddressError::InvalidAddress { input, .. } => {
+            // Truncated to 64 chars rather than bytes
+            assert_eq!(input, format!("{}...", "/\u{e4}".repeat(32)));
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+/// Addresses along with their parts, or the position at which they stop being valid.
+const ADDRESSES: &[(&str, Result<&[&str], usize>)] = &[
+    ("/", Ok(&[])),
+    ("/a", Ok(&["a"])),
+    ("/mixer/1/gain", Ok(&["mixer", "1", "gain"])),
+    ("/!\"$%&'()+-.:;<=>@^_`|~", Ok(&["!\"$%&'()+-.:;<=>@^_`|~"])),
+    ("", Err(0)),
+    ("a/b", Err(0)),
+    ("//", Err(0)),
+    ("/#", Err(0)),
+    ("/a/", Err(2)),
+    ("/a//b", Err(2)),
+    ("/a/#b", Err(2)),
+    ("/a#b", Err(2)),
+    ("/mixer/1#/gain", Err(8)),
+    ("/a b", Err(2)),
+    ("/a,b", Err(2)),
+    ("/a*", Err(2)),
+    ("/a?", Err(2)),
+    ("/a/[b]", Err(2)),
+    ("/a/b{c,d}", Err(4)),
+    ("/a\tb", Err(2)),
+    ("/a\0", Err(2)),
+    ("/\u{e4}", Err(0)),
+    ("/a\u{e4}", Err(2)),
+];
+
+#[test]
+fn test_address_parts() {
+    for &(addr, expected) in ADDRESSES {
+        let parts = AddressParts::new(addr).map(|parts| parts.collect::<Vec<_>>());
+        match expected {
+            Ok(expected) => {
+                assert_eq!(parts.unwrap(), expected, "{:?}", addr);
+                assert!(verify_address(addr).is_ok(), "{:?}", addr);
+            }
+            Err(position) => {
+                assert!(matches!(parts, Err(OscError::BadAddress(_))), "{:?}", addr);
+                assert_eq!(
+                    verify_address(addr),
+                    Err(AddressError::InvalidAddress {
+                        input: addr.to_string(),
+                        position
+                    }),
+                    "{:?}",
+                    addr
+                );
+            }
+        }
+    }
+
+    let mut parts = AddressParts::new("/a/b").unwrap();
+    assert_eq!(parts.next(), Some("a"));
+    assert_eq!(parts.clone().collect::<Vec<_>>(), vec!["b"]);
+    assert_eq!(parts.next(), Some("b"));
+    assert_eq!(parts.next(), None);
+    assert_eq!(parts.next(), None);
+
+    let unprefixed = |addr| AddressParts::unprefixed(addr).map(|parts| parts.collect::<Vec<_>>());
+    assert_eq!(unprefixed("a").unwrap(), vec!["a"]);
+    assert_eq!(unprefixed("status/ping").unwrap(), vec!["status", "ping"]);
+    for addr in ["", "/a", "/", "a/", "a//b", "a#"] {
+        assert!(unprefixed(addr).is_err(), "{:?}", addr);
+    }
+}
+
+#[test]
+fn test_address_validity_is_shared() {
+    let any = Matcher::any();
+    let root = Matcher::new("/").unwrap();
+    let single = Matcher::new("/*").unwrap();
+    for &(addr, expected) in ADDRESSES {
+        let valid = expected.is_ok();
+        assert_eq!(AddressParts::new(addr).is_ok(), valid, "{:?}", addr);
+        assert_eq!(verify_address(addr).is_ok(), valid, "{:?}", addr);
+        // Addresses are valid patterns as well
+        if valid {
+            assert!(verify_address_pattern(addr).is_ok(), "{:?}", addr);
+        }
+        assert_eq!(OscMessage::from(addr).addr_parts().is_ok(), valid, "{:?}", addr);
+        for matcher in [&any, &root, &single] {
+            let matched = matcher.match_address(addr);
+            assert_eq!(matched.is_ok(), valid, "{} {:?}", matcher.pattern, addr);
+            assert_eq!(matched.unwrap_or(false), matcher.filter([addr]) == [addr], "{:?}", addr);
+        }
+        assert_eq!(AddressIndex::new().insert(addr).is_ok(), valid, "{:?}", addr);
+        assert_eq!(any.filter([addr]).len(), valid as usize, "{:?}", addr);
+        match AddressTemplate::new(addr) {
+            Ok(template) => assert!(valid && template.render(&Params::new()).is_ok(), "{:?}", addr),
+            Err(_) => assert!(!valid, "{:?}", addr),
+        }
+        let unprefixed = MatcherOptions {
+            allow_unprefixed: true,
+            ..Default::default()
+        };
+        let matcher = Matcher::with_options("*", &unprefixed).unwrap();
+        if let Some(unprefixed) = addr.strip_prefix('/').filter(|addr| !addr.is_empty()) {
+            assert_eq!(matcher.match_address(unprefixed).is_ok(), valid, "{:?}", addr);
+            assert_eq!(AddressParts::unprefixed(unprefixed).is_ok(), valid, "{:?}", addr);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_address_validity_is_shared_by_dispatch() {
+    use rosc::dispatch::{AddressSpace, RouteTable};
+
+    for &(addr, expected) in ADDRESSES {
+        let valid = expected.is_ok();
+        let mut space = AddressSpace::new();
+        assert_eq!(space.add_method(addr, |_| {}).is_ok(), valid, "{:?}", addr);
+        assert_eq!(space.to_oscquery_json_at(addr).is_some(), valid, "{:?}", addr);
+        assert_eq!(RouteTable::builder().add_method(addr, |_| {}).is_ok(), valid, "{:?}", addr);
+    }
+}
+
+#[test]
+fn test_match_address_bytes() {
+    let matcher = Matcher::new("/oscillator/[0-9]/{frequency,phase}").unwrap();
+    for address in ["/oscillator/1/frequency", "/oscillator/8/phase", "/oscillator/4/detune"] {
+        assert_eq!(
+            matcher.match_address_bytes(address.as_bytes()),
+            matcher.match_address(address)
+        );
+    }
+    assert_eq!(
+        matcher.match_address_bytes(b"/oscillator/1/fr#quency"),
+        matcher.match_address("/oscillator/1/fr#quency")
+    );
+    assert_eq!(
+        matcher.match_address_bytes(b"/oscillator/\xff/phase").unwrap_err(),
+        AddressError::InvalidAddress {
+            input: "/oscillator/\u{fffd}/phase".to_string(),
+            position: 12
+        }
+    );
+}
+
+#[test]
+fn test_tokenize_pattern() {
+    let literal = |s: &str| PatternToken::Literal(s.to_string());
+    assert_eq!(tokenize_pattern("/").unwrap(), Vec::<Vec<PatternToken>>::new());
+    assert_eq!(
+        tokenize_pattern("/osc*??/[a-z!]x/{foo,bar}?").unwrap(),
+        vec![
+            vec![
+                literal("osc"),
+                PatternToken::Wildcard,
+                PatternToken::SingleChar,
+                PatternToken::SingleChar,
+            ],
+            vec![
+                PatternToken::Class {
+                    negated: false,
+                    spec: "a-z!".to_string(),
+                },
+                literal("x"),
+            ],
+            vec![
+                PatternToken::Alternation(vec!["foo".to_string(), "bar".to_string()]),
+                PatternToken::SingleChar,
+            ],
+        ]
+    );
+    assert_eq!(
+        tokenize_pattern("/[!!0-9]").unwrap(),
+        vec![vec![PatternToken::Class {
+            negated: true,
+            spec: "!0-9".to_string(),
+        }]]
+    );
+    assert_eq!(
+        tokenize_pattern("/a//b").unwrap_err(),
+        OscError::BadAddress("Invalid address pattern '/a//b'".to_string())
+    );
+
+    // Tokens that don't form a valid pattern are rejected
+    for parts in &[
+        vec![vec![literal("")]],
+        vec![vec![literal("a/b")]],
+        vec![vec![literal("a")], vec![]],
+        vec![vec![PatternToken::Alternation(vec![])]],
+        vec![vec![PatternToken::Class {
+            negated: false,
+            spec: "b-a".to_string(),
+        }]],
+    ] {
+        Matcher::from_tokens(parts).expect_err("Should not be valid");
+    }
+}
+
+#[test]
+fn test_matcher_from_tokens() {
+    let patterns = [
+        "/",
+        "/oscillator/1/frequency",
+        "/oscillator/[0-9]/*",
+        "/oscillator/[!0-9]*",
+        "/osc*??/{frequency,phase}",
+        "/*/*tor/*?",
+        "/?sc?llator/*[0-9]",
+        "/**/1/*",
+        "/os?*",
+    ];
+    let addresses = [
+        "/",
+        "/oscillator",
+        "/oscillator/1",
+        "/oscillator/a",
+        "/oscillator/1/frequency",
+        "/oscillator/12/phase",
+        "/osc/frequency",
+        "/oscx/phase",
+        "/oscxy/phase",
+        "/mixer/1/gain",
+    ];
+    for pattern in &patterns {
+        let parsed = Matcher::new(pattern).unwrap();
+        let tokens = tokenize_pattern(pattern).unwrap();
+        let built = Matcher::from_tokens(&tokens).unwrap();
+        assert_eq!(built.pattern, *pattern);
+        for address in &addresses {
+            assert_eq!(
+                built.match_address(address).unwrap(),
+                parsed.match_address(address).unwrap(),
+                "{} matching {}",
+                pattern,
+                address
+            );
+        }
+    }
+}
+
+#[test]
+fn test_matcher_requirements() {
+    let requirements = |pattern: &str| {
+        let matcher = Matcher::new(pattern).unwrap();
+        (matcher.part_count(), matcher.literal_prefix().to_string())
+    };
+    // Fully literal
+    assert_eq!(requirements("/mixer/1/gain"), (3, "/mixer/1/gain".to_string()));
+    assert_eq!(requirements("/"), (0, "/".to_string()));
+    // Starting with a wildcard
+    assert_eq!(requirements("/*/gain"), (2, "".to_string()));
+    assert_eq!(requirements("/[ab]c/gain"), (2, "".to_string()));
+    // Wildcards after literal parts, which aren't part of the prefix
+    assert_eq!(requirements("/mixer/1?/gain"), (3, "/mixer".to_string()));
+    assert_eq!(requirements("/mixer/{1,2}"), (2, "/mixer".to_string()));
+    assert_eq!(requirements("/mixer/channel*"), (2, "/mixer".to_string()));
+
+    let unprefixed = |pattern: &str| {
+        let options = MatcherOptions {
+            allow_unprefixed: true,
+            ..Default::default()
+        };
+        let matcher = Matcher::with_options(pattern, &options).unwrap();
+        (matcher.part_count(), matcher.literal_prefix().to_string())
+    };
+    assert_eq!(unprefixed("mixer/*"), (2, "mixer".to_string()));
+    assert_eq!(unprefixed("mixer/1"), (2, "mixer/1".to_string()));
+    assert_eq!(unprefixed("*/gain"), (2, "".to_string()));
+
+    let matcher = Matcher::new("/mixer/*/gain").unwrap();
+    assert!(matcher.could_match_depth(3));
+    assert!(!matcher.could_match_depth(2));
+    assert!(!matcher.could_match_depth(4));
+    assert!(Matcher::new("/").unwrap().could_match_depth(0));
+    // The tree doesn't support `//` wildcards yet, so a pattern with an empty part is invalid
+    assert!(Matcher::new("/mixer//gain").is_err());
+}
+
+#[test]
+fn test_matcher_filter() {
+    let addresses = [
+        "/",
+        "/oscillator",
+        "/oscillator/1/frequency",
+        "/oscillator/12/phase",
+        "/oscillator/2/frequency",
+        "/osc/frequency",
+        "/mixer/1/gain",
+        "invalid",
+        "/oscillator/1/frequency/",
+    ];
+    let patterns = [
+        "/",
+        "/oscillator",
+        "/oscillator/*/frequency",
+        "/oscillator/?/*",
+        "/osc*/frequency",
+        "/{mixer,osc}/*",
+        "/*/*/*",
+        "/[!o]*/1/gain",
+    ];
+    let index: AddressIndex = addresses.iter().copied().collect();
+    // The invalid addresses are skipped
+    assert_eq!(index.len(), addresses.len() - 2);
+    for pattern in &patterns {
+        let matcher = Matcher::new(pattern).unwrap();
+        let expected: Vec<&str> = addresses
+            .iter()
+            .copied()
+            .filter(|address| matcher.match_address(address).unwrap_or(false))
+            .collect();
+        assert_eq!(matcher.filter(addresses.iter().copied()), expected, "{}", pattern);
+        let mut sorted = expected.clone();
+        sorted.sort_unstable();
+        assert_eq!(index.matching(&matcher), sorted, "{}", pattern);
+    }
+
+    let unprefixed = Matcher::with_options(
+        "osc*",
+        &MatcherOptions {
+            allow_unprefixed: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(unprefixed.filter(vec!["oscillator", "/oscillator", "mixer"]), vec!["oscillator"]);
+}
+
+#[test]
+fn test_matcher_any() {
+    let any = Matcher::any();
+    assert!(any.is_any());
+    assert!(!Matcher::new("/*").unwrap().is_any());
+    assert_eq!(any.pattern, "//*");
+    for address in ["/", "/a", "/a/b/c", "/oscillator/1/frequency"] {
+        assert!(any.match_address(address).unwrap(), "{}", address);
+        assert!(any.match_address_bytes(address.as_bytes()).unwrap(), "{}", address);
+    }
+    for address in ["", "a", "/a/", "//", "/a/*"] {
+        assert!(any.match_address(address).is_err(), "{}", address);
+    }
+    assert!(!Matcher::new("/*").unwrap().match_address("/a/b/c").unwrap());
+    assert!(!Matcher::new("/*").unwrap().match_address("/").unwrap());
+
+    assert_eq!(any.part_count(), 0);
+    assert_eq!(any.literal_prefix(), "");
+    assert!((0..10).all(|depth| any.could_match_depth(depth)));
+
+    let addresses = ["/", "/a", "invalid", "/a/b/c", "/b/"];
+    assert_eq!(any.filter(addresses), vec!["/", "/a", "/a/b/c"]);
+    let index: AddressIndex = addresses.iter().copied().collect();
+    assert_eq!(index.matching(&any), vec!["/", "/a", "/a/b/c"]);
+    // `//` is not supported by `Matcher::new` yet
+    assert!(Matcher::new("//*").is_err());
+}
+
+/// Returns an address of `len` bytes with a single part.
+fn address_of_len(len: usize) -> String {
+    format!("/{}", "a".repeat(len - 1))
+}
+
+#[test]
+fn test_max_address_len() {
+    let at_limit = address_of_len(DEFAULT_MAX_ADDRESS_LEN);
+    let over_limit = address_of_len(DEFAULT_MAX_ADDRESS_LEN + 1);
+    assert!(verify_address(&at_limit).is_ok());
+    assert!(verify_address_pattern(&at_limit).is_ok());
+    let err = verify_address(&over_limit).unwrap_err();
+    assert!(matches!(err, AddressError::TooLong { len: 4097, max: 4096, .. }), "{:?}", err);
+    assert!(err.to_string().len() < 200, "{}", err);
+    assert_eq!(OscError::from(err), OscError::AddressTooLong { len: 4097, max: 4096 });
+    assert!(matches!(
+        verify_address_pattern(&over_limit),
+        Err(AddressError::TooLong { len: 4097, max: 4096, .. })
+    ));
+
+    assert!(verify_address_with_max_len("/abc", 4).is_ok());
+    assert!(verify_address_with_max_len("/abcd", 4).is_err());
+    assert!(verify_address_pattern_with_max_len("/a*", 3).is_ok());
+    assert!(verify_address_pattern_with_max_len("/a*c", 3).is_err());
+    // Invalid addresses within the limit are still invalid
+    assert!(matches!(
+        verify_address_with_max_len("/a/", 4),
+        Err(AddressError::InvalidAddress { .. })
+    ));
+    assert!(verify_address_with_max_len(&over_limit, usize::MAX).is_ok());
+}
+
+#[test]
+fn test_matcher_max_address_len() {
+    let at_limit = address_of_len(DEFAULT_MAX_ADDRESS_LEN);
+    let over_limit = address_of_len(DEFAULT_MAX_ADDRESS_LEN + 1);
+    let matcher = Matcher::new("/a*").unwrap();
+    assert!(matcher.match_address(&at_limit).unwrap());
+    assert!(matches!(
+        matcher.match_address(&over_limit),
+        Err(AddressError::TooLong { len: 4097, max: 4096, .. })
+    ));
+    assert!(matches!(
+        Matcher::any().match_address(&over_limit),
+        Err(AddressError::TooLong { .. })
+    ));
+    let addrs = vec![at_limit.as_str(), over_limit.as_str()];
+    assert_eq!(matcher.filter(addrs), vec![at_limit.as_str()]);
+    assert!(Matcher::new(&at_limit).unwrap().match_address(&at_limit).unwrap());
+    assert!(matches!(
+        Matcher::new(&over_limit),
+        Err(AddressError::TooLong { len: 4097, max: 4096, .. })
+    ));
+
+    let options = MatcherOptions {
+        max_address_len: 8,
+        ..Default::default()
+    };
+    let matcher = Matcher::with_options("/mixer/*", &options).unwrap();
+    assert!(matcher.match_address("/mixer/1").unwrap());
+    assert!(matches!(
+        matcher.match_address("/mixer/10"),
+        Err(AddressError::TooLong { len: 9, max: 8, .. })
+    ));
+    assert!(Matcher::with_options("/mixer/1*", &options).is_err());
+
+    // The limit can be raised above the default
+    let options = MatcherOptions {
+        max_address_len: DEFAULT_MAX_ADDRESS_LEN + 1,
+        ..Default::default()
+    };
+    let matcher = Matcher::with_options(&over_limit, &options).unwrap();
+    assert!(matcher.match_address(&over_limit).unwrap());
+}
+
+#[test]
+fn test_address_index() {
+    let mut index = AddressIndex::new();
+    assert!(index.is_empty());
+    assert_eq!(index.insert("/b"), Ok(true));
+    assert_eq!(index.insert("/a"), Ok(true));
+    assert_eq!(index.insert("/a".to_string()), Ok(false));
+    assert!(index.insert("/a/*").is_err());
+    assert_eq!(index.iter().collect::<Vec<_>>(), vec!["/a", "/b"]);
+    assert!(index.contains("/a"));
+    assert!(index.remove("/a"));
+    assert!(!index.remove("/a"));
+    assert!(!index.contains("/a"));
+    assert_eq!(index, vec!["/b", "/b"].into_iter().collect());
+}
+
+#[test]
+fn test_format_part_u32() {
+    assert_eq!(format_part_u32(0, 0), "0");
+    assert_eq!(format_part_u32(7, 3), "007");
+    assert_eq!(format_part_u32(1234, 2), "1234");
+    assert_eq!(format_part_u32(u32::MAX, 0), "4294967295");
+}
+
+#[test]
+fn test_address_template() {
+    let template = AddressTemplate::new("/ch/{ch:02}/mix/{bus}fader").unwrap();
+    assert_eq!(template.template(), "/ch/{ch:02}/mix/{bus}fader");
+    assert_eq!(template.to_string(), template.template());
+
+    let mut params = Params::new();
+    params.set("ch", 3).set("bus", "main");
+    let addr = template.render(&params).unwrap();
+    assert_eq!(addr, "/ch/03/mix/mainfader");
+    verify_address(&addr).unwrap();
+    assert_eq!(template.extract(&addr), Some(params.clone()));
+    // Rendered integers may also be set as strings
+    params.set("ch", "12");
+    assert_eq!(template.render(&params).unwrap(), "/ch/12/mix/mainfader");
+
+    let params = template.extract("/ch/123/mix/fxfader").unwrap();
+    assert_eq!(params.get_u32("ch"), Some(123));
+    assert_eq!(params.get("ch"), Some("123"));
+    assert_eq!(params.get("bus"), Some("fx"));
+    assert_eq!(params.get_u32("bus"), None);
+    assert_eq!(params.get("other"), None);
+    assert_eq!(params.iter().collect::<Vec<_>>(), vec![("ch", "123"), ("bus", "fx")]);
+    assert_eq!(params.len(), 2);
+
+    for addr in &[
+        "/ch/1/mix/mainfader",
+        "/ch/001/mix/mainfader",
+        "/ch/+1/mix/mainfader",
+        "/ch/ab/mix/mainfader",
+        "/ch/01/mix/fader",
+        "/ch/01/mix/a/fader",
+        "/ch/01/mix/mainfader/x",
+        "/ch/01/mix",
+    ] {
+        assert_eq!(template.extract(addr), None, "{}", addr);
+    }
+
+    // Values match the shortest way that lets the rest of the address match
+    let template = AddressTemplate::new("/{a}_{b}").unwrap();
+    let params = template.extract("/x_y_z").unwrap();
+    assert_eq!((params.get("a"), params.get("b")), (Some("x"), Some("y_z")));
+    let template = AddressTemplate::new("/plain/address").unwrap();
+    assert!(template.extract("/plain/address").unwrap().is_empty());
+}
+
+#[test]
+fn test_address_template_errors() {
+    for template in &[
+        "",
+        "ch/{ch}",
+        "/ch/{ch",
+        "/ch/{}",
+        "/ch/{ch:2}",
+        "/ch/{ch:0}",
+        "/ch/{ch:0x}",
+        "/ch/{c h}",
+        "/ch/{a}{b}",
+        "/{ch}/{ch}",
+        "/ch/{ch}/",
+        "/ch//{ch}",
+        "/ch/*/{ch}",
+    ] {
+        match AddressTemplate::new(template) {
+            Err(OscError::BadAddress(_)) => {}
+            other => panic!("{:?}: unexpected result {:?}", template, other),
+        }
+    }
+
+    let template = AddressTemplate::new("/ch/{ch:02}/{name}").unwrap();
+    let render = |ch: &str, name: &str| {
+        let mut params = Params::new();
+        params.set("ch", ch).set("name", name);
+        template.render(&params)
+    };
+    assert_eq!(render("5", "gain").unwrap(), "/ch/05/gain");
+    let invalid = [
+        ("x", "gain"),
+        ("-1", "gain"),
+        ("1", ""),
+        ("1", "a/b"),
+        ("1", "a b"),
+        ("1", "*"),
+    ];
+    for (ch, name) in &invalid {
+        match render(ch, name) {
+            Err(OscError::BadAddress(_)) => {}
+            other => panic!("{:?}: unexpected result {:?}", (ch, name), other),
+        }
+    }
+    let mut missing = Params::new();
+    missing.set("ch", 1);
+    assert!(matches!(template.render(&missing), Err(OscError::BadAddress(_))));
+}
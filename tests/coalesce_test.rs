@@ -0,0 +1,240 @@
+extern crate rosc;
+
+use rosc::coalesce::{self, Coalescer, CoalescerOptions};
+use rosc::{encoder, OscBundle, OscMessage, OscPacket, OscTime, OscType};
+#[cfg(feature = "std")]
+use rosc::coalesce::ThreadedCoalescer;
+#[cfg(feature = "std")]
+use std::sync::mpsc;
+use std::time::Duration;
+
+fn message(addr: &str, value: i32) -> OscMessage {
+    OscMessage {
+        addr: addr.to_string(),
+        args: vec![OscType::Int(value)],
+    }
+}
+
+fn addrs(bundle: &OscBundle) -> Vec<(String, i32)> {
+    bundle
+        .content
+        .iter()
+        .map(|packet| match packet {
+            OscPacket::Message(msg) => (msg.addr.clone(), msg.args[0].clone().int().unwrap()),
+            OscPacket::Bundle(_) => panic!("unexpected bundle"),
+        })
+        .collect()
+}
+
+#[test]
+fn test_size_triggered_flush() {
+    // Each message is 16 bytes plus 4 for its size within the bundle
+    let mut coalescer = Coalescer::new(CoalescerOptions {
+        interval: Duration::from_secs(60),
+        max_size: 16 + 3 * 20,
+        ..Default::default()
+    });
+    for i in 0..3 {
+        assert_eq!(coalescer.push(message("/abc", i)).unwrap(), None);
+    }
+    assert_eq!(coalescer.encoded_size(), 76);
+
+    let bundle = coalescer.push(message("/abc", 3)).unwrap().unwrap();
+    assert!(bundle.is_immediate());
+    assert_eq!(
+        addrs(&bundle),
+        vec![("/abc".into(), 0), ("/abc".into(), 1), ("/abc".into(), 2)]
+    );
+    assert_eq!(
+        encoder::encode(&OscPacket::Bundle(bundle)).unwrap().len(),
+        76
+    );
+    assert_eq!(coalescer.len(), 1);
+    assert_eq!(coalescer.encoded_size(), 36);
+}
+
+#[test]
+fn test_oversized_message_is_flushed_on_its_own() {
+    let mut coalescer = Coalescer::new(CoalescerOptions {
+        max_size: 32,
+        ..Default::default()
+    });
+    assert_eq!(coalescer.push(message("/abc", 0)).unwrap(), None);
+    let big = OscMessage {
+        addr: "/big".to_string(),
+        args: vec![OscType::Blob(vec![0; 64])],
+    };
+    assert_eq!(addrs(&coalescer.push(big).unwrap().unwrap()).len(), 1);
+    assert_eq!(coalescer.len(), 1);
+    assert_eq!(coalescer.flush().unwrap().content.len(), 1);
+    assert!(coalescer.is_empty());
+    assert_eq!(coalescer.flush(), None);
+}
+
+#[test]
+fn test_dedup_by_address() {
+    let mut coalescer = Coalescer::new(CoalescerOptions {
+        dedup_by_address: true,
+        ..Default::default()
+    });
+    coalescer.push(message("/a", 1)).unwrap();
+    coalescer.push(message("/b", 1)).unwrap();
+    coalescer.push(message("/a", 2)).unwrap();
+    coalescer.push(message("/a", 3)).unwrap();
+    assert_eq!(coalescer.len(), 2);
+    assert_eq!(coalescer.encoded_size(), 16 + 2 * 16);
+    assert_eq!(
+        addrs(&coalescer.flush().unwrap()),
+        vec![("/a".into(), 3), ("/b".into(), 1)]
+    );
+
+    // The window ends with the flush
+    coalescer.push(message("/a", 4)).unwrap();
+    assert_eq!(addrs(&coalescer.flush().unwrap()), vec![("/a".into(), 4)]);
+}
+
+#[test]
+fn test_flush_due() {
+    let mut coalescer = Coalescer::new(CoalescerOptions {
+        interval: Duration::from_millis(250),
+        ..Default::default()
+    });
+    assert_eq!(coalescer.flush_due((10, 0).into()), None);
+
+    coalescer.push(message("/a", 1)).unwrap();
+    let start = OscTime::from((20, 0));
+    assert_eq!(coalescer.flush_due(start), None);
+    assert_eq!(coalescer.flush_due((20, (1 << 30) - 1).into()), None);
+    coalescer.push(message("/b", 1)).unwrap();
+    let bundle = coalescer.flush_due((20, 1 << 30).into()).unwrap();
+    assert_eq!(bundle.content.len(), 2);
+
+    // The next interval starts with the next message
+    coalescer.push(message("/c", 1)).unwrap();
+    assert_eq!(coalescer.flush_due((30, 0).into()), None);
+    assert!(coalescer.flush_due((31, 0).into()).is_some());
+}
+
+#[test]
+fn test_pack() {
+    let messages = (0..7).map(|i| message("/abc", i));
+    let bundles: Vec<_> = coalesce::pack(messages, 16 + 3 * 20, (5, 0).into()).collect();
+    assert_eq!(bundles.len(), 3);
+    assert!(bundles.iter().all(|bundle| bundle.timetag == OscTime::from((5, 0))));
+    let values = |bundle: &OscBundle| -> Vec<i32> {
+        addrs(bundle).into_iter().map(|(_, value)| value).collect()
+    };
+    assert_eq!(values(&bundles[0]), vec![0, 1, 2]);
+    assert_eq!(values(&bundles[1]), vec![3, 4, 5]);
+    assert_eq!(values(&bundles[2]), vec![6]);
+    assert_eq!(encoder::encode(&OscPacket::Bundle(bundles[0].clone())).unwrap().len(), 76);
+
+    // A message that doesn't fit on its own gets a bundle of its own
+    let big = OscMessage {
+        addr: "/big".to_string(),
+        args: vec![OscType::Blob(vec![0; 64])],
+    };
+    let messages = vec![message("/abc", 0), big.clone(), message("/abc", 1)];
+    let bundles: Vec<_> = coalesce::pack(messages, 40, OscTime::IMMEDIATE).collect();
+    assert_eq!(bundles.len(), 3);
+    assert_eq!(bundles[1].content, vec![OscPacket::Message(big)]);
+
+    assert_eq!(coalesce::pack(vec![], 1024, OscTime::IMMEDIATE).next(), None);
+}
+
+#[test]
+fn test_pack_fills_bundles() {
+    // A simple xorshift generator, so that failures can be reproduced
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as usize
+    };
+    for _ in 0..200 {
+        let messages: Vec<OscMessage> = (0..next(50))
+            .map(|i| {
+                let mut msg = OscMessage::from(format!("/m/{}", "x".repeat(next(12))).as_str());
+                match next(3) {
+                    0 => msg.push_int(i as i32),
+                    1 => msg.push_str("y".repeat(next(40))),
+                    _ => msg.push_blob(vec![0; next(100)]),
+                };
+                msg
+            })
+            .collect();
+        let max_size = 16 + next(300);
+        let bundles: Vec<_> =
+            coalesce::pack(messages.clone(), max_size, OscTime::IMMEDIATE).collect();
+
+        for (i, bundle) in bundles.iter().enumerate() {
+            let size = encoder::encode(&OscPacket::Bundle(bundle.clone())).unwrap().len();
+            assert!(size <= max_size || bundle.content.len() == 1, "{} > {}", size, max_size);
+            // The first message of the next bundle didn't fit
+            if let Some(OscPacket::Message(msg)) = bundles.get(i + 1).map(|b| &b.content[0]) {
+                assert!(size + msg.encoded_len() + 4 > max_size);
+            }
+        }
+        let packed: Vec<OscPacket> =
+            bundles.into_iter().flat_map(|bundle| bundle.content).collect();
+        let messages: Vec<OscPacket> = messages.into_iter().map(OscPacket::Message).collect();
+        assert_eq!(packed, messages);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_threaded_coalescer() {
+    let (tx, rx) = mpsc::channel();
+    let options = CoalescerOptions {
+        interval: Duration::from_millis(20),
+        max_size: 16 + 2 * 20,
+        ..Default::default()
+    };
+    let coalescer = ThreadedCoalescer::new(options, move |bundle| tx.send(bundle).unwrap());
+    for i in 0..3 {
+        coalescer.push(message("/abc", i)).unwrap();
+    }
+
+    let timeout = Duration::from_secs(5);
+    let mut received = vec![];
+    while received.len() < 3 {
+        received.extend(addrs(&rx.recv_timeout(timeout).unwrap()));
+    }
+    let values: Vec<i32> = received.iter().map(|(_, value)| *value).collect();
+    assert_eq!(values, vec![0, 1, 2]);
+
+    coalescer.push(message("/abc", 3)).unwrap();
+    drop(coalescer);
+    assert_eq!(addrs(&rx.try_recv().unwrap()), vec![("/abc".into(), 3)]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_threaded_coalescer_flushes_after_interval_despite_wakeups() {
+    use rosc::ManualClock;
+
+    let (tx, rx) = mpsc::channel();
+    let interval = Duration::from_secs(3600);
+    let options = CoalescerOptions {
+        interval,
+        ..Default::default()
+    };
+    let clock = ManualClock::new((1000, 0).into());
+    let coalescer = ThreadedCoalescer::with_clock(options, clock.clone(), move |bundle| {
+        tx.send(bundle).unwrap()
+    });
+    coalescer.push(message("/a", 0)).unwrap();
+    clock.advance(interval - Duration::from_millis(100));
+    // Waking the worker must not restart its wait for the whole interval, so it notices the end
+    // of the interval without another wakeup instead of after an hour
+    coalescer.push(message("/b", 1)).unwrap();
+    // Lets the worker start waiting before the interval ends, as the test wouldn't catch that
+    // regression otherwise. The bundle arrives either way.
+    std::thread::sleep(Duration::from_millis(50));
+    clock.advance(Duration::from_millis(100));
+
+    let bundle = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(addrs(&bundle), vec![("/a".into(), 0), ("/b".into(), 1)]);
+}
@@ -0,0 +1,109 @@
+extern crate rosc;
+
+use rosc::debug::{hex_dump, CompactLimits};
+use rosc::{OscArray, OscBundle, OscMessage, OscPacket, OscTime, OscType};
+
+#[test]
+fn test_hex_dump() {
+    assert_eq!(hex_dump(b""), "");
+    assert_eq!(
+        hex_dump(b"#bundle\0\0\0\0\0\0\0\0\x01\0\0\0\x08/a b\0\0\0\0"),
+        concat!(
+            "00000000  23 62 75 6e 64 6c 65 00  00 00 00 00 00 00 00 01  |#bundle.........|\n",
+            "00000010  00 00 00 08 2f 61 20 62  00 00 00 00              |..../a b....|\n",
+        )
+    );
+}
+
+#[test]
+fn test_compact_debug_within_limits() {
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (1, 2).into(),
+        content: vec![OscPacket::Message(OscMessage {
+            addr: "/a".to_string(),
+            args: vec![
+                OscType::Blob(vec![1, 2]),
+                "x".into(),
+                OscType::Array(OscArray {
+                    content: vec![OscType::Int(1)],
+                }),
+            ],
+        })],
+    });
+    assert_eq!(format!("{:?}", packet.compact_debug()), format!("{:?}", packet));
+    assert_eq!(format!("{:#?}", packet.compact_debug()), format!("{:#?}", packet));
+}
+
+#[test]
+fn test_compact_debug_truncates() {
+    let msg = OscMessage {
+        addr: format!("/{}", "a".repeat(99)),
+        args: vec![OscType::Blob(vec![0xff; 1 << 20]), "b".repeat(1000).into()],
+    };
+    let debug = format!("{:?}", msg.compact_debug());
+    assert!(debug.contains(r#"addr: "/aaa"#), "{}", debug);
+    assert!(debug.contains(r#""... 36 more chars, args"#), "{}", debug);
+    assert!(debug.contains("255, 255, ... 1048560 more]"), "{}", debug);
+    assert!(debug.contains(r#""... 936 more chars)"#), "{}", debug);
+    assert!(debug.len() < 500, "{}", debug);
+
+    let many = OscMessage {
+        addr: "/many".to_string(),
+        args: (0..100).map(OscType::Int).collect(),
+    };
+    let limits = CompactLimits {
+        elements: 2,
+        ..Default::default()
+    };
+    assert_eq!(
+        format!("{:?}", many.compact_debug().with_limits(limits)),
+        r#"OscMessage { addr: "/many", args: [Int(0), Int(1), ... 98 more] }"#
+    );
+    assert_eq!(
+        format!("{:?}", OscType::Array(many.args.into_iter().collect()).compact_debug()),
+        "Array(OscArray { content: [Int(0), Int(1), Int(2), Int(3), Int(4), Int(5), Int(6), \
+         Int(7), Int(8), Int(9), Int(10), Int(11), Int(12), Int(13), Int(14), Int(15), \
+         ... 84 more] })"
+    );
+}
+
+#[test]
+fn test_compact_debug_limits_depth() {
+    let mut packet = OscPacket::Message(OscMessage::from("/deep"));
+    for _ in 0..1000 {
+        packet = OscPacket::Bundle(OscBundle {
+            timetag: OscTime::IMMEDIATE,
+            content: vec![packet],
+        });
+    }
+    let limits = CompactLimits {
+        depth: 2,
+        ..Default::default()
+    };
+    let immediate = "OscTime { seconds: 0, fractional: 1 }";
+    assert_eq!(
+        format!("{:?}", packet.compact_debug().with_limits(limits)),
+        format!(
+            "Bundle(OscBundle {{ timetag: {0}, content: [Bundle(OscBundle {{ timetag: {0}, \
+             content: [Bundle(OscBundle {{ .. }})] }})] }})",
+            immediate
+        )
+    );
+    let OscPacket::Bundle(bundle) = &packet else { unreachable!() };
+    let pretty = format!("{:#?}", bundle.compact_debug());
+    assert!(pretty.contains("OscBundle { .. },\n"), "{}", pretty);
+    assert!(pretty.lines().count() < 50, "{}", pretty);
+
+    let mut arg = OscType::Int(1);
+    for _ in 0..10 {
+        arg = OscType::Array(OscArray { content: vec![arg] });
+    }
+    let limits = CompactLimits {
+        depth: 1,
+        ..Default::default()
+    };
+    assert_eq!(
+        format!("{:?}", arg.compact_debug().with_limits(limits)),
+        "Array(OscArray { content: [Array(OscArray { .. })] })"
+    );
+}
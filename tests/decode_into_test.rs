@@ -0,0 +1,104 @@
+extern crate rosc;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use rosc::{decoder, encoder, OscArray, OscBundle, OscMessage, OscPacket};
+
+/// Counts the allocations made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+fn channel_packet(channel: usize, name: &str) -> Vec<u8> {
+    encoder::encode(&OscPacket::Bundle(OscBundle {
+        timetag: (channel as u32, 0).into(),
+        content: vec![
+            OscPacket::Message(OscMessage {
+                addr: format!("/mixer/{}/name", channel),
+                args: vec![name.into(), vec![channel as u8; 16].into()],
+            }),
+            OscPacket::Message(OscMessage {
+                addr: format!("/mixer/{}/gain", channel),
+                args: vec![0.5f32.into(), (channel as i32).into()],
+            }),
+        ],
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_decode_into_reuses_allocations() {
+    let packets: Vec<_> = (0..10)
+        .map(|channel| channel_packet(channel, "vocals"))
+        .collect();
+
+    let mut packet = OscPacket::Message(OscMessage::from("/"));
+    decoder::decode_into(&packets[0], &mut packet).unwrap();
+
+    let before = allocations();
+    for buf in &packets {
+        decoder::decode_into(buf, &mut packet).unwrap();
+    }
+    assert_eq!(allocations(), before, "decode_into must not allocate");
+
+    assert_eq!(packet, decoder::decode_udp(&packets[9]).unwrap().1);
+}
+
+#[test]
+fn test_decode_into_changes_shape() {
+    let bundle = channel_packet(1, "bass");
+    let message = OscPacket::Message(OscMessage {
+        addr: "/array".to_string(),
+        args: vec![
+            1i32.into(),
+            OscArray {
+                content: vec!["nested".into(), 2i32.into()],
+            }
+            .into(),
+        ],
+    });
+    let other_message = OscPacket::Message(OscMessage {
+        addr: "/other".to_string(),
+        args: vec![vec![1u8, 2, 3].into()],
+    });
+
+    let mut packet = OscPacket::Message(OscMessage::from("/"));
+    for expected in [&message, &decoder::decode_udp(&bundle).unwrap().1, &other_message, &message] {
+        decoder::decode_into(&encoder::encode(expected).unwrap(), &mut packet).unwrap();
+        assert_eq!(&packet, expected);
+    }
+}
+
+#[test]
+fn test_decode_into_rejects_invalid_packets() {
+    let mut packet = OscPacket::Message(OscMessage::from("/"));
+    let mut buf = channel_packet(1, "drums");
+    buf.extend([0u8; 4]);
+    assert!(decoder::decode_into(&buf, &mut packet).is_err());
+
+    let mut buf = encoder::encode(&OscPacket::Message(OscMessage::from("/addr"))).unwrap();
+    buf.extend([0u8; 4]);
+    assert!(decoder::decode_into(&buf, &mut packet).is_err());
+    assert!(decoder::decode_into(&buf[..buf.len() - 2], &mut packet).is_err());
+}
@@ -1,10 +1,11 @@
-extern crate byteorder;
 extern crate rosc;
 
-use byteorder::{BigEndian, ByteOrder};
-use std::mem;
-
-use rosc::{decoder, encoder, OscBundle, OscPacket, OscTime, OscType};
+use rosc::address::DEFAULT_MAX_ADDRESS_LEN;
+use rosc::decoder::{BundleErrorPolicy, DecodeError, DecoderOptions, LazyPacket, StringDecoding};
+use rosc::{
+    decoder, encoder, OscArray, OscArrayRef, OscBundle, OscColor, OscMessage, OscMidiMessage,
+    OscPacket, OscPacketRef, OscTime, OscType, OscTypeRef,
+};
 
 #[test]
 fn test_decode_udp_no_args() {
@@ -12,7 +13,7 @@ fn test_decode_udp_no_args() {
     let raw_addr = "/some/valid/address/4";
     let addr = encoder::encode_string(raw_addr);
     let type_tags = encoder::encode_string(",");
-    let merged: Vec<u8> = addr.into_iter().chain(type_tags.into_iter()).collect();
+    let merged: Vec<u8> = addr.into_iter().chain(type_tags).collect();
     let (remainder, osc_packet) = decoder::decode_udp(&merged).unwrap();
 
     assert_eq!(remainder.len(), 0);
@@ -33,16 +34,15 @@ fn test_decode_tcp_vec() {
     let raw_addr = "/some/valid/address/4";
     let addr = encoder::encode_string(raw_addr);
     let type_tags = encoder::encode_string(",");
-    let merged: Vec<u8> = addr.into_iter().chain(type_tags.into_iter()).collect();
+    let merged: Vec<u8> = addr.into_iter().chain(type_tags).collect();
 
     let tcp_msg = std::iter::repeat_with(|| merged.clone())
         .take(2)
-        .map(|bytes| {
+        .flat_map(|bytes| {
             // Prefix the tcp packet with a length byte
             let packet_size_header = (bytes.len() as u32).to_be_bytes().to_vec();
-            vec![packet_size_header, bytes].concat()
+            [packet_size_header, bytes].concat()
         })
-        .flatten()
         .collect::<Vec<u8>>();
 
     let (remainder, osc_packet) = decoder::decode_tcp_vec(&tcp_msg).unwrap();
@@ -75,28 +75,25 @@ fn test_decode_udp_empty_bundle() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_decode_udp_args() {
     // /another/valid/address/123 ,fdih 3.1415 3.14159265359 12345678i32
     // -1234567891011
     let addr = encoder::encode_string("/another/valid/address/123");
     // args
     let f = 3.1415f32;
-    let mut f_bytes: [u8; 4] = [0u8; 4];
-    BigEndian::write_f32(&mut f_bytes, f);
-    assert_eq!(BigEndian::read_f32(&f_bytes), f);
+    let f_bytes: [u8; 4] = f.to_be_bytes();
 
     let d = 3.14159265359f64;
-    let mut d_bytes: [u8; 8] = [0u8; 8];
-    BigEndian::write_f64(&mut d_bytes, d);
-    assert_eq!(BigEndian::read_f64(&d_bytes), d);
+    let d_bytes: [u8; 8] = d.to_be_bytes();
 
     let i = 12345678i32;
-    let i_bytes: [u8; 4] = unsafe { mem::transmute(i.to_be()) };
+    let i_bytes: [u8; 4] = i.to_be_bytes();
 
     let l = -1234567891011i64;
-    let h_bytes: [u8; 8] = unsafe { mem::transmute(l.to_be()) };
+    let h_bytes: [u8; 8] = l.to_be_bytes();
 
-    let blob_size: [u8; 4] = unsafe { mem::transmute(6u32.to_be()) };
+    let blob_size: [u8; 4] = 6u32.to_be_bytes();
     let blob: Vec<u8> = vec![1u8, 2u8, 3u8, 4u8, 5u8, 6u8];
 
     let s = "I am an osc test string.";
@@ -105,7 +102,7 @@ fn test_decode_udp_args() {
     let s_bytes: Vec<u8> = encoder::encode_string(s);
 
     let c = '$';
-    let c_bytes: [u8; 4] = unsafe { mem::transmute((c as u32).to_be()) };
+    let c_bytes: [u8; 4] = (c as u32).to_be_bytes();
 
     let a = vec![OscType::Int(i), OscType::Float(f), OscType::Int(i)];
 
@@ -118,19 +115,18 @@ fn test_decode_udp_args() {
         .chain(i_bytes.iter())
         .chain(blob_size.iter())
         .chain(blob.iter())
-        .chain(vec![0u8, 0u8].iter())
+        .chain([0u8, 0u8].iter())
         .chain(h_bytes.iter())
         .chain(c_bytes.iter())
         // array content
         .chain(i_bytes.iter())
         .chain(f_bytes.iter())
-        .chain(i_bytes.iter())
-        .map(|x| *x)
+        .chain(i_bytes.iter()).copied()
         .collect::<Vec<u8>>();
 
     let merged: Vec<u8> = addr
         .into_iter()
-        .chain(type_tags.into_iter())
+        .chain(type_tags)
         .chain(args)
         .collect::<Vec<u8>>();
 
@@ -148,7 +144,7 @@ fn test_decode_udp_args() {
                     // , so there is no real test wether the value is
                     // correct or not
                     rosc::OscType::Bool(_) => (),
-                    rosc::OscType::Inf => (),
+                    rosc::OscType::Impulse => (),
                     rosc::OscType::Nil => (),
                     // test time-tags, midi-messages and chars
                     rosc::OscType::Char(x) => assert_eq!(c, x),
@@ -160,3 +156,1542 @@ fn test_decode_udp_args() {
         _ => panic!("Expected an OSC message!"),
     }
 }
+
+#[test]
+fn test_decode_ref_borrows_from_buffer() {
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/blob/and/string".to_string(),
+        args: vec![
+            OscType::Blob(vec![1u8, 2u8, 3u8, 4u8, 5u8]),
+            OscType::String("borrowed".to_string()),
+            OscType::Array(OscArray {
+                content: vec![OscType::Int(42), OscType::String("nested".to_string())],
+            }),
+        ],
+    });
+    let buf = encoder::encode(&packet).unwrap();
+
+    let (remainder, packet_ref) = decoder::decode_ref(&buf).unwrap();
+    assert!(remainder.is_empty());
+
+    let buf_range = buf.as_ptr_range();
+    let msg = match packet_ref {
+        OscPacketRef::Message(ref msg) => msg,
+        _ => panic!("Expected an OscMessageRef!"),
+    };
+    assert_eq!(msg.addr, "/blob/and/string");
+    assert!(buf_range.contains(&msg.addr.as_ptr()));
+    match msg.args[0] {
+        OscTypeRef::Blob(blob) => {
+            assert_eq!(blob, &[1u8, 2u8, 3u8, 4u8, 5u8]);
+            assert!(buf_range.contains(&blob.as_ptr()));
+        }
+        _ => panic!("Expected a blob!"),
+    }
+    match msg.args[1] {
+        OscTypeRef::String(ref s) => {
+            assert_eq!(s, "borrowed");
+            assert!(buf_range.contains(&s.as_ptr()));
+        }
+        _ => panic!("Expected a string!"),
+    }
+    assert_eq!(
+        msg.args[2],
+        OscTypeRef::Array(OscArrayRef {
+            content: vec![OscTypeRef::Int(42), OscTypeRef::String("nested".into())],
+        })
+    );
+
+    assert_eq!(packet_ref.to_owned(), packet);
+}
+
+#[test]
+fn test_decode_large_blob_without_copies() {
+    let blob: Vec<u8> = (0..8 << 20).map(|i| i as u8).collect();
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/sample".to_string(),
+        args: vec![OscType::Blob(blob.clone()), OscType::Int(1)],
+    });
+    let buf = encoder::encode(&packet).unwrap();
+    let buf_range = buf.as_ptr_range();
+
+    // The borrowed decoders point into the buffer
+    let msg = match decoder::decode_ref(&buf).unwrap().1 {
+        OscPacketRef::Message(msg) => msg,
+        _ => panic!("Expected an OscMessageRef!"),
+    };
+    let blob_ref = msg.args[0].as_blob().unwrap();
+    assert!(buf_range.contains(&blob_ref.as_ptr()));
+    assert_eq!(blob_ref, blob.as_slice());
+    #[cfg(feature = "std")]
+    {
+        let mut streamed = Vec::with_capacity(blob.len());
+        std::io::copy(&mut msg.args[0].blob_reader().unwrap(), &mut streamed).unwrap();
+        assert_eq!(streamed, blob);
+    }
+    let chunks: Vec<&[u8]> = msg.args[0].blob_chunks(3 << 20).unwrap().collect();
+    let lens: Vec<usize> = chunks.iter().map(|chunk| chunk.len()).collect();
+    assert_eq!(lens, [3 << 20, 3 << 20, 2 << 20]);
+    assert!(chunks.iter().all(|chunk| buf_range.contains(&chunk.as_ptr())));
+    assert!(msg.args[1].as_blob().is_none());
+    assert!(msg.args[1].blob_chunks(1).is_none());
+
+    // The lazy decoder skips the blob to get to the next argument
+    let msg = match decoder::decode_lazy(&buf).unwrap() {
+        LazyPacket::Message(msg) => msg,
+        other => panic!("unexpected packet: {:?}", other),
+    };
+    let mut args = msg.args();
+    assert!(buf_range.contains(&args.next().unwrap().unwrap().as_blob().unwrap().as_ptr()));
+    assert_eq!(args.next().unwrap().unwrap(), OscTypeRef::Int(1));
+
+    // The owned decoder copies the blob once, taking it out of the argument moves it
+    let mut msg = match decoder::decode_udp(&buf).unwrap().1 {
+        OscPacket::Message(msg) => msg,
+        _ => panic!("Expected an OscMessage!"),
+    };
+    let decoded_ptr = match msg.args[0] {
+        OscType::Blob(ref decoded) => decoded.as_ptr(),
+        _ => panic!("Expected a blob!"),
+    };
+    assert!(!buf_range.contains(&decoded_ptr));
+    let taken = msg.args.remove(0).blob().unwrap();
+    assert_eq!(taken.as_ptr(), decoded_ptr);
+    assert_eq!(taken, blob);
+}
+
+#[test]
+fn test_decode_ref_bundle() {
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: OscTime::from((1, 2)),
+        content: vec![
+            OscPacket::Message(OscMessage {
+                addr: "/first".to_string(),
+                args: vec![OscType::Blob(vec![0u8; 8])],
+            }),
+            OscPacket::Bundle(OscBundle {
+                timetag: OscTime::from((3, 4)),
+                content: vec![OscPacket::Message(OscMessage {
+                    addr: "/second".to_string(),
+                    args: vec![],
+                })],
+            }),
+        ],
+    });
+    let buf = encoder::encode(&packet).unwrap();
+
+    let (remainder, packet_ref) = decoder::decode_ref(&buf).unwrap();
+    assert!(remainder.is_empty());
+    match packet_ref {
+        OscPacketRef::Bundle(ref bundle) => {
+            assert_eq!(bundle.timetag, OscTime::from((1, 2)));
+            assert_eq!(bundle.content.len(), 2);
+        }
+        _ => panic!("Expected an OscBundleRef!"),
+    }
+    assert_eq!(OscPacket::from(packet_ref), packet);
+}
+
+#[test]
+fn test_decode_ref_invalid_utf8() {
+    let mut buf = encoder::encode_string("/addr");
+    buf.extend(encoder::encode_string(",s"));
+    buf.extend([0xffu8, 0xfe, 0x00, 0x00]);
+
+    assert!(decoder::decode_ref(&buf).is_err());
+}
+
+#[test]
+fn test_decode_blob_without_padding() {
+    // Blobs whose length is a multiple of four are not followed by padding bytes.
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/blob".to_string(),
+        args: vec![OscType::Blob(vec![1u8, 2u8, 3u8, 4u8]), OscType::Int(5)],
+    });
+    let buf = encoder::encode(&packet).unwrap();
+
+    let (remainder, decoded) = decoder::decode_udp(&buf).unwrap();
+    assert!(remainder.is_empty());
+    assert_eq!(decoded, packet);
+}
+
+#[test]
+fn test_aligned_blob_wire_format() {
+    // A 4 byte blob is followed directly by the next argument, without a padding word
+    let buf: &[u8] = b"/blob\0\0\0,bi\0\0\0\0\x04\x01\x02\x03\x04\0\0\0\x05";
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/blob".to_string(),
+        args: vec![OscType::Blob(vec![1, 2, 3, 4]), OscType::Int(5)],
+    });
+    assert_eq!(encoder::encode(&packet).unwrap(), buf);
+    assert_eq!(decoder::decode_udp(buf).unwrap(), (&[][..], packet.clone()));
+    assert_eq!(decoder::decode_with_options(buf, &DecoderOptions::default()).unwrap(), packet);
+    assert_eq!(decoder::decode_lazy(buf).unwrap().to_owned().unwrap(), packet);
+
+    // The word following an aligned blob is the next argument, not padding to be skipped
+    let mut padded = buf[..20].to_vec();
+    padded.extend([0u8; 4]);
+    padded.extend(&buf[20..]);
+    assert!(decoder::decode_with_options(&padded, &DecoderOptions::default()).is_err());
+}
+
+fn nested_bundles(depth: usize) -> Vec<u8> {
+    let mut packet = encoder::encode(&OscPacket::Message(OscMessage::from("/innermost"))).unwrap();
+    for _ in 0..depth {
+        let mut bundle = encoder::encode_string("#bundle");
+        bundle.extend([0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 1u8]);
+        bundle.extend((packet.len() as u32).to_be_bytes());
+        bundle.extend(packet);
+        packet = bundle;
+    }
+    packet
+}
+
+#[test]
+fn test_decode_with_options_default_limits() {
+    let options = DecoderOptions::default();
+
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/blob".to_string(),
+        args: vec![OscType::Blob(vec![0u8; 1024])],
+    });
+    let buf = encoder::encode(&packet).unwrap();
+    assert_eq!(decoder::decode_with_options(&buf, &options).unwrap(), packet);
+
+    let buf = nested_bundles(options.max_bundle_depth);
+    decoder::decode_with_options(&buf, &options).expect("bundle depth within limit");
+}
+
+#[test]
+fn test_decode_with_options_limits() {
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/some/address".to_string(),
+        args: vec![
+            OscType::Blob(vec![0u8; 100]),
+            OscType::String("a string argument".to_string()),
+            OscType::Array(OscArray {
+                content: vec![OscType::Int(1), OscType::Int(2)],
+            }),
+        ],
+    });
+    let buf = encoder::encode(&packet).unwrap();
+
+    let options = DecoderOptions {
+        max_blob_size: 99,
+        ..Default::default()
+    };
+    match decoder::decode_with_options(&buf, &options).as_ref().map_err(DecodeError::root_cause) {
+        Err(DecodeError::BlobTooLarge { size: 100, max: 99 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let options = DecoderOptions {
+        max_string_len: 16,
+        ..Default::default()
+    };
+    match decoder::decode_with_options(&buf, &options).as_ref().map_err(DecodeError::root_cause) {
+        Err(DecodeError::StringTooLong { len: 17, max: 16 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let options = DecoderOptions {
+        max_args: 3,
+        ..Default::default()
+    };
+    match decoder::decode_with_options(&buf, &options).as_ref().map_err(DecodeError::root_cause) {
+        Err(DecodeError::TooManyArgs { count: 4, max: 3 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let options = DecoderOptions {
+        max_packet_size: buf.len() - 1,
+        ..Default::default()
+    };
+    match decoder::decode_with_options(&buf, &options).as_ref().map_err(DecodeError::root_cause) {
+        Err(DecodeError::PacketTooLarge { .. }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_with_options_max_address_len() {
+    let message = |len: usize| {
+        let packet = OscPacket::Message(OscMessage {
+            addr: format!("/{}", "a".repeat(len - 1)),
+            args: vec![],
+        });
+        (encoder::encode(&packet).unwrap(), packet)
+    };
+    let options = DecoderOptions::default();
+    let (buf, packet) = message(options.max_address_len);
+    assert_eq!(decoder::decode_with_options(&buf, &options).unwrap(), packet);
+    // Takes precedence over the string limit, which would reject it as well
+    let (buf, _) = message(options.max_address_len + 1);
+    match decoder::decode_with_options(&buf, &options).as_ref().map_err(DecodeError::root_cause) {
+        Err(DecodeError::AddressTooLong { len: 4097, max: 4096 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let options = DecoderOptions {
+        max_address_len: 8,
+        ..Default::default()
+    };
+    let (buf, packet) = message(8);
+    assert_eq!(decoder::decode_with_options(&buf, &options).unwrap(), packet);
+    let (buf, _) = message(9);
+    match decoder::decode_with_options(&buf, &options).as_ref().map_err(DecodeError::root_cause) {
+        Err(DecodeError::AddressTooLong { len: 9, max: 8 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let lossy = DecoderOptions {
+        string_decoding: StringDecoding::Lossy,
+        ..options
+    };
+    match decoder::decode_with_options(&buf, &lossy).as_ref().map_err(DecodeError::root_cause) {
+        Err(DecodeError::AddressTooLong { len: 9, max: 8 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    // String arguments are only limited by the string limit
+    let mut msg = OscMessage::from("/a");
+    msg.push_str("a string argument");
+    let buf = encoder::encode(&OscPacket::Message(msg)).unwrap();
+    assert!(decoder::decode_with_options(&buf, &options).is_ok());
+
+    // Addresses within the address limit are still subject to the string limit
+    let options = DecoderOptions {
+        max_address_len: DEFAULT_MAX_ADDRESS_LEN + 1,
+        max_string_len: 16,
+        ..Default::default()
+    };
+    let (buf, _) = message(17);
+    match decoder::decode_with_options(&buf, &options).as_ref().map_err(DecodeError::root_cause) {
+        Err(DecodeError::StringTooLong { len: 17, max: 16 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(decoder::decode_udp(&message(DEFAULT_MAX_ADDRESS_LEN + 1).0).is_ok());
+}
+
+#[test]
+fn test_decode_with_options_bundle_depth() {
+    let options = DecoderOptions {
+        max_bundle_depth: 2,
+        ..Default::default()
+    };
+    decoder::decode_with_options(&nested_bundles(2), &options).expect("depth within limit");
+    match decoder::decode_with_options(&nested_bundles(3), &options)
+        .as_ref()
+        .map_err(DecodeError::root_cause)
+    {
+        Err(DecodeError::BundleTooDeep { max: 2 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_deeply_nested_bundles_does_not_overflow() {
+    let buf = nested_bundles(10_000);
+
+    let options = DecoderOptions {
+        max_bundle_depth: usize::MAX,
+        max_packet_size: usize::MAX,
+        ..Default::default()
+    };
+    match decoder::decode_with_options(&buf, &options).as_ref().map_err(DecodeError::root_cause) {
+        Err(DecodeError::BundleTooDeep { max }) => assert_eq!(*max, decoder::MAX_BUNDLE_DEPTH),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(decoder::decode_udp(&buf).is_err());
+}
+
+fn expect_error_at(buf: &[u8]) -> (usize, String, String) {
+    match decoder::decode_with_options(buf, &DecoderOptions::default()) {
+        Err(err) => match err {
+            DecodeError::At {
+                offset,
+                ref context,
+                ..
+            } => (offset, context.clone(), err.to_string()),
+            other => panic!("error without position: {:?}", other),
+        },
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_error_offsets() {
+    let message = OscPacket::Message(OscMessage {
+        addr: "/mixer/1/name".to_string(),
+        args: vec![1i32.into(), 2i32.into(), 3i32.into(), "name".into()],
+    });
+    let mut buf = encoder::encode(&message).unwrap();
+    // Last padding byte of the string argument
+    buf[43] = 1;
+    let (offset, context, display) = expect_error_at(&buf);
+    assert_eq!(offset, 36);
+    assert_eq!(context, "arg 3 of message '/mixer/1/name'");
+    assert_eq!(
+        display,
+        "bad OSC string: String padding contains non-zero bytes at byte 36 \
+         (arg 3 of message '/mixer/1/name'), near ... 73 00 00 00 00 00 00 01 00 00 00 02 00 00 00 \
+         03 6e 61 6d 65 00 00 00 01"
+    );
+
+    let mut buf = encoder::encode(&message).unwrap();
+    // Type tag of the first argument
+    buf[17] = b'X';
+    let (offset, context, _) = expect_error_at(&buf);
+    assert_eq!(offset, 24);
+    assert_eq!(context, "arg 0 of message '/mixer/1/name'");
+
+    let mut buf = encoder::encode(&message).unwrap();
+    buf[16] = b'i';
+    let (offset, context, _) = expect_error_at(&buf);
+    assert_eq!(offset, 16);
+    assert_eq!(context, "type tags of message '/mixer/1/name'");
+}
+
+#[test]
+fn test_decode_bundle_error_offsets() {
+    let bundle = OscPacket::Bundle(OscBundle {
+        timetag: (0, 1).into(),
+        content: vec![
+            OscPacket::Message(OscMessage::from("/a")),
+            OscPacket::Bundle(OscBundle {
+                timetag: (0, 1).into(),
+                content: vec![
+                    OscPacket::Message(OscMessage::from("/b")),
+                    OscPacket::Message(OscMessage {
+                        addr: "/c".to_string(),
+                        args: vec![1i32.into()],
+                    }),
+                ],
+            }),
+        ],
+    });
+    let buf = encoder::encode(&bundle).unwrap();
+
+    // Type tag of the argument of '/c'
+    let mut corrupted = buf.clone();
+    corrupted[buf.len() - 7] = b'X';
+    let (offset, context, _) = expect_error_at(&corrupted);
+    assert_eq!(offset, buf.len() - 4);
+    assert_eq!(context, "arg 0 of message '/c' in bundle element 1.1");
+
+    // Announce an element larger than the remainder of the bundle
+    let mut oversized = buf.clone();
+    oversized[16..20].copy_from_slice(&1000u32.to_be_bytes());
+    let (offset, context, display) = expect_error_at(&oversized);
+    assert_eq!(offset, 16);
+    assert_eq!(context, "size of bundle element 0");
+    assert_eq!(
+        display,
+        "bad OSC bundle: Bundle element of 1000 bytes exceeds the remaining 56 bytes of the bundle \
+         at byte 16 (size of bundle element 0), near 23 62 75 6e 64 6c 65 00 00 00 00 00 00 00 00 \
+         01 00 00 03 e8 2f 61 00 00 2c 00 00 00 00 00 00 2c 23 62 75 6e 64 6c 65 00 00 00 00 00 00 \
+         00 00 01 00 00 00 08 2f 62 00 00 2c 00 00 00 00 00 00 0c ..."
+    );
+}
+
+fn expect_snippet(buf: &[u8]) -> (usize, String) {
+    match decoder::decode_udp(buf) {
+        Err(nom::Err::Error(DecodeError::At {
+            offset, snippet, ..
+        })) => (offset, snippet),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_error_snippets_are_bounded() {
+    // A string argument of 1 MB whose last padding byte is not zero
+    let mut buf = encoder::encode(&OscPacket::Message(OscMessage {
+        addr: "/big".to_string(),
+        args: vec!["a".repeat(1 << 20).into(), 1i32.into()],
+    }))
+    .unwrap();
+    let padding = buf.len() - 5;
+    buf[padding] = 1;
+    let (offset, snippet) = expect_snippet(&buf);
+    assert_eq!(offset, 12);
+    // The 12 bytes before the offset and 52 after it, the end is cut off
+    assert_eq!(snippet.split(' ').count(), 64 + 1);
+    assert!(snippet.starts_with("2f 62 69 67 00 00 00 00 2c 73 69 00 61 61"));
+    assert!(snippet.ends_with(" 61 ..."));
+
+    // The start is cut off if the offset is further into the packet
+    let mut buf = encoder::encode(&OscPacket::Message(OscMessage {
+        addr: format!("/{}", "a".repeat(1000)),
+        args: vec![1i32.into()],
+    }))
+    .unwrap();
+    let type_tag = buf.len() - 7;
+    buf[type_tag] = b'X';
+    let (offset, snippet) = expect_snippet(&buf);
+    assert_eq!(offset, buf.len() - 4);
+    assert_eq!(
+        snippet,
+        "... 61 61 61 61 61 61 61 61 61 00 00 00 2c 58 00 00 00 00 00 01"
+    );
+}
+
+#[test]
+fn test_decode_lenient_type_tags() {
+    let lenient = DecoderOptions {
+        lenient_type_tags: true,
+        ..Default::default()
+    };
+
+    // Captured from a sender that omits the leading comma
+    let mut buf = encoder::encode_string("/sensor/1");
+    buf.extend(encoder::encode_string("ifs"));
+    buf.extend(42i32.to_be_bytes());
+    buf.extend(0.5f32.to_be_bytes());
+    buf.extend(encoder::encode_string("abc"));
+
+    assert!(decoder::decode_with_options(&buf, &DecoderOptions::default()).is_err());
+    assert_eq!(
+        decoder::decode_with_options(&buf, &lenient).unwrap(),
+        OscPacket::Message(OscMessage {
+            addr: "/sensor/1".to_string(),
+            args: vec![42i32.into(), 0.5f32.into(), "abc".into()],
+        })
+    );
+
+    // Strings containing characters that aren't type tags are still rejected
+    let mut buf = encoder::encode_string("/sensor/1");
+    buf.extend(encoder::encode_string("hello"));
+    assert!(decoder::decode_with_options(&buf, &lenient).is_err());
+}
+
+#[test]
+fn test_decode_lenient_type_tags_without_args() {
+    let lenient = DecoderOptions {
+        lenient_type_tags: true,
+        ..Default::default()
+    };
+    let expected = OscPacket::Message(OscMessage::from("/ping"));
+
+    // Empty type tag string without comma
+    let mut buf = encoder::encode_string("/ping");
+    buf.extend(encoder::encode_string(""));
+    assert!(decoder::decode_with_options(&buf, &DecoderOptions::default()).is_err());
+    assert_eq!(decoder::decode_with_options(&buf, &lenient).unwrap(), expected);
+
+    // No type tag string at all
+    let buf = encoder::encode_string("/ping");
+    assert!(decoder::decode_with_options(&buf, &DecoderOptions::default()).is_err());
+    assert_eq!(decoder::decode_with_options(&buf, &lenient).unwrap(), expected);
+
+    // A zero-arg message inside a bundle must not swallow the following element
+    let mut message = encoder::encode_string("/ping");
+    message.extend(encoder::encode_string(""));
+    let mut bundle = encoder::encode_string("#bundle");
+    bundle.extend([0u8, 0, 0, 0, 0, 0, 0, 1]);
+    for _ in 0..2 {
+        bundle.extend((message.len() as u32).to_be_bytes());
+        bundle.extend(&message);
+    }
+    assert_eq!(
+        decoder::decode_with_options(&bundle, &lenient).unwrap(),
+        OscPacket::Bundle(OscBundle {
+            timetag: (0, 1).into(),
+            content: vec![expected.clone(), expected],
+        })
+    );
+}
+
+#[test]
+fn test_iter_packets() {
+    let options = DecoderOptions::default();
+    assert_eq!(decoder::iter_packets(&[], &options).count(), 0);
+
+    let first = OscPacket::Message(OscMessage {
+        addr: "/first".to_string(),
+        args: vec![1i32.into(), "two".into()],
+    });
+    let mut buf = encoder::encode(&first).unwrap();
+    let packets: Vec<_> = decoder::iter_packets(&buf, &options).collect();
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].as_ref().unwrap(), &first);
+
+    let second = OscPacket::Bundle(OscBundle {
+        timetag: (0, 1).into(),
+        content: vec![first.clone()],
+    });
+    buf.extend(encoder::encode(&second).unwrap());
+    let packets: Result<Vec<_>, _> = decoder::iter_packets(&buf, &options).collect();
+    assert_eq!(packets.unwrap(), vec![first.clone(), second]);
+}
+
+#[test]
+fn test_iter_packets_stops_after_error() {
+    let options = DecoderOptions::default();
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/packet".to_string(),
+        args: vec![1i32.into()],
+    });
+    let encoded = encoder::encode(&packet).unwrap();
+
+    // Trailing partial packet
+    let mut buf = encoded.clone();
+    buf.extend(&encoded);
+    buf.extend(&encoded[..encoded.len() - 2]);
+    let mut iter = decoder::iter_packets(&buf, &options);
+    assert_eq!(iter.next().unwrap().unwrap(), packet);
+    assert_eq!(iter.next().unwrap().unwrap(), packet);
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+
+    // Corrupt region followed by valid packets
+    let mut buf = vec![0xffu8; 8];
+    buf.extend(&encoded);
+    let results: Vec<_> = decoder::iter_packets(&buf, &options).collect();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_err());
+}
+
+#[test]
+fn test_decode_all() {
+    use rosc::OscErrorKind;
+
+    let options = DecoderOptions::default();
+    let first = OscPacket::Message(OscMessage {
+        addr: "/first".to_string(),
+        args: vec![1i32.into()],
+    });
+    let second = OscPacket::Message(OscMessage::from("/second"));
+    let mut buf = encoder::encode(&first).unwrap();
+    buf.extend(encoder::encode(&second).unwrap());
+    assert_eq!(decoder::decode_all(&buf, &options).unwrap(), vec![first.clone(), second]);
+    assert_eq!(
+        decoder::decode_all(&encoder::encode(&first).unwrap(), &options).unwrap(),
+        vec![first.clone()]
+    );
+    assert_eq!(decoder::decode_all(&[], &options), Err(DecodeError::BadPacket("Empty packet.")));
+
+    // Garbage after the packets tells how many decoded
+    let mut garbage = buf.clone();
+    garbage.extend(b"\xff\xff\xff\xff");
+    let err = decoder::decode_all(&garbage, &options).unwrap_err();
+    match err {
+        DecodeError::AfterPackets { decoded: 2, .. } => {}
+        ref other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(matches!(err.root_cause(), DecodeError::ReadError(_)), "{:?}", err);
+    assert_eq!(err.kind(), OscErrorKind::ReadError);
+    assert!(err.to_string().ends_with("after 2 packets decoded successfully"), "{}", err);
+    // A bad first packet is reported as it is
+    let err = decoder::decode_all(&garbage[garbage.len() - 4..], &options).unwrap_err();
+    assert!(matches!(err, DecodeError::At { .. }), "{:?}", err);
+
+    // Trailing bytes that don't make up a word
+    let mut trailing = buf.clone();
+    trailing.extend(b"\0\0");
+    let err = decoder::decode_all(&trailing, &options).unwrap_err();
+    assert!(matches!(err, DecodeError::AfterPackets { decoded: 2, .. }), "{:?}", err);
+    let options = DecoderOptions {
+        allow_trailing_bytes: true,
+        ..Default::default()
+    };
+    assert_eq!(decoder::decode_all(&trailing, &options).unwrap().len(), 2);
+}
+
+/// Message from a sender that encodes strings as Latin-1, the string argument "caf\xe9" contains a
+/// lone 0xE9 byte.
+fn latin1_message(addr: &[u8]) -> Vec<u8> {
+    let mut buf = addr.to_vec();
+    buf.extend(encoder::encode_string(",sis"));
+    buf.extend(b"caf\xe9\0\0\0\0");
+    buf.extend(7i32.to_be_bytes());
+    buf.extend(encoder::encode_string("ok"));
+    buf
+}
+
+#[test]
+fn test_decode_invalid_utf8_strings() {
+    let buf = latin1_message(b"/log\0\0\0\0");
+    let options = |string_decoding| DecoderOptions {
+        string_decoding,
+        ..Default::default()
+    };
+
+    match decoder::decode_with_options(&buf, &options(StringDecoding::Strict))
+        .as_ref()
+        .map_err(DecodeError::root_cause)
+    {
+        Err(DecodeError::StringError(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    assert_eq!(
+        decoder::decode_with_options(&buf, &options(StringDecoding::Lossy)).unwrap(),
+        OscPacket::Message(OscMessage {
+            addr: "/log".to_string(),
+            args: vec!["caf\u{fffd}".into(), 7i32.into(), "ok".into()],
+        })
+    );
+
+    assert_eq!(
+        decoder::decode_with_options(&buf, &options(StringDecoding::Preserve)).unwrap(),
+        OscPacket::Message(OscMessage {
+            addr: "/log".to_string(),
+            args: vec![
+                OscType::Blob(b"caf\xe9".to_vec()),
+                7i32.into(),
+                "ok".into(),
+            ],
+        })
+    );
+}
+
+#[test]
+fn test_decode_invalid_utf8_address() {
+    let buf = latin1_message(b"/caf\xe9\0\0\0");
+    let options = |string_decoding| DecoderOptions {
+        string_decoding,
+        ..Default::default()
+    };
+
+    assert!(decoder::decode_with_options(&buf, &options(StringDecoding::Strict)).is_err());
+    assert!(decoder::decode_with_options(&buf, &options(StringDecoding::Preserve)).is_err());
+    match decoder::decode_with_options(&buf, &options(StringDecoding::Lossy)).unwrap() {
+        OscPacket::Message(msg) => assert_eq!(msg.addr, "/caf\u{fffd}"),
+        other => panic!("unexpected packet: {:?}", other),
+    }
+}
+
+#[test]
+fn test_peek() {
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/mixer/1/name".to_string(),
+        args: vec![1i32.into(), "name".into()],
+    });
+    let buf = encoder::encode(&packet).unwrap();
+    assert_eq!(decoder::peek_address(&buf).unwrap(), "/mixer/1/name");
+    assert_eq!(decoder::peek_type_tags(&buf).unwrap(), ",is");
+    assert!(!decoder::is_bundle(&buf));
+
+    // Valid address and type tags followed by garbage
+    let mut garbage = buf[..24].to_vec();
+    garbage.extend([0xffu8; 3]);
+    assert!(decoder::decode_udp(&garbage).is_err());
+    assert_eq!(decoder::peek_address(&garbage).unwrap(), "/mixer/1/name");
+    assert_eq!(decoder::peek_type_tags(&garbage).unwrap(), ",is");
+
+    // Valid address followed by garbage
+    let mut garbage = buf[..16].to_vec();
+    garbage.extend(b"is\0\0");
+    assert!(decoder::decode_udp(&garbage).is_err());
+    assert_eq!(decoder::peek_address(&garbage).unwrap(), "/mixer/1/name");
+    assert!(decoder::peek_type_tags(&garbage).is_err());
+
+    // Invalid addresses are rejected just like by the decoder
+    let invalid: [&[u8]; 5] = [
+        b"",
+        b"/mix",
+        b"/m\0\x01,\0\0\0",
+        b"mixer\0\0\0,\0\0\0",
+        b"/\xff\0\0,\0\0\0",
+    ];
+    for buf in invalid {
+        assert!(decoder::decode_udp(buf).is_err());
+        assert!(decoder::peek_address(buf).is_err());
+        assert!(decoder::peek_type_tags(buf).is_err());
+    }
+}
+
+#[test]
+fn test_peek_bundle() {
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (0, 1).into(),
+        content: vec![OscPacket::Message(OscMessage::from("/inner"))],
+    });
+    let buf = encoder::encode(&packet).unwrap();
+    assert!(decoder::is_bundle(&buf));
+    assert!(decoder::peek_address(&buf).is_err());
+    assert!(decoder::peek_type_tags(&buf).is_err());
+}
+
+#[test]
+fn test_decode_lazy_message() {
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/lazy".to_string(),
+        args: vec![
+            1i32.into(),
+            OscArray {
+                content: vec!["nested".into(), OscArray { content: vec![] }.into()],
+            }
+            .into(),
+            vec![1u8, 2, 3].into(),
+        ],
+    });
+    let buf = encoder::encode(&packet).unwrap();
+
+    let msg = match decoder::decode_lazy(&buf).unwrap() {
+        LazyPacket::Message(msg) => msg,
+        other => panic!("unexpected packet: {:?}", other),
+    };
+    assert_eq!(msg.addr, "/lazy");
+    assert_eq!(msg.type_tags, ",i[s[]]b");
+    let args: Vec<_> = msg.args().collect::<Result<_, _>>().unwrap();
+    assert_eq!(
+        args,
+        vec![
+            OscTypeRef::Int(1),
+            OscTypeRef::Array(OscArrayRef {
+                content: vec![
+                    OscTypeRef::String("nested".into()),
+                    OscTypeRef::Array(OscArrayRef { content: vec![] }),
+                ],
+            }),
+            OscTypeRef::Blob(&[1, 2, 3]),
+        ]
+    );
+    assert_eq!(OscPacket::Message(msg.to_owned().unwrap()), packet);
+}
+
+#[test]
+fn test_decode_lazy_defers_argument_errors() {
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/lazy".to_string(),
+        args: vec![1i32.into(), "string".into()],
+    });
+    let mut buf = encoder::encode(&packet).unwrap();
+    // Corrupt the padding of the string argument
+    let last = buf.len() - 1;
+    buf[last] = 1;
+    assert!(decoder::decode_udp(&buf).is_err());
+
+    let msg = match decoder::decode_lazy(&buf).unwrap() {
+        LazyPacket::Message(msg) => msg,
+        other => panic!("unexpected packet: {:?}", other),
+    };
+    assert_eq!(msg.addr, "/lazy");
+    let mut args = msg.args();
+    assert_eq!(args.next().unwrap().unwrap(), OscTypeRef::Int(1));
+    assert!(args.next().unwrap().is_err());
+    assert!(args.next().is_none());
+    assert!(msg.to_owned().is_err());
+
+    assert!(decoder::decode_lazy(b"/lazy\0\0\0").is_err());
+    assert!(decoder::decode_lazy(b"lazy\0\0\0\0,\0\0\0").is_err());
+}
+
+#[test]
+fn test_decode_lazy_bundle() {
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (1, 2).into(),
+        content: vec![
+            OscPacket::Message(OscMessage::from("/first")),
+            OscPacket::Bundle(OscBundle {
+                timetag: (3, 4).into(),
+                content: vec![OscPacket::Message(OscMessage {
+                    addr: "/second".to_string(),
+                    args: vec![2i32.into()],
+                })],
+            }),
+        ],
+    });
+    let mut buf = encoder::encode(&packet).unwrap();
+
+    let bundle = match decoder::decode_lazy(&buf).unwrap() {
+        LazyPacket::Bundle(bundle) => bundle,
+        other => panic!("unexpected packet: {:?}", other),
+    };
+    assert_eq!(bundle.timetag, (1, 2).into());
+    let addrs: Vec<_> = bundle
+        .elements()
+        .map(|element| match element.unwrap() {
+            LazyPacket::Message(msg) => msg.addr,
+            LazyPacket::Bundle(_) => "bundle",
+        })
+        .collect();
+    assert_eq!(addrs, vec!["/first", "bundle"]);
+    assert_eq!(OscPacket::Bundle(bundle.to_owned().unwrap()), packet);
+
+    // Announce a larger second element than present
+    buf[32..36].copy_from_slice(&1000u32.to_be_bytes());
+    let bundle = match decoder::decode_lazy(&buf).unwrap() {
+        LazyPacket::Bundle(bundle) => bundle,
+        other => panic!("unexpected packet: {:?}", other),
+    };
+    let mut elements = bundle.elements();
+    assert!(elements.next().unwrap().is_ok());
+    assert!(elements.next().unwrap().is_err());
+    assert!(elements.next().is_none());
+}
+
+#[test]
+fn test_decode_lazy_with_options() {
+    let options = DecoderOptions {
+        max_string_len: 8,
+        max_args: 2,
+        max_bundle_depth: 1,
+        ..Default::default()
+    };
+    let decode = |packet: &OscPacket| {
+        let buf = encoder::encode(packet).unwrap();
+        decoder::decode_lazy_with_options(&buf, &options).and_then(|packet| packet.to_owned())
+    };
+    let root_cause = |packet: &OscPacket| decode(packet).unwrap_err().root_cause().clone();
+
+    let msg = OscPacket::Message(OscMessage {
+        addr: "/lazy".to_string(),
+        args: vec![1i32.into(), "string".into()],
+    });
+    assert_eq!(decode(&msg).unwrap(), msg);
+    let long_string = OscPacket::Message(OscMessage {
+        addr: "/lazy".to_string(),
+        args: vec!["too long string".into()],
+    });
+    assert_eq!(root_cause(&long_string), DecodeError::StringTooLong { len: 15, max: 8 });
+    let many_args = OscPacket::Message(OscMessage {
+        addr: "/lazy".to_string(),
+        args: vec![OscType::Array(vec![1i32, 2, 3].into_iter().collect())],
+    });
+    assert_eq!(root_cause(&many_args), DecodeError::TooManyArgs { count: 3, max: 2 });
+    let nested = OscPacket::Bundle(OscBundle {
+        timetag: (1, 0).into(),
+        content: vec![OscPacket::Bundle(OscBundle {
+            timetag: (1, 0).into(),
+            content: vec![],
+        })],
+    });
+    assert_eq!(root_cause(&nested), DecodeError::BundleTooDeep { max: 1 });
+
+    let mut buf = encoder::encode(&msg).unwrap();
+    buf.push(0);
+    assert_eq!(
+        decoder::decode_lazy(&buf).unwrap_err(),
+        DecodeError::Misaligned { len: buf.len() }
+    );
+    let lenient = DecoderOptions {
+        allow_trailing_bytes: true,
+        ..Default::default()
+    };
+    let packet = decoder::decode_lazy_with_options(&buf, &lenient).unwrap();
+    assert_eq!(packet.to_owned().unwrap(), msg);
+}
+
+#[test]
+fn test_iter_bundle_elements() {
+    use rosc::decoder::ElementKind;
+
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (1, 2).into(),
+        content: vec![
+            OscPacket::Message(OscMessage::from("/first")),
+            OscPacket::Bundle(OscBundle {
+                timetag: (3, 4).into(),
+                content: vec![OscPacket::Message(OscMessage {
+                    addr: "/second".to_string(),
+                    args: vec![2i32.into()],
+                })],
+            }),
+        ],
+    });
+    let buf = encoder::encode(&packet).unwrap();
+
+    let elements = decoder::iter_bundle_elements(&buf).unwrap();
+    assert_eq!(elements.timetag(), (1, 2).into());
+    let elements: Vec<_> = elements.collect::<Result<_, _>>().unwrap();
+    assert_eq!(
+        elements,
+        vec![
+            (&buf[20..32], ElementKind::Message),
+            (&buf[36..72], ElementKind::Bundle),
+        ]
+    );
+    assert_eq!(decoder::peek_address(elements[0].0).unwrap(), "/first");
+    let nested = decoder::iter_bundle_elements(elements[1].0).unwrap();
+    assert_eq!(nested.timetag(), (3, 4).into());
+    let nested: Vec<_> = nested.collect::<Result<_, _>>().unwrap();
+    assert_eq!(nested, vec![(&buf[56..72], ElementKind::Message)]);
+    assert_eq!(decoder::peek_address(nested[0].0).unwrap(), "/second");
+
+    // Errors carry the offset of the offending field
+    let error = |buf: &[u8]| match decoder::iter_bundle_elements(buf)
+        .and_then(|elements| elements.collect::<Result<Vec<_>, _>>())
+        .unwrap_err()
+    {
+        DecodeError::At {
+            offset, context, ..
+        } => (offset, context),
+        err => panic!("unexpected error: {:?}", err),
+    };
+    let corrupt = |offset: usize, bytes: &[u8]| {
+        let mut corrupt = buf.clone();
+        corrupt[offset..offset + bytes.len()].copy_from_slice(bytes);
+        corrupt
+    };
+    // Announce a larger second element than present
+    assert_eq!(
+        error(&corrupt(32, &1000u32.to_be_bytes())),
+        (32, "size of bundle element 1".to_string())
+    );
+    // Announce a first element overlapping the size of the second one
+    assert_eq!(
+        error(&corrupt(16, &14u32.to_be_bytes())),
+        (16, "size of bundle element 0".to_string())
+    );
+    assert_eq!(
+        error(&corrupt(16, &16u32.to_be_bytes())),
+        (36, "size of bundle element 1".to_string())
+    );
+    assert_eq!(
+        error(&corrupt(20, b"x")),
+        (20, "address in bundle element 0".to_string())
+    );
+    assert_eq!(error(&buf[..12]), (8, "time tag of bundle".to_string()));
+    assert_eq!(error(&buf[..34]), (32, "size of bundle element 1".to_string()));
+    assert_eq!(error(elements[0].0), (0, "bundle header".to_string()));
+}
+
+#[test]
+fn test_decode_enforces_bundle_time_monotonicity() {
+    let options = DecoderOptions {
+        enforce_bundle_time_monotonicity: true,
+        ..Default::default()
+    };
+    let bundle = |timetag: (u32, u32), content: Vec<OscPacket>| {
+        OscPacket::Bundle(OscBundle {
+            timetag: timetag.into(),
+            content,
+        })
+    };
+    let packet = bundle(
+        (100, 0),
+        vec![
+            OscPacket::Message(OscMessage::from("/a")),
+            // Immediate bundles take effect at the time of the enclosing bundle
+            bundle((0, 1), vec![bundle((50, 0), vec![])]),
+        ],
+    );
+    let buf = encoder::encode(&packet).unwrap();
+    assert_eq!(decoder::decode_udp(&buf).unwrap().1, packet);
+    match decoder::decode_with_options(&buf, &options).unwrap_err() {
+        DecodeError::At {
+            offset,
+            context,
+            source,
+            ..
+        } => {
+            assert_eq!(offset, 60);
+            assert_eq!(context, "time tag of bundle in bundle element 1.0");
+            assert_eq!(
+                *source,
+                DecodeError::BundleTimeNotMonotonic {
+                    element: 0,
+                    timetag: (50, 0).into(),
+                    parent: (100, 0).into(),
+                }
+            );
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+
+    let valid = vec![
+        bundle((100, 0), vec![bundle((100, 0), vec![]), bundle((0, 1), vec![])]),
+        bundle((0, 1), vec![bundle((0, 0), vec![bundle((1, 0), vec![])])]),
+    ];
+    for packet in valid {
+        let buf = encoder::encode(&packet).unwrap();
+        assert_eq!(decoder::decode_with_options(&buf, &options).unwrap(), packet);
+    }
+
+    // `(0, 0)` is only immediate if the decoder is told so
+    let buf = encoder::encode(&bundle((1, 0), vec![bundle((0, 0), vec![])])).unwrap();
+    assert_eq!(
+        decoder::decode_with_options(&buf, &options).unwrap_err().root_cause(),
+        &DecodeError::BundleTimeNotMonotonic {
+            element: 0,
+            timetag: (0, 0).into(),
+            parent: (1, 0).into(),
+        }
+    );
+    let options = DecoderOptions {
+        zero_timetag_is_immediate: true,
+        ..options
+    };
+    decoder::decode_with_options(&buf, &options).unwrap();
+}
+
+#[test]
+fn test_decode_trailing_newline() {
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (0, 1).into(),
+        content: vec![OscPacket::Message(OscMessage {
+            addr: "/sensor".to_string(),
+            args: vec![0.5f32.into()],
+        })],
+    });
+    let mut buf = encoder::encode(&packet).unwrap();
+    buf.push(b'\n');
+
+    match decoder::decode_with_options(&buf, &DecoderOptions::default()) {
+        Err(DecodeError::Misaligned { len }) => assert_eq!(len, buf.len()),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let lenient = DecoderOptions {
+        allow_trailing_bytes: true,
+        ..Default::default()
+    };
+    assert_eq!(decoder::decode_with_options(&buf, &lenient).unwrap(), packet);
+
+    // Only the padding to the next multiple of 4 is ignored
+    buf.extend(b"\n\n\n\n");
+    assert!(decoder::decode_with_options(&buf, &lenient).is_err());
+}
+
+#[test]
+fn test_parse_text() {
+    assert_eq!(
+        decoder::parse_text("/mixer/1/gain if 3 0.5").unwrap(),
+        OscMessage {
+            addr: "/mixer/1/gain".to_string(),
+            args: vec![3i32.into(), 0.5f32.into()],
+        }
+    );
+    assert_eq!(
+        decoder::parse_text("  /ping  ").unwrap(),
+        OscMessage::from("/ping")
+    );
+
+    let msg = decoder::parse_text(
+        r#"/all ,hdscbtTFNI[i[]]mr -5 0.25 "quoted \"string\"\n" x 0xdeadbeef @3.4 7 0x01903c7f 0x0a0b0c0d"#,
+    )
+    .unwrap();
+    assert_eq!(
+        msg.args,
+        vec![
+            (-5i64).into(),
+            0.25f64.into(),
+            "quoted \"string\"\n".into(),
+            'x'.into(),
+            vec![0xdeu8, 0xad, 0xbe, 0xef].into(),
+            OscType::Time((3, 4).into()),
+            true.into(),
+            false.into(),
+            OscType::Nil,
+            OscType::Impulse,
+            OscArray {
+                content: vec![7i32.into(), OscArray { content: vec![] }.into()],
+            }
+            .into(),
+            OscType::Midi(rosc::OscMidiMessage {
+                port: 1,
+                status: 0x90,
+                data1: 0x3c,
+                data2: 0x7f,
+            }),
+            OscType::Color(rosc::OscColor {
+                red: 10,
+                green: 11,
+                blue: 12,
+                alpha: 13,
+            }),
+        ]
+    );
+
+    #[cfg(feature = "std")]
+    match decoder::parse_text("/time t @now").unwrap().args[0] {
+        OscType::Time(_) => {}
+        ref other => panic!("unexpected argument: {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_text_errors() {
+    let column = |line| match decoder::parse_text(line) {
+        Err(DecodeError::BadText { column, .. }) => column,
+        other => panic!("unexpected result for {:?}: {:?}", line, other),
+    };
+    assert_eq!(column(""), 1);
+    assert_eq!(column("mixer if 3 0.5"), 1);
+    assert_eq!(column("/mixer if x 0.5"), 11);
+    assert_eq!(column("/mixer if 3 zero"), 13);
+    assert_eq!(column("/mixer if 3"), 12);
+    assert_eq!(column("/mixer i 3 4"), 12);
+    assert_eq!(column("/mixer ,iX 3"), 10);
+    assert_eq!(column("/mixer i] 3"), 9);
+    assert_eq!(column("/mixer b 0xabc"), 10);
+    assert_eq!(column("/mixer b 0xzz"), 10);
+    assert_eq!(column("/mixer s \"unterminated"), 10);
+    assert_eq!(column("/mixer s \"bad \\q escape\""), 15);
+    assert_eq!(column("/mixer t 12:00"), 10);
+    assert_eq!(
+        decoder::parse_text("/mixer if x 0.5").unwrap_err().to_string(),
+        "bad OSC text at column 11: Invalid int argument"
+    );
+}
+
+#[test]
+fn test_decode_lazy_deeply_nested_bundles() {
+    let buf = nested_bundles(10_000);
+    let mut packet = decoder::decode_lazy(&buf).unwrap();
+    let mut depth = 1;
+    loop {
+        let element = match packet {
+            LazyPacket::Bundle(bundle) => bundle.elements().next().unwrap(),
+            LazyPacket::Message(_) => panic!("unexpected message"),
+        };
+        match element {
+            Ok(element) => packet = element,
+            Err(err) => {
+                match err.root_cause() {
+                    DecodeError::BundleTooDeep { max } => {
+                        assert_eq!(*max, decoder::MAX_BUNDLE_DEPTH)
+                    }
+                    other => panic!("unexpected error: {:?}", other),
+                }
+                break;
+            }
+        }
+        depth += 1;
+    }
+    assert_eq!(depth, decoder::MAX_BUNDLE_DEPTH);
+    assert!(decoder::decode_lazy(&buf).unwrap().to_owned().is_err());
+}
+
+#[test]
+fn test_parse_text_deeply_nested_arrays() {
+    let depth = decoder::MAX_ARRAY_DEPTH + 1;
+    let line = format!("/a {}{}", "[".repeat(depth), "]".repeat(depth));
+    assert!(decoder::parse_text(&line).is_err());
+}
+
+#[test]
+fn test_zero_timetag_is_immediate() {
+    let bundle = |timetag: OscTime| {
+        encoder::encode(&OscPacket::Bundle(OscBundle {
+            timetag,
+            content: vec![OscPacket::Bundle(OscBundle {
+                timetag,
+                content: vec![],
+            })],
+        }))
+        .unwrap()
+    };
+    let options = DecoderOptions {
+        zero_timetag_is_immediate: true,
+        ..Default::default()
+    };
+    let timetags = |packet| match packet {
+        OscPacket::Bundle(outer) => match outer.content[0] {
+            OscPacket::Bundle(ref inner) => (outer.timetag, inner.timetag),
+            _ => panic!("unexpected message"),
+        },
+        _ => panic!("unexpected message"),
+    };
+
+    let zero = OscTime::from((0, 0));
+    let packet = decoder::decode_with_options(&bundle(zero), &options).unwrap();
+    assert_eq!(timetags(packet), (OscTime::IMMEDIATE, OscTime::IMMEDIATE));
+
+    let packet = decoder::decode_with_options(&bundle(zero), &DecoderOptions::default()).unwrap();
+    assert_eq!(timetags(packet), (zero, zero));
+
+    let later = OscTime::from((0, 2));
+    let packet = decoder::decode_with_options(&bundle(later), &options).unwrap();
+    assert_eq!(timetags(packet), (later, later));
+}
+
+/// Packets in the format of a sender that omits the null word after strings whose length is a
+/// multiple of 4.
+const UNPADDED: &[(&str, &[u8])] = &[
+    ("address", b"/abc,i\0\0\0\0\0\x01"),
+    ("type tags", b"/test\0\0\0,iis\0\0\0\x01\0\0\0\x02abcd"),
+    ("type tags followed by float", b"/test\0\0\0,fff?\x80\0\0\0\0\0\0\0\0\0\0"),
+    ("string argument", b"/test\0\0\0,si\0abcd\0\0\0\x03"),
+];
+
+#[test]
+fn test_decode_unpadded_strings() {
+    let options = DecoderOptions {
+        allow_unpadded_strings: true,
+        ..Default::default()
+    };
+    let expected = [
+        OscMessage {
+            addr: "/abc".to_string(),
+            args: vec![OscType::Int(1)],
+        },
+        OscMessage {
+            addr: "/test".to_string(),
+            args: vec![1i32.into(), 2i32.into(), "abcd".into()],
+        },
+        OscMessage {
+            addr: "/test".to_string(),
+            args: vec![1.0f32.into(), 0.0f32.into(), 0.0f32.into()],
+        },
+        OscMessage {
+            addr: "/test".to_string(),
+            args: vec!["abcd".into(), 3i32.into()],
+        },
+    ];
+    for ((name, packet), expected) in UNPADDED.iter().zip(expected) {
+        assert!(decoder::decode_udp(packet).is_err(), "{}: strict", name);
+        match decoder::decode_with_options(packet, &options) {
+            Ok(OscPacket::Message(msg)) => assert_eq!(msg, expected, "{}", name),
+            other => panic!("{}: unexpected result: {:?}", name, other),
+        }
+    }
+
+    // Padded strings are unaffected
+    let packet = encoder::encode(&OscPacket::Message(OscMessage {
+        addr: "/a,b".to_string(),
+        args: vec!["abcd".into(), "e".into()],
+    }))
+    .unwrap();
+    let msg = decoder::decode_with_options(&packet, &options).unwrap();
+    assert_eq!(msg, decoder::decode_udp(&packet).unwrap().1);
+}
+
+#[test]
+fn test_midi_and_color_arguments() {
+    let msg = OscMessage {
+        addr: "/m".to_string(),
+        args: vec![
+            OscMidiMessage {
+                port: 1,
+                status: 0x90,
+                data1: 60,
+                data2: 127,
+            }
+            .into(),
+            OscColor {
+                red: 0x11,
+                green: 0x22,
+                blue: 0x33,
+                alpha: 0x44,
+            }
+            .into(),
+        ],
+    };
+    let packet = encoder::encode(&OscPacket::Message(msg.clone())).unwrap();
+    assert_eq!(&packet[4..], b",mr\0\x01\x90\x3c\x7f\x11\x22\x33\x44");
+    assert_eq!(
+        decoder::decode_udp(&packet).unwrap().1,
+        OscPacket::Message(msg)
+    );
+
+    // The truncated argument is the last one
+    for (type_tags, arg_index) in [(",m", 0), (",mr", 1)] {
+        let mut packet = encoder::encode_string("/m");
+        packet.extend(encoder::encode_string(type_tags));
+        packet.extend(vec![0u8; 4 * arg_index]);
+        match decoder::decode_with_options(&packet, &DecoderOptions::default())
+            .as_ref()
+            .map_err(DecodeError::root_cause)
+        {
+            Err(DecodeError::Truncated {
+                expected: 4,
+                remaining: 0,
+                arg_index: index,
+            }) => assert_eq!(*index, arg_index, "{}", type_tags),
+            other => panic!("{}: unexpected result: {:?}", type_tags, other),
+        }
+        assert!(decoder::decode_udp(&packet).is_err(), "{}", type_tags);
+    }
+}
+
+#[test]
+fn test_decode_unprefixed_address() {
+    let packet = b"ping\0\0\0\0,i\0\0\0\0\0\x01";
+    assert!(decoder::decode_udp(packet).is_err());
+
+    let options = DecoderOptions {
+        allow_unprefixed_addresses: true,
+        ..Default::default()
+    };
+    match decoder::decode_with_options(packet, &options) {
+        Ok(OscPacket::Message(msg)) => {
+            assert_eq!(msg.addr, "ping");
+            assert_eq!(msg.args, vec![OscType::Int(1)]);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(decoder::decode_with_options(b"#bun\0\0\0\0,\0\0\0", &options).is_err());
+}
+
+#[test]
+fn test_unsigned_extension_tag() {
+    let msg = OscMessage {
+        addr: "/u".to_string(),
+        args: vec![
+            OscType::Unsigned(u32::MAX),
+            OscType::Array(OscArray {
+                content: vec![OscType::Unsigned(1)],
+            }),
+        ],
+    };
+    let packet = encoder::encode(&OscPacket::Message(msg.clone())).unwrap();
+    assert_eq!(&packet[4..], b",u[u]\0\0\0\xff\xff\xff\xff\0\0\0\x01");
+
+    // Strict decoding rejects the nonstandard tag
+    assert!(decoder::decode_udp(&packet).is_err());
+    let err = decoder::decode_with_options(&packet, &DecoderOptions::default()).unwrap_err();
+    assert_eq!(err.root_cause(), &DecodeError::UnknownTypeTag('u'));
+    let others = DecoderOptions {
+        accept_extension_tags: &['x'],
+        ..Default::default()
+    };
+    let err = decoder::decode_with_options(&packet, &others).unwrap_err();
+    assert_eq!(err.root_cause(), &DecodeError::UnknownTypeTag('u'));
+
+    let options = DecoderOptions {
+        accept_extension_tags: &['u'],
+        ..Default::default()
+    };
+    let decoded = decoder::decode_with_options(&packet, &options).unwrap();
+    assert_eq!(decoded, OscPacket::Message(msg.clone()));
+    assert_eq!(encoder::encode(&decoded).unwrap(), packet);
+    assert_eq!(decoder::parse_text("/u u[u] 4294967295 1").unwrap(), msg);
+    assert!(decoder::parse_text("/u u -1").is_err());
+
+    // A truncated argument is reported like for the standard tags
+    let mut truncated = encoder::encode_string("/u");
+    truncated.extend(encoder::encode_string(",u"));
+    match decoder::decode_with_options(&truncated, &options)
+        .as_ref()
+        .map_err(DecodeError::root_cause)
+    {
+        Err(DecodeError::Truncated {
+            expected: 4,
+            remaining: 0,
+            arg_index: 0,
+        }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+/// Encodes a bundle at time (1, 0) of the given raw elements.
+fn raw_bundle(elements: &[Vec<u8>]) -> Vec<u8> {
+    let mut buf = encoder::encode_string("#bundle");
+    buf.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 0]);
+    for element in elements {
+        buf.extend_from_slice(&(element.len() as u32).to_be_bytes());
+        buf.extend_from_slice(element);
+    }
+    buf
+}
+
+fn raw_message(addr: &str) -> Vec<u8> {
+    encoder::encode(&OscPacket::Message(OscMessage::from(addr))).unwrap()
+}
+
+#[test]
+fn test_decode_bundle_skip_element() {
+    let mut corrupt = encoder::encode_string("/corrupt");
+    corrupt.extend(encoder::encode_string(",x"));
+    let skip = DecoderOptions {
+        bundle_error_policy: BundleErrorPolicy::SkipElement,
+        ..Default::default()
+    };
+    let messages = |addrs: &[&str]| -> Vec<OscPacket> {
+        addrs
+            .iter()
+            .map(|addr| OscPacket::Message(OscMessage::from(*addr)))
+            .collect()
+    };
+
+    for position in 0..3 {
+        let mut elements = vec![raw_message("/a"), raw_message("/b")];
+        elements.insert(position, corrupt.clone());
+        let buf = raw_bundle(&elements);
+
+        let err = decoder::decode_with_options(&buf, &DecoderOptions::default()).unwrap_err();
+        assert_eq!(err.root_cause(), &DecodeError::UnknownTypeTag('x'));
+        let decoded = decoder::decode_bundle(&buf, &DecoderOptions::default());
+        assert_eq!(decoded.unwrap_err().root_cause(), &DecodeError::UnknownTypeTag('x'));
+
+        let decoded = decoder::decode_bundle(&buf, &skip).unwrap();
+        assert_eq!(decoded.bundle.timetag, (1, 0).into());
+        assert_eq!(decoded.bundle.content, messages(&["/a", "/b"]));
+        assert_eq!(decoded.errors.len(), 1);
+        let (index, ref err) = decoded.errors[0];
+        assert_eq!(index, position);
+        assert_eq!(err.root_cause(), &DecodeError::UnknownTypeTag('x'));
+        match err {
+            DecodeError::At { context, .. } => {
+                let suffix = format!("in bundle element {}", position);
+                assert!(context.ends_with(&suffix), "{}", context)
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+        // Other decoding functions drop the skipped elements
+        let packet = decoder::decode_with_options(&buf, &skip).unwrap();
+        assert_eq!(packet, OscPacket::Bundle(decoded.bundle));
+    }
+
+    // Nested bundles keep their good elements and are reported by the outermost index
+    let nested = raw_bundle(&[raw_message("/n1"), corrupt.clone(), raw_message("/n2")]);
+    let buf = raw_bundle(&[raw_message("/a"), nested, corrupt.clone()]);
+    let decoded = decoder::decode_bundle(&buf, &skip).unwrap();
+    let expected = vec![
+        OscPacket::Message(OscMessage::from("/a")),
+        OscPacket::Bundle(OscBundle {
+            timetag: (1, 0).into(),
+            content: messages(&["/n1", "/n2"]),
+        }),
+    ];
+    assert_eq!(decoded.bundle.content, expected);
+    let indices: Vec<usize> = decoded.errors.iter().map(|(index, _)| *index).collect();
+    assert_eq!(indices, vec![1, 2]);
+    match &decoded.errors[0].1 {
+        DecodeError::At { context, .. } => assert!(context.ends_with("in bundle element 1.1")),
+        err => panic!("unexpected error: {:?}", err),
+    }
+
+    // A nested bundle failing as a whole is skipped like a message
+    let truncated = encoder::encode_string("#bundle");
+    let buf = raw_bundle(&[truncated, raw_message("/a")]);
+    let decoded = decoder::decode_bundle(&buf, &skip).unwrap();
+    assert_eq!(decoded.bundle.content, messages(&["/a"]));
+    assert_eq!(decoded.errors[0].0, 0);
+
+    // An invalid size ends the bundle, as the following elements can't be found
+    let mut buf = raw_bundle(&[raw_message("/a"), raw_message("/b"), raw_message("/c")]);
+    let size_offset = 16 + 4 + raw_message("/a").len();
+    buf[size_offset..size_offset + 4].copy_from_slice(&1000u32.to_be_bytes());
+    let decoded = decoder::decode_bundle(&buf, &skip).unwrap();
+    assert_eq!(decoded.bundle.content, messages(&["/a"]));
+    assert_eq!(decoded.errors.len(), 1);
+    assert_eq!(decoded.errors[0].0, 1);
+    assert!(matches!(decoded.errors[0].1.root_cause(), DecodeError::BadBundle(_)));
+
+    // Only bundles are accepted, and a corrupt bundle header is still fatal
+    let decoded = decoder::decode_bundle(&raw_message("/a"), &skip);
+    assert!(matches!(decoded.unwrap_err().root_cause(), DecodeError::BadBundleHeader(_)));
+    let decoded = decoder::decode_bundle(&encoder::encode_string("#bundle"), &skip);
+    assert!(decoded.is_err());
+}
+
+#[test]
+fn test_decode_high_byte_chars() {
+    let mut buf = encoder::encode_string("/char");
+    buf.extend(encoder::encode_string(",cc"));
+    buf.extend_from_slice(&[0x41, 0, 0, 0, 0, 0, 0, 0x7a]);
+    let expected = OscPacket::Message(OscMessage {
+        addr: "/char".to_string(),
+        args: vec![OscType::Char('A'), OscType::Char('z')],
+    });
+
+    let err = decoder::decode_with_options(&buf, &DecoderOptions::default()).unwrap_err();
+    assert_eq!(err.root_cause(), &DecodeError::InvalidChar(0x4100_0000));
+    let options = DecoderOptions {
+        allow_high_byte_chars: true,
+        ..Default::default()
+    };
+    assert_eq!(decoder::decode_with_options(&buf, &options), Ok(expected));
+
+    // Only ASCII codes are taken from the first byte
+    let len = buf.len();
+    buf[len - 8] = 0xe9;
+    let err = decoder::decode_with_options(&buf, &options).unwrap_err();
+    assert_eq!(err.root_cause(), &DecodeError::InvalidChar(0xe900_0000));
+    buf[len - 5] = 1;
+    let err = decoder::decode_with_options(&buf, &options).unwrap_err();
+    assert_eq!(err.root_cause(), &DecodeError::InvalidChar(0xe900_0001));
+}
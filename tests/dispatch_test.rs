@@ -0,0 +1,834 @@
+extern crate rosc;
+
+#[cfg(feature = "std")]
+use rosc::dispatch::{
+    Access, AddressSpace, MethodInfo, NamespaceChange, RouteTable, SharedAddressSpace, TriggerArgs,
+    ValueRange,
+};
+#[cfg(feature = "std")]
+use rosc::{OscBundle, OscMessage, OscPacket};
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex};
+
+/// Returns an address space whose handlers record the address of the method they belong to.
+#[cfg(feature = "std")]
+fn recording(addrs: &[&'static str]) -> (AddressSpace, Arc<Mutex<Vec<&'static str>>>) {
+    let calls = Arc::new(Mutex::new(vec![]));
+    let mut space = AddressSpace::new();
+    for &addr in addrs {
+        let calls = calls.clone();
+        space
+            .add_method(addr, move |_| calls.lock().unwrap().push(addr))
+            .unwrap();
+    }
+    (space, calls)
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_dispatch() {
+    let (mut space, calls) = recording(&["/a/1", "/a/2", "/b/1", "/a/1"]);
+    assert_eq!(space.dispatch(&OscMessage::from("/a/1")).unwrap(), 2);
+    assert_eq!(space.dispatch(&OscMessage::from("/*/1")).unwrap(), 3);
+    assert_eq!(space.dispatch(&OscMessage::from("/c")).unwrap(), 0);
+    assert!(space.dispatch(&OscMessage::from("no/slash")).is_err());
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec!["/a/1", "/a/1", "/a/1", "/b/1", "/a/1"]
+    );
+
+    assert_eq!(space.remove_method("/a/1"), 2);
+    assert_eq!(space.addresses().collect::<Vec<_>>(), vec!["/a/2", "/b/1"]);
+    assert!(space.add_method("/a/*", |_| {}).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_dispatch_root() {
+    let (mut space, calls) = recording(&["/", "/x"]);
+    assert_eq!(space.dispatch(&OscMessage::from("/")).unwrap(), 1);
+    assert_eq!(space.dispatch(&OscMessage::from("/*")).unwrap(), 1);
+    assert!(space.dispatch(&OscMessage::from("//")).is_err());
+    assert_eq!(*calls.lock().unwrap(), vec!["/", "/x"]);
+
+    // The method at `/` describes the root container
+    let json = space.to_oscquery_json();
+    assert!(json.starts_with("{\n  \"FULL_PATH\": \"/\",\n  \"ACCESS\""), "{}", json);
+    assert!(!json.contains(r#""": {"#), "{}", json);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_dispatch_packet() {
+    let (mut space, calls) = recording(&["/a", "/b", "/c"]);
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (1, 0).into(),
+        content: vec![
+            OscPacket::Message(OscMessage::from("/c")),
+            OscPacket::Bundle(OscBundle {
+                timetag: (2, 0).into(),
+                content: vec![OscPacket::Message(OscMessage::from("/{a,b}"))],
+            }),
+            OscPacket::Message(OscMessage::from("/a")),
+        ],
+    });
+    assert_eq!(space.dispatch_packet(&packet).unwrap(), 4);
+    assert_eq!(*calls.lock().unwrap(), vec!["/c", "/a", "/b", "/a"]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_oscquery_json() {
+    let mut space = AddressSpace::new();
+    for addr in &["/mixer/2/gain", "/mixer/1/gain", "/mixer/1/mute", "/transport/play"] {
+        space.add_method(addr, |_| {}).unwrap();
+    }
+    let gain = MethodInfo {
+        type_tags: Some("f".to_string()),
+        range: vec![ValueRange {
+            min: Some(0.0),
+            max: Some(1.5),
+        }],
+        description: Some("Gain of \"channel\" 1".to_string()),
+        access: Access::ReadWrite,
+    };
+    space.describe_method("/mixer/1/gain", gain.clone()).unwrap();
+    space
+        .describe_method(
+            "/mixer/1/mute",
+            MethodInfo {
+                type_tags: Some("T".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(space.method_info("/mixer/1/gain"), Some(&gain));
+    assert!(space.describe_method("/mixer/3/gain", gain).is_err());
+
+    assert_eq!(
+        space.to_oscquery_json(),
+        include_str!("data/oscquery_namespace.json")
+    );
+
+    space.remove_method("/mixer/1/gain");
+    assert_eq!(space.method_info("/mixer/1/gain"), None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_namespace_changes() {
+    let hooked = Arc::new(Mutex::new(vec![]));
+    let mut space = AddressSpace::new();
+    {
+        let hooked = hooked.clone();
+        space.add_change_hook(move |generation, change| {
+            hooked.lock().unwrap().push((generation, change.clone()))
+        });
+    }
+    assert_eq!(space.generation(), 0);
+    space.add_method("/mixer/1/gain", |_| {}).unwrap();
+    space.add_method("/mixer/1/gain", |_| {}).unwrap();
+    space.add_method("/transport/play", |_| {}).unwrap();
+    assert!(space.add_method("/bad address", |_| {}).is_err());
+    assert_eq!(space.generation(), 2);
+    assert_eq!(space.remove_method("/mixer/1/gain"), 2);
+    assert_eq!(space.remove_method("/mixer/1/gain"), 0);
+    assert_eq!(space.generation(), 3);
+
+    let added = |addr: &str| NamespaceChange::Added(addr.to_string());
+    let removed = |addr: &str| NamespaceChange::Removed(addr.to_string());
+    assert_eq!(
+        space.changes_since(0),
+        vec![added("/mixer/1/gain"), added("/transport/play"), removed("/mixer/1/gain")]
+    );
+    assert_eq!(space.changes_since(2), vec![removed("/mixer/1/gain")]);
+    assert_eq!(space.changes_since(3), vec![]);
+    assert_eq!(space.changes_since(100), vec![]);
+    assert_eq!(
+        *hooked.lock().unwrap(),
+        vec![
+            (1, added("/mixer/1/gain")),
+            (2, added("/transport/play")),
+            (3, removed("/mixer/1/gain")),
+        ]
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_namespace_changes_from_threads() {
+    use std::collections::BTreeSet;
+    use std::thread;
+
+    let space = Arc::new(Mutex::new(AddressSpace::new()));
+    let threads: Vec<_> = (0..4)
+        .map(|thread| {
+            let space = space.clone();
+            thread::spawn(move || {
+                for i in 0..50 {
+                    let addr = format!("/thread/{}/method/{}", thread, i);
+                    space.lock().unwrap().add_method(&addr, |_| {}).unwrap();
+                    if i % 3 == 0 {
+                        space.lock().unwrap().remove_method(&addr);
+                    }
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let space = space.lock().unwrap();
+    let changes = space.changes_since(0);
+    assert_eq!(space.generation(), changes.len() as u64);
+    assert_eq!(changes.len(), 4 * (50 + 17));
+    // Replaying the log in order gives the namespace
+    let mut namespace = BTreeSet::new();
+    for change in &changes {
+        match change {
+            NamespaceChange::Added(addr) => assert!(namespace.insert(addr.clone()), "{}", addr),
+            NamespaceChange::Removed(addr) => assert!(namespace.remove(addr), "{}", addr),
+        }
+    }
+    let addresses: BTreeSet<String> = space.addresses().map(str::to_string).collect();
+    assert_eq!(namespace, addresses);
+    assert_eq!(addresses.len(), 4 * 33);
+    assert_eq!(space.changes_since(100), changes[100..].to_vec());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_oscquery_json_at() {
+    let mut space = AddressSpace::new();
+    for addr in &["/mixer/2/gain", "/mixer/1/gain", "/mixer/1/mute", "/transport/play"] {
+        space.add_method(addr, |_| {}).unwrap();
+    }
+    assert_eq!(space.to_oscquery_json_at("/").unwrap(), space.to_oscquery_json());
+    assert_eq!(
+        space.to_oscquery_json_at("/mixer/1").unwrap(),
+        concat!(
+            "{\n",
+            "  \"FULL_PATH\": \"/mixer/1\",\n",
+            "  \"CONTENTS\": {\n",
+            "    \"gain\": {\n",
+            "      \"FULL_PATH\": \"/mixer/1/gain\",\n",
+            "      \"ACCESS\": 2\n",
+            "    },\n",
+            "    \"mute\": {\n",
+            "      \"FULL_PATH\": \"/mixer/1/mute\",\n",
+            "      \"ACCESS\": 2\n",
+            "    }\n",
+            "  }\n",
+            "}\n",
+        )
+    );
+    assert_eq!(
+        space.to_oscquery_json_at("/transport/play").unwrap(),
+        "{\n  \"FULL_PATH\": \"/transport/play\",\n  \"ACCESS\": 2\n}\n"
+    );
+    assert_eq!(space.to_oscquery_json_at("/mixer/3"), None);
+    assert_eq!(space.to_oscquery_json_at("mixer"), None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_typed_methods() {
+    use rosc::{OscError, OscType};
+
+    let calls = Arc::new(Mutex::new(vec![]));
+    let errors = Arc::new(Mutex::new(vec![]));
+    let mut space = AddressSpace::new();
+    {
+        let calls = calls.clone();
+        space
+            .add_typed_method("/fader/1/level", move |level: f32| {
+                calls.lock().unwrap().push(format!("level {}", level))
+            })
+            .unwrap();
+    }
+    {
+        let calls = calls.clone();
+        space
+            .add_typed_method("/fader/1/label", move |label: String, color: i32| {
+                calls.lock().unwrap().push(format!("label {} {}", label, color))
+            })
+            .unwrap();
+    }
+    {
+        let calls = calls.clone();
+        space
+            .add_typed_method("/fader/1/reset", move || calls.lock().unwrap().push("reset".into()))
+            .unwrap();
+    }
+    {
+        let errors = errors.clone();
+        space.set_error_handler(move |msg, err| {
+            errors.lock().unwrap().push((msg.addr.clone(), err.clone()))
+        });
+    }
+    let msg = |addr: &str, args: Vec<OscType>| OscMessage {
+        addr: addr.to_string(),
+        args,
+    };
+
+    assert_eq!(space.dispatch(&msg("/fader/1/level", vec![0.5f32.into()])).unwrap(), 1);
+    assert_eq!(
+        space.dispatch(&msg("/fader/1/label", vec!["vox".into(), 3.into()])).unwrap(),
+        1
+    );
+    assert_eq!(space.dispatch(&msg("/fader/1/reset", vec![])).unwrap(), 1);
+    assert_eq!(*calls.lock().unwrap(), vec!["level 0.5", "label vox 3", "reset"]);
+    assert!(errors.lock().unwrap().is_empty());
+
+    // Wrong argument types
+    assert_eq!(space.dispatch(&msg("/fader/1/level", vec![1.into()])).unwrap(), 0);
+    // Wrong argument count, matching all three methods
+    assert_eq!(space.dispatch(&msg("/fader/1/*", vec![0.5f32.into()])).unwrap(), 1);
+    assert_eq!(calls.lock().unwrap().len(), 4);
+    assert_eq!(
+        *errors.lock().unwrap(),
+        vec![
+            (
+                "/fader/1/level".to_string(),
+                OscError::BadArg("Expected Float argument, found Int(1)".to_string())
+            ),
+            (
+                "/fader/1/*".to_string(),
+                OscError::BadArg("Expected 2 arguments, found 1".to_string())
+            ),
+            (
+                "/fader/1/*".to_string(),
+                OscError::BadArg("Expected 0 arguments, found 1".to_string())
+            ),
+        ]
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_trigger_methods() {
+    use rosc::{OscError, OscType};
+
+    let calls = Arc::new(Mutex::new(vec![]));
+    let errors = Arc::new(Mutex::new(vec![]));
+    let mut space = AddressSpace::new();
+    for &(addr, args) in &[("/strict", TriggerArgs::None), ("/lenient", TriggerArgs::NilOrImpulse)]
+    {
+        let calls = calls.clone();
+        space
+            .add_trigger_method(addr, args, move || calls.lock().unwrap().push(addr))
+            .unwrap();
+    }
+    {
+        let errors = errors.clone();
+        space.set_error_handler(move |msg, err| {
+            errors.lock().unwrap().push((msg.addr.clone(), err.clone()))
+        });
+    }
+    let msg = |addr: &str, args: Vec<OscType>| OscMessage {
+        addr: addr.to_string(),
+        args,
+    };
+
+    assert_eq!(space.dispatch(&OscMessage::trigger("/*")).unwrap(), 2);
+    assert_eq!(*calls.lock().unwrap(), vec!["/strict", "/lenient"]);
+
+    // Only the lenient method accepts nil and impulse arguments
+    assert_eq!(space.dispatch(&msg("/*", vec![OscType::Nil, OscType::Impulse])).unwrap(), 1);
+    // Neither accepts values
+    assert_eq!(space.dispatch(&msg("/*", vec![OscType::Int(1)])).unwrap(), 0);
+    assert_eq!(*calls.lock().unwrap(), vec!["/strict", "/lenient", "/lenient"]);
+    let bad_arg = |args: &str| {
+        OscError::BadArg(format!("Expected a trigger without values, found the arguments {}", args))
+    };
+    assert_eq!(
+        *errors.lock().unwrap(),
+        vec![
+            ("/*".to_string(), bad_arg("[Nil, Impulse]")),
+            ("/*".to_string(), bad_arg("[Int(1)]")),
+            ("/*".to_string(), bad_arg("[Int(1)]")),
+        ]
+    );
+
+    assert_eq!(space.dispatch_packet(&OscPacket::trigger("/strict")).unwrap(), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_typed_method_parameter_types() {
+    use rosc::{OscTime, OscType};
+
+    let received = Arc::new(Mutex::new(None));
+    let mut space = AddressSpace::new();
+    let recorded = received.clone();
+    space
+        .add_typed_method(
+            "/all",
+            move |a: i64, b: f64, c: bool, d: Vec<u8>, e: OscTime, f: char, g: OscType| {
+                *recorded.lock().unwrap() = Some((a, b, c, d, e, f, g));
+            },
+        )
+        .unwrap();
+    let args = vec![
+        OscType::Long(1),
+        OscType::Double(2.0),
+        OscType::Bool(true),
+        vec![3u8].into(),
+        OscType::Time((4, 5).into()),
+        OscType::Char('x'),
+        OscType::Nil,
+    ];
+    assert_eq!(
+        space.dispatch(&OscMessage { addr: "/all".to_string(), args }).unwrap(),
+        1
+    );
+    assert_eq!(
+        *received.lock().unwrap(),
+        Some((1, 2.0, true, vec![3], (4, 5).into(), 'x', OscType::Nil))
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hooks() {
+    use rosc::dispatch::{DispatchContext, HookAction};
+
+    let (mut space, calls) = recording(&["/a", "/b", "/b"]);
+    let hooks = Arc::new(Mutex::new(vec![]));
+    let recorded = hooks.clone();
+    space.add_pre_hook(move |msg, _| {
+        recorded.lock().unwrap().push(format!("first {}", msg.addr));
+        HookAction::Continue
+    });
+    let recorded = hooks.clone();
+    space.add_pre_hook(move |msg, _| {
+        recorded.lock().unwrap().push(format!("second {}", msg.addr));
+        match msg.addr.as_str() {
+            "/b" => HookAction::Drop,
+            _ => HookAction::Continue,
+        }
+    });
+    let recorded = hooks.clone();
+    space.add_pre_hook(move |msg, _| {
+        recorded.lock().unwrap().push(format!("third {}", msg.addr));
+        HookAction::Continue
+    });
+    let recorded = hooks.clone();
+    space.add_post_hook(move |msg, context, invoked, _| {
+        recorded
+            .lock()
+            .unwrap()
+            .push(format!("post {} {} {:?}", msg.addr, invoked, context.peer));
+    });
+
+    assert_eq!(space.dispatch(&OscMessage::from("/a")).unwrap(), 1);
+    assert_eq!(space.dispatch(&OscMessage::from("/b")).unwrap(), 0);
+    assert_eq!(space.dispatch(&OscMessage::from("/{a,b}")).unwrap(), 3);
+    let context = DispatchContext {
+        peer: Some("127.0.0.1:9000".parse().unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(space.dispatch_with_context(&OscMessage::from("/c"), &context).unwrap(), 0);
+    assert!(space.dispatch(&OscMessage::from("no/slash")).is_err());
+
+    assert_eq!(*calls.lock().unwrap(), vec!["/a", "/a", "/b", "/b"]);
+    assert_eq!(
+        *hooks.lock().unwrap(),
+        vec![
+            "first /a",
+            "second /a",
+            "third /a",
+            "post /a 1 None",
+            "first /b",
+            "second /b",
+            "first /{a,b}",
+            "second /{a,b}",
+            "third /{a,b}",
+            "post /{a,b} 3 None",
+            "first /c",
+            "second /c",
+            "third /c",
+            "post /c 0 Some(127.0.0.1:9000)",
+        ]
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_register_any() {
+    let (mut space, calls) = recording(&["/a", "/a/b/c"]);
+    let recorded = calls.clone();
+    let seen = Arc::new(Mutex::new(vec![]));
+    let tapped = seen.clone();
+    space.register_any(move |msg| {
+        recorded.lock().unwrap().push("any");
+        tapped.lock().unwrap().push(msg.addr.clone());
+    });
+    let fallbacks = Arc::new(Mutex::new(vec![]));
+    let unmatched = fallbacks.clone();
+    space.set_fallback_handler(move |msg, _, _| unmatched.lock().unwrap().push(msg.addr.clone()));
+
+    assert_eq!(space.dispatch(&OscMessage::from("/a")).unwrap(), 2);
+    assert_eq!(space.dispatch(&OscMessage::from("/a/b/c")).unwrap(), 2);
+    assert_eq!(space.dispatch(&OscMessage::from("/")).unwrap(), 1);
+    assert_eq!(space.dispatch(&OscMessage::from("/*/b/?")).unwrap(), 2);
+    assert_eq!(space.dispatch(&OscMessage::from("/x/y")).unwrap(), 1);
+    // Invalid patterns don't reach it
+    assert!(space.dispatch(&OscMessage::from("/a/")).is_err());
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec!["/a", "any", "/a/b/c", "any", "any", "/a/b/c", "any", "any"]
+    );
+    assert_eq!(*seen.lock().unwrap(), vec!["/a", "/a/b/c", "/", "/*/b/?", "/x/y"]);
+    // Catch-alls are not methods
+    assert_eq!(*fallbacks.lock().unwrap(), vec!["/", "/x/y", "/a/"]);
+    assert_eq!(space.addresses().collect::<Vec<_>>(), vec!["/a", "/a/b/c"]);
+    assert_eq!(space.remove_method("/a"), 1);
+    assert_eq!(space.dispatch(&OscMessage::from("/a")).unwrap(), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_fallback_handler() {
+    use rosc::dispatch::{self, DispatchContext, HookAction};
+    use rosc::OscError;
+
+    let (mut space, calls) = recording(&["/a"]);
+    space.add_typed_method("/level", |_: f32| {}).unwrap();
+    space.add_pre_hook(|msg, _| match msg.addr.as_str() {
+        "/dropped" => HookAction::Drop,
+        _ => HookAction::Continue,
+    });
+    let replies = Arc::new(Mutex::new(vec![]));
+    let recorded = replies.clone();
+    space.set_fallback_handler(move |msg, context, err| {
+        let mut reply = dispatch::reply("{addr}/error", msg);
+        reply.push_str(format!("{:?}", context.peer));
+        if let Some(err) = err {
+            reply.push_str(err.to_string());
+        }
+        recorded.lock().unwrap().push(reply);
+    });
+
+    assert_eq!(space.dispatch(&OscMessage::from("/a")).unwrap(), 1);
+    assert_eq!(space.dispatch(&OscMessage::from("/b")).unwrap(), 0);
+    // Neither methods that can't take the arguments, nor dropped messages are unmatched
+    assert_eq!(space.dispatch(&OscMessage::from("/level")).unwrap(), 0);
+    assert_eq!(space.dispatch(&OscMessage::from("/dropped")).unwrap(), 0);
+    let context = DispatchContext {
+        peer: Some("127.0.0.1:9000".parse().unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(
+        space
+            .dispatch_with_context(&OscMessage::from("/b/*"), &context)
+            .unwrap(),
+        0
+    );
+    let err = space.dispatch(&OscMessage::from("/a/")).unwrap_err();
+    assert_eq!(err, OscError::BadAddress("Invalid address pattern '/a/'".to_string()));
+
+    assert_eq!(*calls.lock().unwrap(), vec!["/a"]);
+    let reply = |addr: &str, args: Vec<String>| OscMessage {
+        addr: addr.to_string(),
+        args: args.into_iter().map(Into::into).collect(),
+    };
+    assert_eq!(
+        *replies.lock().unwrap(),
+        vec![
+            reply("/b/error", vec!["None".to_string()]),
+            reply("/b/*/error", vec!["Some(127.0.0.1:9000)".to_string()]),
+            reply("/a//error", vec!["None".to_string(), err.to_string()]),
+        ]
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hooks_timetag() {
+    use rosc::dispatch::HookAction;
+    use rosc::OscTime;
+
+    let (mut space, _) = recording(&["/a"]);
+    let timetags = Arc::new(Mutex::new(vec![]));
+    let recorded = timetags.clone();
+    space.add_pre_hook(move |_, context| {
+        recorded.lock().unwrap().push(context.timetag);
+        HookAction::Continue
+    });
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (1, 0).into(),
+        content: vec![
+            OscPacket::Message(OscMessage::from("/a")),
+            OscPacket::Bundle(OscBundle {
+                timetag: OscTime::IMMEDIATE,
+                content: vec![OscPacket::Message(OscMessage::from("/a"))],
+            }),
+            OscPacket::Bundle(OscBundle {
+                timetag: (2, 0).into(),
+                content: vec![OscPacket::Message(OscMessage::from("/a"))],
+            }),
+        ],
+    });
+    assert_eq!(space.dispatch_packet(&packet).unwrap(), 3);
+    space.dispatch_packet(&OscPacket::Message(OscMessage::from("/a"))).unwrap();
+    assert_eq!(
+        *timetags.lock().unwrap(),
+        vec![(1, 0).into(), (1, 0).into(), (2, 0).into(), OscTime::IMMEDIATE]
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_dispatch_duplicate_policy() {
+    use rosc::dispatch::{DispatchOptions, DuplicatePolicy};
+
+    let calls = Arc::new(Mutex::new(vec![]));
+    let mut space = AddressSpace::new();
+    for &addr in &["/fader", "/mute", "/pan"] {
+        let calls = calls.clone();
+        space
+            .add_method(addr, move |msg| {
+                calls.lock().unwrap().push(format!("{} {:?}", addr, msg.args))
+            })
+            .unwrap();
+    }
+    let msg = |addr: &str, value: f32| {
+        let mut msg = OscMessage::from(addr);
+        msg.push_float(value);
+        OscPacket::Message(msg)
+    };
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (1, 0).into(),
+        content: vec![
+            msg("/fader", 0.1),
+            msg("/mute", 1.0),
+            msg("/fader", 0.2),
+            msg("/pan", 0.5),
+            msg("/fader", 0.3),
+        ],
+    });
+    let mut dispatch = |duplicates| {
+        let options = DispatchOptions { duplicates };
+        let invoked = space.dispatch_packet_with_options(&packet, &options).unwrap();
+        (invoked, calls.lock().unwrap().drain(..).collect::<Vec<_>>())
+    };
+
+    assert_eq!(
+        dispatch(DuplicatePolicy::All),
+        (
+            5,
+            vec![
+                "/fader [Float(0.1)]".to_string(),
+                "/mute [Float(1.0)]".to_string(),
+                "/fader [Float(0.2)]".to_string(),
+                "/pan [Float(0.5)]".to_string(),
+                "/fader [Float(0.3)]".to_string(),
+            ]
+        )
+    );
+    assert_eq!(
+        dispatch(DuplicatePolicy::LastWins),
+        (
+            3,
+            vec![
+                "/mute [Float(1.0)]".to_string(),
+                "/pan [Float(0.5)]".to_string(),
+                "/fader [Float(0.3)]".to_string(),
+            ]
+        )
+    );
+    assert_eq!(
+        dispatch(DuplicatePolicy::FirstWins),
+        (
+            3,
+            vec![
+                "/fader [Float(0.1)]".to_string(),
+                "/mute [Float(1.0)]".to_string(),
+                "/pan [Float(0.5)]".to_string(),
+            ]
+        )
+    );
+    assert_eq!(DispatchOptions::default().duplicates, DuplicatePolicy::All);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_dispatch_duplicate_policy_nested() {
+    use rosc::dispatch::{DispatchOptions, DuplicatePolicy};
+
+    let (mut space, _) = recording(&[]);
+    let values = Arc::new(Mutex::new(vec![]));
+    let recorded = values.clone();
+    space
+        .add_typed_method("/fader", move |value: i32| recorded.lock().unwrap().push(value))
+        .unwrap();
+    let fader = |value: i32| {
+        let mut msg = OscMessage::from("/fader");
+        msg.push_int(value);
+        OscPacket::Message(msg)
+    };
+    // The nested bundle takes effect before the messages around it, and the immediate one along
+    // with them
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (2, 0).into(),
+        content: vec![
+            fader(1),
+            OscPacket::Bundle(OscBundle {
+                timetag: (1, 0).into(),
+                content: vec![fader(2), fader(3)],
+            }),
+            OscPacket::Bundle(OscBundle {
+                timetag: rosc::OscTime::IMMEDIATE,
+                content: vec![fader(4)],
+            }),
+            fader(5),
+        ],
+    });
+    let mut dispatch = |duplicates| {
+        let options = DispatchOptions { duplicates };
+        space.dispatch_packet_with_options(&packet, &options).unwrap();
+        values.lock().unwrap().drain(..).collect::<Vec<_>>()
+    };
+    assert_eq!(dispatch(DuplicatePolicy::All), vec![1, 2, 3, 4, 5]);
+    assert_eq!(dispatch(DuplicatePolicy::LastWins), vec![5]);
+    assert_eq!(dispatch(DuplicatePolicy::FirstWins), vec![2]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_route_table() {
+    let calls = Arc::new(Mutex::new(vec![]));
+    let mut builder = RouteTable::builder();
+    for &addr in &["/mixer/1/gain", "/mixer/2/gain", "/mixer/1/gain", "/transport/play"] {
+        let calls = calls.clone();
+        builder
+            .add_method(addr, move |msg| calls.lock().unwrap().push((addr, msg.addr.clone())))
+            .unwrap();
+    }
+    assert!(builder.add_method("/mixer/*/gain", |_| {}).is_err());
+    let table = builder.build();
+    assert_eq!(table.addresses().count(), 4);
+
+    assert_eq!(table.dispatch(&OscMessage::from("/mixer/1/gain")).unwrap(), 2);
+    assert_eq!(table.dispatch(&OscMessage::from("/mixer/*/gain")).unwrap(), 3);
+    assert_eq!(table.dispatch(&OscMessage::from("/none")).unwrap(), 0);
+    assert!(table.dispatch(&OscMessage::from("/mixer/{")).is_err());
+    assert_eq!(calls.lock().unwrap().len(), 5);
+
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (1, 0).into(),
+        content: vec![
+            OscPacket::Message(OscMessage::from("/transport/play")),
+            OscPacket::Bundle(OscBundle {
+                timetag: (2, 0).into(),
+                content: vec![OscPacket::Message(OscMessage::from("/mixer/2/gain"))],
+            }),
+        ],
+    });
+    calls.lock().unwrap().clear();
+    assert_eq!(table.dispatch_packet(&packet).unwrap(), 2);
+    let addrs: Vec<_> = calls.lock().unwrap().iter().map(|(addr, _)| *addr).collect();
+    assert_eq!(addrs, vec!["/transport/play", "/mixer/2/gain"]);
+
+    // Changing a copy of the routes leaves the table alone
+    let mut builder = table.to_builder();
+    assert_eq!(builder.remove_method("/mixer/1/gain"), 2);
+    let reduced = builder.build();
+    assert_eq!(reduced.dispatch(&OscMessage::from("/mixer/*/gain")).unwrap(), 1);
+    assert_eq!(table.dispatch(&OscMessage::from("/mixer/*/gain")).unwrap(), 3);
+
+    let space = SharedAddressSpace::new(table);
+    assert_eq!(space.dispatch(&OscMessage::from("/mixer/*/gain")).unwrap(), 3);
+    let previous = space.reload(reduced);
+    assert_eq!(previous.addresses().count(), 4);
+    assert_eq!(space.dispatch(&OscMessage::from("/mixer/*/gain")).unwrap(), 1);
+    assert_eq!(space.table().addresses().count(), 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_shared_address_space_reload_while_dispatching() {
+    use std::cell::RefCell;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::thread;
+
+    thread_local! {
+        // The versions of the tables the messages of the current packet were dispatched to
+        static VERSIONS: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+    }
+    const WORKERS: usize = 4;
+    const PACKETS: usize = 2000;
+
+    let invoked = Arc::new(AtomicUsize::new(0));
+    let counter = invoked.clone();
+    let table = move |version: usize| {
+        let mut builder = RouteTable::builder();
+        let invoked = counter.clone();
+        builder
+            .add_method("/ping", move |_| {
+                invoked.fetch_add(1, Ordering::Relaxed);
+                VERSIONS.with(|versions| versions.borrow_mut().push(version));
+            })
+            .unwrap();
+        // Tables differ in size, so that swapping them frees routes still in use elsewhere
+        for i in 0..version % 5 {
+            builder.add_method(&format!("/other/{}", i), |_| {}).unwrap();
+        }
+        builder.build()
+    };
+    let space = Arc::new(SharedAddressSpace::new(table(0)));
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (1, 0).into(),
+        content: vec![
+            OscPacket::Message(OscMessage::from("/ping")),
+            OscPacket::Message(OscMessage::from("/ping")),
+        ],
+    });
+
+    let done = Arc::new(AtomicBool::new(false));
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|_| {
+            let (space, packet) = (space.clone(), packet.clone());
+            thread::spawn(move || {
+                let mut seen = Vec::new();
+                for _ in 0..PACKETS {
+                    assert_eq!(space.dispatch_packet(&packet).unwrap(), 2);
+                    let versions = VERSIONS.with(|versions| versions.replace(Vec::new()));
+                    // Both messages of a packet are dispatched to the same table
+                    assert_eq!(versions.len(), 2);
+                    assert_eq!(versions[0], versions[1]);
+                    seen.push(versions[0]);
+                }
+                seen
+            })
+        })
+        .collect();
+    let reloader = {
+        let (space, done) = (space.clone(), done.clone());
+        thread::spawn(move || {
+            let mut version = 0;
+            loop {
+                version += 1;
+                let previous = space.reload(table(version));
+                assert!(previous.addresses().any(|addr| addr == "/ping"));
+                if done.load(Ordering::Relaxed) {
+                    return version;
+                }
+                thread::yield_now();
+            }
+        })
+    };
+
+    for worker in workers {
+        let seen = worker.join().unwrap();
+        // Tables are only ever replaced by newer ones
+        assert!(seen.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+    done.store(true, Ordering::Relaxed);
+    let last = reloader.join().unwrap();
+    assert_eq!(invoked.load(Ordering::Relaxed), WORKERS * PACKETS * 2);
+    assert_eq!(space.table().addresses().count(), 1 + last % 5);
+}
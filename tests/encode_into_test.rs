@@ -0,0 +1,128 @@
+extern crate rosc;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use rosc::{decoder, encoder, OscArray, OscBundle, OscColor, OscMessage, OscMidiMessage};
+use rosc::{OscPacket, OscTime, OscType, StaticOscMessage};
+
+/// Counts the allocations made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// A bundle containing every argument type, with strings and blobs of every padding length.
+fn packet() -> OscPacket {
+    let mut args = vec![
+        OscType::Int(1),
+        OscType::Long(2),
+        OscType::Float(3.0),
+        OscType::Double(4.0),
+        OscType::Char('x'),
+        OscType::Time((5, 6).into()),
+        OscType::Midi(OscMidiMessage {
+            port: 1,
+            status: 2,
+            data1: 3,
+            data2: 4,
+        }),
+        OscType::Color(OscColor {
+            red: 1,
+            green: 2,
+            blue: 3,
+            alpha: 4,
+        }),
+        OscType::Bool(true),
+        OscType::Bool(false),
+        OscType::Nil,
+        OscType::Impulse,
+        OscType::Array(OscArray {
+            content: vec![OscType::Int(7), "nested".into()],
+        }),
+    ];
+    for len in 0..4 {
+        args.push("abc"[..len].into());
+        args.push(vec![0xab; len].into());
+    }
+    OscPacket::Bundle(OscBundle {
+        timetag: (1, 2).into(),
+        content: vec![
+            OscPacket::Message(OscMessage {
+                addr: "/all/types".to_string(),
+                args,
+            }),
+            OscPacket::Message(OscMessage::from("/no/args")),
+        ],
+    })
+}
+
+#[test]
+fn test_encode_into_does_not_allocate() {
+    let packet = packet();
+    let mut buf = Vec::with_capacity(1024);
+
+    let before = allocations();
+    for _ in 0..10 {
+        buf.clear();
+        encoder::encode_into(&packet, &mut buf).unwrap();
+    }
+    assert_eq!(allocations(), before, "encode_into must not allocate");
+
+    assert_eq!(decoder::decode_udp(&buf).unwrap().1, packet);
+}
+
+#[test]
+fn test_encode_ref_into_does_not_allocate() {
+    let encoded = encoder::encode(&packet()).unwrap();
+    let (_, borrowed) = decoder::decode_ref(&encoded).unwrap();
+    let mut buf = Vec::with_capacity(1024);
+
+    let before = allocations();
+    encoder::encode_ref_into(&borrowed, &mut buf).unwrap();
+    assert_eq!(allocations(), before, "encode_ref_into must not allocate");
+
+    assert_eq!(buf, encoded);
+}
+
+#[test]
+fn test_encode_static_into_does_not_allocate() {
+    static SYNC: StaticOscMessage = StaticOscMessage {
+        addr: "/sync",
+        args: &[
+            OscType::Int(1),
+            OscType::Float(0.5),
+            OscType::Time(OscTime::new(1, 2)),
+            OscType::String(String::new()),
+            OscType::Nil,
+        ],
+    };
+    let mut buf = Vec::with_capacity(1024);
+
+    let before = allocations();
+    encoder::encode_static_into(&SYNC, &mut buf).unwrap();
+    assert_eq!(allocations(), before, "encode_static_into must not allocate");
+
+    let owned = OscPacket::Message(OscMessage::from(&SYNC));
+    assert_eq!(buf, encoder::encode(&owned).unwrap());
+    assert_eq!(encoder::encode_static(&SYNC).unwrap(), buf);
+}
@@ -2,6 +2,8 @@ extern crate rosc;
 
 use rosc::{decoder, encoder};
 use rosc::{OscArray, OscBundle, OscColor, OscMessage, OscMidiMessage, OscPacket, OscType};
+use rosc::{OscMessageRef, OscPacketRef, OscTypeRef};
+use std::borrow::Cow;
 
 #[test]
 fn test_encode_message_wo_args() {
@@ -51,6 +53,7 @@ fn test_encode_empty_bundle() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_encode_message_with_args() {
     let msg_packet = OscPacket::Message(OscMessage {
         addr: "/another/address/1".to_string(),
@@ -67,7 +70,7 @@ fn test_encode_message_with_args() {
             false.into(),
             true.into(),
             OscType::Nil,
-            OscType::Inf,
+            OscType::Impulse,
             OscMidiMessage {
                 port: 4,
                 status: 41,
@@ -154,3 +157,118 @@ fn test_encode_bundle() {
     let dec_bundle = decoder::decode_udp(&enc_bundle).unwrap().1;
     assert_eq!(root_bundle, dec_bundle);
 }
+
+#[test]
+fn test_encode_into() {
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (1, 2).into(),
+        content: vec![
+            OscPacket::Message(OscMessage {
+                addr: "/a".to_string(),
+                args: vec![
+                    1i32.into(),
+                    "text".into(),
+                    vec![1u8, 2, 3].into(),
+                    OscType::Array(OscArray {
+                        content: vec![true.into(), 0.5f64.into()],
+                    }),
+                ],
+            }),
+            OscPacket::Bundle(OscBundle {
+                timetag: (0, 1).into(),
+                content: vec![OscPacket::Message(OscMessage::from("/b"))],
+            }),
+        ],
+    });
+    let encoded = encoder::encode(&packet).unwrap();
+
+    // Appends to the existing contents, which need not be aligned
+    let mut buf = vec![0xff; 3];
+    assert_eq!(encoder::encode_into(&packet, &mut buf).unwrap(), encoded.len());
+    assert_eq!(&buf[..3], &[0xff; 3]);
+    assert_eq!(&buf[3..], &encoded[..]);
+    assert_eq!(decoder::decode_udp(&buf[3..]).unwrap().1, packet);
+}
+
+#[test]
+fn test_encode_ref() {
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (1, 2).into(),
+        content: vec![OscPacket::Message(OscMessage {
+            addr: "/transport/beat".to_string(),
+            args: vec![
+                1i32.into(),
+                "four".into(),
+                vec![1u8, 2, 3].into(),
+                OscType::Array(OscArray {
+                    content: vec![OscType::Bool(true), OscType::Nil],
+                }),
+            ],
+        })],
+    });
+    let encoded = encoder::encode(&packet).unwrap();
+    let (_, borrowed) = decoder::decode_ref(&encoded).unwrap();
+    assert_eq!(encoder::encode_ref(&borrowed).unwrap(), encoded);
+
+    let msg = OscMessageRef::new("/transport/beat", vec![OscTypeRef::Int(1)]);
+    assert_eq!(msg.addr, Cow::Borrowed("/transport/beat"));
+    let mut buf = vec![0xff];
+    let len = encoder::encode_ref_into(&OscPacketRef::Message(msg), &mut buf).unwrap();
+    assert_eq!(len, buf.len() - 1);
+    assert_eq!(
+        decoder::decode_udp(&buf[1..]).unwrap().1,
+        OscPacket::Message(OscMessage {
+            addr: "/transport/beat".to_string(),
+            args: vec![1i32.into()],
+        })
+    );
+}
+
+#[test]
+fn test_encode_numeric_args_golden() {
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (0x0102_0304, 0x0506_0708).into(),
+        content: vec![OscPacket::Message(OscMessage {
+            addr: "/n".to_string(),
+            args: vec![
+                OscType::Int(-2),
+                OscType::Float(1.5),
+                OscType::Long(0x0102_0304_0506_0708),
+                OscType::Double(-0.25),
+                OscType::Time((1, 2).into()),
+                OscType::Char('é'),
+                OscType::Color(OscColor {
+                    red: 1,
+                    green: 2,
+                    blue: 3,
+                    alpha: 4,
+                }),
+                OscType::Midi(OscMidiMessage {
+                    port: 5,
+                    status: 6,
+                    data1: 7,
+                    data2: 8,
+                }),
+                OscType::Blob(vec![9]),
+            ],
+        })],
+    });
+    let golden: &[u8] = &[
+        b'#', b'b', b'u', b'n', b'd', b'l', b'e', 0, // bundle tag
+        1, 2, 3, 4, 5, 6, 7, 8, // time tag
+        0, 0, 0, 68, // element size
+        b'/', b'n', 0, 0, // address
+        b',', b'i', b'f', b'h', b'd', b't', b'c', b'r', b'm', b'b', 0, 0, // type tags
+        0xff, 0xff, 0xff, 0xfe, // -2
+        0x3f, 0xc0, 0, 0, // 1.5
+        1, 2, 3, 4, 5, 6, 7, 8, // long
+        0xbf, 0xd0, 0, 0, 0, 0, 0, 0, // -0.25
+        0, 0, 0, 1, 0, 0, 0, 2, // time
+        0, 0, 0, 0xe9, // 'é'
+        1, 2, 3, 4, // color
+        5, 6, 7, 8, // midi
+        0, 0, 0, 1, 9, 0, 0, 0, // blob
+    ];
+    assert_eq!(encoder::encode(&packet).unwrap(), golden);
+    assert_eq!(decoder::decode_udp(golden).unwrap().1, packet);
+}
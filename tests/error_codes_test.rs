@@ -0,0 +1,161 @@
+extern crate nom;
+extern crate rosc;
+
+use rosc::address::{verify_address, verify_address_pattern, verify_address_with_max_len};
+use rosc::decoder::{self, DecodeError, DecoderOptions};
+use rosc::encoder::EncodeError;
+use rosc::{OscError, OscErrorKind};
+#[cfg(feature = "std")]
+use rosc::OscTime;
+
+/// The published codes, which must never change: new kinds are appended with new codes.
+const CODES: &[(OscErrorKind, u32)] = &[
+    (OscErrorKind::StringError, 1),
+    (OscErrorKind::ReadError, 2),
+    (OscErrorKind::BadChar, 3),
+    (OscErrorKind::BadPacket, 4),
+    (OscErrorKind::BadMessage, 5),
+    (OscErrorKind::BadString, 6),
+    (OscErrorKind::BadArg, 7),
+    (OscErrorKind::BadBundle, 8),
+    (OscErrorKind::BadBundleHeader, 9),
+    (OscErrorKind::BadAddressPattern, 10),
+    (OscErrorKind::BadAddress, 11),
+    (OscErrorKind::RegexError, 12),
+    (OscErrorKind::Unimplemented, 13),
+    (OscErrorKind::PacketTooLarge, 14),
+    (OscErrorKind::BlobTooLarge, 15),
+    (OscErrorKind::StringTooLong, 16),
+    (OscErrorKind::TooManyArgs, 17),
+    (OscErrorKind::BundleTooDeep, 18),
+    (OscErrorKind::BadText, 19),
+    (OscErrorKind::Truncated, 20),
+    (OscErrorKind::Misaligned, 21),
+    (OscErrorKind::TooFarInFuture, 22),
+    (OscErrorKind::Io, 23),
+    (OscErrorKind::BadRecording, 24),
+    (OscErrorKind::UnknownTypeTag, 25),
+    (OscErrorKind::InvalidChar, 26),
+    (OscErrorKind::BundleTimeNotMonotonic, 27),
+    (OscErrorKind::UnsupportedTypeTag, 28),
+    (OscErrorKind::AddressTooLong, 29),
+];
+
+#[test]
+fn test_error_kind_codes() {
+    for &(kind, code) in CODES {
+        assert_eq!(kind.code(), code, "{:?}", kind);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_error_codes() {
+    let invalid_utf8 = String::from_utf8(vec![0xff]).unwrap_err();
+    let errors = vec![
+        OscError::StringError(invalid_utf8),
+        OscError::ReadError(nom::error::ErrorKind::Eof),
+        OscError::BadChar('x'),
+        OscError::BadPacket("packet"),
+        OscError::BadMessage("message"),
+        OscError::BadString("string"),
+        OscError::BadArg("arg".to_string()),
+        OscError::BadBundle("bundle".to_string()),
+        OscError::BadBundleHeader(b"#bun".to_vec()),
+        OscError::BadAddressPattern("pattern".to_string()),
+        OscError::BadAddress("address".to_string()),
+        OscError::RegexError("regex".to_string()),
+        OscError::Unimplemented,
+        OscError::PacketTooLarge { size: 2, max: 1 },
+        OscError::BlobTooLarge { size: 2, max: 1 },
+        OscError::StringTooLong { len: 2, max: 1 },
+        OscError::TooManyArgs { count: 2, max: 1 },
+        OscError::BundleTooDeep { max: 1 },
+        OscError::BadText {
+            column: 1,
+            msg: "text".to_string(),
+        },
+        OscError::Truncated {
+            expected: 4,
+            remaining: 0,
+            arg_index: 0,
+        },
+        OscError::Misaligned { len: 3 },
+        OscError::TooFarInFuture(OscTime::from((1, 0))),
+        OscError::from(std::io::Error::from(std::io::ErrorKind::TimedOut)),
+        OscError::BadRecording("recording".to_string()),
+        OscError::UnknownTypeTag('X'),
+        OscError::InvalidChar(0x11_0000),
+        OscError::BundleTimeNotMonotonic {
+            element: 0,
+            timetag: OscTime::from((1, 0)),
+            parent: OscTime::from((2, 0)),
+        },
+        OscError::UnsupportedTypeTag('T'),
+        OscError::AddressTooLong { len: 5, max: 4 },
+    ];
+
+    // One error of each kind, in the order of the table
+    assert_eq!(errors.len(), CODES.len());
+    for (err, &(kind, code)) in errors.iter().zip(CODES) {
+        assert_eq!(err.kind(), kind, "{:?}", err);
+        assert_eq!(err.code(), code, "{:?}", err);
+    }
+}
+
+#[test]
+fn test_positioned_errors_have_the_code_of_their_cause() {
+    let err = decoder::decode_with_options(b"/abc\0\0\0\0,X\0\0", &DecoderOptions::default())
+        .unwrap_err();
+    assert!(matches!(err, DecodeError::At { .. }));
+    assert_eq!(err.kind(), OscErrorKind::UnknownTypeTag);
+    assert_eq!(err.code(), 25);
+
+    let err = OscError::from(err);
+    assert!(matches!(err, OscError::At { .. }));
+    assert_eq!(err.kind(), OscErrorKind::UnknownTypeTag);
+}
+
+#[test]
+fn test_domain_error_codes() {
+    assert_eq!(EncodeError::BlobTooLarge { size: 0 }.code(), 2001);
+    assert_eq!(EncodeError::UnsupportedTypeTag('T').code(), 2002);
+}
+
+#[test]
+fn test_address_error_codes() {
+    assert_eq!(verify_address("test").unwrap_err().code(), 1001);
+    assert_eq!(verify_address_pattern("test").unwrap_err().code(), 1002);
+    assert_eq!(verify_address_with_max_len("/test", 4).unwrap_err().code(), 1003);
+}
+
+#[test]
+fn test_decode_errors_convert_into_the_same_kind() {
+    let invalid_utf8 = String::from_utf8(vec![0xff]).unwrap_err();
+    let errors = vec![
+        DecodeError::StringError(invalid_utf8),
+        DecodeError::BadBundleHeader(b"#bun".to_vec()),
+        DecodeError::BlobTooLarge { size: 2, max: 1 },
+        DecodeError::Truncated {
+            expected: 4,
+            remaining: 0,
+            arg_index: 0,
+        },
+        DecodeError::Misaligned { len: 3 },
+        DecodeError::UnknownTypeTag('X'),
+        DecodeError::AfterPackets {
+            decoded: 1,
+            source: Box::new(DecodeError::At {
+                offset: 8,
+                context: "type tags of message '/abc'".to_string(),
+                snippet: "2c 58 00 00".to_string(),
+                source: Box::new(DecodeError::AddressTooLong { len: 5, max: 4 }),
+            }),
+        },
+    ];
+    for err in errors {
+        let converted = OscError::from(err.clone());
+        assert_eq!(err.code(), converted.code(), "{:?}", err);
+        assert_eq!(err.to_string(), converted.to_string());
+    }
+}
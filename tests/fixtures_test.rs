@@ -0,0 +1,138 @@
+#[cfg(feature = "test-util")]
+#[macro_use]
+extern crate rosc;
+
+#[cfg(feature = "test-util")]
+use rosc::decoder::{self, DecoderOptions};
+#[cfg(feature = "test-util")]
+use rosc::test_util::{load_fixture, load_fixture_with_options};
+#[cfg(feature = "test-util")]
+use rosc::{encoder, OscBundle, OscPacket, OscTime};
+#[cfg(feature = "test-util")]
+use std::fs;
+#[cfg(feature = "test-util")]
+use std::path::Path;
+
+/// A fixture as described by `tests/fixtures/manifest.txt`.
+#[cfg(feature = "test-util")]
+#[derive(Debug, Default)]
+struct Fixture {
+    file: String,
+    canonical: bool,
+    options: DecoderOptions,
+    bundle: Option<OscTime>,
+    messages: Vec<String>,
+}
+
+#[cfg(feature = "test-util")]
+impl Fixture {
+    fn expected(&self) -> OscPacket {
+        let mut messages = self.messages.iter().map(|line| {
+            let msg = decoder::parse_text(line)
+                .unwrap_or_else(|err| panic!("{}: bad message {:?}: {}", self.file, line, err));
+            OscPacket::Message(msg)
+        });
+        match self.bundle {
+            Some(timetag) => OscPacket::Bundle(OscBundle {
+                timetag,
+                content: messages.collect(),
+            }),
+            None => {
+                assert_eq!(self.messages.len(), 1, "{}: expected a single message", self.file);
+                messages.next().unwrap()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "test-util")]
+fn parse_manifest(manifest: &str) -> Vec<Fixture> {
+    let mut fixtures: Vec<Fixture> = vec![];
+    for line in manifest.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            fixtures.push(Fixture {
+                file: line[1..line.len() - 1].to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let fixture = fixtures.last_mut().expect("manifest starts with a key outside a fixture");
+        let (key, value) = line.split_once(" = ").expect("manifest line is not `key = value`");
+        match key {
+            "origin" => {}
+            "canonical" => fixture.canonical = value == "yes",
+            "options" => {
+                for option in value.split(',').map(str::trim) {
+                    match option {
+                        "lenient_type_tags" => fixture.options.lenient_type_tags = true,
+                        "allow_trailing_bytes" => fixture.options.allow_trailing_bytes = true,
+                        "zero_timetag_is_immediate" => {
+                            fixture.options.zero_timetag_is_immediate = true
+                        }
+                        "allow_unpadded_strings" => fixture.options.allow_unpadded_strings = true,
+                        "allow_high_byte_chars" => fixture.options.allow_high_byte_chars = true,
+                        _ => panic!("{}: unknown decoder option {}", fixture.file, option),
+                    }
+                }
+            }
+            "bundle" => {
+                let (seconds, fractional) = value
+                    .strip_prefix('@')
+                    .and_then(|time| time.split_once('.'))
+                    .expect("bundle time tag is not `@seconds.fractional`");
+                let timetag = (seconds.parse().unwrap(), fractional.parse().unwrap());
+                fixture.bundle = Some(timetag.into());
+            }
+            "message" => fixture.messages.push(value.to_string()),
+            _ => panic!("{}: unknown key {}", fixture.file, key),
+        }
+    }
+    fixtures
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_fixtures() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let fixtures = parse_manifest(&fs::read_to_string(dir.join("manifest.txt")).unwrap());
+    assert!(!fixtures.is_empty());
+
+    for fixture in &fixtures {
+        let bytes = fs::read(dir.join(&fixture.file)).unwrap();
+        let (packet, encoded) = load_fixture_with_options(&bytes, &fixture.options);
+        assert_osc_eq!(packet, fixture.expected());
+        if fixture.canonical {
+            assert_eq!(encoded, bytes, "{} is not encoded canonically", fixture.file);
+        } else {
+            assert_ne!(encoded, bytes, "{} is encoded canonically", fixture.file);
+            // The canonical encoding needs no decoder options
+            assert_osc_eq!(load_fixture(&encoded).0, packet);
+        }
+    }
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_fixtures_are_listed() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let fixtures = parse_manifest(&fs::read_to_string(dir.join("manifest.txt")).unwrap());
+    let mut listed: Vec<String> = fixtures.into_iter().map(|fixture| fixture.file).collect();
+    let mut files: Vec<String> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|file| file != "manifest.txt")
+        .collect();
+    listed.sort();
+    files.sort();
+    assert_eq!(listed, files);
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+#[should_panic(expected = "fixture failed to decode")]
+fn test_load_fixture_panics_on_malformed_bytes() {
+    load_fixture(&encoder::encode_string("/ping"));
+}
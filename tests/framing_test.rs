@@ -0,0 +1,143 @@
+extern crate rosc;
+
+use rosc::decoder::DecoderOptions;
+use rosc::framing::{encode_length_prefixed, encode_slip, SlipDecoder, TcpDecoder};
+use rosc::{OscBundle, OscError, OscMessage, OscPacket};
+
+fn packets() -> Vec<OscPacket> {
+    vec![
+        OscPacket::Message(OscMessage {
+            addr: "/slip".to_string(),
+            // Contains the bytes that have to be escaped
+            args: vec![vec![0xc0u8, 0xdb, 0xdc, 0xdd, 0xc0].into(), 1i32.into()],
+        }),
+        OscPacket::Bundle(OscBundle {
+            timetag: (1, 2).into(),
+            content: vec![OscPacket::Message(OscMessage::from("/nested"))],
+        }),
+        OscPacket::Message(OscMessage::from("/last")),
+    ]
+}
+
+/// Feeds `stream` to `feed` in chunks of `chunk_size` bytes and collects the decoded packets.
+fn decode_chunked<F, N>(
+    stream: &[u8],
+    chunk_size: usize,
+    mut feed: F,
+    mut next: N,
+) -> Vec<OscPacket>
+where
+    F: FnMut(&[u8]),
+    N: FnMut() -> Result<Option<OscPacket>, OscError>,
+{
+    let mut decoded = vec![];
+    for chunk in stream.chunks(chunk_size) {
+        feed(chunk);
+        while let Some(packet) = next().unwrap() {
+            decoded.push(packet);
+        }
+    }
+    decoded
+}
+
+#[test]
+fn test_tcp_decoder_split_frames() {
+    let stream: Vec<u8> = packets()
+        .iter()
+        .flat_map(|packet| encode_length_prefixed(packet).unwrap())
+        .collect();
+    for chunk_size in 1..=stream.len() {
+        let decoder = std::cell::RefCell::new(TcpDecoder::default());
+        let decoded = decode_chunked(
+            &stream,
+            chunk_size,
+            |chunk| decoder.borrow_mut().feed(chunk),
+            || decoder.borrow_mut().next_packet(),
+        );
+        assert_eq!(decoded, packets(), "chunk size {}", chunk_size);
+        assert_eq!(decoder.borrow().buffered(), 0);
+    }
+}
+
+#[test]
+fn test_slip_decoder_split_frames() {
+    let stream: Vec<u8> = packets()
+        .iter()
+        .flat_map(|packet| encode_slip(packet).unwrap())
+        .collect();
+    for chunk_size in 1..=stream.len() {
+        let decoder = std::cell::RefCell::new(SlipDecoder::default());
+        let decoded = decode_chunked(
+            &stream,
+            chunk_size,
+            |chunk| decoder.borrow_mut().feed(chunk),
+            || decoder.borrow_mut().next_packet(),
+        );
+        assert_eq!(decoded, packets(), "chunk size {}", chunk_size);
+        assert_eq!(decoder.borrow().buffered(), 0);
+    }
+}
+
+#[test]
+fn test_slip_encoding() {
+    let frame = encode_slip(&packets()[0]).unwrap();
+    assert_eq!(frame.first(), Some(&0xc0));
+    assert_eq!(frame.last(), Some(&0xc0));
+    assert!(!frame[1..frame.len() - 1].contains(&0xc0));
+    assert!(frame
+        .windows(4)
+        .any(|window| window == [0xdb, 0xdc, 0xdb, 0xdd]));
+}
+
+#[test]
+fn test_oversized_frames_are_skipped() {
+    let options = DecoderOptions {
+        max_packet_size: 16,
+        ..Default::default()
+    };
+    let large = OscPacket::Message(OscMessage {
+        addr: "/large".to_string(),
+        args: vec![vec![0u8; 64].into()],
+    });
+    let small = OscPacket::Message(OscMessage::from("/small"));
+
+    let mut decoder = TcpDecoder::with_options(options.clone());
+    let frame = encode_length_prefixed(&large).unwrap();
+    decoder.feed(&frame[..8]);
+    match decoder.next_packet() {
+        Err(OscError::PacketTooLarge { size, max }) => assert_eq!((size, max), (80, 16)),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    decoder.feed(&frame[8..]);
+    assert_eq!(decoder.buffered(), 0);
+    decoder.feed(&encode_length_prefixed(&small).unwrap());
+    assert_eq!(decoder.next_packet().unwrap(), Some(small.clone()));
+
+    let mut decoder = SlipDecoder::with_options(options);
+    decoder.feed(&encode_slip(&large).unwrap());
+    decoder.feed(&encode_slip(&small).unwrap());
+    match decoder.next_packet() {
+        Err(OscError::PacketTooLarge { max, .. }) => assert_eq!(max, 16),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(decoder.next_packet().unwrap(), Some(small));
+}
+
+#[test]
+fn test_invalid_frames_are_skipped() {
+    let ping = OscPacket::Message(OscMessage::from("/ping"));
+
+    let mut decoder = SlipDecoder::default();
+    decoder.feed(b"\xc0/a\0\0\xdb\x01,\0\0\0\xc0");
+    decoder.feed(b"\xc0/b\0\0,\0\0\xdb\xc0");
+    decoder.feed(&encode_slip(&ping).unwrap());
+    assert!(matches!(decoder.next_packet(), Err(OscError::BadPacket(_))));
+    assert!(matches!(decoder.next_packet(), Err(OscError::BadPacket(_))));
+    assert_eq!(decoder.next_packet().unwrap(), Some(ping.clone()));
+
+    let mut decoder = TcpDecoder::default();
+    decoder.feed(b"\0\0\0\x04/abc");
+    decoder.feed(&encode_length_prefixed(&ping).unwrap());
+    assert!(decoder.next_packet().is_err());
+    assert_eq!(decoder.next_packet().unwrap(), Some(ping));
+}
@@ -0,0 +1,83 @@
+extern crate rosc;
+
+use rosc::decoder::{self, DecoderOptions};
+use rosc::intern::{InternedPacket, Interner};
+use rosc::{encoder, OscBundle, OscMessage, OscPacket};
+
+use std::sync::Arc;
+
+fn addr(packet: &InternedPacket) -> Arc<str> {
+    match packet {
+        InternedPacket::Message(msg) => msg.addr.clone(),
+        InternedPacket::Bundle(_) => panic!("unexpected bundle: {:?}", packet),
+    }
+}
+
+#[test]
+fn test_decode_with_interner() {
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/mixer/1/fader".to_string(),
+        args: vec![0.5f32.into()],
+    });
+    let buf = encoder::encode(&packet).unwrap();
+    let options = DecoderOptions::default();
+    let mut interner = Interner::default();
+
+    let first = decoder::decode_with_interner(&buf, &options, &mut interner).unwrap();
+    let second = decoder::decode_with_interner(&buf, &options, &mut interner).unwrap();
+    assert!(Arc::ptr_eq(&addr(&first), &addr(&second)));
+    assert_eq!(OscPacket::from(first), packet);
+    assert_eq!(interner.len(), 1);
+}
+
+#[test]
+fn test_interned_bundles() {
+    let bundle = OscPacket::Bundle(OscBundle {
+        timetag: (0, 1).into(),
+        content: vec![
+            OscPacket::Message(OscMessage::from("/a")),
+            OscPacket::Message(OscMessage::from("/a")),
+        ],
+    });
+    let buf = encoder::encode(&bundle).unwrap();
+    let mut interner = Interner::default();
+    let decoded =
+        decoder::decode_with_interner(&buf, &DecoderOptions::default(), &mut interner).unwrap();
+    match decoded {
+        InternedPacket::Bundle(ref interned) => {
+            let addrs: Vec<_> = interned.content.iter().map(addr).collect();
+            assert!(Arc::ptr_eq(&addrs[0], &addrs[1]));
+        }
+        InternedPacket::Message(_) => panic!("unexpected message: {:?}", decoded),
+    }
+    assert_eq!(OscPacket::from(decoded), bundle);
+}
+
+#[test]
+fn test_interner_is_bounded() {
+    let mut interner = Interner::new(2);
+    let a = interner.intern("/a");
+    interner.intern("/b");
+    let c = interner.intern("/c");
+    assert_eq!(interner.len(), 2);
+    assert!(Arc::ptr_eq(&a, &interner.intern("/a")));
+    // Addresses beyond the capacity are allocated every time
+    assert!(!Arc::ptr_eq(&c, &interner.intern("/c")));
+    assert_eq!(&*c, "/c");
+
+    interner.clear();
+    assert!(interner.is_empty());
+    assert_eq!(interner.capacity(), 2);
+}
+
+#[test]
+fn test_decode_with_interner_errors() {
+    let mut interner = Interner::default();
+    let options = DecoderOptions {
+        max_packet_size: 8,
+        ..Default::default()
+    };
+    let buf = encoder::encode(&OscPacket::Message(OscMessage::from("/too/long"))).unwrap();
+    assert!(decoder::decode_with_interner(&buf, &options, &mut interner).is_err());
+    assert!(interner.is_empty());
+}
@@ -0,0 +1,297 @@
+extern crate rosc;
+
+use rosc::decoder::{self, DecodeError, DecoderOptions, LazyPacket, OscVisitor};
+use rosc::{encoder, OscBundle, OscError, OscMessage, OscPacket};
+
+struct Discard;
+
+impl<'a> OscVisitor<'a> for Discard {}
+
+/// Inputs that have to be rejected by every decoder. Crashing inputs found by the fuzz target in
+/// `fuzz/` belong here.
+const MALFORMED: &[(&str, &[u8])] = &[
+    ("empty packet", b""),
+    ("address without terminator", b"/abc"),
+    ("address with bad padding", b"/a\0\x01,\0\0\0"),
+    ("missing type tags", b"/abc\0\0\0\0"),
+    ("type tags without comma", b"/abc\0\0\0\0i\0\0\0\0\0\0\x01"),
+    ("truncated int", b"/abc\0\0\0\0,i\0\0\0\0\x01"),
+    ("truncated long", b"/abc\0\0\0\0,h\0\0\0\0\0\0\0\0\0"),
+    ("truncated double", b"/abc\0\0\0\0,d\0\0\0\0\0\0\0"),
+    ("truncated time tag", b"/abc\0\0\0\0,t\0\0\0\0\0\0\0"),
+    ("unknown type tag", b"/abc\0\0\0\0,X\0\0"),
+    ("invalid utf-8 address", b"/\xff\xfe\0,\0\0\0"),
+    ("invalid utf-8 string argument", b"/abc\0\0\0\0,s\0\0\xc3\x28\0\0"),
+    ("string argument with bad padding", b"/abc\0\0\0\0,s\0\0a\0\0\x07"),
+    ("negative blob size", b"/abc\0\0\0\0,b\0\0\xff\xff\xff\xfc"),
+    ("blob larger than packet", b"/abc\0\0\0\0,b\0\0\0\0\x01\0\x01\x02\x03\x04"),
+    ("blob with bad padding", b"/abc\0\0\0\0,b\0\0\0\0\0\x01\x01\x01\0\0"),
+    ("invalid char", b"/abc\0\0\0\0,c\0\0\0\x11\0\0"),
+    ("unterminated array", b"/abc\0\0\0\0,[i\0\0\0\0\x01"),
+    ("unopened array", b"/abc\0\0\0\0,]\0\0"),
+    ("truncated bundle time tag", b"#bundle\0\0\0\0\0"),
+    (
+        "negative bundle element size",
+        b"#bundle\0\0\0\0\0\0\0\0\x01\xff\xff\xff\xf8/abc\0\0\0\0",
+    ),
+    (
+        "bundle element larger than bundle",
+        b"#bundle\0\0\0\0\0\0\0\0\x01\0\0\0\x10/abc\0\0\0\0",
+    ),
+    (
+        "bundle element with truncated size",
+        b"#bundle\0\0\0\0\0\0\0\0\x01\0\0",
+    ),
+    ("packet that is neither message nor bundle", b"abc\0,\0\0\0"),
+    ("truncated bundle tag", b"#bun\0\0\0\0\0\0\0\0\0\0\0\x01"),
+    ("corrupted bundle tag", b"#bundlX\0\0\0\0\0\0\0\0\x01"),
+    (
+        "empty bundle element",
+        b"#bundle\0\0\0\0\0\0\0\0\x01\0\0\0\0",
+    ),
+    (
+        "bundle element size not a multiple of 4",
+        b"#bundle\0\0\0\0\0\0\0\0\x01\0\0\0\x06/abc\0\0\0\0",
+    ),
+];
+
+fn nested_bundles(depth: usize) -> Vec<u8> {
+    let mut packet = b"/abc\0\0\0\0,\0\0\0".to_vec();
+    for _ in 0..depth {
+        let mut bundle = b"#bundle\0\0\0\0\0\0\0\0\x01".to_vec();
+        bundle.extend((packet.len() as u32).to_be_bytes());
+        bundle.extend(packet);
+        packet = bundle;
+    }
+    packet
+}
+
+fn assert_rejected(name: &str, packet: &[u8]) {
+    assert!(decoder::decode_udp(packet).is_err(), "{}: decode_udp", name);
+    assert!(decoder::decode_ref(packet).is_err(), "{}: decode_ref", name);
+    assert!(
+        decoder::decode_visit(packet, &mut Discard).is_err(),
+        "{}: decode_visit",
+        name
+    );
+    assert!(
+        decoder::decode_with_options(packet, &DecoderOptions::default()).is_err(),
+        "{}: decode_with_options",
+        name
+    );
+
+    let mut framed = (packet.len() as u32).to_be_bytes().to_vec();
+    framed.extend(packet);
+    assert!(decoder::decode_tcp(&framed).is_err(), "{}: decode_tcp", name);
+    assert!(
+        decoder::decode_tcp_vec(&framed).is_err(),
+        "{}: decode_tcp_vec",
+        name
+    );
+}
+
+#[test]
+fn test_malformed_packets_are_rejected() {
+    for (name, packet) in MALFORMED {
+        assert_rejected(name, packet);
+    }
+}
+
+#[test]
+fn test_truncated_packets_are_rejected() {
+    let packet = encoder::encode(&OscPacket::Bundle(OscBundle {
+        timetag: (0, 1).into(),
+        content: vec![OscPacket::Message(OscMessage {
+            addr: "/abc".to_string(),
+            args: vec![
+                1i32.into(),
+                "a".into(),
+                vec![1u8, 2, 3, 4, 5].into(),
+                'x'.into(),
+                0.5f64.into(),
+            ],
+        })],
+    }))
+    .unwrap();
+    assert!(decoder::decode_udp(&packet).is_ok());
+
+    for len in 1..packet.len() {
+        if len == 16 {
+            // Header only, which is a valid empty bundle
+            continue;
+        }
+        assert_rejected(&format!("truncated to {} bytes", len), &packet[..len]);
+    }
+}
+
+#[test]
+fn test_deeply_nested_arrays_are_rejected() {
+    let depth = 100_000;
+    let mut type_tags = ",".to_string();
+    type_tags.extend(std::iter::repeat_n('[', depth));
+    type_tags.extend(std::iter::repeat_n(']', depth));
+    let mut packet = encoder::encode_string("/abc");
+    packet.extend(encoder::encode_string(type_tags));
+    assert_rejected("deeply nested arrays", &packet);
+
+    if let Ok(LazyPacket::Message(msg)) = decoder::decode_lazy(&packet) {
+        assert!(msg.args().any(|arg| arg.is_err()));
+    }
+}
+
+#[test]
+fn test_deeply_nested_bundles_are_rejected() {
+    assert_rejected("deeply nested bundles", &nested_bundles(1000));
+}
+
+fn bundle_error(packet: &[u8]) -> String {
+    match decoder::decode_with_options(packet, &DecoderOptions::default())
+        .as_ref()
+        .map_err(DecodeError::root_cause)
+    {
+        Err(DecodeError::BadBundle(msg)) => msg.clone(),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_bundle_validation_errors() {
+    match decoder::decode_with_options(b"#bun\0\0\0\0\0\0\0\0", &DecoderOptions::default())
+        .as_ref()
+        .map_err(DecodeError::root_cause)
+    {
+        Err(DecodeError::BadBundleHeader(found)) => assert_eq!(found, b"#bun\0\0\0\0"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let header = b"#bundle\0\0\0\0\0\0\0\0\x01";
+    let element = |size: u32| {
+        let mut packet = header.to_vec();
+        packet.extend(size.to_be_bytes());
+        packet.extend(b"/abc\0\0\0\0,\0\0\0");
+        packet
+    };
+    assert!(decoder::decode_with_options(&element(12), &DecoderOptions::default()).is_ok());
+    assert_eq!(bundle_error(&element(0)), "Empty bundle element");
+    assert_eq!(bundle_error(&element(0xffff_fff4)), "Negative bundle element size");
+    assert_eq!(
+        bundle_error(&element(10)),
+        "Bundle element size 10 is not a multiple of 4"
+    );
+    assert_eq!(
+        bundle_error(&element(16)),
+        "Bundle element of 16 bytes exceeds the remaining 12 bytes of the bundle"
+    );
+}
+
+#[test]
+fn test_truncated_fixed_size_arguments() {
+    let args: &[(char, usize)] = &[
+        ('i', 4),
+        ('f', 4),
+        ('c', 4),
+        ('r', 4),
+        ('m', 4),
+        ('h', 8),
+        ('d', 8),
+        ('t', 8),
+    ];
+    for &(tag, size) in args {
+        // The truncated argument follows an int, so it's the second argument
+        let mut packet = encoder::encode_string("/abc");
+        packet.extend(encoder::encode_string(format!(",i{}", tag)));
+        packet.extend(1i32.to_be_bytes());
+        for remaining in (0..size).step_by(4) {
+            let mut truncated = packet.clone();
+            truncated.extend(vec![0u8; remaining]);
+            match decoder::decode_with_options(&truncated, &DecoderOptions::default())
+                .as_ref()
+                .map_err(DecodeError::root_cause)
+            {
+                Err(&DecodeError::Truncated {
+                    expected,
+                    remaining: left,
+                    arg_index,
+                }) => {
+                    assert_eq!((expected, left, arg_index), (size, remaining, 1), "{}", tag)
+                }
+                other => panic!("{}: unexpected result: {:?}", tag, other),
+            }
+            assert_rejected(&format!("truncated '{}'", tag), &truncated);
+        }
+    }
+}
+
+fn root_cause(packet: &[u8]) -> DecodeError {
+    match decoder::decode_with_options(packet, &DecoderOptions::default()) {
+        Err(DecodeError::At { source, .. }) => *source,
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_structured_argument_errors() {
+    assert!(matches!(
+        root_cause(b"/abc\0\0\0\0,X\0\0"),
+        DecodeError::UnknownTypeTag('X')
+    ));
+    assert!(matches!(
+        root_cause(b"/abc\0\0\0\0,c\0\0\0\x11\0\0"),
+        DecodeError::InvalidChar(0x11_0000)
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_error_sources() {
+    use std::error::Error;
+
+    let err = root_cause(b"/abc\0\0\0\0,s\0\0\xc3\x28\0\0");
+    assert!(matches!(err, DecodeError::StringError(_)));
+    assert!(err.source().unwrap().is::<std::string::FromUtf8Error>());
+
+    let err = decoder::decode_with_options(b"/abc\0\0\0\0,X\0\0", &DecoderOptions::default())
+        .unwrap_err();
+    assert!(err.source().unwrap().is::<DecodeError>());
+    assert!(OscError::from(err).source().unwrap().is::<OscError>());
+
+    let err = OscError::from(std::io::Error::from(std::io::ErrorKind::TimedOut));
+    assert!(err.source().unwrap().is::<std::io::Error>());
+    assert!(OscError::Unimplemented.source().is_none());
+}
+
+#[cfg(feature = "core-error")]
+#[test]
+fn test_error_trait_without_std() {
+    // Uses `core::error::Error`, which the `core-error` feature implements without std as well
+    let err = root_cause(b"/abc\0\0\0\0,s\0\0\xc3\x28\0\0");
+    let err: &dyn core::error::Error = &err;
+    assert!(err.source().is_some());
+}
+
+#[test]
+fn test_errors_can_be_compared_and_cloned() {
+    let err = decoder::decode_with_options(b"/abc\0\0\0\0,X\0\0", &DecoderOptions::default())
+        .unwrap_err();
+    assert_eq!(err.clone(), err);
+    assert_eq!(err.root_cause(), &DecodeError::UnknownTypeTag('X'));
+    assert_ne!(err.root_cause(), &DecodeError::UnknownTypeTag('Y'));
+    assert_ne!(OscError::BadPacket("a"), OscError::BadMessage("a"));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_io_errors_are_compared_by_kind() {
+    use std::io;
+
+    let err = OscError::from(io::Error::new(io::ErrorKind::TimedOut, "first"));
+    assert_eq!(err, OscError::from(io::Error::new(io::ErrorKind::TimedOut, "second")));
+    assert_ne!(err, OscError::from(io::Error::from(io::ErrorKind::WouldBlock)));
+    match err.clone() {
+        OscError::Io(clone) => {
+            assert_eq!(clone.kind(), io::ErrorKind::TimedOut);
+            assert_eq!(clone.to_string(), "first");
+        }
+        other => panic!("unexpected clone: {:?}", other),
+    }
+}
@@ -0,0 +1,616 @@
+extern crate rosc;
+
+#[cfg(feature = "std")]
+use rosc::decoder::DecoderOptions;
+#[cfg(feature = "std")]
+use rosc::net::{self, OscSender, OscUdpSocket, SocketStats};
+#[cfg(feature = "std")]
+use rosc::{OscBundle, OscError, OscMessage, OscPacket, OscType};
+
+#[cfg(feature = "std")]
+#[test]
+fn test_udp_socket_round_trip() {
+    let sender = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let mut receiver = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let receiver_addr = receiver.socket().local_addr().unwrap();
+
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/ping".to_string(),
+        args: vec![1i32.into(), "pong".into()],
+    });
+    assert_eq!(sender.send_to(&packet, receiver_addr).unwrap(), 24);
+    let (received, addr) = receiver.recv_from().unwrap();
+    assert_eq!(received, packet);
+    assert_eq!(addr, sender.socket().local_addr().unwrap());
+
+    // Decoding errors don't affect subsequent packets
+    receiver.set_decoder_options(DecoderOptions {
+        max_args: 1,
+        ..Default::default()
+    });
+    sender.send_to(&packet, receiver_addr).unwrap();
+    match receiver.recv_from().map_err(|err| err.root_cause().to_string()) {
+        Err(msg) => assert_eq!(msg, "OSC message with 2 arguments exceeds the limit of 1"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let ping = OscPacket::Message(OscMessage::from("/ping"));
+    sender.send_to(&ping, receiver_addr).unwrap();
+    assert_eq!(receiver.recv_from().unwrap().0, ping);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_udp_socket_io_error() {
+    match OscUdpSocket::bind("256.0.0.1:0") {
+        Err(OscError::Io(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_udp_socket_recv_timeout() {
+    use std::time::Duration;
+
+    let sender = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let mut receiver = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let receiver_addr = receiver.socket().local_addr().unwrap();
+    let timeout = Duration::from_millis(20);
+
+    assert!(receiver.try_recv().unwrap().is_none());
+    assert!(receiver.recv_timeout(timeout).unwrap().is_none());
+    assert_eq!(receiver.socket().read_timeout().unwrap(), None);
+
+    let ping = OscPacket::Message(OscMessage::from("/ping"));
+    sender.send_to(&ping, receiver_addr).unwrap();
+    let (packet, _) = receiver.recv_timeout(Duration::from_secs(5)).unwrap().unwrap();
+    assert_eq!(packet, ping);
+
+    sender.send_to(&ping, receiver_addr).unwrap();
+    let mut received = None;
+    for _ in 0..500 {
+        received = receiver.try_recv().unwrap();
+        if received.is_some() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(received.map(|(packet, _)| packet), Some(ping));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_udp_socket_recv_all_from() {
+    use rosc::encoder;
+
+    let sender = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let sender_addr = sender.socket().local_addr().unwrap();
+    let mut receiver = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let receiver_addr = receiver.socket().local_addr().unwrap();
+
+    let first = OscPacket::Message(OscMessage::from("/first"));
+    let second = OscPacket::Message(OscMessage {
+        addr: "/second".to_string(),
+        args: vec![2i32.into()],
+    });
+    let mut datagram = encoder::encode(&first).unwrap();
+    datagram.extend(encoder::encode(&second).unwrap());
+    sender.socket().send_to(&datagram, receiver_addr).unwrap();
+    assert_eq!(receiver.recv_all_from().unwrap(), (vec![first.clone(), second], sender_addr));
+
+    // A single packet
+    sender.send_to(&first, receiver_addr).unwrap();
+    assert_eq!(receiver.recv_all_from().unwrap(), (vec![first], sender_addr));
+
+    // Trailing garbage
+    datagram.extend(b"junk");
+    sender.socket().send_to(&datagram, receiver_addr).unwrap();
+    match receiver.recv_all_from() {
+        Err(OscError::AfterPackets { decoded: 2, .. }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(receiver.last_peer(), Some(sender_addr));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_udp_socket_stats() {
+    use rosc::{OscErrorKind, OscTime};
+    use std::sync::Arc;
+
+    let sender = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let sender_addr = sender.socket().local_addr().unwrap();
+    let mut receiver = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let receiver_addr = receiver.socket().local_addr().unwrap();
+    assert_eq!(receiver.stats(), None);
+    let stats = Arc::new(SocketStats::new());
+    receiver.set_stats(stats.clone());
+    assert_eq!(receiver.stats(), Some(Default::default()));
+
+    let before = OscTime::now();
+    let packet = OscPacket::Message(OscMessage::from("/ping"));
+    let size = sender.send_to(&packet, receiver_addr).unwrap();
+    receiver.recv_from().unwrap();
+    // Malformed datagrams of different kinds
+    sender.socket().send_to(b"/ping\0\0", receiver_addr).unwrap();
+    sender.socket().send_to(b"/ping\0\0\0,X\0\0", receiver_addr).unwrap();
+    sender.socket().send_to(b"/ping\0\0\0,X\0\0", receiver_addr).unwrap();
+    for _ in 0..3 {
+        assert!(receiver.recv_from().is_err());
+    }
+    receiver.reply(&packet).unwrap();
+
+    let snapshot = receiver.stats().unwrap();
+    assert_eq!(snapshot, stats.snapshot());
+    assert_eq!(snapshot.datagrams_received, 4);
+    assert_eq!(snapshot.bytes_received, size as u64 + 7 + 12 + 12);
+    assert_eq!(snapshot.decode_errors, 3);
+    assert_eq!(snapshot.errors_by_kind.values().sum::<u64>(), 3);
+    assert_eq!(snapshot.errors_by_kind.get(&OscErrorKind::UnknownTypeTag), Some(&2));
+    assert_eq!(snapshot.datagrams_sent, 1);
+    assert_eq!(snapshot.bytes_sent, size as u64);
+    assert_eq!(snapshot.last_packets.len(), 1);
+    let last_packet = snapshot.last_packets[&sender_addr];
+    assert!(last_packet >= before && last_packet <= OscTime::now());
+
+    stats.reset();
+    assert_eq!(receiver.stats(), Some(Default::default()));
+    sender.send_to(&packet, receiver_addr).unwrap();
+    receiver.recv_from().unwrap();
+    assert_eq!(stats.snapshot().datagrams_received, 1);
+    assert_eq!(stats.snapshot().decode_errors, 0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_udp_socket_recv_packet() {
+    use rosc::OscTime;
+    use std::time::Duration;
+
+    let sender = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let sender_addr = sender.socket().local_addr().unwrap();
+    let mut receiver = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let receiver_addr = receiver.socket().local_addr().unwrap();
+    assert!(receiver.try_recv_packet().unwrap().is_none());
+
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (1000, 0).into(),
+        content: vec![
+            OscPacket::Message(OscMessage::from("/a")),
+            OscPacket::Bundle(OscBundle {
+                timetag: (2000, 0).into(),
+                content: vec![OscPacket::Message(OscMessage::from("/b"))],
+            }),
+        ],
+    });
+    let before = OscTime::now();
+    sender.send_to(&packet, receiver_addr).unwrap();
+    let received = receiver.recv_packet().unwrap();
+    assert_eq!(received.packet, packet);
+    assert_eq!(received.source, sender_addr);
+    assert!(received.received_at >= before && received.received_at <= OscTime::now());
+
+    let received_at = received.received_at;
+    let messages: Vec<_> = received
+        .into_messages()
+        .map(|(context, msg)| (msg.addr, context.peer, context.timetag, context.received_at))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            ("/a".to_string(), Some(sender_addr), (1000, 0).into(), Some(received_at)),
+            ("/b".to_string(), Some(sender_addr), (2000, 0).into(), Some(received_at)),
+        ]
+    );
+
+    sender.send_to(&packet, receiver_addr).unwrap();
+    let received = receiver.recv_packet_timeout(Duration::from_secs(5)).unwrap().unwrap();
+    assert_eq!(received.source, sender_addr);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_sender_and_reply() {
+    let mut server = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let server_addr = server.socket().local_addr().unwrap();
+    let mut client = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let client_addr = client.socket().local_addr().unwrap();
+    let mut sender = OscSender::connect("127.0.0.1:0", server_addr).unwrap();
+    assert_eq!(sender.remote(), server_addr);
+
+    let ping = OscPacket::Message(OscMessage::from("/ping"));
+    let pong = OscPacket::Message(OscMessage::from("/pong"));
+    let reply_err = server.reply(&pong).expect_err("no packet received yet");
+    assert!(matches!(reply_err, OscError::Io(_)));
+
+    sender.send(&ping).unwrap();
+    assert_eq!(server.recv_from().unwrap().0, ping);
+    assert_eq!(server.last_peer(), Some(sender.socket().local_addr().unwrap()));
+    server.reply(&pong).unwrap();
+    let mut buf = [0u8; 64];
+    let size = sender.socket().recv(&mut buf).unwrap();
+    assert_eq!(rosc::decoder::decode_udp(&buf[..size]).unwrap().1, pong);
+
+    sender.reconnect(client_addr).unwrap();
+    sender.send(&ping).unwrap();
+    assert_eq!(client.recv_from().unwrap().0, ping);
+
+    sender.rebind("127.0.0.1:0").unwrap();
+    sender.send(&ping).unwrap();
+    let (packet, addr) = client.recv_from().unwrap();
+    assert_eq!((packet, addr), (ping, sender.socket().local_addr().unwrap()));
+
+    let oversized = OscPacket::Message(OscMessage {
+        addr: "/blob".to_string(),
+        args: vec![vec![0u8; 70_000].into()],
+    });
+    match sender.send(&oversized) {
+        Err(OscError::PacketTooLarge { max, .. }) => assert_eq!(max, 65_507),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+#[test]
+fn test_unix_datagram_round_trip() {
+    use rosc::net::OscUnixDatagram;
+
+    let dir = std::env::temp_dir().join(format!("rosc-net-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let receiver_path = dir.join("receiver.sock");
+    let sender_path = dir.join("sender.sock");
+
+    let mut receiver = OscUnixDatagram::bind(&receiver_path).unwrap();
+    receiver.set_remove_on_drop(true);
+    let sender = OscUnixDatagram::bind(&sender_path).unwrap();
+
+    let ping = OscPacket::Message(OscMessage::from("/ping"));
+    sender.send_to(&ping, &receiver_path).unwrap();
+    let (packet, addr) = receiver.recv_from().unwrap();
+    assert_eq!(packet, ping);
+    assert_eq!(addr.as_pathname(), Some(sender_path.as_path()));
+
+    let unbound = OscUnixDatagram::unbound().unwrap();
+    unbound.send_to(&ping, &receiver_path).unwrap();
+    assert_eq!(receiver.recv_from().unwrap().1.as_pathname(), None);
+
+    drop(receiver);
+    drop(sender);
+    assert!(!receiver_path.exists());
+    assert!(sender_path.exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_udp_socket_multicast() {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::Duration;
+
+    let group = Ipv4Addr::new(239, 255, 77, 77);
+    let mut receiver = match OscUdpSocket::bind_multicast(IpAddr::V4(group), 0) {
+        Ok(receiver) => receiver,
+        // No multicast capable interface, e.g. in a network sandbox
+        Err(OscError::Io(_)) => return,
+        Err(err) => panic!("unexpected error: {}", err),
+    };
+    let port = receiver.socket().local_addr().unwrap().port();
+    // Several receivers on the same host can share the port
+    let mut second = OscUdpSocket::bind_multicast(IpAddr::V4(group), port).unwrap();
+
+    let sender = OscUdpSocket::bind("0.0.0.0:0").unwrap();
+    sender.set_multicast_ttl(0).unwrap();
+    sender.set_multicast_loop(true).unwrap();
+    assert!(sender.socket().multicast_loop_v4().unwrap());
+    assert_eq!(sender.socket().multicast_ttl_v4().unwrap(), 0);
+
+    let ping = OscPacket::Message(OscMessage::from("/ping"));
+    if sender.send_to(&ping, (group, port)).is_err() {
+        return;
+    }
+    for receiver in [&mut receiver, &mut second] {
+        if let Some((packet, _)) = receiver.recv_timeout(Duration::from_secs(1)).unwrap() {
+            assert_eq!(packet, ping);
+        }
+    }
+    receiver
+        .leave_multicast_v4(group, Ipv4Addr::UNSPECIFIED)
+        .unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_udp_socket_broadcast() {
+    let socket = OscUdpSocket::bind("0.0.0.0:0").unwrap();
+    assert!(!socket.socket().broadcast().unwrap());
+    socket.enable_broadcast().unwrap();
+    assert!(socket.socket().broadcast().unwrap());
+
+    // Encoding errors are reported before anything is sent
+    let invalid = OscPacket::Message(OscMessage {
+        addr: "/large".to_string(),
+        args: vec![vec![0u8; 70_000].into()],
+    });
+    assert!(matches!(
+        socket.send_broadcast(&invalid, 9),
+        Err(OscError::PacketTooLarge { .. })
+    ));
+
+    // Sending may be rejected by a network sandbox, but only with an IO error
+    let ping = OscPacket::Message(OscMessage::from("/ping"));
+    match socket.send_broadcast(&ping, 9) {
+        Ok(size) => assert_eq!(size, 12),
+        Err(OscError::Io(_)) => {}
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_serve() {
+    use rosc::dispatch::AddressSpace;
+    use rosc::net::serve_with_clock;
+    use rosc::{ManualClock, OscBundle};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::time::Duration;
+
+    let socket = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let server_addr = socket.socket().local_addr().unwrap();
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let (calls, received) = mpsc::channel();
+    let (errors, failed) = mpsc::channel();
+    let clock = ManualClock::new((1000, 0).into());
+
+    let server = {
+        let shutdown = shutdown.clone();
+        let clock = clock.clone();
+        std::thread::spawn(move || {
+            let mut router = AddressSpace::new();
+            router
+                .add_method("/ping", move |msg| calls.send(msg.args.clone()).unwrap())
+                .unwrap();
+            serve_with_clock(
+                socket,
+                &mut router,
+                clock,
+                || shutdown.load(Ordering::SeqCst),
+                |err| errors.send(err.to_string()).unwrap(),
+            )
+        })
+    };
+
+    let sender = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    sender
+        .send_to(
+            &OscPacket::Bundle(OscBundle {
+                timetag: (1000, 1 << 31).into(),
+                content: vec![OscPacket::Message(OscMessage {
+                    addr: "/ping".to_string(),
+                    args: vec![2i32.into()],
+                })],
+            }),
+            server_addr,
+        )
+        .unwrap();
+    sender
+        .socket()
+        .send_to(b"/ping\0\0\0,i\0\0", server_addr)
+        .unwrap();
+    sender
+        .send_to(
+            &OscPacket::Message(OscMessage {
+                addr: "/ping".to_string(),
+                args: vec![1i32.into()],
+            }),
+            server_addr,
+        )
+        .unwrap();
+
+    let timeout = Duration::from_secs(5);
+    // The bundle is held until the clock reaches its time tag
+    assert_eq!(received.recv_timeout(timeout).unwrap(), vec![1i32.into()]);
+    assert!(failed.recv_timeout(timeout).is_ok());
+    clock.advance(Duration::from_millis(500));
+    assert_eq!(received.recv_timeout(timeout).unwrap(), vec![2i32.into()]);
+
+    shutdown.store(true, Ordering::SeqCst);
+    server.join().unwrap().unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_send_once() {
+    use std::time::Duration;
+
+    let mut receiver = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let target = receiver.socket().local_addr().unwrap();
+    let timeout = Duration::from_secs(5);
+
+    net::send_once(target, "/gain", vec![0.5f32]).unwrap();
+    let (packet, _) = receiver.recv_timeout(timeout).unwrap().unwrap();
+    assert_eq!(
+        packet,
+        OscPacket::Message(OscMessage {
+            addr: "/gain".to_string(),
+            args: vec![OscType::Float(0.5)],
+        })
+    );
+
+    let bundle = OscPacket::Bundle(OscBundle {
+        timetag: (1, 0).into(),
+        content: vec![OscPacket::Message(OscMessage::from("/a"))],
+    });
+    net::send_packet_once(target, &bundle).unwrap();
+    assert_eq!(receiver.recv_timeout(timeout).unwrap().unwrap().0, bundle);
+
+    let args: Vec<OscType> = vec![1i32.into(), "label".into()];
+    net::send_once_timeout(target, "/label", args.clone(), timeout).unwrap();
+    let (packet, _) = receiver.recv_timeout(timeout).unwrap().unwrap();
+    assert_eq!(
+        packet,
+        OscPacket::Message(OscMessage {
+            addr: "/label".to_string(),
+            args,
+        })
+    );
+
+    net::send_once(target, "/mixer/*/mute", vec![true]).unwrap();
+    let (packet, _) = receiver.recv_timeout(timeout).unwrap().unwrap();
+    assert_eq!(
+        packet,
+        OscPacket::Message(OscMessage {
+            addr: "/mixer/*/mute".to_string(),
+            args: vec![OscType::Bool(true)],
+        })
+    );
+
+    assert!(matches!(
+        net::send_once(target, "/bad[address", Vec::<OscType>::new()),
+        Err(OscError::BadAddress(_))
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_udp_socket_nonblocking_burst() {
+    use std::net::UdpSocket;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    let mut receiver = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver.set_nonblocking(true).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::io::AsRawFd;
+        assert_eq!(receiver.as_raw_fd(), receiver.socket().as_raw_fd());
+    }
+    let target = receiver.socket().local_addr().unwrap();
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    // Each burst contains a datagram that fails to decode, which must not hide the others
+    let mut received = 0;
+    let mut errors = 0;
+    for burst in 0..3 {
+        for i in 0..20 {
+            let packet = OscPacket::Message(OscMessage::from(format!("/burst/{}/{}", burst, i)));
+            sender.send_to(&rosc::encoder::encode(&packet).unwrap(), target).unwrap();
+        }
+        sender.send_to(b"junk", target).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while received + errors < (burst + 1) * 21 && Instant::now() < deadline {
+            loop {
+                match receiver.try_recv() {
+                    Ok(Some(_)) => received += 1,
+                    Ok(None) => break,
+                    Err(_) => errors += 1,
+                }
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+    assert_eq!((received, errors), (60, 3));
+    assert!(receiver.try_recv().unwrap().is_none());
+
+    // The socket stays in non-blocking mode
+    let err = receiver.recv_from().unwrap_err();
+    assert!(matches!(err, OscError::Io(ref err) if err.kind() == std::io::ErrorKind::WouldBlock));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_udp_socket_buffer_pool() {
+    use rosc::pool::BufferPool;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let pool = Arc::new(BufferPool::default());
+    let mut sender = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    sender.set_buffer_pool(pool.clone());
+    let mut receiver = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let target = receiver.socket().local_addr().unwrap();
+
+    let packet = OscPacket::Message(OscMessage::from("/pooled"));
+    for _ in 0..3 {
+        sender.send_to(&packet, target).unwrap();
+        let received = receiver.recv_timeout(Duration::from_secs(5)).unwrap().unwrap();
+        assert_eq!(received.0, packet);
+    }
+    // The same buffer is used for every packet
+    assert_eq!(pool.len(), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_udp_socket_source_filter() {
+    use rosc::dispatch::AddressSpace;
+    use rosc::net::serve;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::time::Duration;
+
+    let allowed = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let allowed_addr = allowed.socket().local_addr().unwrap();
+    let blocked = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let ping = |value: i32| {
+        OscPacket::Message(OscMessage {
+            addr: "/ping".to_string(),
+            args: vec![value.into()],
+        })
+    };
+
+    let mut socket = OscUdpSocket::bind("127.0.0.1:0").unwrap();
+    let server_addr = socket.socket().local_addr().unwrap();
+    let stats = Arc::new(SocketStats::new());
+    socket.set_stats(stats.clone());
+    socket.set_source_filter(move |source| *source == allowed_addr);
+
+    // Blocked datagrams are dropped without waiting for the next one
+    blocked.send_to(&ping(0), server_addr).unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(socket.try_recv().unwrap(), None);
+    blocked.send_to(&ping(0), server_addr).unwrap();
+    assert_eq!(socket.recv_timeout(Duration::from_millis(100)).unwrap(), None);
+    assert_eq!(socket.last_peer(), None);
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let (calls, received) = mpsc::channel();
+    let (errors, failed) = mpsc::channel();
+    let server = {
+        let shutdown = shutdown.clone();
+        std::thread::spawn(move || {
+            let mut router = AddressSpace::new();
+            router
+                .add_method("/ping", move |msg| calls.send(msg.args.clone()).unwrap())
+                .unwrap();
+            serve(
+                socket,
+                &mut router,
+                || shutdown.load(Ordering::SeqCst),
+                |err| errors.send(err.to_string()).unwrap(),
+            )
+        })
+    };
+
+    for i in 1..=3 {
+        blocked.send_to(&ping(-i), server_addr).unwrap();
+        // Not even decoded, so it doesn't fail
+        blocked.socket().send_to(b"/ping\0\0\0,i\0\0", server_addr).unwrap();
+        allowed.send_to(&ping(i), server_addr).unwrap();
+    }
+    let timeout = Duration::from_secs(5);
+    for i in 1..=3 {
+        assert_eq!(received.recv_timeout(timeout).unwrap(), vec![i.into()]);
+    }
+    shutdown.store(true, Ordering::SeqCst);
+    server.join().unwrap().unwrap();
+    assert_eq!(received.try_recv().ok(), None);
+    assert_eq!(failed.try_recv().ok(), None);
+
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.filtered, 8);
+    assert_eq!(snapshot.datagrams_received, 3);
+    assert_eq!(snapshot.decode_errors, 0);
+    assert_eq!(snapshot.last_packets.keys().collect::<Vec<_>>(), vec![&allowed_addr]);
+}
@@ -0,0 +1,75 @@
+extern crate rosc;
+
+#[cfg(feature = "std")]
+use rosc::pool::BufferPool;
+#[cfg(feature = "std")]
+use rosc::{decoder, encoder, OscMessage, OscPacket};
+
+#[cfg(feature = "std")]
+#[test]
+fn test_buffers_are_reused() {
+    let pool = BufferPool::new(2, 1024);
+    let mut buf = pool.get();
+    buf.extend_from_slice(b"data");
+    let ptr = buf.as_ptr();
+    drop(buf);
+
+    let buf = pool.get();
+    assert!(buf.is_empty());
+    assert_eq!(buf.as_ptr(), ptr);
+    assert!(pool.is_empty());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_pool_is_bounded() {
+    let pool = BufferPool::new(2, 1024);
+    let buffers: Vec<_> = (0..4).map(|_| pool.get()).collect();
+    drop(buffers);
+    assert_eq!(pool.len(), 2);
+
+    // Buffers that grew too large are discarded
+    let mut buf = pool.get();
+    buf.reserve(4096);
+    drop(buf);
+    assert_eq!(pool.len(), 1);
+
+    pool.put(Vec::with_capacity(16));
+    pool.put(Vec::with_capacity(16));
+    assert_eq!(pool.len(), 2);
+
+    let buf = pool.get().into_inner();
+    assert_eq!(pool.len(), 1);
+    drop(buf);
+    assert_eq!(pool.len(), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_pool_shared_between_threads() {
+    use std::sync::Arc;
+    use std::thread;
+
+    let pool = Arc::new(BufferPool::new(4, 64 * 1024));
+    let threads: Vec<_> = (0..8)
+        .map(|t| {
+            let pool = pool.clone();
+            thread::spawn(move || {
+                for i in 0..1000 {
+                    let packet = OscPacket::Message(OscMessage {
+                        addr: format!("/thread/{}", t),
+                        args: vec![(i as i32).into(), vec![t as u8; i % 64].into()],
+                    });
+                    let mut buf = pool.get();
+                    assert!(buf.is_empty());
+                    encoder::encode_into(&packet, &mut buf).unwrap();
+                    assert_eq!(decoder::decode_udp(&buf).unwrap().1, packet);
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert!(pool.len() <= 4);
+}
@@ -0,0 +1,43 @@
+extern crate rosc;
+
+use rosc::prelude::*;
+
+#[test]
+fn test_prelude_types_and_modules() {
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: OscTime::IMMEDIATE,
+        content: vec![OscPacket::Message(OscMessage {
+            addr: "/mixer/1".to_string(),
+            args: vec![
+                OscType::Array(OscArray {
+                    content: vec![1i32.into()],
+                }),
+                OscType::Color(OscColor {
+                    red: 1,
+                    green: 2,
+                    blue: 3,
+                    alpha: 4,
+                }),
+                OscType::Midi(OscMidiMessage {
+                    port: 1,
+                    status: 2,
+                    data1: 3,
+                    data2: 4,
+                }),
+            ],
+        })],
+    });
+    let buf: Result<Vec<u8>, EncodeError> = encoder::encode(&packet);
+    let decoded: Result<OscPacket, DecodeError> =
+        decoder::decode_with_options(&buf.unwrap(), &DecoderOptions::default());
+    assert_eq!(decoded.unwrap(), packet);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_prelude_matcher() {
+    let matcher = Matcher::with_options("/mixer/*", &MatcherOptions::default()).unwrap();
+    assert!(matcher.match_address("/mixer/1").unwrap());
+    let err: AddressError = Matcher::new("mixer").unwrap_err();
+    assert_eq!(err.code(), 1002);
+}
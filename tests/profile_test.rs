@@ -0,0 +1,188 @@
+extern crate rosc;
+
+use rosc::address::{AddressError, Matcher, MatcherOptions};
+#[cfg(feature = "std")]
+use rosc::decoder::{self, DecodeError, DecoderOptions};
+#[cfg(feature = "std")]
+use rosc::encoder::{self, EncodeError, EncoderOptions};
+use rosc::profile::Profile;
+#[cfg(feature = "std")]
+use rosc::{OscArray, OscBundle, OscError, OscMessage, OscPacket, OscType};
+
+/// Returns a bundle using types that only OSC 1.1 peers are expected to support.
+#[cfg(feature = "std")]
+fn full_packet() -> OscPacket {
+    OscPacket::Bundle(OscBundle {
+        timetag: (1, 0).into(),
+        content: vec![
+            OscPacket::Message(OscMessage {
+                addr: "/level".to_string(),
+                args: vec![OscType::Float(0.5), OscType::Int(1)],
+            }),
+            OscPacket::Message(OscMessage {
+                addr: "/mute".to_string(),
+                args: vec![
+                    "ch1".into(),
+                    OscType::Array(OscArray {
+                        content: vec![OscType::Bool(true), OscType::Nil],
+                    }),
+                ],
+            }),
+        ],
+    })
+}
+
+#[test]
+fn test_profile_supports_tag() {
+    for tag in "ifsb".chars() {
+        assert!(Profile::CORE_1_0.supports_tag(tag), "{}", tag);
+    }
+    for tag in "hdtcrmTFNI[]".chars() {
+        assert!(!Profile::CORE_1_0.supports_tag(tag), "{}", tag);
+        assert!(Profile::FULL_1_1.supports_tag(tag), "{}", tag);
+    }
+    for tag in "uSX,é".chars() {
+        assert!(!Profile::FULL_1_1.supports_tag(tag), "{}", tag);
+    }
+    assert!(!Profile::CORE_1_0.allows_path_traversal());
+    assert!(!Profile::CORE_1_0.allows_slip());
+    assert!(Profile::FULL_1_1.allows_path_traversal());
+    assert!(Profile::FULL_1_1.allows_slip());
+    assert_eq!(Profile::default(), Profile::FULL_1_1);
+
+    let profile = Profile::builder()
+        .type_tags("ifsb")
+        .type_tags("TFuX")
+        .path_traversal(true)
+        .build();
+    assert!(profile.supports_tag('i'));
+    assert!(profile.supports_tag('F'));
+    assert!(!profile.supports_tag('u'));
+    assert!(!profile.supports_tag('X'));
+    assert!(!profile.supports_tag('['));
+    assert!(profile.allows_path_traversal());
+    assert!(!profile.allows_slip());
+    assert_eq!(Profile::builder().type_tags("ifsb").build(), Profile::CORE_1_0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_profile_encode() {
+    let packet = full_packet();
+    let core = EncoderOptions {
+        profile: Profile::CORE_1_0,
+    };
+    assert_eq!(
+        encoder::encode_with_options(&packet, &core),
+        Err(EncodeError::UnsupportedTypeTag('['))
+    );
+    let full = EncoderOptions::default();
+    assert_eq!(encoder::encode_with_options(&packet, &full), encoder::encode(&packet));
+
+    let with_arrays = EncoderOptions {
+        profile: Profile::builder().type_tags("ifsb[]N").build(),
+    };
+    assert_eq!(
+        encoder::encode_with_options(&packet, &with_arrays),
+        Err(EncodeError::UnsupportedTypeTag('T'))
+    );
+    assert_eq!(
+        OscError::from(EncodeError::UnsupportedTypeTag('T')),
+        OscError::UnsupportedTypeTag('T')
+    );
+
+    // Extension tags are not restricted by profiles
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/count".to_string(),
+        args: vec![OscType::Unsigned(1)],
+    });
+    assert_eq!(encoder::encode_with_options(&packet, &core), encoder::encode(&packet));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_profile_decode() {
+    let buf = encoder::encode(&full_packet()).unwrap();
+    let core = DecoderOptions {
+        profile: Profile::CORE_1_0,
+        ..Default::default()
+    };
+    let err = decoder::decode_with_options(&buf, &core).unwrap_err();
+    assert_eq!(err.root_cause(), &DecodeError::UnsupportedTypeTag('['));
+    assert_eq!(decoder::decode_with_options(&buf, &DecoderOptions::default()), Ok(full_packet()));
+
+    // The messages that only use the allowed types still decode
+    let level = encoder::encode(&OscPacket::Message(OscMessage {
+        addr: "/level".to_string(),
+        args: vec![OscType::Float(0.5), "x".into(), OscType::Blob(vec![1])],
+    }))
+    .unwrap();
+    assert!(decoder::decode_with_options(&level, &core).is_ok());
+
+    let buf = encoder::encode(&OscPacket::Message(OscMessage {
+        addr: "/count".to_string(),
+        args: vec![OscType::Unsigned(1)],
+    }))
+    .unwrap();
+    let err = decoder::decode_with_options(&buf, &core).unwrap_err();
+    assert_eq!(err.root_cause(), &DecodeError::UnknownTypeTag('u'));
+    let extensions = DecoderOptions {
+        accept_extension_tags: &['u'],
+        ..core
+    };
+    assert!(decoder::decode_with_options(&buf, &extensions).is_ok());
+}
+
+#[test]
+fn test_profile_match() {
+    let core = MatcherOptions {
+        profile: Profile::CORE_1_0,
+        ..Default::default()
+    };
+    for &(pattern, position) in &[("//", 0), ("/mixer//level", 6), ("mixer//level", 5)] {
+        assert_eq!(
+            Matcher::with_options(pattern, &core).unwrap_err(),
+            AddressError::InvalidPattern {
+                input: pattern.to_string(),
+                position,
+            }
+        );
+    }
+    // Matching `//` is not implemented, so it is rejected under any profile
+    assert!(Matcher::with_options("/mixer//level", &MatcherOptions::default()).is_err());
+    assert!(Matcher::with_options("/mixer/*", &core).is_ok());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_profile_in_every_layer() {
+    let profile = Profile::CORE_1_0;
+    let encoder_options = EncoderOptions { profile };
+    let decoder_options = DecoderOptions {
+        profile,
+        ..Default::default()
+    };
+    let matcher_options = MatcherOptions {
+        profile,
+        ..Default::default()
+    };
+
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/mixer/level".to_string(),
+        args: vec![OscType::Float(0.5)],
+    });
+    let buf = encoder::encode_with_options(&packet, &encoder_options).unwrap();
+    let decoded = decoder::decode_with_options(&buf, &decoder_options).unwrap();
+    assert_eq!(decoded, packet);
+    let matcher = Matcher::with_options("/mixer/*", &matcher_options).unwrap();
+    match decoded {
+        OscPacket::Message(msg) => assert!(matcher.match_address(&msg.addr).unwrap()),
+        OscPacket::Bundle(_) => unreachable!(),
+    }
+
+    let packet = full_packet();
+    assert!(encoder::encode_with_options(&packet, &encoder_options).is_err());
+    let buf = encoder::encode(&packet).unwrap();
+    assert!(decoder::decode_with_options(&buf, &decoder_options).is_err());
+    assert!(Matcher::with_options("/mixer//level", &matcher_options).is_err());
+}
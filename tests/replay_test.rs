@@ -0,0 +1,184 @@
+extern crate rosc;
+
+#[cfg(feature = "std")]
+use rosc::replay::{Direction, Player, Record, Recorder};
+#[cfg(feature = "std")]
+use rosc::{decoder, encoder, OscError, OscMessage, OscPacket, OscType};
+#[cfg(feature = "std")]
+use std::net::{SocketAddr, UdpSocket};
+#[cfg(feature = "std")]
+use std::thread;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "std")]
+fn message(addr: &str) -> OscPacket {
+    OscPacket::Message(OscMessage {
+        addr: addr.to_string(),
+        args: vec![OscType::Int(1)],
+    })
+}
+
+/// Returns a recording of three packets, the second of which is sent 20 ms after the first.
+#[cfg(feature = "std")]
+fn recording() -> Vec<u8> {
+    let v4: SocketAddr = "192.168.1.2:9000".parse().unwrap();
+    let v6: SocketAddr = "[::1]:57120".parse().unwrap();
+    let mut recorder = Recorder::new(Vec::new()).unwrap();
+    recorder
+        .record_packet(Direction::Received, Some(v4), &message("/a"))
+        .unwrap();
+    thread::sleep(Duration::from_millis(20));
+    recorder
+        .record_packet(Direction::Sent, Some(v6), &message("/b"))
+        .unwrap();
+    recorder
+        .record_packet(Direction::Received, None, &message("/c"))
+        .unwrap();
+    recorder.into_inner()
+}
+
+#[cfg(feature = "std")]
+fn bad_recording<T: std::fmt::Debug>(result: Result<T, OscError>) -> String {
+    match result {
+        Err(OscError::BadRecording(msg)) => msg,
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_record_and_play() {
+    let recording = recording();
+    assert_eq!(&recording[..9], b"ROSCREC\0\x01");
+
+    let mut player = Player::new(&recording[..]).unwrap();
+    let first = player.next_record().unwrap().unwrap();
+    assert_eq!(first.direction, Direction::Received);
+    assert_eq!(first.peer, Some("192.168.1.2:9000".parse().unwrap()));
+    assert_eq!(first.data, encoder::encode(&message("/a")).unwrap());
+    let second = player.next_record().unwrap().unwrap();
+    assert_eq!(second.direction, Direction::Sent);
+    assert_eq!(second.peer, Some("[::1]:57120".parse().unwrap()));
+    assert!(second.timestamp - first.timestamp >= Duration::from_millis(20));
+    let third = player.next_record().unwrap().unwrap();
+    assert_eq!(third.peer, None);
+    assert_eq!(player.next_record().unwrap(), None);
+
+    let packets: Vec<OscPacket> = Player::new(&recording[..])
+        .unwrap()
+        .map(|result| result.unwrap().1)
+        .collect();
+    assert_eq!(packets, vec![message("/a"), message("/b"), message("/c")]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_invalid_packets_are_recorded() {
+    let mut recorder = Recorder::new(Vec::new()).unwrap();
+    recorder.record(Direction::Received, None, b"junk").unwrap();
+    recorder
+        .record_packet(Direction::Received, None, &message("/a"))
+        .unwrap();
+    let recording = recorder.into_inner();
+
+    let mut player = Player::new(&recording[..]).unwrap();
+    assert!(player.next().unwrap().is_err());
+    assert_eq!(player.next().unwrap().unwrap().1, message("/a"));
+    assert!(player.next().is_none());
+
+    let record = Player::new(&recording[..]).unwrap().next_record().unwrap();
+    assert_eq!(
+        record.map(|record: Record| record.data),
+        Some(b"junk".to_vec())
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_bad_headers() {
+    assert_eq!(bad_recording(Player::new(&b""[..])), "Truncated header");
+    assert_eq!(bad_recording(Player::new(&b"ROSC"[..])), "Truncated header");
+    assert_eq!(
+        bad_recording(Player::new(&b"#bundle\0\0\0\0\0"[..])),
+        "Not an OSC recording"
+    );
+    assert_eq!(
+        bad_recording(Player::new(&b"ROSCREC\0\x02"[..])),
+        "Unsupported version 2"
+    );
+
+    let mut recording = recording();
+    recording[17] = 2;
+    assert_eq!(
+        bad_recording(Player::new(&recording[..]).unwrap().next_record()),
+        "Invalid direction 2"
+    );
+    recording[17] = 0;
+    recording[18] = 5;
+    assert_eq!(
+        bad_recording(Player::new(&recording[..]).unwrap().next_record()),
+        "Invalid peer address family 5"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_truncated_recordings() {
+    let recording = recording();
+    let records = Player::new(&recording[..]).unwrap().count();
+    assert_eq!(records, 3);
+
+    // Offsets at which a record ends
+    let mut boundaries = vec![9];
+    let mut player = Player::new(&recording[..]).unwrap();
+    while let Some(record) = player.next_record().unwrap() {
+        let peer_len = match record.peer {
+            None => 0,
+            Some(SocketAddr::V4(_)) => 6,
+            Some(SocketAddr::V6(_)) => 18,
+        };
+        boundaries.push(boundaries.last().unwrap() + 14 + peer_len + record.data.len());
+    }
+    assert_eq!(*boundaries.last().unwrap(), recording.len());
+
+    for len in 9..recording.len() {
+        let mut player = Player::new(&recording[..len]).unwrap();
+        let complete = boundaries.iter().filter(|&&end| end <= len).count() - 1;
+        for _ in 0..complete {
+            player.next().unwrap().unwrap();
+        }
+        if boundaries.contains(&len) {
+            assert!(player.next().is_none(), "truncated to {} bytes", len);
+        } else {
+            assert_eq!(bad_recording(player.next().unwrap()), "Truncated record");
+            assert!(player.next().is_none());
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_play_into() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    let recording = recording();
+    let target = receiver.local_addr().unwrap();
+    let start = Instant::now();
+    Player::new(&recording[..])
+        .unwrap()
+        .play_into(&sender, target, Direction::Received)
+        .unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    let mut buf = [0u8; 1024];
+    for addr in &["/a", "/c"] {
+        let (size, _) = receiver.recv_from(&mut buf).unwrap();
+        let (_, packet) = decoder::decode_udp(&buf[..size]).unwrap();
+        assert_eq!(packet, message(addr));
+    }
+}
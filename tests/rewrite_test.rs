@@ -0,0 +1,110 @@
+extern crate rosc;
+
+use rosc::decoder::{self, DecoderOptions};
+use rosc::rewrite::rewrite_addresses;
+use rosc::{encoder, OscBundle, OscError, OscMessage, OscPacket, OscType};
+
+/// Translates the prefix `/deviceA` to `/rig/1`.
+fn translate(addr: &str) -> Option<String> {
+    addr.strip_prefix("/deviceA/").map(|rest| format!("/rig/1/{}", rest))
+}
+
+fn message(addr: &str, args: Vec<OscType>) -> OscPacket {
+    OscPacket::Message(OscMessage {
+        addr: addr.to_string(),
+        args,
+    })
+}
+
+#[test]
+fn test_rewrite_message() {
+    // A NaN with a payload and a nonstandard type tag, which must survive untouched
+    let mut buf = encoder::encode_string("/deviceA/x");
+    buf.extend(encoder::encode_string(",fu"));
+    buf.extend_from_slice(&[0x7f, 0xc0, 0x12, 0x34, 0, 0, 0, 7]);
+
+    let mut expected = encoder::encode_string("/rig/1/x");
+    expected.extend_from_slice(&buf[12..]);
+    assert_eq!(rewrite_addresses(&buf, translate).unwrap(), expected);
+
+    // Non-canonical bytes are kept if the address stays the same
+    assert_eq!(rewrite_addresses(&buf, |_| None).unwrap(), buf);
+    let mut other = encoder::encode_string("/other");
+    other.extend_from_slice(&buf[12..]);
+    assert_eq!(rewrite_addresses(&other, translate).unwrap(), other);
+}
+
+#[test]
+fn test_rewrite_bundle() {
+    let packet = |prefix: &str| {
+        OscPacket::Bundle(OscBundle {
+            timetag: (1, 2).into(),
+            content: vec![
+                message(&format!("{}/gain", prefix), vec![OscType::Double(0.1)]),
+                message("/other", vec![OscType::Int(1)]),
+                OscPacket::Bundle(OscBundle {
+                    timetag: (3, 4).into(),
+                    content: vec![message(
+                        &format!("{}/a/long/name", prefix),
+                        vec!["x".into()],
+                    )],
+                }),
+            ],
+        })
+    };
+    let buf = encoder::encode(&packet("/deviceA")).unwrap();
+    assert_eq!(rewrite_addresses(&buf, |_| None).unwrap(), buf);
+
+    // The element sizes of both bundles change along with the addresses
+    let rewritten = rewrite_addresses(&buf, translate).unwrap();
+    assert_eq!(rewritten, encoder::encode(&packet("/rig/1")).unwrap());
+    assert_ne!(rewritten.len(), buf.len());
+    let elements: Vec<_> = decoder::iter_bundle_elements(&rewritten)
+        .unwrap()
+        .map(|element| element.unwrap().0.len())
+        .collect();
+    assert_eq!(elements, vec![24, 16, 48]);
+
+    let shorter = rewrite_addresses(&buf, |addr| {
+        addr.strip_prefix("/deviceA").map(|rest| format!("/A{}", rest))
+    })
+    .unwrap();
+    assert_eq!(
+        decoder::decode_with_options(&shorter, &DecoderOptions::default()).unwrap(),
+        packet("/A")
+    );
+}
+
+#[test]
+fn test_rewrite_errors() {
+    let buf = encoder::encode(&message("/deviceA/x", vec![])).unwrap();
+    match rewrite_addresses(&buf, |_| Some("no/slash".to_string())) {
+        Err(OscError::BadAddress(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(rewrite_addresses(b"", translate).is_err());
+    assert!(rewrite_addresses(b"nope\0\0\0\0", translate).is_err());
+
+    let mut bundle = encoder::encode(&OscPacket::Bundle(OscBundle {
+        timetag: (1, 0).into(),
+        content: vec![message("/deviceA/x", vec![])],
+    }))
+    .unwrap();
+    bundle[19] += 4;
+    let err = rewrite_addresses(&bundle, translate).unwrap_err();
+    assert!(err.to_string().contains("size of bundle element 0"), "{}", err);
+
+    // Bundles nested deeper than the decoder allows are rejected
+    let mut deep = message("/deviceA/x", vec![]);
+    for _ in 0..=decoder::MAX_BUNDLE_DEPTH {
+        deep = OscPacket::Bundle(OscBundle {
+            timetag: (1, 0).into(),
+            content: vec![deep],
+        });
+    }
+    let deep = encoder::encode(&deep).unwrap();
+    match rewrite_addresses(&deep, translate) {
+        Err(OscError::BundleTooDeep { max }) => assert_eq!(max, decoder::MAX_BUNDLE_DEPTH),
+        other => panic!("unexpected result: {:?}", other.map(|buf| buf.len())),
+    }
+}
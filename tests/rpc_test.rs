@@ -0,0 +1,114 @@
+extern crate rosc;
+
+#[cfg(feature = "std")]
+use rosc::rpc::{IdKind, IdPosition, PendingRequests, ReplyAddress, Request, RequestOptions};
+#[cfg(feature = "std")]
+use rosc::{ManualClock, OscError, OscMessage, OscType};
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+/// Returns a message to `addr` with the arguments `args`.
+#[cfg(feature = "std")]
+fn message(addr: &str, args: Vec<OscType>) -> OscMessage {
+    OscMessage {
+        addr: addr.to_string(),
+        args,
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_request() {
+    let first = Request::new("/status", vec![1i32]).unwrap();
+    let second = Request::new("/status", vec![1i32]).unwrap();
+    assert_ne!(first.id(), second.id());
+    match first.id() {
+        OscType::Int(id) => assert!(*id > 0),
+        id => panic!("unexpected ID: {:?}", id),
+    }
+    assert_eq!(first.message().args, vec![OscType::Int(1), first.id().clone()]);
+    assert_eq!(first.reply_address(), "/status/reply");
+
+    let options = RequestOptions {
+        id_kind: IdKind::String,
+        reply_address: ReplyAddress::Same,
+        id_position: IdPosition::First,
+    };
+    let request = Request::with_options("/get", vec!["gain"], &options).unwrap();
+    let id = match request.id() {
+        OscType::String(id) => id.clone(),
+        id => panic!("unexpected ID: {:?}", id),
+    };
+    assert!(id.parse::<u32>().is_ok());
+    assert_eq!(request.reply_address(), "/get");
+    assert!(request.is_reply(&message("/get", vec![id.as_str().into(), 0.5f32.into()])));
+    assert!(!request.is_reply(&message("/get", vec![0.5f32.into(), id.as_str().into()])));
+    // The request itself is sent to the reply address, but the ID is not at the reply position
+    assert!(!request.is_reply(request.message()));
+
+    let options = RequestOptions {
+        reply_address: ReplyAddress::Exact("/answers".to_string()),
+        ..Default::default()
+    };
+    let request = Request::with_options("/ask", Vec::<OscType>::new(), &options).unwrap();
+    assert_eq!(request.reply_address(), "/answers");
+
+    match Request::new("status", Vec::<OscType>::new()) {
+        Err(OscError::BadAddress(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_pending_requests() {
+    let mut pending = PendingRequests::new();
+    let status = Request::new("/status", vec![1i32]).unwrap();
+    let level = Request::new("/level", vec![1i32]).unwrap();
+    let status_token = pending.register(status.clone());
+    let level_token = pending.register(level.clone());
+    assert_ne!(status_token, level_token);
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending.get(level_token), Some(&level));
+
+    let reply = |request: &Request, id: &OscType, result: &str| {
+        let mut args = request.message().args.clone();
+        *args.last_mut().unwrap() = id.clone();
+        args.push(result.into());
+        message(request.reply_address(), args)
+    };
+    // Neither the ID of another request nor the address of another reply match
+    assert_eq!(pending.try_complete(&reply(&status, level.id(), "ok")), None);
+    assert_eq!(pending.try_complete(&reply(&level, status.id(), "ok")), None);
+    assert_eq!(pending.try_complete(status.message()), None);
+    assert_eq!(pending.len(), 2);
+
+    let level_reply = reply(&level, level.id(), "-6 dB");
+    assert_eq!(pending.try_complete(&level_reply), Some((level_token, level_reply.clone())));
+    assert_eq!(pending.try_complete(&level_reply), None);
+    assert_eq!(pending.get(level_token), None);
+
+    assert_eq!(pending.cancel(status_token), Some(status));
+    assert_eq!(pending.cancel(status_token), None);
+    assert!(pending.is_empty());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_pending_requests_expire() {
+    let clock = ManualClock::new((100, 0).into());
+    let mut pending = PendingRequests::with_clock(clock.clone());
+    let request = || Request::new("/ping", Vec::<OscType>::new()).unwrap();
+    let first = pending.register(request());
+    clock.advance(Duration::from_millis(500));
+    let second = pending.register(request());
+    let third = pending.register(request());
+    clock.advance(Duration::from_millis(500));
+
+    // A request exactly as old as the limit is kept
+    assert_eq!(pending.expire_older_than(Duration::from_secs(1)), vec![]);
+    assert_eq!(pending.expire_older_than(Duration::from_millis(999)), vec![first]);
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(pending.expire_older_than(Duration::from_secs(1)), vec![second, third]);
+    assert!(pending.is_empty());
+}
@@ -0,0 +1,191 @@
+extern crate rosc;
+
+#[cfg(feature = "std")]
+use rosc::scheduler::{Queue, Scheduler, SchedulerOptions};
+#[cfg(feature = "std")]
+use rosc::{ManualClock, OscBundle, OscError, OscMessage, OscPacket, OscTime};
+#[cfg(feature = "std")]
+use std::convert::TryFrom;
+#[cfg(feature = "std")]
+use std::sync::mpsc;
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime};
+
+/// The time the clocks of the tests start at.
+#[cfg(feature = "std")]
+const START: OscTime = OscTime {
+    seconds: 1000,
+    fractional: 0,
+};
+
+/// Returns a bundle with a message to `addr` that is due `delay` after [`START`].
+#[cfg(feature = "std")]
+fn bundle(delay: Duration, addr: &str) -> OscPacket {
+    let clock = ManualClock::new(START);
+    clock.advance(delay);
+    bundle_at(rosc::Clock::now(&clock), addr)
+}
+
+#[cfg(feature = "std")]
+fn bundle_at(timetag: OscTime, addr: &str) -> OscPacket {
+    OscPacket::Bundle(OscBundle {
+        timetag,
+        content: vec![OscPacket::Message(OscMessage::from(addr))],
+    })
+}
+
+/// Returns a scheduler whose clock starts at [`START`], which passes the address of each message
+/// along with the time of the clock when the message was delivered to the receiver.
+#[cfg(feature = "std")]
+fn recording(
+    options: SchedulerOptions,
+) -> (Scheduler, ManualClock, mpsc::Receiver<(String, OscTime)>) {
+    use rosc::Clock;
+
+    let clock = ManualClock::new(START);
+    let (tx, rx) = mpsc::channel();
+    let delivered_at = clock.clone();
+    let scheduler = Scheduler::with_clock(
+        move |msg| tx.send((msg.addr, delivered_at.now())).unwrap(),
+        options,
+        clock.clone(),
+    );
+    (scheduler, clock, rx)
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_messages_are_delivered_at_their_timetag() {
+    let (scheduler, clock, rx) = recording(SchedulerOptions::default());
+    scheduler.submit(bundle(Duration::from_secs(60), "/c")).unwrap();
+    scheduler.submit(bundle(Duration::from_secs(30), "/b")).unwrap();
+    scheduler
+        .submit(OscPacket::Message(OscMessage::from("/a")))
+        .unwrap();
+    scheduler.submit(bundle_at(START, "/past")).unwrap();
+
+    let timeout = Duration::from_secs(5);
+    assert_eq!(rx.recv_timeout(timeout).unwrap(), ("/a".to_string(), START));
+    assert_eq!(rx.recv_timeout(timeout).unwrap(), ("/past".to_string(), START));
+    assert_eq!(scheduler.pending(), 2);
+
+    // A message delivered too early would be received with the earlier time
+    clock.set((1029, u32::MAX).into());
+    scheduler.wake();
+    clock.set((1030, 0).into());
+    scheduler.wake();
+    assert_eq!(
+        rx.recv_timeout(timeout).unwrap(),
+        ("/b".to_string(), (1030, 0).into())
+    );
+    assert_eq!(scheduler.pending(), 1);
+
+    clock.advance(Duration::from_secs(30));
+    scheduler.wake();
+    assert_eq!(
+        rx.recv_timeout(timeout).unwrap(),
+        ("/c".to_string(), (1060, 0).into())
+    );
+    assert_eq!(scheduler.pending(), 0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_drain_delivers_pending_messages() {
+    let (scheduler, _, rx) = recording(SchedulerOptions::default());
+    scheduler.submit(bundle(Duration::from_secs(60), "/b")).unwrap();
+    scheduler.submit(bundle(Duration::from_secs(30), "/a")).unwrap();
+    assert_eq!(scheduler.pending(), 2);
+    scheduler.drain();
+    let addrs: Vec<String> = rx.try_iter().map(|(addr, _)| addr).collect();
+    assert_eq!(addrs, vec!["/a", "/b"]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_shutdown_drops_pending_messages() {
+    let (scheduler, _, rx) = recording(SchedulerOptions::default());
+    scheduler.submit(bundle(Duration::from_secs(60), "/a")).unwrap();
+    scheduler.shutdown();
+    assert!(rx.try_recv().is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_max_delay() {
+    let options = SchedulerOptions {
+        max_delay: Some(Duration::from_secs(10)),
+    };
+    let (scheduler, _, rx) = recording(options);
+
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: OscTime::IMMEDIATE,
+        content: vec![
+            OscPacket::Message(OscMessage::from("/now")),
+            bundle(Duration::from_secs(20), "/later"),
+        ],
+    });
+    assert!(matches!(
+        scheduler.submit(packet),
+        Err(OscError::TooFarInFuture(_))
+    ));
+    assert_eq!(scheduler.pending(), 0);
+
+    // The limit is exact, to the nanosecond which is the resolution of the delay
+    assert!(matches!(
+        scheduler.submit(bundle_at((1010, 5).into(), "/later")),
+        Err(OscError::TooFarInFuture(_))
+    ));
+    scheduler.submit(bundle_at((1010, 0).into(), "/soon")).unwrap();
+    assert_eq!(scheduler.pending(), 1);
+    drop(scheduler);
+    assert!(rx.try_recv().is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_queue_order() {
+    let mut queue = Queue::new();
+    queue.push((2, 0).into(), OscMessage::from("/b1"));
+    queue.push((1, 0).into(), OscMessage::from("/a"));
+    queue.push((2, 0).into(), OscMessage::from("/b2"));
+    assert_eq!(queue.len(), 3);
+    assert_eq!(
+        queue.time_until_next((0, 1 << 31).into()),
+        Some(Duration::from_millis(500))
+    );
+
+    let now = OscTime::from((2, 0));
+    let mut addrs = vec![];
+    while let Some(msg) = queue.pop_due(now) {
+        addrs.push(msg.addr);
+    }
+    assert_eq!(addrs, vec!["/a", "/b1", "/b2"]);
+    assert!(queue.is_empty());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_scheduler_with_clock() {
+    // A clock that is an hour ahead of the system time
+    let clock = || OscTime::try_from(SystemTime::now() + Duration::from_secs(3600)).unwrap();
+    let bundle = |delay, addr| {
+        bundle_at(OscTime::try_from(SystemTime::now() + delay).unwrap(), addr)
+    };
+    let (tx, rx) = mpsc::channel();
+    let options = SchedulerOptions {
+        max_delay: Some(Duration::from_secs(10)),
+    };
+    let scheduler = Scheduler::with_clock(move |msg| tx.send(msg).unwrap(), options, clock);
+
+    scheduler
+        .submit(bundle(Duration::from_secs(1800), "/past"))
+        .unwrap();
+    let msg = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(msg, OscMessage::from("/past"));
+
+    assert!(matches!(
+        scheduler.submit(bundle(Duration::from_secs(3620), "/later")),
+        Err(OscError::TooFarInFuture(_))
+    ));
+}
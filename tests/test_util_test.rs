@@ -0,0 +1,191 @@
+#[cfg(feature = "test-util")]
+#[macro_use]
+extern crate rosc;
+
+#[cfg(feature = "test-util")]
+use rosc::test_util::{diff, diff_semantic, diff_with_epsilon};
+#[cfg(feature = "test-util")]
+use rosc::{OscArray, OscBundle, OscMessage, OscPacket, OscType, SemanticEqOptions};
+
+#[cfg(feature = "test-util")]
+fn message(addr: &str, args: Vec<OscType>) -> OscPacket {
+    OscPacket::Message(OscMessage {
+        addr: addr.to_string(),
+        args,
+    })
+}
+
+#[cfg(feature = "test-util")]
+fn bundle(timetag: (u32, u32), content: Vec<OscPacket>) -> OscPacket {
+    OscPacket::Bundle(OscBundle {
+        timetag: timetag.into(),
+        content,
+    })
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_diff() {
+    let args = || {
+        vec![
+            OscType::Int(1),
+            vec![1u8, 2, 3].into(),
+            OscType::Array(OscArray {
+                content: vec![OscType::Bool(true), "a".into()],
+            }),
+        ]
+    };
+    let packet = bundle((1, 0), vec![message("/a", args())]);
+    assert_eq!(diff(&packet, &packet.clone()), None);
+
+    let cases = vec![
+        (
+            message("/a", args()),
+            "left is a bundle, right is a message",
+        ),
+        (
+            bundle((2, 0), vec![message("/a", args())]),
+            "time tag differs: (1, 0) != (2, 0)",
+        ),
+        (bundle((1, 0), vec![]), "bundle element count differs: 1 != 0"),
+        (
+            bundle((1, 0), vec![message("/b", args())]),
+            r#"element 0: address differs: "/a" != "/b""#,
+        ),
+        (
+            bundle((1, 0), vec![message("/a", vec![])]),
+            "element 0: argument count differs: 3 != 0",
+        ),
+        (
+            bundle((1, 0), vec![message("/a", {
+                let mut args = args();
+                args[0] = OscType::Float(1.0);
+                args
+            })]),
+            "element 0: argument 0: type differs: 'i' != 'f' (Int(1) != Float(1.0))",
+        ),
+        (
+            bundle((1, 0), vec![message("/a", {
+                let mut args = args();
+                args[1] = vec![1u8, 5, 3].into();
+                args
+            })]),
+            "element 0: argument 1: blob differs at byte 1: 0x02 != 0x05",
+        ),
+        (
+            bundle((1, 0), vec![message("/a", {
+                let mut args = args();
+                args[1] = vec![1u8, 2].into();
+                args
+            })]),
+            "element 0: argument 1: blob length differs: 3 != 2",
+        ),
+        (
+            bundle((1, 0), vec![message("/a", {
+                let mut args = args();
+                args[2] = OscType::Array(OscArray {
+                    content: vec![OscType::Bool(false), "a".into()],
+                });
+                args
+            })]),
+            "element 0: argument 2: array argument 0: value differs: Bool(true) != Bool(false)",
+        ),
+    ];
+    for (other, expected) in cases {
+        assert_eq!(diff(&packet, &other).as_deref(), Some(expected));
+    }
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_diff_with_epsilon() {
+    let left = message("/a", vec![OscType::Float(0.5), OscType::Double(1.0)]);
+    let right = message("/a", vec![OscType::Float(0.5000001), OscType::Double(1.0 + 1e-9)]);
+    assert_eq!(
+        diff(&left, &right).as_deref(),
+        Some("argument 0: value differs: Float(0.5) != Float(0.5000001)")
+    );
+    assert_eq!(diff_with_epsilon(&left, &right, 1e-6), None);
+    assert!(diff_with_epsilon(&left, &right, 1e-12).is_some());
+
+    assert_osc_eq!(left, right, epsilon = 1e-6);
+    assert_osc_eq!(&left, &left.clone());
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_diff_semantic() {
+    let left = bundle(
+        (1, 0),
+        vec![message(
+            "/Gain",
+            vec![
+                OscType::Float(0.5),
+                OscType::Array(OscArray {
+                    content: vec![OscType::Int(1)],
+                }),
+            ],
+        )],
+    );
+    let right = bundle(
+        (1, 0),
+        vec![message(
+            "/gain",
+            vec![
+                OscType::Double(0.5),
+                OscType::Array(OscArray {
+                    content: vec![OscType::Long(1)],
+                }),
+                OscType::Nil,
+            ],
+        )],
+    );
+    let options = SemanticEqOptions {
+        numeric_widening: true,
+        case_insensitive_address: true,
+        ignore_trailing_nil: true,
+        ..Default::default()
+    };
+    assert_eq!(diff_semantic(&left, &right, &options), None);
+    assert_osc_eq!(left, right, semantic = options);
+
+    let cases = vec![
+        (
+            SemanticEqOptions {
+                case_insensitive_address: false,
+                ..options
+            },
+            r#"element 0: address differs: "/Gain" != "/gain""#,
+        ),
+        (
+            SemanticEqOptions {
+                ignore_trailing_nil: false,
+                ..options
+            },
+            "element 0: argument count differs: 2 != 3",
+        ),
+        (
+            SemanticEqOptions {
+                numeric_widening: false,
+                ..options
+            },
+            "element 0: argument 0: type differs: 'f' != 'd' (Float(0.5) != Double(0.5))",
+        ),
+    ];
+    for (options, expected) in cases {
+        assert_eq!(diff_semantic(&left, &right, &options).as_deref(), Some(expected));
+    }
+
+    let right = message("/a", vec![OscType::Long(2)]);
+    assert_eq!(
+        diff_semantic(&message("/a", vec![OscType::Int(1)]), &right, &options).as_deref(),
+        Some("argument 0: value differs: Int(1) != Long(2)")
+    );
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+#[should_panic(expected = "assertion `left == right` failed: address differs: \"/a\" != \"/b\"")]
+fn test_assert_osc_eq_reports_difference() {
+    assert_osc_eq!(message("/a", vec![]), message("/b", vec![]));
+}
@@ -0,0 +1,241 @@
+extern crate rosc;
+
+#[cfg(feature = "tuio")]
+use rosc::tuio::{
+    ToOscMessage, Tuio2DCursor, Tuio2DObject, TuioAssembler, TuioFrame, TuioMessage, TuioProfile,
+};
+#[cfg(feature = "tuio")]
+use rosc::{OscError, OscMessage, OscType};
+#[cfg(feature = "tuio")]
+use std::convert::TryFrom;
+
+/// Returns a message to `addr` with the arguments `command` and `args`.
+#[cfg(feature = "tuio")]
+fn tuio(addr: &str, command: &str, args: Vec<OscType>) -> OscMessage {
+    let mut msg = OscMessage::from(addr);
+    msg.push_str(command);
+    msg.extend(args);
+    msg
+}
+
+#[cfg(feature = "tuio")]
+fn bad_arg(err: OscError) -> String {
+    match err {
+        OscError::BadArg(msg) => msg,
+        err => panic!("Expected BadArg, found {:?}", err),
+    }
+}
+
+#[cfg(feature = "tuio")]
+#[test]
+fn test_message_round_trip() {
+    let messages = vec![
+        TuioMessage::Source {
+            profile: TuioProfile::Object2D,
+            source: "tracker@10.0.0.1".to_string(),
+        },
+        TuioMessage::Alive {
+            profile: TuioProfile::Cursor2D,
+            session_ids: vec![],
+        },
+        TuioMessage::Alive {
+            profile: TuioProfile::Object2D,
+            session_ids: vec![1, 2, 3],
+        },
+        TuioMessage::Cursor(Tuio2DCursor {
+            session_id: 1,
+            x: 0.1,
+            y: 0.2,
+            vx: 0.3,
+            vy: 0.4,
+            accel: 0.5,
+        }),
+        TuioMessage::Object(Tuio2DObject {
+            session_id: 2,
+            class_id: 3,
+            x: 0.1,
+            y: 0.2,
+            angle: 0.3,
+            vx: 0.4,
+            vy: 0.5,
+            vangle: 0.6,
+            accel: 0.7,
+            rotation_accel: 0.8,
+        }),
+        TuioMessage::Fseq {
+            profile: TuioProfile::Cursor2D,
+            frame_id: -1,
+        },
+    ];
+    for tuio in messages {
+        assert_eq!(TuioMessage::try_from(&tuio.to_osc_message()).unwrap(), tuio);
+    }
+}
+
+#[cfg(feature = "tuio")]
+#[test]
+fn test_malformed_messages() {
+    let set = |args| tuio("/tuio/2Dcur", "set", args);
+    assert_eq!(
+        bad_arg(TuioMessage::try_from(&set(vec![OscType::Int(1), OscType::Float(0.5)]))
+            .unwrap_err()),
+        "Malformed TUIO message /tuio/2Dcur set: missing float argument 3"
+    );
+    assert_eq!(
+        bad_arg(Tuio2DCursor::try_from(&set(vec![
+            OscType::Int(1),
+            OscType::Float(0.5),
+            OscType::Int(2),
+        ]))
+        .unwrap_err()),
+        "Malformed TUIO message /tuio/2Dcur set: expected float argument 3, found Int(2)"
+    );
+    let mut args = vec![OscType::Int(1)];
+    args.extend(vec![OscType::Float(0.0); 6]);
+    assert_eq!(
+        bad_arg(TuioMessage::try_from(&set(args)).unwrap_err()),
+        "Malformed TUIO message /tuio/2Dcur set: expected 7 arguments, found 8"
+    );
+    assert_eq!(
+        bad_arg(
+            TuioMessage::try_from(&tuio("/tuio/2Dobj", "alive", vec![OscType::Float(1.0)]))
+                .unwrap_err()
+        ),
+        "Malformed TUIO message /tuio/2Dobj alive: expected int argument 1, found Float(1.0)"
+    );
+    assert_eq!(
+        bad_arg(TuioMessage::try_from(&tuio("/tuio/2Dobj", "get", vec![])).unwrap_err()),
+        "Unknown TUIO command /tuio/2Dobj get"
+    );
+    assert_eq!(
+        bad_arg(TuioMessage::try_from(&OscMessage::from("/tuio/2Dcur")).unwrap_err()),
+        "Malformed TUIO message /tuio/2Dcur: missing command"
+    );
+    assert_eq!(
+        bad_arg(
+            Tuio2DObject::try_from(&tuio("/tuio/2Dcur", "fseq", vec![OscType::Int(1)]))
+                .unwrap_err()
+        ),
+        r#"Expected a /tuio/2Dobj set message, found /tuio/2Dcur Some(String("fseq"))"#
+    );
+    assert_eq!(
+        TuioMessage::try_from(&tuio("/tuio/2Dblb", "fseq", vec![OscType::Int(1)])).unwrap_err(),
+        OscError::BadAddress("Unsupported TUIO profile /tuio/2Dblb".to_string())
+    );
+    assert_eq!(
+        TuioProfile::from_addr("/mixer/1").unwrap_err(),
+        OscError::BadAddress("/mixer/1 is not a TUIO address".to_string())
+    );
+}
+
+#[cfg(feature = "tuio")]
+#[test]
+fn test_assembler_rejects_invalid_frames() {
+    let mut assembler = TuioAssembler::new();
+    let fseq = |frame_id| tuio("/tuio/2Dcur", "fseq", vec![OscType::Int(frame_id)]);
+    assert_eq!(
+        bad_arg(assembler.push(&fseq(1)).unwrap_err()),
+        "TUIO frame 1 of /tuio/2Dcur has no alive message"
+    );
+
+    let cursor = Tuio2DCursor {
+        session_id: 4,
+        ..Default::default()
+    };
+    let alive = tuio("/tuio/2Dcur", "alive", vec![OscType::Int(5)]);
+    assert_eq!(assembler.push(&alive).unwrap(), None);
+    assert_eq!(assembler.push(&cursor.to_osc_message()).unwrap(), None);
+    assert_eq!(
+        bad_arg(assembler.push(&fseq(2)).unwrap_err()),
+        "TUIO frame 2 of /tuio/2Dcur sets session 4, which is not alive"
+    );
+
+    // The invalid frame is discarded and the next one assembled from scratch
+    assert_eq!(assembler.push(&alive).unwrap(), None);
+    let frame = assembler.push(&fseq(3)).unwrap().unwrap();
+    assert_eq!(frame.cursors, vec![]);
+    assert_eq!(frame.added, vec![5]);
+}
+
+#[cfg(all(feature = "tuio", feature = "std"))]
+#[test]
+fn test_reactivision_recording() {
+    use rosc::replay::Player;
+
+    // Three cursor and object frames of reacTIVision, in which a finger is put down, moves and is
+    // lifted while a fiducial lies still
+    let recording = include_bytes!("data/reactivision_tuio.roscrec");
+    let mut assembler = TuioAssembler::new();
+    let mut frames: Vec<TuioFrame> = vec![];
+    for replayed in Player::new(&recording[..]).unwrap() {
+        let (_, packet) = replayed.unwrap();
+        for (_, msg) in packet.into_messages() {
+            frames.extend(assembler.push(&msg).unwrap());
+        }
+    }
+
+    let source = Some("REACTIVISION@127.0.0.1".to_string());
+    let object = Tuio2DObject {
+        session_id: 3,
+        class_id: 12,
+        x: 0.25,
+        y: 0.5,
+        angle: 1.5,
+        ..Default::default()
+    };
+    let frame = |profile, frame_id, alive: Vec<i32>| TuioFrame {
+        profile,
+        source: source.clone(),
+        frame_id,
+        alive,
+        added: vec![],
+        removed: vec![],
+        cursors: vec![],
+        objects: vec![],
+    };
+    let expected = vec![
+        TuioFrame {
+            added: vec![3],
+            objects: vec![object],
+            ..frame(TuioProfile::Object2D, 101, vec![3])
+        },
+        TuioFrame {
+            added: vec![7],
+            cursors: vec![Tuio2DCursor {
+                session_id: 7,
+                x: 0.75,
+                y: 0.125,
+                ..Default::default()
+            }],
+            ..frame(TuioProfile::Cursor2D, 101, vec![7])
+        },
+        frame(TuioProfile::Object2D, 102, vec![3]),
+        TuioFrame {
+            cursors: vec![Tuio2DCursor {
+                session_id: 7,
+                x: 0.5,
+                y: 0.25,
+                vx: -2.5,
+                vy: 1.25,
+                accel: 0.5,
+            }],
+            ..frame(TuioProfile::Cursor2D, 102, vec![7])
+        },
+        frame(TuioProfile::Object2D, 103, vec![3]),
+        TuioFrame {
+            removed: vec![7],
+            ..frame(TuioProfile::Cursor2D, 103, vec![])
+        },
+    ];
+    assert_eq!(frames, expected);
+
+    // Sending the frames again results in the same frames
+    let mut reassembler = TuioAssembler::new();
+    for frame in &frames {
+        let mut reassembled = None;
+        for (_, msg) in frame.to_packet().into_messages() {
+            reassembled = reassembler.push(&msg).unwrap();
+        }
+        assert_eq!(reassembled.as_ref(), Some(frame));
+    }
+}
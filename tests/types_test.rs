@@ -1,11 +1,17 @@
 extern crate rosc;
 
-use rosc::{OscArray, OscType};
+use rosc::{decoder, encoder};
+use rosc::{
+    Clock, DedupOptions, Keep, ManualClock, OscArray, OscBundle, OscColor, OscError, OscMessage,
+    OscMidiMessage, OscPacket, OscTime, OscType, SemanticEqOptions, TruncatePolicy,
+};
+#[cfg(feature = "std")]
+use rosc::SystemClock;
 
 #[test]
 fn test_osc_array_from_iter() {
     use std::iter::FromIterator;
-    let iter = (0..3).map(|i| OscType::Int(i));
+    let iter = (0..3).map(OscType::Int);
     let osc_arr = OscArray::from_iter(iter);
     assert_eq!(
         osc_arr,
@@ -14,3 +20,1016 @@ fn test_osc_array_from_iter() {
         }
     );
 }
+
+#[test]
+fn test_osc_array_pairs() {
+    let array = OscArray::from_pairs(vec![
+        ("gain", OscType::Float(0.5)),
+        ("name", OscType::String("kick".to_string())),
+        ("mute", OscType::Bool(false)),
+    ]);
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/plugin/params".to_string(),
+        args: vec![OscType::Array(array.clone())],
+    });
+    let decoded = match decoder::decode_udp(&encoder::encode(&packet).unwrap()).unwrap().1 {
+        OscPacket::Message(mut msg) => msg.args.remove(0).array().unwrap(),
+        _ => panic!("Expected a message"),
+    };
+    assert_eq!(decoded, array);
+    assert_eq!(
+        decoded.as_pairs().unwrap(),
+        vec![
+            ("gain", &OscType::Float(0.5)),
+            ("name", &OscType::String("kick".to_string())),
+            ("mute", &OscType::Bool(false)),
+        ]
+    );
+    assert_eq!(decoded.lookup("name").unwrap(), Some(&OscType::String("kick".to_string())));
+    assert_eq!(decoded.lookup("pan").unwrap(), None);
+
+    let empty = OscArray::from_pairs(Vec::<(String, OscType)>::new());
+    assert_eq!(empty.as_pairs().unwrap(), vec![]);
+}
+
+#[test]
+fn test_osc_array_bad_pairs() {
+    use std::iter::FromIterator;
+    let odd = OscArray::from_iter(vec![
+        OscType::String("gain".to_string()),
+        OscType::Float(0.5),
+        OscType::String("mute".to_string()),
+    ]);
+    let err = odd.as_pairs().unwrap_err();
+    assert!(matches!(err, OscError::BadArg(ref msg) if msg.contains("index 2")), "{}", err);
+    assert!(odd.lookup("gain").is_err());
+
+    let bad_key = OscArray::from_iter(vec![
+        OscType::String("gain".to_string()),
+        OscType::Float(0.5),
+        OscType::Int(1),
+        OscType::Int(2),
+    ]);
+    let err = bad_key.as_pairs().unwrap_err();
+    assert!(matches!(err, OscError::BadArg(ref msg) if msg.contains("index 2")), "{}", err);
+}
+
+#[test]
+fn test_immediate_time_tag() {
+    assert_eq!(OscTime::IMMEDIATE, OscTime::from((0, 1)));
+    assert!(OscTime::IMMEDIATE.is_immediate());
+    assert!(!OscTime::from((0, 0)).is_immediate());
+    assert!(!OscTime::from((1, 0)).is_immediate());
+
+    let bundle = OscBundle {
+        timetag: OscTime::IMMEDIATE,
+        content: vec![],
+    };
+    assert!(bundle.is_immediate());
+}
+
+#[test]
+fn test_raw_time_tag() {
+    let vectors = [
+        (0, (0, 0), [0, 0, 0, 0, 0, 0, 0, 0]),
+        (1, (0, 1), [0, 0, 0, 0, 0, 0, 0, 1]),
+        (1 << 32, (1, 0), [0, 0, 0, 1, 0, 0, 0, 0]),
+        (
+            0x0123_4567_89ab_cdef,
+            (0x0123_4567, 0x89ab_cdef),
+            [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef],
+        ),
+        (u64::MAX, (u32::MAX, u32::MAX), [0xff; 8]),
+    ];
+    for (raw, (secs, frac), wire) in vectors {
+        let time = OscTime::from_raw(raw);
+        assert_eq!(time, OscTime::from_parts(secs, frac));
+        assert_eq!(time.parts(), (secs, frac));
+        assert_eq!((time.seconds, time.fractional), (secs, frac));
+        assert_eq!(time.as_raw(), raw);
+        assert_eq!(OscTime::from_parts(secs, frac).as_raw(), raw);
+
+        // The raw value is the big-endian wire value, in bundles and arguments alike
+        let bundle = OscPacket::Bundle(OscBundle {
+            timetag: time,
+            content: vec![],
+        });
+        let buf = encoder::encode(&bundle).unwrap();
+        assert_eq!(buf[8..16], wire);
+        assert_eq!(decoder::decode_udp(&buf).unwrap().1, bundle);
+        let message = OscPacket::Message(OscMessage {
+            addr: "/t".to_string(),
+            args: vec![OscType::Time(time)],
+        });
+        let buf = encoder::encode(&message).unwrap();
+        assert_eq!(buf[8..], wire);
+        assert_eq!(decoder::decode_udp(&buf).unwrap().1, message);
+    }
+    assert_eq!(OscTime::from_raw(1), OscTime::IMMEDIATE);
+    assert_eq!(OscTime::IMMEDIATE.as_raw(), 1);
+}
+
+#[test]
+fn test_into_messages() {
+    use rosc::OscPacket;
+
+    let msg = OscMessage::from("/a");
+    assert_eq!(
+        OscPacket::Message(msg.clone()).into_messages().collect::<Vec<_>>(),
+        vec![(OscTime::IMMEDIATE, msg.clone())]
+    );
+
+    let nested = |timetag: OscTime, content| OscPacket::Bundle(OscBundle { timetag, content });
+    let packet = nested(
+        (1, 0).into(),
+        vec![
+            nested(
+                (2, 0).into(),
+                vec![
+                    OscPacket::Message(msg.clone()),
+                    nested(OscTime::IMMEDIATE, vec![OscPacket::Message(msg.clone())]),
+                ],
+            ),
+            nested((3, 0).into(), vec![]),
+            OscPacket::Message(msg.clone()),
+        ],
+    );
+    let timetags: Vec<OscTime> = packet.into_messages().map(|(timetag, _)| timetag).collect();
+    assert_eq!(timetags, vec![(2, 0).into(), (2, 0).into(), (1, 0).into()]);
+}
+
+#[test]
+fn test_clock() {
+    let clock = || OscTime::from((42, 0));
+    assert_eq!(clock.now(), OscTime::from((42, 0)));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_system_clock() {
+    let before = OscTime::now();
+    let now = SystemClock.now();
+    assert!(before <= now && now <= OscTime::now());
+}
+
+#[test]
+fn test_message_templating() {
+    let prototype = OscMessage {
+        addr: "/synth/1/cutoff".to_string(),
+        args: vec![OscType::Int(1), OscType::Float(0.0)],
+    };
+
+    let msg = prototype.with_arg_at(1, OscType::Float(0.5));
+    assert_eq!(msg.addr, prototype.addr);
+    assert_eq!(msg.args, vec![OscType::Int(1), OscType::Float(0.5)]);
+    // The type of an argument may change
+    let msg = prototype.with_arg_at(0, "one".into());
+    assert_eq!(msg.args, vec!["one".into(), OscType::Float(0.0)]);
+
+    let msg = prototype.retarget("/synth/2/cutoff");
+    assert_eq!(msg.addr, "/synth/2/cutoff");
+    assert_eq!(msg.args, prototype.args);
+
+    let mut msg = prototype.clone();
+    msg.set_arg(1, OscType::Double(0.25)).unwrap();
+    assert_eq!(msg.args, vec![OscType::Int(1), OscType::Double(0.25)]);
+    assert_eq!(
+        msg.set_arg(2, OscType::Nil),
+        Err(OscError::BadArg(
+            "Argument index 2 is out of range for a message with 2 arguments".to_string()
+        ))
+    );
+    assert_eq!(msg.args.len(), 2);
+}
+
+#[test]
+#[should_panic(expected = "argument index 2 is out of range for a message with 2 arguments")]
+fn test_with_arg_at_out_of_range() {
+    OscMessage {
+        addr: "/a".to_string(),
+        args: vec![OscType::Nil, OscType::Impulse],
+    }
+    .with_arg_at(2, OscType::Nil);
+}
+
+#[test]
+fn test_push_args() {
+    let mut pushed = OscMessage::from("/all");
+    pushed
+        .push_int(1)
+        .push_float(2.0)
+        .push_double(3.0)
+        .push_str("four")
+        .push_blob(vec![5u8])
+        .push_bool(true)
+        .push_nil();
+    pushed.extend(vec![OscType::Long(6), OscType::Char('7')]);
+    pushed.extend(vec![8i32, 9]);
+
+    let built = OscMessage {
+        addr: "/all".to_string(),
+        args: vec![
+            OscType::Int(1),
+            OscType::Float(2.0),
+            OscType::Double(3.0),
+            OscType::String("four".to_string()),
+            OscType::Blob(vec![5]),
+            OscType::Bool(true),
+            OscType::Nil,
+            OscType::Long(6),
+            OscType::Char('7'),
+            OscType::Int(8),
+            OscType::Int(9),
+        ],
+    };
+    assert_eq!(pushed, built);
+}
+
+#[test]
+fn test_manual_clock() {
+    use std::time::Duration;
+
+    let clock = ManualClock::new((100, 0).into());
+    let handle = clock.clone();
+    assert_eq!(clock.now(), OscTime::from((100, 0)));
+    handle.advance(Duration::from_millis(250));
+    assert_eq!(clock.now(), OscTime::from((100, 1 << 30)));
+    handle.advance(Duration::from_secs(10));
+    assert_eq!(clock.now(), OscTime::from((110, 1 << 30)));
+    handle.set((5, 0).into());
+    assert_eq!(clock.now(), OscTime::from((5, 0)));
+    handle.advance(Duration::MAX);
+    assert_eq!(clock.now(), OscTime::from((u32::MAX, u32::MAX)));
+    assert_eq!(ManualClock::default().now(), OscTime::from((0, 0)));
+}
+
+#[test]
+fn test_delta_from() {
+    use rosc::SignedDuration;
+    use std::time::Duration;
+
+    // Straddling the boundary of a second in either direction
+    let before = OscTime::from((9, 3 << 30));
+    let after = OscTime::from((10, 1 << 31));
+    assert_eq!(after.delta_from(before), SignedDuration::new(false, Duration::from_millis(750)));
+    assert_eq!(before.delta_from(after), SignedDuration::new(true, Duration::from_millis(750)));
+    assert_eq!(before.delta_from(after).as_nanos(), -750_000_000);
+    assert_eq!(before.delta_from(after).as_secs_f64(), -0.75);
+    assert_eq!(
+        OscTime::from((10, 0)).delta_from((9, u32::MAX).into()),
+        SignedDuration::new(false, Duration::from_nanos(0))
+    );
+    assert_eq!(OscTime::from((10, 0)).delta_from((10, 0).into()), SignedDuration::ZERO);
+    assert_eq!(
+        OscTime::from((u32::MAX, u32::MAX)).delta_from((0, 0).into()).abs(),
+        Duration::from_nanos(4_294_967_295_999_999_999)
+    );
+
+    assert_eq!(after.saturating_duration_since(before), Duration::from_millis(750));
+    assert_eq!(before.saturating_duration_since(after), Duration::ZERO);
+
+    // Zero is never negative, and ordering follows the sign
+    assert_eq!(SignedDuration::new(true, Duration::ZERO), SignedDuration::ZERO);
+    assert!(!SignedDuration::new(true, Duration::ZERO).is_negative());
+    assert!(before.delta_from(after) < SignedDuration::ZERO);
+    assert!(SignedDuration::new(true, Duration::from_secs(1)) < before.delta_from(after));
+    assert!(after.delta_from(before) > SignedDuration::ZERO);
+}
+
+#[test]
+fn test_bundle_lateness() {
+    use rosc::SignedDuration;
+    use std::time::Duration;
+
+    let bundle = |timetag: OscTime| OscBundle {
+        timetag,
+        content: vec![],
+    };
+    // Positive when arriving after the time tag
+    assert_eq!(
+        bundle((100, 3 << 30).into()).lateness((101, 1 << 30).into()),
+        Some(SignedDuration::new(false, Duration::from_millis(500)))
+    );
+    assert_eq!(
+        bundle((101, 1 << 30).into()).lateness((100, 3 << 30).into()),
+        Some(SignedDuration::new(true, Duration::from_millis(500)))
+    );
+    assert_eq!(bundle(OscTime::IMMEDIATE).lateness((100, 0).into()), None);
+    // `(0, 0)` is a point in time like any other
+    assert_eq!(
+        bundle((0, 0).into()).lateness((1, 0).into()),
+        Some(SignedDuration::new(false, Duration::from_secs(1)))
+    );
+}
+
+#[test]
+fn test_numerically_eq() {
+    let equal = vec![
+        (OscType::Int(1), OscType::Long(1), 0.0),
+        (OscType::Int(1), OscType::Float(1.0), 0.0),
+        (OscType::Long(2), OscType::Double(2.0), 0.0),
+        (OscType::Float(0.1), OscType::Double(0.1), 1e-7),
+        (OscType::Int(1), OscType::Double(1.25), 0.25),
+        (OscType::Long(i64::MAX), OscType::Long(i64::MAX), 0.0),
+        (OscType::Long(i64::MIN), OscType::Int(i32::MIN), u64::MAX as f64),
+        (OscType::Double(f64::INFINITY), OscType::Float(f32::INFINITY), 0.0),
+        (OscType::String("a".into()), OscType::String("a".into()), 1.0),
+        (
+            OscType::Array(OscArray {
+                content: vec![OscType::Int(1), OscType::Nil],
+            }),
+            OscType::Array(OscArray {
+                content: vec![OscType::Double(1.0), OscType::Nil],
+            }),
+            0.0,
+        ),
+    ];
+    for (left, right, epsilon) in equal {
+        assert!(left.numerically_eq(&right, epsilon), "{:?} != {:?}", left, right);
+        assert!(right.numerically_eq(&left, epsilon), "{:?} != {:?}", right, left);
+    }
+
+    let different = vec![
+        (OscType::Float(0.1), OscType::Double(0.1), 0.0),
+        // Compared exactly, even though both are the same double
+        (OscType::Long(i64::MAX), OscType::Long(i64::MAX - 1), 0.0),
+        (OscType::Int(1), OscType::Int(2), 0.5),
+        (OscType::Double(f64::NAN), OscType::Double(f64::NAN), 1.0),
+        (OscType::Int(1), OscType::Bool(true), 1.0),
+        (OscType::Int(0), OscType::Nil, 1.0),
+        (OscType::Time((1, 0).into()), OscType::Time((1, 1).into()), 1.0),
+        (
+            OscType::Array(OscArray {
+                content: vec![OscType::Int(1)],
+            }),
+            OscType::Array(OscArray {
+                content: vec![OscType::Int(1), OscType::Int(2)],
+            }),
+            0.0,
+        ),
+    ];
+    for (left, right, epsilon) in different {
+        assert!(!left.numerically_eq(&right, epsilon), "{:?} == {:?}", left, right);
+    }
+}
+
+#[test]
+fn test_semantically_eq() {
+    let msg = |addr: &str, args: Vec<OscType>| OscMessage {
+        addr: addr.to_string(),
+        args,
+    };
+    let sent = msg("/Mixer/Gain", vec![OscType::Int(1), OscType::Float(0.5)]);
+    let strict = SemanticEqOptions::default();
+    assert!(sent.semantically_eq(&sent.clone(), &strict));
+
+    let cases = vec![
+        (
+            msg("/Mixer/Gain", vec![OscType::Long(1), OscType::Double(0.5)]),
+            SemanticEqOptions {
+                numeric_widening: true,
+                ..Default::default()
+            },
+        ),
+        (
+            msg("/Mixer/Gain", vec![OscType::Int(1), OscType::Float(0.5000001)]),
+            SemanticEqOptions {
+                epsilon: 1e-6,
+                ..Default::default()
+            },
+        ),
+        (
+            msg("/mixer/GAIN", vec![OscType::Int(1), OscType::Float(0.5)]),
+            SemanticEqOptions {
+                case_insensitive_address: true,
+                ..Default::default()
+            },
+        ),
+        (
+            msg("/Mixer/Gain", vec![OscType::Int(1), OscType::Float(0.5), OscType::Nil]),
+            SemanticEqOptions {
+                ignore_trailing_nil: true,
+                ..Default::default()
+            },
+        ),
+    ];
+    for (received, options) in cases {
+        assert!(sent.semantically_eq(&received, &options), "{:?}", received);
+        assert!(received.semantically_eq(&sent, &options), "{:?}", received);
+        assert!(!sent.semantically_eq(&received, &strict), "{:?}", received);
+        assert_ne!(sent, received);
+    }
+
+    let all = SemanticEqOptions {
+        epsilon: 1e-6,
+        numeric_widening: true,
+        case_insensitive_address: true,
+        ignore_trailing_nil: true,
+    };
+    let different = vec![
+        msg("/Mixer/Level", vec![OscType::Int(1), OscType::Float(0.5)]),
+        msg("/Mixer/Gain", vec![OscType::Int(1), OscType::Float(0.6)]),
+        msg("/Mixer/Gain", vec![OscType::Int(1), OscType::Float(0.5), OscType::Int(0)]),
+        // Only nils at the end are ignored
+        msg("/Mixer/Gain", vec![OscType::Nil, OscType::Int(1), OscType::Float(0.5)]),
+        msg("/Mixer/Gain", vec![OscType::Bool(true), OscType::Float(0.5)]),
+    ];
+    for received in different {
+        assert!(!sent.semantically_eq(&received, &all), "{:?}", received);
+    }
+    assert!(msg("/a", vec![OscType::Nil]).semantically_eq(&msg("/a", vec![]), &all));
+}
+
+fn packet(addr: &str, args: Vec<OscType>) -> OscPacket {
+    OscPacket::Message(OscMessage {
+        addr: addr.to_string(),
+        args,
+    })
+}
+
+fn bundle(content: Vec<OscPacket>) -> OscBundle {
+    OscBundle {
+        timetag: OscTime::IMMEDIATE,
+        content,
+    }
+}
+
+#[test]
+fn test_bundle_dedup_by_address() {
+    let nested = || OscPacket::Bundle(bundle(vec![packet("/a", vec![]), packet("/a", vec![])]));
+    let content = vec![
+        packet("/a", vec![OscType::Int(1)]),
+        packet("/b", vec![OscType::Int(1)]),
+        nested(),
+        packet("/a", vec![OscType::Int(2)]),
+        packet("/a", vec![OscType::Float(3.0)]),
+    ];
+
+    // Messages differing only in their arguments are duplicates, nested bundles are left alone
+    let mut last = bundle(content.clone());
+    assert_eq!(last.dedup_by_address(Keep::Last), 2);
+    let expected = vec![
+        packet("/b", vec![OscType::Int(1)]),
+        nested(),
+        packet("/a", vec![OscType::Float(3.0)]),
+    ];
+    assert_eq!(last.content, expected);
+    assert_eq!(last.dedup_by_address(Keep::Last), 0);
+
+    let mut first = bundle(content.clone());
+    assert_eq!(first.dedup_by_address(Keep::First), 2);
+    let expected = vec![
+        packet("/a", vec![OscType::Int(1)]),
+        packet("/b", vec![OscType::Int(1)]),
+        nested(),
+    ];
+    assert_eq!(first.content, expected);
+
+    let mut typed = bundle(content.clone());
+    let options = DedupOptions {
+        keep: Keep::First,
+        match_type_tags: true,
+        ..Default::default()
+    };
+    assert_eq!(typed.dedup_with_options(&options), 1);
+    let expected = vec![
+        packet("/a", vec![OscType::Int(1)]),
+        packet("/b", vec![OscType::Int(1)]),
+        nested(),
+        packet("/a", vec![OscType::Float(3.0)]),
+    ];
+    assert_eq!(typed.content, expected);
+
+    let mut bools = bundle(vec![
+        packet("/t", vec![OscType::Bool(true)]),
+        packet("/t", vec![OscType::Bool(false)]),
+        packet("/t", vec![OscType::Array(vec![1].into_iter().collect())]),
+        packet("/t", vec![OscType::Array(vec![2].into_iter().collect())]),
+    ]);
+    assert_eq!(bools.dedup_with_options(&options), 1);
+    assert_eq!(bools.content.len(), 3);
+}
+
+#[test]
+fn test_bundle_dedup_recursive() {
+    let mut outer = bundle(vec![
+        packet("/a", vec![OscType::Int(1)]),
+        OscPacket::Bundle(bundle(vec![
+            packet("/a", vec![OscType::Int(2)]),
+            packet("/a", vec![OscType::Int(3)]),
+            OscPacket::Bundle(bundle(vec![packet("/b", vec![]), packet("/b", vec![])])),
+        ])),
+        packet("/a", vec![OscType::Int(4)]),
+    ]);
+    let options = DedupOptions {
+        recursive: true,
+        ..Default::default()
+    };
+    assert_eq!(outer.dedup_with_options(&options), 3);
+    // Nested bundles are deduplicated among themselves only
+    let expected = bundle(vec![
+        OscPacket::Bundle(bundle(vec![
+            packet("/a", vec![OscType::Int(3)]),
+            OscPacket::Bundle(bundle(vec![packet("/b", vec![])])),
+        ])),
+        packet("/a", vec![OscType::Int(4)]),
+    ]);
+    assert_eq!(outer, expected);
+}
+
+fn encoded_size(msg: &OscMessage) -> usize {
+    encoder::encode(&OscPacket::Message(msg.clone())).unwrap().len()
+}
+
+#[test]
+fn test_message_encoded_len() {
+    let mut msg = OscMessage::from("/len");
+    assert_eq!(msg.encoded_len(), encoded_size(&msg));
+    let args = vec![
+        OscType::Int(1),
+        OscType::Long(2),
+        OscType::Float(3.0),
+        OscType::Double(4.0),
+        OscType::Char('x'),
+        OscType::Time((1, 2).into()),
+        OscType::Midi(OscMidiMessage {
+            port: 1,
+            status: 2,
+            data1: 3,
+            data2: 4,
+        }),
+        OscType::Color(OscColor {
+            red: 1,
+            green: 2,
+            blue: 3,
+            alpha: 4,
+        }),
+        OscType::Bool(true),
+        OscType::Nil,
+        OscType::Impulse,
+        OscType::Unsigned(5),
+        OscType::Array(OscArray {
+            content: vec![OscType::Int(1), OscType::String("ab".to_string())],
+        }),
+    ];
+    for arg in args {
+        msg.args.push(arg);
+        assert_eq!(msg.encoded_len(), encoded_size(&msg), "{:?}", msg);
+    }
+    for len in 0..9 {
+        msg.args.push(OscType::String("x".repeat(len)));
+        msg.args.push(OscType::Blob(vec![0; len]));
+        assert_eq!(msg.encoded_len(), encoded_size(&msg), "{:?}", msg);
+    }
+}
+
+#[test]
+fn test_message_truncate_to_fit() {
+    let mut msg = OscMessage::from("/status");
+    msg.push_int(1).push_str("abcdefghijklmnop").push_blob(vec![7; 32]);
+    let size = msg.encoded_len();
+    let original = msg.clone();
+
+    // Messages that fit are left alone
+    assert!(!msg.clone().truncate_to_fit(size, TruncatePolicy::default()).unwrap());
+
+    // The last argument is shortened first
+    assert!(msg.truncate_to_fit(size - 8, TruncatePolicy::default()).unwrap());
+    assert_eq!(msg.args[..2], original.args[..2]);
+    assert_eq!(msg.args[2], OscType::Blob(vec![7; 24]));
+    assert_eq!(msg.encoded_len(), size - 8);
+
+    // Strings keep a prefix and end with the marker
+    let mut msg = original.clone();
+    assert!(msg.truncate_to_fit(size - 40, TruncatePolicy::default()).unwrap());
+    assert_eq!(msg.args[1], OscType::String("abcdefgh...".to_string()));
+    assert_eq!(msg.args[2], OscType::Blob(vec![]));
+    assert_eq!(msg.encoded_len(), size - 40);
+
+    // Only the arguments chosen by the policy are shortened
+    let blobs_only = TruncatePolicy {
+        strings: false,
+        ..Default::default()
+    };
+    let mut msg = original.clone();
+    match msg.truncate_to_fit(size - 40, blobs_only) {
+        Err(OscError::PacketTooLarge { size: min, max }) => {
+            assert_eq!((min, max), (size - 32, size - 40))
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(msg, original);
+    let drop = TruncatePolicy {
+        drop_trailing_args: true,
+        ..blobs_only
+    };
+    assert!(msg.truncate_to_fit(size - 40, drop).unwrap());
+    assert_eq!(msg.args, original.args[..2]);
+
+    // Strings are cut at character boundaries
+    let mut msg = OscMessage::from("/utf8");
+    msg.push_str("aääääääääää");
+    assert!(msg.truncate_to_fit(24, TruncatePolicy::default()).unwrap());
+    assert_eq!(msg.args, vec![OscType::String("aäää...".to_string())]);
+
+    // The address and type tags can't be shortened
+    let mut msg = OscMessage::from("/a/long/address");
+    msg.push_str("x");
+    let everything = TruncatePolicy {
+        drop_trailing_args: true,
+        ..Default::default()
+    };
+    match msg.truncate_to_fit(16, everything) {
+        Err(OscError::PacketTooLarge { size: 20, max: 16 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_message_truncate_to_fit_always_fits() {
+    // A simple xorshift generator, so that failures can be reproduced
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as usize
+    };
+    let policies = [
+        TruncatePolicy::default(),
+        TruncatePolicy {
+            blobs: false,
+            ..Default::default()
+        },
+        TruncatePolicy {
+            strings: false,
+            drop_trailing_args: true,
+            ..Default::default()
+        },
+        TruncatePolicy {
+            drop_trailing_args: true,
+            ..Default::default()
+        },
+    ];
+
+    for _ in 0..2000 {
+        let mut msg = OscMessage::from("/p".repeat(1 + next(4)));
+        for _ in 0..next(6) {
+            let arg = match next(4) {
+                0 => OscType::Int(1),
+                1 => OscType::String("aé€".chars().cycle().take(next(40)).collect()),
+                2 => OscType::Blob(vec![1; next(40)]),
+                _ => OscType::Array(OscArray {
+                    content: vec![OscType::Blob(vec![2; next(8)])],
+                }),
+            };
+            msg.args.push(arg);
+        }
+        let max = next(msg.encoded_len() + 8);
+        let policy = policies[next(policies.len())];
+        let original = msg.clone();
+        match msg.truncate_to_fit(max, policy) {
+            Ok(truncated) => {
+                let size = encoded_size(&msg);
+                assert!(size <= max, "{:?} encodes into {} bytes, max {}", msg, size, max);
+                assert_eq!(size, msg.encoded_len());
+                assert_eq!(truncated, msg != original);
+                let packet = OscPacket::Message(msg);
+                let encoded = encoder::encode(&packet).unwrap();
+                assert_eq!(decoder::decode_udp(&encoded).unwrap().1, packet);
+            }
+            Err(OscError::PacketTooLarge { size, max: limit }) => {
+                assert_eq!(msg, original);
+                assert!(size > max && limit == max);
+            }
+            Err(err) => panic!("unexpected error {:?}", err),
+        }
+    }
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_impulse_legacy_name() {
+    use rosc::decoder::OscVisitor;
+    use rosc::OscTypeRef;
+
+    assert_eq!(OscType::Inf, OscType::Impulse);
+    assert_eq!(OscTypeRef::Inf, OscTypeRef::Impulse);
+    let legacy = OscMessage {
+        addr: "/bang".to_string(),
+        args: vec![OscType::Inf],
+    };
+    let mut msg = OscMessage::from("/bang");
+    msg.args.push(OscType::Impulse);
+    let encoded = encoder::encode(&OscPacket::Message(msg.clone())).unwrap();
+    assert_eq!(encoder::encode(&OscPacket::Message(legacy)).unwrap(), encoded);
+    assert_eq!(&encoded[8..], b",I\0\0");
+    assert!(msg.is_impulse_only());
+
+    match decoder::decode_udp(&encoded).unwrap().1 {
+        OscPacket::Message(decoded) => match decoded.args[..] {
+            [OscType::Inf] => assert_eq!(decoded, msg),
+            ref args => panic!("unexpected arguments {:?}", args),
+        },
+        packet => panic!("unexpected packet {:?}", packet),
+    }
+
+    // Visitors implementing the callback by its former name still receive impulses
+    #[derive(Default)]
+    struct Legacy(usize);
+    impl<'a> OscVisitor<'a> for Legacy {
+        fn arg_inf(&mut self) {
+            self.0 += 1;
+        }
+    }
+    let mut visitor = Legacy::default();
+    decoder::decode_visit(&encoded, &mut visitor).unwrap();
+    assert_eq!(visitor.0, 1);
+}
+
+#[test]
+fn test_char_conversions() {
+    assert_eq!(OscType::from(b'a'), OscType::Char('a'));
+    assert_eq!(OscType::from(0u8), OscType::Char('\0'));
+    assert_eq!(OscType::from(127u8), OscType::Char('\x7f'));
+    assert_eq!(OscType::from(128u8), OscType::Int(128));
+    assert_eq!(OscType::from(255u8), OscType::Int(255));
+
+    assert_eq!(OscType::Char('x').char_value(), Some('x'));
+    assert_eq!(OscType::Int(120).char_value(), None);
+    assert_eq!(OscType::String("x".to_string()).char_value(), None);
+
+    // Chars are encoded as big-endian Unicode scalar values
+    let mut msg = OscMessage::from("/c");
+    msg.args.push(b'A'.into());
+    let encoded = encoder::encode(&OscPacket::Message(msg)).unwrap();
+    assert_eq!(&encoded[4..], b",c\0\0\0\0\0A");
+}
+
+#[test]
+fn test_partial_cmp() {
+    use std::cmp::Ordering;
+
+    assert!(OscType::Float(-0.5) < OscType::Float(0.5));
+    assert!(OscType::String("a".to_string()) < OscType::String("ab".to_string()));
+    assert!(OscType::Time((1, 5).into()) < OscType::Time((2, 0).into()));
+    assert!(OscType::Bool(false) < OscType::Bool(true));
+    let array = |content: Vec<OscType>| OscType::Array(OscArray { content });
+    assert!(array(vec![1.into(), 2.into()]) < array(vec![1.into(), 3.into()]));
+    assert!(array(vec![1.into()]) < array(vec![1.into(), 0.into()]));
+    assert_eq!(OscType::Nil.partial_cmp(&OscType::Nil), Some(Ordering::Equal));
+    assert_eq!(OscType::Float(0.0).partial_cmp(&OscType::Float(-0.0)), Some(Ordering::Equal));
+
+    assert_eq!(OscType::Int(1).partial_cmp(&OscType::Float(1.0)), None);
+    assert_eq!(OscType::Nil.partial_cmp(&OscType::Impulse), None);
+    assert_eq!(OscType::Double(f64::NAN).partial_cmp(&OscType::Double(f64::NAN)), None);
+    let nan = array(vec![OscType::Float(f32::NAN)]);
+    assert_eq!(nan.partial_cmp(&array(vec![])), Some(Ordering::Greater));
+    assert_eq!(nan.partial_cmp(&nan), None);
+}
+
+#[test]
+fn test_total_cmp() {
+    use rosc::CanonicalOscType;
+    use std::cmp::Ordering;
+    use std::collections::BTreeMap;
+
+    let negative_nan = OscType::Float(-f32::NAN);
+    let sorted = vec![
+        OscType::Bool(false),
+        OscType::Impulse,
+        OscType::Nil,
+        OscType::Bool(true),
+        OscType::Array(OscArray { content: vec![] }),
+        OscType::Array(OscArray {
+            content: vec![OscType::Float(f32::NAN)],
+        }),
+        OscType::Array(OscArray {
+            content: vec![OscType::Int(0)],
+        }),
+        OscType::Blob(vec![]),
+        OscType::Blob(vec![0, 255]),
+        OscType::Blob(vec![1]),
+        OscType::Char('A'),
+        OscType::Char('a'),
+        OscType::Double(f64::NEG_INFINITY),
+        OscType::Double(f64::NAN),
+        negative_nan.clone(),
+        OscType::Float(f32::NEG_INFINITY),
+        OscType::Float(-0.0),
+        OscType::Float(0.0),
+        OscType::Float(f32::INFINITY),
+        OscType::Float(f32::NAN),
+        OscType::Long(-1),
+        OscType::Int(i32::MIN),
+        OscType::Int(7),
+        OscType::Midi(OscMidiMessage {
+            port: 0,
+            status: 0x90,
+            data1: 60,
+            data2: 127,
+        }),
+        OscType::Color(OscColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+            alpha: 255,
+        }),
+        OscType::Color(OscColor {
+            red: 0,
+            green: 1,
+            blue: 0,
+            alpha: 0,
+        }),
+        OscType::String("".to_string()),
+        OscType::String("Z".to_string()),
+        OscType::String("a".to_string()),
+        OscType::Time((1, u32::MAX).into()),
+        OscType::Time((2, 0).into()),
+        OscType::Unsigned(0),
+    ];
+    for pair in sorted.windows(2) {
+        assert_eq!(pair[0].total_cmp(&pair[1]), Ordering::Less, "{:?}", pair);
+        assert_eq!(pair[1].total_cmp(&pair[0]), Ordering::Greater, "{:?}", pair);
+    }
+    for arg in &sorted {
+        assert_eq!(arg.total_cmp(arg), Ordering::Equal, "{:?}", arg);
+    }
+    let canonical = |args: &[OscType]| -> Vec<CanonicalOscType> {
+        args.iter().cloned().map(CanonicalOscType::from).collect()
+    };
+
+    // Shuffles with a simple xorshift generator, so that failures can be reproduced
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as usize
+    };
+    for _ in 0..50 {
+        let mut shuffled = sorted.clone();
+        for i in (1..shuffled.len()).rev() {
+            shuffled.swap(i, next(i + 1));
+        }
+        let mut by_total_cmp = shuffled.clone();
+        by_total_cmp.sort_by(OscType::total_cmp);
+        assert_eq!(canonical(&by_total_cmp), canonical(&sorted));
+        let mut keys = canonical(&shuffled);
+        keys.sort();
+        assert_eq!(keys, canonical(&sorted));
+    }
+
+    // NaNs with equal bits are the same key, zeros of different signs are not
+    let mut map = BTreeMap::new();
+    for arg in [OscType::Float(f32::NAN), OscType::Float(0.0), OscType::Float(-0.0)] {
+        *map.entry(CanonicalOscType(arg)).or_insert(0) += 1;
+    }
+    *map.entry(CanonicalOscType(OscType::Float(f32::NAN))).or_insert(0) += 1;
+    assert_eq!(map.len(), 3);
+    assert_eq!(map[&CanonicalOscType(OscType::Float(f32::NAN))], 2);
+    assert_ne!(CanonicalOscType(negative_nan), CanonicalOscType(OscType::Float(f32::NAN)));
+}
+
+#[test]
+fn test_message_from_kv_inference() {
+    let cases: Vec<(&str, OscType)> = vec![
+        // Ints within the range of i32
+        ("0", OscType::Int(0)),
+        ("42", OscType::Int(42)),
+        ("-7", OscType::Int(-7)),
+        ("+3", OscType::Int(3)),
+        ("-0", OscType::Int(0)),
+        ("007", OscType::Int(7)),
+        ("2147483647", OscType::Int(i32::MAX)),
+        ("-2147483648", OscType::Int(i32::MIN)),
+        // Integers outside of it are floats
+        ("2147483648", OscType::Float(2_147_483_648.0)),
+        ("-2147483649", OscType::Float(-2_147_483_649.0)),
+        ("9223372036854775808", OscType::Float(9_223_372_036_854_775_808.0)),
+        // Floats
+        ("0.5", OscType::Float(0.5)),
+        ("-1.25", OscType::Float(-1.25)),
+        (".5", OscType::Float(0.5)),
+        ("1.", OscType::Float(1.0)),
+        ("1e3", OscType::Float(1000.0)),
+        ("-1E-3", OscType::Float(-0.001)),
+        ("+0.0", OscType::Float(0.0)),
+        ("inf", OscType::Float(f32::INFINITY)),
+        ("-infinity", OscType::Float(f32::NEG_INFINITY)),
+        ("1e39", OscType::Float(f32::INFINITY)),
+        // Bools and nil, case-sensitive
+        ("true", OscType::Bool(true)),
+        ("false", OscType::Bool(false)),
+        ("nil", OscType::Nil),
+        ("True", "True".into()),
+        ("FALSE", "FALSE".into()),
+        ("Nil", "Nil".into()),
+        ("null", "null".into()),
+        // Everything else is a string
+        ("", "".into()),
+        (" 1", " 1".into()),
+        ("1 ", "1 ".into()),
+        ("1_000", "1_000".into()),
+        ("0x10", "0x10".into()),
+        ("1,5", "1,5".into()),
+        ("1e", "1e".into()),
+        ("--1", "--1".into()),
+        ("one", "one".into()),
+        ("\u{e4}", "\u{e4}".into()),
+        ("/mixer/1", "/mixer/1".into()),
+    ];
+    for (value, expected) in &cases {
+        let msg = OscMessage::from_kv("/kv", &[value]).unwrap();
+        assert_eq!(msg.args, vec![expected.clone()], "{:?}", value);
+    }
+    // NaN never equals itself
+    for value in ["NaN", "nan", "-NaN"] {
+        match OscMessage::from_kv("/kv", &[value]).unwrap().args[..] {
+            [OscType::Float(x)] => assert!(x.is_nan(), "{:?}", value),
+            ref args => panic!("unexpected arguments for {:?}: {:?}", value, args),
+        }
+    }
+
+    let values: Vec<&str> = cases.iter().map(|(value, _)| *value).collect();
+    let msg = OscMessage::from_kv("/kv/all", &values).unwrap();
+    let expected: Vec<OscType> = cases.iter().map(|(_, arg)| arg.clone()).collect();
+    assert_eq!(msg.to_kv(), ("/kv/all".to_string(), expected));
+    assert_eq!(OscMessage::from_kv("/*", &[]).unwrap(), OscMessage::from("/*"));
+
+    assert!(matches!(OscMessage::from_kv("kv", &["1"]), Err(OscError::BadAddress(_))));
+    assert!(matches!(OscMessage::from_kv("/kv/", &["1"]), Err(OscError::BadAddress(_))));
+    assert_eq!(
+        OscMessage::from_kv("/kv", &["1", "a\0b"]),
+        Err(OscError::BadArg(
+            "Value 1 'a\0b': Strings must not contain null characters".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_message_from_kv_with_tags() {
+    let msg = OscMessage::from_kv_with_tags(
+        "/kv",
+        &[
+            "1", "2", "3", "0.5", "0.25", "42", "x", "0x0102", "@1.2", "0x00904060", "0xff000080",
+        ],
+        ",ihufdscbtmr",
+    )
+    .unwrap();
+    assert_eq!(
+        msg.args,
+        vec![
+            OscType::Int(1),
+            OscType::Long(2),
+            OscType::Unsigned(3),
+            OscType::Float(0.5),
+            OscType::Double(0.25),
+            "42".into(),
+            OscType::Char('x'),
+            OscType::Blob(vec![1, 2]),
+            OscType::Time(OscTime::from((1, 2))),
+            OscType::Midi(OscMidiMessage {
+                port: 0,
+                status: 0x90,
+                data1: 0x40,
+                data2: 0x60,
+            }),
+            OscType::Color(OscColor {
+                red: 0xff,
+                green: 0,
+                blue: 0,
+                alpha: 0x80,
+            }),
+        ]
+    );
+    // The leading comma is optional and strings are taken verbatim
+    assert_eq!(
+        OscMessage::from_kv_with_tags("/kv", &["true", ""], "ss").unwrap().args,
+        vec!["true".into(), "".into()]
+    );
+    assert_eq!(OscMessage::from_kv_with_tags("/kv", &[], ",").unwrap(), OscMessage::from("/kv"));
+
+    let err = |values: &[&str], tags: &str| {
+        OscMessage::from_kv_with_tags("/kv", values, tags).unwrap_err().to_string()
+    };
+    assert_eq!(err(&["1", "2"], "i"), "bad OSC argument: 1 type tags for 2 values");
+    assert_eq!(err(&["1"], "ii"), "bad OSC argument: 2 type tags for 1 values");
+    assert_eq!(err(&["0.5"], "i"), "bad OSC argument: Value 0 '0.5': Invalid int argument");
+    assert_eq!(
+        err(&["2147483648"], "i"),
+        "bad OSC argument: Value 0 '2147483648': Invalid int argument"
+    );
+    assert_eq!(err(&["1", "ab"], "ic"), "bad OSC argument: Value 1 'ab': Invalid char argument");
+    assert_eq!(
+        err(&["0102"], "b"),
+        "bad OSC argument: Value 0 '0102': Expected hex bytes starting with 0x"
+    );
+    assert_eq!(err(&["true"], "T"), "bad OSC argument: Value 0 'true': Unsupported type tag 'T'");
+    assert_eq!(err(&["1"], "["), "bad OSC argument: Value 0 '1': Unsupported type tag '['");
+    assert_eq!(err(&["1"], "X"), "bad OSC argument: Value 0 '1': Unsupported type tag 'X'");
+    assert!(matches!(
+        OscMessage::from_kv_with_tags("kv", &["1"], "i"),
+        Err(OscError::BadAddress(_))
+    ));
+}
@@ -0,0 +1,197 @@
+extern crate rosc;
+
+#[cfg(feature = "std")]
+use rosc::address::AddressError;
+#[cfg(feature = "std")]
+use rosc::validate::{ValidationIssue, ValidationProblem, ValidationProfile};
+#[cfg(feature = "std")]
+use rosc::{OscArray, OscBundle, OscMessage, OscPacket, OscType};
+
+#[cfg(feature = "std")]
+fn issue(
+    element: Vec<usize>,
+    arg_index: Option<usize>,
+    problem: ValidationProblem,
+) -> ValidationIssue {
+    ValidationIssue {
+        element,
+        arg_index,
+        problem,
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_validate_message() {
+    let profile = ValidationProfile::default();
+    let msg = OscMessage {
+        addr: "/mixer/{1,2}/gain".to_string(),
+        args: vec![
+            "ok".into(),
+            OscType::Char('a'),
+            OscType::Float(f32::INFINITY),
+            vec![0u8; 16].into(),
+        ],
+    };
+    assert_eq!(msg.validate(&profile), Ok(()));
+
+    let msg = OscMessage {
+        addr: "mixer".to_string(),
+        args: vec![
+            OscType::Int(1),
+            "a\0b".into(),
+            OscType::Array(OscArray {
+                content: vec![OscType::Char('é'), OscType::Double(f64::NAN)],
+            }),
+            OscType::Float(f32::NAN),
+            vec![0u8; 5].into(),
+        ],
+    };
+    let strict = ValidationProfile {
+        max_blob_size: 4,
+        reject_non_finite: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        msg.validate(&strict),
+        Err(vec![
+            issue(
+                vec![],
+                None,
+                ValidationProblem::InvalidAddress(AddressError::InvalidPattern {
+                    input: "mixer".to_string(),
+                    position: 0,
+                })
+            ),
+            issue(vec![], Some(1), ValidationProblem::NulInString),
+            issue(vec![], Some(2), ValidationProblem::NonAsciiChar('é')),
+            issue(vec![], Some(3), ValidationProblem::NonFiniteFloat),
+            issue(vec![], Some(4), ValidationProblem::NonFiniteFloat),
+            issue(vec![], Some(5), ValidationProblem::BlobTooLarge { size: 5, max: 4 }),
+        ])
+    );
+    // Non-finite floats are only rejected on request
+    assert_eq!(msg.validate(&profile).unwrap_err().len(), 3);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_validate_nesting() {
+    let profile = ValidationProfile {
+        max_array_depth: 1,
+        max_bundle_depth: 2,
+        ..Default::default()
+    };
+    let nested_array = |depth: usize| {
+        let mut arg = OscType::Int(1);
+        for _ in 0..depth {
+            arg = OscType::Array(OscArray {
+                content: vec![arg, OscType::Int(2)],
+            });
+        }
+        arg
+    };
+    let msg = |arg: OscType| {
+        OscPacket::Message(OscMessage {
+            addr: "/a".to_string(),
+            args: vec![arg, "\0".into()],
+        })
+    };
+    let bundle = |content: Vec<OscPacket>| {
+        OscPacket::Bundle(OscBundle {
+            timetag: (0, 1).into(),
+            content,
+        })
+    };
+
+    assert_eq!(
+        msg(nested_array(1)).validate(&profile),
+        Err(vec![issue(vec![], Some(2), ValidationProblem::NulInString)])
+    );
+    // The rest of the arguments keep their flattened index
+    assert_eq!(
+        msg(nested_array(2)).validate(&profile),
+        Err(vec![
+            issue(vec![], Some(0), ValidationProblem::ArrayTooDeep { max: 1 }),
+            issue(vec![], Some(3), ValidationProblem::NulInString),
+        ])
+    );
+
+    let packet = bundle(vec![
+        msg(OscType::Nil),
+        bundle(vec![msg(OscType::Nil), bundle(vec![msg(OscType::Nil)])]),
+    ]);
+    let issues = packet.validate(&profile).unwrap_err();
+    assert_eq!(
+        issues,
+        vec![
+            issue(vec![0], Some(1), ValidationProblem::NulInString),
+            issue(vec![1, 0], Some(1), ValidationProblem::NulInString),
+            issue(vec![1, 1], None, ValidationProblem::BundleTooDeep { max: 2 }),
+        ]
+    );
+    assert_eq!(
+        issues[2].to_string(),
+        "element 1: element 1: bundles are nested deeper than 2 levels"
+    );
+    assert_eq!(
+        issues[0].to_string(),
+        "element 0: argument 1: string contains a NUL character"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_validate_bundle_time_monotonicity() {
+    use rosc::OscTime;
+
+    let profile = ValidationProfile {
+        enforce_bundle_time_monotonicity: true,
+        ..Default::default()
+    };
+    let bundle = |timetag: (u32, u32), content: Vec<OscPacket>| {
+        OscPacket::Bundle(OscBundle {
+            timetag: timetag.into(),
+            content,
+        })
+    };
+    let packet = bundle(
+        (100, 0),
+        vec![
+            OscPacket::Message(OscMessage::from("/a")),
+            // Immediate bundles take effect at the time of the enclosing bundle
+            bundle((0, 1), vec![bundle((50, 0), vec![]), bundle((100, 0), vec![])]),
+            bundle((99, u32::MAX), vec![]),
+            bundle((0, 0), vec![]),
+        ],
+    );
+    packet.validate(&ValidationProfile::default()).unwrap();
+    let problem = |timetag: (u32, u32)| ValidationProblem::BundleTimeNotMonotonic {
+        timetag: timetag.into(),
+        parent: (100, 0).into(),
+    };
+    let issues = packet.validate(&profile).unwrap_err();
+    assert_eq!(
+        issues,
+        vec![
+            issue(vec![1, 0], None, problem((50, 0))),
+            issue(vec![2], None, problem((99, u32::MAX))),
+            issue(vec![3], None, problem((0, 0))),
+        ]
+    );
+    assert_eq!(
+        issues[0].to_string(),
+        "element 1: element 0: time tag 50.0 is earlier than 100.0 of the enclosing bundle"
+    );
+
+    // Immediate bundles may contain bundles of any time
+    bundle((0, 1), vec![bundle((0, 0), vec![]), bundle((1, 0), vec![])])
+        .validate(&profile)
+        .unwrap();
+    OscPacket::Bundle(OscBundle {
+        timetag: OscTime::IMMEDIATE,
+        content: vec![],
+    })
+    .validate(&profile)
+    .unwrap();
+}
@@ -0,0 +1,256 @@
+extern crate rosc;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::borrow::Cow;
+use std::cell::Cell;
+
+use rosc::decoder::{self, OscVisitor};
+use rosc::{
+    encoder, OscArray, OscBundle, OscColor, OscMessage, OscMidiMessage, OscPacket, OscTime,
+    OscType,
+};
+
+/// Counts the allocations made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// Re-encodes the visited packet into a fixed-size buffer without allocating.
+struct Reencoder {
+    out: [u8; 1024],
+    len: usize,
+    args: [u8; 512],
+    args_len: usize,
+    // Start offsets of the bundle elements that are currently being written
+    elements: [usize; 8],
+    elements_len: usize,
+    bundle_depth: usize,
+}
+
+impl Reencoder {
+    fn new() -> Self {
+        Reencoder {
+            out: [0u8; 1024],
+            len: 0,
+            args: [0u8; 512],
+            args_len: 0,
+            elements: [0usize; 8],
+            elements_len: 0,
+            bundle_depth: 0,
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.out[..self.len]
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.out[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    fn write_padded_string(&mut self, s: &str) {
+        self.write(s.as_bytes());
+        self.write(&[0u8; 4][..4 - s.len() % 4]);
+    }
+
+    fn write_arg(&mut self, bytes: &[u8]) {
+        self.args[self.args_len..self.args_len + bytes.len()].copy_from_slice(bytes);
+        self.args_len += bytes.len();
+    }
+
+    fn begin_element(&mut self) {
+        if self.bundle_depth > 0 {
+            self.elements[self.elements_len] = self.len;
+            self.elements_len += 1;
+            // Placeholder for the element size
+            self.write(&[0u8; 4]);
+        }
+    }
+
+    fn end_element(&mut self) {
+        if self.bundle_depth > 0 {
+            self.elements_len -= 1;
+            let start = self.elements[self.elements_len];
+            let size = (self.len - start - 4) as u32;
+            self.out[start..start + 4].copy_from_slice(&size.to_be_bytes());
+        }
+    }
+}
+
+impl<'a> OscVisitor<'a> for Reencoder {
+    fn begin_bundle(&mut self, timetag: OscTime) {
+        self.begin_element();
+        self.write_padded_string("#bundle");
+        self.write(&timetag.seconds.to_be_bytes());
+        self.write(&timetag.fractional.to_be_bytes());
+        self.bundle_depth += 1;
+    }
+
+    fn end_bundle(&mut self) {
+        self.bundle_depth -= 1;
+        self.end_element();
+    }
+
+    fn message_addr(&mut self, addr: Cow<'a, str>) {
+        self.begin_element();
+        self.write_padded_string(&addr);
+    }
+
+    fn message_type_tags(&mut self, type_tags: &'a str) {
+        self.write_padded_string(type_tags);
+    }
+
+    fn end_message(&mut self) {
+        let args = self.args;
+        let args_len = self.args_len;
+        self.write(&args[..args_len]);
+        self.args_len = 0;
+        self.end_element();
+    }
+
+    fn arg_int(&mut self, x: i32) {
+        self.write_arg(&x.to_be_bytes());
+    }
+
+    fn arg_float(&mut self, x: f32) {
+        self.write_arg(&x.to_be_bytes());
+    }
+
+    fn arg_string(&mut self, x: Cow<'a, str>) {
+        self.write_arg(x.as_bytes());
+        self.write_arg(&[0u8; 4][..4 - x.len() % 4]);
+    }
+
+    fn arg_blob(&mut self, x: &'a [u8]) {
+        self.write_arg(&(x.len() as u32).to_be_bytes());
+        self.write_arg(x);
+        self.write_arg(&[0u8; 4][..(4 - x.len() % 4) % 4]);
+    }
+
+    fn arg_time(&mut self, x: OscTime) {
+        self.write_arg(&x.seconds.to_be_bytes());
+        self.write_arg(&x.fractional.to_be_bytes());
+    }
+
+    fn arg_long(&mut self, x: i64) {
+        self.write_arg(&x.to_be_bytes());
+    }
+
+    fn arg_double(&mut self, x: f64) {
+        self.write_arg(&x.to_be_bytes());
+    }
+
+    fn arg_char(&mut self, x: char) {
+        self.write_arg(&(x as u32).to_be_bytes());
+    }
+
+    fn arg_color(&mut self, x: OscColor) {
+        self.write_arg(&[x.red, x.green, x.blue, x.alpha]);
+    }
+
+    fn arg_midi(&mut self, x: OscMidiMessage) {
+        self.write_arg(&[x.port, x.status, x.data1, x.data2]);
+    }
+}
+
+fn test_packet() -> OscPacket {
+    OscPacket::Bundle(OscBundle {
+        timetag: (1234, 5678).into(),
+        content: vec![
+            OscPacket::Message(OscMessage {
+                addr: "/everything".to_string(),
+                args: vec![
+                    42i32.into(),
+                    (-1337i64).into(),
+                    0.5f32.into(),
+                    0.25f64.into(),
+                    "string".into(),
+                    vec![1u8, 2u8, 3u8, 4u8, 5u8].into(),
+                    (1, 2).into(),
+                    'x'.into(),
+                    true.into(),
+                    false.into(),
+                    OscType::Nil,
+                    OscType::Impulse,
+                    OscMidiMessage {
+                        port: 1,
+                        status: 0x90,
+                        data1: 60,
+                        data2: 127,
+                    }
+                    .into(),
+                    OscColor {
+                        red: 1,
+                        green: 2,
+                        blue: 3,
+                        alpha: 4,
+                    }
+                    .into(),
+                    OscArray {
+                        content: vec![1i32.into(), OscArray { content: vec![] }.into()],
+                    }
+                    .into(),
+                ],
+            }),
+            OscPacket::Bundle(OscBundle {
+                timetag: (1, 2).into(),
+                content: vec![OscPacket::Message(OscMessage {
+                    addr: "/nested".to_string(),
+                    args: vec![],
+                })],
+            }),
+        ],
+    })
+}
+
+#[test]
+fn test_decode_visit_reencodes_identically() {
+    let buf = encoder::encode(&test_packet()).unwrap();
+    let mut reencoder = Reencoder::new();
+
+    let before = allocations();
+    decoder::decode_visit(&buf, &mut reencoder).unwrap();
+    assert_eq!(allocations(), before, "decode_visit must not allocate");
+
+    assert_eq!(reencoder.bytes(), &buf[..]);
+}
+
+#[test]
+fn test_decode_visit_rejects_trailing_bytes() {
+    let mut buf = encoder::encode(&OscPacket::Message(OscMessage::from("/addr"))).unwrap();
+    buf.extend([0u8; 4]);
+
+    let mut reencoder = Reencoder::new();
+    decoder::decode_visit(&buf, &mut reencoder).expect_err("trailing bytes");
+}
+
+#[test]
+fn test_decode_visit_rejects_unbalanced_arrays() {
+    let mut buf = encoder::encode_string("/addr");
+    buf.extend(encoder::encode_string(",[i"));
+    buf.extend(1i32.to_be_bytes());
+
+    let mut reencoder = Reencoder::new();
+    decoder::decode_visit(&buf, &mut reencoder).expect_err("unterminated array");
+}
@@ -0,0 +1,39 @@
+extern crate rosc;
+
+#[cfg(feature = "ws")]
+use rosc::decoder::DecoderOptions;
+#[cfg(feature = "ws")]
+use rosc::{encoder, ws, OscBundle, OscError, OscMessage, OscPacket, OscType};
+
+#[cfg(feature = "ws")]
+#[test]
+fn test_ws_binary_round_trip() {
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (1, 2).into(),
+        content: vec![OscPacket::Message(OscMessage {
+            addr: "/fader/1".to_string(),
+            args: vec![OscType::Float(0.5), "label".into()],
+        })],
+    });
+    let payload = ws::to_ws_binary(&packet).unwrap();
+    assert_eq!(payload, encoder::encode(&packet).unwrap());
+    assert_eq!(ws::from_ws_binary(&payload).unwrap(), packet);
+}
+
+#[cfg(feature = "ws")]
+#[test]
+fn test_ws_binary_rejects_framed_payloads() {
+    let packet = OscPacket::Message(OscMessage::from("/a"));
+    let mut payload = ws::to_ws_binary(&packet).unwrap();
+    payload.extend(ws::to_ws_binary(&packet).unwrap());
+    assert!(ws::from_ws_binary(&payload).is_err());
+
+    let options = DecoderOptions {
+        max_packet_size: 4,
+        ..Default::default()
+    };
+    assert!(matches!(
+        ws::from_ws_binary_with_options(&ws::to_ws_binary(&packet).unwrap(), &options),
+        Err(OscError::PacketTooLarge { size: 8, max: 4 })
+    ));
+}